@@ -0,0 +1,631 @@
+use std::{collections::HashMap, io::Read, path::Path, time::Duration};
+
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    layout::{Constraint, Layout},
+    style::{Color, Modifier, Style},
+    text::Line,
+    widgets::{Block, List, ListItem, ListState, Paragraph, Wrap},
+    DefaultTerminal, Frame,
+};
+
+use xsd_delphi_codegen_core::openapi::{
+    preview_operation, spec_operations, ClientOptions, Operation, ParameterLocation, Request,
+    SpecOperations,
+};
+
+use crate::error::CliError;
+
+/// Time after which a request of the playground is aborted
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+/// Size of the response body, which is shown
+const MAX_BODY_LENGTH: u64 = 1024 * 1024;
+/// Lines scrolled by `PageUp` and `PageDown`
+const SCROLL_LINES: u16 = 10;
+
+/// Lists the operations of the spec in the terminal with a preview of the Delphi method and models,
+/// which the generation with `options` writes for the selected operation. A selected operation is
+/// sent against a server of the spec with the values entered in a form of its parameters, which
+/// shows the raw request and response.
+pub(crate) fn browse(input: &Path, options: &ClientOptions) -> Result<(), CliError> {
+    let operations = spec_operations(input)?;
+
+    if operations.operations.is_empty() {
+        return Err(CliError::InvalidArguments(format!(
+            "Spec {input:?} has no operations to browse"
+        )));
+    }
+
+    let mut terminal = ratatui::try_init().map_err(terminal_error)?;
+    let previewer = |operation: &Operation| {
+        preview_operation(input, &operation.name, options).map_or_else(
+            |e| e.to_string(),
+            |preview| format!("{}\n\n{}", preview.signature, preview.models),
+        )
+    };
+    let result = run(&mut terminal, App::new(operations, Box::new(previewer)));
+
+    ratatui::try_restore().map_err(terminal_error)?;
+
+    result
+}
+
+fn run(terminal: &mut DefaultTerminal, mut app: App<'_>) -> Result<(), CliError> {
+    loop {
+        terminal
+            .draw(|frame| app.render(frame))
+            .map_err(terminal_error)?;
+
+        let Event::Key(key) = event::read().map_err(terminal_error)? else {
+            continue;
+        };
+
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+
+        match app.handle_key(key) {
+            Action::Quit => return Ok(()),
+            Action::Send(request) => {
+                terminal
+                    .draw(|frame| app.render(frame))
+                    .map_err(terminal_error)?;
+
+                let response = send(&request);
+
+                if let Some(form) = &mut app.form {
+                    form.response = Some(response);
+                }
+            }
+            Action::None => {}
+        }
+    }
+}
+
+fn terminal_error(e: std::io::Error) -> CliError {
+    CliError::Output(format!(
+        "Could not draw the browser due to following error: \"{e:?}\""
+    ))
+}
+
+/// Sends the request and returns the raw response, or the error, if no response was received
+fn send(request: &Request) -> String {
+    let agent = ureq::AgentBuilder::new().timeout(REQUEST_TIMEOUT).build();
+    let mut call = agent.request(&request.method, &request.url);
+
+    for (name, value) in &request.headers {
+        call = call.set(name, value);
+    }
+
+    let result = match &request.body {
+        Some(body) => call.send_string(body),
+        None => call.call(),
+    };
+
+    // Responses with an error status are shown like any other response
+    let response = match result {
+        Ok(response) | Err(ureq::Error::Status(_, response)) => response,
+        Err(e) => return format!("Request failed: {e}"),
+    };
+
+    let mut raw = format!(
+        "{} {} {}\n",
+        response.http_version(),
+        response.status(),
+        response.status_text()
+    );
+
+    for name in response.headers_names() {
+        raw.push_str(&format!(
+            "{name}: {}\n",
+            response.header(&name).unwrap_or_default()
+        ));
+    }
+
+    let mut body = vec![];
+
+    if let Err(e) = response
+        .into_reader()
+        .take(MAX_BODY_LENGTH)
+        .read_to_end(&mut body)
+    {
+        return format!("{raw}\nBody could not be read: {e}");
+    }
+
+    let body = match String::from_utf8(body) {
+        Ok(text) => serde_json::from_str::<serde_json::Value>(&text)
+            .and_then(|json| serde_json::to_string_pretty(&json))
+            .unwrap_or(text),
+        Err(e) => format!("<{} bytes of binary content>", e.as_bytes().len()),
+    };
+
+    format!("{raw}\n{body}")
+}
+
+/// What the event loop does after a key was handled
+#[derive(Debug, PartialEq, Eq)]
+enum Action {
+    None,
+    Quit,
+    Send(Request),
+}
+
+/// Renders the Delphi code generated for an operation
+type Previewer<'a> = Box<dyn Fn(&Operation) -> String + 'a>;
+
+struct App<'a> {
+    spec: SpecOperations,
+    list: ListState,
+    /// Form of the selected operation, while it is open
+    form: Option<Form>,
+    previewer: Previewer<'a>,
+    /// Previews by the index of their operation, which are rendered once they are selected
+    previews: HashMap<usize, String>,
+    preview_scroll: u16,
+}
+
+/// Values entered for an operation and its last request and response
+struct Form {
+    operation: usize,
+    server: usize,
+    /// Values of the parameters of the operation
+    values: Vec<String>,
+    body: Option<String>,
+    focus: Field,
+    /// Values, which don't match their parameter, and block the request
+    problems: Vec<String>,
+    request: Option<String>,
+    response: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Field {
+    Server,
+    Parameter(usize),
+    Body,
+}
+
+impl<'a> App<'a> {
+    fn new(spec: SpecOperations, previewer: Previewer<'a>) -> Self {
+        Self {
+            spec,
+            list: ListState::default().with_selected(Some(0)),
+            form: None,
+            previewer,
+            previews: HashMap::new(),
+            preview_scroll: 0,
+        }
+    }
+
+    fn selected(&self) -> &Operation {
+        &self.spec.operations[self.list.selected().unwrap_or_default()]
+    }
+
+    /// Preview of the selected operation
+    fn preview(&mut self) -> &str {
+        let index = self.list.selected().unwrap_or_default();
+        let operation = &self.spec.operations[index];
+
+        self.previews
+            .entry(index)
+            .or_insert_with(|| (self.previewer)(operation))
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> Action {
+        match &mut self.form {
+            Some(form) => {
+                let operation = &self.spec.operations[form.operation];
+
+                if key.code == KeyCode::Esc {
+                    self.form = None;
+                    return Action::None;
+                }
+
+                form.handle_key(key, operation, &self.spec.servers)
+            }
+            None => match key.code {
+                KeyCode::Esc | KeyCode::Char('q') => Action::Quit,
+                KeyCode::Up | KeyCode::Char('k') => {
+                    self.list.select_previous();
+                    self.preview_scroll = 0;
+                    Action::None
+                }
+                KeyCode::Down | KeyCode::Char('j') => {
+                    let last = self.spec.operations.len() - 1;
+                    self.list
+                        .select(Some(self.list.selected().map_or(0, |s| (s + 1).min(last))));
+                    self.preview_scroll = 0;
+                    Action::None
+                }
+                KeyCode::PageDown => {
+                    self.preview_scroll = self.preview_scroll.saturating_add(SCROLL_LINES);
+                    Action::None
+                }
+                KeyCode::PageUp => {
+                    self.preview_scroll = self.preview_scroll.saturating_sub(SCROLL_LINES);
+                    Action::None
+                }
+                KeyCode::Enter => {
+                    let operation = self.list.selected().unwrap_or_default();
+                    self.form = Some(Form::new(operation, self.selected()));
+                    Action::None
+                }
+                _ => Action::None,
+            },
+        }
+    }
+
+    fn render(&mut self, frame: &mut Frame) {
+        let [main, help] =
+            Layout::vertical([Constraint::Min(3), Constraint::Length(1)]).areas(frame.area());
+
+        match &self.form {
+            Some(form) => {
+                let operation = &self.spec.operations[form.operation];
+                form.render(frame, main, operation, &self.spec.servers);
+                frame.render_widget(
+                    Line::from(
+                        "Tab: next field  ←/→: server  F5/Ctrl+S: send  Esc: back to operations",
+                    ),
+                    help,
+                );
+            }
+            None => {
+                let [list, details] =
+                    Layout::horizontal([Constraint::Percentage(45), Constraint::Percentage(55)])
+                        .areas(main);
+                let [details, preview] =
+                    Layout::vertical([Constraint::Percentage(40), Constraint::Percentage(60)])
+                        .areas(details);
+                let items = self
+                    .spec
+                    .operations
+                    .iter()
+                    .map(|o| ListItem::new(format!("{:<6} {}  {}", o.method, o.path, o.name)))
+                    .collect::<Vec<_>>();
+
+                frame.render_stateful_widget(
+                    List::new(items)
+                        .block(Block::bordered().title(self.spec.title.as_str()))
+                        .highlight_style(Style::new().add_modifier(Modifier::REVERSED)),
+                    list,
+                    &mut self.list,
+                );
+                frame.render_widget(
+                    Paragraph::new(describe(self.selected()))
+                        .block(Block::bordered().title("Operation"))
+                        .wrap(Wrap { trim: false }),
+                    details,
+                );
+
+                let scroll = self.preview_scroll;
+
+                frame.render_widget(
+                    Paragraph::new(self.preview())
+                        .block(Block::bordered().title("Delphi"))
+                        .scroll((scroll, 0)),
+                    preview,
+                );
+                frame.render_widget(
+                    Line::from(
+                        "↑/↓: select  PgUp/PgDn: scroll the Delphi code  Enter: send the operation  q: quit",
+                    ),
+                    help,
+                );
+            }
+        }
+    }
+}
+
+impl Form {
+    fn new(index: usize, operation: &Operation) -> Self {
+        Self {
+            operation: index,
+            server: 0,
+            values: operation
+                .parameters
+                .iter()
+                .map(|p| p.sample.clone())
+                .collect(),
+            body: operation.body.as_ref().map(|b| b.sample.clone()),
+            focus: Field::Server,
+            problems: vec![],
+            request: None,
+            response: None,
+        }
+    }
+
+    /// Fields in the order they are focused by `Tab`
+    fn fields(&self) -> Vec<Field> {
+        std::iter::once(Field::Server)
+            .chain((0..self.values.len()).map(Field::Parameter))
+            .chain(self.body.iter().map(|_| Field::Body))
+            .collect()
+    }
+
+    fn handle_key(&mut self, key: KeyEvent, operation: &Operation, servers: &[String]) -> Action {
+        let is_send = key.code == KeyCode::F(5)
+            || (key.code == KeyCode::Char('s') && key.modifiers.contains(KeyModifiers::CONTROL));
+
+        if is_send {
+            return self.send(operation, servers);
+        }
+
+        let fields = self.fields();
+        let position = fields.iter().position(|f| *f == self.focus).unwrap_or(0);
+        let text = match self.focus {
+            Field::Server => None,
+            Field::Parameter(index) => self.values.get_mut(index),
+            Field::Body => self.body.as_mut(),
+        };
+
+        match (key.code, text) {
+            (KeyCode::Tab, _) => self.focus = fields[(position + 1) % fields.len()],
+            (KeyCode::BackTab, _) => {
+                self.focus = fields[(position + fields.len() - 1) % fields.len()];
+            }
+            (KeyCode::Left, None) => {
+                self.server = (self.server + servers.len() - 1) % servers.len()
+            }
+            (KeyCode::Right, None) => self.server = (self.server + 1) % servers.len(),
+            (KeyCode::Enter, Some(text)) if self.focus == Field::Body => text.push('\n'),
+            (KeyCode::Char(c), Some(text)) => text.push(c),
+            (KeyCode::Backspace, Some(text)) => {
+                text.pop();
+            }
+            _ => {}
+        }
+
+        Action::None
+    }
+
+    /// Checks the values and builds the request, which is sent, if all values are valid
+    fn send(&mut self, operation: &Operation, servers: &[String]) -> Action {
+        self.problems = operation
+            .parameters
+            .iter()
+            .zip(&self.values)
+            .filter_map(|(p, v)| p.check(v).err())
+            .collect();
+
+        if !self.problems.is_empty() {
+            return Action::None;
+        }
+
+        let request =
+            operation.build_request(&servers[self.server], &self.values, self.body.as_deref());
+
+        self.request = Some(request.to_raw());
+        self.response = Some(String::from("Sending..."));
+
+        Action::Send(request)
+    }
+
+    fn render(
+        &self,
+        frame: &mut Frame,
+        area: ratatui::layout::Rect,
+        operation: &Operation,
+        servers: &[String],
+    ) {
+        let [form, exchange] =
+            Layout::horizontal([Constraint::Percentage(45), Constraint::Percentage(55)])
+                .areas(area);
+        let fields = self.fields();
+        let constraints = fields.iter().map(|f| match f {
+            Field::Body => Constraint::Min(3),
+            _ => Constraint::Length(3),
+        });
+        let areas =
+            Layout::vertical(constraints.chain([Constraint::Length(self.problems.len() as u16)]))
+                .split(form);
+
+        for (field, area) in fields.iter().zip(areas.iter()) {
+            let (title, text) = match field {
+                Field::Server => (
+                    format!("{} {}", operation.method, operation.path),
+                    format!("< {} >", servers[self.server]),
+                ),
+                Field::Parameter(index) => {
+                    let parameter = &operation.parameters[*index];
+                    let location = match parameter.location {
+                        ParameterLocation::Path => "path",
+                        ParameterLocation::Query => "query",
+                        ParameterLocation::Header => "header",
+                    };
+                    let required = if parameter.is_required { "*" } else { "" };
+
+                    (
+                        format!(
+                            "{}{required} ({location}, {})",
+                            parameter.key, parameter.type_name
+                        ),
+                        self.values[*index].clone(),
+                    )
+                }
+                Field::Body => (
+                    operation
+                        .body
+                        .as_ref()
+                        .map(|b| format!("Body ({})", b.content_type))
+                        .unwrap_or_default(),
+                    self.body.clone().unwrap_or_default(),
+                ),
+            };
+            let border = match *field == self.focus {
+                true => Style::new().fg(Color::Yellow),
+                false => Style::new(),
+            };
+
+            frame.render_widget(
+                Paragraph::new(text).block(Block::bordered().title(title).border_style(border)),
+                *area,
+            );
+        }
+
+        if let Some(area) = areas.last() {
+            frame.render_widget(
+                Paragraph::new(self.problems.join("\n")).style(Style::new().fg(Color::Red)),
+                *area,
+            );
+        }
+
+        let [request, response] =
+            Layout::vertical([Constraint::Percentage(40), Constraint::Percentage(60)])
+                .areas(exchange);
+
+        frame.render_widget(
+            Paragraph::new(self.request.clone().unwrap_or_default())
+                .block(Block::bordered().title("Request"))
+                .wrap(Wrap { trim: false }),
+            request,
+        );
+        frame.render_widget(
+            Paragraph::new(self.response.clone().unwrap_or_default())
+                .block(Block::bordered().title("Response"))
+                .wrap(Wrap { trim: false }),
+            response,
+        );
+    }
+}
+
+/// Parameters and body of the operation as collected from the spec
+fn describe(operation: &Operation) -> String {
+    let mut description = format!(
+        "{}\n{} {}\n\nParameters:\n",
+        operation.name, operation.method, operation.path
+    );
+
+    for parameter in &operation.parameters {
+        description.push_str(&format!(
+            "  {} ({:?}, {}{})",
+            parameter.key,
+            parameter.location,
+            parameter.type_name,
+            if parameter.is_required {
+                ", required"
+            } else {
+                ""
+            }
+        ));
+
+        if !parameter.allowed_values.is_empty() {
+            description.push_str(&format!(" one of {}", parameter.allowed_values.join(", ")));
+        }
+
+        description.push('\n');
+    }
+
+    if let Some(body) = &operation.body {
+        description.push_str(&format!(
+            "\nBody ({}):\n{}\n",
+            body.content_type, body.sample
+        ));
+    }
+
+    description
+}
+
+#[cfg(test)]
+mod tests {
+    use xsd_delphi_codegen_core::openapi::Parameter;
+
+    use super::*;
+
+    fn app() -> App<'static> {
+        App::new(
+            SpecOperations {
+                title: String::from("Pets 1.0.0"),
+                servers: vec![
+                    String::from("https://pets.example.com"),
+                    String::from("http://localhost:8080"),
+                ],
+                operations: vec![Operation {
+                    name: String::from("GetPet"),
+                    method: String::from("GET"),
+                    path: String::from("/pets/{petId}"),
+                    parameters: vec![Parameter {
+                        key: String::from("petId"),
+                        location: ParameterLocation::Path,
+                        type_name: String::from("integer"),
+                        is_required: true,
+                        sample: String::from("0"),
+                        allowed_values: vec![],
+                    }],
+                    body: None,
+                    accepts_json: true,
+                }],
+            },
+            Box::new(|operation| format!("function {}: TPet;", operation.name)),
+        )
+    }
+
+    fn press(app: &mut App<'_>, codes: &[KeyCode]) -> Action {
+        codes.iter().fold(Action::None, |_, code| {
+            app.handle_key(KeyEvent::from(*code))
+        })
+    }
+
+    #[test]
+    fn form_sends_the_entered_values_to_the_chosen_server() {
+        let mut app = app();
+
+        let action = press(
+            &mut app,
+            &[
+                KeyCode::Enter,
+                KeyCode::Right,
+                KeyCode::Tab,
+                KeyCode::Backspace,
+                KeyCode::Char('4'),
+                KeyCode::Char('2'),
+                KeyCode::F(5),
+            ],
+        );
+
+        let Action::Send(request) = action else {
+            panic!("Request was not sent");
+        };
+
+        assert_eq!(request.url, "http://localhost:8080/pets/42");
+        assert_eq!(
+            app.form.and_then(|f| f.request).unwrap(),
+            "GET http://localhost:8080/pets/42\nAccept: application/json\n"
+        );
+    }
+
+    #[test]
+    fn invalid_values_are_not_sent() {
+        let mut app = app();
+
+        let action = press(
+            &mut app,
+            &[
+                KeyCode::Enter,
+                KeyCode::Tab,
+                KeyCode::Char('x'),
+                KeyCode::F(5),
+            ],
+        );
+
+        assert_eq!(action, Action::None);
+        assert_eq!(
+            app.form.as_ref().unwrap().problems,
+            ["\"petId\" must be of type integer"]
+        );
+        assert_eq!(press(&mut app, &[KeyCode::Esc]), Action::None);
+        assert!(app.form.is_none());
+        assert_eq!(press(&mut app, &[KeyCode::Char('q')]), Action::Quit);
+    }
+
+    #[test]
+    fn preview_of_the_selected_operation_is_rendered_once() {
+        let mut app = app();
+
+        assert_eq!(app.preview(), "function GetPet: TPet;");
+
+        app.previewer = Box::new(|_| String::from("rendered again"));
+
+        assert_eq!(app.preview(), "function GetPet: TPet;");
+    }
+}
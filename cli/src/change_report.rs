@@ -0,0 +1,148 @@
+use std::fmt::Write;
+
+use serde_json::{Map, Value};
+
+/// Compares the manifests of two generations and lists the changes of the generated types.
+///
+/// Removed types and members as well as changed member types are marked as breaking,
+/// because code using the generated units may no longer compile.
+pub fn compare(previous: Option<&str>, current: &str) -> Result<String, String> {
+    let current = parse_types(current)?;
+
+    let Some(previous) = previous else {
+        return Ok(format!(
+            "No previous generation found, {} types generated.\n",
+            current.len()
+        ));
+    };
+    let previous = parse_types(previous)?;
+
+    let mut changes = vec![];
+
+    for (name, new_type) in &current {
+        match previous.get(name) {
+            None => changes.push(format!("+ {name} ({})", kind(new_type))),
+            Some(old_type) if kind(old_type) != kind(new_type) => changes.push(format!(
+                "~ {name}: {} -> {} (breaking)",
+                kind(old_type),
+                kind(new_type)
+            )),
+            Some(old_type) => {
+                let member_changes = compare_members(&members(old_type), &members(new_type));
+
+                if !member_changes.is_empty() {
+                    changes.push(format!("~ {name}\n{}", member_changes.join("\n")));
+                }
+            }
+        }
+    }
+
+    for (name, old_type) in &previous {
+        if !current.contains_key(name) {
+            changes.push(format!("- {name} ({}) (breaking)", kind(old_type)));
+        }
+    }
+
+    if changes.is_empty() {
+        return Ok(String::from("No changes.\n"));
+    }
+
+    let breaking = changes
+        .iter()
+        .map(|c| c.matches("(breaking)").count())
+        .sum::<usize>();
+
+    let mut report = format!(
+        "{} changed types, {breaking} breaking changes\n\n",
+        changes.len()
+    );
+    for change in changes {
+        let _ = writeln!(report, "{change}");
+    }
+
+    Ok(report)
+}
+
+fn compare_members(old: &Map<String, Value>, new: &Map<String, Value>) -> Vec<String> {
+    let mut changes = vec![];
+
+    for (name, new_type) in new {
+        match old.get(name) {
+            None => changes.push(format!("    + {name}: {}", as_text(new_type))),
+            Some(old_type) if old_type != new_type => changes.push(format!(
+                "    ~ {name}: {} -> {} (breaking)",
+                as_text(old_type),
+                as_text(new_type)
+            )),
+            Some(_) => (),
+        }
+    }
+
+    for (name, old_type) in old {
+        if !new.contains_key(name) {
+            changes.push(format!("    - {name}: {} (breaking)", as_text(old_type)));
+        }
+    }
+
+    changes
+}
+
+fn parse_types(manifest: &str) -> Result<Map<String, Value>, String> {
+    let manifest = serde_json::from_str::<Value>(manifest)
+        .map_err(|e| format!("Could not read manifest due to following error: \"{e:?}\""))?;
+
+    match manifest.get("types") {
+        Some(Value::Object(types)) => Ok(types.clone()),
+        _ => Err(String::from("Manifest contains no types")),
+    }
+}
+
+fn kind(type_: &Value) -> &str {
+    type_
+        .get("kind")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+}
+
+fn members(type_: &Value) -> Map<String, Value> {
+    type_
+        .get("members")
+        .and_then(Value::as_object)
+        .cloned()
+        .unwrap_or_default()
+}
+
+fn as_text(value: &Value) -> String {
+    value
+        .as_str()
+        .map_or_else(|| value.to_string(), str::to_owned)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn added_removed_and_changed_types_are_reported() {
+        let previous = r#"{"types": {
+            "TOrder": {"kind": "class", "members": {"Id": "Integer", "Note": "String"}},
+            "TStatus": {"kind": "enum", "members": {"sOpen": "open"}},
+            "TLegacy": {"kind": "class", "members": {}}
+        }}"#;
+        let current = r#"{"types": {
+            "TOrder": {"kind": "class", "members": {"Id": "Int64", "Total": "Double"}},
+            "TStatus": {"kind": "enum", "members": {"sOpen": "open", "sClosed": "closed"}},
+            "TInvoice": {"kind": "class", "members": {}}
+        }}"#;
+
+        assert_eq!(
+            compare(Some(previous), current).unwrap(),
+            "4 changed types, 3 breaking changes\n\n\
+             + TInvoice (class)\n\
+             ~ TOrder\n    ~ Id: Integer -> Int64 (breaking)\n    + Total: Double\n    - Note: String (breaking)\n\
+             ~ TStatus\n    + sClosed: closed\n\
+             - TLegacy (class) (breaking)\n"
+        );
+        assert_eq!(compare(Some(current), current).unwrap(), "No changes.\n");
+    }
+}
@@ -0,0 +1,91 @@
+use std::path::Path;
+
+use serde_json::Value;
+use xsd_delphi_codegen_core::xml::generator::code_generator_trait::CodeGenOptions;
+
+/// Reads the options of the generation from xml schemas from the JSON file passed by `--config`.
+///
+/// Options missing in the file keep their default, which is the default of the arguments too.
+/// Options of the arguments, which differ from their default, override the ones of the file.
+pub fn apply_config(path: &Path, arguments: CodeGenOptions) -> Result<CodeGenOptions, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Could not read config {path:?} due to following error: \"{e:?}\""))?;
+
+    merge(&content, arguments).map_err(|e| format!("Config {path:?} is not valid: {e}"))
+}
+
+fn merge(content: &str, arguments: CodeGenOptions) -> Result<CodeGenOptions, serde_json::Error> {
+    let config = serde_json::from_str::<CodeGenOptions>(content)?;
+
+    let mut merged = serde_json::to_value(&config)?;
+    overlay(
+        &mut merged,
+        &serde_json::to_value(&arguments)?,
+        &serde_json::to_value(CodeGenOptions::default())?,
+    );
+
+    Ok(CodeGenOptions {
+        custom_passes: arguments.custom_passes,
+        ..serde_json::from_value(merged)?
+    })
+}
+
+/// Replaces the values of `target` by the ones of `overrides`, which differ from `defaults`
+fn overlay(target: &mut Value, overrides: &Value, defaults: &Value) {
+    match (target, overrides, defaults) {
+        (Value::Object(target), Value::Object(overrides), Value::Object(defaults)) => {
+            for (key, value) in overrides {
+                if let (Some(target), Some(default)) = (target.get_mut(key), defaults.get(key)) {
+                    overlay(target, value, default);
+                }
+            }
+        }
+        (target, overrides, defaults) => {
+            if overrides != defaults {
+                *target = overrides.clone();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use xsd_delphi_codegen_core::xml::generator::code_generator_trait::{
+        ListType, OutputOptions, SerializationOptions,
+    };
+
+    use super::*;
+
+    #[test]
+    fn partial_config_keeps_the_defaults_and_is_overridden_by_arguments() {
+        let config = r#"{
+            "serialization": { "lenient_from_xml": true },
+            "output": { "unit_name": "uConfig", "generate_records": true, "list_type": "list" }
+        }"#;
+        let arguments = CodeGenOptions::builder("uOrders")
+            .output(OutputOptions {
+                list_type: ListType::Array,
+                ..OutputOptions::default()
+            })
+            .build();
+
+        let options = merge(config, arguments).unwrap();
+
+        assert_eq!(
+            options,
+            CodeGenOptions::builder("uOrders")
+                .serialization(SerializationOptions {
+                    lenient_from_xml: true,
+                    ..SerializationOptions::default()
+                })
+                .output(OutputOptions {
+                    generate_records: true,
+                    list_type: ListType::Array,
+                    ..OutputOptions::default()
+                })
+                .build()
+        );
+        assert!(options.serialization.generate_from_xml);
+        assert!(options.serialization.generate_to_xml);
+    }
+}
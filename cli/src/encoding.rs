@@ -0,0 +1,116 @@
+use std::path::Path;
+
+use clap::ValueEnum;
+use encoding_rs::WINDOWS_1252;
+
+/// Encoding of the generated units
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum UnitEncoding {
+    /// UTF-8 without byte order mark
+    #[default]
+    Utf8,
+
+    /// UTF-8 with byte order mark, which the Delphi IDE requires to detect UTF-8
+    Utf8Bom,
+
+    /// ANSI code page Windows-1252
+    Windows1252,
+}
+
+/// Line endings of the generated units
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum LineEnding {
+    #[default]
+    Lf,
+
+    Crlf,
+}
+
+/// Rewrites the generated unit with the given encoding and line endings.
+///
+/// Fails for Windows-1252, if the unit contains characters, which the code page can't represent,
+/// e.g. from the documentation of the schema.
+pub fn convert_unit(
+    path: &Path,
+    encoding: UnitEncoding,
+    line_ending: LineEnding,
+) -> Result<(), String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Could not read unit {path:?} due to following error: \"{e:?}\""))?;
+
+    std::fs::write(path, encode(&content, encoding, line_ending)?)
+        .map_err(|e| format!("Could not write unit {path:?} due to following error: \"{e:?}\""))
+}
+
+fn encode(
+    content: &str,
+    encoding: UnitEncoding,
+    line_ending: LineEnding,
+) -> Result<Vec<u8>, String> {
+    let content = content.replace("\r\n", "\n");
+    let content = match line_ending {
+        LineEnding::Lf => content,
+        LineEnding::Crlf => content.replace('\n', "\r\n"),
+    };
+
+    encode_text(content, encoding)
+}
+
+/// Encodes the content of a unit and keeps its line endings
+pub fn encode_text(content: String, encoding: UnitEncoding) -> Result<Vec<u8>, String> {
+    match encoding {
+        UnitEncoding::Utf8 => Ok(content.into_bytes()),
+        UnitEncoding::Utf8Bom => Ok([b"\xEF\xBB\xBF".as_slice(), content.as_bytes()].concat()),
+        UnitEncoding::Windows1252 => match WINDOWS_1252.encode(&content) {
+            (bytes, _, false) => Ok(bytes.into_owned()),
+            // Unmappable characters are replaced by numeric character references
+            (_, _, true) => {
+                let c = content
+                    .chars()
+                    .find(|c| WINDOWS_1252.encode(c.encode_utf8(&mut [0; 4])).2)
+                    .unwrap_or_default();
+
+                Err(format!(
+                    "Character '{c}' can't be encoded in Windows-1252, use UTF-8 instead"
+                ))
+            }
+        },
+    }
+}
+
+/// Decodes a unit written with the encoding and keeps its line endings
+pub fn decode_text(bytes: &[u8], encoding: UnitEncoding) -> Result<String, String> {
+    match encoding {
+        UnitEncoding::Utf8 | UnitEncoding::Utf8Bom => String::from_utf8(
+            bytes
+                .strip_prefix(b"\xEF\xBB\xBF")
+                .unwrap_or(bytes)
+                .to_vec(),
+        )
+        .map_err(|_| String::from("The unit is not encoded in UTF-8")),
+        UnitEncoding::Windows1252 => Ok(WINDOWS_1252
+            .decode_without_bom_handling(bytes)
+            .0
+            .into_owned()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn units_are_encoded_with_the_selected_encoding_and_line_endings() {
+        let content = "unit uÄ;\r\n\ninterface\n";
+
+        assert_eq!(
+            encode(content, UnitEncoding::Utf8Bom, LineEnding::Crlf).unwrap(),
+            b"\xEF\xBB\xBFunit u\xC3\x84;\r\n\r\ninterface\r\n"
+        );
+        assert_eq!(
+            encode(content, UnitEncoding::Windows1252, LineEnding::Lf).unwrap(),
+            b"unit u\xC4;\n\ninterface\n"
+        );
+        assert!(encode("// 漢字", UnitEncoding::Windows1252, LineEnding::Lf).is_err());
+    }
+}
@@ -0,0 +1,113 @@
+use std::{fmt::Display, process::ExitCode};
+
+use clap::ValueEnum;
+
+use xsd_delphi_codegen_core::{openapi, xml};
+
+/// Format in which errors are printed to stderr
+#[derive(Clone, Debug, Default, ValueEnum)]
+pub enum ErrorFormat {
+    /// Human readable message
+    #[default]
+    Text,
+
+    /// Single line JSON object with `kind`, `message` and `exit_code`
+    Json,
+}
+
+/// Errors, which abort the cli. Each kind of error exits with its own code.
+#[derive(Debug)]
+pub enum CliError {
+    /// Arguments or referenced files like the header are invalid
+    InvalidArguments(String),
+    /// The package could not be read, updated or written
+    Package(String),
+    /// The change report or the manifests could not be read or written
+    ChangeReport(String),
+    /// The output directory could not be created or is not writable,
+    /// or the units could not be converted to the selected encoding
+    Output(String),
+    Xml(xml::GenerationError),
+    OpenApi(openapi::GenerationError),
+}
+
+impl CliError {
+    /// Exit code for arguments, which are invalid
+    const INVALID_ARGUMENTS: u8 = 2;
+    /// Exit code for input files, which could not be parsed
+    const INVALID_INPUT: u8 = 3;
+    /// Exit code for failures while generating the code
+    const GENERATION_FAILED: u8 = 4;
+    /// Exit code for output files, which could not be written
+    const IO_FAILED: u8 = 5;
+
+    pub(crate) fn exit_code(&self) -> u8 {
+        match self {
+            Self::InvalidArguments(_)
+            | Self::OpenApi(
+                openapi::GenerationError::MissingSource
+                | openapi::GenerationError::InvalidDestination,
+            ) => Self::INVALID_ARGUMENTS,
+            Self::Xml(xml::GenerationError::Parser(_))
+            | Self::OpenApi(openapi::GenerationError::Spec(_)) => Self::INVALID_INPUT,
+            Self::Xml(xml::GenerationError::CodeGen(_))
+            | Self::OpenApi(openapi::GenerationError::Template(_)) => Self::GENERATION_FAILED,
+            Self::Package(_)
+            | Self::ChangeReport(_)
+            | Self::Output(_)
+            | Self::Xml(xml::GenerationError::Io(_))
+            | Self::OpenApi(openapi::GenerationError::Io(_)) => Self::IO_FAILED,
+        }
+    }
+
+    pub(crate) fn kind(&self) -> &'static str {
+        match self.exit_code() {
+            Self::INVALID_ARGUMENTS => "invalid_arguments",
+            Self::INVALID_INPUT => "invalid_input",
+            Self::GENERATION_FAILED => "generation_failed",
+            _ => "io_failed",
+        }
+    }
+
+    /// Prints the error to stderr and returns the matching exit code.
+    pub fn report(&self, format: &ErrorFormat) -> ExitCode {
+        match format {
+            ErrorFormat::Text => eprintln!("{self}"),
+            ErrorFormat::Json => eprintln!(
+                "{}",
+                serde_json::json!({
+                    "kind": self.kind(),
+                    "message": self.to_string(),
+                    "exit_code": self.exit_code(),
+                })
+            ),
+        }
+
+        ExitCode::from(self.exit_code())
+    }
+}
+
+impl Display for CliError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidArguments(message)
+            | Self::Package(message)
+            | Self::ChangeReport(message)
+            | Self::Output(message) => write!(f, "{message}"),
+            Self::Xml(e) => write!(f, "{e}"),
+            Self::OpenApi(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl From<xml::GenerationError> for CliError {
+    fn from(value: xml::GenerationError) -> Self {
+        Self::Xml(value)
+    }
+}
+
+impl From<openapi::GenerationError> for CliError {
+    fn from(value: openapi::GenerationError) -> Self {
+        Self::OpenApi(value)
+    }
+}
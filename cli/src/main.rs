@@ -1,49 +1,728 @@
 #![allow(clippy::too_many_lines)]
-use std::path::PathBuf;
+use std::{
+    path::{Path, PathBuf},
+    process::ExitCode,
+    time::Instant,
+};
 
-use clap::{Parser, ValueEnum};
+use clap::{Parser, Subcommand, ValueEnum};
 
-use openapi::generate_openapi_client;
-use xml::{generate_xml, generator::code_generator_trait::CodeGenOptions};
+#[cfg(feature = "browser")]
+mod browser;
+mod change_report;
+mod config;
+mod encoding;
+mod error;
+mod output;
+mod package;
+mod server;
 
-fn main() {
+use encoding::{LineEnding, UnitEncoding};
+use error::{CliError, ErrorFormat};
+use output::{resolve_output_path, OutputTarget};
+
+use xsd_delphi_codegen_core::openapi::{
+    generate_http_requests, generate_openapi_client, generate_openapi_mapping, spec_manifest,
+    ClientOptions, EnumLookup, ModelSplit, PlatformDirective, ResponseEnvelope,
+    SerializerAttributes,
+};
+use xsd_delphi_codegen_core::xml::{
+    flatten_xml_schema, generate_sample, generate_xml_with_registry,
+    generation_metadata::GenerationMetadata,
+    generator::code_generator_trait::{
+        AnonymousTypeNaming, CodeGenOptions, DateTimeHandling, DecimalMapping,
+        EnumLookup as XmlEnumLookup, ListType, NamingOptions, OutputOptions, SchemaMetadataOutput,
+        SerializationOptions, UnknownXmlNodes, XmlFormatting,
+    },
+    generator::documentation_coverage::CoverageFormat,
+    generator::passes::BuiltinPass,
+    generator::rename_map::RenameMap,
+    generator::transliteration::Transliteration,
+    sections::{update_sections, Section},
+    type_registry::TypeRegistry,
+    xml_documentation_coverage, ParserLimits,
+};
+
+fn main() -> ExitCode {
     let args = Args::parse();
 
-    let output_path = match resolve_output_path(&args.output) {
-        Ok(p) => p,
-        Err(e) => {
-            eprintln!("{e}");
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => e.report(&args.error_format),
+    }
+}
+
+fn run(args: &Args) -> Result<(), CliError> {
+    if let Some(Command::Sample {
+        input,
+        root,
+        output,
+    }) = &args.command
+    {
+        return run_sample(input, root, output);
+    }
+
+    if let Some(Command::Flatten { input, output }) = &args.command {
+        return run_flatten(input, output);
+    }
+
+    if let Some(Command::Diff {
+        old,
+        new,
+        type_prefix,
+        output,
+    }) = &args.command
+    {
+        return run_diff(old, new, type_prefix, output.as_deref());
+    }
+
+    if let Some(Command::Map {
+        old,
+        new,
+        old_prefix,
+        new_prefix,
+        output,
+    }) = &args.command
+    {
+        return run_map(old, new, old_prefix, new_prefix, output);
+    }
+
+    if let Some(Command::Requests { input, output }) = &args.command {
+        return run_requests(input, output);
+    }
+
+    #[cfg(feature = "browser")]
+    if let Some(Command::Browse { input }) = &args.command {
+        return browser::browse(input, &build_client_options(args, None)?);
+    }
+
+    if let Some(Command::Patch {
+        unit,
+        section,
+        only_class,
+    }) = &args.command
+    {
+        return run_patch(args, unit, section, only_class);
+    }
+
+    if let Some(Command::Serve) = &args.command {
+        return server::serve(std::io::stdin().lock(), std::io::stdout().lock());
+    }
+
+    let overall_instant = Instant::now();
 
-            return;
+    generate(args)?;
+
+    println!(
+        "Completed successfully within {}ms",
+        overall_instant.elapsed().as_millis(),
+    );
+
+    Ok(())
+}
+
+/// Generates the units selected by the arguments and returns the paths of the written units
+fn generate(args: &Args) -> Result<Vec<PathBuf>, CliError> {
+    // Both are required by clap, unless a subcommand is given
+    let (Some(output), Some(source_format)) = (&args.output, &args.source_format) else {
+        return Err(CliError::InvalidArguments(String::from(
+            "Output and source format are required",
+        )));
+    };
+
+    let output = match source_format {
+        SourceFormat::Xml => OutputTarget::unit_file(output, args.unit_name.as_deref()),
+        SourceFormat::OpenApi => OutputTarget::directory(output),
+    }
+    .map_err(CliError::InvalidArguments)?;
+    let header = load_header(args).map_err(CliError::InvalidArguments)?;
+
+    output.prepare().map_err(CliError::Output)?;
+
+    // The manifest of the previous generation is overwritten by the generators
+    let manifest_path = args.change_report.as_ref().map(|_| output.manifest_path());
+    let previous_manifest = match &manifest_path {
+        Some(path) if path.exists() => Some(read_manifest(path)?),
+        _ => None,
+    };
+
+    let units = match source_format {
+        SourceFormat::Xml => {
+            let options = build_code_gen_options(args, header)?;
+            let metadata = match args.emit_metadata {
+                true => Some(GenerationMetadata::collect(&args.input, &options)?),
+                false => None,
+            };
+
+            if let Some(report_path) = &args.doc_coverage {
+                write_documentation_coverage(args, report_path, &options)?;
+            }
+
+            let units = generate_xml_with_registry(
+                &args.input,
+                &output.path().to_path_buf(),
+                options,
+                build_type_registry(args)?,
+                &args.emit_graph,
+                &args.emit_name_map,
+                &manifest_path,
+            )?;
+
+            if let Some(metadata) = metadata {
+                write_metadata(&output.metadata_path(), &metadata)?;
+            }
+
+            units
         }
+        SourceFormat::OpenApi if args.emit_metadata => {
+            return Err(CliError::InvalidArguments(String::from(
+                "Generation metadata is only written for xml schemas",
+            )))
+        }
+        SourceFormat::OpenApi if args.doc_coverage.is_some() => {
+            return Err(CliError::InvalidArguments(String::from(
+                "The documentation coverage is only reported for xml schemas",
+            )))
+        }
+        SourceFormat::OpenApi => generate_openapi_client(
+            &args.input,
+            output.path(),
+            &build_client_options(args, header)?,
+            &args.emit_graph,
+            &manifest_path,
+        )?,
+    };
+
+    for unit in &units {
+        encoding::convert_unit(unit, args.encoding, args.line_endings).map_err(CliError::Output)?;
+    }
+
+    if let (Some(report_path), Some(manifest_path)) = (&args.change_report, &manifest_path) {
+        let report =
+            change_report::compare(previous_manifest.as_deref(), &read_manifest(manifest_path)?)
+                .map_err(CliError::ChangeReport)?;
+
+        std::fs::write(report_path, report).map_err(|e| {
+            CliError::ChangeReport(format!(
+                "Could not write change report {report_path:?} due to following error: \"{e:?}\""
+            ))
+        })?;
+    }
+
+    if let Some(package_path) = &args.emit_package {
+        let package_path = resolve_output_path(package_path).map_err(CliError::InvalidArguments)?;
+        let requires: &[&str] = match source_format {
+            SourceFormat::Xml => &["rtl", "dbrtl", "xmlrtl"],
+            SourceFormat::OpenApi => &["rtl", "RESTComponents"],
+        };
+
+        package::write_package(&package_path, &units, requires).map_err(CliError::Package)?;
+    }
+
+    Ok(units)
+}
+
+fn build_client_options(args: &Args, header: Option<String>) -> Result<ClientOptions, CliError> {
+    Ok(ClientOptions {
+        prefix: args.type_prefix.clone(),
+        header,
+        emit_support_units: args.emit_support_units,
+        validate_request_bodies: args.validate_requests,
+        group_by_tag: args.group_by_tag,
+        roots: args.roots.clone(),
+        split_models: args.split_models.as_ref().map(|s| match s {
+            ModelSplitArg::Tag => ModelSplit::Tag,
+            ModelSplitArg::Namespace => ModelSplit::Namespace,
+        }),
+        enum_lookup: match args.enum_lookup {
+            EnumLookupArg::IfChain => EnumLookup::IfChain,
+            EnumLookupArg::BinarySearch => EnumLookup::BinarySearch,
+        },
+        serializer_attributes: match args.serializer_attributes {
+            Some(SerializerAttributesArg::RestJson) => Some(SerializerAttributes::RestJson),
+            Some(SerializerAttributesArg::Neon) => Some(SerializerAttributes::Neon),
+            Some(SerializerAttributesArg::Xml) => {
+                return Err(CliError::InvalidArguments(String::from(
+                    "Serializer attributes for OpenAPI specs can be one of `RestJson`, `Neon`",
+                )))
+            }
+            None => None,
+        },
+        response_envelope: args
+            .envelope_data
+            .as_ref()
+            .map(|data_key| ResponseEnvelope {
+                data_key: data_key.clone(),
+                error_key: args.envelope_error.clone(),
+                meta_key: args.envelope_meta.clone(),
+            }),
+        log_requests: args.request_logging,
+        request_options: args.request_options,
+        resumable_downloads: args.resumable_downloads,
+        platform_directives: load_platform_config(args).map_err(CliError::InvalidArguments)?,
+    })
+}
+
+fn build_code_gen_options(args: &Args, header: Option<String>) -> Result<CodeGenOptions, CliError> {
+    let Some(unit_name) = args.unit_name.clone() else {
+        return Err(CliError::InvalidArguments(String::from(
+            "Unit name is required for xml schemas",
+        )));
+    };
+
+    if matches!(
+        args.serializer_attributes,
+        Some(SerializerAttributesArg::RestJson | SerializerAttributesArg::Neon)
+    ) {
+        return Err(CliError::InvalidArguments(String::from(
+            "Serializer attributes for xml schemas can only be `Xml`",
+        )));
+    }
+
+    if args.no_xml_declaration && args.xml_standalone.is_some() {
+        return Err(CliError::InvalidArguments(String::from(
+            "The standalone attribute is part of the xml declaration, which is left out",
+        )));
+    }
+
+    let options = CodeGenOptions::builder(unit_name)
+        .naming(NamingOptions {
+            type_prefix: args.type_prefix.clone(),
+            anonymous_type_naming: match args.anonymous_type_names {
+                AnonymousTypeNamingArg::Element => AnonymousTypeNaming::Element,
+                AnonymousTypeNamingArg::Path => AnonymousTypeNaming::Path,
+            },
+            max_type_name_length: args.max_type_name_length.map(usize::from),
+            transliteration: build_transliteration(args)?,
+            rename_map: load_rename_map(args)?,
+        })
+        .serialization(SerializationOptions {
+            generate_from_xml: !matches!(&args.mode, CodeGenMode::ToXml),
+            generate_to_xml: !matches!(&args.mode, CodeGenMode::FromXml),
+            lenient_from_xml: args.lenient,
+            unknown_xml_nodes: match args.unknown_nodes {
+                UnknownNodesArg::Ignore => UnknownXmlNodes::Ignore,
+                UnknownNodesArg::Collect => UnknownXmlNodes::Collect,
+                UnknownNodesArg::Raise => UnknownXmlNodes::Raise,
+            },
+            date_time_handling: match args.date_time {
+                DateTimeArg::Utc => DateTimeHandling::Utc,
+                DateTimeArg::Local => DateTimeHandling::Local,
+                DateTimeArg::Offset => DateTimeHandling::Offset,
+            },
+            preserve_document_nodes: args.preserve_comments,
+            enum_lookup: match args.enum_lookup {
+                EnumLookupArg::IfChain => XmlEnumLookup::IfChain,
+                EnumLookupArg::BinarySearch => XmlEnumLookup::BinarySearch,
+            },
+            generate_serializer_attributes: args.serializer_attributes.is_some(),
+            validate_patterns: args.validate_patterns,
+            generate_constraint_checks: args.constraint_checks,
+            generate_presence_flags: args.presence_flags,
+            xml_formatting: XmlFormatting {
+                indentation: args.xml_indent.map(usize::from),
+                include_declaration: !args.no_xml_declaration,
+                standalone: args.xml_standalone.as_ref().map(|s| match s {
+                    StandaloneArg::Yes => true,
+                    StandaloneArg::No => false,
+                }),
+            },
+        })
+        .output(OutputOptions {
+            header,
+            generate_records: args.records,
+            record_types: args.record_type.clone(),
+            unowned_fields: args.unowned_field.clone(),
+            decimal_mapping: match args.decimal_mapping {
+                DecimalMappingArg::Double => DecimalMapping::Double,
+                DecimalMappingArg::Currency => DecimalMapping::Currency,
+                DecimalMappingArg::Bcd => DecimalMapping::Bcd,
+            },
+            generate_enum_sets: args.enum_sets,
+            list_type: match args.list_type {
+                ListTypeArg::ObjectList => ListType::ObjectList,
+                ListTypeArg::List => ListType::List,
+                ListTypeArg::Array => ListType::Array,
+            },
+            generics_free: args.generics_free,
+            documentation_language: args.doc_lang.clone(),
+            generate_debug_dump: args.debug_dump,
+            schema_metadata: match args.schema_metadata {
+                SchemaMetadataArg::None => SchemaMetadataOutput::None,
+                SchemaMetadataArg::Constants => SchemaMetadataOutput::Constants,
+                SchemaMetadataArg::Registry => SchemaMetadataOutput::Registry,
+            },
+            generate_include_file: args.include_file,
+            ..OutputOptions::default()
+        })
+        .roots(args.roots.clone())
+        .parser_limits(ParserLimits {
+            max_depth: args.max_depth,
+            max_types: args.max_types,
+            max_file_size: args.max_file_size.saturating_mul(MIB),
+        })
+        .max_memory(args.max_memory.map(|m| m.saturating_mul(MIB)))
+        .passes(
+            args.ir_pass
+                .iter()
+                .map(|p| match p {
+                    IrPassArg::FlattenAliases => BuiltinPass::FlattenAliases,
+                    IrPassArg::MergeIdenticalAnonymousTypes => {
+                        BuiltinPass::MergeIdenticalAnonymousTypes
+                    }
+                    IrPassArg::StripUnusedTypes => BuiltinPass::StripUnusedTypes,
+                })
+                .collect(),
+        )
+        .build();
+
+    match &args.config {
+        Some(path) => config::apply_config(path, options).map_err(CliError::InvalidArguments),
+        None => Ok(options),
+    }
+}
+
+/// Transliteration of the identifiers with the replacements passed as `<CHAR>=<REPLACEMENT>`,
+/// `None` if neither `--transliterate` nor a replacement is given
+fn build_transliteration(args: &Args) -> Result<Option<Transliteration>, CliError> {
+    if !args.transliterate && args.transliterate_char.is_empty() {
+        return Ok(None);
+    }
+
+    let mut transliteration = Transliteration::default();
+
+    for replacement in &args.transliterate_char {
+        let mut chars = replacement.chars();
+        let (Some(from), Some('=')) = (chars.next(), chars.next()) else {
+            return Err(CliError::InvalidArguments(format!(
+                "Replacement \"{replacement}\" is not of the form <CHAR>=<REPLACEMENT>"
+            )));
+        };
+
+        transliteration = transliteration.with_replacement(from, chars.collect());
+    }
+
+    Ok(Some(transliteration))
+}
+
+/// Reads the rename map passed by `--rename-map`
+fn load_rename_map(args: &Args) -> Result<Option<RenameMap>, CliError> {
+    let Some(path) = &args.rename_map else {
+        return Ok(None);
+    };
+
+    let content = std::fs::read_to_string(path).map_err(|e| {
+        CliError::InvalidArguments(format!(
+            "Could not read rename map {path:?} due to following error: \"{e:?}\""
+        ))
+    })?;
+
+    serde_json::from_str(&content)
+        .map(Some)
+        .map_err(|e| CliError::InvalidArguments(format!("Rename map {path:?} is not valid: {e}")))
+}
+
+/// Bytes of a mebibyte, the unit of the size and memory limits
+const MIB: u64 = 1024 * 1024;
+
+/// Registers the hand-written types passed as `<NAME>=<UNIT>.<TYPE>`
+fn build_type_registry(args: &Args) -> Result<TypeRegistry, CliError> {
+    let mut registry = TypeRegistry::new();
+
+    for external_type in &args.external_type {
+        let Some((name, (unit, delphi_type))) = external_type
+            .split_once('=')
+            .and_then(|(name, t)| Some((name, t.rsplit_once('.')?)))
+            .filter(|(name, (unit, delphi_type))| {
+                !name.is_empty() && !unit.is_empty() && !delphi_type.is_empty()
+            })
+        else {
+            return Err(CliError::InvalidArguments(format!(
+                "External type \"{external_type}\" is not of the form <NAME>=<UNIT>.<TYPE>"
+            )));
+        };
+
+        registry.register_external_type(name, delphi_type, unit);
+    }
+
+    Ok(registry)
+}
+
+/// Reads the header file and fills in every placeholder except `{unit}`,
+/// which is resolved by the generators for each unit they write.
+fn load_header(args: &Args) -> Result<Option<String>, String> {
+    let Some(path) = &args.header_file else {
+        return Ok(None);
+    };
+
+    let content = std::fs::read_to_string(path).map_err(|e| {
+        format!("Could not read header file {path:?} due to following error: \"{e:?}\"")
+    })?;
+
+    let source_files = args
+        .input
+        .iter()
+        .map(|p| {
+            p.file_name()
+                .map_or_else(|| p.to_string_lossy(), |n| n.to_string_lossy())
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let header = content
+        .trim_end()
+        .replace(
+            "{date}",
+            &chrono::Local::now().format("%Y-%m-%d").to_string(),
+        )
+        .replace("{tool_version}", env!("CARGO_PKG_VERSION"))
+        .replace("{source_files}", &source_files);
+
+    Ok(Some(header))
+}
+
+/// Reads the conditional units and code of the OpenAPI client units, a JSON array of
+/// `PlatformDirective`, e.g. `[{ "define": "MSWINDOWS", "units": ["Winapi.Windows"] }]`
+fn load_platform_config(args: &Args) -> Result<Vec<PlatformDirective>, String> {
+    let Some(path) = &args.platform_config else {
+        return Ok(vec![]);
+    };
+
+    let content = std::fs::read_to_string(path).map_err(|e| {
+        format!("Could not read platform config {path:?} due to following error: \"{e:?}\"")
+    })?;
+    let directives = serde_json::from_str::<Vec<PlatformDirective>>(&content).map_err(|e| {
+        format!("Could not read platform config {path:?} due to following error: \"{e}\"")
+    })?;
+
+    let is_identifier = |define: &str| {
+        define.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && define
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_')
     };
 
-    match &args.source_format {
-        SourceFormat::Xml => generate_xml(&args.input, &output_path, build_code_gen_options(&args)),
-        SourceFormat::OpenApi => {
-            generate_openapi_client(&args.input, &output_path, &args.type_prefix)
+    if let Some(directive) = directives.iter().find(|d| !is_identifier(&d.define)) {
+        return Err(format!(
+            "\"{}\" of the platform config {path:?} is no valid conditional symbol",
+            directive.define
+        ));
+    }
+
+    Ok(directives)
+}
+
+fn run_sample(input: &[PathBuf], root: &str, output: &Path) -> Result<(), CliError> {
+    let output = resolve_output_path(output).map_err(CliError::InvalidArguments)?;
+    let target = OutputTarget::File(output);
+
+    target.prepare().map_err(CliError::Output)?;
+
+    generate_sample(input, root, &target.path().to_path_buf())?;
+
+    Ok(())
+}
+
+fn run_flatten(input: &Path, output: &Path) -> Result<(), CliError> {
+    let output = resolve_output_path(output).map_err(CliError::InvalidArguments)?;
+    let target = OutputTarget::File(output);
+
+    target.prepare().map_err(CliError::Output)?;
+
+    flatten_xml_schema(input, target.path())?;
+
+    Ok(())
+}
+
+fn run_diff(
+    old: &Path,
+    new: &Path,
+    type_prefix: &Option<String>,
+    output: Option<&Path>,
+) -> Result<(), CliError> {
+    let previous = spec_manifest(old, type_prefix)?;
+    let current = spec_manifest(new, type_prefix)?;
+
+    let report =
+        change_report::compare(Some(&previous), &current).map_err(CliError::ChangeReport)?;
+
+    match output {
+        Some(path) => std::fs::write(path, report).map_err(|e| {
+            CliError::ChangeReport(format!(
+                "Could not write change report {path:?} due to following error: \"{e:?}\""
+            ))
+        }),
+        None => {
+            print!("{report}");
+            Ok(())
         }
     }
 }
 
-fn build_code_gen_options(args: &Args) -> CodeGenOptions {
-    CodeGenOptions {
-        generate_from_xml: !matches!(&args.mode, CodeGenMode::ToXml),
-        generate_to_xml: !matches!(&args.mode, CodeGenMode::FromXml),
-        unit_name: args.unit_name.clone().expect("Unit name is required"),
-        type_prefix: args.type_prefix.clone(),
+fn run_map(
+    old: &Path,
+    new: &Path,
+    old_prefix: &Option<String>,
+    new_prefix: &Option<String>,
+    output: &Path,
+) -> Result<(), CliError> {
+    if old_prefix == new_prefix {
+        return Err(CliError::InvalidArguments(String::from(
+            "The type prefixes of both versions must differ, so their units can be used side by side",
+        )));
     }
+
+    let output = resolve_output_path(output).map_err(CliError::InvalidArguments)?;
+    let target = OutputTarget::File(output);
+
+    target.prepare().map_err(CliError::Output)?;
+
+    generate_openapi_mapping(old, new, old_prefix, new_prefix, target.path())?;
+
+    Ok(())
 }
 
-fn resolve_output_path(path: &PathBuf) -> Result<PathBuf, String> {
-    if path.is_relative() {
-        std::env::current_dir()
-            .map(|d| d.join(path))
-            .map_err(|e| format!("Relative path not supported due to following error: \"{e:?}\""))
-    } else {
-        std::path::absolute(path)
-            .map_err(|e| format!("Could not resolve output path due to following error: \"{e:?}\""))
+fn run_requests(input: &Path, output: &Path) -> Result<(), CliError> {
+    let output = resolve_output_path(output).map_err(CliError::InvalidArguments)?;
+    let target = OutputTarget::File(output);
+
+    target.prepare().map_err(CliError::Output)?;
+
+    generate_http_requests(input, target.path())?;
+
+    Ok(())
+}
+
+/// Generates the unit from the xml schemas with the options of the arguments into a temporary
+/// directory and replaces the sections of the existing `unit` by the generated ones
+fn run_patch(
+    args: &Args,
+    unit: &Path,
+    regions: &[String],
+    classes: &[String],
+) -> Result<(), CliError> {
+    if let Some(SourceFormat::OpenApi) = args.source_format {
+        return Err(CliError::InvalidArguments(String::from(
+            "Only units generated from xml schemas can be patched",
+        )));
     }
+
+    let sections = regions
+        .iter()
+        .map(|r| Section::Region(r.clone()))
+        .chain(classes.iter().map(|c| Section::Class(c.clone())))
+        .collect::<Vec<_>>();
+
+    if sections.is_empty() {
+        return Err(CliError::InvalidArguments(String::from(
+            "At least one section or class is required",
+        )));
+    }
+
+    let existing = std::fs::read(unit).map_err(|e| {
+        CliError::InvalidArguments(format!(
+            "Could not read unit {unit:?} due to following error: \"{e:?}\""
+        ))
+    })?;
+    let existing =
+        encoding::decode_text(&existing, args.encoding).map_err(CliError::InvalidArguments)?;
+
+    let options =
+        build_code_gen_options(args, load_header(args).map_err(CliError::InvalidArguments)?)?;
+    let directory = std::env::temp_dir().join(format!("genphi-patch-{}", std::process::id()));
+    let generated_path = directory.join(unit.file_name().unwrap_or_default());
+
+    std::fs::create_dir_all(&directory).map_err(|e| {
+        CliError::Output(format!(
+            "Could not create directory {directory:?} due to following error: \"{e:?}\""
+        ))
+    })?;
+
+    let generated = generate_xml_with_registry(
+        &args.input,
+        &generated_path,
+        options,
+        build_type_registry(args)?,
+        &None,
+        &None,
+        &None,
+    )
+    .map_err(CliError::Xml)
+    .and_then(|_| {
+        encoding::convert_unit(&generated_path, args.encoding, args.line_endings)
+            .map_err(CliError::Output)?;
+
+        std::fs::read(&generated_path).map_err(|e| {
+            CliError::Output(format!(
+                "Could not read unit {generated_path:?} due to following error: \"{e:?}\""
+            ))
+        })
+    });
+    let _ = std::fs::remove_dir_all(&directory);
+    let generated = encoding::decode_text(&generated?, args.encoding).map_err(CliError::Output)?;
+
+    let content =
+        update_sections(&existing, &generated, &sections).map_err(CliError::InvalidArguments)?;
+
+    std::fs::write(
+        unit,
+        encoding::encode_text(content, args.encoding).map_err(CliError::Output)?,
+    )
+    .map_err(|e| {
+        CliError::Output(format!(
+            "Could not write unit {unit:?} due to following error: \"{e:?}\""
+        ))
+    })
+}
+
+/// Warns about the changes since the generation described by the metadata at `path`, which
+/// may change the generated code, and replaces it with `metadata`
+fn write_metadata(path: &Path, metadata: &GenerationMetadata) -> Result<(), CliError> {
+    if let Ok(content) = std::fs::read_to_string(path) {
+        match GenerationMetadata::from_json(&content) {
+            Ok(previous) => {
+                for change in metadata.drift(&previous) {
+                    eprintln!("Warning: {change}");
+                }
+            }
+            Err(e) => eprintln!("Warning: {e}, it is replaced"),
+        }
+    }
+
+    std::fs::write(path, metadata.to_json()).map_err(|e| {
+        CliError::Output(format!(
+            "Could not write generation metadata {path:?} due to following error: \"{e:?}\""
+        ))
+    })
+}
+
+/// Writes the report of the undocumented types and fields as CSV for paths ending with `.csv`,
+/// otherwise as JSON
+fn write_documentation_coverage(
+    args: &Args,
+    path: &Path,
+    options: &CodeGenOptions,
+) -> Result<(), CliError> {
+    let format = match path.extension() {
+        Some(extension) if extension.eq_ignore_ascii_case("csv") => CoverageFormat::Csv,
+        _ => CoverageFormat::Json,
+    };
+    let report =
+        xml_documentation_coverage(&args.input, options, build_type_registry(args)?, format)?;
+
+    std::fs::write(path, report).map_err(|e| {
+        CliError::Output(format!(
+            "Could not write documentation coverage {path:?} due to following error: \"{e:?}\""
+        ))
+    })
+}
+
+fn read_manifest(path: &PathBuf) -> Result<String, CliError> {
+    std::fs::read_to_string(path).map_err(|e| {
+        CliError::ChangeReport(format!(
+            "Could not read manifest {path:?} due to following error: \"{e:?}\""
+        ))
+    })
 }
 
 /// `XSD2DelphiCodeGen` generates Types from XSD-Files for Delphi
@@ -85,15 +764,20 @@ fn resolve_output_path(path: &PathBuf) -> Result<PathBuf, String> {
 /// XSD2DelphiCodeGen --type-prefix MyPrefix input.xsd output.pas MyUnit
 /// ```
 #[derive(Parser, Debug)]
-#[command(author, version, about, long_about = None)]
+#[command(author, version, about, long_about = None, subcommand_negates_reqs = true)]
 pub struct Args {
+    #[command(subcommand)]
+    pub(crate) command: Option<Command>,
+
     /// One or multiple paths to xsd files. Paths can be relative or absolut.
     #[arg(short, long, value_hint = clap::ValueHint::DirPath, num_args(1..))]
     pub(crate) input: Vec<std::path::PathBuf>,
 
-    /// Path to output file. Path can be relative or absolut. File will be created or truncated before write.
+    /// Path to output file for xml schemas or output directory for OpenAPI specs. Path can be relative or absolut.
+    /// File will be created or truncated before write, missing directories are created.
+    /// For xml schemas a directory or a path ending with a separator writes the unit `<unit-name>.pas` into it.
     #[arg(short, long, required(true))]
-    pub(crate) output: std::path::PathBuf,
+    pub(crate) output: Option<std::path::PathBuf>,
 
     /// Name of the generated unit
     #[arg(long)]
@@ -108,8 +792,386 @@ pub struct Args {
     pub(crate) mode: CodeGenMode,
 
     /// Source format of the input files. Can be one of `Xml`, `OpenApi`. Default is `Xml`
+    #[arg(long, value_enum, required(true))]
+    pub(crate) source_format: Option<SourceFormat>,
+
+    /// Path to a JSON file with the options of the generation from xml schemas, e.g. `{"output": {"generate_records": true}}`.
+    /// Missing options keep their default, arguments differing from their default override the file.
+    #[arg(long, value_hint = clap::ValueHint::FilePath)]
+    pub(crate) config: Option<std::path::PathBuf>,
+
+    /// Path to a file whose content is inserted at the top of every generated unit.
+    /// Supports the placeholders `{unit}`, `{date}`, `{tool_version}` and `{source_files}`.
+    #[arg(long, value_hint = clap::ValueHint::FilePath)]
+    pub(crate) header_file: Option<std::path::PathBuf>,
+
+    /// Path to a file, where a Graphviz (DOT) graph of the generated types and their relations is written to.
+    #[arg(long, value_hint = clap::ValueHint::FilePath)]
+    pub(crate) emit_graph: Option<std::path::PathBuf>,
+
+    /// Path to a file, where the names of the generated types and the schema paths they are generated from are written to.
+    #[arg(long, value_hint = clap::ValueHint::FilePath)]
+    pub(crate) emit_name_map: Option<std::path::PathBuf>,
+
+    /// Write the generator version, the options and the constructs of the xml schemas, including the skipped ones, to `<unit name>.genphi-metadata.json` next to the unit.
+    /// Changes since the previous generation, which may change the generated code, are printed as warnings.
+    #[arg(long)]
+    pub(crate) emit_metadata: bool,
+
+    /// Path to a file, where the types, elements, attributes and enumeration values of the xml schemas without `xs:documentation` are written to.
+    /// A path ending with `.csv` writes a CSV file, any other path a JSON file with the number of the documented and of all entries.
+    #[arg(long, value_hint = clap::ValueHint::FilePath)]
+    pub(crate) doc_coverage: Option<std::path::PathBuf>,
+
+    /// Path to a file, where the changes of the generated types compared to the previous generation are written to.
+    /// The types of each generation are kept in a `genphi.json` manifest next to the generated units.
+    #[arg(long, value_hint = clap::ValueHint::FilePath)]
+    pub(crate) change_report: Option<std::path::PathBuf>,
+
+    /// Path to a Delphi package (.dpk), which should contain the generated units. An existing package is updated.
+    #[arg(long, value_hint = clap::ValueHint::FilePath)]
+    pub(crate) emit_package: Option<std::path::PathBuf>,
+
+    /// Generate only the types reachable from these roots: names of root elements for xml schemas,
+    /// names or tags of operations for OpenAPI specs. All types are generated, if omitted.
+    #[arg(long, num_args(1..))]
+    pub(crate) roots: Vec<String>,
+
+    /// Generate small types, which only contain value type fields and take no part in inheritance, as records instead of classes.
+    #[arg(long)]
+    pub(crate) records: bool,
+
+    /// Name of a type, which should be generated as record regardless of its size. Can be specified multiple times.
+    #[arg(long)]
+    pub(crate) record_type: Vec<String>,
+
+    /// Field as `<TYPE>.<FIELD>`, whose object is owned elsewhere, e.g. a shared lookup, and neither created nor freed by its class.
+    /// The type is given by its name, the field by the name of its element or attribute. Can be specified multiple times.
+    #[arg(long)]
+    pub(crate) unowned_field: Vec<String>,
+
+    /// Complex type implemented by hand as `<NAME>=<UNIT>.<TYPE>`, e.g. `Address=uAddress.TAddress`.
+    /// The type is not generated, its usages reference the Delphi type and the unit is added to the uses clause.
+    /// Can be specified multiple times.
+    #[arg(long)]
+    pub(crate) external_type: Vec<String>,
+
+    /// Delphi type for decimals restricted by xs:totalDigits or xs:fractionDigits. Can be one of `Double`, `Currency`, `Bcd`. Default is `Double`
+    #[arg(long, value_enum, default_value_t)]
+    pub(crate) decimal_mapping: DecimalMappingArg,
+
+    /// Generate repeated values of an enumeration with at most 256 values as set instead of list.
+    #[arg(long)]
+    pub(crate) enum_sets: bool,
+
+    /// How the conversion of strings to enumeration values looks up the value. Can be one of `IfChain`, `BinarySearch`. Default is `IfChain`.
+    /// A binary search pays off for enumerations with many values.
+    #[arg(long, value_enum, default_value_t)]
+    pub(crate) enum_lookup: EnumLookupArg,
+
+    /// Decorate the fields of the generated types with custom attributes naming their JSON key or xml node,
+    /// so RTTI based serializers can be used instead of the generated conversions.
+    /// Can be one of `RestJson`, `Neon` for OpenAPI specs and `Xml` for xml schemas.
     #[arg(long, value_enum)]
-    pub(crate) source_format: SourceFormat,
+    pub(crate) serializer_attributes: Option<SerializerAttributesArg>,
+
+    /// Keep comments and processing instructions on document level, when a document is read and written again.
+    #[arg(long)]
+    pub(crate) preserve_comments: bool,
+
+    /// Indent the nested elements of the xml written by `ToXml` by the given number of spaces per level.
+    /// Without it the xml is written compact without line breaks.
+    #[arg(long)]
+    pub(crate) xml_indent: Option<u8>,
+
+    /// Leave out the xml declaration of the xml written by `ToXml`.
+    #[arg(long)]
+    pub(crate) no_xml_declaration: bool,
+
+    /// Value of the standalone attribute of the xml declaration written by `ToXml`. Can be one of `Yes`, `No`. Omitted by default
+    #[arg(long, value_enum)]
+    pub(crate) xml_standalone: Option<StandaloneArg>,
+
+    /// Read missing or invalid required elements and attributes as default value and collect a warning in `FromXmlWarnings`, instead of raising an exception.
+    #[arg(long)]
+    pub(crate) lenient: bool,
+
+    /// Generate required fields of string types with xs:pattern as properties, whose setter raises an `EArgumentException` for values not matching the pattern.
+    #[arg(long)]
+    pub(crate) validate_patterns: bool,
+
+    /// Replace the non-ASCII characters of the type, field and enumeration value identifiers generated for xml schemas, e.g. `Größe` becomes `Groesse`.
+    /// The names in the xml documents are kept. Identifiers colliding after the replacement get a number appended.
+    #[arg(long)]
+    pub(crate) transliterate: bool,
+
+    /// Replacement of a character as `<CHAR>=<REPLACEMENT>`, e.g. `ä=a`, which takes precedence over the built-in one.
+    /// Implies `--transliterate`. Can be specified multiple times.
+    #[arg(long)]
+    pub(crate) transliterate_char: Vec<String>,
+
+    /// Path to a JSON file with names, which replace the generated names of types, fields and enumeration values of xml schemas,
+    /// e.g. `{"types": {"Addr": "Address"}, "fields": {"Addr": {"str": "Street"}}, "values": {"Status": {"o": "Open"}}}`.
+    /// Types are identified by their name or qualified name, fields by their element or attribute name. Entries not matching the schema are an error.
+    #[arg(long, value_hint = clap::ValueHint::FilePath)]
+    pub(crate) rename_map: Option<std::path::PathBuf>,
+
+    /// How anonymous types declared inside of an element are named. Can be one of `Element`, `Path`. Default is `Element`
+    #[arg(long, value_enum, default_value_t)]
+    pub(crate) anonymous_type_names: AnonymousTypeNamingArg,
+
+    /// Maximum length of the names of anonymous types. Longer names are cut and get a hash of their schema path appended.
+    #[arg(long, value_parser = clap::value_parser!(u16).range(16..))]
+    pub(crate) max_type_name_length: Option<u16>,
+
+    /// Language of the `xs:documentation` elements used for the comments, e.g. `en`. Falls back to the first language of an annotation.
+    #[arg(long)]
+    pub(crate) doc_lang: Option<String>,
+
+    /// Generate a `ToString` override for xml schemas, which lists the fields and their values recursively, e.g. for logging.
+    /// Requires the generation of `ToXml`.
+    #[arg(long)]
+    pub(crate) debug_dump: bool,
+
+    /// Include `<unit name>.inc` into every class generated for xml schemas and at the end of the unit, so manual code can be kept apart.
+    /// The include file is written, if it doesn't exist, and never overwritten.
+    #[arg(long)]
+    pub(crate) include_file: bool,
+
+    /// Generate `CheckConstraints` for the classes of xml schema elements with `xs:key`, `xs:keyref` or `xs:unique`.
+    /// Requires the generation of `ToXml`.
+    #[arg(long)]
+    pub(crate) constraint_checks: bool,
+
+    /// Generate a read-only `<Field>WasPresentInXml` property for the optional elements and attributes of xml schema classes,
+    /// which tells whether `FromXml` found the node, e.g. to distinguish a value equal to the default from a supplied one.
+    #[arg(long)]
+    pub(crate) presence_flags: bool,
+
+    /// How `FromXml` handles child elements and attributes, which are not defined by the xml schema. Can be one of `Ignore`, `Collect`, `Raise`. Default is `Ignore`.
+    /// Collected nodes are listed by `UnknownElements` of the object.
+    #[arg(long, value_enum, default_value_t)]
+    pub(crate) unknown_nodes: UnknownNodesArg,
+
+    /// Time zone handling of `xs:dateTime` and `xs:date` in xml schemas. Can be one of `Utc`, `Local`, `Offset`. Default is `Utc`.
+    /// `Offset` generates `xs:dateTime` as `TXmlDateTime`, which keeps the value and the offset of the document.
+    #[arg(long, value_enum, default_value_t)]
+    pub(crate) date_time: DateTimeArg,
+
+    /// Generation of the `xs:appinfo` of the types and the `xs:notation` elements of xml schemas. Can be one of `None`, `Constants`, `Registry`. Default is `None`.
+    /// `Registry` generates `TSchemaMetadata` in addition to the constants, which looks up the `xs:appinfo` by the name of the generated type.
+    #[arg(long, value_enum, default_value_t)]
+    pub(crate) schema_metadata: SchemaMetadataArg,
+
+    /// Delphi type of the lists of repeated elements in xml schemas. Can be one of `ObjectList`, `List`, `Array`. Default is `ObjectList`.
+    /// `List` and `Array` don't own the items, the destructor of the class frees the items of classes.
+    #[arg(long, value_enum, default_value_t)]
+    pub(crate) list_type: ListTypeArg,
+
+    /// Generate units for xml schemas without generics, e.g. for older Delphi versions.
+    /// Lists, arrays and optional values are generated as concrete types per item type, like `TListOfInteger`.
+    /// Units are used without their scope, like `SysUtils`, and xs:anyURI is generated as `String`.
+    #[arg(long)]
+    pub(crate) generics_free: bool,
+
+    /// Maximum nesting depth of the elements of a xml schema file. Deeper schemas are rejected, instead of overflowing the stack.
+    #[arg(long, default_value_t = ParserLimits::default().max_depth)]
+    pub(crate) max_depth: usize,
+
+    /// Maximum number of types defined by the xml schemas.
+    #[arg(long, default_value_t = ParserLimits::default().max_types)]
+    pub(crate) max_types: usize,
+
+    /// Maximum size of a xml schema file in MiB.
+    #[arg(long, default_value_t = ParserLimits::default().max_file_size / MIB)]
+    pub(crate) max_file_size: u64,
+
+    /// Memory in MiB available for the generation from xml schemas. Schemas, which probably need more,
+    /// are processed slower, but with less memory.
+    #[arg(long)]
+    pub(crate) max_memory: Option<u64>,
+
+    /// Transformation of the types of xml schemas before the unit is generated. Can be one of `FlattenAliases`,
+    /// `MergeIdenticalAnonymousTypes`, `StripUnusedTypes`. Can be specified multiple times, the passes run in the given order.
+    #[arg(long, value_enum)]
+    pub(crate) ir_pass: Vec<IrPassArg>,
+
+    /// Write the helper units referenced by the generated OpenAPI units, e.g. `uJsonHelper`, next to them.
+    /// Units generated from xml schemas contain their helpers and need no support units.
+    #[arg(long)]
+    pub(crate) emit_support_units: bool,
+
+    /// Validate request bodies against the constraints of their schema, before an OpenAPI client sends them.
+    #[arg(long)]
+    pub(crate) validate_requests: bool,
+
+    /// Generate a separate OpenAPI client for the operations of every tag and a facade `ApiClient`, which gives access to them.
+    #[arg(long)]
+    pub(crate) group_by_tag: bool,
+
+    /// Split the OpenAPI models into a unit per tag or per `x-namespace` of the schemas. Can be one of `Tag`, `Namespace`.
+    /// Enums and models used by several units are generated into shared units.
+    #[arg(long, value_enum)]
+    pub(crate) split_models: Option<ModelSplitArg>,
+
+    /// Key of the payload of a standard response envelope, e.g. `data` of `{ data, error, meta }`.
+    /// Responses with the payload and no other properties than error and meta are unwrapped,
+    /// the client methods return the payload and the envelope models are not generated.
+    #[arg(long)]
+    pub(crate) envelope_data: Option<String>,
+
+    /// Key of the error of the response envelope. A set error is raised as `ApiException` with the error as JSON.
+    #[arg(long, requires = "envelope_data")]
+    pub(crate) envelope_error: Option<String>,
+
+    /// Key of the metadata of the response envelope, which the client methods pass out as `pMeta`.
+    #[arg(long, requires = "envelope_data")]
+    pub(crate) envelope_meta: Option<String>,
+
+    /// Log every request of an OpenAPI client with the `IApiLogger` of its settings. The logger is declared in the support unit `uApiLogger`.
+    #[arg(long)]
+    pub(crate) request_logging: bool,
+
+    /// Add an overload to every method of an OpenAPI client, which takes a `RequestOptions` record with headers, query parameters and a timeout for the single request.
+    #[arg(long)]
+    pub(crate) request_options: bool,
+
+    /// Resume the file downloads of an OpenAPI client after the bytes, which the target stream already holds, by requesting the rest of the file with a `Range` header.
+    #[arg(long)]
+    pub(crate) resumable_downloads: bool,
+
+    /// Path to a JSON file with units and code of the OpenAPI client units, which are enclosed in `{$IFDEF}`, so a single unit compiles for several platforms,
+    /// e.g. `[{ "define": "MSWINDOWS", "units": ["Winapi.Windows"], "else_units": ["Posix.Unistd"] }]`. `code` and `else_code` are inserted at the start of the implementation.
+    #[arg(long, value_hint = clap::ValueHint::FilePath)]
+    pub(crate) platform_config: Option<std::path::PathBuf>,
+
+    /// Encoding of the generated units. Can be one of `Utf8`, `Utf8Bom`, `Windows1252`. Default is `Utf8`
+    #[arg(long, value_enum, default_value_t)]
+    pub(crate) encoding: UnitEncoding,
+
+    /// Line endings of the generated units. Can be one of `Lf`, `Crlf`. Default is `Lf`
+    #[arg(long, value_enum, default_value_t)]
+    pub(crate) line_endings: LineEnding,
+
+    /// Format in which errors are printed. Can be one of `Text`, `Json`. Default is `Text`
+    #[arg(long, value_enum, default_value_t)]
+    pub(crate) error_format: ErrorFormat,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Writes an example document for a root element of xml schemas, e.g. to test the generated units.
+    /// Values honor enumerations, simple patterns and the digits of decimals.
+    Sample {
+        /// One or multiple paths to xsd files. Paths can be relative or absolut.
+        #[arg(short, long, value_hint = clap::ValueHint::FilePath, num_args(1..), required(true))]
+        input: Vec<PathBuf>,
+
+        /// Name of the root element
+        #[arg(long)]
+        root: String,
+
+        /// Path to the example document
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+    /// Writes a xml schema with all schemas it includes or imports as a single, self-contained schema,
+    /// e.g. to share a reproduction case. References to named groups and attribute groups are
+    /// replaced by their content.
+    Flatten {
+        /// Path to the xsd file
+        #[arg(short, long, value_hint = clap::ValueHint::FilePath)]
+        input: PathBuf,
+
+        /// Path to the flattened xsd file
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+    /// Compares two versions of an OpenAPI spec and reports the changes of the generated client,
+    /// i.e. added, removed and changed methods, models and enums. Changes, which break code
+    /// using the client, are marked as breaking.
+    Diff {
+        /// Path to the previous version of the spec
+        old: PathBuf,
+
+        /// Path to the new version of the spec
+        new: PathBuf,
+
+        /// Optional prefix for type names, as passed to the generation
+        #[arg(long)]
+        type_prefix: Option<String>,
+
+        /// Path to the report, which is printed if omitted
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Writes a unit, which converts the OpenAPI models of one version of a spec into the models
+    /// with the same name of another version, e.g. `TUserV1ToV2.Map`. Properties with the same
+    /// key and type are copied, the remaining ones are marked as TODO.
+    Map {
+        /// Path to the spec, whose models are converted
+        old: PathBuf,
+
+        /// Path to the spec, into whose models they are converted
+        new: PathBuf,
+
+        /// Prefix for type names of the old version, as passed to its generation
+        #[arg(long)]
+        old_prefix: Option<String>,
+
+        /// Prefix for type names of the new version, as passed to its generation
+        #[arg(long)]
+        new_prefix: Option<String>,
+
+        /// Path to the mapping unit, whose file name is the unit name
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+    /// Writes a `.http` file with a sample request per operation of an OpenAPI spec, which can be
+    /// sent with the REST Client extension of VS Code or the HTTP Client of IntelliJ.
+    /// Bodies match the generated models, credentials are variables at the top of the file.
+    Requests {
+        /// Path to the spec
+        #[arg(short, long, value_hint = clap::ValueHint::FilePath)]
+        input: PathBuf,
+
+        /// Path to the `.http` file
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+    /// Browses the operations of an OpenAPI spec in the terminal with a preview of the Delphi method
+    /// and models generated for the selected one. Options of the generation are passed before the
+    /// subcommand, e.g. `genphi --type-prefix Pet browse -i petstore.yaml`. A selected operation
+    /// is sent against a server of the spec with the values entered in a form of its parameters,
+    /// which shows the raw request and response, e.g. to check the collected endpoints.
+    #[cfg(feature = "browser")]
+    Browse {
+        /// Path to the spec
+        #[arg(short, long, value_hint = clap::ValueHint::FilePath)]
+        input: PathBuf,
+    },
+    /// Regenerates only some regions or classes of a unit generated from xml schemas and keeps the
+    /// rest of the unit byte-identical, e.g. to keep the diff of a schema change small. The schemas
+    /// and options are passed like for the generation of the unit, e.g.
+    /// `genphi -i orders.xsd --unit-name uOrders patch uOrders.pas --only-class TOrder`.
+    Patch {
+        /// Path to the generated unit, which is updated
+        unit: PathBuf,
+
+        /// Name of a region, e.g. `Enumerations`. Regions of the interface and the implementation with
+        /// this name are replaced. Can be specified multiple times.
+        #[arg(long)]
+        section: Vec<String>,
+
+        /// Name of a class or record, whose declaration and implementation are replaced, e.g. `TOrder`.
+        /// Can be specified multiple times.
+        #[arg(long)]
+        only_class: Vec<String>,
+    },
+    /// Answers JSON-RPC 2.0 requests read line by line from stdin until stdin is closed,
+    /// e.g. for IDE plugins and build daemons generating repeatedly. Parsed xml schemas are
+    /// kept in memory and only parsed again, once they are modified.
+    Serve,
 }
 
 /// Which code should be generated. Can be one of `All`, `ToXml`, `FromXml`. Default is `All`
@@ -126,9 +1188,229 @@ enum CodeGenMode {
     FromXml,
 }
 
+/// Delphi type for decimals restricted by xs:totalDigits or xs:fractionDigits
+#[derive(Clone, Debug, Default, ValueEnum)]
+enum DecimalMappingArg {
+    /// Keep `Double`
+    #[default]
+    Double,
+
+    /// Use `Currency`, falls back to `TBcd` if the digits don't fit
+    Currency,
+
+    /// Use `TBcd`
+    Bcd,
+}
+
+/// How the conversion of strings to enumeration values looks up the value
+#[derive(Clone, Debug, Default, ValueEnum)]
+enum EnumLookupArg {
+    /// Compare the string with every value in turn
+    #[default]
+    IfChain,
+
+    /// Binary search in a sorted constant array of the values
+    BinarySearch,
+}
+
+/// Time zone handling of dates in xml schemas
+#[derive(Clone, Debug, Default, ValueEnum)]
+enum DateTimeArg {
+    /// `TDateTime` in UTC, values are converted from and to UTC
+    #[default]
+    Utc,
+
+    /// `TDateTime` in the local time zone, values are converted from and to local time
+    Local,
+
+    /// `TXmlDateTime` for `xs:dateTime`, which keeps the value and the offset of the document
+    Offset,
+}
+
+/// Transformation of the types of xml schemas before the unit is generated
+#[derive(Clone, Debug, ValueEnum)]
+enum IrPassArg {
+    /// Replace aliases, which wrap another type without pattern or digits, by the wrapped type
+    FlattenAliases,
+
+    /// Merge anonymous types declared with the same content by several elements
+    MergeIdenticalAnonymousTypes,
+
+    /// Remove the types not used by a root element
+    StripUnusedTypes,
+}
+
+/// Delphi type of the lists of repeated elements in xml schemas
+#[derive(Clone, Debug, Default, ValueEnum)]
+enum ListTypeArg {
+    /// `TObjectList<T>` for classes and `TList<T>` for other types
+    #[default]
+    ObjectList,
+
+    /// `TList<T>` for all types
+    List,
+
+    /// Dynamic arrays `TArray<T>`
+    Array,
+}
+
+/// Generation of the metadata of xml schemas
+#[derive(Clone, Debug, Default, ValueEnum)]
+enum SchemaMetadataArg {
+    /// Leave the metadata out
+    #[default]
+    None,
+
+    /// A string constant per type with `xs:appinfo` and per notation
+    Constants,
+
+    /// The constants and `TSchemaMetadata`, which looks up the `xs:appinfo` by type name
+    Registry,
+}
+
+/// How `FromXml` handles nodes, which are not defined by the xml schema
+#[derive(Clone, Debug, Default, ValueEnum)]
+enum UnknownNodesArg {
+    /// Skip them silently
+    #[default]
+    Ignore,
+
+    /// Add their names to `UnknownElements` of the object
+    Collect,
+
+    /// Raise an exception
+    Raise,
+}
+
+/// Custom attributes, which name the JSON key or xml node of the fields
+#[derive(Clone, Debug, ValueEnum)]
+enum SerializerAttributesArg {
+    /// `[JSONName('key')]` read by `TJson` of REST.Json
+    RestJson,
+
+    /// `[NeonProperty('key')]` read by the Neon serializer
+    Neon,
+
+    /// `[XmlElement('name')]` and `[XmlAttribute('name')]` declared by the generated unit
+    Xml,
+}
+
+/// Value of the standalone attribute of the xml declaration
+#[derive(Clone, Debug, ValueEnum)]
+enum StandaloneArg {
+    /// The document doesn't depend on external markup declarations
+    Yes,
+
+    /// The document may depend on external markup declarations
+    No,
+}
+
+/// How anonymous types declared inside of an element are named
+#[derive(Clone, Debug, Default, ValueEnum)]
+enum AnonymousTypeNamingArg {
+    /// Use the name of the element
+    #[default]
+    Element,
+
+    /// Prepend the name of the parent type to the name of the element
+    Path,
+}
+
+/// How the OpenAPI models are split into units
+#[derive(Clone, Debug, ValueEnum)]
+enum ModelSplitArg {
+    /// A unit per tag with the models only used by its operations
+    Tag,
+
+    /// A unit per `x-namespace` extension of the schemas
+    Namespace,
+}
+
 /// Source format of the input files. Can be one of `Xml`, `OpenApi`. Default is `Xml`
 #[derive(Clone, Debug, ValueEnum)]
 enum SourceFormat {
     Xml,
     OpenApi,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn header_file_placeholders_are_filled_in_for_every_unit() {
+        let root = std::env::temp_dir().join(format!("genphi-header-{}", std::process::id()));
+        let header_file = root.join("header.txt");
+        let schema =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("../xml/tests/fixtures/sequence.xsd");
+        let spec =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("../openapi/tests/fixtures/petstore.yaml");
+
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(
+            &header_file,
+            "// {unit} from {source_files}\n// genphi {tool_version} on {date}\n\n",
+        )
+        .unwrap();
+
+        let generate_with_header = |arguments: &[&Path], format: &str| {
+            let mut args = vec![Path::new("genphi")];
+            args.extend(arguments);
+            args.extend([
+                Path::new("--source-format"),
+                Path::new(format),
+                Path::new("--header-file"),
+                &header_file,
+            ]);
+
+            generate(&Args::parse_from(args)).unwrap()
+        };
+        let xml_units = generate_with_header(
+            &[
+                Path::new("-i"),
+                &schema,
+                Path::new("-o"),
+                &root.join("uSequence.pas"),
+                Path::new("--unit-name"),
+                Path::new("uSequence"),
+            ],
+            "xml",
+        );
+        let openapi_units = generate_with_header(
+            &[
+                Path::new("-i"),
+                &spec,
+                Path::new("-o"),
+                &root.join("petstore"),
+            ],
+            "open-api",
+        );
+
+        for (unit, source_file) in xml_units
+            .iter()
+            .map(|u| (u, "sequence.xsd"))
+            .chain(openapi_units.iter().map(|u| (u, "petstore.yaml")))
+        {
+            let content = std::fs::read_to_string(unit).unwrap();
+            let mut lines = content.lines();
+            let unit_name = unit.file_stem().unwrap().to_string_lossy();
+
+            assert_eq!(
+                lines.next(),
+                Some(format!("// {unit_name} from {source_file}").as_str())
+            );
+
+            let version_line = lines.next().unwrap();
+            let date = version_line
+                .strip_prefix(&format!("// genphi {} on ", env!("CARGO_PKG_VERSION")))
+                .unwrap();
+
+            assert!(chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").is_ok());
+        }
+
+        assert_eq!(xml_units.len(), 1);
+        assert!(openapi_units.len() > 1);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,158 @@
+use std::{
+    fs::OpenOptions,
+    path::{is_separator, Path, PathBuf},
+};
+
+/// Location, where the generated units are written to.
+///
+/// Units generated from xml schemas are written to a single file,
+/// those generated from OpenAPI specs to a directory.
+#[derive(Debug, PartialEq, Eq)]
+pub enum OutputTarget {
+    File(PathBuf),
+    Directory(PathBuf),
+}
+
+impl OutputTarget {
+    /// Target for a single unit.
+    ///
+    /// If `path` is an existing directory or ends with a path separator,
+    /// the unit is written to `{unit_name}.pas` inside of it.
+    pub fn unit_file(path: &Path, unit_name: Option<&str>) -> Result<Self, String> {
+        let path = resolve_output_path(path)?;
+
+        if !path.is_dir() && !ends_with_separator(&path) {
+            return Ok(Self::File(path));
+        }
+
+        match unit_name {
+            Some(unit_name) => Ok(Self::File(path.join(format!("{unit_name}.pas")))),
+            None => Err(format!(
+                "Output path {path:?} is a directory, a unit name is required to name the unit file"
+            )),
+        }
+    }
+
+    /// Target for several units
+    pub fn directory(path: &Path) -> Result<Self, String> {
+        let path = resolve_output_path(path)?;
+
+        if path.is_file() {
+            return Err(format!(
+                "Output path {path:?} is a file, OpenAPI specs are generated into a directory"
+            ));
+        }
+
+        Ok(Self::Directory(path))
+    }
+
+    pub fn path(&self) -> &Path {
+        match self {
+            Self::File(path) | Self::Directory(path) => path,
+        }
+    }
+
+    /// Path of the manifest of the generated types, which is written next to the units
+    pub fn manifest_path(&self) -> PathBuf {
+        match self {
+            Self::File(path) => path.with_extension("genphi.json"),
+            Self::Directory(path) => path.join("genphi.json"),
+        }
+    }
+
+    /// Path of the metadata of the generation, which is written next to the unit
+    pub fn metadata_path(&self) -> PathBuf {
+        match self {
+            Self::File(path) => path.with_extension("genphi-metadata.json"),
+            Self::Directory(path) => path.join("genphi-metadata.json"),
+        }
+    }
+
+    /// Creates missing directories and checks, that the units can be written,
+    /// so an output, which is not writable, is reported before the input is parsed.
+    pub fn prepare(&self) -> Result<(), String> {
+        let directory = match self {
+            Self::File(path) => path.parent().unwrap_or_else(|| Path::new("")),
+            Self::Directory(path) => path,
+        };
+
+        std::fs::create_dir_all(directory).map_err(|e| {
+            format!(
+                "Could not create output directory {directory:?} due to following error: \"{e:?}\""
+            )
+        })?;
+
+        match self {
+            Self::File(path) if path.is_dir() => {
+                Err(format!("Output path {path:?} is a directory"))
+            }
+            // Opening in append mode keeps the content of an existing unit until it is generated
+            Self::File(path) if path.exists() => OpenOptions::new()
+                .append(true)
+                .open(path)
+                .map(|_| ())
+                .map_err(|e| {
+                    format!(
+                        "Output file {path:?} is not writable due to following error: \"{e:?}\""
+                    )
+                }),
+            _ => {
+                let probe = directory.join(".genphi-write-check");
+
+                std::fs::write(&probe, []).map_err(|e| {
+                    format!(
+                        "Output directory {directory:?} is not writable due to following error: \"{e:?}\""
+                    )
+                })?;
+
+                // A leftover probe file does not affect the generation
+                let _ = std::fs::remove_file(probe);
+
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Resolves relative paths against the current directory
+pub fn resolve_output_path(path: &Path) -> Result<PathBuf, String> {
+    if path.is_relative() {
+        std::env::current_dir()
+            .map(|d| d.join(path))
+            .map_err(|e| format!("Relative path not supported due to following error: \"{e:?}\""))
+    } else {
+        std::path::absolute(path)
+            .map_err(|e| format!("Could not resolve output path due to following error: \"{e:?}\""))
+    }
+}
+
+fn ends_with_separator(path: &Path) -> bool {
+    path.as_os_str().to_string_lossy().ends_with(is_separator)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_directories_are_created() {
+        let root = std::env::temp_dir().join(format!("genphi-output-{}", std::process::id()));
+        let directory = root.join("units").join("");
+
+        let target = OutputTarget::unit_file(&directory, Some("Orders")).unwrap();
+        assert_eq!(
+            target,
+            OutputTarget::File(root.join("units").join("Orders.pas"))
+        );
+        assert!(OutputTarget::unit_file(&directory, None).is_err());
+
+        target.prepare().unwrap();
+        assert!(root.join("units").is_dir());
+        assert!(OutputTarget::directory(&root.join("units")).is_ok());
+
+        std::fs::write(root.join("units").join("Orders.pas"), "").unwrap();
+        assert!(OutputTarget::directory(&root.join("units").join("Orders.pas")).is_err());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,182 @@
+use std::path::{Path, PathBuf};
+
+/// Creates a runtime package, which contains the given units and requires the given packages.
+///
+/// If the package already exists, only its `requires` and `contains` clauses are
+/// extended by the missing entries, everything else is kept as it is.
+pub fn write_package(path: &Path, units: &[PathBuf], requires: &[&str]) -> Result<(), String> {
+    let package_dir = path.parent().unwrap_or_else(|| Path::new(""));
+    let contains = units
+        .iter()
+        .map(|unit| contains_entry(package_dir, unit))
+        .collect::<Vec<_>>();
+    let requires = requires.iter().map(|r| (*r).to_owned()).collect::<Vec<_>>();
+
+    let package = if path.exists() {
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            format!("Could not read package {path:?} due to following error: \"{e:?}\"")
+        })?;
+
+        let content = merge_clause(&content, "requires", &requires)?;
+        merge_clause(&content, "contains", &contains)?
+    } else {
+        let name = path
+            .file_stem()
+            .map(|n| n.to_string_lossy().into_owned())
+            .ok_or_else(|| format!("Package path {path:?} has no file name"))?;
+
+        new_package(&name, &requires, &contains)
+    };
+
+    std::fs::write(path, package)
+        .map_err(|e| format!("Could not write package {path:?} due to following error: \"{e:?}\""))
+}
+
+/// Returns `uName in 'path'` with the path relative to the package, if the unit is below it.
+fn contains_entry(package_dir: &Path, unit: &Path) -> String {
+    let name = unit
+        .file_stem()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let unit_path = unit.strip_prefix(package_dir).unwrap_or(unit);
+
+    format!(
+        "{name} in '{}'",
+        unit_path.to_string_lossy().replace('/', "\\")
+    )
+}
+
+fn new_package(name: &str, requires: &[String], contains: &[String]) -> String {
+    format!(
+        "package {name};\n\n{{$RUNONLY}}\n{{$IMPLICITBUILD ON}}\n\nrequires\n{};\n\ncontains\n{};\n\nend.\n",
+        format_entries(requires),
+        format_entries(contains),
+    )
+}
+
+fn format_entries(entries: &[String]) -> String {
+    entries
+        .iter()
+        .map(|e| format!("  {e}"))
+        .collect::<Vec<_>>()
+        .join(",\n")
+}
+
+/// Appends the entries, whose unit or package is missing, to the clause.
+/// A missing clause is inserted before the final `end.`.
+fn merge_clause(content: &str, clause: &str, entries: &[String]) -> Result<String, String> {
+    let Some(start) = find_clause(content, clause) else {
+        let end = content
+            .to_lowercase()
+            .rfind("end.")
+            .ok_or_else(|| String::from("Package has no final \"end.\""))?;
+
+        return Ok(format!(
+            "{}{clause}\n{};\n\n{}",
+            &content[..end],
+            format_entries(entries),
+            &content[end..]
+        ));
+    };
+
+    let body_start = start + clause.len();
+    let body_end = content[body_start..]
+        .find(';')
+        .map(|i| body_start + i)
+        .ok_or_else(|| format!("The {clause} clause of the package is not terminated"))?;
+
+    let mut existing = content[body_start..body_end]
+        .split(',')
+        .map(str::trim)
+        .filter(|e| !e.is_empty())
+        .map(str::to_owned)
+        .collect::<Vec<_>>();
+
+    for entry in entries {
+        let name = entry_name(entry);
+
+        if !existing
+            .iter()
+            .any(|e| entry_name(e).eq_ignore_ascii_case(name))
+        {
+            existing.push(entry.clone());
+        }
+    }
+
+    Ok(format!(
+        "{}\n{}{}",
+        &content[..body_start],
+        format_entries(&existing),
+        &content[body_end..]
+    ))
+}
+
+/// Finds the keyword of a clause at the start of a line.
+fn find_clause(content: &str, clause: &str) -> Option<usize> {
+    let mut offset = 0;
+
+    for line in content.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+
+        if trimmed
+            .get(..clause.len())
+            .is_some_and(|k| k.eq_ignore_ascii_case(clause))
+            && trimmed[clause.len()..]
+                .chars()
+                .next()
+                .is_none_or(char::is_whitespace)
+        {
+            return Some(offset + line.len() - trimmed.len());
+        }
+
+        offset += line.len();
+    }
+
+    None
+}
+
+fn entry_name(entry: &str) -> &str {
+    entry.split_whitespace().next().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_entries_are_appended_to_existing_package() {
+        let content = "package Api;\n\n{$RUNONLY}\n\nrequires\n  rtl;\n\ncontains\n  uOther in 'uOther.pas',\n  uPetApiModels in 'old\\uPetApiModels.pas';\n\nend.\n";
+
+        let content = merge_clause(
+            content,
+            "requires",
+            &[String::from("rtl"), String::from("RESTComponents")],
+        )
+        .unwrap();
+        let content = merge_clause(
+            &content,
+            "contains",
+            &[
+                String::from("uPetApiModels in 'uPetApiModels.pas'"),
+                String::from("uPetApiClient in 'uPetApiClient.pas'"),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(
+            content,
+            "package Api;\n\n{$RUNONLY}\n\nrequires\n  rtl,\n  RESTComponents;\n\ncontains\n  uOther in 'uOther.pas',\n  uPetApiModels in 'old\\uPetApiModels.pas',\n  uPetApiClient in 'uPetApiClient.pas';\n\nend.\n"
+        );
+    }
+
+    #[test]
+    fn new_package_contains_units_relative_to_package() {
+        let entry = contains_entry(Path::new("/out"), Path::new("/out/api/uPetApiModels.pas"));
+
+        assert_eq!(entry, "uPetApiModels in 'api\\uPetApiModels.pas'");
+        assert_eq!(
+            new_package("Api", &[String::from("rtl")], &[entry]),
+            "package Api;\n\n{$RUNONLY}\n{$IMPLICITBUILD ON}\n\nrequires\n  rtl;\n\ncontains\n  uPetApiModels in 'api\\uPetApiModels.pas';\n\nend.\n"
+        );
+    }
+}
@@ -0,0 +1,278 @@
+use std::{
+    io::{BufRead, Write},
+    time::Instant,
+};
+
+use clap::Parser;
+use serde_json::{json, Value};
+
+use xsd_delphi_codegen_core::openapi::{self, spec_manifest};
+use xsd_delphi_codegen_core::xml::{enable_schema_cache, schema_cache_stats, xml_manifest};
+
+use crate::{
+    build_code_gen_options, build_type_registry, error::CliError, generate, Args, SourceFormat,
+};
+
+/// Request is not valid JSON
+const PARSE_ERROR: i64 = -32700;
+/// Request is not a JSON-RPC request
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+/// Arguments of `generate` or `validate` are invalid
+const INVALID_PARAMS: i64 = -32602;
+/// Generation or validation failed, the `kind` and `exit_code` of the error are passed as `data`
+const GENERATION_FAILED: i64 = -32000;
+
+/// Answers the JSON-RPC 2.0 requests of `input` with one response per line in `output`,
+/// until `input` ends or `shutdown` is requested. Notifications, i.e. requests without
+/// `id`, are executed without response.
+///
+/// Methods:
+/// * `generate` - Generates the units for the cli arguments `params.args` and returns their paths
+/// * `validate` - Parses the input for the cli arguments `params.args` and returns the manifest
+///   of the types, which would be generated, without writing any files
+/// * `stats` - Returns the number of handled requests, the uptime and the schema cache statistics
+/// * `shutdown` - Stops the server
+pub(crate) fn serve(input: impl BufRead, mut output: impl Write) -> Result<(), CliError> {
+    enable_schema_cache();
+
+    let mut server = Server {
+        started: Instant::now(),
+        requests: 0,
+    };
+
+    for line in input.lines() {
+        let line = line.map_err(|e| {
+            CliError::Output(format!(
+                "Could not read request due to following error: \"{e:?}\""
+            ))
+        })?;
+
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let (response, shutdown) = server.handle(&line);
+
+        if let Some(response) = response {
+            writeln!(output, "{response}")
+                .and_then(|()| output.flush())
+                .map_err(|e| {
+                    CliError::Output(format!(
+                        "Could not write response due to following error: \"{e:?}\""
+                    ))
+                })?;
+        }
+
+        if shutdown {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+struct Server {
+    started: Instant,
+    /// Number of handled requests, including invalid ones
+    requests: u64,
+}
+
+struct RpcError {
+    code: i64,
+    message: String,
+    data: Option<Value>,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            data: None,
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        match &self.data {
+            Some(data) => json!({ "code": self.code, "message": self.message, "data": data }),
+            None => json!({ "code": self.code, "message": self.message }),
+        }
+    }
+}
+
+impl From<CliError> for RpcError {
+    fn from(value: CliError) -> Self {
+        Self {
+            code: GENERATION_FAILED,
+            message: value.to_string(),
+            data: Some(json!({ "kind": value.kind(), "exit_code": value.exit_code() })),
+        }
+    }
+}
+
+impl Server {
+    /// Returns the response, if the request has an `id`, and whether the server should stop
+    fn handle(&mut self, line: &str) -> (Option<Value>, bool) {
+        self.requests += 1;
+
+        let request = match serde_json::from_str::<Value>(line) {
+            Ok(request) => request,
+            Err(e) => {
+                let error = RpcError::new(PARSE_ERROR, e.to_string());
+
+                return (Some(response(Value::Null, Err(error))), false);
+            }
+        };
+
+        let Some(method) = request.get("method").and_then(Value::as_str) else {
+            let id = request.get("id").cloned().unwrap_or(Value::Null);
+            let error = RpcError::new(INVALID_REQUEST, "Request has no method");
+
+            return (Some(response(id, Err(error))), false);
+        };
+
+        let result = match method {
+            "generate" => parse_args(&request).and_then(|args| {
+                let units = generate(&args)?;
+
+                Ok(json!({ "units": units }))
+            }),
+            "validate" => parse_args(&request).and_then(|args| validate(&args)),
+            "stats" => Ok(self.stats()),
+            "shutdown" => Ok(Value::Null),
+            _ => Err(RpcError::new(
+                METHOD_NOT_FOUND,
+                format!("Method \"{method}\" is not supported"),
+            )),
+        };
+
+        (
+            request.get("id").cloned().map(|id| response(id, result)),
+            method == "shutdown",
+        )
+    }
+
+    fn stats(&self) -> Value {
+        let schema_cache = schema_cache_stats();
+
+        json!({
+            "requests": self.requests,
+            "uptime_ms": self.started.elapsed().as_millis(),
+            "schema_cache": {
+                "entries": schema_cache.entries,
+                "hits": schema_cache.hits,
+                "misses": schema_cache.misses,
+            },
+        })
+    }
+}
+
+fn response(id: Value, result: Result<Value, RpcError>) -> Value {
+    match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(error) => json!({ "jsonrpc": "2.0", "id": id, "error": error.to_json() }),
+    }
+}
+
+/// Parses `params.args` like the arguments of the cli
+fn parse_args(request: &Value) -> Result<Args, RpcError> {
+    let Some(args) = request
+        .pointer("/params/args")
+        .and_then(Value::as_array)
+        .and_then(|args| args.iter().map(Value::as_str).collect::<Option<Vec<_>>>())
+    else {
+        return Err(RpcError::new(
+            INVALID_PARAMS,
+            "Params require `args`, an array of the cli arguments",
+        ));
+    };
+
+    let args = Args::try_parse_from(std::iter::once("genphi").chain(args))
+        .map_err(|e| RpcError::new(INVALID_PARAMS, e.to_string()))?;
+
+    if args.command.is_some() {
+        return Err(RpcError::new(
+            INVALID_PARAMS,
+            "Subcommands can't be run by the server",
+        ));
+    }
+
+    Ok(args)
+}
+
+fn validate(args: &Args) -> Result<Value, RpcError> {
+    let manifest = match args.source_format {
+        Some(SourceFormat::Xml) => xml_manifest(
+            &args.input,
+            &build_code_gen_options(args, None)?,
+            build_type_registry(args)?,
+        )
+        .map_err(CliError::from)?,
+        Some(SourceFormat::OpenApi) => {
+            let Some(source) = args.input.first() else {
+                return Err(CliError::from(openapi::GenerationError::MissingSource).into());
+            };
+
+            spec_manifest(source, &args.type_prefix).map_err(CliError::from)?
+        }
+        None => {
+            return Err(RpcError::new(INVALID_PARAMS, "Source format is required"));
+        }
+    };
+
+    Ok(serde_json::from_str(&manifest).unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn responses(requests: &str) -> Vec<Value> {
+        let mut output = Vec::new();
+        serve(requests.as_bytes(), &mut output).unwrap();
+
+        String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn requests_are_answered_line_by_line() {
+        let responses = responses(
+            r#"{"jsonrpc":"2.0","id":1,"method":"stats"}
+{"jsonrpc":"2.0","method":"stats"}
+no json
+{"jsonrpc":"2.0","id":2,"method":"compile"}
+{"jsonrpc":"2.0","id":3,"method":"generate","params":{"args":["--unknown"]}}
+{"jsonrpc":"2.0","id":4,"method":"validate","params":{"args":["-i","missing.xsd","-o","out","--source-format","xml","--unit-name","Missing"]}}
+{"jsonrpc":"2.0","id":5,"method":"shutdown"}
+{"jsonrpc":"2.0","id":6,"method":"stats"}
+"#,
+        );
+
+        let codes = responses
+            .iter()
+            .map(|r| (r["id"].clone(), r.pointer("/error/code").cloned()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            codes,
+            vec![
+                (json!(1), None),
+                (Value::Null, Some(json!(PARSE_ERROR))),
+                (json!(2), Some(json!(METHOD_NOT_FOUND))),
+                (json!(3), Some(json!(INVALID_PARAMS))),
+                (json!(4), Some(json!(GENERATION_FAILED))),
+                (json!(5), None),
+            ]
+        );
+        assert_eq!(responses[0]["result"]["requests"], json!(1));
+        assert_eq!(
+            responses[4]["error"]["data"]["kind"],
+            json!("invalid_input")
+        );
+    }
+}
@@ -0,0 +1,126 @@
+//! Runs `genphi` and checks the exit code and the error printed to stderr for every kind of
+//! error, as CI pipelines and tools rely on both.
+
+use std::{path::PathBuf, process::Command};
+
+use serde_json::{json, Value};
+
+fn fixture(path: &str) -> String {
+    format!("{}/../{path}", env!("CARGO_MANIFEST_DIR"))
+}
+
+/// Empty output directory of a test
+fn output_directory(name: &str) -> PathBuf {
+    let directory = std::env::temp_dir().join(format!("genphi-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&directory);
+    std::fs::create_dir_all(&directory).unwrap();
+
+    directory
+}
+
+fn path(path: PathBuf) -> String {
+    path.to_string_lossy().into_owned()
+}
+
+/// Runs `genphi` with the arguments and returns the exit code and stderr
+fn genphi(args: &[String]) -> (i32, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_genphi"))
+        .args(args)
+        .output()
+        .unwrap();
+
+    (
+        output.status.code().unwrap(),
+        String::from_utf8(output.stderr).unwrap(),
+    )
+}
+
+/// Arguments generating the unit `uOrders` from the xml schema `input`
+fn xml_args(input: &str, unit: &str) -> Vec<String> {
+    ["-i", input, "-o", unit, "--unit-name", "uOrders"]
+        .into_iter()
+        .chain(["--source-format", "xml"])
+        .map(String::from)
+        .collect()
+}
+
+#[test]
+fn successful_generation_exits_with_zero() {
+    let output = output_directory("success");
+
+    let (code, stderr) = genphi(&xml_args(
+        &fixture("xml/tests/fixtures/sequence.xsd"),
+        &path(output.join("uOrders.pas")),
+    ));
+
+    assert_eq!((code, stderr.as_str()), (0, ""));
+
+    std::fs::remove_dir_all(output).unwrap();
+}
+
+#[test]
+fn every_kind_of_error_exits_with_its_code_and_is_printed_as_json() {
+    let output = output_directory("errors");
+    let schema = fixture("xml/tests/fixtures/sequence.xsd");
+    let broken_schema = path(output.join("broken.xsd"));
+    let unit = path(output.join("uOrders.pas"));
+
+    std::fs::write(&broken_schema, "<xs:schema").unwrap();
+    // A file, where the directory of the unit is expected
+    std::fs::write(output.join("file"), "").unwrap();
+
+    let without_unit_name = xml_args(&schema, &unit)
+        .into_iter()
+        .filter(|a| a != "--unit-name" && a != "uOrders")
+        .collect();
+    let mut unknown_root = xml_args(&schema, &unit);
+    unknown_root.extend([String::from("--roots"), String::from("missing")]);
+
+    let cases = [
+        (without_unit_name, 2, "invalid_arguments"),
+        (xml_args(&broken_schema, &unit), 3, "invalid_input"),
+        (unknown_root, 4, "generation_failed"),
+        (
+            xml_args(&schema, &path(output.join("file").join("uOrders.pas"))),
+            5,
+            "io_failed",
+        ),
+    ];
+
+    for (mut args, expected_code, expected_kind) in cases {
+        args.extend([String::from("--error-format"), String::from("json")]);
+
+        let (code, stderr) = genphi(&args);
+        let error = serde_json::from_str::<Value>(stderr.trim_end()).unwrap();
+
+        assert_eq!(stderr.lines().count(), 1, "{stderr}");
+        assert_eq!(code, expected_code, "{stderr}");
+        assert_eq!(error["kind"], json!(expected_kind));
+        assert_eq!(error["exit_code"], json!(expected_code));
+        assert!(error["message"].as_str().is_some_and(|m| !m.is_empty()));
+    }
+
+    std::fs::remove_dir_all(output).unwrap();
+}
+
+#[test]
+fn errors_are_printed_as_text_by_default() {
+    let output = output_directory("text");
+    let spec = path(output.join("broken.yaml"));
+
+    std::fs::write(&spec, "openapi: [").unwrap();
+
+    let (code, stderr) = genphi(&[
+        String::from("-i"),
+        spec,
+        String::from("-o"),
+        path(output.clone()),
+        String::from("--source-format"),
+        String::from("open-api"),
+    ]);
+
+    assert_eq!(code, 3);
+    assert!(stderr.starts_with("Failed to parse OpenAPI Spec file at"));
+
+    std::fs::remove_dir_all(output).unwrap();
+}
@@ -0,0 +1,205 @@
+//! Checks the syntax of generated units with Free Pascal.
+//!
+//! Run with `cargo test -p cli --features fpc-check`. The check is skipped, if `fpc` is not
+//! on the `PATH`. Free Pascal neither knows the Delphi RTL units nor inline variables, so the
+//! uses clauses are removed and Delphi only syntax is rewritten before the units are compiled.
+//! Only syntax errors fail the check, unknown identifiers and types are expected.
+#![cfg(feature = "fpc-check")]
+
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+const SCHEMA: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:simpleType name="Status">
+    <xs:restriction base="xs:string">
+      <xs:enumeration value="open"/>
+      <xs:enumeration value="closed"/>
+    </xs:restriction>
+  </xs:simpleType>
+  <xs:complexType name="Line">
+    <xs:sequence>
+      <xs:element name="sku" type="xs:string"/>
+      <xs:element name="quantity" type="xs:int"/>
+    </xs:sequence>
+    <xs:attribute name="note" type="xs:string"/>
+  </xs:complexType>
+  <xs:element name="order">
+    <xs:complexType>
+      <xs:sequence>
+        <xs:element name="status" type="Status"/>
+        <xs:element name="created" type="xs:dateTime"/>
+        <xs:element name="line" type="Line" maxOccurs="unbounded"/>
+      </xs:sequence>
+    </xs:complexType>
+  </xs:element>
+</xs:schema>
+"#;
+
+const SPEC: &str = r##"
+openapi: 3.0.0
+info:
+  title: Pet Store
+  version: "1.0"
+paths:
+  /pets/{petId}:
+    get:
+      operationId: showPet
+      tags: [pets]
+      parameters:
+        - name: petId
+          in: path
+          required: true
+          schema:
+            type: integer
+      responses:
+        "200":
+          description: ok
+          content:
+            application/json:
+              schema:
+                $ref: "#/components/schemas/Pet"
+components:
+  schemas:
+    Pet:
+      type: object
+      required: [name]
+      properties:
+        name:
+          type: string
+          maxLength: 20
+        kind:
+          type: string
+          enum: [cat, dog]
+"##;
+
+#[test]
+fn generated_units_pass_the_syntax_check() {
+    if Command::new("fpc").arg("-iV").output().is_err() {
+        eprintln!("fpc not found, skipping syntax check of generated units");
+        return;
+    }
+
+    let dir = std::env::temp_dir().join(format!("genphi-fpc-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("order.xsd"), SCHEMA).unwrap();
+    std::fs::write(dir.join("pets.yaml"), SPEC).unwrap();
+
+    genphi(
+        &[
+            "-i",
+            "order.xsd",
+            "-o",
+            "xml/",
+            "--unit-name",
+            "uOrder",
+            "--source-format",
+            "xml",
+        ],
+        &dir,
+    );
+    genphi(
+        &[
+            "-i",
+            "pets.yaml",
+            "-o",
+            "openapi",
+            "--source-format",
+            "open-api",
+            "--emit-support-units",
+        ],
+        &dir,
+    );
+
+    let mut errors = vec![];
+
+    for unit in units(&dir.join("xml")).chain(units(&dir.join("openapi"))) {
+        errors.extend(syntax_errors(&unit, &dir));
+    }
+
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert!(errors.is_empty(), "{}", errors.join("\n"));
+}
+
+fn genphi(args: &[&str], dir: &Path) {
+    let status = Command::new(env!("CARGO_BIN_EXE_genphi"))
+        .args(args)
+        .current_dir(dir)
+        .status()
+        .unwrap();
+
+    assert!(status.success(), "genphi {args:?} failed");
+}
+
+fn units(dir: &Path) -> impl Iterator<Item = PathBuf> {
+    std::fs::read_dir(dir)
+        .unwrap()
+        .filter_map(Result::ok)
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|e| e == "pas"))
+}
+
+/// Compiles the rewritten unit and returns the syntax errors reported by fpc
+fn syntax_errors(unit: &Path, dir: &Path) -> Vec<String> {
+    let content = std::fs::read_to_string(unit).unwrap();
+    let checked = dir.join("check").join(unit.file_name().unwrap());
+    std::fs::create_dir_all(checked.parent().unwrap()).unwrap();
+    std::fs::write(&checked, rewrite_for_fpc(&content)).unwrap();
+
+    let output = Command::new("fpc")
+        .arg("-Mdelphi")
+        .arg("-s")
+        .arg("-Se1000")
+        .arg(format!("-FU{}", dir.join("check").display()))
+        .arg(&checked)
+        .output()
+        .unwrap();
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|l| l.contains("Syntax error") || l.contains("Illegal expression"))
+        .map(|l| format!("{}: {l}", unit.display()))
+        .collect()
+}
+
+/// Removes the uses clauses and rewrites Delphi syntax, which fpc does not support
+fn rewrite_for_fpc(content: &str) -> String {
+    let mut rewritten = String::new();
+    let mut in_uses = false;
+
+    for line in content.lines() {
+        let trimmed = line.trim_start();
+
+        if trimmed.starts_with("uses ") || trimmed == "uses" {
+            in_uses = true;
+        }
+
+        if in_uses {
+            in_uses = !line.contains(';');
+            rewritten.push('\n');
+            continue;
+        }
+
+        let indent = &line[..line.len() - trimmed.len()];
+        let line = match trimmed.strip_prefix("var ") {
+            // Inline variable, `var vX := ...;` or `var vX: T := ...;`
+            Some(declaration) if declaration.contains(":=") => {
+                let (name, value) = declaration.split_once(":=").unwrap();
+                let name = name.split(':').next().unwrap().trim();
+
+                format!("{indent}{name} :={value}")
+            }
+            _ => line
+                .replace("for var ", "for ")
+                .replace("reference to ", ""),
+        };
+
+        rewritten.push_str(&line);
+        rewritten.push('\n');
+    }
+
+    rewritten
+}
@@ -0,0 +1,20 @@
+//! Library of `genphi`, which generates Delphi units from XML schemas and OpenAPI
+//! specifications, for tools, which want to generate units without calling the binary.
+//!
+//! The subsystems are gated by cargo features, so a tool only depends on the parts it uses:
+//!
+//! - [`xml`]: XML schemas, always available
+//! - `openapi`: OpenAPI specifications, enabled by default with the feature `openapi`
+//!
+//! ```toml
+//! [dependencies]
+//! xsd-delphi-codegen-core = { version = "0.1", default-features = false }
+//! ```
+//!
+//! The crate follows semantic versioning. The public items of the re-exported modules are its
+//! API, breaking changes of them are released with a new major version, or a new minor version
+//! before 1.0.
+
+#[cfg(feature = "openapi")]
+pub use openapi;
+pub use xml;
@@ -0,0 +1,109 @@
+//! Generates units through the re-exported modules, as downstream tools do, and checks that
+//! the subsystems behind the cargo features only pull in their dependencies when enabled.
+
+use std::{path::PathBuf, process::Command};
+
+use xsd_delphi_codegen_core::xml::{self, generator::code_generator_trait::CodeGenOptions};
+
+fn fixture(path: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("..")
+        .join(path)
+}
+
+/// Empty output directory of a test
+fn output_directory(name: &str) -> PathBuf {
+    let directory = std::env::temp_dir().join(format!("core-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&directory);
+    std::fs::create_dir_all(&directory).unwrap();
+
+    directory
+}
+
+/// Names of the packages, which the core crate depends on with the given cargo arguments
+fn dependencies(args: &[&str]) -> String {
+    let cargo = std::env::var("CARGO").unwrap_or_else(|_| String::from("cargo"));
+    let output = Command::new(cargo)
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .args(["tree", "--offline", "-e", "normal", "--prefix", "none"])
+        .args(["-p", env!("CARGO_PKG_NAME")])
+        .args(args)
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{:?}", output);
+
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn xml_units_are_generated_through_the_reexported_module() {
+    let output = output_directory("xml");
+    let unit = output.join("uOrders.pas");
+
+    let written = xml::generate_xml(
+        &[fixture("xml/tests/fixtures/sequence.xsd")],
+        &unit,
+        CodeGenOptions::builder("uOrders").build(),
+        &None,
+        &None,
+        &None,
+    )
+    .unwrap();
+
+    assert_eq!(written, [unit.as_path()]);
+    assert!(std::fs::read_to_string(&unit)
+        .unwrap()
+        .lines()
+        .any(|l| l == "unit uOrders;"));
+
+    std::fs::remove_dir_all(output).unwrap();
+}
+
+#[cfg(feature = "openapi")]
+#[test]
+fn openapi_clients_are_generated_through_the_reexported_module() {
+    use xsd_delphi_codegen_core::openapi;
+
+    let output = output_directory("openapi");
+    let options = openapi::ClientOptions {
+        prefix: Some(String::from("Pet")),
+        ..openapi::ClientOptions::default()
+    };
+
+    let written = openapi::generate_openapi_client(
+        &[fixture("openapi/tests/fixtures/petstore.yaml")],
+        &output,
+        &options,
+        &None,
+        &None,
+    )
+    .unwrap();
+
+    assert!(!written.is_empty());
+    assert!(written.iter().all(|p| p.starts_with(&output) && p.exists()));
+
+    std::fs::remove_dir_all(output).unwrap();
+}
+
+#[test]
+fn openapi_dependencies_are_only_pulled_in_by_the_feature() {
+    let default = dependencies(&[]);
+    let without_openapi = dependencies(&["--no-default-features"]);
+
+    for package in ["xsd-delphi-codegen-openapi ", "sw4rm-rs ", "serde_yaml "] {
+        assert!(default.lines().any(|l| l.starts_with(package)), "{package}");
+        assert!(
+            !without_openapi.lines().any(|l| l.starts_with(package)),
+            "{package}"
+        );
+    }
+
+    // The spec browser belongs to the cli, the library never depends on a terminal UI
+    for package in ["ratatui ", "crossterm ", "ureq "] {
+        assert!(
+            !default.lines().any(|l| l.starts_with(package)),
+            "{package}"
+        );
+    }
+}
@@ -1,116 +1,231 @@
+use std::collections::BTreeMap;
+
+use pascal::as_pascal_literal;
 use sw4rm_rs::{
-    shared::{Operation, ParameterLocation, ParameterSchemaType, StringOrHttpCode},
-    Spec,
+    shared::{
+        Operation, Parameter, ParameterLocation, ParameterSchemaType, PathItem, Response,
+        SchemaType, StringOrHttpCode,
+    },
+    RefOr, Reference, Spec,
 };
 use tera::Value;
 
 use crate::{
-    helper::{self, capitalize},
-    models::{ClassType, Endpoint, EndpointArg, EnumType, Response as ResponseModel, Type},
+    helper::{self, capitalize, deprecation_comment, resolve, sorted_entries},
+    models::{
+        AllowedValue, BodyEncoding, ClassType, ClientGroup, Endpoint, EndpointArg, EnumType,
+        PathSegment, Response as ResponseModel, Type,
+    },
     schema_collector,
 };
 
+/// Collects the endpoints of all paths of the spec.
+///
+/// Fails if a placeholder of a path is not defined as path parameter of its operation,
+/// a reference can't be resolved or a parameter is passed in an unsupported location.
 pub(crate) fn collect_endpoints(
     spec: &Spec,
     class_types: &mut Vec<ClassType>,
     enum_types: &mut Vec<EnumType>,
-) -> Vec<Endpoint> {
+) -> Result<Vec<Endpoint>, String> {
     let mut endpoints = vec![];
 
-    for (k, v) in &spec.paths {
-        let Ok(v) = v.resolve(spec) else {
-            continue;
-        };
-
-        if let Some(o) = v.get {
-            let name = get_endpoint_name(&o, k, "Get");
-            let response_type =
-                get_endpoint_response_type(&o, spec, &name, class_types, enum_types);
-            let status_codes = get_endpoint_responses(&o, spec, &name, class_types, enum_types);
-            let request_body = get_endpoint_request_body(&o, spec, &name, class_types, enum_types)
-                .unwrap_or_default();
-
-            let endpoint = Endpoint {
-                name,
-                response_type,
-                status_codes,
-                args: get_endpoint_args(&o, spec),
-                method: "GET".to_string(),
-                path: k.to_string(),
-                request_body,
-            };
+    for (k, v) in sorted_entries(&spec.paths) {
+        let v = resolve(v, spec, &format!("Path \"{k}\""))?;
 
+        for mut endpoint in collect_path_item_endpoints(v, k, spec, class_types, enum_types)? {
+            endpoint.path_segments = build_path_segments(&endpoint)?;
             endpoints.push(endpoint);
         }
+    }
 
-        if let Some(o) = v.post {
-            let name = get_endpoint_name(&o, k, "Post");
-            let response_type =
-                get_endpoint_response_type(&o, spec, &name, class_types, enum_types);
-            let status_codes = get_endpoint_responses(&o, spec, &name, class_types, enum_types);
-            let request_body = get_endpoint_request_body(&o, spec, &name, class_types, enum_types)
-                .unwrap_or_default();
-
-            let endpoint = Endpoint {
-                name,
-                response_type,
-                status_codes,
-                args: get_endpoint_args(&o, spec),
-                method: "POST".to_string(),
-                path: k.to_string(),
-                request_body,
-            };
+    Ok(endpoints)
+}
 
-            endpoints.push(endpoint);
-        }
+/// Groups the endpoints by their first tag, endpoints without tag end up in the `Default` group.
+/// The groups are sorted by name and keep the order of their endpoints.
+pub(crate) fn group_by_tag(endpoints: Vec<Endpoint>) -> Vec<ClientGroup> {
+    let mut groups = BTreeMap::<String, Vec<Endpoint>>::new();
 
-        if let Some(o) = v.put {
-            let name = get_endpoint_name(&o, k, "Put");
-            let response_type =
-                get_endpoint_response_type(&o, spec, &name, class_types, enum_types);
-            let status_codes = get_endpoint_responses(&o, spec, &name, class_types, enum_types);
-            let request_body = get_endpoint_request_body(&o, spec, &name, class_types, enum_types)
-                .unwrap_or_default();
-
-            let endpoint = Endpoint {
-                name,
-                response_type,
-                status_codes,
-                args: get_endpoint_args(&o, spec),
-                method: "PUT".to_string(),
-                path: k.to_string(),
-                request_body,
-            };
+    for endpoint in endpoints {
+        groups
+            .entry(group_name(&endpoint))
+            .or_default()
+            .push(endpoint);
+    }
 
-            endpoints.push(endpoint);
-        }
+    groups
+        .into_iter()
+        .map(|(name, endpoints)| ClientGroup { name, endpoints })
+        .collect()
+}
 
-        if let Some(o) = v.delete {
-            let name = get_endpoint_name(&o, k, "Delete");
-            let response_type =
-                get_endpoint_response_type(&o, spec, &name, class_types, enum_types);
-            let status_codes = get_endpoint_responses(&o, spec, &name, class_types, enum_types);
-            let request_body = get_endpoint_request_body(&o, spec, &name, class_types, enum_types)
-                .unwrap_or_default();
-
-            let endpoint = Endpoint {
-                name,
-                response_type,
-                status_codes,
-                args: get_endpoint_args(&o, spec),
-                method: "DELETE".to_string(),
-                path: k.to_string(),
-                request_body,
-            };
+/// Name of the group of the endpoint, its first tag in PascalCase or `Default`.
+pub(crate) fn group_name(endpoint: &Endpoint) -> String {
+    endpoint
+        .tag
+        .as_deref()
+        .map(sanitize_operation_id)
+        .filter(|n| !n.is_empty())
+        .unwrap_or_else(|| String::from("Default"))
+}
 
-            endpoints.push(endpoint);
+/// Splits the path of the endpoint into literal parts and `{placeholders}`
+/// and assigns the matching path parameter to each placeholder.
+fn build_path_segments(endpoint: &Endpoint) -> Result<Vec<PathSegment>, String> {
+    let mut segments = vec![];
+    let mut rest = endpoint.path.as_str();
+
+    while let Some(start) = rest.find('{') {
+        let Some(end) = rest[start..].find('}').map(|e| start + e) else {
+            return Err(format!(
+                "Path \"{}\" of {} contains an unclosed placeholder",
+                endpoint.path, endpoint.name
+            ));
+        };
+
+        if start > 0 {
+            segments.push(PathSegment {
+                literal: rest[..start].to_owned(),
+                arg: None,
+            });
         }
+
+        let placeholder = &rest[start + 1..end];
+        let Some(arg) = endpoint
+            .args
+            .iter()
+            .find(|a| a.arg_type == "path" && a.key == placeholder)
+        else {
+            return Err(format!(
+                "Placeholder {{{placeholder}}} in path \"{}\" of {} is not defined as path parameter",
+                endpoint.path, endpoint.name
+            ));
+        };
+
+        segments.push(PathSegment {
+            literal: String::new(),
+            arg: Some(arg.clone()),
+        });
+
+        rest = &rest[end + 1..];
+    }
+
+    if !rest.is_empty() {
+        segments.push(PathSegment {
+            literal: rest.to_owned(),
+            arg: None,
+        });
     }
 
-    endpoints
+    Ok(segments)
 }
 
-fn get_endpoint_name(operation: &Operation, path: &str, method: &str) -> String {
+/// Builds an endpoint for every supported operation of the given path item.
+pub(crate) fn collect_path_item_endpoints(
+    path_item: PathItem,
+    path: &str,
+    spec: &Spec,
+    class_types: &mut Vec<ClassType>,
+    enum_types: &mut Vec<EnumType>,
+) -> Result<Vec<Endpoint>, String> {
+    [
+        (path_item.get, "GET"),
+        (path_item.post, "POST"),
+        (path_item.put, "PUT"),
+        (path_item.delete, "DELETE"),
+    ]
+    .into_iter()
+    .filter_map(|(o, method)| {
+        o.map(|o| {
+            let o = inherit_path_parameters(o, &path_item.parameters, spec);
+
+            build_endpoint(&o, path, method, spec, class_types, enum_types)
+        })
+    })
+    .collect::<Result<Vec<Endpoint>, String>>()
+}
+
+/// Prepends the parameters of the path item, which the operation doesn't override.
+/// A parameter is overridden by one with the same name and location.
+fn inherit_path_parameters(
+    mut operation: Operation,
+    path_parameters: &[RefOr<Parameter>],
+    spec: &Spec,
+) -> Operation {
+    let key = |parameter: &RefOr<Parameter>| {
+        parameter
+            .resolve(spec)
+            .ok()
+            .map(|p| (p.name, p.location.unwrap_or_default()))
+    };
+
+    let own_keys = operation
+        .parameters
+        .iter()
+        .filter_map(key)
+        .collect::<Vec<_>>();
+
+    let mut parameters = path_parameters
+        .iter()
+        .filter(|p| key(p).is_none_or(|k| !own_keys.contains(&k)))
+        .cloned()
+        .collect::<Vec<_>>();
+    parameters.append(&mut operation.parameters);
+    operation.parameters = parameters;
+
+    operation
+}
+
+fn build_endpoint(
+    operation: &Operation,
+    path: &str,
+    method: &str,
+    spec: &Spec,
+    class_types: &mut Vec<ClassType>,
+    enum_types: &mut Vec<EnumType>,
+) -> Result<Endpoint, String> {
+    let name = get_endpoint_name(operation, path, &capitalize(&method.to_lowercase()));
+    let mut response_type =
+        get_endpoint_response_type(operation, spec, &name, class_types, enum_types)?;
+    let status_codes = get_endpoint_responses(operation, spec, &name, class_types, enum_types)?;
+    let is_binary_response = has_binary_response(operation, spec);
+    let has_result_wrapper = !is_binary_response && has_distinct_success_types(&status_codes);
+
+    if has_result_wrapper {
+        response_type = Type {
+            name: result_wrapper_name(&name, class_types, enum_types),
+            is_class: true,
+            is_enum: false,
+            is_set: false,
+        };
+    }
+    let (request_body, request_body_encoding) =
+        get_endpoint_request_body(operation, spec, &name, class_types, enum_types)?
+            .unwrap_or_default();
+    let args = get_endpoint_args(operation, spec, &name, class_types, enum_types)?;
+
+    Ok(Endpoint {
+        name,
+        operation_id: operation.operation_id.clone(),
+        response_type,
+        response_is_list: false,
+        envelope: None,
+        is_binary_response,
+        status_codes,
+        has_result_wrapper,
+        args,
+        method: method.to_string(),
+        path: path.to_string(),
+        request_body,
+        request_body_encoding,
+        path_segments: vec![],
+        tag: operation.tags.first().cloned(),
+        deprecation_comment: deprecation_comment(operation.deprecated, &operation.description),
+    })
+}
+
+pub(crate) fn get_endpoint_name(operation: &Operation, path: &str, method: &str) -> String {
     match operation.operation_id.as_ref() {
         Some(name) => {
             if name.contains('/') {
@@ -120,7 +235,7 @@ fn get_endpoint_name(operation: &Operation, path: &str, method: &str) -> String
                     capitalize(
                         path.trim_end_matches('/')
                             .split('/')
-                            .last()
+                            .next_back()
                             .unwrap()
                             .to_string()
                             .as_str()
@@ -136,7 +251,7 @@ fn get_endpoint_name(operation: &Operation, path: &str, method: &str) -> String
             capitalize(
                 path.trim_end_matches('/')
                     .split('/')
-                    .last()
+                    .next_back()
                     .unwrap()
                     .to_string()
                     .as_str()
@@ -151,94 +266,185 @@ fn get_endpoint_response_type(
     endpoint_name: &str,
     class_types: &mut Vec<ClassType>,
     enum_types: &mut Vec<EnumType>,
-) -> Type {
-    let (response_type, is_class, is_enum) = operation
-        .responses
-        .iter()
-        .find(|r| match r.0 {
-            StringOrHttpCode::String(s) => s.starts_with('2'),
-            StringOrHttpCode::StatusCode(c) => *c >= 200 && *c < 300,
-        })
-        .and_then(|r| r.1.resolve(spec).ok())
-        .and_then(|r| r.content.get("application/json").cloned())
-        .and_then(|m| m.schema)
-        .and_then(|s| s.resolve(spec).ok())
-        .and_then(|s| {
-            schema_collector::schema_to_type(
-                &s,
-                endpoint_name,
-                spec,
-                &None,
-                class_types,
-                enum_types,
-            )
-        })
-        .unwrap_or(("none".to_string(), false, false));
-
-    Type {
+) -> Result<Type, String> {
+    let context = format!("Response of operation \"{endpoint_name}\"");
+    let schema = match sorted_responses(operation)
+        .into_iter()
+        .find(|r| r.0.starts_with('2'))
+    {
+        Some((_, response)) => resolve(response, spec, &context)?
+            .content
+            .get("application/json")
+            .cloned()
+            .and_then(|m| m.schema),
+        None => None,
+    };
+    let type_info = match schema {
+        Some(s) => schema_collector::schema_to_type(
+            &resolve(&s, spec, &context)?,
+            endpoint_name,
+            spec,
+            &None,
+            class_types,
+            enum_types,
+        )?,
+        None => None,
+    };
+    let (response_type, is_class, is_enum) =
+        type_info.unwrap_or(("none".to_string(), false, false));
+
+    Ok(Type {
         name: response_type,
         is_class,
         is_enum,
+        is_set: false,
+    })
+}
+
+/// Whether the success responses have different schemas, e.g. `200` returns the resource and
+/// `202` the state of its processing, so a single response type can't represent them
+fn has_distinct_success_types(responses: &[ResponseModel]) -> bool {
+    let mut success_types = responses
+        .iter()
+        .filter(|r| r.status_code.starts_with('2'))
+        .map(|r| &r.type_);
+
+    success_types
+        .next()
+        .is_some_and(|first| success_types.any(|t| t != first))
+}
+
+/// Name of the result class of the operation, which doesn't collide with a model
+fn result_wrapper_name(
+    endpoint_name: &str,
+    class_types: &[ClassType],
+    enum_types: &[EnumType],
+) -> String {
+    let is_taken = |name: &str| {
+        class_types.iter().any(|c| c.name == name) || enum_types.iter().any(|e| e.name == name)
+    };
+
+    let name = format!("{endpoint_name}Result");
+
+    match is_taken(&name) {
+        true => (2..)
+            .map(|i| format!("{name}{i}"))
+            .find(|n| !is_taken(n))
+            .unwrap_or(name),
+        false => name,
     }
 }
 
+fn has_binary_response(operation: &Operation, spec: &Spec) -> bool {
+    sorted_responses(operation)
+        .into_iter()
+        .find(|r| r.0.starts_with('2'))
+        .and_then(|r| r.1.resolve(spec).ok())
+        .is_some_and(|r| {
+            !r.content.contains_key("application/json")
+                && r.content.keys().any(|k| helper::is_binary_content_type(k))
+        })
+}
+
 fn get_endpoint_responses(
     operation: &Operation,
     spec: &Spec,
     endpoint_name: &str,
     class_types: &mut Vec<ClassType>,
     enum_types: &mut Vec<EnumType>,
-) -> Vec<ResponseModel> {
+) -> Result<Vec<ResponseModel>, String> {
     let mut responses = vec![];
+    let mut first_success_schema = None;
+
+    for (status_code, v) in sorted_responses(operation) {
+        let context = format!("Response \"{status_code}\" of operation \"{endpoint_name}\"");
+        let v = resolve(v, spec, &context)?;
+        let schema = v
+            .content
+            .get("application/json")
+            .cloned()
+            .and_then(|m| m.schema);
+
+        // Further success responses with another schema need a type of their own, the first
+        // one is named after the operation like the response type
+        let type_name = match &schema {
+            Some(s) if status_code.starts_with('2') => match &first_success_schema {
+                Some(first) if first != s => schema_collector::reference_name(s)
+                    .unwrap_or(format!("{endpoint_name}{status_code}")),
+                Some(_) => endpoint_name.to_owned(),
+                None => {
+                    first_success_schema = Some(s.clone());
+
+                    endpoint_name.to_owned()
+                }
+            },
+            _ => endpoint_name.to_owned(),
+        };
 
-    for (k, v) in &operation.responses {
-        let Ok(v) = v.resolve(spec) else {
-            continue;
+        let type_info = match schema {
+            Some(s) => schema_collector::schema_to_type(
+                &resolve(&s, spec, &context)?,
+                &type_name,
+                spec,
+                &None,
+                class_types,
+                enum_types,
+            )?,
+            None => None,
         };
 
         let response = ResponseModel {
-            status_code: match k {
-                StringOrHttpCode::String(s) => s.to_string(),
-                StringOrHttpCode::StatusCode(c) => c.to_string(),
-            },
-            type_: v
-                .content
-                .get("application/json")
-                .cloned()
-                .and_then(|m| m.schema)
-                .and_then(|s| s.resolve(spec).ok())
-                .and_then(|s| {
-                    schema_collector::schema_to_type(
-                        &s,
-                        endpoint_name,
-                        spec,
-                        &None,
-                        class_types,
-                        enum_types,
-                    )
-                })
-                .map_or(Type::default(), |(n, c, e)| Type {
-                    name: n,
-                    is_class: c,
-                    is_enum: e,
-                }),
+            status_code,
+            type_: type_info.map_or(Type::default(), |(n, c, e)| Type {
+                name: n,
+                is_class: c,
+                is_enum: e,
+                is_set: false,
+            }),
             is_list_type: false,
         };
 
         responses.push(response);
     }
 
-    responses.sort_by_key(|r| r.status_code.clone());
+    Ok(responses)
+}
+
+/// Returns the responses of the operation sorted by their status code.
+fn sorted_responses(operation: &Operation) -> Vec<(String, &RefOr<Response>)> {
+    let mut responses = operation
+        .responses
+        .iter()
+        .map(|(k, v)| {
+            let status_code = match k {
+                StringOrHttpCode::String(s) => s.to_string(),
+                StringOrHttpCode::StatusCode(c) => c.to_string(),
+            };
+
+            (status_code, v)
+        })
+        .collect::<Vec<(String, &RefOr<Response>)>>();
+    responses.sort_by(|a, b| a.0.cmp(&b.0));
+
     responses
 }
 
-fn get_endpoint_args(operation: &Operation, spec: &Spec) -> Vec<EndpointArg> {
+fn get_endpoint_args(
+    operation: &Operation,
+    spec: &Spec,
+    endpoint_name: &str,
+    class_types: &mut Vec<ClassType>,
+    enum_types: &mut Vec<EnumType>,
+) -> Result<Vec<EndpointArg>, String> {
     let mut args = operation
         .parameters
         .iter()
-        .filter_map(|p| {
-            p.resolve(spec).ok().map(|p| {
-                let name = capitalize(&p.name.clone().unwrap_or_default());
+        .map(|p| {
+            let context = format!("Parameter of operation \"{endpoint_name}\"");
+            resolve(p, spec, &context).and_then(|p| {
+                let key = p.name.clone().unwrap_or_default();
+                let context = format!("Parameter \"{key}\" of operation \"{endpoint_name}\"");
+                let name = capitalize(&key);
 
                 let s_type_name = match p.schema_type {
                     Some(ParameterSchemaType::Boolean) => "boolean".to_string(),
@@ -248,30 +454,65 @@ fn get_endpoint_args(operation: &Operation, spec: &Spec) -> Vec<EndpointArg> {
                     _ => String::new(),
                 };
 
-                let type_name = match &p.schema {
-                    Some(s) => s.resolve(spec).ok().and_then(|s| {
-                        s.schema_type
-                            .as_ref()
-                            .map(|t| helper::schema_type_to_base_type(*t, &None))
-                    }),
+                let schema = match &p.schema {
+                    Some(s) => Some(resolve(s, spec, &context)?),
                     None => None,
                 };
+                let type_name = schema
+                    .as_ref()
+                    .and_then(|s| s.schema_type)
+                    .map(|t| helper::schema_type_to_base_type(t, &None));
 
                 let arg_type = match p.location.unwrap_or_default() {
                     ParameterLocation::Query => "query".to_owned(),
                     ParameterLocation::Path => "path".to_owned(),
                     ParameterLocation::Body => "body".to_owned(),
-                    ParameterLocation::Header => todo!(),
-                    ParameterLocation::FormData => todo!(),
-                    ParameterLocation::Cookie => todo!(),
+                    location @ (ParameterLocation::Header
+                    | ParameterLocation::FormData
+                    | ParameterLocation::Cookie) => {
+                        return Err(format!(
+                            "{context}: Parameters in {location:?} are not supported"
+                        ));
+                    }
+                };
+
+                let (type_name, is_enum) = match arg_type.as_str() {
+                    "path" => get_path_arg_type(&p, spec, endpoint_name, class_types, enum_types)?,
+                    "query" => get_enum_arg_type(&p, spec, endpoint_name, class_types, enum_types)?,
+                    _ => None,
+                }
+                .unwrap_or((type_name.unwrap_or(s_type_name), false));
+
+                // Enums of other types than string are checked by the client against their values
+                let allowed_values = match (&schema, is_enum, arg_type.as_str()) {
+                    (Some(schema), false, "query") => schema
+                        .enum_values
+                        .iter()
+                        .filter_map(|v| {
+                            Some(AllowedValue {
+                                literal: arg_literal(v, &type_name, false, enum_types)?,
+                                value: match v {
+                                    Value::String(s) => s.clone(),
+                                    v => v.to_string(),
+                                },
+                            })
+                        })
+                        .collect(),
+                    _ => vec![],
                 };
+                let default = p.default.clone().or_else(|| schema.and_then(|s| s.default));
 
-                EndpointArg {
+                Ok(EndpointArg {
                     name,
-                    type_name: type_name.unwrap_or(s_type_name),
+                    key,
+                    default_arg: default
+                        .as_ref()
+                        .and_then(|d| arg_literal(d, &type_name, is_enum, enum_types)),
+                    type_name,
+                    is_enum,
                     arg_type,
                     is_required: p.required.unwrap_or_default(),
-                    default_value: match &p.default {
+                    default_value: match &default {
                         Some(Value::String(s)) => s.to_string(),
                         Some(Value::Bool(s)) => {
                             if *s {
@@ -284,14 +525,114 @@ fn get_endpoint_args(operation: &Operation, spec: &Spec) -> Vec<EndpointArg> {
                         Some(d) => d.to_string(),
                         None => String::new(),
                     },
-                }
+                    allowed_values,
+                })
             })
         })
-        .collect::<Vec<EndpointArg>>();
+        .collect::<Result<Vec<EndpointArg>, String>>()?;
 
-    args.sort_by_key(|a| a.default_value.is_empty());
+    // Delphi only accepts default values for the last arguments
+    args.sort_by_key(|a| a.default_arg.is_some());
+
+    Ok(args)
+}
+
+/// Delphi literal of a value of the argument, e.g. the variant of an enum.
+/// Returns `None` for values, which don't match the type of the argument.
+fn arg_literal(
+    value: &Value,
+    type_name: &str,
+    is_enum: bool,
+    enum_types: &[EnumType],
+) -> Option<String> {
+    match (value, type_name) {
+        (Value::String(s), _) if is_enum => enum_types
+            .iter()
+            .find(|e| e.name == type_name)?
+            .variants
+            .iter()
+            .find(|v| &v.key == s)
+            .map(|v| v.name.clone()),
+        (Value::String(s), "string") => Some(as_pascal_literal(s)),
+        (Value::Number(n), "integer") if !n.is_f64() => Some(n.to_string()),
+        (Value::Number(n), "double") => Some(n.to_string()),
+        (Value::Bool(b), "boolean") => Some(String::from(if *b { "True" } else { "False" })),
+        _ => None,
+    }
+}
+
+/// Path parameters are typed more strictly than other parameters, as they are
+/// converted to a string by the client anyway: `format: uuid` becomes a GUID and
+/// string enums the matching enum type.
+fn get_path_arg_type(
+    parameter: &Parameter,
+    spec: &Spec,
+    endpoint_name: &str,
+    class_types: &mut Vec<ClassType>,
+    enum_types: &mut Vec<EnumType>,
+) -> Result<Option<(String, bool)>, String> {
+    if let Some(enum_type) =
+        get_enum_arg_type(parameter, spec, endpoint_name, class_types, enum_types)?
+    {
+        return Ok(Some(enum_type));
+    }
 
-    args
+    let Some(schema_ref) = parameter.schema.as_ref() else {
+        return Ok(None);
+    };
+    let parameter_name = parameter.name.clone().unwrap_or_default();
+    let schema = resolve(
+        schema_ref,
+        spec,
+        &format!("Parameter \"{parameter_name}\" of operation \"{endpoint_name}\""),
+    )?;
+
+    Ok(
+        (schema.schema_type == Some(SchemaType::String)
+            && schema.format.as_deref() == Some("uuid"))
+        .then(|| (String::from("guid"), false)),
+    )
+}
+
+/// Parameters with string enums are typed as the matching enum, so only valid values can
+/// be passed. The enum is named after a referenced schema or after the endpoint and the
+/// parameter.
+fn get_enum_arg_type(
+    parameter: &Parameter,
+    spec: &Spec,
+    endpoint_name: &str,
+    class_types: &mut Vec<ClassType>,
+    enum_types: &mut Vec<EnumType>,
+) -> Result<Option<(String, bool)>, String> {
+    let Some(schema_ref) = parameter.schema.as_ref() else {
+        return Ok(None);
+    };
+    let parameter_name = parameter.name.clone().unwrap_or_default();
+    let schema = resolve(
+        schema_ref,
+        spec,
+        &format!("Parameter \"{parameter_name}\" of operation \"{endpoint_name}\""),
+    )?;
+
+    if schema.schema_type != Some(SchemaType::String) || schema.enum_values.is_empty() {
+        return Ok(None);
+    }
+
+    let name = match schema_ref {
+        RefOr::Reference { reference_path } => {
+            let Ok(reference) = Reference::try_from(reference_path.clone()) else {
+                return Ok(None);
+            };
+
+            reference.name
+        }
+        _ => endpoint_name.to_owned() + &capitalize(&parameter_name),
+    };
+
+    Ok(
+        schema_collector::schema_to_type(&schema, &name, spec, &None, class_types, enum_types)?
+            .map(|(n, _, e)| (n, e)),
+    )
 }
 
 fn get_endpoint_request_body(
@@ -300,27 +641,76 @@ fn get_endpoint_request_body(
     endpoint_name: &str,
     class_types: &mut Vec<ClassType>,
     enum_types: &mut Vec<EnumType>,
-) -> Option<Type> {
+) -> Result<Option<(Type, BodyEncoding)>, String> {
     let name = endpoint_name.to_string() + "RequestBody";
+    let context = format!("Request body of operation \"{endpoint_name}\"");
+    let Some(request_body) = operation.request_body.as_ref() else {
+        return Ok(None);
+    };
+    let request_body = resolve(request_body, spec, &context)?;
+
+    // Form and text bodies are passed as they are sent, JSON bodies as model
+    if !request_body.content.contains_key("application/json") {
+        let plain_type = |name: &str, encoding| {
+            Ok(Some((
+                Type {
+                    name: name.to_owned(),
+                    is_class: false,
+                    is_enum: false,
+                    is_set: false,
+                },
+                encoding,
+            )))
+        };
 
-    operation
-        .request_body
-        .as_ref()
-        .and_then(|r| r.resolve(spec).ok())
-        .and_then(|r| r.content.get("application/json").cloned())
+        if request_body
+            .content
+            .keys()
+            .any(|k| helper::is_content_type(k, "application/x-www-form-urlencoded"))
+        {
+            return plain_type("TStrings", BodyEncoding::FormUrlEncoded);
+        }
+
+        if request_body
+            .content
+            .keys()
+            .any(|k| helper::is_content_type(k, "text/plain"))
+        {
+            return plain_type("string", BodyEncoding::PlainText);
+        }
+    }
+
+    let type_info = match request_body
+        .content
+        .get("application/json")
+        .cloned()
         .and_then(|m| m.schema)
-        .and_then(|s| s.resolve(spec).ok())
-        .and_then(|s| {
-            schema_collector::schema_to_type(&s, &name, spec, &None, class_types, enum_types)
-        })
-        .map(|(n, c, e)| Type {
-            name: n,
-            is_class: c,
-            is_enum: e,
-        })
+    {
+        Some(s) => schema_collector::schema_to_type(
+            &resolve(&s, spec, &context)?,
+            &name,
+            spec,
+            &None,
+            class_types,
+            enum_types,
+        )?,
+        None => None,
+    };
+
+    Ok(type_info.map(|(n, c, e)| {
+        (
+            Type {
+                name: n,
+                is_class: c,
+                is_enum: e,
+                is_set: false,
+            },
+            BodyEncoding::Json,
+        )
+    }))
 }
 
-fn sanitize_operation_id(name: &str) -> String {
+pub(crate) fn sanitize_operation_id(name: &str) -> String {
     let chars = name.chars();
 
     let mut next_char_upper = false;
@@ -342,3 +732,265 @@ fn sanitize_operation_id(name: &str) -> String {
 
     sanitized
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPEC: &str = r##"
+openapi: 3.0.0
+info:
+  title: Pet Store
+  version: "1.0"
+paths:
+  /pets/{petId}/photos/{photoId}:
+    get:
+      operationId: showPhoto
+      parameters:
+        - name: petId
+          in: path
+          required: true
+          schema:
+            type: integer
+      responses:
+        "204":
+          description: ok
+"##;
+
+    #[test]
+    fn undefined_path_placeholder_is_rejected() {
+        let spec = sw4rm_rs::from_reader(SPEC.as_bytes()).unwrap();
+
+        let error = collect_endpoints(&spec, &mut vec![], &mut vec![]).err();
+
+        assert_eq!(
+            error.as_deref(),
+            Some(
+                "Placeholder {photoId} in path \"/pets/{petId}/photos/{photoId}\" of ShowPhoto is not defined as path parameter"
+            )
+        );
+    }
+
+    #[test]
+    fn unsupported_parts_of_the_spec_are_reported_with_their_location() {
+        let collect = |operation: &str| {
+            let spec = format!(
+                r##"
+openapi: 3.0.0
+info:
+  title: Pet Store
+  version: "1.0"
+paths:
+  /pets:
+{operation}
+      responses:
+        "204":
+          description: ok
+"##
+            );
+            let spec = sw4rm_rs::from_reader(spec.as_bytes()).unwrap();
+
+            collect_endpoints(&spec, &mut vec![], &mut vec![])
+                .err()
+                .unwrap_or_default()
+        };
+
+        assert_eq!(
+            collect(
+                r#"    get:
+      operationId: listPets
+      parameters:
+        - name: X-Request-Id
+          in: header
+          schema:
+            type: string"#
+            ),
+            "Parameter \"X-Request-Id\" of operation \"ListPets\": Parameters in Header are not supported"
+        );
+
+        let error = collect(
+            r##"    get:
+      operationId: listPets
+      parameters:
+        - name: limit
+          in: query
+          schema:
+            $ref: "#/components/schemas/Limit""##,
+        );
+        assert!(
+            error.starts_with(
+                "Parameter \"limit\" of operation \"ListPets\": Reference \"#/components/schemas/Limit\" could not be resolved"
+            ),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn form_and_text_bodies_are_passed_as_sent() {
+        let spec = sw4rm_rs::from_reader(
+            r##"
+openapi: 3.0.0
+info:
+  title: Pet Store
+  version: "1.0"
+paths:
+  /login:
+    post:
+      operationId: login
+      requestBody:
+        content:
+          application/x-www-form-urlencoded:
+            schema:
+              type: object
+              properties:
+                user:
+                  type: string
+      responses:
+        "204":
+          description: ok
+  /notes:
+    post:
+      operationId: addNote
+      requestBody:
+        content:
+          text/plain; charset=utf-8:
+            schema:
+              type: string
+      responses:
+        "204":
+          description: ok
+"##
+            .as_bytes(),
+        )
+        .unwrap();
+
+        let endpoints = collect_endpoints(&spec, &mut vec![], &mut vec![]).unwrap();
+        let bodies = endpoints
+            .iter()
+            .map(|e| (e.request_body.name.as_str(), e.request_body_encoding))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            bodies,
+            [
+                ("TStrings", BodyEncoding::FormUrlEncoded),
+                ("string", BodyEncoding::PlainText)
+            ]
+        );
+    }
+
+    #[test]
+    fn query_parameters_are_typed_by_their_enum_and_default() {
+        let spec = sw4rm_rs::from_reader(
+            r##"
+openapi: 3.0.0
+info:
+  title: Pet Store
+  version: "1.0"
+paths:
+  /pets:
+    get:
+      operationId: listPets
+      parameters:
+        - name: sort
+          in: query
+          schema:
+            type: string
+            enum: [name, age]
+            default: age
+        - name: order
+          in: query
+          schema:
+            type: string
+            default: desc
+        - name: limit
+          in: query
+          schema:
+            type: integer
+            enum: [10, 50]
+            default: 5.5
+      responses:
+        "204":
+          description: ok
+"##
+            .as_bytes(),
+        )
+        .unwrap();
+
+        let mut enum_types = vec![];
+        let endpoints = collect_endpoints(&spec, &mut vec![], &mut enum_types).unwrap();
+        let args = endpoints[0]
+            .args
+            .iter()
+            .map(|a| {
+                (
+                    a.key.as_str(),
+                    a.type_name.as_str(),
+                    a.default_arg.as_deref(),
+                    a.allowed_values
+                        .iter()
+                        .map(|v| v.literal.clone())
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            args,
+            [
+                (
+                    "limit",
+                    "integer",
+                    None,
+                    vec![String::from("10"), String::from("50")]
+                ),
+                ("sort", "ListPetsSort", Some("lpsAge"), vec![]),
+                ("order", "string", Some("'desc'"), vec![]),
+            ]
+        );
+        assert_eq!(enum_types[0].name, "ListPetsSort");
+    }
+
+    #[test]
+    fn deprecated_operations_keep_their_description() {
+        let spec = sw4rm_rs::from_reader(
+            r##"
+openapi: 3.0.0
+info:
+  title: Pet Store
+  version: "1.0"
+paths:
+  /pets:
+    get:
+      operationId: listPets
+      deprecated: true
+      description: |
+        Lists all pets.
+
+        Use searchPets instead.
+      responses:
+        "204":
+          description: ok
+    post:
+      operationId: addPet
+      responses:
+        "204":
+          description: ok
+"##
+            .as_bytes(),
+        )
+        .unwrap();
+
+        let endpoints = collect_endpoints(&spec, &mut vec![], &mut vec![]).unwrap();
+        let comments = endpoints
+            .iter()
+            .map(|e| (e.name.as_str(), e.deprecation_comment.clone()))
+            .collect::<BTreeMap<_, _>>();
+
+        assert_eq!(
+            comments["ListPets"],
+            ["Deprecated", "Lists all pets.", "Use searchPets instead."]
+        );
+        assert!(comments["AddPet"].is_empty());
+    }
+}
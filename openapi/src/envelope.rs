@@ -0,0 +1,188 @@
+use crate::{
+    models::{ClassType, Endpoint, Envelope, EnvelopeProperty, HandlerGroup, Property},
+    pruning, ResponseEnvelope,
+};
+
+/// Replaces envelope responses by the type of their payload.
+///
+/// A response model is an envelope, if it has a property named by the data key of `envelope`
+/// and all other properties are named by its error or meta key. The client methods return
+/// the payload, raise the error and pass the metadata out. Envelope models, which are not
+/// used anymore, are removed, including those of the components.
+pub(crate) fn unwrap_responses(
+    endpoints: &mut [Endpoint],
+    handler_groups: &[HandlerGroup],
+    class_types: &mut Vec<ClassType>,
+    envelope: &ResponseEnvelope,
+) {
+    for endpoint in endpoints.iter_mut() {
+        let Some(class_type) = class_types
+            .iter()
+            .find(|c| endpoint.response_type.is_class && c.name == endpoint.response_type.name)
+        else {
+            continue;
+        };
+
+        if !is_envelope(class_type, envelope) {
+            continue;
+        }
+
+        let property_by_key =
+            |key: Option<&String>| class_type.properties.iter().find(|p| Some(&p.key) == key);
+        let Some(data) = property_by_key(Some(&envelope.data_key)) else {
+            continue;
+        };
+
+        for response in &mut endpoint.status_codes {
+            if response.status_code.starts_with('2') && response.type_ == endpoint.response_type {
+                response.type_ = data.type_.clone();
+                response.is_list_type = data.is_list_type;
+            }
+        }
+
+        endpoint.response_type = data.type_.clone();
+        endpoint.response_is_list = data.is_list_type;
+        endpoint.envelope = Some(Envelope {
+            data_key: data.key.clone(),
+            error_key: property_by_key(envelope.error_key.as_ref()).map(|p| p.key.clone()),
+            meta: property_by_key(envelope.meta_key.as_ref()).map(envelope_property),
+        });
+    }
+
+    let reachable = pruning::reachable_types(
+        endpoints
+            .iter()
+            .chain(handler_groups.iter().flat_map(|g| &g.operations)),
+        class_types,
+    );
+
+    class_types.retain(|c| reachable.contains(&c.name) || !is_envelope(c, envelope));
+}
+
+/// Model has the data property and no other properties than error and meta
+fn is_envelope(class_type: &ClassType, envelope: &ResponseEnvelope) -> bool {
+    let is_envelope_key = |key: &String| {
+        *key == envelope.data_key
+            || envelope.error_key.as_ref() == Some(key)
+            || envelope.meta_key.as_ref() == Some(key)
+    };
+
+    class_type
+        .properties
+        .iter()
+        .any(|p| p.key == envelope.data_key)
+        && class_type
+            .properties
+            .iter()
+            .all(|p| is_envelope_key(&p.key))
+}
+
+fn envelope_property(property: &Property) -> EnvelopeProperty {
+    EnvelopeProperty {
+        key: property.key.clone(),
+        type_: property.type_.clone(),
+        is_list_type: property.is_list_type,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{endpoint_collector, schema_collector};
+
+    const SPEC: &str = r##"
+openapi: 3.0.0
+info:
+  title: Shop
+  version: "1.0"
+paths:
+  /orders:
+    get:
+      operationId: listOrders
+      responses:
+        "200":
+          description: ok
+          content:
+            application/json:
+              schema:
+                $ref: "#/components/schemas/OrdersEnvelope"
+  /status:
+    get:
+      operationId: getStatus
+      responses:
+        "200":
+          description: ok
+          content:
+            application/json:
+              schema:
+                $ref: "#/components/schemas/Status"
+components:
+  schemas:
+    Order:
+      type: object
+      properties:
+        id:
+          type: integer
+    Paging:
+      type: object
+      properties:
+        total:
+          type: integer
+    OrdersEnvelope:
+      type: object
+      properties:
+        data:
+          type: array
+          items:
+            $ref: "#/components/schemas/Order"
+        error:
+          type: string
+        meta:
+          $ref: "#/components/schemas/Paging"
+    Status:
+      type: object
+      properties:
+        data:
+          type: string
+        healthy:
+          type: boolean
+"##;
+
+    #[test]
+    fn envelope_responses_return_their_payload() {
+        let spec = sw4rm_rs::from_reader(SPEC.as_bytes()).unwrap();
+        let (mut class_types, mut enum_types) =
+            schema_collector::collect_types(&spec, &None).unwrap();
+        let mut endpoints =
+            endpoint_collector::collect_endpoints(&spec, &mut class_types, &mut enum_types)
+                .unwrap();
+
+        unwrap_responses(
+            &mut endpoints,
+            &[],
+            &mut class_types,
+            &ResponseEnvelope {
+                data_key: String::from("data"),
+                error_key: Some(String::from("error")),
+                meta_key: Some(String::from("meta")),
+            },
+        );
+
+        let list_orders = &endpoints[0];
+        assert_eq!(list_orders.response_type.name, "Order");
+        assert!(list_orders.response_is_list);
+        let envelope = list_orders.envelope.as_ref().unwrap();
+        assert_eq!(envelope.error_key.as_deref(), Some("error"));
+        assert_eq!(envelope.meta.as_ref().unwrap().type_.name, "Paging");
+
+        // Status has a property besides the envelope keys
+        let get_status = &endpoints[1];
+        assert_eq!(get_status.response_type.name, "GetStatus");
+        assert!(get_status.envelope.is_none());
+
+        assert!(!class_types
+            .iter()
+            .any(|c| c.name == "ListOrders" || c.name == "OrdersEnvelope"));
+        assert!(class_types.iter().any(|c| c.name == "Paging"));
+    }
+}
@@ -0,0 +1,61 @@
+//! Regression tests, which collect the models, endpoints and webhooks of every spec in
+//! `tests/fixtures` and compare them with the snapshot next to it.
+//!
+//! A new case is a spec `tests/fixtures/<name>.yaml` or `.json`. Its snapshot
+//! `tests/fixtures/snapshots/representation@<name>.yaml.snap` is written by
+//! `INSTA_UPDATE=always cargo test -p xsd-delphi-codegen-openapi fixtures` or `cargo insta review`.
+
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::{
+    endpoint_collector,
+    models::{ClassType, Endpoint, EnumType, HandlerGroup},
+    parse_spec, schema_collector, webhook_collector,
+};
+
+/// Types and operations collected from a spec, before they are rendered
+#[derive(Serialize)]
+struct Representation {
+    class_types: Vec<ClassType>,
+    enum_types: Vec<EnumType>,
+    endpoints: Vec<Endpoint>,
+    handler_groups: Vec<HandlerGroup>,
+}
+
+/// Collects the spec like the generation without roots, envelopes and type prefix
+fn build(path: &Path) -> Representation {
+    let spec =
+        parse_spec(path).unwrap_or_else(|e| panic!("{} can not be parsed: {e}", path.display()));
+
+    let collection_failed =
+        |e: String| -> ! { panic!("{} can not be collected: {e}", path.display()) };
+    let (mut class_types, mut enum_types) =
+        schema_collector::collect_types(&spec, &None).unwrap_or_else(|e| collection_failed(e));
+    let endpoints = endpoint_collector::collect_endpoints(&spec, &mut class_types, &mut enum_types)
+        .unwrap_or_else(|e| collection_failed(e));
+    let handler_groups =
+        webhook_collector::collect_handler_groups(&spec, &mut class_types, &mut enum_types)
+            .unwrap_or_else(|e| collection_failed(e));
+    schema_collector::mark_recursive_properties(&mut class_types);
+
+    Representation {
+        class_types,
+        enum_types,
+        endpoints,
+        handler_groups,
+    }
+}
+
+#[test]
+fn representation() {
+    insta::with_settings!({
+        snapshot_path => "../tests/fixtures/snapshots",
+        prepend_module_to_snapshot => false,
+    }, {
+        insta::glob!("../tests/fixtures", "*.{json,yaml}", |path| {
+            insta::assert_yaml_snapshot!(build(path));
+        });
+    });
+}
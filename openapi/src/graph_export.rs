@@ -0,0 +1,35 @@
+use std::fmt::Write;
+
+use crate::models::{ClassType, EnumType};
+
+/// Renders the model types and their relations as a Graphviz DOT graph.
+pub(crate) fn to_dot(
+    unit_name: &str,
+    class_types: &[ClassType],
+    enum_types: &[EnumType],
+) -> String {
+    let mut nodes = String::new();
+    let mut edges = String::new();
+
+    for class_type in class_types {
+        let _ = writeln!(nodes, "    \"{}\" [shape=box];", class_type.name);
+
+        for property in &class_type.properties {
+            if property.type_.is_class || property.type_.is_enum {
+                let _ = writeln!(
+                    edges,
+                    "  \"{}\" -> \"{}\" [label=\"{}\"];",
+                    class_type.name, property.type_.name, property.key
+                );
+            }
+        }
+    }
+
+    for enum_type in enum_types {
+        let _ = writeln!(nodes, "    \"{}\" [shape=ellipse];", enum_type.name);
+    }
+
+    format!(
+        "digraph \"{unit_name}\" {{\n  rankdir=LR;\n\n  subgraph \"cluster_{unit_name}\" {{\n    label=\"{unit_name}\";\n{nodes}  }}\n\n{edges}}}\n"
+    )
+}
@@ -0,0 +1,122 @@
+/// Namespace of the interface GUIDs, the UUIDv5 of the repository URL in the URL namespace
+const NAMESPACE: [u8; 16] = [
+    0x83, 0x64, 0x2c, 0x4f, 0x92, 0x2f, 0x53, 0x5a, 0x95, 0x96, 0xd8, 0x7b, 0x02, 0x88, 0xa4, 0x12,
+];
+
+/// Returns the GUID of a generated interface in Delphi notation, e.g. `['{...}']`.
+///
+/// The GUID is a UUIDv5 of the interface name, so regenerating a unit keeps its GUIDs.
+pub(crate) fn interface_guid(interface_name: &str) -> String {
+    let uuid = uuid_v5(&NAMESPACE, interface_name);
+    let hex = uuid.iter().map(|b| format!("{b:02X}")).collect::<String>();
+
+    format!(
+        "['{{{}-{}-{}-{}-{}}}']",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+/// Name based UUID as defined in RFC 4122 section 4.3
+fn uuid_v5(namespace: &[u8; 16], name: &str) -> [u8; 16] {
+    let mut data = namespace.to_vec();
+    data.extend_from_slice(name.as_bytes());
+
+    let mut uuid = [0; 16];
+    uuid.copy_from_slice(&sha1(&data)[..16]);
+    uuid[6] = (uuid[6] & 0x0f) | 0x50;
+    uuid[8] = (uuid[8] & 0x3f) | 0x80;
+
+    uuid
+}
+
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut h: [u32; 5] = [
+        0x6745_2301,
+        0xefcd_ab89,
+        0x98ba_dcfe,
+        0x1032_5476,
+        0xc3d2_e1f0,
+    ];
+
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&((data.len() as u64) * 8).to_be_bytes());
+
+    for chunk in message.chunks_exact(64) {
+        let mut w = [0u32; 80];
+
+        for (i, word) in chunk.chunks_exact(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = h;
+
+        for (i, word) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5a82_7999),
+                20..=39 => (b ^ c ^ d, 0x6ed9_eba1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8f1b_bcdc),
+                _ => (b ^ c ^ d, 0xca62_c1d6),
+            };
+
+            let temp = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(*word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+
+        for (h, v) in h.iter_mut().zip([a, b, c, d, e]) {
+            *h = h.wrapping_add(v);
+        }
+    }
+
+    let mut digest = [0; 20];
+    for (i, v) in h.iter().enumerate() {
+        digest[i * 4..i * 4 + 4].copy_from_slice(&v.to_be_bytes());
+    }
+
+    digest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interface_guid_is_uuid_v5_of_the_name() {
+        // uuid.uuid5(uuid.NAMESPACE_DNS, "python.org")
+        let dns = [
+            0x6b, 0xa7, 0xb8, 0x10, 0x9d, 0xad, 0x11, 0xd1, 0x80, 0xb4, 0x00, 0xc0, 0x4f, 0xd4,
+            0x30, 0xc8,
+        ];
+        assert_eq!(
+            uuid_v5(&dns, "python.org"),
+            [
+                0x88, 0x63, 0x13, 0xe1, 0x3b, 0x8a, 0x53, 0x72, 0x9b, 0x90, 0x0c, 0x9a, 0xee, 0x19,
+                0x9e, 0x5d
+            ]
+        );
+
+        assert_eq!(
+            interface_guid("IPetApiClient"),
+            "['{A8DE6612-673E-52FA-ADBB-B7000F8B484A}']"
+        );
+    }
+}
@@ -1,11 +1,41 @@
-use sw4rm_rs::shared::SchemaType;
+use std::collections::HashMap;
+
+use sw4rm_rs::{
+    shared::{Schema, SchemaType},
+    RefOr, Resolvable, Spec,
+};
+use tera::Value;
 
 pub(crate) fn capitalize(value: &str) -> String {
     let mut c = value.chars();
 
     c.next()
         .map(|f| f.to_uppercase().collect::<String>() + c.as_str())
-        .unwrap_or(String::new())
+        .unwrap_or_default()
+}
+
+/// Returns the entries of the map sorted by their key, so the generated code
+/// doesn't depend on the iteration order of the map.
+pub(crate) fn sorted_entries<K: Ord, V>(map: &HashMap<K, V>) -> Vec<(&K, &V)> {
+    let mut entries = map.iter().collect::<Vec<(&K, &V)>>();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+
+    entries
+}
+
+/// Resolves a reference of the spec, `context` describes the referencing part of the spec
+/// in the error, e.g. `Schema "Pet"`.
+pub(crate) fn resolve<T: Resolvable>(
+    item: &RefOr<T>,
+    spec: &Spec,
+    context: &str,
+) -> Result<T, String> {
+    item.resolve(spec).map_err(|e| match item {
+        RefOr::Reference { reference_path } => {
+            format!("{context}: Reference \"{reference_path}\" could not be resolved ({e:?})")
+        }
+        RefOr::Item(_) => format!("{context}: Could not be resolved ({e:?})"),
+    })
 }
 
 pub(crate) fn get_enum_variant_prefix(name: &str, type_prefix: &str) -> String {
@@ -18,8 +48,14 @@ pub(crate) fn get_enum_variant_prefix(name: &str, type_prefix: &str) -> String {
         .to_lowercase()
 }
 
+/// Replaces the characters, which are not allowed in a Delphi identifier, by underscores.
 pub(crate) fn sanitize_name(name: &str) -> String {
-    name.replace(['-', '.'], "_")
+    name.chars()
+        .map(|c| match c.is_alphanumeric() {
+            true => c,
+            false => '_',
+        })
+        .collect()
 }
 
 pub(crate) fn schema_type_to_base_type(schema_type: SchemaType, format: &Option<String>) -> String {
@@ -37,3 +73,80 @@ pub(crate) fn schema_type_to_base_type(schema_type: SchemaType, format: &Option<
         _ => String::new(),
     }
 }
+
+/// Generation overrides read from the `x-delphi-*` and `x-namespace` extensions of a schema.
+pub(crate) struct SchemaOverrides {
+    /// `x-delphi-name`, name used in the generated code
+    pub(crate) name: Option<String>,
+    /// `x-delphi-skip`, excludes the schema or property from generation
+    pub(crate) skip: bool,
+    /// `x-delphi-type`, base type used instead of the type of the schema
+    pub(crate) data_type: Option<String>,
+    /// `x-delphi-owned`, whether the model frees the value of the property
+    pub(crate) owned: Option<bool>,
+    /// `x-namespace`, models unit of the schema, if the models are split by namespace
+    pub(crate) namespace: Option<String>,
+    /// `x-sensitive`, value is redacted in logged request and response bodies
+    pub(crate) sensitive: bool,
+}
+
+pub(crate) fn schema_overrides(schema: &Schema) -> SchemaOverrides {
+    let extension = |name: &str| schema.x_fields.get(name);
+
+    SchemaOverrides {
+        name: extension("x-delphi-name")
+            .and_then(Value::as_str)
+            .map(str::to_owned),
+        skip: extension("x-delphi-skip")
+            .and_then(Value::as_bool)
+            .unwrap_or(false),
+        data_type: extension("x-delphi-type")
+            .and_then(Value::as_str)
+            .filter(|t| matches!(*t, "integer" | "double" | "string" | "boolean" | "datetime"))
+            .map(str::to_owned),
+        owned: extension("x-delphi-owned").and_then(Value::as_bool),
+        namespace: extension("x-namespace")
+            .and_then(Value::as_str)
+            .map(str::to_owned),
+        sensitive: extension("x-sensitive")
+            .and_then(Value::as_bool)
+            .unwrap_or(false),
+    }
+}
+
+/// Lines of the comment written above the declaration of a deprecated operation or property.
+///
+/// The description of the spec is kept, so the comment tells what to use instead.
+pub(crate) fn deprecation_comment(
+    deprecated: Option<bool>,
+    description: &Option<String>,
+) -> Vec<String> {
+    if deprecated != Some(true) {
+        return vec![];
+    }
+
+    let mut lines = vec![String::from("Deprecated")];
+    lines.extend(
+        description
+            .iter()
+            .flat_map(|d| d.lines())
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .map(str::to_owned),
+    );
+
+    lines
+}
+
+/// Returns true, if the content type without its parameters, e.g. `; charset=utf-8`, is `expected`.
+pub(crate) fn is_content_type(content_type: &str, expected: &str) -> bool {
+    content_type
+        .split(';')
+        .next()
+        .is_some_and(|c| c.trim().eq_ignore_ascii_case(expected))
+}
+
+/// Returns true for response content types that carry raw bytes instead of JSON.
+pub(crate) fn is_binary_content_type(content_type: &str) -> bool {
+    content_type == "application/octet-stream" || content_type.starts_with("image/")
+}
@@ -0,0 +1,435 @@
+use std::{collections::BTreeMap, fmt::Write};
+
+use serde_json::{json, Map, Value};
+use sw4rm_rs::{shared::SecuritySchemeType, Spec};
+
+use crate::models::{BodyEncoding, ClassType, Endpoint, EndpointArg, EnumType, Property, Type};
+
+/// Base URL used, if the spec declares no server
+pub(crate) const DEFAULT_BASE_URL: &str = "http://localhost";
+
+/// Renders a sample request per endpoint in the `.http` format of the REST Client
+/// extension of VS Code and the HTTP Client of IntelliJ.
+///
+/// The base URL and the credentials of the security schemes are file variables, which
+/// are declared once at the top. Bodies are built from the models of the client, so their
+/// shape matches the generated units. `example` values of the schemas are used, where given.
+pub(crate) fn to_http_file(
+    spec: &Spec,
+    endpoints: &[Endpoint],
+    class_types: &[ClassType],
+    enum_types: &[EnumType],
+) -> String {
+    let base_url = spec
+        .servers
+        .first()
+        .map_or(DEFAULT_BASE_URL, |s| s.url.trim_end_matches('/'));
+    let mut variables = BTreeMap::from([(String::from("baseUrl"), base_url.to_owned())]);
+    let mut requests = String::new();
+
+    for endpoint in endpoints {
+        let auth = auth_placeholders(spec, endpoint);
+
+        for variable in auth.iter().flat_map(|a| a.variables.iter()) {
+            variables
+                .entry((*variable).to_owned())
+                .or_insert_with(|| format!("<{variable}>"));
+        }
+
+        let _ = writeln!(requests, "### {}", endpoint.name);
+        let _ = writeln!(requests, "# @name {}", endpoint.name);
+        let _ = writeln!(
+            requests,
+            "{} {{{{baseUrl}}}}{}",
+            endpoint.method,
+            request_target(endpoint, &auth, enum_types)
+        );
+
+        for auth in auth.iter().filter(|a| !a.in_query) {
+            let _ = writeln!(requests, "{}: {}", auth.name, auth.value);
+        }
+
+        if !endpoint.is_binary_response && endpoint.response_type.name != "none" {
+            let _ = writeln!(requests, "Accept: application/json");
+        }
+
+        if let Some((content_type, body)) = request_body(endpoint, class_types, enum_types) {
+            let _ = writeln!(requests, "Content-Type: {content_type}");
+            let _ = writeln!(requests, "\n{body}");
+        }
+
+        requests.push('\n');
+    }
+
+    let mut content = String::new();
+
+    for (name, value) in variables {
+        let _ = writeln!(content, "@{name} = {value}");
+    }
+
+    content.push('\n');
+    content.push_str(&requests);
+
+    content
+}
+
+/// Header or query parameter, which passes the credentials of a security scheme
+pub(crate) struct AuthPlaceholder {
+    /// Parameter is passed in the query instead of a header
+    pub(crate) in_query: bool,
+    /// Name of the header or query parameter
+    pub(crate) name: String,
+    /// Value with the file variables in double braces, e.g. `Bearer {{token}}`
+    pub(crate) value: String,
+    /// File variables referenced by the value
+    pub(crate) variables: Vec<&'static str>,
+}
+
+/// Placeholders for the first security requirement of the operation or, if the operation
+/// has none, of the spec.
+pub(crate) fn auth_placeholders(spec: &Spec, endpoint: &Endpoint) -> Vec<AuthPlaceholder> {
+    let operation_security = spec
+        .paths
+        .get(&endpoint.path)
+        .and_then(|p| p.resolve(spec).ok())
+        .and_then(|p| match endpoint.method.as_str() {
+            "GET" => p.get,
+            "POST" => p.post,
+            "PUT" => p.put,
+            "DELETE" => p.delete,
+            _ => None,
+        })
+        .map(|o| o.security)
+        .unwrap_or_default();
+    let security = if operation_security.is_empty() {
+        &spec.security
+    } else {
+        &operation_security
+    };
+    let Some(requirement) = security.first() else {
+        return vec![];
+    };
+
+    let mut scheme_names = requirement.keys().collect::<Vec<_>>();
+    scheme_names.sort();
+
+    scheme_names
+        .into_iter()
+        .filter_map(|name| {
+            let scheme = spec
+                .components
+                .as_ref()?
+                .security_schemes
+                .get(name)?
+                .resolve(spec)
+                .ok()?;
+
+            let placeholder = match scheme.scheme_type {
+                SecuritySchemeType::ApiKey => match scheme.location.as_str() {
+                    "query" => AuthPlaceholder {
+                        in_query: true,
+                        name: scheme.name,
+                        value: String::from("{{apiKey}}"),
+                        variables: vec!["apiKey"],
+                    },
+                    "cookie" => AuthPlaceholder {
+                        in_query: false,
+                        name: String::from("Cookie"),
+                        value: format!("{}={{{{apiKey}}}}", scheme.name),
+                        variables: vec!["apiKey"],
+                    },
+                    _ => AuthPlaceholder {
+                        in_query: false,
+                        name: scheme.name,
+                        value: String::from("{{apiKey}}"),
+                        variables: vec!["apiKey"],
+                    },
+                },
+                SecuritySchemeType::Http
+                    if scheme
+                        .scheme
+                        .as_deref()
+                        .is_some_and(|s| s.eq_ignore_ascii_case("basic")) =>
+                {
+                    AuthPlaceholder {
+                        in_query: false,
+                        name: String::from("Authorization"),
+                        value: String::from("Basic {{username}} {{password}}"),
+                        variables: vec!["username", "password"],
+                    }
+                }
+                SecuritySchemeType::Http
+                | SecuritySchemeType::OAuth2
+                | SecuritySchemeType::OpenIdConnect => AuthPlaceholder {
+                    in_query: false,
+                    name: String::from("Authorization"),
+                    value: String::from("Bearer {{token}}"),
+                    variables: vec!["token"],
+                },
+            };
+
+            Some(placeholder)
+        })
+        .collect()
+}
+
+/// Path of the request with sample values for the path and query parameters
+fn request_target(
+    endpoint: &Endpoint,
+    auth: &[AuthPlaceholder],
+    enum_types: &[EnumType],
+) -> String {
+    let mut target = endpoint
+        .path_segments
+        .iter()
+        .map(|s| match &s.arg {
+            Some(arg) => arg_value(arg, enum_types),
+            None => s.literal.clone(),
+        })
+        .collect::<String>();
+
+    let query = endpoint
+        .args
+        .iter()
+        .filter(|a| a.arg_type == "query")
+        .map(|a| format!("{}={}", a.key, arg_value(a, enum_types)))
+        .chain(
+            auth.iter()
+                .filter(|a| a.in_query)
+                .map(|a| format!("{}={}", a.name, a.value)),
+        )
+        .collect::<Vec<_>>();
+
+    if !query.is_empty() {
+        target.push('?');
+        target.push_str(&query.join("&"));
+    }
+
+    target
+}
+
+/// Default value of the parameter or a sample value of its type
+pub(crate) fn arg_value(arg: &EndpointArg, enum_types: &[EnumType]) -> String {
+    if !arg.default_value.is_empty() {
+        return arg.default_value.clone();
+    }
+
+    let value = if arg.is_enum {
+        enum_sample(&arg.type_name, enum_types)
+    } else {
+        base_type_sample(&arg.type_name)
+    };
+
+    match value {
+        Value::String(s) => s,
+        v => v.to_string(),
+    }
+}
+
+/// Content type and sample of the request body, if the endpoint has one
+pub(crate) fn request_body(
+    endpoint: &Endpoint,
+    class_types: &[ClassType],
+    enum_types: &[EnumType],
+) -> Option<(&'static str, String)> {
+    let body_type = &endpoint.request_body;
+
+    if body_type.name == "none" {
+        return None;
+    }
+
+    match endpoint.request_body_encoding {
+        BodyEncoding::Json => {
+            let sample = type_sample(body_type, class_types, enum_types, &mut vec![]);
+
+            Some((
+                "application/json",
+                serde_json::to_string_pretty(&sample).unwrap_or_default(),
+            ))
+        }
+        BodyEncoding::FormUrlEncoded => Some((
+            "application/x-www-form-urlencoded",
+            String::from("name=value"),
+        )),
+        BodyEncoding::PlainText => Some(("text/plain", String::from("text"))),
+    }
+}
+
+/// Sample JSON value of a type. Classes already on the `visiting` stack are recursive
+/// and sampled as `null`, so the sample stays finite.
+fn type_sample(
+    type_: &Type,
+    class_types: &[ClassType],
+    enum_types: &[EnumType],
+    visiting: &mut Vec<String>,
+) -> Value {
+    if type_.is_enum {
+        let sample = enum_sample(&type_.name, enum_types);
+
+        return if type_.is_set {
+            json!([sample])
+        } else {
+            sample
+        };
+    }
+
+    if !type_.is_class {
+        return base_type_sample(&type_.name);
+    }
+
+    let Some(class_type) = class_types.iter().find(|c| c.name == type_.name) else {
+        return json!({});
+    };
+
+    if visiting.contains(&class_type.name) {
+        return Value::Null;
+    }
+
+    visiting.push(class_type.name.clone());
+
+    let properties = class_type
+        .properties
+        .iter()
+        .map(|p| {
+            (
+                p.key.clone(),
+                property_sample(p, class_types, enum_types, visiting),
+            )
+        })
+        .collect::<Map<_, _>>();
+
+    visiting.pop();
+
+    Value::Object(properties)
+}
+
+fn property_sample(
+    property: &Property,
+    class_types: &[ClassType],
+    enum_types: &[EnumType],
+    visiting: &mut Vec<String>,
+) -> Value {
+    if let Some(example) = &property.example {
+        return example.clone();
+    }
+
+    let sample = type_sample(&property.type_, class_types, enum_types, visiting);
+
+    match sample {
+        Value::Null => sample,
+        _ if property.is_list_type => json!([sample]),
+        _ if property.is_map_type => json!({ "key": sample }),
+        _ => sample,
+    }
+}
+
+/// First value of the enum
+pub(crate) fn enum_sample(name: &str, enum_types: &[EnumType]) -> Value {
+    enum_types
+        .iter()
+        .find(|e| e.name == name)
+        .and_then(|e| e.variants.first())
+        .map_or(Value::Null, |v| Value::String(v.key.clone()))
+}
+
+fn base_type_sample(type_name: &str) -> Value {
+    match type_name {
+        "integer" => json!(0),
+        "double" => json!(0.0),
+        "boolean" => json!(false),
+        "datetime" => json!("2024-01-01T00:00:00Z"),
+        _ => json!("string"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::collect_models;
+
+    use super::*;
+
+    const SPEC: &str = r##"
+openapi: 3.0.0
+info:
+  title: Pets
+  version: 1.0.0
+servers:
+  - url: https://pets.example.com/v1/
+security:
+  - bearer: []
+paths:
+  /pets/{petId}:
+    get:
+      operationId: getPet
+      parameters:
+        - name: petId
+          in: path
+          required: true
+          schema:
+            type: integer
+        - name: verbose
+          in: query
+          schema:
+            type: boolean
+      responses:
+        "200":
+          description: The pet
+          content:
+            application/json:
+              schema:
+                $ref: "#/components/schemas/Pet"
+    put:
+      operationId: updatePet
+      security:
+        - apiKey: []
+      parameters:
+        - name: petId
+          in: path
+          required: true
+          schema:
+            type: integer
+      requestBody:
+        content:
+          application/json:
+            schema:
+              $ref: "#/components/schemas/Pet"
+      responses:
+        "204":
+          description: Updated
+components:
+  securitySchemes:
+    bearer:
+      type: http
+      scheme: bearer
+    apiKey:
+      type: apiKey
+      in: header
+      name: X-Api-Key
+  schemas:
+    Pet:
+      type: object
+      properties:
+        name:
+          type: string
+          example: Rex
+        status:
+          type: string
+          enum: [available, sold]
+        tags:
+          type: array
+          items:
+            type: string
+        parent:
+          $ref: "#/components/schemas/Pet"
+"##;
+
+    #[test]
+    fn http_file_contains_a_request_per_operation() {
+        let spec = sw4rm_rs::from_reader(SPEC.as_bytes()).unwrap();
+        let (class_types, enum_types, endpoints, _) = collect_models(&spec, &None).unwrap();
+
+        let content = to_http_file(&spec, &endpoints, &class_types, &enum_types);
+
+        insta::assert_snapshot!(content);
+    }
+}
@@ -1,82 +1,514 @@
-use std::path::{Path, PathBuf};
+use std::{
+    error::Error,
+    fmt::Display,
+    path::{Path, PathBuf},
+};
 
-use sw4rm_rs::from_path;
-use tera::Tera;
+use serde::{Deserialize, Serialize};
+
+pub use playground::{
+    Operation, Parameter, ParameterLocation, Request, RequestBody, SpecOperations,
+};
+pub use preview::OperationPreview;
 
 mod endpoint_collector;
+mod envelope;
+#[cfg(test)]
+mod fixtures;
+mod graph_export;
+mod guid;
 mod helper;
+mod http_export;
+mod manifest;
+mod mapping;
+mod model_units;
 mod models;
+mod normalize;
+mod playground;
+mod preview;
+mod pruning;
 mod render;
 mod schema_collector;
 mod type_registry;
+mod webhook_collector;
 
-pub fn generate_openapi_client(source: &[PathBuf], dest: &Path, prefix: &Option<String>) {
-    let Some(source) = source.first() else {
-        eprintln!("No source file provided");
+/// Errors, which abort the generation of the client units
+#[derive(Debug)]
+pub enum GenerationError {
+    /// No spec file was passed
+    MissingSource,
+    /// The destination of the units is not a directory
+    InvalidDestination,
+    /// The spec file could not be parsed
+    Spec(String),
+    /// A template could not be loaded or rendered
+    Template(String),
+    /// A unit or the graph could not be written
+    Io(String),
+}
+
+impl Display for GenerationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingSource => write!(f, "No source file provided"),
+            Self::InvalidDestination => write!(f, "Destination path is not a directory"),
+            Self::Spec(message) | Self::Template(message) | Self::Io(message) => {
+                write!(f, "{message}")
+            }
+        }
+    }
+}
+
+impl Error for GenerationError {}
+
+/// Options of the generated client units
+#[derive(Debug, Default)]
+pub struct ClientOptions {
+    /// Prefix of the unit and type names
+    pub prefix: Option<String>,
+    /// Header inserted at the top of every unit, `{unit}` is replaced by the unit name
+    pub header: Option<String>,
+    /// Write the helper units, which are referenced by the generated units
+    pub emit_support_units: bool,
+    /// Call `Validate` of request bodies before they are sent
+    pub validate_request_bodies: bool,
+    /// Generate a client per tag and a facade, which gives access to them
+    pub group_by_tag: bool,
+    /// Names or tags of the operations, which are generated with the models they use.
+    /// All operations and models are generated, if empty.
+    pub roots: Vec<String>,
+    /// Generate a models unit per tag or namespace instead of a single one
+    pub split_models: Option<ModelSplit>,
+    /// How `FromString` of the enums looks up the value
+    pub enum_lookup: EnumLookup,
+    /// Decorate the fields of the models with the JSON keys for a RTTI based serializer
+    pub serializer_attributes: Option<SerializerAttributes>,
+    /// Envelope wrapped around the payload of the responses, which is unwrapped by the client
+    pub response_envelope: Option<ResponseEnvelope>,
+    /// Log every request with the `IApiLogger` of the client settings
+    pub log_requests: bool,
+    /// Declare an overload of every client method, which takes headers, query parameters
+    /// and a timeout for the single request
+    pub request_options: bool,
+    /// Resume downloads after the bytes, which the target stream already holds, by
+    /// requesting the rest of the file with a `Range` header
+    pub resumable_downloads: bool,
+    /// Units and code of the client units, which are only compiled for some platforms
+    pub platform_directives: Vec<PlatformDirective>,
+}
+
+/// Units and code of the client units enclosed in `{$IFDEF}`, so a single unit compiles
+/// for several platforms or frameworks, e.g. `Winapi.Windows` for `MSWINDOWS` and
+/// `Posix.Unistd` otherwise.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlatformDirective {
+    /// Conditional symbol, e.g. `MSWINDOWS` or `FRAMEWORK_FMX`
+    pub define: String,
+    /// Units added to the uses of the interface, if the symbol is defined
+    pub units: Vec<String>,
+    /// Units added to the uses of the interface, if the symbol is not defined
+    pub else_units: Vec<String>,
+    /// Code inserted at the start of the implementation, if the symbol is defined
+    pub code: Option<String>,
+    /// Code inserted at the start of the implementation, if the symbol is not defined
+    pub else_code: Option<String>,
+}
+
+/// Standard envelope of the responses, e.g. `{ data, error, meta }`.
+///
+/// A response model is unwrapped, if it has the data property and no other properties
+/// than error and meta.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResponseEnvelope {
+    /// Key of the payload, which is returned by the client methods
+    pub data_key: String,
+    /// Key of the error, which is raised as `ApiException`, if it is set
+    pub error_key: Option<String>,
+    /// Key of the metadata, which is passed out as `pMeta`
+    pub meta_key: Option<String>,
+}
+
+/// Lookup of the enum value for a string
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EnumLookup {
+    /// Compare the string with every value in turn
+    #[default]
+    IfChain,
+    /// Binary search in a constant array of the values sorted by `CompareStr`
+    BinarySearch,
+}
+
+/// Serializer, whose custom attributes name the JSON keys of the model fields
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SerializerAttributes {
+    /// `[JSONName('key')]` of `REST.Json.Types`, read by `TJson`
+    RestJson,
+    /// `[NeonProperty('key')]` of `Neon.Core.Attributes`
+    Neon,
+}
+
+/// How the models are distributed on multiple units
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ModelSplit {
+    /// A unit per tag, which contains the models only used by its operations
+    Tag,
+    /// A unit per `x-namespace` extension of the schemas
+    Namespace,
+}
+
+/// Describes the models and client methods generated for the spec as JSON manifest,
+/// e.g. to compare two versions of a spec without generating their units.
+pub fn spec_manifest(source: &Path, prefix: &Option<String>) -> Result<String, GenerationError> {
+    let openapi_spec = parse_spec(source)?;
+    let (class_types, enum_types, endpoints, _) =
+        collect_models(&openapi_spec, prefix).map_err(spec_error(source))?;
+
+    Ok(manifest::to_manifest(
+        prefix,
+        &class_types,
+        &enum_types,
+        &endpoints,
+    ))
+}
+
+/// Writes a `.http` file with a sample request per operation of the spec, which can be sent
+/// with the REST Client extension of VS Code or the HTTP Client of IntelliJ. Credentials of
+/// the security schemes are file variables to be filled in.
+pub fn generate_http_requests(source: &Path, output_path: &Path) -> Result<(), GenerationError> {
+    let openapi_spec = parse_spec(source)?;
+    let (class_types, enum_types, endpoints, _) =
+        collect_models(&openapi_spec, &None).map_err(spec_error(source))?;
+
+    let content = http_export::to_http_file(&openapi_spec, &endpoints, &class_types, &enum_types);
+
+    std::fs::write(output_path, content).map_err(|e| {
+        GenerationError::Io(format!(
+            "Could not write requests due to following error: \"{e:?}\""
+        ))
+    })
+}
+
+/// Collects the operations of the spec with a form of their parameters and a sample body,
+/// so they can be sent against one of its servers, e.g. to check the collected endpoints.
+pub fn spec_operations(source: &Path) -> Result<SpecOperations, GenerationError> {
+    let openapi_spec = parse_spec(source)?;
+    let (class_types, enum_types, endpoints, _) =
+        collect_models(&openapi_spec, &None).map_err(spec_error(source))?;
+
+    Ok(playground::to_spec_operations(
+        &openapi_spec,
+        &endpoints,
+        &class_types,
+        &enum_types,
+    ))
+}
+
+/// Renders the client method and the models, which the generation with `options` writes for
+/// the operation named `operation`, in memory without writing any unit.
+pub fn preview_operation(
+    source: &Path,
+    operation: &str,
+    options: &ClientOptions,
+) -> Result<OperationPreview, GenerationError> {
+    let openapi_spec = parse_spec(source)?;
+    let tera = render::load_templates().map_err(|e| {
+        GenerationError::Template(format!("Failed to load templates due to {:?}", e))
+    })?;
+
+    preview::preview_operation(&openapi_spec, operation, options, &tera)
+}
+
+/// Writes a unit converting the models of the `old` spec into the models with the same
+/// name of the `new` spec, e.g. `TUserV1ToV2`. Properties with the same key and type are
+/// copied, the remaining ones are marked as TODO. The models of both specs must have been
+/// generated with different prefixes, so their units can be used side by side.
+pub fn generate_openapi_mapping(
+    old: &Path,
+    new: &Path,
+    old_prefix: &Option<String>,
+    new_prefix: &Option<String>,
+    output_path: &Path,
+) -> Result<(), GenerationError> {
+    let old_spec = parse_spec(old)?;
+    let new_spec = parse_spec(new)?;
+    let (old_classes, old_enums, _, _) =
+        collect_models(&old_spec, old_prefix).map_err(spec_error(old))?;
+    let (new_classes, new_enums, _, _) =
+        collect_models(&new_spec, new_prefix).map_err(spec_error(new))?;
 
-        return;
+    let old_prefix = old_prefix.as_deref().unwrap_or_default();
+    let new_prefix = new_prefix.as_deref().unwrap_or_default();
+    let unit_name = output_path
+        .file_stem()
+        .map(|s| s.to_string_lossy())
+        .unwrap_or_default();
+    let content = mapping::MappingContent {
+        unit_name: &unit_name,
+        old_prefix,
+        new_prefix,
+        old_api: format!("{} {}", old_spec.info.title, old_spec.info.version),
+        new_api: format!("{} {}", new_spec.info.title, new_spec.info.version),
+        mappers: mapping::build_mappers(
+            (&old_classes, &old_enums, old_prefix),
+            (&new_classes, &new_enums, new_prefix),
+        ),
+        removed_models: mapping::models_without_counterpart(&old_classes, &new_classes),
+        added_models: mapping::models_without_counterpart(&new_classes, &old_classes),
     };
 
-    if !dest.is_dir() {
-        eprintln!("Destination path is not a directory");
+    let tera = render::load_templates().map_err(|e| {
+        GenerationError::Template(format!("Failed to load templates due to {:?}", e))
+    })?;
+    let mut context = tera::Context::from_serialize(content).map_err(|e| {
+        GenerationError::Template(format!("Failed to build template context due to {:?}", e))
+    })?;
+    context.insert("crate_version", env!("CARGO_PKG_VERSION"));
+
+    let unit = tera.render("mapping.pas", &context).map_err(|e| {
+        GenerationError::Template(format!("Failed to render mapping unit due to {:?}", e))
+    })?;
+
+    std::fs::write(output_path, unit).map_err(|e| {
+        GenerationError::Io(format!(
+            "Could not write mapping unit due to following error: \"{e:?}\""
+        ))
+    })
+}
+
+/// Models, endpoints and handlers of the webhooks and callbacks of a spec
+type SpecModels = (
+    Vec<models::ClassType>,
+    Vec<models::EnumType>,
+    Vec<models::Endpoint>,
+    Vec<models::HandlerGroup>,
+);
+
+/// Collects the models, the endpoints and the handlers of the spec, including the inline
+/// models of operations, webhooks and callbacks.
+fn collect_models(spec: &sw4rm_rs::Spec, prefix: &Option<String>) -> Result<SpecModels, String> {
+    let (mut class_types, mut enum_types) = schema_collector::collect_types(spec, prefix)?;
+    let endpoints = endpoint_collector::collect_endpoints(spec, &mut class_types, &mut enum_types)?;
+    let handler_groups =
+        webhook_collector::collect_handler_groups(spec, &mut class_types, &mut enum_types)?;
+
+    Ok((class_types, enum_types, endpoints, handler_groups))
+}
 
-        return;
+/// Maps an error of the collectors, which names the affected part of the spec, to an error
+/// naming the spec file as well.
+fn spec_error(source: &Path) -> impl Fn(String) -> GenerationError + '_ {
+    move |e| {
+        GenerationError::Spec(format!(
+            "Failed to process OpenAPI Spec file at {:?} due to {}",
+            source, e
+        ))
     }
+}
 
-    let openapi_spec = match from_path(source) {
-        Ok(spec) => spec,
-        Err(e) => {
-            eprintln!(
-                "Failed to parse OpenAPI Spec file at {:?} due to {:?}",
-                source, e
-            );
+fn parse_spec(source: &Path) -> Result<sw4rm_rs::Spec, GenerationError> {
+    normalize::from_path(source).map_err(|e| {
+        GenerationError::Spec(format!(
+            "Failed to parse OpenAPI Spec file at {:?} due to {}",
+            source, e
+        ))
+    })
+}
 
-            return;
-        }
+/// Replaces built-in templates by name, e.g. `client.pas` or `models.pas`, for all following
+/// generations of the process. Templates, which are not given, keep their built-in version.
+/// An empty list restores the built-in templates.
+///
+/// The templates are compiled once and shared by all generations instead of being
+/// compiled again for every spec.
+pub fn override_templates(templates: &[(&str, &str)]) -> Result<(), GenerationError> {
+    render::override_templates(templates)
+        .map_err(|e| GenerationError::Template(format!("Failed to load templates due to {:?}", e)))
+}
+
+/// Generates the units of the client and returns the paths of all written units.
+pub fn generate_openapi_client(
+    source: &[PathBuf],
+    dest: &Path,
+    options: &ClientOptions,
+    graph_path: &Option<PathBuf>,
+    manifest_path: &Option<PathBuf>,
+) -> Result<Vec<PathBuf>, GenerationError> {
+    let ClientOptions {
+        prefix,
+        header,
+        emit_support_units,
+        validate_request_bodies,
+        group_by_tag,
+        roots,
+        split_models,
+        response_envelope,
+        log_requests,
+        request_options,
+        resumable_downloads,
+        platform_directives,
+        ..
+    } = options;
+
+    let Some(source) = source.first() else {
+        return Err(GenerationError::MissingSource);
     };
 
-    let macros_template_str = include_str!("templates/macros.pas");
-    let client_template_str = include_str!("templates/client.pas");
-    let client_interface_template_str = include_str!("templates/client_interface.pas");
-    let models_template_str = include_str!("templates/models.pas");
+    if !dest.is_dir() {
+        return Err(GenerationError::InvalidDestination);
+    }
 
-    let mut tera = Tera::default();
-    if let Err(e) = tera.add_raw_template("macros.pas", macros_template_str) {
-        eprintln!("Failed to add macros template due to {:?}", e);
+    let openapi_spec = parse_spec(source)?;
 
-        return;
+    let tera = render::load_templates().map_err(|e| {
+        GenerationError::Template(format!("Failed to load templates due to {:?}", e))
+    })?;
+
+    let (mut class_types, mut enum_types, endpoints, handler_groups) =
+        collect_models(&openapi_spec, prefix).map_err(spec_error(source))?;
+    let mut endpoints =
+        pruning::select_endpoints(endpoints, roots).map_err(GenerationError::Spec)?;
+    schema_collector::mark_recursive_properties(&mut class_types);
+
+    if let Some(response_envelope) = response_envelope {
+        envelope::unwrap_responses(
+            &mut endpoints,
+            &handler_groups,
+            &mut class_types,
+            response_envelope,
+        );
     }
-    if let Err(e) = tera.add_raw_template("client.pas", client_template_str) {
-        eprintln!("Failed to add client template due to {:?}", e);
 
-        return;
+    if !roots.is_empty() {
+        pruning::prune_types(
+            &endpoints,
+            &handler_groups,
+            &mut class_types,
+            &mut enum_types,
+        );
     }
-    if let Err(e) = tera.add_raw_template("client_interface.pas", client_interface_template_str) {
-        eprintln!("Failed to add client interface template due to {:?}", e);
 
-        return;
+    if let Some(graph_path) = graph_path {
+        let unit_name = format!("u{}ApiModels", prefix.clone().unwrap_or_default());
+        let graph = graph_export::to_dot(&unit_name, &class_types, &enum_types);
+
+        std::fs::write(graph_path, graph).map_err(|e| {
+            GenerationError::Io(format!("Failed to write dependency graph due to {:?}", e))
+        })?;
     }
-    if let Err(e) = tera.add_raw_template("models.pas", models_template_str) {
-        eprintln!("Failed to add models template due to {:?}", e);
 
-        return;
+    if let Some(manifest_path) = manifest_path {
+        let manifest = manifest::to_manifest(prefix, &class_types, &enum_types, &endpoints);
+
+        std::fs::write(manifest_path, manifest)
+            .map_err(|e| GenerationError::Io(format!("Failed to write manifest due to {:?}", e)))?;
     }
 
-    // TODO: Iterate over all paths and generate endpoints
-    // TODO: Build context for client template
+    let model_units =
+        model_units::split_models(&class_types, &enum_types, &endpoints, *split_models);
+    let model_unit_names = model_units
+        .iter()
+        .map(|u| u.name.clone())
+        .collect::<Vec<_>>();
+    let sensitive_keys = render::sensitive_keys(&class_types);
 
-    let (mut class_types, mut enum_types) = schema_collector::collect_types(&openapi_spec, prefix);
-    let endpoints =
-        endpoint_collector::collect_endpoints(&openapi_spec, &mut class_types, &mut enum_types);
+    let mut units = model_units
+        .iter()
+        .map(|u| render::render_models(&openapi_spec, dest, u, options, &tera))
+        .collect::<Result<Vec<_>, _>>()?;
 
-    render::render_models(
+    units.push(render::render_routes(
         &openapi_spec,
         dest,
-        prefix.clone(),
-        &class_types,
-        &enum_types,
+        prefix,
+        &endpoints,
+        header,
         &tera,
-    );
-    render::render_client_interface(&openapi_spec, dest, prefix.clone(), &endpoints, &tera);
-    render::render_client(&openapi_spec, dest, prefix.clone(), &endpoints, &tera);
+    )?);
+
+    if *group_by_tag {
+        let groups = endpoint_collector::group_by_tag(endpoints);
+
+        for group in &groups {
+            let content = render::ClientContent {
+                endpoints: &group.endpoints,
+                client_name: &group.name,
+                is_group: true,
+                validate_request_bodies: *validate_request_bodies,
+                model_units: &model_unit_names,
+                unwraps_envelopes: group.endpoints.iter().any(|e| e.envelope.is_some()),
+                has_downloads: group.endpoints.iter().any(|e| e.is_binary_response),
+                resumable_downloads: *resumable_downloads,
+                logs_requests: *log_requests,
+                sensitive_keys: &sensitive_keys,
+                request_options: *request_options,
+                platform_directives,
+            };
+
+            units.extend(render::render_client_units(
+                &openapi_spec,
+                dest,
+                prefix,
+                &content,
+                header,
+                &tera,
+            )?);
+        }
+
+        units.extend(render::render_client_facade(
+            &openapi_spec,
+            dest,
+            &groups,
+            options,
+            &tera,
+        )?);
+    } else {
+        let content = render::ClientContent {
+            endpoints: &endpoints,
+            client_name: "Api",
+            is_group: false,
+            validate_request_bodies: *validate_request_bodies,
+            model_units: &model_unit_names,
+            unwraps_envelopes: endpoints.iter().any(|e| e.envelope.is_some()),
+            has_downloads: endpoints.iter().any(|e| e.is_binary_response),
+            resumable_downloads: *resumable_downloads,
+            logs_requests: *log_requests,
+            sensitive_keys: &sensitive_keys,
+            request_options: *request_options,
+            platform_directives,
+        };
+
+        units.extend(render::render_client_units(
+            &openapi_spec,
+            dest,
+            prefix,
+            &content,
+            header,
+            &tera,
+        )?);
+    }
+
+    if !handler_groups.is_empty() {
+        units.push(render::render_webhooks(
+            &openapi_spec,
+            dest,
+            prefix,
+            &handler_groups,
+            &model_unit_names,
+            header,
+            &tera,
+        )?);
+    }
+
+    if *emit_support_units {
+        units.extend(render::render_support_units(
+            &openapi_spec,
+            dest,
+            *log_requests,
+            header,
+            &tera,
+        )?);
+    }
+
+    Ok(units)
 }
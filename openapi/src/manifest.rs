@@ -0,0 +1,207 @@
+use serde_json::{json, Map, Value};
+
+use crate::models::{ClassType, Endpoint, EnumType, Property, Type};
+
+/// Describes the generated models and client methods as JSON, so the next generation can
+/// report the changes.
+///
+/// Every type is listed by its Delphi name with its kind and its members, the properties
+/// of classes mapped to their Delphi type and the variants of enums mapped to their value.
+/// Methods of the client are listed by their name with their Delphi signature as only member,
+/// so a changed parameter or result is reported as a breaking change.
+pub(crate) fn to_manifest(
+    prefix: &Option<String>,
+    class_types: &[ClassType],
+    enum_types: &[EnumType],
+    endpoints: &[Endpoint],
+) -> String {
+    let prefix = prefix.as_deref().unwrap_or_default();
+    let mut types = Map::new();
+
+    for class_type in class_types {
+        let members = class_type
+            .properties
+            .iter()
+            .map(|p| (p.name.clone(), Value::from(property_type(p, prefix))))
+            .collect::<Map<_, _>>();
+
+        types.insert(
+            format!("T{prefix}{}", class_type.name),
+            json!({ "kind": "class", "members": members }),
+        );
+    }
+
+    for enum_type in enum_types {
+        let members = enum_type
+            .variants
+            .iter()
+            .map(|v| (v.name.clone(), Value::from(v.key.clone())))
+            .collect::<Map<_, _>>();
+
+        types.insert(
+            format!("T{prefix}{}", enum_type.name),
+            json!({ "kind": "enum", "members": members }),
+        );
+    }
+
+    for endpoint in endpoints {
+        types.insert(
+            endpoint.name.clone(),
+            json!({ "kind": "method", "members": { "Signature": signature(endpoint, prefix) } }),
+        );
+    }
+
+    serde_json::to_string_pretty(&json!({ "types": types })).unwrap_or_default()
+}
+
+/// Declaration of the method in the client interface
+fn signature(endpoint: &Endpoint, prefix: &str) -> String {
+    let mut params = endpoint
+        .args
+        .iter()
+        .map(|a| {
+            let type_ = Type {
+                name: a.type_name.clone(),
+                is_class: false,
+                is_enum: a.is_enum,
+                is_set: false,
+            };
+
+            format!("p{}: {}", a.name, type_name(&type_, prefix))
+        })
+        .collect::<Vec<_>>();
+
+    if endpoint.request_body.name != "none" {
+        params.push(format!(
+            "pBody: {}",
+            type_name(&endpoint.request_body, prefix)
+        ));
+    }
+
+    if let Some(meta) = endpoint.envelope.as_ref().and_then(|e| e.meta.as_ref()) {
+        params.push(format!(
+            "out pMeta: {}",
+            value_type(&meta.type_, meta.is_list_type, prefix)
+        ));
+    }
+
+    if endpoint.response_type.name == "none" {
+        if endpoint.is_binary_response {
+            params.push(String::from("pTarget: TStream"));
+            params.push(format!("pOnProgress: T{prefix}DownloadProgressEvent = nil"));
+        }
+
+        format!("procedure {}({})", endpoint.name, params.join("; "))
+    } else {
+        format!(
+            "function {}({}): {}",
+            endpoint.name,
+            params.join("; "),
+            value_type(&endpoint.response_type, endpoint.response_is_list, prefix)
+        )
+    }
+}
+
+/// Delphi type of the property, as declared by the `type_name` macro of the templates
+pub(crate) fn property_type(property: &Property, prefix: &str) -> String {
+    match property.is_map_type {
+        true if property.type_.is_class => format!(
+            "TObjectDictionary<String, {}>",
+            type_name(&property.type_, prefix)
+        ),
+        true => format!(
+            "TDictionary<String, {}>",
+            type_name(&property.type_, prefix)
+        ),
+        false => value_type(&property.type_, property.is_list_type, prefix),
+    }
+}
+
+/// Delphi type of a single value or a list of values
+fn value_type(type_: &Type, is_list_type: bool, prefix: &str) -> String {
+    let base_type = type_name(type_, prefix);
+
+    match (is_list_type, type_.is_class) {
+        (true, true) => format!("TObjectList<{base_type}>"),
+        (true, false) => format!("TList<{base_type}>"),
+        _ => base_type,
+    }
+}
+
+/// Delphi type of a single value
+fn type_name(type_: &Type, prefix: &str) -> String {
+    match type_.name.as_str() {
+        _ if type_.is_set => format!("T{prefix}{}Set", type_.name),
+        _ if type_.is_class || type_.is_enum => format!("T{prefix}{}", type_.name),
+        "datetime" => String::from("TDateTime"),
+        "guid" => String::from("TGUID"),
+        name => name.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{endpoint_collector::collect_endpoints, schema_collector::collect_types};
+
+    #[test]
+    fn methods_are_listed_with_their_signature() {
+        let spec = sw4rm_rs::from_reader(
+            r##"
+openapi: 3.0.0
+info:
+  title: Pet Store
+  version: "1.0"
+paths:
+  /pets/{petId}:
+    get:
+      operationId: showPet
+      parameters:
+        - name: petId
+          in: path
+          required: true
+          schema:
+            type: integer
+      responses:
+        "200":
+          description: ok
+          content:
+            application/json:
+              schema:
+                $ref: "#/components/schemas/Pet"
+components:
+  schemas:
+    Pet:
+      type: object
+      properties:
+        name:
+          type: string
+"##
+            .as_bytes(),
+        )
+        .unwrap();
+        let prefix = Some(String::from("Shop"));
+
+        let (mut class_types, mut enum_types) = collect_types(&spec, &prefix).unwrap();
+        let endpoints = collect_endpoints(&spec, &mut class_types, &mut enum_types).unwrap();
+        let manifest = serde_json::from_str::<Value>(&to_manifest(
+            &prefix,
+            &class_types,
+            &enum_types,
+            &endpoints,
+        ))
+        .unwrap();
+
+        assert_eq!(
+            manifest["types"]["ShowPet"],
+            json!({
+                "kind": "method",
+                "members": { "Signature": "function ShowPet(pPetId: integer): TShopShowPet" }
+            })
+        );
+        assert_eq!(
+            manifest["types"]["TShopPet"]["members"]["Name"],
+            json!("string")
+        );
+    }
+}
@@ -0,0 +1,303 @@
+use serde::Serialize;
+
+use crate::{
+    manifest::property_type,
+    models::{ClassType, EnumType, Property},
+};
+
+/// Context of the `mapping.pas` template
+#[derive(Serialize)]
+pub(crate) struct MappingContent<'a> {
+    pub(crate) unit_name: &'a str,
+    pub(crate) old_prefix: &'a str,
+    pub(crate) new_prefix: &'a str,
+    pub(crate) old_api: String,
+    pub(crate) new_api: String,
+    pub(crate) mappers: Vec<Mapper<'a>>,
+    /// Models of the old spec without counterpart in the new spec
+    pub(crate) removed_models: Vec<&'a str>,
+    /// Models of the new spec without counterpart in the old spec
+    pub(crate) added_models: Vec<&'a str>,
+}
+
+/// Conversion of the model of the old spec into the model with the same name of the new spec
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub(crate) struct Mapper<'a> {
+    pub(crate) name: &'a str,
+    /// Keys of the properties with the same type, which are copied as they are
+    pub(crate) copied: Vec<&'a str>,
+    /// Properties of models, which are mapped by the mapper of their model
+    pub(crate) nested: Vec<NestedMapping<'a>>,
+    /// Keys of the properties, whose type changed. They are removed from the JSON.
+    pub(crate) changed: Vec<ChangedProperty<'a>>,
+    /// Properties, which can't be mapped completely
+    pub(crate) todos: Vec<String>,
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub(crate) struct NestedMapping<'a> {
+    pub(crate) key: &'a str,
+    /// Name of the model, whose mapper is used
+    pub(crate) model: &'a str,
+    pub(crate) is_list_type: bool,
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub(crate) struct ChangedProperty<'a> {
+    pub(crate) key: &'a str,
+    pub(crate) old_type: String,
+    pub(crate) new_type: String,
+}
+
+/// Builds the mappers of the models, which have the same name in both specs.
+///
+/// Properties are matched by their JSON key. Properties with the same type are copied,
+/// properties of models are mapped by the mapper of their model. Everything else is left
+/// to the developer and marked as TODO.
+pub(crate) fn build_mappers<'a>(
+    old: (&'a [ClassType], &'a [EnumType], &str),
+    new: (&'a [ClassType], &'a [EnumType], &str),
+) -> Vec<Mapper<'a>> {
+    let (old_classes, old_enums, old_prefix) = old;
+    let (new_classes, new_enums, new_prefix) = new;
+
+    new_classes
+        .iter()
+        .filter_map(|new_class| {
+            let old_class = old_classes.iter().find(|c| c.name == new_class.name)?;
+            let mut mapper = Mapper {
+                name: &new_class.name,
+                copied: vec![],
+                nested: vec![],
+                changed: vec![],
+                todos: vec![],
+            };
+
+            for new_property in &new_class.properties {
+                let Some(old_property) = old_class
+                    .properties
+                    .iter()
+                    .find(|p| p.key == new_property.key)
+                else {
+                    mapper.todos.push(format!(
+                        "\"{}\" of T{new_prefix}{} has no counterpart",
+                        new_property.key, new_class.name
+                    ));
+                    continue;
+                };
+
+                let is_same_model = old_property.type_.is_class
+                    && new_property.type_.is_class
+                    && old_property.type_.name == new_property.type_.name
+                    && old_property.is_list_type == new_property.is_list_type
+                    && !old_property.is_map_type
+                    && !new_property.is_map_type;
+
+                if is_same_model
+                    && old_classes
+                        .iter()
+                        .any(|c| c.name == old_property.type_.name)
+                    && new_classes
+                        .iter()
+                        .any(|c| c.name == new_property.type_.name)
+                {
+                    mapper.nested.push(NestedMapping {
+                        key: &new_property.key,
+                        model: &new_property.type_.name,
+                        is_list_type: new_property.is_list_type,
+                    });
+                } else if is_same_type(old_property, new_property) {
+                    mapper.copied.push(&new_property.key);
+
+                    if let Some(todo) = missing_enum_values(old_property, old_enums, new_enums) {
+                        mapper.todos.push(todo);
+                    }
+                } else {
+                    mapper.changed.push(ChangedProperty {
+                        key: &new_property.key,
+                        old_type: property_type(old_property, old_prefix),
+                        new_type: property_type(new_property, new_prefix),
+                    });
+                }
+            }
+
+            for old_property in &old_class.properties {
+                if !new_class
+                    .properties
+                    .iter()
+                    .any(|p| p.key == old_property.key)
+                {
+                    mapper.todos.push(format!(
+                        "\"{}\" of T{old_prefix}{} has no counterpart",
+                        old_property.key, old_class.name
+                    ));
+                }
+            }
+
+            Some(mapper)
+        })
+        .collect()
+}
+
+/// Names of the models, whose name isn't used by any of the `others`
+pub(crate) fn models_without_counterpart<'a>(
+    classes: &'a [ClassType],
+    others: &[ClassType],
+) -> Vec<&'a str> {
+    classes
+        .iter()
+        .filter(|c| !others.iter().any(|o| o.name == c.name))
+        .map(|c| c.name.as_str())
+        .collect()
+}
+
+/// Both properties hold the same JSON, enums are compared by name only
+fn is_same_type(old: &Property, new: &Property) -> bool {
+    old.type_ == new.type_
+        && old.is_list_type == new.is_list_type
+        && old.is_map_type == new.is_map_type
+}
+
+/// Describes the values of the old enum of the property, which the new enum doesn't define
+fn missing_enum_values(
+    property: &Property,
+    old_enums: &[EnumType],
+    new_enums: &[EnumType],
+) -> Option<String> {
+    if !property.type_.is_enum {
+        return None;
+    }
+
+    let old_enum = old_enums.iter().find(|e| e.name == property.type_.name)?;
+    let new_enum = new_enums.iter().find(|e| e.name == property.type_.name)?;
+    let missing = old_enum
+        .variants
+        .iter()
+        .filter(|v| !new_enum.variants.iter().any(|n| n.key == v.key))
+        .map(|v| v.key.as_str())
+        .collect::<Vec<&str>>();
+
+    match missing.is_empty() {
+        true => None,
+        false => Some(format!(
+            "\"{}\" may hold the values {}, which the new enum doesn't define",
+            property.key,
+            missing.join(", ")
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Constraints, EnumVariant, Type};
+
+    fn property(key: &str, type_name: &str, is_class: bool, is_list_type: bool) -> Property {
+        Property {
+            name: key.to_owned(),
+            type_: Type {
+                name: type_name.to_owned(),
+                is_class,
+                is_enum: type_name == "Status",
+                is_set: false,
+            },
+            key: key.to_owned(),
+            is_list_type,
+            is_map_type: false,
+            is_owned: is_class || is_list_type,
+            deprecation_comment: vec![],
+            is_recursive: false,
+            is_nullable: false,
+            is_sensitive: false,
+            example: None,
+            constraints: Constraints::default(),
+        }
+    }
+
+    fn class_type(name: &str, properties: Vec<Property>) -> ClassType {
+        ClassType {
+            name: name.to_owned(),
+            properties,
+            needs_destructor: false,
+            has_constraints: false,
+            namespace: None,
+        }
+    }
+
+    fn status(keys: &[&str]) -> EnumType {
+        EnumType {
+            is_flags: false,
+            name: String::from("Status"),
+            variants: keys
+                .iter()
+                .map(|k| EnumVariant {
+                    name: format!("Status{k}"),
+                    key: (*k).to_owned(),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn properties_are_matched_by_key() {
+        let old_classes = vec![
+            class_type(
+                "User",
+                vec![
+                    property("id", "integer", false, false),
+                    property("age", "integer", false, false),
+                    property("status", "Status", false, false),
+                    property("pets", "Pet", true, true),
+                    property("legacy", "string", false, false),
+                ],
+            ),
+            class_type("Pet", vec![property("name", "string", false, false)]),
+        ];
+        let new_classes = vec![
+            class_type(
+                "User",
+                vec![
+                    property("id", "integer", false, false),
+                    property("age", "string", false, false),
+                    property("status", "Status", false, false),
+                    property("pets", "Pet", true, true),
+                    property("email", "string", false, false),
+                ],
+            ),
+            class_type("Pet", vec![property("name", "string", false, false)]),
+        ];
+        let old_enums = vec![status(&["active", "banned"])];
+        let new_enums = vec![status(&["active"])];
+
+        let mappers = build_mappers(
+            (&old_classes, &old_enums, "V1"),
+            (&new_classes, &new_enums, "V2"),
+        );
+
+        assert_eq!(
+            mappers[0],
+            Mapper {
+                name: "User",
+                copied: vec!["id", "status"],
+                nested: vec![NestedMapping {
+                    key: "pets",
+                    model: "Pet",
+                    is_list_type: true,
+                }],
+                changed: vec![ChangedProperty {
+                    key: "age",
+                    old_type: String::from("integer"),
+                    new_type: String::from("string"),
+                }],
+                todos: vec![
+                    String::from(
+                        "\"status\" may hold the values banned, which the new enum doesn't define"
+                    ),
+                    String::from("\"email\" of TV2User has no counterpart"),
+                    String::from("\"legacy\" of TV1User has no counterpart"),
+                ],
+            }
+        );
+        assert_eq!(mappers[1].copied, vec!["name"]);
+    }
+}
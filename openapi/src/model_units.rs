@@ -0,0 +1,253 @@
+use std::collections::{BTreeSet, HashMap};
+
+use crate::{
+    endpoint_collector::{group_name, sanitize_operation_id},
+    models::{ClassType, Endpoint, EnumType},
+    pruning::reachable_types,
+    ModelSplit,
+};
+
+/// Unit of the models, which are not specific to a tag or namespace
+pub(crate) const SHARED_UNIT: &str = "ApiModels";
+/// Unit of all enums, used when the models are split
+pub(crate) const ENUMS_UNIT: &str = "ApiEnums";
+
+/// Models unit, its name is used without `u` and prefix, e.g. `ApiModels`
+pub(crate) struct ModelUnit<'a> {
+    pub(crate) name: String,
+    pub(crate) class_types: Vec<&'a ClassType>,
+    pub(crate) enum_types: Vec<&'a EnumType>,
+    /// Models units used by this unit
+    pub(crate) uses: Vec<String>,
+    /// Declares the exception raised by `Validate`
+    pub(crate) declares_exception: bool,
+}
+
+/// Distributes the models on units.
+///
+/// Without split all models end up in `ApiModels`. Otherwise enums are declared in `ApiEnums`
+/// and every tag or namespace gets a unit `{Group}Models`. A model, which is used by several
+/// tags or by a model of another unit, moves to the shared `ApiModels` together with all
+/// models it uses. So a group unit only uses the shared units and the units never form a cycle.
+pub(crate) fn split_models<'a>(
+    class_types: &'a [ClassType],
+    enum_types: &'a [EnumType],
+    endpoints: &[Endpoint],
+    split: Option<ModelSplit>,
+) -> Vec<ModelUnit<'a>> {
+    let Some(split) = split else {
+        return vec![ModelUnit {
+            name: String::from(SHARED_UNIT),
+            class_types: class_types.iter().collect(),
+            enum_types: enum_types.iter().collect(),
+            uses: vec![],
+            declares_exception: true,
+        }];
+    };
+
+    let mut groups = match split {
+        ModelSplit::Tag => groups_by_tag(class_types, endpoints),
+        ModelSplit::Namespace => class_types
+            .iter()
+            .filter_map(|c| {
+                c.namespace
+                    .as_deref()
+                    .map(sanitize_operation_id)
+                    .filter(|n| !n.is_empty())
+                    .map(|n| (c.name.as_str(), n))
+            })
+            .collect::<HashMap<_, _>>(),
+    };
+
+    // A group named `Api` would clash with the shared unit and is merged into it
+    groups.retain(|_, g| g != "Api");
+
+    while let Some(name) = class_types.iter().find_map(|c| {
+        c.properties.iter().find_map(|p| {
+            groups
+                .get(p.type_.name.as_str())
+                .filter(|g| groups.get(c.name.as_str()) != Some(*g))
+                .map(|_| p.type_.name.as_str())
+        })
+    }) {
+        groups.remove(name);
+    }
+
+    let group_names = groups.values().cloned().collect::<BTreeSet<_>>();
+    let shared_uses = vec![String::from(ENUMS_UNIT)];
+    let group_uses = vec![String::from(ENUMS_UNIT), String::from(SHARED_UNIT)];
+
+    let mut units = vec![
+        ModelUnit {
+            name: String::from(ENUMS_UNIT),
+            class_types: vec![],
+            enum_types: enum_types.iter().collect(),
+            uses: vec![],
+            declares_exception: false,
+        },
+        ModelUnit {
+            name: String::from(SHARED_UNIT),
+            class_types: class_types
+                .iter()
+                .filter(|c| !groups.contains_key(c.name.as_str()))
+                .collect(),
+            enum_types: vec![],
+            uses: shared_uses,
+            declares_exception: true,
+        },
+    ];
+
+    units.extend(group_names.into_iter().map(|group| {
+        ModelUnit {
+            class_types: class_types
+                .iter()
+                .filter(|c| groups.get(c.name.as_str()) == Some(&group))
+                .collect(),
+            name: format!("{group}Models"),
+            enum_types: vec![],
+            uses: group_uses.clone(),
+            declares_exception: false,
+        }
+    }));
+
+    units
+}
+
+/// Assigns every model, which is only used by the endpoints of a single tag, to this tag.
+fn groups_by_tag<'a>(
+    class_types: &'a [ClassType],
+    endpoints: &[Endpoint],
+) -> HashMap<&'a str, String> {
+    let mut endpoints_by_group = HashMap::<String, Vec<&Endpoint>>::new();
+
+    for endpoint in endpoints {
+        endpoints_by_group
+            .entry(group_name(endpoint))
+            .or_default()
+            .push(endpoint);
+    }
+
+    let reachable_by_group = endpoints_by_group
+        .into_iter()
+        .map(|(group, endpoints)| (group, reachable_types(endpoints.into_iter(), class_types)))
+        .collect::<Vec<_>>();
+
+    class_types
+        .iter()
+        .filter_map(|c| {
+            let mut using_groups = reachable_by_group
+                .iter()
+                .filter(|(_, reachable)| reachable.contains(&c.name));
+
+            match (using_groups.next(), using_groups.next()) {
+                (Some((group, _)), None) => Some((c.name.as_str(), group.clone())),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::endpoint_collector;
+
+    const SPEC: &str = r##"
+openapi: 3.0.0
+info:
+  title: Shop
+  version: "1.0"
+paths:
+  /orders:
+    get:
+      operationId: listOrders
+      tags: [orders]
+      responses:
+        "200":
+          description: ok
+          content:
+            application/json:
+              schema:
+                type: object
+                properties:
+                  orders:
+                    type: array
+                    items:
+                      $ref: "#/components/schemas/Order"
+  /users:
+    get:
+      operationId: listUsers
+      tags: [users]
+      responses:
+        "200":
+          description: ok
+          content:
+            application/json:
+              schema:
+                type: object
+                properties:
+                  users:
+                    type: array
+                    items:
+                      $ref: "#/components/schemas/User"
+components:
+  schemas:
+    Order:
+      type: object
+      properties:
+        buyers:
+          type: array
+          items:
+            $ref: "#/components/schemas/User"
+        lines:
+          type: array
+          items:
+            $ref: "#/components/schemas/Line"
+    Line:
+      type: object
+      properties:
+        sku:
+          type: string
+    User:
+      type: object
+      properties:
+        status:
+          type: string
+          enum: [active, locked]
+"##;
+
+    #[test]
+    fn models_used_by_several_tags_are_shared() {
+        let spec = sw4rm_rs::from_reader(SPEC.as_bytes()).unwrap();
+        let (mut class_types, mut enum_types) =
+            crate::schema_collector::collect_types(&spec, &None).unwrap();
+        let endpoints =
+            endpoint_collector::collect_endpoints(&spec, &mut class_types, &mut enum_types)
+                .unwrap();
+
+        let units = split_models(&class_types, &enum_types, &endpoints, Some(ModelSplit::Tag));
+        let summary = units
+            .iter()
+            .map(|u| {
+                let types = u
+                    .class_types
+                    .iter()
+                    .map(|c| c.name.as_str())
+                    .chain(u.enum_types.iter().map(|e| e.name.as_str()))
+                    .collect::<Vec<_>>();
+
+                format!("{}: {}", u.name, types.join(", "))
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            summary,
+            [
+                "ApiEnums: Status",
+                "ApiModels: User",
+                "OrdersModels: Line, Order, ListOrders",
+                "UsersModels: ListUsers"
+            ]
+        );
+    }
+}
@@ -5,6 +5,10 @@ pub(crate) struct ClassType {
     pub(crate) name: String,
     pub(crate) properties: Vec<Property>,
     pub(crate) needs_destructor: bool,
+    /// At least one property has a constraint, which is checked by `Validate`
+    pub(crate) has_constraints: bool,
+    /// `x-namespace` of the schema
+    pub(crate) namespace: Option<String>,
 }
 
 #[derive(Serialize, Eq, PartialEq)]
@@ -13,12 +17,53 @@ pub(crate) struct Property {
     pub(crate) type_: Type,
     pub(crate) key: String,
     pub(crate) is_list_type: bool,
+    /// Object with `additionalProperties`, whose values are kept in a dictionary by their key
+    pub(crate) is_map_type: bool,
+    /// Value is freed by the model
+    pub(crate) is_owned: bool,
+    /// Comment of a deprecated property, empty if the property isn't deprecated
+    pub(crate) deprecation_comment: Vec<String>,
+    /// Class of the property references the model again, the value may be missing
+    pub(crate) is_recursive: bool,
+    /// Property is `nullable`, an explicit `null` is kept apart from a missing key
+    pub(crate) is_nullable: bool,
+    /// Value is marked `x-sensitive` and redacted in logged bodies
+    pub(crate) is_sensitive: bool,
+    /// `example` of the schema, used for the sample requests
+    pub(crate) example: Option<serde_json::Value>,
+    pub(crate) constraints: Constraints,
+}
+
+/// Constraints of a property, which are checked by the generated `Validate` method
+#[derive(Serialize, Eq, PartialEq, Default)]
+pub(crate) struct Constraints {
+    /// Property is listed in `required` of its schema
+    pub(crate) is_required: bool,
+    pub(crate) min_length: Option<u64>,
+    pub(crate) max_length: Option<u64>,
+    /// Regular expression with quotes escaped for a Delphi string literal
+    pub(crate) pattern: Option<String>,
+    pub(crate) minimum: Option<i64>,
+    pub(crate) maximum: Option<i64>,
+}
+
+impl Constraints {
+    pub(crate) const fn is_empty(&self) -> bool {
+        !self.is_required
+            && self.min_length.is_none()
+            && self.max_length.is_none()
+            && self.pattern.is_none()
+            && self.minimum.is_none()
+            && self.maximum.is_none()
+    }
 }
 
 #[derive(Serialize, Eq, PartialEq)]
 pub(crate) struct EnumType {
     pub(crate) name: String,
     pub(crate) variants: Vec<EnumVariant>,
+    /// `x-flags`, arrays of the enum are sets of the flags
+    pub(crate) is_flags: bool,
 }
 
 #[derive(Serialize, Eq, PartialEq)]
@@ -30,21 +75,91 @@ pub(crate) struct EnumVariant {
 #[derive(Serialize, Eq, PartialEq)]
 pub(crate) struct Endpoint {
     pub(crate) name: String,
+    /// operationId of the operation as defined by the spec
+    pub(crate) operation_id: Option<String>,
     pub(crate) response_type: Type,
+    /// The payload of an unwrapped envelope is a list of `response_type`
+    pub(crate) response_is_list: bool,
+    /// Envelope wrapped around the response, whose payload is returned
+    pub(crate) envelope: Option<Envelope>,
+    pub(crate) is_binary_response: bool,
     pub(crate) args: Vec<EndpointArg>,
     pub(crate) method: String,
     pub(crate) path: String,
     pub(crate) status_codes: Vec<Response>,
+    /// Success responses have different schemas, `response_type` is a result class with a
+    /// constructor and a `TryGet` accessor per success status code
+    pub(crate) has_result_wrapper: bool,
     pub(crate) request_body: Type,
+    pub(crate) request_body_encoding: BodyEncoding,
+    /// Literal parts and placeholders of the path, empty for webhooks and callbacks
+    pub(crate) path_segments: Vec<PathSegment>,
+    /// First tag of the operation, used to group the endpoints into clients
+    pub(crate) tag: Option<String>,
+    /// Comment of a deprecated operation, empty if the operation isn't deprecated
+    pub(crate) deprecation_comment: Vec<String>,
+}
+
+/// Keys of an envelope response, see [`crate::ResponseEnvelope`]
+#[derive(Serialize, Eq, PartialEq)]
+pub(crate) struct Envelope {
+    pub(crate) data_key: String,
+    /// Key of the error, which is raised, if the envelope has an error property
+    pub(crate) error_key: Option<String>,
+    /// Metadata passed out as `pMeta`, if the envelope has a meta property
+    pub(crate) meta: Option<EnvelopeProperty>,
 }
 
 #[derive(Serialize, Eq, PartialEq)]
+pub(crate) struct EnvelopeProperty {
+    pub(crate) key: String,
+    pub(crate) type_: Type,
+    pub(crate) is_list_type: bool,
+}
+
+#[derive(Clone, Serialize, Eq, PartialEq)]
 pub(crate) struct EndpointArg {
     pub(crate) name: String,
+    /// Name of the parameter in the spec
+    pub(crate) key: String,
     pub(crate) type_name: String,
+    pub(crate) is_enum: bool,
     pub(crate) arg_type: String,
     pub(crate) is_required: bool,
     pub(crate) default_value: String,
+    /// Delphi literal of the default value, declared as default of the argument
+    pub(crate) default_arg: Option<String>,
+    /// Enum values of a query parameter, which isn't typed as enum
+    pub(crate) allowed_values: Vec<AllowedValue>,
+}
+
+/// Enum value of a query parameter, which is checked by the client
+#[derive(Clone, Serialize, Eq, PartialEq)]
+pub(crate) struct AllowedValue {
+    /// Value as sent in the query
+    pub(crate) value: String,
+    /// Delphi literal of the value
+    pub(crate) literal: String,
+}
+
+/// Content type, in which the request body is sent
+#[derive(Serialize, Default, Clone, Copy, Debug, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum BodyEncoding {
+    /// `application/json`, the body is a model
+    #[default]
+    Json,
+    /// `application/x-www-form-urlencoded`, the body is a `TStrings` of `Name=Value` pairs
+    FormUrlEncoded,
+    /// `text/plain`, the body is a `String`
+    PlainText,
+}
+
+/// Part of an endpoint path, either literal text or a placeholder filled by a path parameter
+#[derive(Serialize, Eq, PartialEq)]
+pub(crate) struct PathSegment {
+    pub(crate) literal: String,
+    pub(crate) arg: Option<EndpointArg>,
 }
 
 #[derive(Serialize, Eq, PartialEq)]
@@ -54,11 +169,13 @@ pub(crate) struct Response {
     pub(crate) is_list_type: bool,
 }
 
-#[derive(Serialize, Eq, PartialEq)]
+#[derive(Clone, Serialize, Eq, PartialEq)]
 pub(crate) struct Type {
     pub(crate) name: String,
     pub(crate) is_class: bool,
     pub(crate) is_enum: bool,
+    /// Set of the flags of an enum with `x-flags`, which is read from and written to a JSON array
+    pub(crate) is_set: bool,
 }
 
 impl Default for Type {
@@ -67,6 +184,21 @@ impl Default for Type {
             name: "none".to_string(),
             is_class: false,
             is_enum: false,
+            is_set: false,
         }
     }
 }
+
+/// Endpoints sharing a tag, which are generated as a separate client
+#[derive(Serialize, Eq, PartialEq)]
+pub(crate) struct ClientGroup {
+    pub(crate) name: String,
+    pub(crate) endpoints: Vec<Endpoint>,
+}
+
+/// Operations the API calls on the consumer, e.g. webhooks or callbacks
+#[derive(Serialize, Eq, PartialEq)]
+pub(crate) struct HandlerGroup {
+    pub(crate) name: String,
+    pub(crate) operations: Vec<Endpoint>,
+}
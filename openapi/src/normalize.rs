@@ -0,0 +1,666 @@
+use std::{fs::File, path::Path};
+
+use serde_yaml::{Mapping, Sequence, Value};
+use sw4rm_rs::Spec;
+
+/// Operations of a path item
+const OPERATIONS: [&str; 7] = ["get", "put", "post", "delete", "options", "head", "patch"];
+
+/// Keywords of a Swagger 2.0 parameter, which belong to the schema in OpenAPI 3
+const PARAMETER_SCHEMA_KEYWORDS: [&str; 14] = [
+    "type",
+    "format",
+    "items",
+    "enum",
+    "default",
+    "maximum",
+    "exclusiveMaximum",
+    "minimum",
+    "exclusiveMinimum",
+    "maxLength",
+    "minLength",
+    "pattern",
+    "maxItems",
+    "minItems",
+];
+
+/// Reads a spec and normalizes it with `normalize`.
+pub(crate) fn from_path(path: &Path) -> Result<Spec, String> {
+    let file = File::open(path).map_err(|e| format!("{e:?}"))?;
+    let mut spec: Value = serde_yaml::from_reader(file).map_err(|e| format!("{e:?}"))?;
+
+    normalize(&mut spec);
+
+    serde_yaml::from_value(spec).map_err(|e| format!("{e:?}"))
+}
+
+/// Converts the parts of a spec, which are not understood by the collectors, to OpenAPI 3.0.
+///
+/// Swagger 2.0 specs are converted as a whole: definitions become component schemas,
+/// body and form parameters become request bodies and response schemas become content.
+/// Of OpenAPI 3.1 type arrays with `null`, `const` and `examples` are mapped to
+/// `nullable`, a single valued `enum` and `example`.
+pub(crate) fn normalize(spec: &mut Value) {
+    if let Some(root) = spec.as_mapping_mut() {
+        if root.contains_key("swagger") {
+            convert_swagger_2(root);
+        }
+    }
+
+    normalize_schemas(spec);
+}
+
+fn normalize_schemas(value: &mut Value) {
+    match value {
+        Value::Mapping(mapping) => {
+            normalize_schema(mapping);
+
+            for (_, v) in mapping.iter_mut() {
+                normalize_schemas(v);
+            }
+        }
+        Value::Sequence(sequence) => {
+            for v in sequence {
+                normalize_schemas(v);
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Maps the OpenAPI 3.1 keywords of a schema. Properties named like a keyword hold a
+/// schema and not a scalar or sequence, so they are left untouched.
+fn normalize_schema(schema: &mut Mapping) {
+    if let Some(Value::Sequence(types)) = schema.get("type") {
+        let is_nullable = types.iter().any(|t| t.as_str() == Some("null"));
+        let schema_type = types.iter().find(|t| t.as_str() != Some("null")).cloned();
+
+        schema.remove("type");
+        if let Some(schema_type) = schema_type {
+            schema.insert("type".into(), schema_type);
+        }
+        if is_nullable {
+            schema.insert("nullable".into(), Value::Bool(true));
+        }
+    }
+
+    if schema.get("const").is_some_and(|c| !c.is_mapping()) && !schema.contains_key("enum") {
+        if let Some(value) = schema.remove("const") {
+            schema.insert("enum".into(), Value::Sequence(vec![value]));
+        }
+    }
+
+    if let Some(Value::Sequence(examples)) = schema.get("examples") {
+        let example = examples.first().cloned();
+
+        schema.remove("examples");
+        if let Some(example) = example.filter(|_| !schema.contains_key("example")) {
+            schema.insert("example".into(), example);
+        }
+    }
+
+    if schema.get("type").and_then(Value::as_str) == Some("file") {
+        schema.insert("type".into(), "string".into());
+        schema.insert("format".into(), "binary".into());
+    }
+}
+
+fn convert_swagger_2(root: &mut Mapping) {
+    root.remove("swagger");
+    root.insert("openapi".into(), "3.0.3".into());
+
+    let consumes = take_media_types(root, "consumes");
+    let produces = take_media_types(root, "produces");
+
+    if let Some(server) = server_url(root) {
+        let mut server_object = Mapping::new();
+        server_object.insert("url".into(), server.into());
+        root.insert(
+            "servers".into(),
+            Value::Sequence(vec![server_object.into()]),
+        );
+    }
+
+    let global_parameters = root
+        .get("parameters")
+        .and_then(Value::as_mapping)
+        .cloned()
+        .unwrap_or_default();
+
+    let mut components = Mapping::new();
+    for (from, to) in [
+        ("definitions", "schemas"),
+        ("parameters", "parameters"),
+        ("responses", "responses"),
+        ("securityDefinitions", "securitySchemes"),
+    ] {
+        if let Some(Value::Mapping(mut items)) = root.remove(from) {
+            for (_, item) in items.iter_mut() {
+                if let Some(item) = item.as_mapping_mut() {
+                    match to {
+                        "parameters" => convert_parameter(item),
+                        "responses" => convert_response(item, &produces),
+                        _ => (),
+                    }
+                }
+            }
+
+            components.insert(to.into(), Value::Mapping(items));
+        }
+    }
+    if !components.is_empty() {
+        root.insert("components".into(), Value::Mapping(components));
+    }
+
+    if let Some(Value::Mapping(paths)) = root.get_mut("paths") {
+        for (_, path_item) in paths.iter_mut() {
+            let Some(path_item) = path_item.as_mapping_mut() else {
+                continue;
+            };
+
+            // `body` and `formData` parameters of the path item become request bodies of
+            // its operations, so all path parameters are moved into the operations.
+            let path_parameters = match path_item.remove("parameters") {
+                Some(Value::Sequence(parameters)) => parameters,
+                _ => Sequence::new(),
+            };
+
+            for operation in OPERATIONS {
+                if let Some(Value::Mapping(operation)) = path_item.get_mut(operation) {
+                    inherit_path_parameters(operation, &path_parameters, &global_parameters);
+                    convert_operation(operation, &global_parameters, &consumes, &produces);
+                }
+            }
+        }
+    }
+
+    rewrite_references(root);
+}
+
+fn take_media_types(mapping: &mut Mapping, key: &str) -> Vec<String> {
+    match mapping.remove(key) {
+        Some(Value::Sequence(types)) => types
+            .iter()
+            .filter_map(|t| t.as_str().map(str::to_owned))
+            .collect(),
+        _ => vec![],
+    }
+}
+
+fn server_url(root: &mut Mapping) -> Option<String> {
+    let host = root.remove("host")?.as_str()?.to_owned();
+    let base_path = root
+        .remove("basePath")
+        .and_then(|b| b.as_str().map(str::to_owned))
+        .unwrap_or_default();
+    let scheme = match root.remove("schemes") {
+        Some(Value::Sequence(schemes)) => schemes
+            .iter()
+            .filter_map(Value::as_str)
+            .find(|s| *s == "https")
+            .or_else(|| schemes.first().and_then(Value::as_str))
+            .unwrap_or("https")
+            .to_owned(),
+        _ => String::from("https"),
+    };
+
+    Some(format!("{scheme}://{host}{base_path}"))
+}
+
+fn convert_operation(
+    operation: &mut Mapping,
+    global_parameters: &Mapping,
+    consumes: &[String],
+    produces: &[String],
+) {
+    let consumes = media_types_or(take_media_types(operation, "consumes"), consumes);
+    let produces = media_types_or(take_media_types(operation, "produces"), produces);
+
+    let mut parameters = Sequence::new();
+    let mut form_properties = Mapping::new();
+    let mut form_required = Sequence::new();
+    let mut has_file = false;
+
+    if let Some(Value::Sequence(original)) = operation.remove("parameters") {
+        for parameter in original {
+            let Some(mut resolved) = resolve_parameter(&parameter, global_parameters) else {
+                parameters.push(parameter);
+                continue;
+            };
+
+            match resolved.get("in").and_then(Value::as_str) {
+                Some("body") => {
+                    let mut request_body = Mapping::new();
+                    for key in ["description", "required"] {
+                        if let Some(value) = resolved.remove(key) {
+                            request_body.insert(key.into(), value);
+                        }
+                    }
+
+                    let schema = resolved.remove("schema").unwrap_or_default();
+                    request_body.insert("content".into(), content(&consumes, &schema));
+                    operation.insert("requestBody".into(), Value::Mapping(request_body));
+                }
+                Some("formData") => {
+                    let name = resolved.get("name").cloned().unwrap_or_default();
+                    if resolved.get("required").and_then(Value::as_bool) == Some(true) {
+                        form_required.push(name.clone());
+                    }
+                    has_file |= resolved.get("type").and_then(Value::as_str) == Some("file");
+
+                    convert_parameter(&mut resolved);
+                    form_properties.insert(name, resolved.remove("schema").unwrap_or_default());
+                }
+                _ if parameter.get("$ref").is_some() => parameters.push(parameter),
+                _ => {
+                    convert_parameter(&mut resolved);
+                    parameters.push(Value::Mapping(resolved));
+                }
+            }
+        }
+    }
+
+    if !parameters.is_empty() {
+        operation.insert("parameters".into(), Value::Sequence(parameters));
+    }
+
+    if !form_properties.is_empty() {
+        let mut schema = Mapping::new();
+        schema.insert("type".into(), "object".into());
+        schema.insert("properties".into(), Value::Mapping(form_properties));
+        if !form_required.is_empty() {
+            schema.insert("required".into(), Value::Sequence(form_required));
+        }
+
+        let media_type = if has_file || consumes.iter().any(|c| c == "multipart/form-data") {
+            "multipart/form-data"
+        } else {
+            "application/x-www-form-urlencoded"
+        };
+
+        let mut request_body = Mapping::new();
+        request_body.insert(
+            "content".into(),
+            content(&[media_type.to_owned()], &Value::Mapping(schema)),
+        );
+        operation.insert("requestBody".into(), Value::Mapping(request_body));
+    }
+
+    if let Some(Value::Mapping(responses)) = operation.get_mut("responses") {
+        for (_, response) in responses.iter_mut() {
+            if let Some(response) = response.as_mapping_mut() {
+                convert_response(response, &produces);
+            }
+        }
+    }
+}
+
+fn media_types_or(types: Vec<String>, default: &[String]) -> Vec<String> {
+    match (types.is_empty(), default.is_empty()) {
+        (false, _) => types,
+        (true, false) => default.to_vec(),
+        (true, true) => vec![String::from("application/json")],
+    }
+}
+
+/// Prepends the parameters of the path item, which the operation doesn't override.
+/// A parameter is overridden by one with the same name and location.
+fn inherit_path_parameters(
+    operation: &mut Mapping,
+    path_parameters: &Sequence,
+    global_parameters: &Mapping,
+) {
+    if path_parameters.is_empty() {
+        return;
+    }
+
+    let key = |parameter: &Value| {
+        resolve_parameter(parameter, global_parameters)
+            .map(|p| (p.get("name").cloned(), p.get("in").cloned()))
+    };
+
+    let own_parameters = match operation.remove("parameters") {
+        Some(Value::Sequence(parameters)) => parameters,
+        _ => Sequence::new(),
+    };
+    let own_keys = own_parameters.iter().filter_map(key).collect::<Vec<_>>();
+
+    let mut parameters = path_parameters
+        .iter()
+        .filter(|p| key(p).is_none_or(|k| !own_keys.contains(&k)))
+        .cloned()
+        .collect::<Sequence>();
+    parameters.extend(own_parameters);
+
+    operation.insert("parameters".into(), Value::Sequence(parameters));
+}
+
+/// Returns the parameter or the global parameter it references.
+fn resolve_parameter(parameter: &Value, global_parameters: &Mapping) -> Option<Mapping> {
+    match parameter.get("$ref").and_then(Value::as_str) {
+        Some(reference) => global_parameters
+            .get(reference.trim_start_matches("#/parameters/"))
+            .and_then(Value::as_mapping)
+            .cloned(),
+        None => parameter.as_mapping().cloned(),
+    }
+}
+
+/// Moves the type keywords of a query, path or header parameter into its schema.
+fn convert_parameter(parameter: &mut Mapping) {
+    if parameter.contains_key("schema") {
+        return;
+    }
+
+    let mut schema = Mapping::new();
+    for key in PARAMETER_SCHEMA_KEYWORDS {
+        if let Some(value) = parameter.remove(key) {
+            schema.insert(key.into(), value);
+        }
+    }
+    parameter.remove("collectionFormat");
+
+    if !schema.is_empty() {
+        parameter.insert("schema".into(), Value::Mapping(schema));
+    }
+}
+
+fn convert_response(response: &mut Mapping, produces: &[String]) {
+    response.remove("examples");
+
+    if let Some(schema) = response.remove("schema") {
+        let produces = media_types_or(produces.to_vec(), &[]);
+        response.insert("content".into(), content(&produces, &schema));
+    }
+
+    if let Some(Value::Mapping(headers)) = response.get_mut("headers") {
+        for (_, header) in headers.iter_mut() {
+            if let Some(header) = header.as_mapping_mut() {
+                convert_parameter(header);
+            }
+        }
+    }
+}
+
+fn content(media_types: &[String], schema: &Value) -> Value {
+    let mut media_type = Mapping::new();
+    media_type.insert("schema".into(), schema.clone());
+
+    Value::Mapping(
+        media_types
+            .iter()
+            .map(|t| (Value::from(t.as_str()), Value::Mapping(media_type.clone())))
+            .collect(),
+    )
+}
+
+fn rewrite_references(value: &mut Mapping) {
+    for (key, v) in value.iter_mut() {
+        match v {
+            Value::String(reference) if key.as_str() == Some("$ref") => {
+                for (from, to) in [
+                    ("#/definitions/", "#/components/schemas/"),
+                    ("#/parameters/", "#/components/parameters/"),
+                    ("#/responses/", "#/components/responses/"),
+                ] {
+                    if let Some(name) = reference.strip_prefix(from) {
+                        *reference = format!("{to}{name}");
+                    }
+                }
+            }
+            Value::Mapping(mapping) => rewrite_references(mapping),
+            Value::Sequence(sequence) => {
+                for mapping in sequence.iter_mut().filter_map(Value::as_mapping_mut) {
+                    rewrite_references(mapping);
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sw4rm_rs::{
+        shared::{SchemaType, StringOrHttpCode},
+        RefOr,
+    };
+
+    use super::*;
+
+    fn normalized(spec: &str) -> Spec {
+        let mut spec: Value = serde_yaml::from_str(spec).unwrap();
+        normalize(&mut spec);
+
+        serde_yaml::from_value(spec).unwrap()
+    }
+
+    #[test]
+    fn swagger_2_is_converted_to_openapi_3() {
+        let spec = normalized(
+            r##"
+swagger: "2.0"
+info:
+  title: Pet Store
+  version: "1.0"
+host: petstore.example.com
+basePath: /v1
+schemes: [http, https]
+consumes: [application/json]
+produces: [application/json]
+paths:
+  /pets:
+    post:
+      operationId: createPet
+      parameters:
+        - name: limit
+          in: query
+          type: integer
+        - name: pet
+          in: body
+          required: true
+          schema:
+            $ref: "#/definitions/Pet"
+      responses:
+        200:
+          description: ok
+          schema:
+            $ref: "#/definitions/Pet"
+definitions:
+  Pet:
+    type: object
+    properties:
+      name:
+        type: string
+"##,
+        );
+
+        assert_eq!(spec.servers[0].url, "https://petstore.example.com/v1");
+        assert!(spec.schemas().contains_key("Pet"));
+
+        let Some(RefOr::Item(path)) = spec.paths.get("/pets") else {
+            panic!("path should be an item");
+        };
+        let operation = path.post.as_ref().unwrap();
+        assert_eq!(operation.parameters.len(), 1);
+
+        let Some(RefOr::Item(request_body)) = &operation.request_body else {
+            panic!("request body should be an item");
+        };
+        let schema = request_body.content["application/json"].schema.as_ref();
+        assert!(matches!(
+            schema,
+            Some(RefOr::Reference { reference_path }) if reference_path == "#/components/schemas/Pet"
+        ));
+
+        let Some(RefOr::Item(response)) =
+            operation.responses.get(&StringOrHttpCode::StatusCode(200))
+        else {
+            panic!("response should be an item");
+        };
+        assert!(response.content.contains_key("application/json"));
+    }
+
+    #[test]
+    fn swagger_2_body_and_form_parameters_generate_the_same_endpoints() {
+        let swagger = normalized(
+            r##"
+swagger: "2.0"
+info:
+  title: Pet Store
+  version: "1.0"
+paths:
+  /pets/{petId}:
+    parameters:
+      - name: petId
+        in: path
+        required: true
+        type: integer
+      - name: pet
+        in: body
+        required: true
+        schema:
+          $ref: "#/definitions/Pet"
+    put:
+      operationId: updatePet
+      consumes: [application/json]
+      parameters:
+        - name: dryRun
+          in: query
+          type: boolean
+      responses:
+        204:
+          description: ok
+  /login:
+    post:
+      operationId: login
+      consumes: [application/x-www-form-urlencoded]
+      parameters:
+        - name: user
+          in: formData
+          required: true
+          type: string
+      responses:
+        204:
+          description: ok
+definitions:
+  Pet:
+    type: object
+    properties:
+      name:
+        type: string
+"##,
+        );
+        let openapi = normalized(
+            r##"
+openapi: 3.0.3
+info:
+  title: Pet Store
+  version: "1.0"
+paths:
+  /pets/{petId}:
+    parameters:
+      - name: petId
+        in: path
+        required: true
+        schema:
+          type: integer
+    put:
+      operationId: updatePet
+      parameters:
+        - name: dryRun
+          in: query
+          schema:
+            type: boolean
+      requestBody:
+        required: true
+        content:
+          application/json:
+            schema:
+              $ref: "#/components/schemas/Pet"
+      responses:
+        "204":
+          description: ok
+  /login:
+    post:
+      operationId: login
+      requestBody:
+        content:
+          application/x-www-form-urlencoded:
+            schema:
+              type: object
+              properties:
+                user:
+                  type: string
+              required: [user]
+      responses:
+        "204":
+          description: ok
+components:
+  schemas:
+    Pet:
+      type: object
+      properties:
+        name:
+          type: string
+"##,
+        );
+
+        let endpoints = |spec: &Spec| {
+            let (class_types, _, endpoints, _) = crate::collect_models(spec, &None).unwrap();
+
+            serde_json::to_value((class_types, endpoints)).unwrap()
+        };
+
+        assert_eq!(endpoints(&swagger), endpoints(&openapi));
+
+        let (_, _, endpoints, _) = crate::collect_models(&openapi, &None).unwrap();
+        let update_pet = endpoints.iter().find(|e| e.name == "UpdatePet").unwrap();
+
+        assert_eq!(
+            update_pet
+                .args
+                .iter()
+                .map(|a| a.key.as_str())
+                .collect::<Vec<_>>(),
+            ["petId", "dryRun"]
+        );
+    }
+
+    #[test]
+    fn openapi_3_1_keywords_are_mapped() {
+        let spec = normalized(
+            r##"
+openapi: 3.1.0
+info:
+  title: Pet Store
+  version: "1.0"
+components:
+  schemas:
+    Pet:
+      type: object
+      properties:
+        name:
+          type: [string, "null"]
+          examples: [Rex]
+        kind:
+          const: dog
+"##,
+        );
+
+        let Some(RefOr::Item(pet)) = spec.schemas().get("Pet").cloned() else {
+            panic!("Pet should be an item");
+        };
+        let Some(RefOr::Item(name)) = pet.properties.get("name") else {
+            panic!("name should be an item");
+        };
+        assert_eq!(name.schema_type, Some(SchemaType::String));
+        assert_eq!(name.nullable, Some(true));
+        assert_eq!(name.example, Some(serde_json::Value::from("Rex")));
+
+        let Some(RefOr::Item(kind)) = pet.properties.get("kind") else {
+            panic!("kind should be an item");
+        };
+        assert_eq!(kind.enum_values, vec![serde_json::Value::from("dog")]);
+    }
+}
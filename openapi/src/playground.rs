@@ -0,0 +1,423 @@
+use std::fmt::Write;
+
+use sw4rm_rs::Spec;
+
+use crate::{
+    http_export::{self, DEFAULT_BASE_URL},
+    models::{ClassType, Endpoint, EndpointArg, EnumType},
+};
+
+/// Operations of a spec, which are sent interactively with values entered by the user,
+/// e.g. by the spec browser of the cli.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpecOperations {
+    /// Title and version of the spec
+    pub title: String,
+    /// Base URLs of the servers with the defaults of their variables filled in
+    pub servers: Vec<String>,
+    pub operations: Vec<Operation>,
+}
+
+/// Operation of the spec as the generated client method sends it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Operation {
+    /// Name of the client method
+    pub name: String,
+    pub method: String,
+    /// Path of the spec with the path parameters in braces, e.g. `/pets/{petId}`
+    pub path: String,
+    /// Parameters of the operation followed by the credentials of its security requirement
+    pub parameters: Vec<Parameter>,
+    pub body: Option<RequestBody>,
+    /// The operation returns a JSON model
+    pub accepts_json: bool,
+}
+
+/// Value sent with a request, which is entered by the user
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Parameter {
+    /// Name of the parameter or header
+    pub key: String,
+    pub location: ParameterLocation,
+    /// Type of the parameter, e.g. `integer` or the name of an enum
+    pub type_name: String,
+    pub is_required: bool,
+    /// Default value of the parameter or a sample value of its type
+    pub sample: String,
+    /// Values accepted by the API, empty if any value of the type is accepted
+    pub allowed_values: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParameterLocation {
+    Path,
+    Query,
+    Header,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestBody {
+    pub content_type: String,
+    /// Sample matching the model of the body
+    pub sample: String,
+}
+
+/// Request built from the values entered for an operation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Request {
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<String>,
+}
+
+impl Request {
+    /// Request as it is sent, i.e. request line, headers and body
+    pub fn to_raw(&self) -> String {
+        let mut raw = format!("{} {}\n", self.method, self.url);
+
+        for (name, value) in &self.headers {
+            let _ = writeln!(raw, "{name}: {value}");
+        }
+
+        if let Some(body) = &self.body {
+            let _ = write!(raw, "\n{body}");
+        }
+
+        raw
+    }
+}
+
+impl Parameter {
+    /// Checks the entered value against the type and the allowed values of the parameter.
+    /// Empty values of optional parameters are valid, they are left out of the request.
+    pub fn check(&self, value: &str) -> Result<(), String> {
+        if value.is_empty() {
+            return match self.is_required {
+                true => Err(format!("\"{}\" is required", self.key)),
+                false => Ok(()),
+            };
+        }
+
+        if !self.allowed_values.is_empty() && !self.allowed_values.iter().any(|v| v == value) {
+            return Err(format!(
+                "\"{}\" must be one of {}",
+                self.key,
+                self.allowed_values.join(", ")
+            ));
+        }
+
+        let is_valid = match self.type_name.as_str() {
+            "integer" => value.parse::<i64>().is_ok(),
+            "double" => value.parse::<f64>().is_ok(),
+            "boolean" => matches!(value, "true" | "false"),
+            _ => true,
+        };
+
+        match is_valid {
+            true => Ok(()),
+            false => Err(format!(
+                "\"{}\" must be of type {}",
+                self.key, self.type_name
+            )),
+        }
+    }
+}
+
+impl Operation {
+    /// Builds the request for `server` from the `values` of the parameters in the order
+    /// of [`Operation::parameters`]. Path and query values are percent-encoded.
+    pub fn build_request(&self, server: &str, values: &[String], body: Option<&str>) -> Request {
+        let mut path = self.path.clone();
+        let mut query = vec![];
+        let mut headers = vec![];
+
+        for (parameter, value) in self.parameters.iter().zip(values) {
+            match parameter.location {
+                ParameterLocation::Path => {
+                    path = path.replace(&format!("{{{}}}", parameter.key), &percent_encode(value));
+                }
+                _ if value.is_empty() => {}
+                ParameterLocation::Query => query.push(format!(
+                    "{}={}",
+                    percent_encode(&parameter.key),
+                    percent_encode(value)
+                )),
+                ParameterLocation::Header => headers.push((parameter.key.clone(), value.clone())),
+            }
+        }
+
+        let mut url = format!("{}{path}", server.trim_end_matches('/'));
+
+        if !query.is_empty() {
+            url.push('?');
+            url.push_str(&query.join("&"));
+        }
+
+        if self.accepts_json {
+            headers.push((String::from("Accept"), String::from("application/json")));
+        }
+
+        if let (Some(request_body), Some(_)) = (&self.body, body) {
+            headers.push((
+                String::from("Content-Type"),
+                request_body.content_type.clone(),
+            ));
+        }
+
+        Request {
+            method: self.method.clone(),
+            url,
+            headers,
+            body: self.body.as_ref().and(body).map(str::to_owned),
+        }
+    }
+}
+
+pub(crate) fn to_spec_operations(
+    spec: &Spec,
+    endpoints: &[Endpoint],
+    class_types: &[ClassType],
+    enum_types: &[EnumType],
+) -> SpecOperations {
+    let mut servers = spec
+        .servers
+        .iter()
+        .map(|s| {
+            s.variables
+                .iter()
+                .fold(s.url.clone(), |url, (name, variable)| {
+                    url.replace(&format!("{{{name}}}"), &variable.default)
+                })
+                .trim_end_matches('/')
+                .to_owned()
+        })
+        .collect::<Vec<_>>();
+
+    if servers.is_empty() {
+        servers.push(DEFAULT_BASE_URL.to_owned());
+    }
+
+    let operations = endpoints
+        .iter()
+        .filter(|e| !e.path_segments.is_empty())
+        .map(|e| Operation {
+            name: e.name.clone(),
+            method: e.method.clone(),
+            path: e.path.clone(),
+            parameters: e
+                .args
+                .iter()
+                .filter_map(|a| parameter(a, enum_types))
+                .chain(
+                    http_export::auth_placeholders(spec, e)
+                        .into_iter()
+                        .map(|a| Parameter {
+                            key: a.name,
+                            location: match a.in_query {
+                                true => ParameterLocation::Query,
+                                false => ParameterLocation::Header,
+                            },
+                            type_name: String::from("string"),
+                            is_required: true,
+                            sample: a.value.replace("{{", "<").replace("}}", ">"),
+                            allowed_values: vec![],
+                        }),
+                )
+                .collect(),
+            body: http_export::request_body(e, class_types, enum_types).map(
+                |(content_type, sample)| RequestBody {
+                    content_type: content_type.to_owned(),
+                    sample,
+                },
+            ),
+            accepts_json: !e.is_binary_response && e.response_type.name != "none",
+        })
+        .collect();
+
+    SpecOperations {
+        title: format!("{} {}", spec.info.title, spec.info.version),
+        servers,
+        operations,
+    }
+}
+
+/// Parameter of a path or query argument, the body is entered separately
+fn parameter(arg: &EndpointArg, enum_types: &[EnumType]) -> Option<Parameter> {
+    let location = match arg.arg_type.as_str() {
+        "path" => ParameterLocation::Path,
+        "query" => ParameterLocation::Query,
+        _ => return None,
+    };
+
+    let allowed_values = match arg.is_enum {
+        true => enum_types
+            .iter()
+            .find(|e| e.name == arg.type_name)
+            .map(|e| e.variants.iter().map(|v| v.key.clone()).collect())
+            .unwrap_or_default(),
+        false => arg.allowed_values.iter().map(|v| v.value.clone()).collect(),
+    };
+
+    Some(Parameter {
+        key: arg.key.clone(),
+        location,
+        type_name: arg.type_name.clone(),
+        is_required: arg.is_required || location == ParameterLocation::Path,
+        sample: http_export::arg_value(arg, enum_types),
+        allowed_values,
+    })
+}
+
+/// Encodes all characters except the unreserved ones of RFC 3986
+fn percent_encode(value: &str) -> String {
+    value.bytes().fold(String::new(), |mut encoded, b| {
+        if b.is_ascii_alphanumeric() || matches!(b, b'-' | b'.' | b'_' | b'~') {
+            encoded.push(b as char);
+        } else {
+            let _ = write!(encoded, "%{b:02X}");
+        }
+
+        encoded
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::collect_models;
+
+    use super::*;
+
+    const SPEC: &str = r##"
+openapi: 3.0.0
+info:
+  title: Pets
+  version: 1.0.0
+servers:
+  - url: https://{region}.pets.example.com/v1/
+    variables:
+      region:
+        default: eu
+security:
+  - bearer: []
+paths:
+  /pets/{petId}:
+    get:
+      operationId: getPet
+      parameters:
+        - name: petId
+          in: path
+          required: true
+          schema:
+            type: integer
+        - name: sort
+          in: query
+          schema:
+            type: string
+            enum: [asc, desc]
+        - name: status
+          in: query
+          schema:
+            $ref: "#/components/schemas/Status"
+      responses:
+        "200":
+          description: The pet
+          content:
+            application/json:
+              schema:
+                $ref: "#/components/schemas/Pet"
+    put:
+      operationId: updatePet
+      security:
+        - apiKey: []
+      parameters:
+        - name: petId
+          in: path
+          required: true
+          schema:
+            type: string
+      requestBody:
+        content:
+          application/json:
+            schema:
+              $ref: "#/components/schemas/Pet"
+      responses:
+        "204":
+          description: Updated
+components:
+  securitySchemes:
+    bearer:
+      type: http
+      scheme: bearer
+    apiKey:
+      type: apiKey
+      in: query
+      name: key
+  schemas:
+    Status:
+      type: string
+      enum: [available, sold]
+    Pet:
+      type: object
+      properties:
+        name:
+          type: string
+"##;
+
+    fn operations() -> SpecOperations {
+        let spec = sw4rm_rs::from_reader(SPEC.as_bytes()).unwrap();
+        let (class_types, enum_types, endpoints, _) = collect_models(&spec, &None).unwrap();
+
+        to_spec_operations(&spec, &endpoints, &class_types, &enum_types)
+    }
+
+    #[test]
+    fn parameters_are_generated_from_the_schemas() {
+        let operations = operations();
+        let get_pet = &operations.operations[0];
+
+        assert_eq!(operations.servers, ["https://eu.pets.example.com/v1"]);
+        assert_eq!(
+            get_pet
+                .parameters
+                .iter()
+                .map(|p| (p.key.as_str(), p.location, p.allowed_values.join(",")))
+                .collect::<Vec<_>>(),
+            [
+                ("petId", ParameterLocation::Path, String::new()),
+                ("sort", ParameterLocation::Query, String::from("asc,desc")),
+                (
+                    "status",
+                    ParameterLocation::Query,
+                    String::from("available,sold")
+                ),
+                ("Authorization", ParameterLocation::Header, String::new()),
+            ]
+        );
+        assert_eq!(get_pet.parameters[3].sample, "Bearer <token>");
+        assert!(get_pet.parameters[0].check("abc").is_err());
+        assert!(get_pet.parameters[1].check("up").is_err());
+        assert!(get_pet.parameters[1].check("").is_ok());
+    }
+
+    #[test]
+    fn request_contains_the_encoded_values_and_the_body() {
+        let operations = operations();
+        let update_pet = &operations.operations[1];
+        let values = [String::from("a b/c"), String::from("secret&1")];
+
+        let request = update_pet.build_request(
+            &operations.servers[0],
+            &values,
+            Some(&update_pet.body.as_ref().unwrap().sample),
+        );
+
+        assert_eq!(
+            request.to_raw(),
+            "PUT https://eu.pets.example.com/v1/pets/a%20b%2Fc?key=secret%261\n\
+             Content-Type: application/json\n\
+             \n\
+             {\n  \"name\": \"string\"\n}"
+        );
+    }
+}
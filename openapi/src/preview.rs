@@ -0,0 +1,213 @@
+use sw4rm_rs::Spec;
+use tera::Tera;
+
+use crate::{
+    endpoint_collector, envelope, model_units, pruning, render, schema_collector, ClientOptions,
+    GenerationError,
+};
+
+/// Delphi code, which the generation writes for a single operation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OperationPreview {
+    /// Declarations of the method in the client interface, including its overloads
+    pub signature: String,
+    /// Type declarations of the models unit, which only declares the models used by the method
+    pub models: String,
+}
+
+/// Generates the client for the operation named `operation` alone, like with it as the only root,
+/// and renders the client interface and the models unit in memory. The models are previewed as
+/// a single unit, even if the generation splits them.
+pub(crate) fn preview_operation(
+    spec: &Spec,
+    operation: &str,
+    options: &ClientOptions,
+    tera: &Tera,
+) -> Result<OperationPreview, GenerationError> {
+    let (mut class_types, mut enum_types) =
+        schema_collector::collect_types(spec, &options.prefix).map_err(GenerationError::Spec)?;
+    let mut endpoints =
+        endpoint_collector::collect_endpoints(spec, &mut class_types, &mut enum_types)
+            .map_err(GenerationError::Spec)?;
+
+    endpoints.retain(|e| e.name.eq_ignore_ascii_case(operation));
+
+    let Some(name) = endpoints.first().map(|e| e.name.clone()) else {
+        return Err(GenerationError::Spec(format!(
+            "Operation \"{operation}\" is not part of the spec"
+        )));
+    };
+
+    schema_collector::mark_recursive_properties(&mut class_types);
+
+    if let Some(response_envelope) = &options.response_envelope {
+        envelope::unwrap_responses(&mut endpoints, &[], &mut class_types, response_envelope);
+    }
+
+    pruning::prune_types(&endpoints, &[], &mut class_types, &mut enum_types);
+
+    let model_units = model_units::split_models(&class_types, &enum_types, &endpoints, None);
+    let model_unit_names = model_units
+        .iter()
+        .map(|u| u.name.clone())
+        .collect::<Vec<_>>();
+    let sensitive_keys = render::sensitive_keys(&class_types);
+
+    let models = model_units
+        .iter()
+        .map(|u| render::render_models_unit(spec, u, options, tera))
+        .collect::<Result<String, _>>()
+        .map_err(template_error("models"))?;
+
+    let content = render::ClientContent {
+        endpoints: &endpoints,
+        client_name: "Api",
+        is_group: false,
+        validate_request_bodies: options.validate_request_bodies,
+        model_units: &model_unit_names,
+        unwraps_envelopes: endpoints.iter().any(|e| e.envelope.is_some()),
+        has_downloads: endpoints.iter().any(|e| e.is_binary_response),
+        resumable_downloads: options.resumable_downloads,
+        logs_requests: options.log_requests,
+        sensitive_keys: &sensitive_keys,
+        request_options: options.request_options,
+        platform_directives: &options.platform_directives,
+    };
+    let client_interface = render::render_unit(
+        tera,
+        "client_interface.pas",
+        "ApiClientInterface",
+        spec,
+        &options.prefix,
+        &None,
+        &content,
+    )
+    .map_err(template_error("client interface"))?;
+
+    Ok(OperationPreview {
+        signature: method_declarations(&client_interface, &name),
+        models: type_section(&models),
+    })
+}
+
+fn template_error(description: &str) -> impl Fn(tera::Error) -> GenerationError + '_ {
+    move |e| {
+        GenerationError::Template(format!(
+            "Failed to render {description} template due to {:?}",
+            e
+        ))
+    }
+}
+
+/// Lines of the unit, which declare the method `name`
+fn method_declarations(unit: &str, name: &str) -> String {
+    unit.lines()
+        .map(str::trim)
+        .filter(|l| {
+            ["function ", "procedure "].iter().any(|keyword| {
+                l.strip_prefix(keyword)
+                    .and_then(|l| l.strip_prefix(name))
+                    .is_some_and(|rest| rest.starts_with(['(', ';', ':']))
+            })
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Declarations of the interface of the unit, from `type` up to `implementation`
+fn type_section(unit: &str) -> String {
+    unit.lines()
+        .skip_while(|l| *l != "type")
+        .take_while(|l| *l != "implementation")
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim_end()
+        .to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPEC: &str = r##"
+openapi: 3.0.0
+info:
+  title: Pets
+  version: 1.0.0
+paths:
+  /pets/{petId}:
+    get:
+      operationId: getPet
+      parameters:
+        - name: petId
+          in: path
+          required: true
+          schema:
+            type: integer
+      responses:
+        "200":
+          description: The pet
+          content:
+            application/json:
+              schema:
+                $ref: "#/components/schemas/Pet"
+  /stores:
+    get:
+      operationId: listStores
+      responses:
+        "200":
+          description: The stores
+          content:
+            application/json:
+              schema:
+                type: array
+                items:
+                  $ref: "#/components/schemas/Store"
+components:
+  schemas:
+    Pet:
+      type: object
+      properties:
+        name:
+          type: string
+        status:
+          $ref: "#/components/schemas/Status"
+    Status:
+      type: string
+      enum: [available, sold]
+    Store:
+      type: object
+      properties:
+        city:
+          type: string
+"##;
+
+    #[test]
+    fn preview_contains_the_method_and_only_its_models() {
+        let spec = sw4rm_rs::from_reader(SPEC.as_bytes()).unwrap();
+        let options = ClientOptions {
+            prefix: Some(String::from("Pet")),
+            request_options: true,
+            ..ClientOptions::default()
+        };
+        let tera = render::load_templates().unwrap();
+
+        let preview = preview_operation(&spec, "getpet", &options, &tera).unwrap();
+
+        assert!(!preview.models.contains("TPetStore"));
+        insta::assert_snapshot!(format!("{}\n\n{}", preview.signature, preview.models));
+    }
+
+    #[test]
+    fn unknown_operation_is_reported() {
+        let spec = sw4rm_rs::from_reader(SPEC.as_bytes()).unwrap();
+        let tera = render::load_templates().unwrap();
+
+        let error = preview_operation(&spec, "deletePet", &ClientOptions::default(), &tera);
+
+        assert!(matches!(
+            error,
+            Err(GenerationError::Spec(message)) if message.contains("deletePet")
+        ));
+    }
+}
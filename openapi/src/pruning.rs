@@ -0,0 +1,82 @@
+use std::collections::HashSet;
+
+use crate::models::{ClassType, Endpoint, EnumType, HandlerGroup};
+
+/// Keeps the endpoints, whose operation or first tag is listed in `roots`.
+/// Operations are matched by their name, regardless of the case. Without roots every endpoint is kept.
+///
+/// Fails if a root matches neither an operation nor a tag.
+pub(crate) fn select_endpoints(
+    endpoints: Vec<Endpoint>,
+    roots: &[String],
+) -> Result<Vec<Endpoint>, String> {
+    if roots.is_empty() {
+        return Ok(endpoints);
+    }
+
+    let is_selected_by = |endpoint: &Endpoint, root: &String| {
+        endpoint.name.eq_ignore_ascii_case(root) || endpoint.tag.as_ref() == Some(root)
+    };
+
+    if let Some(root) = roots
+        .iter()
+        .find(|r| !endpoints.iter().any(|e| is_selected_by(e, r)))
+    {
+        return Err(format!(
+            "Root \"{root}\" is neither the name of an operation nor a tag"
+        ));
+    }
+
+    Ok(endpoints
+        .into_iter()
+        .filter(|e| roots.iter().any(|r| is_selected_by(e, r)))
+        .collect())
+}
+
+/// Removes the model types, which are used neither by the endpoints nor by the handlers,
+/// directly or through the properties of other models.
+pub(crate) fn prune_types(
+    endpoints: &[Endpoint],
+    handler_groups: &[HandlerGroup],
+    class_types: &mut Vec<ClassType>,
+    enum_types: &mut Vec<EnumType>,
+) {
+    let reachable = reachable_types(
+        endpoints
+            .iter()
+            .chain(handler_groups.iter().flat_map(|g| &g.operations)),
+        class_types,
+    );
+
+    class_types.retain(|c| reachable.contains(&c.name));
+    enum_types.retain(|e| reachable.contains(&e.name));
+}
+
+/// Names of the types used by the endpoints, directly or through the properties of other models.
+pub(crate) fn reachable_types<'a>(
+    endpoints: impl Iterator<Item = &'a Endpoint>,
+    class_types: &[ClassType],
+) -> HashSet<String> {
+    let mut reachable = HashSet::new();
+    let mut pending = endpoints
+        .flat_map(|e| {
+            [&e.response_type.name, &e.request_body.name]
+                .into_iter()
+                .chain(e.status_codes.iter().map(|r| &r.type_.name))
+                .chain(e.args.iter().filter(|a| a.is_enum).map(|a| &a.type_name))
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+
+    while let Some(name) = pending.pop() {
+        if !reachable.insert(name.clone()) {
+            continue;
+        }
+
+        if let Some(class_type) = class_types.iter().find(|c| c.name == name) {
+            pending.extend(class_type.properties.iter().map(|p| p.type_.name.clone()));
+        }
+    }
+
+    reachable
+}
@@ -1,93 +1,1508 @@
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{Arc, OnceLock, RwLock},
+};
+
+use serde::Serialize;
 use sw4rm_rs::Spec;
-use tera::{Context, Tera};
+use tera::{Context, Tera, Value};
+
+use crate::{
+    endpoint_collector, guid,
+    model_units::ModelUnit,
+    models::{ClassType, ClientGroup, Endpoint, EnumType, HandlerGroup},
+    ClientOptions, EnumLookup, GenerationError, PlatformDirective, SerializerAttributes,
+};
+
+/// Context shared by all units, the unit specific content is flattened into it.
+#[derive(Serialize)]
+struct UnitContext<'a, T: Serialize> {
+    #[serde(rename = "unitPrefix")]
+    unit_prefix: &'a str,
+    prefix: &'a str,
+    crate_version: &'static str,
+    api_title: &'a str,
+    api_spec_version: &'a str,
+    header: Option<String>,
+    #[serde(flatten)]
+    content: T,
+}
+
+#[derive(Serialize)]
+struct ModelsContent<'a> {
+    unit_name: &'a str,
+    #[serde(rename = "classTypes")]
+    class_types: &'a [&'a ClassType],
+    #[serde(rename = "enumTypes")]
+    enum_types: &'a [&'a EnumType],
+    /// Models units used by the unit
+    model_uses: &'a [String],
+    declares_exception: bool,
+    /// Look up the values of `FromString` by binary search instead of an if chain
+    binary_search_enums: bool,
+    /// Custom attribute naming the JSON key of a field, e.g. `JSONName`
+    json_name_attribute: Option<&'static str>,
+    /// Unit declaring the custom attribute
+    attribute_unit: Option<&'static str>,
+}
+
+#[derive(Serialize)]
+pub(crate) struct ClientContent<'a> {
+    pub(crate) endpoints: &'a [Endpoint],
+    /// Part of the unit and type names between prefix and `Client`, e.g. `Api` or the tag
+    pub(crate) client_name: &'a str,
+    /// Client of a tag, which shares exception and events with the facade via `ApiClientBase`
+    pub(crate) is_group: bool,
+    /// Call `Validate` of the request body before it is sent
+    pub(crate) validate_request_bodies: bool,
+    /// Names of all models units
+    pub(crate) model_units: &'a [String],
+    /// At least one endpoint returns the payload of an envelope
+    pub(crate) unwraps_envelopes: bool,
+    /// At least one endpoint downloads a file into a stream
+    pub(crate) has_downloads: bool,
+    /// Downloads send a `Range` header to resume after the bytes of the target stream
+    pub(crate) resumable_downloads: bool,
+    /// Log the requests with the `IApiLogger` of the settings
+    pub(crate) logs_requests: bool,
+    /// Keys of the properties marked `x-sensitive`, whose values are redacted in logged bodies
+    pub(crate) sensitive_keys: &'a [String],
+    /// Declare an overload of every method, which takes a `RequestOptions` record
+    pub(crate) request_options: bool,
+    /// Units and code enclosed in `{$IFDEF}`
+    pub(crate) platform_directives: &'a [PlatformDirective],
+}
+
+#[derive(Serialize)]
+struct FacadeContent<'a> {
+    groups: Vec<&'a str>,
+    /// Declare the logger in the settings
+    logs_requests: bool,
+    /// Declare the `RequestOptions` record taken by the clients of the groups
+    request_options: bool,
+    /// Declare the progress event of the downloads of the groups
+    has_downloads: bool,
+}
+
+#[derive(Serialize)]
+struct WebhooksContent<'a> {
+    #[serde(rename = "handlerGroups")]
+    handler_groups: &'a [HandlerGroup],
+    model_units: &'a [String],
+}
+
+#[derive(Serialize)]
+struct RoutesContent<'a> {
+    routes: &'a [Endpoint],
+    tags: Vec<TagRoutes<'a>>,
+}
+
+/// Operations of a tag, which define an operationId
+#[derive(Serialize)]
+struct TagRoutes<'a> {
+    /// Name of the tag in PascalCase, as used for the clients of the tags
+    name: String,
+    tag: &'a str,
+    /// Names of the endpoints
+    routes: Vec<&'a str>,
+}
+
+/// Support units don't depend on the spec
+#[derive(Serialize)]
+struct SupportContent {}
+
+/// Built-in templates, which are compiled once per process
+static BUILTIN_TEMPLATES: OnceLock<Arc<Tera>> = OnceLock::new();
+
+/// Built-in templates with the overrides of `override_templates`
+static OVERRIDDEN_TEMPLATES: RwLock<Option<Arc<Tera>>> = RwLock::new(None);
+
+/// Templates of all generated units, the overridden ones if `override_templates` was called.
+///
+/// The templates are compiled by the first call and shared by all later generations,
+/// e.g. of the server mode, which generates repeatedly.
+pub(crate) fn load_templates() -> Result<Arc<Tera>, tera::Error> {
+    if let Some(tera) = OVERRIDDEN_TEMPLATES
+        .read()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .as_ref()
+    {
+        return Ok(Arc::clone(tera));
+    }
+
+    builtin_templates()
+}
+
+fn builtin_templates() -> Result<Arc<Tera>, tera::Error> {
+    if let Some(tera) = BUILTIN_TEMPLATES.get() {
+        return Ok(Arc::clone(tera));
+    }
 
-use crate::models::{ClassType, Endpoint, EnumType};
+    // Concurrent first calls compile the templates twice, but share the first result
+    let tera = compile_builtin_templates()?;
+
+    Ok(Arc::clone(BUILTIN_TEMPLATES.get_or_init(|| Arc::new(tera))))
+}
+
+/// Replaces the built-in templates by name, e.g. `client.pas`, for all following generations
+/// of the process. Templates, which are not given, keep their built-in version. Macros of an
+/// overridden template are imported from `macros.pas` like in the built-in templates.
+/// An empty list restores the built-in templates.
+pub(crate) fn override_templates(templates: &[(&str, &str)]) -> Result<(), tera::Error> {
+    let overridden = match templates.is_empty() {
+        true => None,
+        false => {
+            let mut tera = Tera::clone(builtin_templates()?.as_ref());
+            tera.add_raw_templates(templates.to_vec())?;
+
+            Some(Arc::new(tera))
+        }
+    };
+
+    *OVERRIDDEN_TEMPLATES
+        .write()
+        .unwrap_or_else(std::sync::PoisonError::into_inner) = overridden;
+
+    Ok(())
+}
+
+fn compile_builtin_templates() -> Result<Tera, tera::Error> {
+    let mut tera = Tera::default();
+    tera.add_raw_templates(vec![
+        ("macros.pas", include_str!("templates/macros.pas")),
+        ("client.pas", include_str!("templates/client.pas")),
+        (
+            "client_interface.pas",
+            include_str!("templates/client_interface.pas"),
+        ),
+        ("client_mock.pas", include_str!("templates/client_mock.pas")),
+        (
+            "client_facade_interface.pas",
+            include_str!("templates/client_facade_interface.pas"),
+        ),
+        (
+            "client_facade.pas",
+            include_str!("templates/client_facade.pas"),
+        ),
+        ("client_base.pas", include_str!("templates/client_base.pas")),
+        ("models.pas", include_str!("templates/models.pas")),
+        ("webhooks.pas", include_str!("templates/webhooks.pas")),
+        ("json_helper.pas", include_str!("templates/json_helper.pas")),
+        ("api_logger.pas", include_str!("templates/api_logger.pas")),
+        ("mapping.pas", include_str!("templates/mapping.pas")),
+        ("routes.pas", include_str!("templates/routes.pas")),
+    ])?;
+    tera.register_function("interface_guid", interface_guid);
+    tera.register_filter("ordinal_sort", ordinal_sort);
+    tera.register_filter("pascal_literal", pascal::pascal_literal);
+
+    Ok(tera)
+}
 
 pub(crate) fn render_models(
     spec: &Spec,
     dest: &std::path::Path,
-    prefix: Option<String>,
-    class_types: &[ClassType],
-    enum_types: &[EnumType],
+    unit: &ModelUnit,
+    options: &ClientOptions,
     tera: &Tera,
-) {
-    let mut models_context = Context::new();
-    models_context.insert("unitPrefix", &prefix.clone().unwrap_or_default());
-    models_context.insert("prefix", &prefix.clone().unwrap_or_default());
-    models_context.insert("crate_version", "0.0.1");
-    models_context.insert("api_title", &spec.info.title);
-    models_context.insert("api_spec_version", &spec.info.version);
-    models_context.insert("classTypes", &class_types);
-    models_context.insert("enumTypes", &enum_types);
-
-    let models = tera.render("models.pas", &models_context);
-
-    match models {
-        Ok(s) => {
-            let models_path = dest.join(format!("u{}ApiModels.pas", prefix.unwrap_or_default()));
-            if let Err(e) = std::fs::write(models_path, s) {
-                eprintln!("Failed to write models file due to {:?}", e);
-            }
-        }
-        Err(e) => eprintln!("Failed to render model template due to {:?}", e),
-    }
+) -> Result<PathBuf, GenerationError> {
+    write_unit(
+        render_models_unit(spec, unit, options, tera),
+        dest,
+        &unit.name,
+        &options.prefix,
+        "models",
+    )
+}
+
+/// Renders a models unit without writing it, e.g. for a preview
+pub(crate) fn render_models_unit(
+    spec: &Spec,
+    unit: &ModelUnit,
+    options: &ClientOptions,
+    tera: &Tera,
+) -> Result<String, tera::Error> {
+    let ClientOptions { prefix, header, .. } = options;
+    let (json_name_attribute, attribute_unit) = match options.serializer_attributes {
+        Some(SerializerAttributes::RestJson) => (Some("JSONName"), Some("REST.Json.Types")),
+        Some(SerializerAttributes::Neon) => (Some("NeonProperty"), Some("Neon.Core.Attributes")),
+        None => (None, None),
+    };
+
+    let content = ModelsContent {
+        unit_name: &unit.name,
+        class_types: &unit.class_types,
+        enum_types: &unit.enum_types,
+        model_uses: &unit.uses,
+        declares_exception: unit.declares_exception,
+        binary_search_enums: options.enum_lookup == EnumLookup::BinarySearch,
+        json_name_attribute,
+        attribute_unit,
+    };
+
+    render_unit(
+        tera,
+        "models.pas",
+        &unit.name,
+        spec,
+        prefix,
+        header,
+        content,
+    )
 }
 
-pub(crate) fn render_client_interface(
+/// Writes the interface, the implementation and the mock of a client.
+pub(crate) fn render_client_units(
     spec: &Spec,
     dest: &std::path::Path,
-    prefix: Option<String>,
+    prefix: &Option<String>,
+    content: &ClientContent,
+    header: &Option<String>,
+    tera: &Tera,
+) -> Result<Vec<PathBuf>, GenerationError> {
+    [
+        (
+            "client_interface.pas",
+            "ClientInterface",
+            "client interface",
+        ),
+        ("client.pas", "Client", "client"),
+        ("client_mock.pas", "ClientMock", "client mock"),
+    ]
+    .into_iter()
+    .map(|(template, suffix, description)| {
+        let unit = format!("{}{suffix}", content.client_name);
+
+        write_unit(
+            render_unit(tera, template, &unit, spec, prefix, header, content),
+            dest,
+            &unit,
+            prefix,
+            description,
+        )
+    })
+    .collect()
+}
+
+/// Writes the units of the facade, which creates the clients of all groups,
+/// and the unit with the exception and events shared by them.
+pub(crate) fn render_client_facade(
+    spec: &Spec,
+    dest: &std::path::Path,
+    groups: &[ClientGroup],
+    options: &ClientOptions,
+    tera: &Tera,
+) -> Result<Vec<PathBuf>, GenerationError> {
+    let ClientOptions { prefix, header, .. } = options;
+    let content = FacadeContent {
+        groups: groups.iter().map(|g| g.name.as_str()).collect(),
+        logs_requests: options.log_requests,
+        request_options: options.request_options,
+        has_downloads: groups
+            .iter()
+            .flat_map(|g| &g.endpoints)
+            .any(|e| e.is_binary_response),
+    };
+
+    [
+        ("client_base.pas", "ApiClientBase", "client base"),
+        (
+            "client_facade_interface.pas",
+            "ApiClientInterface",
+            "client facade interface",
+        ),
+        ("client_facade.pas", "ApiClient", "client facade"),
+    ]
+    .into_iter()
+    .map(|(template, unit, description)| {
+        write_unit(
+            render_unit(tera, template, unit, spec, prefix, header, &content),
+            dest,
+            unit,
+            prefix,
+            description,
+        )
+    })
+    .collect()
+}
+
+pub(crate) fn render_webhooks(
+    spec: &Spec,
+    dest: &std::path::Path,
+    prefix: &Option<String>,
+    handler_groups: &[HandlerGroup],
+    model_units: &[String],
+    header: &Option<String>,
+    tera: &Tera,
+) -> Result<PathBuf, GenerationError> {
+    let content = WebhooksContent {
+        handler_groups,
+        model_units,
+    };
+
+    write_unit(
+        render_unit(
+            tera,
+            "webhooks.pas",
+            "ApiWebhooks",
+            spec,
+            prefix,
+            header,
+            content,
+        ),
+        dest,
+        "ApiWebhooks",
+        prefix,
+        "webhooks",
+    )
+}
+
+/// Writes the unit with the methods, paths and operation ids of the endpoints as constants,
+/// so code, which isn't generated, doesn't need to repeat them.
+pub(crate) fn render_routes(
+    spec: &Spec,
+    dest: &std::path::Path,
+    prefix: &Option<String>,
     endpoints: &[Endpoint],
+    header: &Option<String>,
     tera: &Tera,
-) {
-    let mut models_context = Context::new();
-    models_context.insert("unitPrefix", &prefix.clone().unwrap_or_default());
-    models_context.insert("prefix", &prefix.clone().unwrap_or_default());
-    models_context.insert("crate_version", "0.0.1");
-    models_context.insert("api_title", &spec.info.title);
-    models_context.insert("api_spec_version", &spec.info.version);
-    models_context.insert("endpoints", &endpoints);
-
-    let models = tera.render("client_interface.pas", &models_context);
-
-    match models {
-        Ok(s) => {
-            let models_path = dest.join(format!(
-                "u{}ApiClientInterface.pas",
-                prefix.unwrap_or_default()
-            ));
-            if let Err(e) = std::fs::write(models_path, s) {
-                eprintln!("Failed to write client interface file due to {:?}", e);
-            }
+) -> Result<PathBuf, GenerationError> {
+    write_unit(
+        render_unit(
+            tera,
+            "routes.pas",
+            "ApiRoutes",
+            spec,
+            prefix,
+            header,
+            routes_content(endpoints),
+        ),
+        dest,
+        "ApiRoutes",
+        prefix,
+        "routes",
+    )
+}
+
+/// Groups the operations with an operationId by their first tag
+fn routes_content(endpoints: &[Endpoint]) -> RoutesContent<'_> {
+    let mut tags = Vec::<TagRoutes>::new();
+
+    for endpoint in endpoints.iter().filter(|e| e.operation_id.is_some()) {
+        let Some(tag) = endpoint.tag.as_deref() else {
+            continue;
+        };
+
+        match tags.iter_mut().find(|t| t.tag == tag) {
+            Some(tag_routes) => tag_routes.routes.push(&endpoint.name),
+            None => tags.push(TagRoutes {
+                name: endpoint_collector::group_name(endpoint),
+                tag,
+                routes: vec![&endpoint.name],
+            }),
         }
-        Err(e) => eprintln!("Failed to render client interface template due to {:?}", e),
     }
+
+    RoutesContent {
+        routes: endpoints,
+        tags,
+    }
+}
+
+/// Keys of the properties marked `x-sensitive` of all models, sorted and without duplicates
+pub(crate) fn sensitive_keys(class_types: &[ClassType]) -> Vec<String> {
+    let mut keys = class_types
+        .iter()
+        .flat_map(|c| c.properties.iter())
+        .filter(|p| p.is_sensitive)
+        .map(|p| p.key.clone())
+        .collect::<Vec<_>>();
+    keys.sort();
+    keys.dedup();
+
+    keys
 }
 
-pub(crate) fn render_client(
+/// Writes the units, which are used by the generated units but not generated
+/// from the spec. Their names have no prefix, so they are shared by all clients.
+/// The logger unit is only written, if the clients log their requests.
+pub(crate) fn render_support_units(
     spec: &Spec,
     dest: &std::path::Path,
-    prefix: Option<String>,
-    endpoints: &[Endpoint],
+    logs_requests: bool,
+    header: &Option<String>,
+    tera: &Tera,
+) -> Result<Vec<PathBuf>, GenerationError> {
+    let mut units = vec![("json_helper.pas", "JsonHelper", "json helper")];
+
+    if logs_requests {
+        units.push(("api_logger.pas", "ApiLogger", "api logger"));
+    }
+
+    units
+        .into_iter()
+        .map(|(template, unit, description)| {
+            write_unit(
+                render_unit(tera, template, unit, spec, &None, header, SupportContent {}),
+                dest,
+                unit,
+                &None,
+                description,
+            )
+        })
+        .collect()
+}
+
+/// Renders the template of a unit named `u{prefix}{unit}`.
+pub(crate) fn render_unit<T: Serialize>(
     tera: &Tera,
-) {
-    let mut models_context = Context::new();
-    models_context.insert("unitPrefix", &prefix.clone().unwrap_or_default());
-    models_context.insert("prefix", &prefix.clone().unwrap_or_default());
-    models_context.insert("crate_version", "0.0.1");
-    models_context.insert("api_title", &spec.info.title);
-    models_context.insert("api_spec_version", &spec.info.version);
-    models_context.insert("endpoints", &endpoints);
-
-    let models = tera.render("client.pas", &models_context);
-
-    match models {
-        Ok(s) => {
-            let models_path = dest.join(format!("u{}ApiClient.pas", prefix.unwrap_or_default()));
-            if let Err(e) = std::fs::write(models_path, s) {
-                eprintln!("Failed to write client file due to {:?}", e);
+    template: &str,
+    unit: &str,
+    spec: &Spec,
+    prefix: &Option<String>,
+    header: &Option<String>,
+    content: T,
+) -> Result<String, tera::Error> {
+    let prefix = prefix.as_deref().unwrap_or_default();
+    let unit_context = UnitContext {
+        unit_prefix: prefix,
+        prefix,
+        crate_version: "0.0.1",
+        api_title: &spec.info.title,
+        api_spec_version: &spec.info.version,
+        header: header_for_unit(header, &format!("u{prefix}{unit}")),
+        content,
+    };
+
+    tera.render(template, &Context::from_serialize(unit_context)?)
+}
+
+fn write_unit(
+    rendered: Result<String, tera::Error>,
+    dest: &std::path::Path,
+    unit: &str,
+    prefix: &Option<String>,
+    description: &str,
+) -> Result<PathBuf, GenerationError> {
+    let rendered = rendered.map_err(|e| {
+        GenerationError::Template(format!(
+            "Failed to render {description} template due to {:?}",
+            e
+        ))
+    })?;
+
+    let path = dest.join(format!(
+        "u{}{unit}.pas",
+        prefix.as_deref().unwrap_or_default()
+    ));
+
+    std::fs::write(&path, rendered).map_err(|e| {
+        GenerationError::Io(format!("Failed to write {description} file due to {:?}", e))
+    })?;
+
+    Ok(path)
+}
+
+/// Tera function, which returns the GUID of the interface passed as `name`
+fn interface_guid(args: &HashMap<String, Value>) -> tera::Result<Value> {
+    args.get("name")
+        .and_then(Value::as_str)
+        .map(|name| Value::String(guid::interface_guid(name)))
+        .ok_or_else(|| tera::Error::msg("interface_guid requires the name of the interface"))
+}
+
+/// Sorts an array of objects by the string `attribute` like `CompareStr`,
+/// which compares the UTF-16 code units
+fn ordinal_sort(value: &Value, args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let Some(attribute) = args.get("attribute").and_then(Value::as_str) else {
+        return Err(tera::Error::msg(
+            "ordinal_sort requires the attribute to sort by",
+        ));
+    };
+    let Some(items) = value.as_array() else {
+        return Err(tera::Error::msg("ordinal_sort requires an array"));
+    };
+
+    let key = |item: &Value| {
+        item.get(attribute)
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .encode_utf16()
+            .collect::<Vec<u16>>()
+    };
+
+    let mut items = items.clone();
+    items.sort_by_cached_key(key);
+
+    Ok(Value::Array(items))
+}
+
+fn header_for_unit(header: &Option<String>, unit_name: &str) -> Option<String> {
+    header.as_ref().map(|h| h.replace("{unit}", unit_name))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::LazyLock;
+
+    use super::*;
+    use crate::{endpoint_collector, schema_collector, webhook_collector};
+
+    const SPEC: &str = r##"
+openapi: 3.0.0
+info:
+  title: Pet Store
+  version: "1.0"
+paths:
+  /pets:
+    get:
+      operationId: listPets
+      tags: [pets]
+      parameters:
+        - name: limit
+          in: query
+          schema:
+            type: integer
+        - name: pageSize
+          in: query
+          schema:
+            type: integer
+            enum: [10, 50]
+        - name: status
+          in: query
+          schema:
+            type: string
+            enum: [available, sold]
+            default: available
+      responses:
+        "200":
+          description: ok
+          content:
+            application/json:
+              schema:
+                $ref: "#/components/schemas/Pet"
+    post:
+      operationId: createPet
+      tags: [pets]
+      requestBody:
+        content:
+          application/json:
+            schema:
+              $ref: "#/components/schemas/Pet"
+      responses:
+        "201":
+          description: created
+  /pets/{petId}:
+    get:
+      operationId: showPetById
+      tags: [pets]
+      parameters:
+        - name: petId
+          in: path
+          required: true
+          schema:
+            type: string
+            format: uuid
+      responses:
+        "200":
+          description: ok
+          content:
+            application/json:
+              schema:
+                $ref: "#/components/schemas/Pet"
+  /owners/{kind}/{ownerId}/name:
+    put:
+      operationId: renameOwner
+      tags: [pet owners]
+      parameters:
+        - name: kind
+          in: path
+          required: true
+          schema:
+            type: string
+            enum: [person, company]
+        - name: ownerId
+          in: path
+          required: true
+          schema:
+            type: integer
+      responses:
+        "204":
+          description: renamed
+  /stores/{storeName}:
+    delete:
+      operationId: deleteStore
+      parameters:
+        - name: storeName
+          in: path
+          required: true
+          schema:
+            type: string
+      responses:
+        "204":
+          description: deleted
+components:
+  schemas:
+    Pet:
+      type: object
+      properties:
+        id:
+          type: integer
+        name:
+          type: string
+          minLength: 1
+        born:
+          type: string
+          format: date-time
+        status:
+          type: string
+          enum: [available, sold]
+        tags:
+          type: array
+          items:
+            type: string
+        owner:
+          $ref: "#/components/schemas/Owner"
+        labels:
+          type: object
+          additionalProperties:
+            type: string
+        previousOwners:
+          type: object
+          additionalProperties:
+            $ref: "#/components/schemas/Owner"
+      required: [name]
+    Owner:
+      type: object
+      properties:
+        name:
+          type: string
+          minLength: 2
+          maxLength: 40
+          pattern: "^[A-Z]"
+        age:
+          type: integer
+          minimum: 0
+          maximum: 150
+        nickname:
+          type: string
+          nullable: true
+          x-sensitive: true
+        permissions:
+          type: array
+          items:
+            $ref: "#/components/schemas/Permission"
+      required: [nickname]
+    Permission:
+      type: string
+      enum: [read, write, admin]
+      x-flags: true
+"##;
+
+    static MODEL_UNITS: LazyLock<[String; 1]> = LazyLock::new(|| [String::from("ApiModels")]);
+
+    /// Content of a client unit of the endpoints, which uses the unit `ApiModels` and
+    /// has all options turned off
+    fn client_content(endpoints: &[Endpoint]) -> ClientContent<'_> {
+        ClientContent {
+            endpoints,
+            client_name: "Api",
+            is_group: false,
+            validate_request_bodies: false,
+            model_units: &*MODEL_UNITS,
+            unwraps_envelopes: false,
+            has_downloads: false,
+            resumable_downloads: false,
+            logs_requests: false,
+            sensitive_keys: &[],
+            request_options: false,
+            platform_directives: &[],
+        }
+    }
+
+    /// Renders the unit without a header, with the timestamp removed.
+    fn render_stripped<T: Serialize>(
+        tera: &Tera,
+        template: &str,
+        unit: &str,
+        spec: &Spec,
+        prefix: &Option<String>,
+        content: T,
+    ) -> String {
+        render_unit(tera, template, unit, spec, prefix, &None, content)
+            .unwrap()
+            .lines()
+            .filter(|l| !l.contains("Timestamp:"))
+            .collect::<Vec<&str>>()
+            .join("\n")
+    }
+
+    /// Renders the given unit of the test spec, with the timestamp removed.
+    fn render(template: &str, unit: &str) -> String {
+        let spec = sw4rm_rs::from_reader(SPEC.as_bytes()).unwrap();
+        let prefix = Some(String::from("Pet"));
+        let (mut class_types, mut enum_types) =
+            schema_collector::collect_types(&spec, &prefix).unwrap();
+        let endpoints =
+            endpoint_collector::collect_endpoints(&spec, &mut class_types, &mut enum_types)
+                .unwrap();
+
+        let tera = load_templates().unwrap();
+        match template {
+            "models.pas" => {
+                let class_types = class_types.iter().collect::<Vec<_>>();
+                let enum_types = enum_types.iter().collect::<Vec<_>>();
+                let content = ModelsContent {
+                    unit_name: "ApiModels",
+                    class_types: &class_types,
+                    enum_types: &enum_types,
+                    model_uses: &[],
+                    declares_exception: true,
+                    binary_search_enums: false,
+                    json_name_attribute: None,
+                    attribute_unit: None,
+                };
+
+                render_stripped(&tera, template, unit, &spec, &prefix, content)
+            }
+            "routes.pas" => render_stripped(
+                &tera,
+                template,
+                unit,
+                &spec,
+                &prefix,
+                routes_content(&endpoints),
+            ),
+            _ => {
+                let content = ClientContent {
+                    validate_request_bodies: true,
+                    ..client_content(&endpoints)
+                };
+
+                render_stripped(&tera, template, unit, &spec, &prefix, content)
             }
         }
-        Err(e) => eprintln!("Failed to render client template due to {:?}", e),
+    }
+
+    /// Renders the units of the facade and the given group, with the timestamps removed.
+    fn render_grouped(group: &str) -> (String, String) {
+        let spec = sw4rm_rs::from_reader(SPEC.as_bytes()).unwrap();
+        let prefix = Some(String::from("Pet"));
+        let (mut class_types, mut enum_types) =
+            schema_collector::collect_types(&spec, &prefix).unwrap();
+        let endpoints =
+            endpoint_collector::collect_endpoints(&spec, &mut class_types, &mut enum_types)
+                .unwrap();
+        let groups = endpoint_collector::group_by_tag(endpoints);
+
+        let tera = load_templates().unwrap();
+        let facade = FacadeContent {
+            groups: groups.iter().map(|g| g.name.as_str()).collect(),
+            logs_requests: false,
+            request_options: false,
+            has_downloads: false,
+        };
+        let group = groups.iter().find(|g| g.name == group).unwrap();
+        let client = ClientContent {
+            client_name: &group.name,
+            is_group: true,
+            ..client_content(&group.endpoints)
+        };
+
+        (
+            render_stripped(
+                &tera,
+                "client_facade.pas",
+                "ApiClient",
+                &spec,
+                &prefix,
+                facade,
+            ),
+            render_stripped(
+                &tera,
+                "client.pas",
+                "PetOwnersClient",
+                &spec,
+                &prefix,
+                client,
+            ),
+        )
+    }
+
+    /// Renders a client unit of the test spec with the given options, with the timestamp removed.
+    fn render_client_with(
+        template: &str,
+        unit: &str,
+        logs_requests: bool,
+        request_options: bool,
+        platform_directives: &[PlatformDirective],
+    ) -> String {
+        let spec = sw4rm_rs::from_reader(SPEC.as_bytes()).unwrap();
+        let prefix = Some(String::from("Pet"));
+        let (mut class_types, mut enum_types) =
+            schema_collector::collect_types(&spec, &prefix).unwrap();
+        let endpoints =
+            endpoint_collector::collect_endpoints(&spec, &mut class_types, &mut enum_types)
+                .unwrap();
+        let sensitive_keys = sensitive_keys(&class_types);
+
+        let tera = load_templates().unwrap();
+        let content = ClientContent {
+            logs_requests,
+            sensitive_keys: &sensitive_keys,
+            request_options,
+            platform_directives,
+            ..client_content(&endpoints)
+        };
+
+        render_stripped(&tera, template, unit, &spec, &prefix, content)
+    }
+
+    #[test]
+    fn models_unit() {
+        insta::assert_snapshot!(render("models.pas", "ApiModels"));
+    }
+
+    #[test]
+    fn min_length_covered_by_the_required_check_is_not_validated() {
+        let models = render("models.pas", "ApiModels");
+
+        assert!(models.contains("if FName = '' then vViolations.Add('name is required');"));
+        assert!(!models.contains("Length(FName) < 1"));
+        assert!(models.contains("Length(FName) < 2"));
+    }
+
+    #[test]
+    fn client_interface_unit() {
+        insta::assert_snapshot!(render("client_interface.pas", "ApiClientInterface"));
+    }
+
+    #[test]
+    fn client_unit() {
+        insta::assert_snapshot!(render("client.pas", "ApiClient"));
+    }
+
+    #[test]
+    fn client_mock_unit() {
+        insta::assert_snapshot!(render("client_mock.pas", "ApiClientMock"));
+    }
+
+    #[test]
+    fn routes_unit() {
+        insta::assert_snapshot!(render("routes.pas", "ApiRoutes"));
+    }
+
+    #[test]
+    fn clients_grouped_by_tag() {
+        let (facade, client) = render_grouped("PetOwners");
+
+        insta::assert_snapshot!("client_facade_unit", facade);
+        insta::assert_snapshot!("grouped_client_unit", client);
+    }
+
+    #[test]
+    fn ordinal_sort_compares_utf16_code_units() {
+        let keys = ["\u{FF21}", "b", "\u{1F600}", "B"]
+            .iter()
+            .map(|k| serde_json::json!({ "key": k }))
+            .collect::<Vec<_>>();
+        let args = HashMap::from([(String::from("attribute"), Value::from("key"))]);
+
+        let sorted = ordinal_sort(&Value::Array(keys), &args).unwrap();
+
+        assert_eq!(
+            sorted
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|v| v["key"].as_str().unwrap())
+                .collect::<Vec<_>>(),
+            ["B", "b", "\u{1F600}", "\u{FF21}"]
+        );
+    }
+
+    #[test]
+    fn json_helper_unit() {
+        insta::assert_snapshot!(render("json_helper.pas", "JsonHelper"));
+    }
+
+    #[test]
+    fn api_logger_unit() {
+        insta::assert_snapshot!(render("api_logger.pas", "ApiLogger"));
+    }
+
+    #[test]
+    fn logging_client_unit() {
+        insta::assert_snapshot!(render_client_with(
+            "client.pas",
+            "ApiClient",
+            true,
+            false,
+            &[]
+        ));
+    }
+
+    #[test]
+    fn client_units_with_request_options() {
+        insta::assert_snapshot!(
+            "request_options_client_interface_unit",
+            render_client_with(
+                "client_interface.pas",
+                "ApiClientInterface",
+                false,
+                true,
+                &[]
+            )
+        );
+        insta::assert_snapshot!(
+            "request_options_client_unit",
+            render_client_with("client.pas", "ApiClient", false, true, &[])
+        );
+        insta::assert_snapshot!(
+            "request_options_client_mock_unit",
+            render_client_with("client_mock.pas", "ApiClientMock", false, true, &[])
+        );
+    }
+
+    #[test]
+    fn client_unit_with_platform_directives() {
+        let directives = [
+            PlatformDirective {
+                define: String::from("MSWINDOWS"),
+                units: vec![String::from("Winapi.Windows")],
+                else_units: vec![String::from("Posix.Unistd")],
+                ..Default::default()
+            },
+            PlatformDirective {
+                define: String::from("FRAMEWORK_FMX"),
+                units: vec![String::from("FMX.Forms")],
+                code: Some(String::from(
+                    "procedure ProcessMessages;\nbegin\n  Application.ProcessMessages;\nend;",
+                )),
+                else_code: Some(String::from("procedure ProcessMessages;\nbegin\nend;")),
+                ..Default::default()
+            },
+        ];
+
+        insta::assert_snapshot!(render_client_with(
+            "client.pas",
+            "ApiClient",
+            false,
+            false,
+            &directives
+        ));
+    }
+
+    #[test]
+    fn distinct_success_responses_are_returned_as_result() {
+        let spec = sw4rm_rs::from_reader(
+            r##"
+openapi: 3.0.0
+info:
+  title: Jobs
+  version: "1.0"
+paths:
+  /jobs:
+    post:
+      operationId: submitJob
+      responses:
+        "200":
+          description: finished
+          content:
+            application/json:
+              schema:
+                $ref: "#/components/schemas/Report"
+        "202":
+          description: queued
+          content:
+            application/json:
+              schema:
+                $ref: "#/components/schemas/Ticket"
+        "204":
+          description: nothing to do
+        "400":
+          description: invalid
+          content:
+            application/json:
+              schema:
+                $ref: "#/components/schemas/Ticket"
+components:
+  schemas:
+    Report:
+      type: object
+      properties:
+        lines:
+          type: integer
+    Ticket:
+      type: object
+      properties:
+        id:
+          type: string
+"##
+            .as_bytes(),
+        )
+        .unwrap();
+        let prefix = Some(String::from("Job"));
+        let (mut class_types, mut enum_types) =
+            schema_collector::collect_types(&spec, &prefix).unwrap();
+        let endpoints =
+            endpoint_collector::collect_endpoints(&spec, &mut class_types, &mut enum_types)
+                .unwrap();
+
+        assert!(endpoints[0].has_result_wrapper);
+        assert_eq!(endpoints[0].response_type.name, "SubmitJobResult");
+
+        let tera = load_templates().unwrap();
+        let render = |template: &str, unit: &str| {
+            render_stripped(
+                &tera,
+                template,
+                unit,
+                &spec,
+                &prefix,
+                client_content(&endpoints),
+            )
+        };
+
+        insta::assert_snapshot!(
+            "result_client_interface_unit",
+            render("client_interface.pas", "ApiClientInterface")
+        );
+        insta::assert_snapshot!("result_client_unit", render("client.pas", "ApiClient"));
+    }
+
+    #[test]
+    fn binary_downloads_report_progress_and_can_be_resumed() {
+        let spec = sw4rm_rs::from_reader(
+            r##"
+openapi: 3.0.0
+info:
+  title: Reports
+  version: "1.0"
+paths:
+  /reports/{reportId}/file:
+    get:
+      operationId: downloadReport
+      parameters:
+        - name: reportId
+          in: path
+          required: true
+          schema:
+            type: integer
+      responses:
+        "200":
+          description: report
+          content:
+            application/octet-stream:
+              schema:
+                type: string
+                format: binary
+"##
+            .as_bytes(),
+        )
+        .unwrap();
+        let prefix = Some(String::from("Report"));
+        let (mut class_types, mut enum_types) =
+            schema_collector::collect_types(&spec, &prefix).unwrap();
+        let endpoints =
+            endpoint_collector::collect_endpoints(&spec, &mut class_types, &mut enum_types)
+                .unwrap();
+
+        assert!(endpoints[0].is_binary_response);
+
+        let tera = load_templates().unwrap();
+        let render = |template: &str, unit: &str, resumable_downloads: bool| {
+            let content = ClientContent {
+                has_downloads: true,
+                resumable_downloads,
+                ..client_content(&endpoints)
+            };
+
+            render_stripped(&tera, template, unit, &spec, &prefix, content)
+        };
+
+        insta::assert_snapshot!(
+            "download_client_interface_unit",
+            render("client_interface.pas", "ApiClientInterface", false)
+        );
+        insta::assert_snapshot!(
+            "download_client_unit",
+            render("client.pas", "ApiClient", false)
+        );
+        insta::assert_snapshot!(
+            "resumable_download_client_unit",
+            render("client.pas", "ApiClient", true)
+        );
+        insta::assert_snapshot!(
+            "download_client_mock_unit",
+            render("client_mock.pas", "ApiClientMock", false)
+        );
+    }
+
+    #[test]
+    fn nullable_properties_keep_null_apart_from_missing_keys() {
+        let spec = sw4rm_rs::from_reader(
+            r##"
+openapi: 3.0.0
+info:
+  title: People
+  version: "1.0"
+paths: {}
+components:
+  schemas:
+    Person:
+      type: object
+      properties:
+        note:
+          type: string
+        middleName:
+          type: string
+          nullable: true
+        nickname:
+          type: string
+          nullable: true
+      required: [nickname]
+"##
+            .as_bytes(),
+        )
+        .unwrap();
+        let (class_types, _) = schema_collector::collect_types(&spec, &None).unwrap();
+        let properties = class_types[0]
+            .properties
+            .iter()
+            .map(|p| (p.key.as_str(), p.is_nullable, p.constraints.is_required))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            properties,
+            [
+                ("middleName", true, false),
+                ("nickname", true, true),
+                ("note", false, false)
+            ]
+        );
+
+        let class_types = class_types.iter().collect::<Vec<_>>();
+        let content = ModelsContent {
+            unit_name: "ApiModels",
+            class_types: &class_types,
+            enum_types: &[],
+            model_uses: &[],
+            declares_exception: true,
+            binary_search_enums: false,
+            json_name_attribute: None,
+            attribute_unit: None,
+        };
+        let tera = load_templates().unwrap();
+        let models = render_unit(
+            &tera,
+            "models.pas",
+            "ApiModels",
+            &spec,
+            &None,
+            &None,
+            content,
+        )
+        .unwrap();
+
+        // Optional properties, which aren't nullable, have no flags and are always written
+        assert!(!models.contains("FHasNote"));
+        assert!(!models.contains("FNoteIsNull"));
+        assert!(models.contains("    Result.AddPair(cnPersonNoteKey, TJSONString.Create(FNote));"));
+
+        for name in ["MiddleName", "Nickname"] {
+            let key = format!("cnPerson{name}Key");
+
+            assert!(models.contains(&format!(
+                "  F{name}IsNull := pJson.FindValue({key}) is TJSONNull;"
+            )));
+            assert!(models.contains(&format!(
+                "    if F{name}IsNull then begin\n      Result.AddPair({key}, TJSONNull.Create);\n    end else if FHas{name} then begin"
+            )));
+        }
+
+        assert!(
+            models.contains("if not FHasNickname then vViolations.Add('nickname is required');")
+        );
+        assert!(!models.contains("if not FHasMiddleName then"));
+    }
+
+    #[test]
+    fn binary_responses_are_written_to_a_stream() {
+        let spec = sw4rm_rs::from_reader(
+            r##"
+openapi: 3.0.0
+info:
+  title: Photos
+  version: "1.0"
+paths:
+  /photos/{photoId}:
+    get:
+      operationId: getPhoto
+      parameters:
+        - name: photoId
+          in: path
+          required: true
+          schema:
+            type: integer
+      responses:
+        "200":
+          description: photo
+          content:
+            image/png:
+              schema:
+                type: string
+                format: binary
+  /photos/{photoId}/raw:
+    get:
+      operationId: getRawPhoto
+      parameters:
+        - name: photoId
+          in: path
+          required: true
+          schema:
+            type: integer
+      responses:
+        "200":
+          description: raw photo
+          content:
+            application/octet-stream:
+              schema:
+                type: string
+                format: binary
+  /photos/{photoId}/meta:
+    get:
+      operationId: getPhotoMeta
+      parameters:
+        - name: photoId
+          in: path
+          required: true
+          schema:
+            type: integer
+      responses:
+        "200":
+          description: metadata, also offered as file
+          content:
+            application/json:
+              schema:
+                type: object
+                properties:
+                  width:
+                    type: integer
+            application/octet-stream:
+              schema:
+                type: string
+                format: binary
+"##
+            .as_bytes(),
+        )
+        .unwrap();
+        let prefix = Some(String::from("Photo"));
+        let (mut class_types, mut enum_types) =
+            schema_collector::collect_types(&spec, &prefix).unwrap();
+        let endpoints =
+            endpoint_collector::collect_endpoints(&spec, &mut class_types, &mut enum_types)
+                .unwrap();
+
+        assert_eq!(
+            endpoints
+                .iter()
+                .map(|e| (e.name.as_str(), e.is_binary_response))
+                .collect::<Vec<_>>(),
+            [
+                ("GetPhoto", true),
+                ("GetPhotoMeta", false),
+                ("GetRawPhoto", true)
+            ]
+        );
+
+        let tera = load_templates().unwrap();
+        let content = ClientContent {
+            has_downloads: true,
+            ..client_content(&endpoints)
+        };
+        let client_interface = render_stripped(
+            &tera,
+            "client_interface.pas",
+            "ApiClientInterface",
+            &spec,
+            &prefix,
+            content,
+        );
+
+        assert!(client_interface.contains(
+            "procedure GetPhoto(pPhotoId: integer; pTarget: TStream; pOnProgress: TPhotoDownloadProgressEvent = nil);"
+        ));
+        assert!(client_interface.contains(
+            "procedure GetRawPhoto(pPhotoId: integer; pTarget: TStream; pOnProgress: TPhotoDownloadProgressEvent = nil);"
+        ));
+        assert!(client_interface
+            .contains("function GetPhotoMeta(pPhotoId: integer): TPhotoGetPhotoMeta;"));
+    }
+
+    #[test]
+    fn literals_escape_quotes_line_breaks_and_non_ascii_characters() {
+        let spec = sw4rm_rs::from_reader(
+            r##"
+openapi: 3.0.0
+info:
+  title: Sizes
+  version: "1.0"
+paths:
+  /größe/{id}:
+    get:
+      operationId: getSize
+      parameters:
+        - name: id
+          in: path
+          required: true
+          schema:
+            type: integer
+        - name: größe
+          in: query
+          schema:
+            type: string
+            default: it's
+      responses:
+        "200":
+          description: ok
+          content:
+            application/json:
+              schema:
+                $ref: "#/components/schemas/Size"
+components:
+  schemas:
+    Size:
+      type: object
+      properties:
+        größe:
+          type: string
+          pattern: "^[^']+$"
+        unit:
+          $ref: "#/components/schemas/Unit"
+    Unit:
+      type: string
+      enum: ["it's", "line\nbreak", "grün"]
+"##
+            .as_bytes(),
+        )
+        .unwrap();
+        let (mut class_types, mut enum_types) =
+            schema_collector::collect_types(&spec, &None).unwrap();
+        let endpoints =
+            endpoint_collector::collect_endpoints(&spec, &mut class_types, &mut enum_types)
+                .unwrap();
+
+        let class_types = class_types.iter().collect::<Vec<_>>();
+        let enum_types = enum_types.iter().collect::<Vec<_>>();
+        let content = ModelsContent {
+            unit_name: "ApiModels",
+            class_types: &class_types,
+            enum_types: &enum_types,
+            model_uses: &[],
+            declares_exception: true,
+            binary_search_enums: false,
+            json_name_attribute: Some("JSONName"),
+            attribute_unit: Some("REST.Json.Types"),
+        };
+        let tera = load_templates().unwrap();
+        let models = render_stripped(&tera, "models.pas", "ApiModels", &spec, &None, content);
+        let client = render_stripped(
+            &tera,
+            "client.pas",
+            "ApiClient",
+            &spec,
+            &None,
+            client_content(&endpoints),
+        );
+
+        for line in [
+            "    [JSONName('gr'#$00F6#$00DF'e')]",
+            "  cnSizeGrößeKey: string = 'gr'#$00F6#$00DF'e';",
+            "  if pValue = 'it''s' then begin ",
+            "    uLine_break: Result := 'line'#10'break';",
+            "    uGrün: Result := 'gr'#$00FC'n';",
+            "    if (FGröße <> '') and not TRegEx.IsMatch(FGröße, '^[^'']+$') then vViolations.Add('gr'#$00F6#$00DF'e must match ^[^'']+$');",
+        ] {
+            assert!(models.lines().any(|l| l == line), "{line}");
+        }
+
+        for line in [
+            "    function GetSize(pId: integer; pGröße: string = 'it''s'): TGetSize;",
+            "    vRequest.Resource := '/gr'#$00F6#$00DF'e/'",
+            "      vRequest.AddParameter('gr'#$00F6#$00DF'e', pGröße, pkQUERY);",
+        ] {
+            assert!(client.lines().any(|l| l == line), "{line}");
+        }
+    }
+
+    #[test]
+    fn webhooks_unit() {
+        let spec =
+            sw4rm_rs::from_reader(include_str!("../tests/fixtures/webhooks.yaml").as_bytes())
+                .unwrap();
+        let prefix = Some(String::from("Pet"));
+        let (mut class_types, mut enum_types) =
+            schema_collector::collect_types(&spec, &prefix).unwrap();
+        let handler_groups =
+            webhook_collector::collect_handler_groups(&spec, &mut class_types, &mut enum_types)
+                .unwrap();
+
+        let tera = load_templates().unwrap();
+        let content = WebhooksContent {
+            handler_groups: &handler_groups,
+            model_units: &*MODEL_UNITS,
+        };
+        let webhooks = render_stripped(
+            &tera,
+            "webhooks.pas",
+            "ApiWebhooks",
+            &spec,
+            &prefix,
+            content,
+        );
+
+        assert!(webhooks.contains("  TPetWebhooksHandler = class abstract"));
+        assert!(webhooks.contains("  TPetSubscribeCallbacksHandler = class abstract"));
+        assert!(webhooks.contains(
+            "    procedure PostNewPet(pPayload: TPetPostNewPetRequestBody); virtual; abstract;"
+        ));
+        assert!(webhooks.contains(
+            "    procedure PostPetRemoved(pPayload: TPetPostPetRemovedRequestBody); virtual; abstract;"
+        ));
+        assert!(webhooks.contains(
+            "    procedure PostOnPetSold(pPayload: TPetPostOnPetSoldRequestBody); virtual; abstract;"
+        ));
+        assert!(webhooks.contains("    procedure GetPing(); virtual; abstract;"));
+        insta::assert_snapshot!(webhooks);
+    }
+
+    #[test]
+    fn templates_are_compiled_once_and_can_be_overridden() {
+        // Other tests render concurrently, so only a template unknown to them is overridden
+        override_templates(&[(
+            "custom.pas",
+            "{% import \"macros.pas\" as macros %}unit {{ unit_name }};",
+        )])
+        .unwrap();
+
+        let tera = load_templates().unwrap();
+        let mut context = Context::new();
+        context.insert("unit_name", "uCustom");
+
+        assert!(Arc::ptr_eq(&tera, &load_templates().unwrap()));
+        assert_eq!(
+            tera.render("custom.pas", &context).unwrap(),
+            "unit uCustom;"
+        );
+        assert!(tera.get_template_names().any(|n| n == "client.pas"));
+
+        override_templates(&[]).unwrap();
+
+        assert!(load_templates()
+            .unwrap()
+            .get_template_names()
+            .all(|n| n != "custom.pas"));
     }
 }
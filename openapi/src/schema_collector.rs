@@ -1,26 +1,33 @@
+use std::collections::{HashMap, HashSet};
+
 use sw4rm_rs::{
     shared::{Schema, SchemaType},
-    RefOr, Reference, Spec,
+    RefOr, Reference, Resolvable, Spec,
 };
 use tera::Value;
 
-use crate::models::{ClassType, EnumType, EnumVariant, Property};
+use crate::models::{ClassType, Constraints, EnumType, EnumVariant, Property};
 use crate::{
-    helper::{capitalize, get_enum_variant_prefix, sanitize_name, schema_type_to_base_type},
+    helper::{
+        capitalize, deprecation_comment, get_enum_variant_prefix, resolve, sanitize_name,
+        schema_overrides, schema_type_to_base_type, sorted_entries,
+    },
     models::Type,
 };
 
+/// Collects the types of all schemas of the spec.
+///
+/// Fails if a schema or a property references a schema, which doesn't exist, or an array
+/// has no items.
 pub(crate) fn collect_types(
     spec: &Spec,
     prefix: &Option<String>,
-) -> (Vec<ClassType>, Vec<EnumType>) {
+) -> Result<(Vec<ClassType>, Vec<EnumType>), String> {
     let mut class_types = vec![];
     let mut enum_types = vec![];
 
-    for (k, v) in spec.schemas() {
-        let Ok(s) = v.resolve(spec) else {
-            continue;
-        };
+    for (k, v) in sorted_entries(&spec.schemas()) {
+        let s = resolve(v, spec, &format!("Schema \"{k}\""))?;
 
         schema_to_type(
             &s,
@@ -29,10 +36,10 @@ pub(crate) fn collect_types(
             prefix,
             &mut class_types,
             &mut enum_types,
-        );
+        )?;
     }
 
-    (class_types, enum_types)
+    Ok((class_types, enum_types))
 }
 
 pub(crate) fn schema_to_type(
@@ -42,122 +49,515 @@ pub(crate) fn schema_to_type(
     prefix: &Option<String>,
     class_types: &mut Vec<ClassType>,
     enum_types: &mut Vec<EnumType>,
-) -> Option<(String, bool, bool)> {
+) -> Result<Option<(String, bool, bool)>, String> {
+    collect_schema(
+        schema,
+        name,
+        spec,
+        prefix,
+        class_types,
+        enum_types,
+        &mut vec![],
+    )
+}
+
+/// Collects the types of the schema and of its properties.
+///
+/// `visiting` holds the classes, whose properties are collected. A schema referencing one of
+/// them, directly or through other schemas, is recursive and only referenced by its name.
+fn collect_schema(
+    schema: &Schema,
+    name: &str,
+    spec: &Spec,
+    prefix: &Option<String>,
+    class_types: &mut Vec<ClassType>,
+    enum_types: &mut Vec<EnumType>,
+    visiting: &mut Vec<String>,
+) -> Result<Option<(String, bool, bool)>, String> {
+    let overrides = schema_overrides(schema);
+
+    if overrides.skip {
+        return Ok(None);
+    }
+
     match schema.schema_type {
         Some(SchemaType::String) if !schema.enum_values.is_empty() => {
-            let enum_type = build_enum_type(name, &schema.enum_values, prefix.clone());
+            let enum_type = build_enum_type(
+                overrides.name.as_deref().unwrap_or(name),
+                schema,
+                prefix.clone(),
+            );
             let name = enum_type.name.clone();
 
-            if !enum_types.iter().any(|e| *e == enum_type) {
+            if !enum_types.contains(&enum_type) {
                 enum_types.push(enum_type);
             }
 
-            Some((name, false, true))
+            Ok(Some((name, false, true)))
         }
         Some(SchemaType::Object) => {
-            let properties = schema
-                .properties
-                .iter()
-                .filter_map(|(k, v)| {
-                    v.resolve(spec).ok().and_then(|s| {
-                        let (type_name, is_reference_type, is_enum_type) =
-                            s.schema_type.as_ref().map(|t| match t {
-                                SchemaType::String if !s.enum_values.is_empty() => {
-                                    let enum_type =
-                                        build_enum_type(k, &s.enum_values, prefix.clone());
-                                    let name = enum_type.name.clone();
-
-                                    if !enum_types.iter().any(|e| *e == enum_type) {
-                                        enum_types.push(enum_type);
-                                    }
-
-                                    (name, false, true)
-                                }
-                                SchemaType::Array => {
-                                    let items = s
-                                        .items
-                                        .as_ref()
-                                        .expect("Array must have items property set");
-                                    let item_schema = items
-                                        .resolve(spec)
-                                        .expect("Type of array items must be resolved");
-
-                                    let (name, is_class, is_enum) = schema_to_type(
-                                        &item_schema,
-                                        &match items {
-                                            RefOr::Reference { reference_path } => {
-                                                let reference =
-                                                    Reference::try_from(reference_path.clone())
-                                                        .unwrap();
-
-                                                reference.name
-                                            }
-                                            _ => k.to_owned() + "Item",
-                                        },
-                                        spec,
-                                        prefix,
-                                        class_types,
-                                        enum_types,
-                                    )
-                                    .expect("Type of array items must be resolved");
-
-                                    (name, is_class, is_enum)
-                                }
-                                SchemaType::Object => {
-                                    (s.title.clone().unwrap_or(k.to_string()), true, false)
-                                }
-                                _ => (schema_type_to_base_type(*t, &s.format), false, false),
-                            })?;
-
-                        Some(Property {
-                            name: capitalize(k),
-                            key: k.to_owned(),
-                            is_list_type: s.schema_type.is_some_and(|t| t == SchemaType::Array),
-                            type_: Type {
-                                name: type_name,
-                                is_class: is_reference_type,
-                                is_enum: is_enum_type,
-                            },
-                        })
-                    })
+            let name = overrides
+                .name
+                .or_else(|| schema.title.clone())
+                .unwrap_or(name.to_string());
+            let name = capitalize(&name);
+
+            if visiting.contains(&name) {
+                return Ok(Some((name, true, false)));
+            }
+
+            visiting.push(name.clone());
+
+            let properties = sorted_entries(&schema.properties)
+                .into_iter()
+                .map(|(k, v)| {
+                    let context = format!("Property \"{k}\" of schema \"{name}\"");
+                    let s = resolve(v, spec, &context)?;
+                    let property_overrides = schema_overrides(&s);
+
+                    if property_overrides.skip {
+                        return Ok(None);
+                    }
+
+                    let Some(t) = s.schema_type.as_ref() else {
+                        return Ok(None);
+                    };
+
+                    let map_values = map_values(&s);
+                    let type_info = match t {
+                        _ if property_overrides.data_type.is_some() => Some((
+                            property_overrides.data_type.clone().unwrap_or_default(),
+                            false,
+                            false,
+                        )),
+                        SchemaType::String if !s.enum_values.is_empty() => {
+                            let enum_type = build_enum_type(k, &s, prefix.clone());
+                            let name = enum_type.name.clone();
+
+                            if !enum_types.contains(&enum_type) {
+                                enum_types.push(enum_type);
+                            }
+
+                            Some((name, false, true))
+                        }
+                        SchemaType::Array => {
+                            let items = s
+                                .items
+                                .as_ref()
+                                .ok_or_else(|| format!("{context}: Array has no items"))?;
+                            let item_schema = resolve(items, spec, &context)?;
+
+                            collect_schema(
+                                &item_schema,
+                                &reference_name(items).unwrap_or(k.to_owned() + "Item"),
+                                spec,
+                                prefix,
+                                class_types,
+                                enum_types,
+                                visiting,
+                            )?
+                        }
+                        SchemaType::Object => match map_values {
+                            Some(values) => {
+                                let value_schema = resolve(values, spec, &context)?;
+
+                                collect_schema(
+                                    &value_schema,
+                                    &reference_name(values).unwrap_or(k.to_owned() + "Value"),
+                                    spec,
+                                    prefix,
+                                    class_types,
+                                    enum_types,
+                                    visiting,
+                                )?
+                            }
+                            None => collect_schema(
+                                &s,
+                                &reference_name(v).unwrap_or(k.to_owned()),
+                                spec,
+                                prefix,
+                                class_types,
+                                enum_types,
+                                visiting,
+                            )?,
+                        },
+                        _ => Some((schema_type_to_base_type(*t, &s.format), false, false)),
+                    };
+
+                    let Some((type_name, is_reference_type, is_enum_type)) = type_info else {
+                        return Ok(None);
+                    };
+
+                    let is_map_type = map_values.is_some();
+                    let is_set = is_enum_type
+                        && !is_map_type
+                        && enum_types.iter().any(|e| e.name == type_name && e.is_flags);
+                    let is_list_type =
+                        s.schema_type.is_some_and(|t| t == SchemaType::Array) && !is_set;
+                    let name = match s.schema_type {
+                        Some(SchemaType::Object) => capitalize(k),
+                        _ => property_overrides.name.unwrap_or_else(|| capitalize(k)),
+                    };
+
+                    let constraints = match is_map_type {
+                        true => Constraints {
+                            is_required: schema.required.contains(k),
+                            ..Constraints::default()
+                        },
+                        false => Constraints {
+                            is_required: schema.required.contains(k),
+                            min_length: s.min_length,
+                            max_length: s.max_length,
+                            pattern: s.pattern.clone(),
+                            minimum: s.minimum,
+                            maximum: s.maximum,
+                        },
+                    };
+
+                    Ok(Some(Property {
+                        name,
+                        key: k.to_owned(),
+                        constraints,
+                        is_list_type,
+                        is_map_type,
+                        is_owned: property_overrides
+                            .owned
+                            .unwrap_or(is_reference_type || is_list_type || is_map_type),
+                        is_recursive: false,
+                        is_nullable: s.nullable.unwrap_or(false),
+                        is_sensitive: property_overrides.sensitive,
+                        example: s.example.clone(),
+                        deprecation_comment: deprecation_comment(s.deprecated, &s.description),
+                        type_: Type {
+                            name: type_name,
+                            is_class: is_reference_type,
+                            is_enum: is_enum_type,
+                            is_set,
+                        },
+                    }))
                 })
+                .collect::<Result<Vec<Option<Property>>, String>>()?
+                .into_iter()
+                .flatten()
                 .collect::<Vec<Property>>();
 
-            let name = schema.title.clone().unwrap_or(name.to_string());
-            let name = capitalize(&name);
+            visiting.pop();
 
             let class_type = ClassType {
                 name: name.clone(),
-                needs_destructor: properties.iter().any(|p| p.type_.is_class),
+                needs_destructor: properties.iter().any(|p| p.is_owned),
+                has_constraints: properties.iter().any(|p| !p.constraints.is_empty()),
                 properties,
+                namespace: overrides.namespace,
             };
 
-            if !class_types.iter().any(|c| *c == class_type) {
+            if !class_types.contains(&class_type) {
                 class_types.push(class_type);
             }
 
-            Some((name, true, false))
+            Ok(Some((name, true, false)))
+        }
+        Some(SchemaType::Array) => Ok(None),
+        Some(t) => Ok(Some((
+            schema_type_to_base_type(t, &schema.format),
+            false,
+            false,
+        ))),
+        _ => Ok(None),
+    }
+}
+
+/// Schema of the values of an object, which has no properties but `additionalProperties`.
+/// Its properties are read into a dictionary instead of a class.
+fn map_values(schema: &Schema) -> Option<&RefOr<Box<Schema>>> {
+    match schema.schema_type {
+        Some(SchemaType::Object) if schema.properties.is_empty() => {
+            schema.additional_properties.as_ref()
         }
-        Some(SchemaType::Array) => None,
-        Some(t) => Some((schema_type_to_base_type(t, &schema.format), false, false)),
         _ => None,
     }
 }
 
-fn build_enum_type(name: &str, variants: &[Value], prefix: Option<String>) -> EnumType {
+/// Name of the referenced schema
+pub(crate) fn reference_name<T: Resolvable>(schema: &RefOr<T>) -> Option<String> {
+    match schema {
+        RefOr::Reference { reference_path } => Reference::try_from(reference_path.clone())
+            .ok()
+            .map(|r| r.name),
+        RefOr::Item(_) => None,
+    }
+}
+
+/// Marks the properties, whose class references the class of the property again.
+///
+/// A chain of recursive properties ends with a missing value, so these are only read,
+/// if present in the JSON. Called after all types are collected, because types collected
+/// later must not differ from those collected before.
+pub(crate) fn mark_recursive_properties(class_types: &mut [ClassType]) {
+    let references = class_types
+        .iter()
+        .map(|c| {
+            (
+                c.name.clone(),
+                c.properties
+                    .iter()
+                    .filter(|p| p.type_.is_class)
+                    .map(|p| p.type_.name.clone())
+                    .collect::<Vec<String>>(),
+            )
+        })
+        .collect::<HashMap<String, Vec<String>>>();
+
+    for class_type in class_types.iter_mut() {
+        for property in class_type
+            .properties
+            .iter_mut()
+            .filter(|p| p.type_.is_class)
+        {
+            property.is_recursive = reaches(&references, &property.type_.name, &class_type.name);
+        }
+    }
+}
+
+fn reaches(references: &HashMap<String, Vec<String>>, from: &str, to: &str) -> bool {
+    let mut visited = HashSet::new();
+    let mut pending = vec![from];
+
+    while let Some(name) = pending.pop() {
+        if name == to {
+            return true;
+        }
+
+        if visited.insert(name) {
+            pending.extend(
+                references
+                    .get(name)
+                    .into_iter()
+                    .flatten()
+                    .map(String::as_str),
+            );
+        }
+    }
+
+    false
+}
+
+/// Maximum number of values of an enum, which can be part of a Delphi set
+const MAX_SET_VALUES: usize = 256;
+
+/// Builds the enum of a schema with enum values.
+///
+/// The variants are named after the `x-enum-varnames` or `x-enumNames` extension,
+/// which lists a symbolic name for each value, falling back to the value itself.
+/// An enum with `x-flags` is a set of flags, if a Delphi set can hold all of its values.
+fn build_enum_type(name: &str, schema: &Schema, prefix: Option<String>) -> EnumType {
     let name = capitalize(name);
     let variant_prefix = get_enum_variant_prefix(&name, &prefix.unwrap_or_default());
+    let variant_names = schema
+        .x_fields
+        .get("x-enum-varnames")
+        .or_else(|| schema.x_fields.get("x-enumNames"))
+        .and_then(Value::as_array);
+
+    let is_flags = schema
+        .x_fields
+        .get("x-flags")
+        .and_then(Value::as_bool)
+        .unwrap_or(false)
+        && schema.enum_values.len() <= MAX_SET_VALUES;
 
     EnumType {
         name: name.clone(),
-        variants: variants
+        is_flags,
+        variants: schema
+            .enum_values
             .iter()
-            .filter_map(|v| {
+            .enumerate()
+            .filter_map(|(i, v)| {
+                let variant_name = variant_names
+                    .and_then(|n| n.get(i))
+                    .and_then(Value::as_str)
+                    .filter(|n| !n.is_empty());
+
                 v.as_str().map(|s| EnumVariant {
-                    name: variant_prefix.clone() + &sanitize_name(&capitalize(s)),
+                    name: variant_prefix.clone()
+                        + &sanitize_name(&capitalize(variant_name.unwrap_or(s))),
                     key: s.to_owned(),
                 })
             })
             .collect::<Vec<EnumVariant>>(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recursive_schemas_are_collected_once() {
+        let spec = sw4rm_rs::from_reader(
+            r##"
+openapi: 3.0.0
+info:
+  title: Tree
+  version: "1.0"
+paths: {}
+components:
+  schemas:
+    Node:
+      type: object
+      properties:
+        parent:
+          $ref: "#/components/schemas/Node"
+        children:
+          type: array
+          items:
+            $ref: "#/components/schemas/Node"
+        owner:
+          $ref: "#/components/schemas/Person"
+    Person:
+      type: object
+      properties:
+        name:
+          type: string
+"##
+            .as_bytes(),
+        )
+        .unwrap();
+
+        let (mut class_types, _) = collect_types(&spec, &None).unwrap();
+        mark_recursive_properties(&mut class_types);
+
+        let names = class_types
+            .iter()
+            .map(|c| c.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["Person", "Node"]);
+
+        let properties = class_types[1]
+            .properties
+            .iter()
+            .map(|p| (p.name.as_str(), p.type_.name.as_str(), p.is_recursive))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            properties,
+            [
+                ("Children", "Node", true),
+                ("Owner", "Person", false),
+                ("Parent", "Node", true),
+            ]
+        );
+    }
+
+    #[test]
+    fn enum_variants_are_named_by_extension() {
+        let spec = sw4rm_rs::from_reader(
+            r##"
+openapi: 3.0.0
+info:
+  title: Orders
+  version: "1.0"
+paths: {}
+components:
+  schemas:
+    Status:
+      type: string
+      enum: ["1", "2", "3"]
+      x-enum-varnames: [active, inactive]
+    Kind:
+      type: string
+      enum: [a, b]
+      x-enumNames: [Person, Company]
+"##
+            .as_bytes(),
+        )
+        .unwrap();
+
+        let (_, enum_types) = collect_types(&spec, &None).unwrap();
+
+        let variants = enum_types
+            .iter()
+            .flat_map(|e| &e.variants)
+            .map(|v| (v.name.as_str(), v.key.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            variants,
+            [
+                ("kPerson", "a"),
+                ("kCompany", "b"),
+                ("sActive", "1"),
+                ("sInactive", "2"),
+                ("s3", "3"),
+            ]
+        );
+    }
+
+    #[test]
+    fn additional_properties_are_collected_as_maps() {
+        let spec = sw4rm_rs::from_reader(
+            r##"
+openapi: 3.0.0
+info:
+  title: Inventory
+  version: "1.0"
+paths: {}
+components:
+  schemas:
+    Item:
+      type: object
+      properties:
+        stock:
+          type: object
+          additionalProperties:
+            type: integer
+        states:
+          type: object
+          additionalProperties:
+            type: string
+            enum: [open, closed]
+        suppliers:
+          type: object
+          additionalProperties:
+            $ref: "#/components/schemas/Supplier"
+        tags:
+          type: object
+          additionalProperties:
+            type: array
+            items:
+              type: string
+    Supplier:
+      type: object
+      properties:
+        name:
+          type: string
+"##
+            .as_bytes(),
+        )
+        .unwrap();
+
+        let (class_types, enum_types) = collect_types(&spec, &None).unwrap();
+        let item = class_types.iter().find(|c| c.name == "Item").unwrap();
+
+        let properties = item
+            .properties
+            .iter()
+            .map(|p| {
+                (
+                    p.name.as_str(),
+                    p.type_.name.as_str(),
+                    p.is_map_type,
+                    p.is_owned,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            properties,
+            [
+                ("States", "StatesValue", true, true),
+                ("Stock", "integer", true, true),
+                ("Suppliers", "Supplier", true, true),
+            ]
+        );
+        assert_eq!(enum_types[0].name, "StatesValue");
+    }
+}
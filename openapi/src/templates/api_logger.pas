@@ -0,0 +1,108 @@
+{% import "macros.pas" as macros %}
+
+{%- set timestamp = now() | date(format="%d.%m.%Y %H:%m:%S") -%}
+{% if header -%}
+{{ header }}
+{% endif -%}
+// ========================================================================== //
+// Generated by Delphi Code Gen - Mode OpenAPI                                //
+// {{ macros::fixed_size_line(content="Version: " ~ crate_version, size=74) }} //
+// {{ macros::fixed_size_line(content="Timestamp: " ~ timestamp, size=74) }} //
+// ========================================================================== //
+
+unit uApiLogger;
+
+interface
+
+uses System.JSON,
+     System.SysUtils;
+
+type
+  /// <summary>Request executed by a generated client</summary>
+  TApiRequestLogEntry = record
+    /// <summary>operationId of the operation or the name of the client method, if it has none</summary>
+    OperationId: string;
+    Method: string;
+    Url: string;
+    /// <summary>0, if no response has been received</summary>
+    StatusCode: Integer;
+    DurationMs: Int64;
+    /// <summary>Only set, if the client logs bodies. Values of sensitive fields are redacted.</summary>
+    RequestBody: string;
+    /// <summary>Only set, if the client logs bodies and the response is text. Values of sensitive fields are redacted.</summary>
+    ResponseBody: string;
+    /// <summary>Message of the exception, which aborted the request</summary>
+    ErrorMessage: string;
+  end;
+
+  /// <summary>Receives an entry for every request of the clients, whose settings reference the logger</summary>
+  IApiLogger = interface
+    {{ interface_guid(name="IApiLogger") }}
+    procedure LogRequest(const pEntry: TApiRequestLogEntry);
+  end;
+
+  /// <summary>Functions called by the generated clients to prepare the logged bodies</summary>
+  TApiLogHelper = class
+  strict private
+    class procedure RedactValue(pValue: TJSONValue; const pSensitiveKeys: array of string); static;
+  public
+    /// <summary>
+    /// Replaces the values of the keys in pSensitiveKeys by "***" at any depth of the JSON document.
+    /// A body, which isn't JSON, is returned unchanged.
+    /// </summary>
+    class function Redact(const pBody: string; const pSensitiveKeys: array of string): string; static;
+    /// <summary>Decides whether a body with the content type can be logged as text</summary>
+    class function IsTextContent(const pContentType: string): Boolean; static;
+  end;
+
+implementation
+
+uses System.StrUtils;
+
+const
+  cnRedactedValue = '***';
+
+{ TApiLogHelper }
+
+class procedure TApiLogHelper.RedactValue(pValue: TJSONValue; const pSensitiveKeys: array of string);
+begin
+  if pValue is TJSONObject then begin
+    for var vPair in TJSONObject(pValue) do begin
+      if MatchText(vPair.JsonString.Value, pSensitiveKeys) then begin
+        vPair.JsonValue := TJSONString.Create(cnRedactedValue);
+      end else begin
+        RedactValue(vPair.JsonValue, pSensitiveKeys);
+      end;
+    end;
+  end else if pValue is TJSONArray then begin
+    for var vItem in TJSONArray(pValue) do begin
+      RedactValue(vItem, pSensitiveKeys);
+    end;
+  end;
+end;
+
+class function TApiLogHelper.Redact(const pBody: string; const pSensitiveKeys: array of string): string;
+begin
+  if Length(pSensitiveKeys) = 0 then Exit(pBody);
+
+  var vJson := TJSONValue.ParseJSONValue(pBody);
+
+  if not Assigned(vJson) then Exit(pBody);
+
+  try
+    RedactValue(vJson, pSensitiveKeys);
+    Result := vJson.ToJSON;
+  finally
+    FreeAndNil(vJson);
+  end;
+end;
+
+class function TApiLogHelper.IsTextContent(const pContentType: string): Boolean;
+begin
+  Result := StartsText('text/', pContentType)
+    or ContainsText(pContentType, 'json')
+    or ContainsText(pContentType, 'xml')
+    or StartsText('application/x-www-form-urlencoded', pContentType);
+end;
+
+end.
@@ -1,6 +1,9 @@
 {% import "macros.pas" as macros %}
 
 {%- set timestamp = now() | date(format="%d.%m.%Y %H:%m:%S") -%}
+{% if header -%}
+{{ header }}
+{% endif -%}
 // ========================================================================== //
 // Generated by Delphi Code Gen - Mode OpenAPI                                //
 // {{ macros::fixed_size_line(content="Version: " ~ crate_version, size=74) }} //
@@ -10,93 +13,388 @@
 // {{ macros::fixed_size_line(content="API Spec Version: " ~ api_spec_version, size=74) }} //
 // ========================================================================== //
 
-unit u{{unitPrefix}}ApiClient,
-     u{{unitPrefix}}ApiModels;
+unit u{{unitPrefix}}{{client_name}}Client;
 
 interface
 
-uses u{{unitPrefix}}ApiClient,
+uses u{{unitPrefix}}{{client_name}}ClientInterface,
+     {% if is_group -%}
+     u{{unitPrefix}}ApiClientBase,
+     {% endif -%}
+     {% for unit in model_units -%}
+     u{{unitPrefix}}{{unit}},
+     {% endfor -%}
+     {% if logs_requests -%}
+     uApiLogger,
+     {% endif -%}
+     System.Classes,
+     {% if not is_group -%}
+     System.Net.HttpClient,
+     System.Net.URLClient,
+     {% endif -%}
+     {% for directive in platform_directives -%}
+     {% if directive.units or directive.else_units -%}
+     {$IFDEF {{directive.define}}}
+     {% for unit in directive.units -%}
+     {{unit}},
+     {% endfor -%}
+     {% if directive.else_units -%}
+     {$ELSE}
+     {% for unit in directive.else_units -%}
+     {{unit}},
+     {% endfor -%}
+     {% endif -%}
+     {$ENDIF}
+     {% endif -%}
+     {% endfor -%}
      REST.Client;
 
-type  
-  T{{prefix}}ApiClient = class(TInterfacedObject, I{{prefix}}ApiClient)
+type
+  {% if not is_group -%}
+  /// <summary>Called before a request is executed. URL, headers and body can be read and modified.</summary>
+  T{{prefix}}BeforeRequestEvent = reference to procedure(pRequest: TRESTRequest);
+  /// <summary>Called after the response of a request has been received.</summary>
+  T{{prefix}}AfterResponseEvent = reference to procedure(pRequest: TRESTRequest; pResponse: TRESTResponse);
+
+  /// <summary>
+  /// Connection settings of a client, which are applied to the HTTP client it
+  /// reuses for all of its requests. Empty proxy settings use the proxy of the system.
+  /// </summary>
+  T{{prefix}}ClientSettings = record
+    ProxyServer: string;
+    ProxyPort: Integer;
+    ProxyUsername: string;
+    ProxyPassword: string;
+    /// <summary>Timeout in milliseconds to establish the connection</summary>
+    ConnectTimeout: Integer;
+    /// <summary>Timeout in milliseconds to receive the response</summary>
+    ReadTimeout: Integer;
+    SecureProtocols: THTTPSecureProtocols;
+    /// <summary>Decides whether a server certificate, which failed the validation of the system, is accepted</summary>
+    OnValidateCertificate: TValidateCertificateEvent;    {%- if logs_requests %}
+    /// <summary>Receives an entry for every request, nothing is logged if unassigned</summary>
+    Logger: IApiLogger;
+    /// <summary>Logs request and response bodies with the values of sensitive fields redacted</summary>
+    LogBodies: Boolean;
+    {%- endif %}
+
+    class function Default: T{{prefix}}ClientSettings; static;
+  end;
+
+  {% endif -%}
+  /// <summary>
+  /// The client reuses a single HTTP client and its connections for all requests.
+  /// An instance must not be used by several threads at the same time, create a
+  /// client per thread instead.
+  /// </summary>
+  T{{prefix}}{{client_name}}Client = class(TInterfacedObject, I{{prefix}}{{client_name}}Client)
   strict private
     FClient: TRESTClient;
+    FBeforeRequest: T{{prefix}}BeforeRequestEvent;
+    FAfterResponse: T{{prefix}}AfterResponseEvent;
+    {%- if logs_requests %}
+    FLogger: IApiLogger;
+    FLogBodies: Boolean;
+
+    procedure LogRequest(const pOperationId: string; pRequest: TRESTRequest; const pDurationMs: Int64; const pErrorMessage: string);
+    {%- endif %}
+    {%- if has_downloads %}
+    FDownloadProgress: T{{prefix}}DownloadProgressEvent;
+    /// <summary>Bytes of the file, which were received before the current request</summary>
+    FDownloadOffset: Int64;
+
+    procedure ReceiveData(const Sender: TObject; AContentLength: Int64; AReadCount: Int64; var AAbort: Boolean);
+    /// <summary>
+    /// Executes a file download with a THTTPClient, which writes the response into pTarget
+    /// and reports the progress.{% if resumable_downloads %} A download is resumed after the bytes of pTarget.{% endif %}
+    /// AfterResponse isn't called, because there is no TRESTResponse.
+    /// </summary>
+    procedure Download(pRequest: TRESTRequest; pTarget: TStream; pOnProgress: T{{prefix}}DownloadProgressEvent);
+    {%- endif %}
   public
-    constructor Create(const pBaseUrl: string);
+    constructor Create(const pBaseUrl: string); overload;
+    constructor Create(const pBaseUrl: string; const pSettings: T{{prefix}}ClientSettings); overload;
     destructor Destroy; override;
 
+    property BeforeRequest: T{{prefix}}BeforeRequestEvent read FBeforeRequest write FBeforeRequest;
+    property AfterResponse: T{{prefix}}AfterResponseEvent read FAfterResponse write FAfterResponse;
+
     {% for endpoint in endpoints -%}
-    {% if endpoint.response_type.name == "none" -%}
-    procedure {{endpoint.name}}({{macros::join_args(args=endpoint.args)}}
-      {%- if not endpoint.request_body.name == "none" -%}
-      {%- set args_length = endpoint.args | length -%}
-      {%- if args_length > 0 -%}; {% endif -%}
-      pBody: {{ macros::type_name(base_type=endpoint.request_body.name, is_list_type=false, is_reference_type=endpoint.request_body.is_class, is_enum_type=endpoint.request_body.is_enum) }}
-      {%- endif -%});
-    {% else -%}
-    function {{endpoint.name}}({{macros::join_args(args=endpoint.args)}}
-      {%- if not endpoint.request_body.name == "none" -%}
-      {%- set args_length = endpoint.args | length -%}
-      {%- if args_length > 0 -%}; {% endif -%}
-      pBody: {{ macros::type_name(base_type=endpoint.request_body.name, is_list_type=false, is_reference_type=endpoint.request_body.is_class, is_enum_type=endpoint.request_body.is_enum) }}
-      {%- endif -%}): {{ macros::type_name(base_type=endpoint.response_type.name, is_list_type=false, is_reference_type=endpoint.response_type.is_class, is_enum_type=endpoint.response_type.is_enum) }};
+    {% for line in endpoint.deprecation_comment -%}
+    // {{line}}
+    {% endfor -%}
+    {{ macros::endpoint_signature(endpoint=endpoint) }}
+      {%- if request_options %} overload;{% endif %}
+      {%- if endpoint.deprecation_comment %} deprecated;{% endif %}
+    {% if request_options -%}
+    {{ macros::endpoint_signature(endpoint=endpoint, with_options=true) }} overload;
+      {%- if endpoint.deprecation_comment %} deprecated;{% endif %}
     {% endif -%}
     {% endfor %}
   end;
 
 implementation
 
-uses REST.Types;
+uses {% if unwraps_envelopes -%}
+     uJsonHelper,
+     {% endif -%}
+     REST.Types,
+     System.DateUtils,
+     {% if logs_requests -%}
+     System.Diagnostics,
+     {% endif -%}
+     {% if unwraps_envelopes -%}
+     System.JSON,
+     {% endif -%}
+     {% if has_downloads and is_group -%}
+     System.Net.HttpClient,
+     System.Net.URLClient,
+     {% endif -%}
+     System.NetEncoding,
+     System.SysUtils;
+
+{% for directive in platform_directives -%}
+{% if directive.code or directive.else_code -%}
+{$IFDEF {{directive.define}}}
+{% if directive.code -%}
+{{ directive.code }}
+{% endif -%}
+{% if directive.else_code -%}
+{$ELSE}
+{{ directive.else_code }}
+{% endif -%}
+{$ENDIF}
+
+{% endif -%}
+{% endfor -%}
+{% if logs_requests and sensitive_keys -%}
+{%- set sensitive_key_count = sensitive_keys | length -%}
+const
+  /// <summary>Keys of the properties marked with x-sensitive</summary>
+  cnSensitiveKeys: array[0..{{ sensitive_key_count - 1 }}] of string = (
+    {%- for key in sensitive_keys %}{{key | pascal_literal}}{% if not loop.last %}, {% endif %}{% endfor -%}
+  );
 
-{ T{{prefix}}ApiClient }
+{% endif -%}
+{% if not is_group -%}
+{ T{{prefix}}ClientSettings }
 
-constructor T{{prefix}}ApiClient.Create(const pBaseUrl: string);
+class function T{{prefix}}ClientSettings.Default: T{{prefix}}ClientSettings;
 begin
-  inherited;
+  Result := System.Default(T{{prefix}}ClientSettings);
+  Result.ConnectTimeout := 60000;
+  Result.ReadTimeout := 60000;
+  Result.SecureProtocols := CHTTPDefSecureProtocols;
+end;
+
+{% endif -%}
+{ T{{prefix}}{{client_name}}Client }
+
+constructor T{{prefix}}{{client_name}}Client.Create(const pBaseUrl: string);
+begin
+  Create(pBaseUrl, T{{prefix}}ClientSettings.Default);
+end;
+
+constructor T{{prefix}}{{client_name}}Client.Create(const pBaseUrl: string; const pSettings: T{{prefix}}ClientSettings);
+begin
+  inherited Create;
 
   FClient := TRESTClient.Create(pBaseUrl);
+  FClient.ProxyServer := pSettings.ProxyServer;
+  FClient.ProxyPort := pSettings.ProxyPort;
+  FClient.ProxyUsername := pSettings.ProxyUsername;
+  FClient.ProxyPassword := pSettings.ProxyPassword;
+  FClient.ConnectTimeout := pSettings.ConnectTimeout;
+  FClient.ReadTimeout := pSettings.ReadTimeout;
+  FClient.SecureProtocols := pSettings.SecureProtocols;
+  FClient.OnValidateCertificate := pSettings.OnValidateCertificate;
+  {%- if logs_requests %}
+  FLogger := pSettings.Logger;
+  FLogBodies := pSettings.LogBodies;
+  {%- endif %}
 end;
 
-destructor T{{prefix}}ApiClient.Destroy;
+destructor T{{prefix}}{{client_name}}Client.Destroy;
 begin
   FreeAndNil(FClient);
 
   inherited;
 end;
+{% if logs_requests %}
+procedure T{{prefix}}{{client_name}}Client.LogRequest(const pOperationId: string; pRequest: TRESTRequest; const pDurationMs: Int64; const pErrorMessage: string);
+begin
+  if not Assigned(FLogger) then Exit;
+
+  var vEntry := Default(TApiRequestLogEntry);
+  vEntry.OperationId := pOperationId;
+  vEntry.Method := RESTRequestMethodToString(pRequest.Method);
+  vEntry.Url := pRequest.GetFullRequestURL(False);
+  vEntry.DurationMs := pDurationMs;
+  vEntry.ErrorMessage := pErrorMessage;
+
+  if Assigned(pRequest.Response) then begin
+    vEntry.StatusCode := pRequest.Response.StatusCode;
+  end;
+
+  if FLogBodies then begin
+    for var vIndex := 0 to pRequest.Params.Count - 1 do begin
+      if pRequest.Params[vIndex].Kind = pkREQUESTBODY then begin
+        vEntry.RequestBody := TApiLogHelper.Redact(pRequest.Params[vIndex].Value, {% if sensitive_keys %}cnSensitiveKeys{% else %}[]{% endif %});
+      end;
+    end;
+
+    if Assigned(pRequest.Response) and TApiLogHelper.IsTextContent(pRequest.Response.ContentType) then begin
+      vEntry.ResponseBody := TApiLogHelper.Redact(pRequest.Response.Content, {% if sensitive_keys %}cnSensitiveKeys{% else %}[]{% endif %});
+    end;
+  end;
+
+  FLogger.LogRequest(vEntry);
+end;
+{% endif %}
+{%- if has_downloads %}
+procedure T{{prefix}}{{client_name}}Client.ReceiveData(const Sender: TObject; AContentLength: Int64; AReadCount: Int64; var AAbort: Boolean);
+begin
+  if not Assigned(FDownloadProgress) then Exit;
+
+  if AContentLength > 0 then begin
+    FDownloadProgress(FDownloadOffset + AReadCount, FDownloadOffset + AContentLength);
+  end else begin
+    FDownloadProgress(FDownloadOffset + AReadCount, -1);
+  end;
+end;
+
+procedure T{{prefix}}{{client_name}}Client.Download(pRequest: TRESTRequest; pTarget: TStream; pOnProgress: T{{prefix}}DownloadProgressEvent);
+begin
+  var vHttp := THTTPClient.Create;
+  var vHeaders: TNetHeaders := [];
+  var vBody: TStringStream := nil;
+  {%- if not resumable_downloads %}
+  var vContent := TMemoryStream.Create;
+  {%- endif %}
+
+  try
+    vHttp.ProxySettings := TProxySettings.Create(FClient.ProxyServer, FClient.ProxyPort, FClient.ProxyUsername, FClient.ProxyPassword);
+    vHttp.ConnectionTimeout := FClient.ConnectTimeout;
+    vHttp.ResponseTimeout := FClient.ReadTimeout;
+    vHttp.SecureProtocols := FClient.SecureProtocols;
+    vHttp.OnValidateServerCertificate := FClient.OnValidateCertificate;
+    vHttp.OnReceiveData := ReceiveData;
+
+    if pRequest.Timeout > 0 then begin
+      vHttp.ResponseTimeout := pRequest.Timeout;
+    end;
+
+    for var vIndex := 0 to pRequest.Params.Count - 1 do begin
+      var vParam := pRequest.Params[vIndex];
+
+      if vParam.Kind = pkHTTPHEADER then begin
+        vHeaders := vHeaders + [TNetHeader.Create(vParam.Name, vParam.Value)];
+      end else if vParam.Kind = pkREQUESTBODY then begin
+        vBody := TStringStream.Create(vParam.Value, TEncoding.UTF8);
+        vHeaders := vHeaders + [TNetHeader.Create('Content-Type', ContentTypeToString(vParam.ContentType))];
+      end;
+    end;
 
+    FDownloadProgress := pOnProgress;
+    {%- if resumable_downloads %}
+    FDownloadOffset := pTarget.Size;
+
+    if FDownloadOffset > 0 then begin
+      vHeaders := vHeaders + [TNetHeader.Create('Range', 'bytes=' + IntToStr(FDownloadOffset) + '-')];
+    end;
+
+    // The received bytes are written into the target at once, so a failed download keeps them
+    pTarget.Seek(0, soEnd);
+    {%- else %}
+    FDownloadOffset := 0;
+    {%- endif %}
+
+    var vResponse := vHttp.Execute(RESTRequestMethodToString(pRequest.Method), pRequest.GetFullRequestURL(True), vBody, {% if resumable_downloads %}pTarget{% else %}vContent{% endif %}, vHeaders);
+
+    if (vResponse.StatusCode < 200) or (vResponse.StatusCode >= 300) then begin
+      {%- if resumable_downloads %}
+      // Drops the body of the error
+      pTarget.Size := FDownloadOffset;
+      {%- endif %}
+      raise T{{prefix}}ApiException.Create();
+    end;
+    {%- if resumable_downloads %}
+
+    if (FDownloadOffset > 0) and (vResponse.StatusCode <> 206) then begin
+      // The server ignored the range and sent the whole file after the bytes received before
+      var vFile := TMemoryStream.Create;
+
+      try
+        pTarget.Position := FDownloadOffset;
+        vFile.CopyFrom(pTarget, pTarget.Size - FDownloadOffset);
+        pTarget.Size := 0;
+        pTarget.CopyFrom(vFile, 0);
+      finally
+        vFile.Free;
+      end;
+    end;
+    {%- else %}
+
+    pTarget.CopyFrom(vContent, 0);
+    {%- endif %}
+  finally
+    FDownloadProgress := nil;
+    {%- if not resumable_downloads %}
+    vContent.Free;
+    {%- endif %}
+    vBody.Free;
+    vHttp.Free;
+  end;
+end;
+{% endif %}
 {% for endpoint in endpoints %}
-{% if endpoint.response_type.name == "none" -%}
-procedure T{{prefix}}ApiClient.{{endpoint.name}}({{macros::join_args(args=endpoint.args)}}
-{%- if not endpoint.request_body.name == "none" -%}
-{%- set args_length = endpoint.args | length -%}
-{%- if args_length > 0 -%}{{"; "}}{% endif -%}
-pBody: {{ macros::type_name(base_type=endpoint.request_body.name, is_list_type=false, is_reference_type=endpoint.request_body.is_class, is_enum_type=endpoint.request_body.is_enum) }}
-{%- endif -%});
-{% else -%}
-function T{{prefix}}ApiClient.{{endpoint.name}}({{macros::join_args(args=endpoint.args)}}
-{%- if not endpoint.request_body.name == "none" -%}
-{%- set args_length = endpoint.args | length -%}
-{%- if args_length > 0 -%}{{"; "}}{% endif -%}
-pBody: {{ macros::type_name(base_type=endpoint.request_body.name, is_list_type=false, is_reference_type=endpoint.request_body.is_class, is_enum_type=endpoint.request_body.is_enum) }}
-{%- endif -%}): {{ macros::type_name(base_type=endpoint.response_type.name, is_list_type=false, is_reference_type=endpoint.response_type.is_class, is_enum_type=endpoint.response_type.is_enum) }};
+{% set class_name = "T" ~ prefix ~ client_name ~ "Client" -%}
+{% if endpoint.is_binary_response %}{% set execute = "Download(vRequest, pTarget, pOnProgress)" %}{% else %}{% set execute = "vRequest.Execute" %}{% endif -%}
+{% if request_options -%}
+{{ macros::endpoint_signature(endpoint=endpoint, class_name=class_name) }}
+begin
+  {% if not endpoint.response_type.name == "none" %}Result := {% endif %}{{endpoint.name}}({{ macros::forward_args(endpoint=endpoint) }});
+end;
+
 {% endif -%}
+{{ macros::endpoint_signature(endpoint=endpoint, class_name=class_name, with_options=request_options) }}
 begin
   var vRequest := TRESTRequest.Create(nil);
 
   try
     vRequest.Client := FClient;
     vRequest.Method := rm{{endpoint.method}};
-    vRequest.Resource := '{{endpoint.path}}';
+    {% for segment in endpoint.path_segments -%}
+    {% if segment.arg and segment.arg.type_name == "string" and not segment.arg.is_enum -%}
+    if p{{segment.arg.name}} = '' then begin
+      raise EArgumentException.Create({{ 'Path parameter "' ~ segment.arg.key ~ '" must not be empty' | pascal_literal }});
+    end;
+    {% endif -%}
+    {% endfor -%}
     {% for param in endpoint.args -%}
-    {% if param.arg_type == "path" -%}
-    vRequest.AddParameter('{{param.name}}', p{{param.name}}, pkURLSEGMENT);
-    {% elif param.arg_type == "query" -%}
+    {% if param.arg_type == "query" and param.allowed_values -%}
+    {% set allowed_values = param.allowed_values | map(attribute="literal") | join(sep=", ") -%}
+    if {% if not param.is_required %}(p{{param.name}} <> {{ macros::omitted_value(arg=param) }}) and {% endif %}not ({% for value in param.allowed_values %}{% if not loop.first %} or {% endif %}(p{{param.name}} = {{value.literal}}){% endfor %}) then begin
+      raise EArgumentException.Create({{ 'Query parameter "' ~ param.key ~ '" must be one of ' ~ allowed_values | pascal_literal }});
+    end;
+    {% endif -%}
+    {% endfor -%}
+    vRequest.Resource := {% for segment in endpoint.path_segments -%}
+    {%- if not loop.first %}
+      + {% endif -%}
+    {%- if segment.arg -%}
+    TNetEncoding.URL.EncodePath({{ macros::arg_value(arg=segment.arg) }}, [Ord('/')])
+    {%- else -%}
+    {{segment.literal | pascal_literal}}
+    {%- endif -%}
+    {%- endfor %};
+    {% for param in endpoint.args -%}
+    {% if param.arg_type == "query" -%}
     {% if param.is_required -%}
-    vRequest.AddParameter('{{param.name}}', p{{param.name}}, pkQUERY);
+    vRequest.AddParameter({{param.key | pascal_literal}}, {{ macros::arg_value(arg=param) }}, pkQUERY);
     {% else -%}
-    if p{{param.name}} <> Default({{param.type_name}}) then begin
-      vRequest.AddParameter('{{param.name}}', p{{param.name}}, pkQUERY);
+    if p{{param.name}} <> {{ macros::omitted_value(arg=param) }} then begin
+      vRequest.AddParameter({{param.key | pascal_literal}}, {{ macros::arg_value(arg=param) }}, pkQUERY);
     end;
     {% endif -%}
     {% elif param.arg_type == "body" -%}
@@ -110,16 +408,99 @@ begin
     {% endif -%}
     {% endfor -%}
     {%- if not endpoint.request_body.name == "none" -%}
+    {% if endpoint.request_body_encoding == "form_url_encoded" -%}
+    for var vIndex := 0 to pBody.Count - 1 do begin
+      vRequest.AddParameter(pBody.Names[vIndex], pBody.ValueFromIndex[vIndex], pkGETorPOST);
+    end;
+    {% elif endpoint.request_body_encoding == "plain_text" -%}
+    vRequest.AddBody(pBody, ctTEXT_PLAIN);
+    {% else -%}
+    {% if validate_request_bodies and endpoint.request_body.is_class -%}
+    pBody.Validate;
+    {% endif -%}
     vRequest.AddBody(pBody.ToJson, ctAPPLICATION_JSON);
     {% endif -%}
+    {% endif -%}
+    {% if request_options -%}
+    for var vHeader in pOptions.Headers do begin
+      vRequest.AddParameter(vHeader.Name, vHeader.Value, pkHTTPHEADER, [poDoNotEncode]);
+    end;
 
-    vRequest.Execute;
+    for var vParam in pOptions.QueryParams do begin
+      vRequest.AddParameter(vParam.Name, vParam.Value, pkQUERY);
+    end;
 
-    {% if not endpoint.response_type.name == "none" -%}
+    if pOptions.Timeout > 0 then begin
+      vRequest.Timeout := pOptions.Timeout;
+    end;
+    {% endif -%}
+
+    if Assigned(FBeforeRequest) then begin
+      FBeforeRequest(vRequest);
+    end;
+
+    {% if logs_requests -%}
+    {%- if endpoint.operation_id -%}
+    {%- set operation_id = endpoint.operation_id -%}
+    {%- else -%}
+    {%- set operation_id = endpoint.name -%}
+    {%- endif -%}
+    var vStopwatch := TStopwatch.StartNew;
+
+    try
+      {{execute}};
+    except
+      on E: Exception do begin
+        LogRequest({{operation_id | pascal_literal}}, vRequest, vStopwatch.ElapsedMilliseconds, E.Message);
+        raise;
+      end;
+    end;
+
+    LogRequest({{operation_id | pascal_literal}}, vRequest, vStopwatch.ElapsedMilliseconds, '');
+    {%- else -%}
+    {{execute}};
+    {%- endif %}
+    {%- if not endpoint.is_binary_response %}
+
+    if Assigned(FAfterResponse) then begin
+      FAfterResponse(vRequest, vRequest.Response);
+    end;
+    {%- endif %}
+
+    {% if endpoint.envelope -%}
+    {%- set envelope = endpoint.envelope -%}
+    var vEnvelope := vRequest.Response.JSONValue;
+    {%- if envelope.error_key %}
+    var vError: TJSONValue := nil;
+
+    if Assigned(vEnvelope) then begin
+      vError := vEnvelope.FindValue({{envelope.error_key | pascal_literal}});
+    end;
+
+    if Assigned(vError) and not (vError is TJSONNull) then begin
+      raise T{{prefix}}ApiException.Create(vError.ToJSON);
+    end;
+    {%- endif %}
+
+    if (vRequest.Response.StatusCode < 200) or (vRequest.Response.StatusCode >= 300) or not Assigned(vEnvelope) then begin
+      raise T{{prefix}}ApiException.Create();
+    end;
+
+    {% if envelope.meta -%}
+    pMeta := {{ macros::from_json(json_obj_name="vEnvelope", base_type=envelope.meta.type_.name, is_list_type=envelope.meta.is_list_type, is_reference_type=envelope.meta.type_.is_class, is_enum_type=envelope.meta.type_.is_enum, key="'" ~ envelope.meta.key ~ "'") }};
+    {% endif -%}
+    Result := {{ macros::from_json(json_obj_name="vEnvelope", base_type=endpoint.response_type.name, is_list_type=endpoint.response_is_list, is_reference_type=endpoint.response_type.is_class, is_enum_type=endpoint.response_type.is_enum, key="'" ~ envelope.data_key ~ "'") }};
+    {% elif not endpoint.response_type.name == "none" -%}
     case vRequest.Response.StatusCode of
       {% for resp in endpoint.status_codes -%}
       {{resp.status_code}}: begin
-      {%- if resp.status_code is starting_with("2") %}
+      {%- if resp.status_code is starting_with("2") and endpoint.has_result_wrapper %}
+        {%- if resp.type_.name == "none" %}
+        Result := T{{prefix}}{{endpoint.response_type.name}}.Create{{resp.status_code}};
+        {%- else %}
+        Result := T{{prefix}}{{endpoint.response_type.name}}.Create{{resp.status_code}}({{ macros::from_json_raw(json_obj_name="vRequest.Response.JSONValue", base_type=resp.type_.name, is_list_type=resp.is_list_type, is_reference_type=resp.type_.is_class, is_enum_type=resp.type_.is_enum) }});
+        {%- endif %}
+      {% elif resp.status_code is starting_with("2") %}
         Result := {{ macros::from_json_raw(json_obj_name="vRequest.Response.JSONValue", base_type=resp.type_.name, is_list_type=resp.is_list_type, is_reference_type=resp.type_.is_class, is_enum_type=resp.type_.is_enum) }};
       {% else %}
         var vRes := {{ macros::from_json_raw(json_obj_name="vRequest.Response.JSONValue", base_type=resp.type_.name, is_list_type=resp.is_list_type, is_reference_type=resp.type_.is_class, is_enum_type=resp.type_.is_enum) }};
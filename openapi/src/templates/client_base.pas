@@ -0,0 +1,96 @@
+{% import "macros.pas" as macros %}
+
+{%- set timestamp = now() | date(format="%d.%m.%Y %H:%m:%S") -%}
+{% if header -%}
+{{ header }}
+{% endif -%}
+// ========================================================================== //
+// Generated by Delphi Code Gen - Mode OpenAPI                                //
+// {{ macros::fixed_size_line(content="Version: " ~ crate_version, size=74) }} //
+// {{ macros::fixed_size_line(content="Timestamp: " ~ timestamp, size=74) }} //
+//                                                                            //
+// {{ macros::fixed_size_line(content="Title: " ~ api_title, size=74) }} //
+// {{ macros::fixed_size_line(content="API Spec Version: " ~ api_spec_version, size=74) }} //
+// ========================================================================== //
+
+unit u{{unitPrefix}}ApiClientBase;
+
+interface
+
+uses {% if logs_requests -%}
+     uApiLogger,
+     {% endif -%}
+     System.Net.HttpClient,
+     System.Net.URLClient,
+     System.SysUtils,
+     REST.Client;
+
+type
+  T{{prefix}}ApiException = class(Exception)
+  end;
+  {%- if request_options %}
+
+  /// <summary>
+  /// Settings of a single request, which override or extend the settings of the client
+  /// </summary>
+  T{{prefix}}RequestOptions = record
+    /// <summary>Headers added to the request</summary>
+    Headers: TNetHeaders;
+    /// <summary>Query parameters added to the request</summary>
+    QueryParams: TArray<TNameValuePair>;
+    /// <summary>Timeout in milliseconds of the request, 0 keeps the timeouts of the client</summary>
+    Timeout: Integer;
+  end;
+  {%- endif %}
+  {%- if has_downloads %}
+
+  /// <summary>
+  /// Called while a file is downloaded with the bytes received so far and the size
+  /// of the file, which is -1 if the server didn't send it
+  /// </summary>
+  T{{prefix}}DownloadProgressEvent = reference to procedure(const pReceived, pTotal: Int64);
+  {%- endif %}
+
+  /// <summary>Called before a request is executed. URL, headers and body can be read and modified.</summary>
+  T{{prefix}}BeforeRequestEvent = reference to procedure(pRequest: TRESTRequest);
+  /// <summary>Called after the response of a request has been received.</summary>
+  T{{prefix}}AfterResponseEvent = reference to procedure(pRequest: TRESTRequest; pResponse: TRESTResponse);
+
+  /// <summary>
+  /// Connection settings of a client, which are applied to the HTTP client it
+  /// reuses for all of its requests. Empty proxy settings use the proxy of the system.
+  /// </summary>
+  T{{prefix}}ClientSettings = record
+    ProxyServer: string;
+    ProxyPort: Integer;
+    ProxyUsername: string;
+    ProxyPassword: string;
+    /// <summary>Timeout in milliseconds to establish the connection</summary>
+    ConnectTimeout: Integer;
+    /// <summary>Timeout in milliseconds to receive the response</summary>
+    ReadTimeout: Integer;
+    SecureProtocols: THTTPSecureProtocols;
+    /// <summary>Decides whether a server certificate, which failed the validation of the system, is accepted</summary>
+    OnValidateCertificate: TValidateCertificateEvent;    {%- if logs_requests %}
+    /// <summary>Receives an entry for every request, nothing is logged if unassigned</summary>
+    Logger: IApiLogger;
+    /// <summary>Logs request and response bodies with the values of sensitive fields redacted</summary>
+    LogBodies: Boolean;
+    {%- endif %}
+
+    class function Default: T{{prefix}}ClientSettings; static;
+  end;
+
+implementation
+
+{ T{{prefix}}ClientSettings }
+
+class function T{{prefix}}ClientSettings.Default: T{{prefix}}ClientSettings;
+begin
+  Result := System.Default(T{{prefix}}ClientSettings);
+  Result.ConnectTimeout := 60000;
+  Result.ReadTimeout := 60000;
+  Result.SecureProtocols := CHTTPDefSecureProtocols;
+end;
+
+end.
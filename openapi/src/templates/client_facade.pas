@@ -0,0 +1,105 @@
+{% import "macros.pas" as macros %}
+
+{%- set timestamp = now() | date(format="%d.%m.%Y %H:%m:%S") -%}
+{% if header -%}
+{{ header }}
+{% endif -%}
+// ========================================================================== //
+// Generated by Delphi Code Gen - Mode OpenAPI                                //
+// {{ macros::fixed_size_line(content="Version: " ~ crate_version, size=74) }} //
+// {{ macros::fixed_size_line(content="Timestamp: " ~ timestamp, size=74) }} //
+//                                                                            //
+// {{ macros::fixed_size_line(content="Title: " ~ api_title, size=74) }} //
+// {{ macros::fixed_size_line(content="API Spec Version: " ~ api_spec_version, size=74) }} //
+// ========================================================================== //
+
+unit u{{unitPrefix}}ApiClient;
+
+interface
+
+uses u{{unitPrefix}}ApiClientBase,
+     u{{unitPrefix}}ApiClientInterface,
+     {% for group in groups -%}
+     u{{unitPrefix}}{{group}}ClientInterface,
+     {% endfor -%}
+     REST.Client;
+
+type
+  /// <summary>
+  /// Creates the client of a tag on first use. The events and settings of the
+  /// facade are used for the requests of all clients. Like the clients, the
+  /// facade must not be used by several threads at the same time.
+  /// </summary>
+  T{{prefix}}ApiClient = class(TInterfacedObject, I{{prefix}}ApiClient)
+  strict private
+    FBaseUrl: string;
+    FSettings: T{{prefix}}ClientSettings;
+    FBeforeRequest: T{{prefix}}BeforeRequestEvent;
+    FAfterResponse: T{{prefix}}AfterResponseEvent;
+    {%- for group in groups %}
+    F{{group}}: I{{prefix}}{{group}}Client;
+    {%- endfor %}
+
+    procedure DoBeforeRequest(pRequest: TRESTRequest);
+    procedure DoAfterResponse(pRequest: TRESTRequest; pResponse: TRESTResponse);
+  public
+    constructor Create(const pBaseUrl: string); overload;
+    constructor Create(const pBaseUrl: string; const pSettings: T{{prefix}}ClientSettings); overload;
+
+    property BeforeRequest: T{{prefix}}BeforeRequestEvent read FBeforeRequest write FBeforeRequest;
+    property AfterResponse: T{{prefix}}AfterResponseEvent read FAfterResponse write FAfterResponse;
+{% for group in groups %}
+    function {{group}}: I{{prefix}}{{group}}Client;
+    {%- endfor %}
+  end;
+
+implementation
+
+uses {% for group in groups -%}
+     u{{unitPrefix}}{{group}}Client{% if loop.last %};{% else %},
+     {% endif %}
+     {%- endfor %}
+
+{ T{{prefix}}ApiClient }
+
+constructor T{{prefix}}ApiClient.Create(const pBaseUrl: string);
+begin
+  Create(pBaseUrl, T{{prefix}}ClientSettings.Default);
+end;
+
+constructor T{{prefix}}ApiClient.Create(const pBaseUrl: string; const pSettings: T{{prefix}}ClientSettings);
+begin
+  inherited Create;
+
+  FBaseUrl := pBaseUrl;
+  FSettings := pSettings;
+end;
+
+procedure T{{prefix}}ApiClient.DoBeforeRequest(pRequest: TRESTRequest);
+begin
+  if Assigned(FBeforeRequest) then begin
+    FBeforeRequest(pRequest);
+  end;
+end;
+
+procedure T{{prefix}}ApiClient.DoAfterResponse(pRequest: TRESTRequest; pResponse: TRESTResponse);
+begin
+  if Assigned(FAfterResponse) then begin
+    FAfterResponse(pRequest, pResponse);
+  end;
+end;
+{% for group in groups %}
+function T{{prefix}}ApiClient.{{group}}: I{{prefix}}{{group}}Client;
+begin
+  if not Assigned(F{{group}}) then begin
+    var vClient := T{{prefix}}{{group}}Client.Create(FBaseUrl, FSettings);
+    vClient.BeforeRequest := DoBeforeRequest;
+    vClient.AfterResponse := DoAfterResponse;
+
+    F{{group}} := vClient;
+  end;
+
+  Result := F{{group}};
+end;
+{% endfor %}
+end.
@@ -0,0 +1,36 @@
+{% import "macros.pas" as macros %}
+
+{%- set timestamp = now() | date(format="%d.%m.%Y %H:%m:%S") -%}
+{% if header -%}
+{{ header }}
+{% endif -%}
+// ========================================================================== //
+// Generated by Delphi Code Gen - Mode OpenAPI                                //
+// {{ macros::fixed_size_line(content="Version: " ~ crate_version, size=74) }} //
+// {{ macros::fixed_size_line(content="Timestamp: " ~ timestamp, size=74) }} //
+//                                                                            //
+// {{ macros::fixed_size_line(content="Title: " ~ api_title, size=74) }} //
+// {{ macros::fixed_size_line(content="API Spec Version: " ~ api_spec_version, size=74) }} //
+// ========================================================================== //
+
+unit u{{unitPrefix}}ApiClientInterface;
+
+interface
+
+uses {% for group in groups -%}
+     u{{unitPrefix}}{{group}}ClientInterface{% if loop.last %};{% else %},
+     {% endif %}
+     {%- endfor %}
+
+type
+  /// <summary>Gives access to the clients of all tags of the API</summary>
+  I{{prefix}}ApiClient = interface
+    {{ interface_guid(name="I" ~ prefix ~ "ApiClient") }}
+    {%- for group in groups %}
+    function {{group}}: I{{prefix}}{{group}}Client;
+    {%- endfor %}
+  end;
+
+implementation
+
+end.
@@ -1,6 +1,9 @@
 {% import "macros.pas" as macros %}
 
 {%- set timestamp = now() | date(format="%d.%m.%Y %H:%m:%S") -%}
+{% if header -%}
+{{ header }}
+{% endif -%}
 // ========================================================================== //
 // Generated by Delphi Code Gen - Mode OpenAPI                                //
 // {{ macros::fixed_size_line(content="Version: " ~ crate_version, size=74) }} //
@@ -10,37 +13,146 @@
 // {{ macros::fixed_size_line(content="API Spec Version: " ~ api_spec_version, size=74) }} //
 // ========================================================================== //
 
-unit u{{unitPrefix}}ApiClientInterface;
+unit u{{unitPrefix}}{{client_name}}ClientInterface;
 
 interface
 
-uses u{{unitPrefix}}ApiModels,
+uses {% for unit in model_units -%}
+     u{{unitPrefix}}{{unit}},
+     {% endfor -%}
+     {% if is_group and request_options or is_group and has_downloads -%}
+     u{{unitPrefix}}ApiClientBase,
+     {% endif -%}
+     System.Classes,
+     {% if request_options and not is_group -%}
+     System.Net.URLClient,
+     {% endif -%}
      System.SysUtils;
 
 type
+  {% if not is_group -%}
   T{{prefix}}ApiException = class(Exception)
   end;
 
-  I{{prefix}}ApiClient = interface
-    {% for endpoint in endpoints -%}
-    {% if endpoint.response_type.name == "none" -%}
-    procedure {{endpoint.name}}({{macros::join_args(args=endpoint.args)}}
-      {%- if not endpoint.request_body.name == "none" -%}
-      {%- set args_length = endpoint.args | length -%}
-      {%- if args_length > 0 -%}{{"; "}}{% endif -%}
-      pBody: {{ macros::type_name(base_type=endpoint.request_body.name, is_list_type=false, is_reference_type=endpoint.request_body.is_class, is_enum_type=endpoint.request_body.is_enum) }}
-      {%- endif -%});
+  {% if has_downloads -%}
+  /// <summary>
+  /// Called while a file is downloaded with the bytes received so far and the size
+  /// of the file, which is -1 if the server didn't send it
+  /// </summary>
+  T{{prefix}}DownloadProgressEvent = reference to procedure(const pReceived, pTotal: Int64);
+
+  {% endif -%}
+  {% if request_options -%}
+  /// <summary>
+  /// Settings of a single request, which override or extend the settings of the client
+  /// </summary>
+  T{{prefix}}RequestOptions = record
+    /// <summary>Headers added to the request</summary>
+    Headers: TNetHeaders;
+    /// <summary>Query parameters added to the request</summary>
+    QueryParams: TArray<TNameValuePair>;
+    /// <summary>Timeout in milliseconds of the request, 0 keeps the timeouts of the client</summary>
+    Timeout: Integer;
+  end;
+
+  {% endif -%}
+  {% endif -%}
+  {% for endpoint in endpoints | filter(attribute="has_result_wrapper", value=true) -%}
+  /// <summary>
+  /// Result of {{endpoint.name}}, whose success responses have different schemas.
+  /// StatusCode tells the response, whose value is returned by its TryGet function.
+  /// Values are owned and freed by the result.
+  /// </summary>
+  T{{prefix}}{{endpoint.response_type.name}} = class
+  strict private
+    FStatusCode: Integer;
+    {%- for resp in endpoint.status_codes %}
+    {%- if resp.status_code is starting_with("2") and not resp.type_.name == "none" %}
+    F{{resp.status_code}}: {{ macros::type_name(base_type=resp.type_.name, is_list_type=resp.is_list_type, is_reference_type=resp.type_.is_class, is_enum_type=resp.type_.is_enum) }};
+    {%- endif %}
+    {%- endfor %}
+  public
+    {% for resp in endpoint.status_codes -%}
+    {% if resp.status_code is starting_with("2") -%}
+    {% if resp.type_.name == "none" -%}
+    constructor Create{{resp.status_code}};
     {% else -%}
-    function {{endpoint.name}}({{macros::join_args(args=endpoint.args)}}
-      {%- if not endpoint.request_body.name == "none" -%}
-      {%- set args_length = endpoint.args | length -%}
-      {%- if args_length > 0 -%}{{"; "}}{% endif -%}
-      pBody: {{ macros::type_name(base_type=endpoint.request_body.name, is_list_type=false, is_reference_type=endpoint.request_body.is_class, is_enum_type=endpoint.request_body.is_enum) }}
-      {%- endif -%}): {{ macros::type_name(base_type=endpoint.response_type.name, is_list_type=false, is_reference_type=endpoint.response_type.is_class, is_enum_type=endpoint.response_type.is_enum) }};
+    constructor Create{{resp.status_code}}(const pValue: {{ macros::type_name(base_type=resp.type_.name, is_list_type=resp.is_list_type, is_reference_type=resp.type_.is_class, is_enum_type=resp.type_.is_enum) }});
+    {% endif -%}
+    {% endif -%}
+    {% endfor -%}
+    destructor Destroy; override;
+
+    {% for resp in endpoint.status_codes -%}
+    {% if resp.status_code is starting_with("2") and not resp.type_.name == "none" -%}
+    /// <summary>Returns the value of the response {{resp.status_code}}, False for other responses</summary>
+    function TryGet{{resp.status_code}}(out pValue: {{ macros::type_name(base_type=resp.type_.name, is_list_type=resp.is_list_type, is_reference_type=resp.type_.is_class, is_enum_type=resp.type_.is_enum) }}): Boolean;
+    {% endif -%}
+    {% endfor -%}
+    property StatusCode: Integer read FStatusCode;
+  end;
+
+  {% endfor -%}
+  I{{prefix}}{{client_name}}Client = interface
+    {{ interface_guid(name="I" ~ prefix ~ client_name ~ "Client") }}
+    {% for endpoint in endpoints -%}
+    {% for line in endpoint.deprecation_comment -%}
+    // {{line}}
+    {% endfor -%}
+    {{ macros::endpoint_signature(endpoint=endpoint) }}
+      {%- if request_options %} overload;{% endif %}
+      {%- if endpoint.deprecation_comment %} deprecated;{% endif %}
+    {% if request_options -%}
+    {{ macros::endpoint_signature(endpoint=endpoint, with_options=true) }} overload;
+      {%- if endpoint.deprecation_comment %} deprecated;{% endif %}
     {% endif -%}
     {% endfor %}
   end;
 
 implementation
+{% for endpoint in endpoints | filter(attribute="has_result_wrapper", value=true) %}
+{%- set class_name = "T" ~ prefix ~ endpoint.response_type.name %}
+{{"{"}} {{class_name}} {{"}"}}
+{% for resp in endpoint.status_codes -%}
+{% if resp.status_code is starting_with("2") %}
+{% if resp.type_.name == "none" -%}
+constructor {{class_name}}.Create{{resp.status_code}};
+begin
+  inherited Create;
+
+  FStatusCode := {{resp.status_code}};
+end;
+{% else -%}
+constructor {{class_name}}.Create{{resp.status_code}}(const pValue: {{ macros::type_name(base_type=resp.type_.name, is_list_type=resp.is_list_type, is_reference_type=resp.type_.is_class, is_enum_type=resp.type_.is_enum) }});
+begin
+  inherited Create;
+
+  FStatusCode := {{resp.status_code}};
+  F{{resp.status_code}} := pValue;
+end;
+{% endif -%}
+{% endif -%}
+{% endfor %}
+destructor {{class_name}}.Destroy;
+begin
+  {%- for resp in endpoint.status_codes %}
+  {%- if resp.status_code is starting_with("2") %}
+  {%- if resp.type_.is_class or resp.is_list_type %}
+  F{{resp.status_code}}.Free;
+  {%- endif %}
+  {%- endif %}
+  {%- endfor %}
 
+  inherited;
+end;
+{% for resp in endpoint.status_codes -%}
+{% if resp.status_code is starting_with("2") and not resp.type_.name == "none" %}
+function {{class_name}}.TryGet{{resp.status_code}}(out pValue: {{ macros::type_name(base_type=resp.type_.name, is_list_type=resp.is_list_type, is_reference_type=resp.type_.is_class, is_enum_type=resp.type_.is_enum) }}): Boolean;
+begin
+  Result := FStatusCode = {{resp.status_code}};
+  pValue := F{{resp.status_code}};
+end;
+{% endif -%}
+{% endfor -%}
+{% endfor %}
 end.
\ No newline at end of file
@@ -0,0 +1,132 @@
+{% import "macros.pas" as macros %}
+
+{%- set timestamp = now() | date(format="%d.%m.%Y %H:%m:%S") -%}
+{% if header -%}
+{{ header }}
+{% endif -%}
+// ========================================================================== //
+// Generated by Delphi Code Gen - Mode OpenAPI                                //
+// {{ macros::fixed_size_line(content="Version: " ~ crate_version, size=74) }} //
+// {{ macros::fixed_size_line(content="Timestamp: " ~ timestamp, size=74) }} //
+//                                                                            //
+// {{ macros::fixed_size_line(content="Title: " ~ api_title, size=74) }} //
+// {{ macros::fixed_size_line(content="API Spec Version: " ~ api_spec_version, size=74) }} //
+// ========================================================================== //
+
+unit u{{unitPrefix}}{{client_name}}ClientMock;
+
+interface
+
+uses u{{unitPrefix}}{{client_name}}ClientInterface,
+     {% for unit in model_units -%}
+     u{{unitPrefix}}{{unit}},
+     {% endfor -%}
+     System.Classes,
+     System.Generics.Collections,
+     System.SysUtils;
+
+type
+  /// <summary>
+  /// Implementation of I{{prefix}}{{client_name}}Client for unit tests.
+  /// Every call is recorded in Calls. The result of an operation is provided by
+  /// the assigned response, otherwise the default value is returned.
+  /// </summary>
+  T{{prefix}}Mock{{client_name}}Client = class(TInterfacedObject, I{{prefix}}{{client_name}}Client)
+  strict private
+    FCalls: TList<String>;
+  public
+    {% for endpoint in endpoints -%}
+    {% if endpoint.is_binary_response -%}
+    {{endpoint.name}}Response: TBytes;
+    {% elif not endpoint.response_type.name == "none" -%}
+    {{endpoint.name}}Response: TFunc<{{ macros::type_name(base_type=endpoint.response_type.name, is_list_type=endpoint.response_is_list, is_reference_type=endpoint.response_type.is_class, is_enum_type=endpoint.response_type.is_enum) }}>;
+    {% endif -%}
+    {% endfor %}
+    constructor Create;
+    destructor Destroy; override;
+
+    function CallCount(const pOperation: String): Integer;
+
+    {% for endpoint in endpoints -%}
+    {% for line in endpoint.deprecation_comment -%}
+    // {{line}}
+    {% endfor -%}
+    {{ macros::endpoint_signature(endpoint=endpoint) }}
+      {%- if request_options %} overload;{% endif %}
+      {%- if endpoint.deprecation_comment %} deprecated;{% endif %}
+    {% if request_options -%}
+    {{ macros::endpoint_signature(endpoint=endpoint, with_options=true) }} overload;
+      {%- if endpoint.deprecation_comment %} deprecated;{% endif %}
+    {% endif -%}
+    {% endfor %}
+    /// <summary>Names of the called operations in call order</summary>
+    property Calls: TList<String> read FCalls;
+  end;
+
+implementation
+
+{ T{{prefix}}Mock{{client_name}}Client }
+
+constructor T{{prefix}}Mock{{client_name}}Client.Create;
+begin
+  inherited;
+
+  FCalls := TList<String>.Create;
+end;
+
+destructor T{{prefix}}Mock{{client_name}}Client.Destroy;
+begin
+  FCalls.Free;
+
+  inherited;
+end;
+
+function T{{prefix}}Mock{{client_name}}Client.CallCount(const pOperation: String): Integer;
+begin
+  Result := 0;
+
+  for var vCall in FCalls do begin
+    if vCall = pOperation then Inc(Result);
+  end;
+end;
+{% for endpoint in endpoints %}
+{% set class_name = "T" ~ prefix ~ "Mock" ~ client_name ~ "Client" -%}
+{% if request_options -%}
+{{ macros::endpoint_signature(endpoint=endpoint, class_name=class_name) }}
+begin
+  {% if not endpoint.response_type.name == "none" %}Result := {% endif %}{{endpoint.name}}({{ macros::forward_args(endpoint=endpoint) }});
+end;
+
+{% endif -%}
+{% if endpoint.response_type.name == "none" -%}
+{{ macros::endpoint_signature(endpoint=endpoint, class_name=class_name, with_options=request_options) }}
+begin
+  FCalls.Add('{{endpoint.name}}');
+  {%- if endpoint.is_binary_response %}
+
+  if Length({{endpoint.name}}Response) > 0 then begin
+    pTarget.WriteBuffer({{endpoint.name}}Response[0], Length({{endpoint.name}}Response));
+  end;
+
+  if Assigned(pOnProgress) then begin
+    pOnProgress(Length({{endpoint.name}}Response), Length({{endpoint.name}}Response));
+  end;
+  {%- endif %}
+end;
+{% else -%}
+{{ macros::endpoint_signature(endpoint=endpoint, class_name=class_name, with_options=request_options) }}
+begin
+  FCalls.Add('{{endpoint.name}}');
+  {%- if endpoint.envelope %}{% if endpoint.envelope.meta %}
+  pMeta := Default({{ macros::type_name(base_type=endpoint.envelope.meta.type_.name, is_list_type=endpoint.envelope.meta.is_list_type, is_reference_type=endpoint.envelope.meta.type_.is_class, is_enum_type=endpoint.envelope.meta.type_.is_enum) }});
+  {%- endif %}{% endif %}
+
+  if Assigned({{endpoint.name}}Response) then begin
+    Result := {{endpoint.name}}Response();
+  end else begin
+    Result := Default({{ macros::type_name(base_type=endpoint.response_type.name, is_list_type=endpoint.response_is_list, is_reference_type=endpoint.response_type.is_class, is_enum_type=endpoint.response_type.is_enum) }});
+  end;
+end;
+{% endif -%}
+{% endfor %}
+end.
@@ -0,0 +1,121 @@
+{% import "macros.pas" as macros %}
+
+{%- set timestamp = now() | date(format="%d.%m.%Y %H:%m:%S") -%}
+{% if header -%}
+{{ header }}
+{% endif -%}
+// ========================================================================== //
+// Generated by Delphi Code Gen - Mode OpenAPI                                //
+// {{ macros::fixed_size_line(content="Version: " ~ crate_version, size=74) }} //
+// {{ macros::fixed_size_line(content="Timestamp: " ~ timestamp, size=74) }} //
+// ========================================================================== //
+
+unit uJsonHelper;
+
+interface
+
+uses System.Generics.Collections,
+     System.JSON,
+     System.SysUtils;
+
+type
+  /// <summary>Functions called by the generated models to read and write JSON values</summary>
+  TJsonHelper = class
+  public
+    /// <summary>
+    /// Returns the value of pKey as R, if it exists and is a T.
+    /// Otherwise pDefault is returned.
+    /// </summary>
+    class function TryGetValueOrDefault<T: TJSONValue; R>(pJson: TJSONValue; const pKey: String; pDefault: R): R; static;
+    /// <summary>Maps every item of the array, returns an empty list for a missing array</summary>
+    class function DeserializeList<T>(pJson: TJSONArray; pMapper: TFunc<TJSONValue, T>): TList<T>; static;
+    /// <summary>Maps every item of the array to an object owned by the list</summary>
+    class function DeserializeObjectList<T: class>(pJson: TJSONArray; pMapper: TFunc<TJSONValue, T>): TObjectList<T>; static;
+    /// <summary>Maps every item of the list to a JSON value of the returned array</summary>
+    class function SerializeList<T>(pList: TList<T>; pMapper: TFunc<T, TJSONValue>): TJSONArray; static;
+    /// <summary>Maps the value of every pair of the object, returns an empty dictionary for a missing object</summary>
+    class function DeserializeDictionary<T>(pJson: TJSONValue; pMapper: TFunc<TJSONValue, T>): TDictionary<String, T>; static;
+    /// <summary>Maps the value of every pair of the object to an object owned by the dictionary</summary>
+    class function DeserializeObjectDictionary<T: class>(pJson: TJSONValue; pMapper: TFunc<TJSONValue, T>): TObjectDictionary<String, T>; static;
+    /// <summary>Maps every value of the dictionary to a pair of the returned object</summary>
+    class function SerializeDictionary<T>(pDictionary: TDictionary<String, T>; pMapper: TFunc<T, TJSONValue>): TJSONObject; static;
+  end;
+
+implementation
+
+{ TJsonHelper }
+
+class function TJsonHelper.TryGetValueOrDefault<T, R>(pJson: TJSONValue; const pKey: String; pDefault: R): R;
+begin
+  var vValue: T;
+
+  if Assigned(pJson) and pJson.TryGetValue<T>(pKey, vValue) then begin
+    Result := vValue.AsType<R>;
+  end else begin
+    Result := pDefault;
+  end;
+end;
+
+class function TJsonHelper.DeserializeList<T>(pJson: TJSONArray; pMapper: TFunc<TJSONValue, T>): TList<T>;
+begin
+  Result := TList<T>.Create;
+
+  if not Assigned(pJson) then Exit;
+
+  for var vItem in pJson do begin
+    Result.Add(pMapper(vItem));
+  end;
+end;
+
+class function TJsonHelper.DeserializeObjectList<T>(pJson: TJSONArray; pMapper: TFunc<TJSONValue, T>): TObjectList<T>;
+begin
+  Result := TObjectList<T>.Create(True);
+
+  if not Assigned(pJson) then Exit;
+
+  for var vItem in pJson do begin
+    Result.Add(pMapper(vItem));
+  end;
+end;
+
+class function TJsonHelper.SerializeList<T>(pList: TList<T>; pMapper: TFunc<T, TJSONValue>): TJSONArray;
+begin
+  Result := TJSONArray.Create;
+
+  for var vItem in pList do begin
+    Result.AddElement(pMapper(vItem));
+  end;
+end;
+
+class function TJsonHelper.DeserializeDictionary<T>(pJson: TJSONValue; pMapper: TFunc<TJSONValue, T>): TDictionary<String, T>;
+begin
+  Result := TDictionary<String, T>.Create;
+
+  if not (pJson is TJSONObject) then Exit;
+
+  for var vPair in TJSONObject(pJson) do begin
+    Result.AddOrSetValue(vPair.JsonString.Value, pMapper(vPair.JsonValue));
+  end;
+end;
+
+class function TJsonHelper.DeserializeObjectDictionary<T>(pJson: TJSONValue; pMapper: TFunc<TJSONValue, T>): TObjectDictionary<String, T>;
+begin
+  Result := TObjectDictionary<String, T>.Create([doOwnsValues]);
+
+  if not (pJson is TJSONObject) then Exit;
+
+  for var vPair in TJSONObject(pJson) do begin
+    Result.AddOrSetValue(vPair.JsonString.Value, pMapper(vPair.JsonValue));
+  end;
+end;
+
+class function TJsonHelper.SerializeDictionary<T>(pDictionary: TDictionary<String, T>; pMapper: TFunc<T, TJSONValue>): TJSONObject;
+begin
+  Result := TJSONObject.Create;
+
+  for var vPair in pDictionary do begin
+    Result.AddPair(vPair.Key, pMapper(vPair.Value));
+  end;
+end;
+
+end.
@@ -9,14 +9,83 @@
   {%- endif -%}
 {% endmacro fixed_size_line -%}
 
-{% macro join_args(args) %}
+{% macro join_args(args, with_defaults=false) %}
   {%- for arg in args -%}
-  p{{arg.name}}: {{arg.type_name}} {%- if not loop.last -%}{{"; "}}{%- endif -%}
+  p{{arg.name}}: {{ self::type_name(base_type=arg.type_name, is_list_type=false, is_reference_type=false, is_enum_type=arg.is_enum) }}
+  {%- if with_defaults and arg.default_arg %} = {{arg.default_arg}}{% endif -%}
+  {%- if not loop.last -%}{{"; "}}{%- endif -%}
   {%- endfor -%}
 {% endmacro join_args -%}
 
-{% macro type_name(base_type, is_list_type, is_reference_type, is_enum_type) %}
-  {%- if is_list_type and is_reference_type -%}
+{% macro binary_target_arg(endpoint, with_default) %}
+  {%- if endpoint.is_binary_response -%}
+  {%- if endpoint.args | length > 0 or not endpoint.request_body.name == "none" -%}{{"; "}}{%- endif -%}
+  pTarget: TStream; pOnProgress: T{{prefix}}DownloadProgressEvent{% if with_default %} = nil{% endif %}
+  {%- endif -%}
+{% endmacro binary_target_arg -%}
+
+{% macro envelope_meta_arg(endpoint) %}
+  {%- if endpoint.envelope -%}
+  {%- if endpoint.envelope.meta -%}
+  {%- if endpoint.args | length > 0 or not endpoint.request_body.name == "none" -%}{{"; "}}{%- endif -%}
+  out pMeta: {{ self::type_name(base_type=endpoint.envelope.meta.type_.name, is_list_type=endpoint.envelope.meta.is_list_type, is_reference_type=endpoint.envelope.meta.type_.is_class, is_enum_type=endpoint.envelope.meta.type_.is_enum) }}
+  {%- endif -%}
+  {%- endif -%}
+{% endmacro envelope_meta_arg -%}
+
+{% macro endpoint_signature(endpoint, class_name="", with_options=false) %}
+  {%- set is_procedure = endpoint.response_type.name == "none" -%}
+  {%- set with_default = not class_name and not with_options -%}
+  {#- Defaults of the arguments are only allowed, if no argument without default follows -#}
+  {%- set with_arg_defaults = with_default and endpoint.request_body.name == "none" -%}
+  {%- if is_procedure and endpoint.is_binary_response %}{% set with_arg_defaults = false %}{% endif -%}
+  {%- if not is_procedure and endpoint.envelope -%}
+  {%- if endpoint.envelope.meta %}{% set with_arg_defaults = false %}{% endif -%}
+  {%- endif -%}
+  {%- if is_procedure %}procedure {% else %}function {% endif -%}
+  {%- if class_name %}{{class_name}}.{% endif -%}
+  {{endpoint.name}}({{self::join_args(args=endpoint.args, with_defaults=with_arg_defaults)}}
+  {%- if not endpoint.request_body.name == "none" -%}
+  {%- if endpoint.args | length > 0 -%}{{"; "}}{% endif -%}
+  pBody: {{ self::type_name(base_type=endpoint.request_body.name, is_list_type=false, is_reference_type=endpoint.request_body.is_class, is_enum_type=endpoint.request_body.is_enum) }}
+  {%- endif -%}
+  {%- if is_procedure -%}
+  {{- self::binary_target_arg(endpoint=endpoint, with_default=with_default) -}}
+  {%- else -%}
+  {{- self::envelope_meta_arg(endpoint=endpoint) -}}
+  {%- endif -%}
+  {%- if with_options -%}
+  {%- set has_args = endpoint.args | length > 0 or not endpoint.request_body.name == "none" -%}
+  {%- if is_procedure and endpoint.is_binary_response -%}
+  {%- set has_args = true -%}
+  {%- elif not is_procedure and endpoint.envelope -%}
+  {%- if endpoint.envelope.meta %}{% set has_args = true %}{% endif -%}
+  {%- endif -%}
+  {%- if has_args %}; {% endif -%}
+  const pOptions: T{{prefix}}RequestOptions
+  {%- endif -%}
+  ){% if not is_procedure %}: {{ self::type_name(base_type=endpoint.response_type.name, is_list_type=endpoint.response_is_list, is_reference_type=endpoint.response_type.is_class, is_enum_type=endpoint.response_type.is_enum) }}{% endif %};
+{%- endmacro endpoint_signature -%}
+
+{% macro forward_args(endpoint) %}
+  {%- for arg in endpoint.args -%}
+  p{{arg.name}}, {% endfor -%}
+  {%- if not endpoint.request_body.name == "none" %}pBody, {% endif -%}
+  {%- if endpoint.response_type.name == "none" and endpoint.is_binary_response %}pTarget, pOnProgress, {% endif -%}
+  {%- if not endpoint.response_type.name == "none" and endpoint.envelope -%}
+  {%- if endpoint.envelope.meta %}pMeta, {% endif -%}
+  {%- endif -%}
+  Default(T{{prefix}}RequestOptions)
+{%- endmacro forward_args -%}
+
+{% macro type_name(base_type, is_list_type, is_reference_type, is_enum_type, is_set_type=false, is_map_type=false) %}
+  {%- if is_map_type and is_reference_type -%}
+  TObjectDictionary<String, T{{prefix}}{{base_type}}>
+  {%- elif is_map_type -%}
+  TDictionary<String, {{ self::type_name(base_type=base_type, is_list_type=false, is_reference_type=false, is_enum_type=is_enum_type) }}>
+  {%- elif is_set_type -%}
+  T{{prefix}}{{base_type}}Set
+  {%- elif is_list_type and is_reference_type -%}
   TObjectList<T{{prefix}}{{base_type}}>
   {%- elif is_list_type and is_enum_type -%}
   TList<T{{prefix}}{{base_type}}>
@@ -26,13 +95,61 @@
   T{{prefix}}{{base_type}}
   {%- elif base_type == "datetime" -%}
   TDateTime
+  {%- elif base_type == "guid" -%}
+  TGUID
   {%- else -%}
   {{base_type}}
   {%- endif -%}
 {% endmacro type_name -%}
 
-{% macro from_json(json_obj_name, base_type, is_list_type, is_reference_type, is_enum_type, key) %}
-  {%- if is_list_type and is_reference_type -%}
+{% macro arg_value(arg) %}
+  {%- if arg.is_enum -%}
+  p{{arg.name}}.ToString
+  {%- elif arg.type_name == "integer" -%}
+  IntToStr(p{{arg.name}})
+  {%- elif arg.type_name == "double" -%}
+  FloatToStr(p{{arg.name}}, TFormatSettings.Invariant)
+  {%- elif arg.type_name == "boolean" -%}
+  BoolToStr(p{{arg.name}}, True).ToLower
+  {%- elif arg.type_name == "datetime" -%}
+  DateToISO8601(p{{arg.name}})
+  {%- elif arg.type_name == "guid" -%}
+  p{{arg.name}}.ToString(True)
+  {%- else -%}
+  p{{arg.name}}
+  {%- endif -%}
+{% endmacro arg_value -%}
+
+{#- Value of an optional query parameter, which is not sent, as the server assumes it anyway -#}
+{% macro omitted_value(arg) %}
+  {%- if arg.default_arg -%}
+  {{arg.default_arg}}
+  {%- else -%}
+  Default({{ self::type_name(base_type=arg.type_name, is_list_type=false, is_reference_type=false, is_enum_type=arg.is_enum) }})
+  {%- endif -%}
+{% endmacro omitted_value -%}
+
+{% macro from_json(json_obj_name, base_type, is_list_type, is_reference_type, is_enum_type, key, is_set_type=false, is_map_type=false) %}
+  {%- if is_map_type and is_reference_type -%}
+  TJsonHelper.DeserializeObjectDictionary<T{{prefix}}{{base_type}}>(
+    {{json_obj_name}}.FindValue({{key}}),
+    function (pJson: TJSONValue): T{{prefix}}{{base_type}}
+    begin
+      Result := T{{prefix}}{{base_type}}.FromJsonRaw(pJson);
+    end
+  )
+  {%- elif is_map_type -%}
+  {%- set value_type = self::type_name(base_type=base_type, is_list_type=false, is_reference_type=false, is_enum_type=is_enum_type) -%}
+  TJsonHelper.DeserializeDictionary<{{value_type}}>(
+    {{json_obj_name}}.FindValue({{key}}),
+    function (pJson: TJSONValue): {{value_type}}
+    begin
+      Result := {{ self::from_json_raw(json_obj_name="pJson", base_type=base_type, is_list_type=false, is_reference_type=false, is_enum_type=is_enum_type) | trim }};
+    end
+  )
+  {%- elif is_set_type -%}
+  T{{prefix}}{{base_type}}Set.FromJson({{json_obj_name}}.FindValue({{key}}))
+  {%- elif is_list_type and is_reference_type -%}
   TJsonHelper.DeserializeObjectList<T{{prefix}}{{base_type}}>(
     {{json_obj_name}}.GetValue<TJSONArray>({{key}}),
     function (pJson: TJSONValue): T{{prefix}}{{base_type}}
@@ -87,6 +204,43 @@
   {%- endif -%}
 {% endmacro from_json -%}
 
+{% macro to_json(value, base_type, is_list_type, is_reference_type, is_enum_type, is_set_type=false, is_map_type=false) %}
+  {%- if is_map_type -%}
+  {%- set value_type = self::type_name(base_type=base_type, is_list_type=false, is_reference_type=is_reference_type, is_enum_type=is_enum_type) -%}
+  TJsonHelper.SerializeDictionary<{{value_type}}>(
+        {{value}},
+        function (pValue: {{value_type}}): TJSONValue
+        begin
+          Result := {{ self::to_json(value="pValue", base_type=base_type, is_list_type=false, is_reference_type=is_reference_type, is_enum_type=is_enum_type) }};
+        end
+      )
+  {%- elif is_set_type -%}
+  {{value}}.ToJson
+  {%- elif is_list_type -%}
+  TJsonHelper.SerializeList<{% if is_reference_type or is_enum_type %}T{{prefix}}{{base_type}}{% else %}{{base_type}}{% endif %}>(
+        {{value}},
+        function (pValue: {% if is_reference_type or is_enum_type %}T{{prefix}}{{base_type}}{% else %}{{base_type}}{% endif %}): TJSONValue
+        begin
+          Result := {{ self::to_json(value="pValue", base_type=base_type, is_list_type=false, is_reference_type=is_reference_type, is_enum_type=is_enum_type) }};
+        end
+      )
+  {%- elif is_reference_type -%}
+  {{value}}.ToJsonRaw
+  {%- elif is_enum_type -%}
+  TJSONString.Create({{value}}.ToString)
+  {%- elif base_type == "integer" or base_type == "double" -%}
+  TJSONNumber.Create({{value}})
+  {%- elif base_type == "string" -%}
+  TJSONString.Create({{value}})
+  {%- elif base_type == "boolean" -%}
+  TJSONBool.Create({{value}})
+  {%- elif base_type == "datetime" -%}
+  TJSONString.Create(DateToISO8601({{value}}))
+  {%- else -%}
+  {{ throw(message= "unsupported type " ~ base_type) }}
+  {%- endif -%}
+{% endmacro to_json -%}
+
 {% macro from_json_raw(json_obj_name, base_type, is_list_type, is_reference_type, is_enum_type) %}
   {%- if is_reference_type -%}
   T{{prefix}}{{base_type}}.FromJsonRaw({{json_obj_name}})
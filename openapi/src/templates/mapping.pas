@@ -0,0 +1,93 @@
+{% import "macros.pas" as macros %}
+
+{%- set timestamp = now() | date(format="%d.%m.%Y %H:%m:%S") -%}
+// ========================================================================== //
+// Generated by Delphi Code Gen - Mode OpenAPI                                //
+// {{ macros::fixed_size_line(content="Version: " ~ crate_version, size=74) }} //
+// {{ macros::fixed_size_line(content="Timestamp: " ~ timestamp, size=74) }} //
+//                                                                            //
+// {{ macros::fixed_size_line(content="From: " ~ old_api, size=74) }} //
+// {{ macros::fixed_size_line(content="To: " ~ new_api, size=74) }} //
+// ========================================================================== //
+
+unit {{unit_name}};
+
+interface
+
+uses u{{old_prefix}}ApiModels,
+     u{{new_prefix}}ApiModels,
+     System.JSON;
+
+{%- if removed_models | length > 0 %}
+
+// Models without counterpart in u{{new_prefix}}ApiModels:
+{%- for model in removed_models %}
+//   T{{old_prefix}}{{model}}
+{%- endfor %}
+{%- endif %}
+{%- if added_models | length > 0 %}
+
+// Models without counterpart in u{{old_prefix}}ApiModels:
+{%- for model in added_models %}
+//   T{{new_prefix}}{{model}}
+{%- endfor %}
+{%- endif %}
+
+type
+{%- for mapper in mappers %}
+  /// <summary>Converts T{{old_prefix}}{{mapper.name}} into T{{new_prefix}}{{mapper.name}} via their JSON</summary>
+  T{{mapper.name}}{{old_prefix}}To{{new_prefix}} = class abstract
+  public
+    class function Map(pSource: T{{old_prefix}}{{mapper.name}}): T{{new_prefix}}{{mapper.name}}; static;
+    /// <summary>Changes the JSON of T{{old_prefix}}{{mapper.name}} into the JSON of T{{new_prefix}}{{mapper.name}}</summary>
+    class procedure MapJson(pJson: TJSONObject); static;
+  end;
+{% endfor %}
+implementation
+
+{% for mapper in mappers -%}
+{ T{{mapper.name}}{{old_prefix}}To{{new_prefix}} }
+
+class function T{{mapper.name}}{{old_prefix}}To{{new_prefix}}.Map(pSource: T{{old_prefix}}{{mapper.name}}): T{{new_prefix}}{{mapper.name}};
+begin
+  var vJson := pSource.ToJsonRaw;
+  try
+    MapJson(vJson);
+    Result := T{{new_prefix}}{{mapper.name}}.FromJsonRaw(vJson);
+  finally
+    vJson.Free;
+  end;
+end;
+
+class procedure T{{mapper.name}}{{old_prefix}}To{{new_prefix}}.MapJson(pJson: TJSONObject);
+begin
+  {%- if mapper.copied | length > 0 %}
+  // Copied: {{ mapper.copied | join(sep=", ") }}
+  {%- endif %}
+  {%- for nested in mapper.nested %}
+  {%- set key = nested.key | pascal_literal %}
+  {%- if nested.is_list_type %}
+  if pJson.Values[{{key}}] is TJSONArray then begin
+    for var vItem in TJSONArray(pJson.Values[{{key}}]) do begin
+      if vItem is TJSONObject then begin
+        T{{nested.model}}{{old_prefix}}To{{new_prefix}}.MapJson(TJSONObject(vItem));
+      end;
+    end;
+  end;
+  {%- else %}
+  if pJson.Values[{{key}}] is TJSONObject then begin
+    T{{nested.model}}{{old_prefix}}To{{new_prefix}}.MapJson(TJSONObject(pJson.Values[{{key}}]));
+  end;
+  {%- endif %}
+  {%- endfor %}
+  {%- for changed in mapper.changed %}
+  // TODO: "{{changed.key}}" changed from {{changed.old_type}} to {{changed.new_type}} and is left out
+  pJson.RemovePair({{changed.key | pascal_literal}}).Free;
+  {%- endfor %}
+  {%- for todo in mapper.todos %}
+  // TODO: {{todo}}
+  {%- endfor %}
+end;
+
+{% endfor -%}
+end.
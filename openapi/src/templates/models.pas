@@ -1,6 +1,9 @@
 {% import "macros.pas" as macros %}
 
 {%- set timestamp = now() | date(format="%d.%m.%Y %H:%m:%S") -%}
+{% if header -%}
+{{ header }}
+{% endif -%}
 // ========================================================================== //
 // Generated by Delphi Code Gen - Mode OpenAPI                                //
 // {{ macros::fixed_size_line(content="Version: " ~ crate_version, size=74) }} //
@@ -10,11 +13,11 @@
 // {{ macros::fixed_size_line(content="API Spec Version: " ~ api_spec_version, size=74) }} //
 // ========================================================================== //
 
-unit u{{unitPrefix}}ApiModels;
+unit u{{unitPrefix}}{{unit_name}};
 
 interface
 
-uses System.Generics.Collections, System.JSON;
+uses {% for unit in model_uses %}u{{unitPrefix}}{{unit}}, {% endfor %}{% if attribute_unit %}{{attribute_unit}}, {% endif %}System.Generics.Collections, System.JSON, System.SysUtils;
 
 type
   {$REGION 'Forward Declerations'}
@@ -26,32 +29,88 @@ type
   {$REGION 'Enums and Helper'}
   {% for enumType in enumTypes -%}
   T{{prefix}}{{enumType.name}} = ({{enumType.variants | map(attribute="name") | join(sep=", ")}});
+  {% if enumType.is_flags -%}
+  T{{prefix}}{{enumType.name}}Set = set of T{{prefix}}{{enumType.name}};
+  {% endif -%}
   {% endfor -%}
   {{""}}
   {% for enumType in enumTypes -%}
   T{{prefix}}{{enumType.name}}Helper = record helper for T{{prefix}}{{enumType.name}}
     class function FromString(const pValue: String): T{{prefix}}{{enumType.name}}; static;
+    function ToString: String;
+  end;
+
+  {% if enumType.is_flags -%}
+  T{{prefix}}{{enumType.name}}SetHelper = record helper for T{{prefix}}{{enumType.name}}Set
+    /// <summary>Reads the flags of a JSON array, any other value is an empty set</summary>
+    class function FromJson(pJson: TJSONValue): T{{prefix}}{{enumType.name}}Set; static;
+    function ToJson: TJSONArray;
+    {%- if enumType.variants | length <= 64 %}
+    /// <summary>The flag with the ordinal value n is bit n of the mask</summary>
+    class function FromInteger(const pValue: UInt64): T{{prefix}}{{enumType.name}}Set; static;
+    function ToInteger: UInt64;
+    {%- endif %}
   end;
 
+  {% endif -%}
   {% endfor -%}
   {$ENDREGION}
+  {%- if declares_exception %}
+
+  {$REGION 'Exceptions'}
+  /// <summary>Raised by Validate, if a model violates constraints of the spec</summary>
+  T{{prefix}}ValidationException = class(Exception);
+  {$ENDREGION}
+  {%- endif %}
 
   {$REGION 'Models'}
   {% for classType in classTypes -%}
   T{{prefix}}{{classType.name}} = class
   strict private
     {%- for property in classType.properties %}
-    F{{property.name}}: {{ macros::type_name(base_type=property.type_.name, is_list_type=property.is_list_type, is_reference_type=property.type_.is_class, is_enum_type=property.type_.is_enum) }};
+    {%- if json_name_attribute %}
+    [{{json_name_attribute}}({{property.key | pascal_literal}})]
+    {%- endif %}
+    F{{property.name}}: {{ macros::type_name(base_type=property.type_.name, is_list_type=property.is_list_type, is_reference_type=property.type_.is_class, is_enum_type=property.type_.is_enum, is_set_type=property.type_.is_set, is_map_type=property.is_map_type) }};
+    {%- if property.is_nullable %}
+    FHas{{property.name}}: Boolean;
+    F{{property.name}}IsNull: Boolean;
+    {%- endif %}
+    {%- endfor %}
+    {%- for property in classType.properties %}
+    {%- if property.is_recursive and property.is_list_type %}
+    function Get{{property.name}}: {{ macros::type_name(base_type=property.type_.name, is_list_type=true, is_reference_type=true, is_enum_type=false) }};
+    {%- endif %}
     {%- endfor -%}{{" "}}
   public
     constructor FromJson(const pJson: String);
     constructor FromJsonRaw(pJson: TJSONValue);
+    function ToJson: String;
+    /// <summary>Nullable properties are written as null, if they were null, and left out, if they were missing</summary>
+    function ToJsonRaw: TJSONObject;
+    /// <summary>Raises T{{prefix}}ValidationException listing all violated constraints</summary>
+    procedure Validate;
     {% if classType.needs_destructor -%}
     destructor Destroy; override;
     {%- endif -%}
     {{""}}
     {% for property in classType.properties %}
-    property {{property.name}}: {{ macros::type_name(base_type=property.type_.name, is_list_type=property.is_list_type, is_reference_type=property.type_.is_class, is_enum_type=property.type_.is_enum) }} read F{{property.name}};
+    {%- for line in property.deprecation_comment %}
+    // {{line}}
+    {%- endfor %}
+    {%- if property.is_recursive and property.is_list_type %}
+    /// <summary>Created on first access, if missing in the JSON</summary>
+    property {{property.name}}: {{ macros::type_name(base_type=property.type_.name, is_list_type=true, is_reference_type=true, is_enum_type=false) }} read Get{{property.name}};
+    {%- else %}
+    property {{property.name}}: {{ macros::type_name(base_type=property.type_.name, is_list_type=property.is_list_type, is_reference_type=property.type_.is_class, is_enum_type=property.type_.is_enum, is_set_type=property.type_.is_set, is_map_type=property.is_map_type) }} read F{{property.name}};
+    {%- endif %}
+    {%- if property.deprecation_comment %} deprecated;{% endif %}
+    {%- if property.is_nullable %}
+    /// <summary>{{property.key}} exists in the JSON, its value may be null</summary>
+    property Has{{property.name}}: Boolean read FHas{{property.name}};
+    /// <summary>{{property.key}} exists in the JSON and is null, {{property.name}} keeps its default</summary>
+    property {{property.name}}IsNull: Boolean read F{{property.name}}IsNull;
+    {%- endif %}
     {%- endfor %}
   end;
 
@@ -62,20 +121,57 @@ implementation
 
 uses uJsonHelper,
      System.DateUtils,
-     System.SysUtils;
+     System.RegularExpressions;
 
 {$REGION 'Enumhelper'}
 {% for enumType in enumTypes -%}
 { T{{prefix}}{{enumType.name}} }
 
 class function T{{prefix}}{{enumType.name}}Helper.FromString(const pValue: String): T{{prefix}}{{enumType.name}};
+{% if binary_search_enums -%}
+// Binary search over the values sorted by CompareStr. Needs about log2(n) comparisons
+// instead of up to n comparisons of an if chain, which pays off from about 20 values on.
+const
+  cKeys: array[0..{{enumType.variants | length - 1}}] of String = (
+    {%- for variant in enumType.variants | ordinal_sort(attribute="key") %}
+    {{variant.key | pascal_literal}}{%- if not loop.last -%}{{","}}{%- endif -%}
+    {%- endfor %}
+  );
+  cValues: array[0..{{enumType.variants | length - 1}}] of T{{prefix}}{{enumType.name}} = (
+    {%- for variant in enumType.variants | ordinal_sort(attribute="key") %}
+    {{variant.name}}{%- if not loop.last -%}{{","}}{%- endif -%}
+    {%- endfor %}
+  );
+var
+  vLow, vHigh, vMiddle, vComparison: Integer;
+begin
+  vLow := 0;
+  vHigh := High(cKeys);
+
+  while vLow <= vHigh do begin
+    vMiddle := (vLow + vHigh) div 2;
+    vComparison := CompareStr(cKeys[vMiddle], pValue);
+
+    if vComparison = 0 then Exit(cValues[vMiddle]);
+
+    if vComparison < 0 then begin
+      vLow := vMiddle + 1;
+    end else begin
+      vHigh := vMiddle - 1;
+    end;
+  end;
+
+  raise Exception.Create('\"' + pValue + '\" is a unknown value for T{{prefix}}{{enumType.name}}');
+end;
+{%- else -%}
+begin
   {% for variant in enumType.variants -%}
   {% if loop.first -%}
-  if pValue = '{{variant.key}}' then begin 
+  if pValue = {{variant.key | pascal_literal}} then begin 
     Result := {{variant.name}};
   end
   {%- else -%}
-  {{" "}}else if pValue = '{{variant.key}}' then begin 
+  {{" "}}else if pValue = {{variant.key | pascal_literal}} then begin 
     Result := {{variant.name}};
   end
   {%- endif -%}
@@ -84,7 +180,63 @@ class function T{{prefix}}{{enumType.name}}Helper.FromString(const pValue: Strin
     raise Exception.Create('\"' + pValue + '\" is a unknown value for T{{prefix}}{{enumType.name}}');
   end;
 end;
+{%- endif %}
 
+function T{{prefix}}{{enumType.name}}Helper.ToString: String;
+begin
+  case Self of
+    {%- for variant in enumType.variants %}
+    {{variant.name}}: Result := {{variant.key | pascal_literal}};
+    {%- endfor %}
+  end;
+end;
+
+{% if enumType.is_flags -%}
+{ T{{prefix}}{{enumType.name}}Set }
+
+class function T{{prefix}}{{enumType.name}}SetHelper.FromJson(pJson: TJSONValue): T{{prefix}}{{enumType.name}}Set;
+begin
+  Result := [];
+
+  if not (pJson is TJSONArray) then Exit;
+
+  for var vItem in TJSONArray(pJson) do begin
+    Include(Result, T{{prefix}}{{enumType.name}}.FromString(vItem.Value));
+  end;
+end;
+
+function T{{prefix}}{{enumType.name}}SetHelper.ToJson: TJSONArray;
+begin
+  Result := TJSONArray.Create;
+
+  for var vValue in Self do begin
+    Result.Add(vValue.ToString);
+  end;
+end;
+{%- if enumType.variants | length <= 64 %}
+
+class function T{{prefix}}{{enumType.name}}SetHelper.FromInteger(const pValue: UInt64): T{{prefix}}{{enumType.name}}Set;
+begin
+  Result := [];
+
+  for var vValue := Low(T{{prefix}}{{enumType.name}}) to High(T{{prefix}}{{enumType.name}}) do begin
+    if pValue and (UInt64(1) shl Ord(vValue)) <> 0 then begin
+      Include(Result, vValue);
+    end;
+  end;
+end;
+
+function T{{prefix}}{{enumType.name}}SetHelper.ToInteger: UInt64;
+begin
+  Result := 0;
+
+  for var vValue in Self do begin
+    Result := Result or (UInt64(1) shl Ord(vValue));
+  end;
+end;
+{%- endif %}
+
+{% endif -%}
 {% endfor -%}
 {$ENDREGION}
 
@@ -93,7 +245,7 @@ end;
 { T{{prefix}}{{classType.name}} }
 const
   {% for property in classType.properties -%}
-  cn{{classType.name}}{{property.name}}Key: string = '{{property.key}}';
+  cn{{classType.name}}{{property.name}}Key: string = {{property.key | pascal_literal}};
   {% endfor -%}
 {{""}}
 constructor T{{prefix}}{{classType.name}}.FromJson(const pJson: String);
@@ -110,15 +262,131 @@ end;
 constructor T{{prefix}}{{classType.name}}.FromJsonRaw(pJson: TJSONValue);
 begin
   {%- for property in classType.properties %}
-  F{{property.name}} := {{ macros::from_json(json_obj_name="vRoot", base_type=property.type_.name, is_list_type=property.is_list_type, is_reference_type=property.type_.is_class, is_enum_type=property.type_.is_enum, key="cn" ~ classType.name ~ property.key ~ "Key") }};
+  {%- set key = "cn" ~ classType.name ~ property.name ~ "Key" %}
+  {%- if property.is_nullable %}
+  FHas{{property.name}} := Assigned(pJson.FindValue({{key}}));
+  F{{property.name}}IsNull := pJson.FindValue({{key}}) is TJSONNull;
+  {%- endif %}
+  {%- if property.is_recursive %}
+  if pJson.FindValue({{key}}) is {% if property.is_list_type %}TJSONArray{% else %}TJSONObject{% endif %} then begin
+    F{{property.name}} := {{ macros::from_json(json_obj_name="pJson", base_type=property.type_.name, is_list_type=property.is_list_type, is_reference_type=true, is_enum_type=false, key=key, is_map_type=property.is_map_type) }};
+  end;
+  {%- elif property.is_nullable %}
+  if not F{{property.name}}IsNull then begin
+    F{{property.name}} := {{ macros::from_json(json_obj_name="pJson", base_type=property.type_.name, is_list_type=property.is_list_type, is_reference_type=property.type_.is_class, is_enum_type=property.type_.is_enum, is_set_type=property.type_.is_set, key=key, is_map_type=property.is_map_type) }};
+  end;
+  {%- else %}
+  F{{property.name}} := {{ macros::from_json(json_obj_name="pJson", base_type=property.type_.name, is_list_type=property.is_list_type, is_reference_type=property.type_.is_class, is_enum_type=property.type_.is_enum, is_set_type=property.type_.is_set, key=key, is_map_type=property.is_map_type) }};
+  {%- endif %}
   {%- endfor%}
 end;
+{%- for property in classType.properties %}
+{%- if property.is_recursive and property.is_list_type %}
+{%- set list_type = macros::type_name(base_type=property.type_.name, is_list_type=true, is_reference_type=true, is_enum_type=false) %}
+
+function T{{prefix}}{{classType.name}}.Get{{property.name}}: {{ list_type }};
+begin
+  if not Assigned(F{{property.name}}) then begin
+    F{{property.name}} := {{ list_type }}.Create(True);
+  end;
+
+  Result := F{{property.name}};
+end;
+{%- endif %}
+{%- endfor %}
+
+function T{{prefix}}{{classType.name}}.ToJson: String;
+begin
+  var vRoot := ToJsonRaw;
+
+  try
+    Result := vRoot.ToJSON;
+  finally
+    vRoot.Free;
+  end;
+end;
+
+function T{{prefix}}{{classType.name}}.ToJsonRaw: TJSONObject;
+begin
+  Result := TJSONObject.Create;
+
+  try
+    {%- for property in classType.properties %}
+    {%- set key = "cn" ~ classType.name ~ property.name ~ "Key" %}
+    {%- set value = macros::to_json(value="F" ~ property.name, base_type=property.type_.name, is_list_type=property.is_list_type, is_reference_type=property.type_.is_class, is_enum_type=property.type_.is_enum, is_set_type=property.type_.is_set, is_map_type=property.is_map_type) %}
+    {%- if property.is_nullable %}
+    if F{{property.name}}IsNull then begin
+      Result.AddPair({{key}}, TJSONNull.Create);
+    end else if FHas{{property.name}}{% if property.type_.is_class or property.is_list_type or property.is_map_type %} and Assigned(F{{property.name}}){% endif %} then begin
+      Result.AddPair({{key}}, {{ value }});
+    end;
+    {%- elif property.type_.is_class or property.is_list_type or property.is_map_type %}
+    if Assigned(F{{property.name}}) then begin
+      Result.AddPair({{key}}, {{ value }});
+    end;
+    {%- else %}
+    Result.AddPair({{key}}, {{ value }});
+    {%- endif %}
+    {%- endfor %}
+  except
+    Result.Free;
+    raise;
+  end;
+end;
+
+procedure T{{prefix}}{{classType.name}}.Validate;
+begin
+  {%- if classType.has_constraints %}
+  var vViolations := TList<String>.Create;
+
+  try
+    {%- for property in classType.properties %}
+    {%- set c = property.constraints %}
+    {%- set field = "F" ~ property.name %}
+    {%- if c.is_required %}
+    {%- if property.is_nullable %}
+    if not FHas{{property.name}} then vViolations.Add({{property.key ~ " is required" | pascal_literal}});
+    {%- elif property.type_.is_class or property.is_list_type or property.is_map_type %}
+    if not Assigned({{field}}) then vViolations.Add({{property.key ~ " is required" | pascal_literal}});
+    {%- elif property.type_.name == "string" and not property.type_.is_enum %}
+    if {{field}} = '' then vViolations.Add({{property.key ~ " is required" | pascal_literal}});
+    {%- endif %}
+    {%- endif %}
+    {%- if not property.is_list_type and property.type_.name == "string" and not property.type_.is_enum %}
+    {#- Empty strings count as missing, which is already covered by the required check #}
+    {%- if c.min_length is number and c.min_length > 1 %}
+    if ({{field}} <> '') and (Length({{field}}) < {{c.min_length}}) then vViolations.Add({{property.key ~ " must have at least " ~ c.min_length ~ " characters" | pascal_literal}});
+    {%- endif %}
+    {%- if c.max_length is number %}
+    if Length({{field}}) > {{c.max_length}} then vViolations.Add({{property.key ~ " must have at most " ~ c.max_length ~ " characters" | pascal_literal}});
+    {%- endif %}
+    {%- if c.pattern %}
+    if ({{field}} <> '') and not TRegEx.IsMatch({{field}}, {{c.pattern | pascal_literal}}) then vViolations.Add({{property.key ~ " must match " ~ c.pattern | pascal_literal}});
+    {%- endif %}
+    {%- elif not property.is_list_type and property.type_.name in ["integer", "double"] %}
+    {%- if c.minimum is number %}
+    if {{field}} < {{c.minimum}} then vViolations.Add({{property.key ~ " must be at least " ~ c.minimum | pascal_literal}});
+    {%- endif %}
+    {%- if c.maximum is number %}
+    if {{field}} > {{c.maximum}} then vViolations.Add({{property.key ~ " must be at most " ~ c.maximum | pascal_literal}});
+    {%- endif %}
+    {%- endif %}
+    {%- endfor %}
+
+    if vViolations.Count > 0 then begin
+      raise T{{prefix}}ValidationException.Create('{{classType.name}} is invalid: ' + String.Join(', ', vViolations.ToArray));
+    end;
+  finally
+    vViolations.Free;
+  end;
+  {%- endif %}
+end;
 
 {% if classType.needs_destructor -%}
 destructor T{{prefix}}{{classType.name}}.Destroy;
 begin
   {% for property in classType.properties -%}
-  {% if property.is_reference_type or property.is_list_type -%}
+  {% if property.is_owned -%}
   FreeAndNil(F{{property.name}});
   {% endif -%}
   {%- endfor %}
@@ -0,0 +1,46 @@
+{% import "macros.pas" as macros %}
+
+{%- set timestamp = now() | date(format="%d.%m.%Y %H:%m:%S") -%}
+{% if header -%}
+{{ header }}
+{% endif -%}
+// ========================================================================== //
+// Generated by Delphi Code Gen - Mode OpenAPI                                //
+// {{ macros::fixed_size_line(content="Version: " ~ crate_version, size=74) }} //
+// {{ macros::fixed_size_line(content="Timestamp: " ~ timestamp, size=74) }} //
+//                                                                            //
+// {{ macros::fixed_size_line(content="Title: " ~ api_title, size=74) }} //
+// {{ macros::fixed_size_line(content="API Spec Version: " ~ api_spec_version, size=74) }} //
+// ========================================================================== //
+
+unit u{{unitPrefix}}ApiRoutes;
+
+interface
+
+{%- if routes | length > 0 %}
+
+const
+{%- for route in routes %}
+{%- if not loop.first %}
+{% endif %}
+  // {{route.method}} {{route.path}}
+  cn{{route.name}}Method = '{{route.method}}';
+  cn{{route.name}}Path = {{route.path | pascal_literal}};
+  {%- if route.operation_id %}
+  cn{{route.name}}OperationId = {{route.operation_id | pascal_literal}};
+  {%- endif %}
+{%- endfor %}
+{%- for tag in tags %}
+
+  // Operation ids of the operations tagged {{tag.tag}}
+  cn{{tag.name}}OperationIds: array[0..{{tag.routes | length - 1}}] of string = (
+    {%- for route in tag.routes %}
+    cn{{route}}OperationId{% if not loop.last %},{% endif %}
+    {%- endfor %}
+  );
+{%- endfor %}
+{%- endif %}
+
+implementation
+
+end.
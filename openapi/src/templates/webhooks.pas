@@ -0,0 +1,40 @@
+{% import "macros.pas" as macros %}
+
+{%- set timestamp = now() | date(format="%d.%m.%Y %H:%m:%S") -%}
+{% if header -%}
+{{ header }}
+{% endif -%}
+// ========================================================================== //
+// Generated by Delphi Code Gen - Mode OpenAPI                                //
+// {{ macros::fixed_size_line(content="Version: " ~ crate_version, size=74) }} //
+// {{ macros::fixed_size_line(content="Timestamp: " ~ timestamp, size=74) }} //
+//                                                                            //
+// {{ macros::fixed_size_line(content="Title: " ~ api_title, size=74) }} //
+// {{ macros::fixed_size_line(content="API Spec Version: " ~ api_spec_version, size=74) }} //
+// ========================================================================== //
+
+unit u{{unitPrefix}}ApiWebhooks;
+
+interface
+
+uses {% for unit in model_units -%}
+     u{{unitPrefix}}{{unit}},
+     {% endfor -%}
+     System.Classes;
+
+type
+{%- for group in handlerGroups %}
+  T{{prefix}}{{group.name}}Handler = class abstract
+  public
+    {%- for operation in group.operations %}
+    // {{operation.method}} {{operation.path}}
+    procedure {{operation.name}}(
+      {%- if not operation.request_body.name == "none" -%}
+      pPayload: {{ macros::type_name(base_type=operation.request_body.name, is_list_type=false, is_reference_type=operation.request_body.is_class, is_enum_type=operation.request_body.is_enum) }}
+      {%- endif -%}); virtual; abstract;
+    {%- endfor %}
+  end;
+{% endfor %}
+implementation
+
+end.
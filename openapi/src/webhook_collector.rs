@@ -0,0 +1,106 @@
+use sw4rm_rs::{
+    shared::{Callback, Operation, PathItem},
+    Spec,
+};
+use tera::Value;
+
+use crate::{
+    endpoint_collector::{collect_path_item_endpoints, get_endpoint_name},
+    helper::{resolve, sorted_entries},
+    models::{ClassType, Endpoint, EnumType, HandlerGroup},
+};
+
+/// Collects the webhooks of the spec and the callbacks of all operations.
+///
+/// Webhooks are grouped into a single handler, callbacks get one handler per operation.
+/// Fails with a description of the affected part of the spec, if it cannot be processed.
+pub(crate) fn collect_handler_groups(
+    spec: &Spec,
+    class_types: &mut Vec<ClassType>,
+    enum_types: &mut Vec<EnumType>,
+) -> Result<Vec<HandlerGroup>, String> {
+    let mut groups = vec![];
+
+    let mut webhooks = vec![];
+    for (k, v) in sorted_entries(&spec.webhooks) {
+        let v = resolve(v, spec, &format!("Webhook \"{k}\""))?;
+
+        webhooks.append(&mut collect_path_item_endpoints(
+            v,
+            k,
+            spec,
+            class_types,
+            enum_types,
+        )?);
+    }
+
+    if !webhooks.is_empty() {
+        groups.push(HandlerGroup {
+            name: String::from("Webhooks"),
+            operations: webhooks,
+        });
+    }
+
+    for (k, v) in sorted_entries(&spec.paths) {
+        let v = resolve(v, spec, &format!("Path \"{k}\""))?;
+
+        for (o, method) in [
+            (v.get, "Get"),
+            (v.post, "Post"),
+            (v.put, "Put"),
+            (v.delete, "Delete"),
+        ] {
+            let Some(o) = o else {
+                continue;
+            };
+
+            let operations = collect_callback_endpoints(&o, spec, class_types, enum_types)?;
+
+            if !operations.is_empty() {
+                groups.push(HandlerGroup {
+                    name: get_endpoint_name(&o, k, method) + "Callbacks",
+                    operations,
+                });
+            }
+        }
+    }
+
+    Ok(groups)
+}
+
+fn collect_callback_endpoints(
+    operation: &Operation,
+    spec: &Spec,
+    class_types: &mut Vec<ClassType>,
+    enum_types: &mut Vec<EnumType>,
+) -> Result<Vec<Endpoint>, String> {
+    let mut endpoints = vec![];
+
+    for (name, callback) in sorted_entries(&operation.callbacks) {
+        for path_item in callback_path_items(callback) {
+            endpoints.append(&mut collect_path_item_endpoints(
+                path_item,
+                name,
+                spec,
+                class_types,
+                enum_types,
+            )?);
+        }
+    }
+
+    Ok(endpoints)
+}
+
+/// sw4rm-rs does not expose the path items of a callback, which are keyed by
+/// runtime expressions. They end up in the flattened extension fields, so we
+/// take the detour through a serialized value to read them.
+fn callback_path_items(callback: &Callback) -> Vec<PathItem> {
+    let Ok(Value::Object(expressions)) = tera::to_value(callback) else {
+        return vec![];
+    };
+
+    expressions
+        .into_iter()
+        .filter_map(|(_, v)| tera::from_value::<PathItem>(v).ok())
+        .collect()
+}
@@ -0,0 +1,95 @@
+//! Delphi source code, which the XML schema and the OpenAPI generator write the same way.
+
+use std::collections::HashMap;
+
+/// Returns a Delphi string expression for `value` on a single line, which compiles
+/// independent of the encoding of the unit. Quotes are doubled, control characters and
+/// non-ASCII characters are written as character codes of their UTF-16 code units, e.g.
+/// `'it''s'#9'Gr'#$00F6'n'`. Literals are split after 255 characters, the limit of Delphi.
+pub fn as_pascal_literal(value: &str) -> String {
+    if value.is_empty() {
+        return String::from("''");
+    }
+
+    let chars = value.chars().collect::<Vec<_>>();
+
+    chars
+        .chunks(255)
+        .map(|chunk| {
+            let mut literal = String::new();
+            let mut is_quoted = false;
+
+            for c in chunk {
+                let is_printable = c.is_ascii() && !c.is_ascii_control();
+
+                if is_printable != is_quoted {
+                    literal.push('\'');
+                    is_quoted = is_printable;
+                }
+
+                match c {
+                    '\'' => literal.push_str("''"),
+                    c if is_printable => literal.push(*c),
+                    c if c.is_ascii() => literal.push_str(&format!("#{}", *c as u32)),
+                    c => {
+                        for unit in c.encode_utf16(&mut [0; 2]) {
+                            literal.push_str(&format!("#${unit:04X}"));
+                        }
+                    }
+                }
+            }
+
+            if is_quoted {
+                literal.push('\'');
+            }
+
+            literal
+        })
+        .collect::<Vec<_>>()
+        .join(" + ")
+}
+
+/// Filter writing a string as Delphi literal, see [as_pascal_literal]. Templates register it
+/// as `pascal_literal`, e.g. `{{ property.key | pascal_literal }}`.
+pub fn pascal_literal(
+    value: &tera::Value,
+    _: &HashMap<String, tera::Value>,
+) -> tera::Result<tera::Value> {
+    match value.as_str() {
+        Some(value) => Ok(tera::Value::from(as_pascal_literal(value))),
+        None => Err(tera::Error::msg("pascal_literal requires a string")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pascal_literal_escapes_quotes_control_and_non_ascii_characters() {
+        assert_eq!(as_pascal_literal(""), "''");
+        assert_eq!(as_pascal_literal("it's"), "'it''s'");
+        assert_eq!(as_pascal_literal("a\tb\r\n"), "'a'#9'b'#13#10");
+        assert_eq!(as_pascal_literal("Größe"), "'Gr'#$00F6#$00DF'e'");
+        // Characters outside of the BMP are written as surrogate pair
+        assert_eq!(as_pascal_literal("\u{1F600}!"), "#$D83D#$DE00'!'");
+    }
+
+    #[test]
+    fn pascal_literal_is_split_after_255_characters() {
+        let res = as_pascal_literal(&format!("{}é", "a".repeat(255)));
+
+        assert_eq!(res, format!("'{}' + #$00E9", "a".repeat(255)));
+    }
+
+    #[test]
+    fn filter_rejects_values_other_than_strings() {
+        let args = HashMap::new();
+
+        assert_eq!(
+            pascal_literal(&tera::Value::from("it's"), &args).unwrap(),
+            tera::Value::from("'it''s'")
+        );
+        assert!(pascal_literal(&tera::Value::from(1), &args).is_err());
+    }
+}
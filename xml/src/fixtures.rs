@@ -0,0 +1,167 @@
+//! Regression tests, which build the internal representation of every schema in
+//! `tests/fixtures` and compare it with the snapshot next to it.
+//!
+//! A new case is a schema `tests/fixtures/<name>.xsd`. Its snapshot
+//! `tests/fixtures/snapshots/representation@<name>.xsd.snap` is written by
+//! `INSTA_UPDATE=always cargo test -p xsd-delphi-codegen-xml fixtures` or `cargo insta review`.
+
+use std::path::Path;
+
+use crate::{
+    build_representation,
+    generator::{
+        code_generator_trait::CodeGenOptions, internal_representation::InternalRepresentation,
+    },
+    type_registry::TypeRegistry,
+};
+
+/// Builds the internal representation of the schema with the default options. The types are
+/// sorted by name, as the order of independent types varies between runs.
+fn build(path: &Path) -> InternalRepresentation {
+    let (_, mut representation) = build_representation(
+        &[path.to_path_buf()],
+        &CodeGenOptions::default(),
+        &mut TypeRegistry::new(),
+    )
+    .unwrap_or_else(|e| panic!("{} can not be built: {e}", path.display()));
+
+    representation.classes.sort_by(|a, b| a.name.cmp(&b.name));
+    representation
+        .types_aliases
+        .sort_by(|a, b| a.name.cmp(&b.name));
+    representation
+        .enumerations
+        .sort_by(|a, b| a.name.cmp(&b.name));
+    representation
+        .union_types
+        .sort_by(|a, b| a.name.cmp(&b.name));
+    representation
+        .external_classes
+        .sort_by(|a, b| a.name.cmp(&b.name));
+
+    representation
+}
+
+#[test]
+fn representation() {
+    insta::with_settings!({
+        snapshot_path => "../tests/fixtures/snapshots",
+        prepend_module_to_snapshot => false,
+    }, {
+        insta::glob!("../tests/fixtures", "*.xsd", |path| {
+            insta::assert_yaml_snapshot!(build(path));
+        });
+    });
+}
+
+/// Schema with 250 classes in one inheritance chain, a chain of 150 aliases and 100 unions
+/// nested in each other, which are referenced by every class.
+fn large_schema() -> String {
+    let mut schema = String::from(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+  <xs:simpleType name="Alias0">
+    <xs:restriction base="xs:string"/>
+  </xs:simpleType>
+  <xs:simpleType name="Union0">
+    <xs:union memberTypes="xs:int">
+      <xs:simpleType>
+        <xs:list itemType="xs:int"/>
+      </xs:simpleType>
+    </xs:union>
+  </xs:simpleType>
+"#,
+    );
+
+    for i in 1..150 {
+        schema += &format!(
+            r#"  <xs:simpleType name="Alias{i}">
+    <xs:restriction base="Alias{}"/>
+  </xs:simpleType>
+"#,
+            i - 1
+        );
+    }
+
+    for i in 1..100 {
+        schema += &format!(
+            r#"  <xs:simpleType name="Union{i}">
+    <xs:union memberTypes="Alias{i} Union{}"/>
+  </xs:simpleType>
+"#,
+            i - 1
+        );
+    }
+
+    for i in 0..250 {
+        let content = format!(
+            r#"<xs:sequence>
+          <xs:element name="text{i}" type="Alias{}"/>
+          <xs:element name="value{i}" type="Union{}"/>
+        </xs:sequence>"#,
+            i % 150,
+            i % 100
+        );
+
+        schema += &match i {
+            0 => format!(
+                r#"  <xs:complexType name="Class0">
+        {content}
+  </xs:complexType>
+"#
+            ),
+            _ => format!(
+                r#"  <xs:complexType name="Class{i}">
+    <xs:complexContent>
+      <xs:extension base="Class{}">
+        {content}
+      </xs:extension>
+    </xs:complexContent>
+  </xs:complexType>
+"#,
+                i - 1
+            ),
+        };
+    }
+
+    schema + "</xs:schema>\n"
+}
+
+/// Building the representation of a large schema must neither build nested unions per
+/// reference nor clone the types per path through the dependency graph, which needed
+/// close to a gigabyte of memory for this schema.
+#[test]
+fn large_schema_is_built_with_every_type_once() {
+    let path = std::env::temp_dir().join(format!("genphi-large-{}.xsd", std::process::id()));
+    std::fs::write(&path, large_schema()).unwrap();
+
+    let (_, representation) = build_representation(
+        std::slice::from_ref(&path),
+        &CodeGenOptions::default(),
+        &mut TypeRegistry::new(),
+    )
+    .unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    // The classes contain the document
+    assert_eq!(representation.classes.len(), 251);
+    // The aliases contain the inline list of `Union0`
+    assert_eq!(representation.types_aliases.len(), 151);
+    assert_eq!(representation.union_types.len(), 100);
+    assert!(representation
+        .union_types
+        .iter()
+        .all(|u| u.is_class_hierarchy()));
+
+    let position = |name: &str| {
+        representation
+            .classes
+            .iter()
+            .position(|c| c.name == name)
+            .unwrap()
+    };
+
+    for i in 1..250 {
+        assert!(position(&format!("Class{}", i - 1)) < position(&format!("Class{i}")));
+    }
+}
@@ -0,0 +1,190 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    generator::code_generator_trait::CodeGenOptions,
+    parser::constructs::{count_constructs, is_supported_construct},
+    GenerationError,
+};
+
+/// Describes a generation, so the next generation of the unit can detect changes of the
+/// generator or its options, which change the generated code although the schemas are the same.
+///
+/// It is written as JSON file next to the unit and should be committed together with it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GenerationMetadata {
+    pub generator_version: String,
+    /// Options of the generation. The header is omitted, as it may contain the current date.
+    pub options: Value,
+    /// Number of the elements of the XSD namespace by their local name, e.g. `complexType`
+    pub constructs: BTreeMap<String, usize>,
+    /// Constructs of `constructs`, which were skipped by the generator
+    pub unsupported_constructs: BTreeMap<String, usize>,
+}
+
+impl GenerationMetadata {
+    /// Metadata of the generation of the xml schemas `source` with `options`
+    pub fn collect(source: &[PathBuf], options: &CodeGenOptions) -> Result<Self, GenerationError> {
+        let constructs = count_constructs(source).map_err(GenerationError::Parser)?;
+        let unsupported_constructs = constructs
+            .iter()
+            .filter(|(name, _)| !is_supported_construct(name))
+            .map(|(name, count)| (name.clone(), *count))
+            .collect();
+
+        let mut options = options.clone();
+        options.output.header = None;
+
+        Ok(Self {
+            generator_version: env!("CARGO_PKG_VERSION").to_owned(),
+            options: serde_json::to_value(&options)
+                .map_err(|e| GenerationError::Io(e.to_string()))?,
+            constructs,
+            unsupported_constructs,
+        })
+    }
+
+    pub fn from_json(content: &str) -> Result<Self, GenerationError> {
+        serde_json::from_str(content).map_err(|e| {
+            GenerationError::Io(format!(
+                "Could not read generation metadata due to following error: \"{e}\""
+            ))
+        })
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    /// Describes the changes since the `previous` generation, which may change the generated
+    /// code, e.g. another generator version or changed options.
+    pub fn drift(&self, previous: &Self) -> Vec<String> {
+        let mut changes = vec![];
+
+        if self.generator_version != previous.generator_version {
+            changes.push(format!(
+                "The unit was generated by version {} of the generator, it is now generated by version {}",
+                previous.generator_version, self.generator_version
+            ));
+        }
+
+        let mut changed_options = vec![];
+        compare_values("", &previous.options, &self.options, &mut changed_options);
+
+        for (path, old_value, new_value) in changed_options {
+            changes.push(format!(
+                "Option \"{path}\" changed from {old_value} to {new_value}"
+            ));
+        }
+
+        for name in self.unsupported_constructs.keys() {
+            if !previous.unsupported_constructs.contains_key(name) {
+                changes.push(format!(
+                    "The schemas now use xs:{name}, which is not supported and skipped"
+                ));
+            }
+        }
+
+        changes
+    }
+}
+
+/// Collects the paths and values of the leaves of `new`, which differ from `old`.
+/// Options missing in `old`, e.g. added by a newer version, are compared with `null`.
+fn compare_values(path: &str, old: &Value, new: &Value, changes: &mut Vec<(String, Value, Value)>) {
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => {
+            let keys = old.keys().chain(new.keys()).collect::<BTreeSet<_>>();
+
+            for key in keys {
+                let child_path = match path.is_empty() {
+                    true => key.clone(),
+                    false => format!("{path}.{key}"),
+                };
+
+                compare_values(
+                    &child_path,
+                    old.get(key).unwrap_or(&Value::Null),
+                    new.get(key).unwrap_or(&Value::Null),
+                    changes,
+                );
+            }
+        }
+        _ if old != new => changes.push((path.to_owned(), old.clone(), new.clone())),
+        _ => (),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn changed_version_options_and_constructs_are_reported() {
+        let previous = GenerationMetadata {
+            generator_version: String::from("0.1.0"),
+            options: json!({"naming": {"type_prefix": null}, "roots": []}),
+            constructs: BTreeMap::from([(String::from("element"), 2)]),
+            unsupported_constructs: BTreeMap::new(),
+        };
+        let current = GenerationMetadata {
+            generator_version: String::from("0.2.0"),
+            options: json!({"naming": {"type_prefix": "X"}, "roots": [], "passes": []}),
+            constructs: BTreeMap::from([(String::from("element"), 2), (String::from("any"), 1)]),
+            unsupported_constructs: BTreeMap::from([(String::from("any"), 1)]),
+        };
+
+        assert_eq!(
+            current.drift(&previous),
+            vec![
+                "The unit was generated by version 0.1.0 of the generator, it is now generated by version 0.2.0",
+                "Option \"naming.type_prefix\" changed from null to \"X\"",
+                "Option \"passes\" changed from null to []",
+                "The schemas now use xs:any, which is not supported and skipped",
+            ]
+        );
+        assert!(previous.drift(&previous).is_empty());
+    }
+
+    #[test]
+    fn unsupported_constructs_of_the_schema_are_collected() {
+        let path = std::env::temp_dir().join(format!("genphi-metadata-{}.xsd", std::process::id()));
+        std::fs::write(
+            &path,
+            r#"<xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">
+                <xs:element name="Order">
+                  <xs:complexType>
+                    <xs:sequence>
+                      <xs:element name="Id" type="xs:string"/>
+                      <xs:any minOccurs="0"/>
+                    </xs:sequence>
+                    <xs:anyAttribute/>
+                  </xs:complexType>
+                </xs:element>
+              </xs:schema>"#,
+        )
+        .unwrap();
+
+        let metadata = GenerationMetadata::collect(
+            std::slice::from_ref(&path),
+            &CodeGenOptions::builder("uTest").build(),
+        )
+        .unwrap();
+        std::fs::remove_file(path).unwrap();
+
+        assert_eq!(metadata.constructs.get("element"), Some(&2));
+        assert_eq!(
+            metadata.unsupported_constructs,
+            BTreeMap::from([(String::from("anyAttribute"), 1)])
+        );
+        assert_eq!(metadata.options["output"]["unit_name"], "uTest");
+    }
+}
@@ -1,9 +1,18 @@
 use std::{
     fmt,
     io::{BufWriter, Write},
+    sync::Arc,
 };
 
-use super::internal_representation::InternalRepresentation;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    internal_representation::InternalRepresentation,
+    passes::{BuiltinPass, CustomPasses, IrPass},
+    rename_map::RenameMap,
+    transliteration::Transliteration,
+};
+use crate::parser::types::ParserLimits;
 
 /// Trait for code generators
 pub trait CodeGenerator<T: Write> {
@@ -17,26 +26,377 @@ pub trait CodeGenerator<T: Write> {
     fn generate(&mut self) -> Result<(), CodeGenError>;
 }
 
-/// Options for the code generator
-#[derive(Debug, Default)]
+/// Options for the code generator, grouped by the part of the generation they control.
+///
+/// The options can be read from a configuration file with serde, missing values keep their default.
+/// Library callers build them with [`CodeGenOptions::builder`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct CodeGenOptions {
+    /// Names of the generated types and identifiers
+    pub naming: NamingOptions,
+
+    /// Reading and writing of the xml documents
+    pub serialization: SerializationOptions,
+
+    /// Unit and Delphi types, which are generated
+    pub output: OutputOptions,
+
+    /// Names of the root elements, whose types are generated. All types are generated, if empty.
+    pub roots: Vec<String>,
+
+    /// Limits of the schema files and the types defined by them
+    pub parser_limits: ParserLimits,
+
+    /// Memory in bytes available for the generation. Schemas, which probably need more,
+    /// are processed slower, but with less memory.
+    pub max_memory: Option<u64>,
+
+    /// Built-in passes transforming the types before the unit is generated, run in the given order
+    pub passes: Vec<BuiltinPass>,
+
+    /// Passes registered by `CodeGenOptionsBuilder::register_pass`, run after the built-in passes
+    #[serde(skip)]
+    pub custom_passes: CustomPasses,
+}
+
+/// Options for the names of the generated types and identifiers
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NamingOptions {
+    /// The prefix for the type
+    pub type_prefix: Option<String>,
+
+    /// How anonymous types, which are declared inside of an element, are named
+    pub anonymous_type_naming: AnonymousTypeNaming,
+
+    /// Maximum length of the names of anonymous types, longer names are cut and get a hash suffix
+    pub max_type_name_length: Option<usize>,
+
+    /// Replaces the non-ASCII characters of the type, field and enumeration value identifiers
+    pub transliteration: Option<Transliteration>,
+
+    /// Names, which replace the generated names of specific types, fields and enumeration values
+    pub rename_map: Option<RenameMap>,
+}
+
+/// Options for reading and writing the xml documents. By default both `from_xml` and `to_xml`
+/// are generated, like by the CLI.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SerializationOptions {
     /// Generate the `from_xml` function
     pub generate_from_xml: bool,
 
     /// Generate the `to_xml` function
     pub generate_to_xml: bool,
 
+    /// Read missing or invalid required elements and attributes as default value and collect a warning,
+    /// instead of raising an exception
+    pub lenient_from_xml: bool,
+
+    /// How `FromXml` of classes handles child elements and attributes, which are not defined by the schema
+    pub unknown_xml_nodes: UnknownXmlNodes,
+
+    /// Time zone handling of xs:dateTime and xs:date values
+    pub date_time_handling: DateTimeHandling,
+
+    /// Keep comments and processing instructions on document level, when reading and writing the document
+    pub preserve_document_nodes: bool,
+
+    /// How `FromXmlValue` of enumerations looks up the value
+    pub enum_lookup: EnumLookup,
+
+    /// Decorate the fields with `[XmlElement('name')]` and `[XmlAttribute('name')]`,
+    /// whose attribute classes are declared by the unit, for RTTI based serializers
+    pub generate_serializer_attributes: bool,
+
+    /// Required fields of string aliases with xs:pattern are properties, whose setter checks the pattern
+    pub validate_patterns: bool,
+
+    /// Generate `CheckConstraints` for classes of elements with xs:key, xs:keyref or xs:unique.
+    /// Requires the generation of `to_xml`, whose conversions are used for the values.
+    pub generate_constraint_checks: bool,
+
+    /// Generate a `<Field>WasPresentInXml` flag for optional elements and attributes of classes,
+    /// which tells whether `FromXml` found the node, even if its value equals the default value
+    pub generate_presence_flags: bool,
+
+    /// Formatting of the xml written by `ToXml`
+    pub xml_formatting: XmlFormatting,
+}
+
+impl Default for SerializationOptions {
+    fn default() -> Self {
+        Self {
+            generate_from_xml: true,
+            generate_to_xml: true,
+            lenient_from_xml: false,
+            unknown_xml_nodes: UnknownXmlNodes::default(),
+            date_time_handling: DateTimeHandling::default(),
+            preserve_document_nodes: false,
+            enum_lookup: EnumLookup::default(),
+            generate_serializer_attributes: false,
+            validate_patterns: false,
+            generate_constraint_checks: false,
+            generate_presence_flags: false,
+            xml_formatting: XmlFormatting::default(),
+        }
+    }
+}
+
+/// Options for the generated unit and the Delphi types used by it
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OutputOptions {
     /// The name of the unit
     pub unit_name: String,
 
-    /// The prefix for the type
-    pub type_prefix: Option<String>,
+    /// Header inserted at the top of the generated unit. `{unit}` is replaced with the unit name.
+    pub header: Option<String>,
+
+    /// Generate small types, which only contain value types, as records instead of classes
+    pub generate_records: bool,
+
+    /// Types, which should be generated as records regardless of their size
+    pub record_types: Vec<String>,
+
+    /// Fields as `<TYPE>.<FIELD>`, whose objects are owned elsewhere, e.g. shared lookups.
+    /// They are neither created nor freed by their class.
+    pub unowned_fields: Vec<String>,
+
+    /// Delphi type used for decimals restricted by xs:totalDigits or xs:fractionDigits
+    pub decimal_mapping: DecimalMapping,
+
+    /// Generate repeated values of an enumeration as set instead of list
+    pub generate_enum_sets: bool,
+
+    /// Delphi type of the lists of repeated elements
+    pub list_type: ListType,
+
+    /// Avoid generics, e.g. for older compilers: every list, array and optional value type
+    /// is generated as a concrete type per item type, like `TListOfInteger` for `TList<Integer>`.
+    /// Units are used without their scope and xs:anyURI is generated as `String`.
+    pub generics_free: bool,
+
+    /// Language of the xs:documentation elements used for the comments, e.g. `en`.
+    /// Falls back to the first language of an annotation.
+    pub documentation_language: Option<String>,
+
+    /// Generate a `ToString` override, which lists the fields and their values recursively.
+    /// Requires the generation of `to_xml`, whose conversions are used for the values.
+    pub generate_debug_dump: bool,
+
+    /// How the xs:appinfo of the types and the xs:notation elements are generated
+    pub schema_metadata: SchemaMetadataOutput,
+
+    /// Include `<unit name>.inc` into every class and at the end of the unit, so manual code
+    /// can be kept in a file, which is only written if it doesn't exist
+    pub generate_include_file: bool,
+}
+
+impl CodeGenOptions {
+    /// Starts the options of the unit with the default values of all other options
+    pub fn builder(unit_name: impl Into<String>) -> CodeGenOptionsBuilder {
+        CodeGenOptionsBuilder {
+            options: CodeGenOptions {
+                output: OutputOptions {
+                    unit_name: unit_name.into(),
+                    ..OutputOptions::default()
+                },
+                ..CodeGenOptions::default()
+            },
+        }
+    }
+}
+
+/// Builds [`CodeGenOptions`] from the option groups. The unit name of the builder is kept,
+/// when the output options are replaced.
+#[derive(Clone, Debug)]
+pub struct CodeGenOptionsBuilder {
+    options: CodeGenOptions,
+}
+
+impl CodeGenOptionsBuilder {
+    #[must_use]
+    pub fn naming(mut self, naming: NamingOptions) -> Self {
+        self.options.naming = naming;
+
+        self
+    }
+
+    #[must_use]
+    pub fn serialization(mut self, serialization: SerializationOptions) -> Self {
+        self.options.serialization = serialization;
+
+        self
+    }
+
+    #[must_use]
+    pub fn output(mut self, output: OutputOptions) -> Self {
+        self.options.output = OutputOptions {
+            unit_name: std::mem::take(&mut self.options.output.unit_name),
+            ..output
+        };
+
+        self
+    }
+
+    #[must_use]
+    pub fn roots(mut self, roots: Vec<String>) -> Self {
+        self.options.roots = roots;
+
+        self
+    }
+
+    #[must_use]
+    pub fn parser_limits(mut self, parser_limits: ParserLimits) -> Self {
+        self.options.parser_limits = parser_limits;
+
+        self
+    }
+
+    #[must_use]
+    pub fn max_memory(mut self, max_memory: Option<u64>) -> Self {
+        self.options.max_memory = max_memory;
+
+        self
+    }
+
+    #[must_use]
+    pub fn passes(mut self, passes: Vec<BuiltinPass>) -> Self {
+        self.options.passes = passes;
+
+        self
+    }
+
+    /// Appends a pass, which runs after the built-in passes and the passes registered before
+    #[must_use]
+    pub fn register_pass(mut self, pass: impl IrPass + 'static) -> Self {
+        self.options.custom_passes.0.push(Arc::new(pass));
+
+        self
+    }
+
+    pub fn build(self) -> CodeGenOptions {
+        self.options
+    }
+}
+
+/// Naming strategy for anonymous types declared inside of an element
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AnonymousTypeNaming {
+    /// Use the name of the element
+    #[default]
+    Element,
+    /// Prepend the name of the parent type to the name of the element
+    Path,
+}
+
+/// Lookup of the enumeration value for a xml value
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum EnumLookup {
+    /// Compare the xml value with every value in turn
+    #[default]
+    IfChain,
+    /// Binary search in a constant array of the xml values sorted by `CompareStr`
+    BinarySearch,
+}
+
+/// Handling of child elements and attributes, which are not defined by the schema
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum UnknownXmlNodes {
+    /// Skip them silently
+    #[default]
+    Ignore,
+    /// Add their names to `UnknownElements` of the object
+    Collect,
+    /// Raise an exception
+    Raise,
+}
+
+/// Time zone handling of xs:dateTime and xs:date values
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DateTimeHandling {
+    /// `TDateTime` in UTC, values are converted from and to UTC
+    #[default]
+    Utc,
+    /// `TDateTime` in the local time zone, values are converted from and to local time
+    Local,
+    /// xs:dateTime as `TXmlDateTime`, which keeps the value and the offset of the document
+    Offset,
+}
+
+/// Formatting of the xml written by `ToXml`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct XmlFormatting {
+    /// Spaces per level of nested elements, `None` writes compact xml without line breaks
+    pub indentation: Option<usize>,
+
+    /// Write the xml declaration, e.g. `<?xml version="1.0"?>`
+    pub include_declaration: bool,
+
+    /// Value of the standalone attribute of the xml declaration, which is omitted for `None`
+    pub standalone: Option<bool>,
+}
+
+impl Default for XmlFormatting {
+    fn default() -> Self {
+        Self {
+            indentation: None,
+            include_declaration: true,
+            standalone: None,
+        }
+    }
+}
+
+/// Delphi type of the lists of repeated elements
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ListType {
+    /// `TObjectList<T>`, which owns its items, for classes and `TList<T>` for other types
+    #[default]
+    ObjectList,
+    /// `TList<T>` for all types, the items of classes are freed by the destructor
+    List,
+    /// Dynamic arrays `TArray<T>`, the items of classes are freed by the destructor
+    Array,
+}
+
+/// Generation of the xs:appinfo of the types and the xs:notation elements
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SchemaMetadataOutput {
+    /// Leave them out
+    #[default]
+    None,
+    /// A string constant per type with xs:appinfo and per notation
+    Constants,
+    /// The constants and `TSchemaMetadata`, which looks up the xs:appinfo by the name of the generated type
+    Registry,
+}
+
+/// Delphi type used for decimals with digit facets
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DecimalMapping {
+    /// Keep `Double`, like every other decimal
+    #[default]
+    Double,
+    /// Use `Currency`, if the facets fit into its four decimal places, otherwise `TBcd`
+    Currency,
+    /// Use `TBcd`
+    Bcd,
 }
 
 /// Errors that can occur during code generation
 pub enum CodeGenError {
     IOError(std::io::Error),
-    ComplexTypeInSimpleTypeNotAllowed(String, String),
     TemplateEngineError(String),
     /// A required data type is missing
     MissingDataType(String, String),
@@ -44,6 +404,12 @@ pub enum CodeGenError {
     NestedFixedSizeList(String, String),
     /// A list inside of a fixed size list is not supported
     NestedListInFixedSizeList(String, String),
+    /// A root element selected for generation is not declared in the schema
+    UnknownRootElement(String),
+    /// An entry of the rename map doesn't match a type, field or enumeration value
+    InvalidRenameMap(String),
+    /// A field, which shouldn't be owned by its class, is not generated
+    InvalidUnownedField(String),
 }
 
 impl From<std::io::Error> for CodeGenError {
@@ -61,10 +427,6 @@ impl fmt::Debug for CodeGenError {
                 f,
                 "Required DataType is missing. Class: {type_name}, Variable: {variable}"
             ),
-            Self::ComplexTypeInSimpleTypeNotAllowed(union_type, variant) => write!(
-                f,
-                "A complex type inside a union type is not supported. Class: {union_type}, Variable: {variant}"
-            ),
             Self::NestedFixedSizeList(class, variable) => write!(
                 f,
                 "Fixed size list inside of a fixed size list is not supported. Class: {class}, Variable: {variable}"
@@ -73,6 +435,80 @@ impl fmt::Debug for CodeGenError {
                 f,
                 "Lists inside of a fixed size list is not supported. Class: {class}, Variable: {variable}"
             ),
+            Self::UnknownRootElement(name) => write!(
+                f,
+                "Root element \"{name}\" is not declared in the schema"
+            ),
+            Self::InvalidRenameMap(message) => write!(f, "Invalid rename map: {message}"),
+            Self::InvalidUnownedField(message) => write!(f, "Invalid unowned field: {message}"),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn builder_keeps_the_unit_name() {
+        let options = CodeGenOptions::builder("uApi")
+            .output(OutputOptions {
+                generate_records: true,
+                ..OutputOptions::default()
+            })
+            .build();
+
+        assert_eq!(options.output.unit_name, "uApi");
+        assert!(options.output.generate_records);
+    }
+
+    #[test]
+    fn missing_values_of_a_configuration_keep_their_default() {
+        let json = r#"{
+            "naming": { "type_prefix": "Api" },
+            "serialization": { "generate_to_xml": true, "date_time_handling": "offset" },
+            "output": { "unit_name": "uApi", "list_type": "object-list" },
+            "parser_limits": { "max_depth": 64 },
+            "passes": ["strip-unused-types"]
+        }"#;
+
+        let options = serde_json::from_str::<CodeGenOptions>(json).unwrap();
+
+        assert_eq!(
+            options,
+            CodeGenOptions::builder("uApi")
+                .naming(NamingOptions {
+                    type_prefix: Some(String::from("Api")),
+                    ..NamingOptions::default()
+                })
+                .serialization(SerializationOptions {
+                    date_time_handling: DateTimeHandling::Offset,
+                    ..SerializationOptions::default()
+                })
+                .parser_limits(ParserLimits {
+                    max_depth: 64,
+                    ..ParserLimits::default()
+                })
+                .passes(vec![BuiltinPass::StripUnusedTypes])
+                .build()
+        );
+    }
+
+    #[test]
+    fn partial_configuration_round_trips() {
+        let json = r#"{ "serialization": { "lenient_from_xml": true } }"#;
+
+        let options = serde_json::from_str::<CodeGenOptions>(json).unwrap();
+        let round_trip =
+            serde_json::from_str::<CodeGenOptions>(&serde_json::to_string(&options).unwrap())
+                .unwrap();
+
+        assert_eq!(round_trip, options);
+        assert!(options.serialization.lenient_from_xml);
+        assert!(options.serialization.generate_from_xml);
+        assert!(options.serialization.generate_to_xml);
+        assert_eq!(options.output, OutputOptions::default());
+    }
+}
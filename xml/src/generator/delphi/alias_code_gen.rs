@@ -1,7 +1,7 @@
 use crate::generator::{
-    code_generator_trait::CodeGenOptions,
+    code_generator_trait::{CodeGenOptions, DecimalMapping},
     delphi::template_models::TypeAlias as TemplateTypeAlias,
-    types::{DataType, TypeAlias},
+    types::{DataType, ResolvedTypeAliases, TypeAlias},
 };
 
 use super::helper::Helper;
@@ -12,12 +12,14 @@ use super::helper::Helper;
 ///
 /// ## Input
 ///
-/// ```rust
+/// ```ignore
 /// use xsd_types::generator::types::{DataType, TypeAlias};
 ///
 /// let type_aliases = vec![
 ///     TypeAlias {
 ///         pattern: None,
+///         total_digits: None,
+///         fraction_digits: None,
 ///         name: String::from("CustomString"),
 ///         qualified_name: String::from("CustomString"),
 ///         for_type: DataType::String,
@@ -25,6 +27,8 @@ use super::helper::Helper;
 ///     },
 ///     TypeAlias {
 ///         pattern: None,
+///         total_digits: None,
+///         fraction_digits: None,
 ///         name: String::from("CustomIntList"),
 ///         qualified_name: String::from("CustomIntList"),
 ///         for_type: DataType::List(Box::new(DataType::Integer)),
@@ -46,34 +50,103 @@ use super::helper::Helper;
 pub struct TypeAliasCodeGenerator;
 
 impl TypeAliasCodeGenerator {
+    /// Number of decimal places of the Delphi `Currency` type
+    const CURRENCY_FRACTION_DIGITS: usize = 4;
+    /// Number of digits before the decimal point of the Delphi `Currency` type
+    const CURRENCY_INTEGER_DIGITS: usize = 15;
+
+    /// Replaces `Double` with `Currency` or `TBcd` for decimals, which are
+    /// restricted by xs:totalDigits or xs:fractionDigits.
+    ///
+    /// With [`DecimalMapping::Currency`] decimals, whose facets exceed the range of
+    /// `Currency`, are mapped to `TBcd` to avoid losing precision.
+    pub(crate) fn apply_decimal_mapping(type_aliases: &mut [TypeAlias], mapping: DecimalMapping) {
+        for alias in type_aliases.iter_mut() {
+            if !matches!(alias.for_type, DataType::Double)
+                || (alias.total_digits.is_none() && alias.fraction_digits.is_none())
+            {
+                continue;
+            }
+
+            // Without xs:fractionDigits every digit may be a decimal place
+            let fraction_digits = alias.fraction_digits.or(alias.total_digits);
+            let fits_currency = fraction_digits
+                .is_some_and(|f| f <= Self::CURRENCY_FRACTION_DIGITS)
+                && alias.total_digits.is_none_or(|t| {
+                    t.saturating_sub(fraction_digits.unwrap_or_default())
+                        <= Self::CURRENCY_INTEGER_DIGITS
+                });
+
+            alias.for_type = match mapping {
+                DecimalMapping::Double => continue,
+                DecimalMapping::Currency if fits_currency => DataType::Currency,
+                DecimalMapping::Currency | DecimalMapping::Bcd => DataType::Bcd,
+            };
+        }
+    }
+
     pub(crate) fn build_template_models<'a>(
-        type_aliases: &'a [TypeAlias],
+        type_aliases: &ResolvedTypeAliases<'a>,
         options: &'a CodeGenOptions,
     ) -> Vec<TemplateTypeAlias<'a>> {
         type_aliases
+            .aliases()
             .iter()
             .filter_map(|a| {
                 if matches!(&a.for_type, DataType::FixedSizeList(_, _)) {
                     return None;
                 }
 
-                let documentations = a
-                    .documentations
-                    .iter()
-                    .flat_map(|d| d.lines())
-                    .collect::<Vec<&str>>();
+                let documentations = Helper::documentation_lines(&a.documentations);
+
+                let name = Helper::as_type_name(&a.name, &options.naming.type_prefix);
+                let validator =
+                    Self::pattern_validator(&a.name, type_aliases, &options.naming.type_prefix);
+                // Derived aliases check their own pattern and the one of their base alias
+                let own_pattern = a.pattern.as_deref().filter(|_| validator.is_some());
+                let base_validator = match &a.for_type {
+                    DataType::Custom(n) => {
+                        Self::pattern_validator(n, type_aliases, &options.naming.type_prefix)
+                    }
+                    _ => None,
+                };
 
                 Some(TemplateTypeAlias {
-                    name: Helper::as_type_name(&a.name, &options.type_prefix),
+                    pattern_constant: own_pattern.map(|_| format!("cn{}Pattern", &name[1..])),
+                    pattern_value: own_pattern
+                        .map(Helper::as_string_literal)
+                        .unwrap_or_default(),
+                    validator,
+                    base_validator,
+                    name,
                     qualified_name: &a.qualified_name,
                     pattern: &a.pattern,
                     data_type_repr: Helper::get_datatype_language_representation(
                         &a.for_type,
-                        &options.type_prefix,
+                        &options.naming.type_prefix,
+                        options.output.generics_free,
                     ),
                     documentations,
                 })
             })
             .collect::<Vec<TemplateTypeAlias<'a>>>()
     }
+
+    /// Name of the function, which checks a value of a string alias against its xs:pattern
+    /// and the patterns of the aliases it is derived from, e.g. `ValidatePostalCode`.
+    ///
+    /// Returns `None`, if the alias isn't a string or has no pattern.
+    pub(crate) fn pattern_validator(
+        alias: &str,
+        type_aliases: &ResolvedTypeAliases,
+        type_prefix: &Option<String>,
+    ) -> Option<String> {
+        match Helper::get_alias_data_type(alias, type_aliases)? {
+            (DataType::String, Some(_)) => Some(format!(
+                "Validate{}",
+                &Helper::as_type_name(alias, type_prefix)[1..]
+            )),
+            _ => None,
+        }
+    }
 }
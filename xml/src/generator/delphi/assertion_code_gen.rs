@@ -0,0 +1,273 @@
+use crate::generator::types::{ClassType, DataType, ResolvedTypeAliases, Variable, XMLSource};
+
+use super::helper::Helper;
+
+/// Comparison operators of XPath and their Delphi counterparts, longer symbols first
+const COMPARISONS: [(&str, &str); 12] = [
+    ("!=", "<>"),
+    ("<=", "<="),
+    (">=", ">="),
+    ("=", "="),
+    ("<", "<"),
+    (">", ">"),
+    ("eq", "="),
+    ("ne", "<>"),
+    ("lt", "<"),
+    ("le", "<="),
+    ("gt", ">"),
+    ("ge", ">="),
+];
+
+#[derive(Debug, PartialEq, Eq)]
+enum OperandKind {
+    Number,
+    Text,
+}
+
+/// Operand of a comparison
+struct Operand {
+    /// Conditions, which are true if the value is present
+    conditions: Vec<String>,
+    /// Delphi expression of the value
+    expr: String,
+    kind: OperandKind,
+    is_literal: bool,
+}
+
+/// Code generator for `CheckAssertions`, which checks the XSD 1.1 xs:assert of a class.
+///
+/// Only the comparison of two values is translated, e.g. `@min le @max` or `count > 0`.
+/// A value is an attribute or a single child element with a number, string or enumeration,
+/// or a literal. Other assertions are skipped with a warning.
+pub struct AssertionCodeGenerator;
+
+impl AssertionCodeGenerator {
+    /// Builds the statements of `CheckAssertions`, which add the test of every failed
+    /// assertion to the result
+    pub(crate) fn build_checks(
+        class_type: &ClassType,
+        hierarchy: &[ClassType],
+        type_aliases: &ResolvedTypeAliases,
+    ) -> Vec<String> {
+        class_type
+            .assertions
+            .iter()
+            .filter_map(
+                |test| match Self::build_check(class_type, test, hierarchy, type_aliases) {
+                    Ok(check) => Some(check),
+                    Err(reason) => {
+                        eprintln!(
+                            "Warning: xs:assert \"{test}\" of type \"{}\" is not checked, because {reason}",
+                            class_type.qualified_name
+                        );
+                        None
+                    }
+                },
+            )
+            .collect()
+    }
+
+    /// Whether `CheckAssertions` checks any assertion of the class. Doesn't warn about the
+    /// assertions, which are not translated.
+    pub(crate) fn has_checks(
+        class_type: &ClassType,
+        hierarchy: &[ClassType],
+        type_aliases: &ResolvedTypeAliases,
+    ) -> bool {
+        class_type
+            .assertions
+            .iter()
+            .any(|test| Self::build_check(class_type, test, hierarchy, type_aliases).is_ok())
+    }
+
+    fn build_check(
+        class_type: &ClassType,
+        test: &str,
+        hierarchy: &[ClassType],
+        type_aliases: &ResolvedTypeAliases,
+    ) -> Result<String, String> {
+        let (left, operator, right) = Self::split_comparison(test)?;
+        let left = Self::operand(left, class_type, hierarchy, type_aliases)?;
+        let right = Self::operand(right, class_type, hierarchy, type_aliases)?;
+
+        if left.is_literal && right.is_literal {
+            return Err(String::from("it doesn't test a value of the type"));
+        }
+
+        if left.kind != right.kind {
+            return Err(String::from("it compares a number with a string"));
+        }
+
+        let mut conditions = left.conditions;
+        conditions.extend(right.conditions);
+
+        let comparison = format!("{} {operator} {}", left.expr, right.expr);
+        let condition = match conditions.is_empty() {
+            true => comparison,
+            false => format!("{} and ({comparison})", conditions.join(" and ")),
+        };
+
+        Ok(format!(
+            "if not ({condition}) then Result := Result + [{}];",
+            Helper::as_string_literal(test).join(" ")
+        ))
+    }
+
+    /// Splits the test into the operands and the Delphi operator of its only comparison
+    fn split_comparison(test: &str) -> Result<(&str, &'static str, &str), String> {
+        let mut quote = None;
+        let mut skip_until = 0;
+        let mut comparisons = Vec::new();
+
+        for (i, c) in test.char_indices() {
+            match quote {
+                Some(q) if c == q => quote = None,
+                Some(_) => (),
+                None if c == '\'' || c == '"' => quote = Some(c),
+                None if i < skip_until => (),
+                None => {
+                    let comparison = COMPARISONS.iter().find(|(op, _)| {
+                        let is_word = op.starts_with(char::is_alphabetic);
+
+                        test[i..].starts_with(op)
+                            && (!is_word
+                                || test[..i].ends_with(char::is_whitespace)
+                                    && test[i + op.len()..].starts_with(char::is_whitespace))
+                    });
+
+                    if let Some((op, delphi)) = comparison {
+                        comparisons.push((i, op.len(), *delphi));
+                        skip_until = i + op.len();
+                    }
+                }
+            }
+        }
+
+        match comparisons.as_slice() {
+            [(i, len, operator)] => Ok((&test[..*i], operator, &test[i + len..])),
+            _ => Err(String::from(
+                "only the comparison of two values is supported",
+            )),
+        }
+    }
+
+    fn operand(
+        value: &str,
+        class_type: &ClassType,
+        hierarchy: &[ClassType],
+        type_aliases: &ResolvedTypeAliases,
+    ) -> Result<Operand, String> {
+        let value = value.trim();
+
+        if let Some(quote) = value.chars().next().filter(|c| *c == '\'' || *c == '"') {
+            let Some(text) = value[1..].strip_suffix(quote) else {
+                return Err(format!("\"{value}\" is not a string literal"));
+            };
+            let text = text.replace(&format!("{quote}{quote}"), &quote.to_string());
+
+            return Ok(Operand {
+                conditions: vec![],
+                expr: Helper::as_string_literal(&text).join(" "),
+                kind: OperandKind::Text,
+                is_literal: true,
+            });
+        }
+
+        if value.starts_with(|c: char| c.is_ascii_digit() || c == '-' || c == '.') {
+            if !value
+                .chars()
+                .all(|c| c.is_ascii_digit() || c == '-' || c == '.')
+                || value.parse::<f64>().is_err()
+            {
+                return Err(format!("\"{value}\" is not a number"));
+            }
+
+            return Ok(Operand {
+                conditions: vec![],
+                expr: value.to_owned(),
+                kind: OperandKind::Number,
+                is_literal: true,
+            });
+        }
+
+        let path = value.strip_prefix("./").unwrap_or(value);
+        let (xml_name, source) = match path.strip_prefix('@') {
+            Some(attribute) => (attribute, XMLSource::Attribute),
+            None => (path, XMLSource::Element),
+        };
+        let xml_name = xml_name.rsplit(':').next().unwrap_or(xml_name);
+
+        if xml_name.is_empty()
+            || !xml_name
+                .chars()
+                .all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.'))
+        {
+            return Err(format!("\"{value}\" is neither a child nor a literal"));
+        }
+
+        let variable = Self::variable(class_type, xml_name, &source, hierarchy)
+            .ok_or_else(|| format!("\"{value}\" is not a child of the type"))?;
+
+        let mut conditions = vec![];
+        let mut expr = format!("Self.{}", Helper::as_variable_name(&variable.name));
+
+        if !variable.required && !variable.is_const && variable.default_value.is_none() {
+            conditions.push(format!("{expr}.IsSome"));
+            expr.push_str(".Unwrap");
+        }
+
+        let data_type = match &variable.data_type {
+            DataType::Alias(name) => Helper::get_alias_data_type(name, type_aliases)
+                .map(|(data_type, _)| data_type)
+                .unwrap_or(DataType::Alias(name.clone())),
+            data_type => data_type.clone(),
+        };
+
+        let kind = match data_type {
+            DataType::Double
+            | DataType::Currency
+            | DataType::ShortInteger
+            | DataType::SmallInteger
+            | DataType::Integer
+            | DataType::LongInteger
+            | DataType::UnsignedShortInteger
+            | DataType::UnsignedSmallInteger
+            | DataType::UnsignedInteger
+            | DataType::UnsignedLongInteger => OperandKind::Number,
+            DataType::String => OperandKind::Text,
+            DataType::Enumeration(_) => {
+                expr.push_str(".ToXmlValue");
+                OperandKind::Text
+            }
+            _ => {
+                return Err(format!(
+                    "\"{value}\" is neither a single number nor a string"
+                ))
+            }
+        };
+
+        Ok(Operand {
+            conditions,
+            expr,
+            kind,
+            is_literal: false,
+        })
+    }
+
+    /// Variable of the element or attribute `xml_name` of the class or its ancestors
+    fn variable<'a>(
+        class_type: &'a ClassType,
+        xml_name: &str,
+        source: &XMLSource,
+        hierarchy: &'a [ClassType],
+    ) -> Option<&'a Variable> {
+        std::iter::successors(Some(class_type), |c| {
+            let (name, _) = c.super_type.as_ref()?;
+
+            hierarchy.iter().find(|h| &h.name == name)
+        })
+        .take(hierarchy.len() + 1)
+        .flat_map(|c| &c.variables)
+        .find(|v| v.xml_name == xml_name && &v.source == source)
+    }
+}
@@ -1,26 +1,65 @@
 use crate::generator::{
-    code_generator_trait::{CodeGenError, CodeGenOptions},
+    code_generator_trait::{
+        CodeGenError, CodeGenOptions, DateTimeHandling, ListType, UnknownXmlNodes,
+    },
     delphi::template_models::{
-        AttributeDeserializeVariable, ClassType as TemplateClassType, ElementDeserializeVariable,
+        AnyElements, AttributeDeserializeVariable, ClassType as TemplateClassType, DerivedClass,
+        ElementDeserializeVariable, OccurrenceCheck, PresenceFlag,
         SerializeVariable as TemplateSerializeVariable, Variable as TemplateVariable,
     },
-    internal_representation::DOCUMENT_NAME,
-    types::{BinaryEncoding, ClassType, DataType, TypeAlias, Variable, XMLSource},
+    internal_representation::{InternalRepresentation, DOCUMENT_NAME},
+    types::{
+        BinaryEncoding, ClassType, DataType, ProcessContents, ResolvedTypeAliases, Variable,
+        WildcardNamespaces, XMLSource,
+    },
 };
+use pascal::as_pascal_literal;
 
-use super::helper::Helper;
+use super::{
+    alias_code_gen::TypeAliasCodeGenerator,
+    assertion_code_gen::AssertionCodeGenerator,
+    constraint_code_gen::{ConstraintChecks, ConstraintCodeGenerator},
+    generics_free::{instance_name, Generic},
+    helper::Helper,
+};
+
+/// Maximum number of fields of a type, which is generated as record by the size heuristic.
+const MAX_RECORD_FIELDS: usize = 8;
 
 impl DataType {
     /// Determines if the data type is a reference type.
-    fn is_reference_type(&self, type_aliases: &[TypeAlias]) -> bool {
+    fn is_reference_type(&self, type_aliases: &ResolvedTypeAliases) -> bool {
         match self {
             Self::Alias(n) => Helper::get_alias_data_type(n.as_str(), type_aliases)
-                .map_or(true, |(dt, _)| dt.is_reference_type(type_aliases)),
+                .is_none_or(|(dt, _)| dt.is_reference_type(type_aliases)),
             Self::Custom(_) | Self::List(_) | Self::InlineList(_) => true,
             Self::FixedSizeList(dt, _) => dt.as_ref().is_reference_type(type_aliases),
             _ => false,
         }
     }
+
+    /// Determines if a field of this data type can be part of a record.
+    fn is_record_field_type(&self, type_aliases: &ResolvedTypeAliases) -> bool {
+        !self.is_reference_type(type_aliases)
+            && !matches!(self, Self::Uri | Self::Union(_) | Self::FixedSizeList(_, _))
+    }
+
+    /// Replaces references to classes, which are generated as records.
+    fn replace_record_references(&mut self, records: &[String]) {
+        match self {
+            Self::Custom(n) if records.contains(n) => *self = Self::Record(n.clone()),
+            Self::List(dt) | Self::FixedSizeList(dt, _) => dt.replace_record_references(records),
+            _ => (),
+        }
+    }
+
+    fn is_record(&self) -> bool {
+        match self {
+            Self::Record(_) => true,
+            Self::FixedSizeList(dt, _) => dt.is_record(),
+            _ => false,
+        }
+    }
 }
 
 impl Variable {
@@ -28,8 +67,11 @@ impl Variable {
         !self.required && !self.is_const && self.default_value.is_none()
     }
 
-    fn needs_optional_wrapper(&self, type_aliases: &[TypeAlias]) -> bool {
-        self.is_optional() && !self.data_type.is_reference_type(type_aliases)
+    pub(crate) fn needs_optional_wrapper(&self, type_aliases: &ResolvedTypeAliases) -> bool {
+        // An empty set already represents missing values, like an empty list
+        self.is_optional()
+            && !self.data_type.is_reference_type(type_aliases)
+            && !matches!(self.data_type, DataType::EnumSet(_))
     }
 }
 
@@ -37,7 +79,7 @@ impl Variable {
 pub struct ClassCodeGenerator;
 
 impl ClassCodeGenerator {
-    fn generate_standard_type_from_xml(
+    pub(super) fn generate_standard_type_from_xml(
         data_type: &DataType,
         value: String,
         pattern: Option<String>,
@@ -45,21 +87,26 @@ impl ClassCodeGenerator {
         match data_type {
             DataType::Boolean => format!("({value} = cnXmlTrueValue) or ({value} = '1')"),
             DataType::DateTime | DataType::Date if pattern.is_some() => format!(
-                "DecodeDateTime({}, '{}')",
+                "DecodeDateTime({}, {})",
                 value,
-                pattern.unwrap_or_default(),
+                as_pascal_literal(&pattern.unwrap_or_default()),
             ),
-            DataType::DateTime | DataType::Date => format!("ISO8601ToDate({value})"),
+            DataType::DateTime | DataType::Date => {
+                format!("ISO8601ToDate({value}, cnXmlDateTimeIsUtc)")
+            }
+            DataType::DateTimeOffset => format!("TXmlDateTime.FromXml({value})"),
             DataType::Double => format!("StrToFloat({value})"),
+            DataType::Currency => format!("StrToCurr({value}, TFormatSettings.Invariant)"),
+            DataType::Bcd => format!("StrToBcd({value}, TFormatSettings.Invariant)"),
             DataType::Binary(BinaryEncoding::Base64) => {
                 format!("TNetEncoding.Base64.DecodeStringToBytes({value})")
             }
             DataType::Binary(BinaryEncoding::Hex) => format!("HexStrToBin({value})"),
             DataType::String => value,
             DataType::Time if pattern.is_some() => format!(
-                "TimeOf(DecodeDateTime({}, '{}'))",
+                "TimeOf(DecodeDateTime({}, {}))",
                 value,
-                pattern.unwrap_or_default(),
+                as_pascal_literal(&pattern.unwrap_or_default()),
             ),
             DataType::Time => format!("TimeOf(ISO8601ToDate({value}))"),
             DataType::Uri => format!("TURI.Create({value})"),
@@ -83,49 +130,250 @@ impl ClassCodeGenerator {
         is_required: bool,
         is_value_type: bool,
         default_value: &Option<String>,
+        generics_free: bool,
     ) -> String {
         match (is_required, is_value_type, default_value) {
             (false, false, _) => format!("{name} := nil;"),
-            (false, true, None) => format!("{name} := TNone<{type_name}>.Create;"),
+            (false, true, None) => format!(
+                "{name} := {}.Create;",
+                instance_name(Generic::None, type_name, generics_free)
+            ),
             (true, false, _) => format!("{name} := {type_name}.Create;"),
             (true, true, None) => format!("{name} := Default({type_name});"),
             (_, true, Some(v)) => format!("{name} := {v};"),
         }
     }
 
+    /// Replaces xs:dateTime with `TXmlDateTime`, if the offsets of the values are kept.
+    ///
+    /// Returns true, if a xs:dateTime was replaced.
+    pub(crate) fn apply_date_time_offsets(
+        internal_representation: &mut InternalRepresentation,
+        options: &CodeGenOptions,
+    ) -> bool {
+        fn replace(data_type: &mut DataType) -> bool {
+            match data_type {
+                DataType::DateTime => {
+                    *data_type = DataType::DateTimeOffset;
+                    true
+                }
+                DataType::List(item)
+                | DataType::InlineList(item)
+                | DataType::FixedSizeList(item, _) => replace(item),
+                _ => false,
+            }
+        }
+
+        if options.serialization.date_time_handling != DateTimeHandling::Offset {
+            return false;
+        }
+
+        Self::replace_data_types(internal_representation, replace)
+    }
+
+    /// Replaces xs:anyURI with `String` in units without generics, as `TURI` is declared
+    /// by `System.Net.URLClient`, which older Delphi versions don't provide.
+    pub(crate) fn apply_uri_strings(
+        internal_representation: &mut InternalRepresentation,
+        options: &CodeGenOptions,
+    ) {
+        fn replace(data_type: &mut DataType) -> bool {
+            match data_type {
+                DataType::Uri => {
+                    *data_type = DataType::String;
+                    true
+                }
+                DataType::List(item)
+                | DataType::InlineList(item)
+                | DataType::FixedSizeList(item, _) => replace(item),
+                _ => false,
+            }
+        }
+
+        if !options.output.generics_free {
+            return;
+        }
+
+        // Strings are no objects, which are freed
+        let InternalRepresentation {
+            document, classes, ..
+        } = &mut *internal_representation;
+        std::iter::once(document)
+            .chain(classes.iter_mut())
+            .flat_map(|c| c.variables.iter_mut())
+            .filter(|v| v.data_type == DataType::Uri)
+            .for_each(|v| v.requires_free = false);
+
+        Self::replace_data_types(internal_representation, replace);
+    }
+
+    /// Applies `replace` to the data types of all variables, aliases and union variants.
+    ///
+    /// Returns true, if a data type was replaced.
+    fn replace_data_types(
+        internal_representation: &mut InternalRepresentation,
+        replace: fn(&mut DataType) -> bool,
+    ) -> bool {
+        let InternalRepresentation {
+            document,
+            classes,
+            types_aliases,
+            union_types,
+            ..
+        } = internal_representation;
+
+        let variables = std::iter::once(document)
+            .chain(classes.iter_mut())
+            .flat_map(|c| c.variables.iter_mut())
+            .map(|v| &mut v.data_type);
+        let aliases = types_aliases.iter_mut().map(|a| &mut a.for_type);
+        let variants = union_types
+            .iter_mut()
+            .flat_map(|u| u.variants.iter_mut())
+            .map(|v| &mut v.data_type);
+
+        let mut replaced = false;
+
+        for data_type in variables.chain(aliases).chain(variants) {
+            replaced |= replace(data_type);
+        }
+
+        replaced
+    }
+
+    /// Removes the classes, which should be generated as records, from the internal
+    /// representation and replaces all references to them.
+    ///
+    /// A class is generated as record, if it is selected by name in `record_types` or
+    /// by the size heuristic, takes no part in inheritance and only contains fields,
+    /// which are value types and not optional.
+    pub(crate) fn extract_records(
+        internal_representation: &mut InternalRepresentation,
+        options: &CodeGenOptions,
+    ) -> Vec<ClassType> {
+        let type_aliases = internal_representation.resolved_type_aliases();
+        let record_names = internal_representation
+            .classes
+            .iter()
+            .filter(|c| {
+                Self::can_be_record(c, &internal_representation.classes, &type_aliases, options)
+            })
+            .map(|c| c.name.clone())
+            .collect::<Vec<String>>();
+
+        if record_names.is_empty() {
+            return vec![];
+        }
+
+        for variable in internal_representation
+            .classes
+            .iter_mut()
+            .chain(std::iter::once(&mut internal_representation.document))
+            .flat_map(|c| c.variables.iter_mut())
+        {
+            variable.data_type.replace_record_references(&record_names);
+
+            if variable.data_type.is_record() {
+                variable.requires_free = false;
+            }
+        }
+
+        let (records, classes) = std::mem::take(&mut internal_representation.classes)
+            .into_iter()
+            .partition(|c| record_names.contains(&c.name));
+        internal_representation.classes = classes;
+
+        records
+    }
+
+    fn can_be_record(
+        class_type: &ClassType,
+        classes: &[ClassType],
+        type_aliases: &ResolvedTypeAliases,
+        options: &CodeGenOptions,
+    ) -> bool {
+        let is_selected = options.output.record_types.contains(&class_type.name)
+            || (options.output.generate_records && class_type.variables.len() <= MAX_RECORD_FIELDS);
+
+        is_selected
+            && class_type.name != DOCUMENT_NAME
+            && class_type.super_type.is_none()
+            && !class_type.is_abstract
+            && class_type.wildcard.is_none()
+            && !classes.iter().any(|c| {
+                c.super_type
+                    .as_ref()
+                    .is_some_and(|(n, _)| n == &class_type.name)
+            })
+            && class_type.variables.iter().all(|v| {
+                v.is_const
+                    || (!v.needs_optional_wrapper(type_aliases)
+                        && v.data_type.is_record_field_type(type_aliases))
+            })
+    }
+
+    /// Builds the template models of `classes`, `hierarchy` are all generated classes,
+    /// which are searched for abstract classes and their derived classes. The global elements
+    /// of `document` are allowed by wildcards with `processContents="strict"`.
     pub(crate) fn build_template_models<'a>(
         classes: &'a [ClassType],
-        type_aliases: &'a [TypeAlias],
+        hierarchy: &'a [ClassType],
+        document: &'a ClassType,
+        type_aliases: &ResolvedTypeAliases<'a>,
         options: &'a CodeGenOptions,
+        is_record: bool,
     ) -> Result<Vec<TemplateClassType<'a>>, CodeGenError> {
         classes
             .iter()
             .filter(|c| c.name != DOCUMENT_NAME)
-            .map(|c| Self::build_class_template_model(c, type_aliases, options))
+            .map(|c| {
+                Self::build_class_template_model(
+                    c,
+                    hierarchy,
+                    document,
+                    type_aliases,
+                    options,
+                    is_record,
+                )
+            })
             .collect::<Result<Vec<TemplateClassType<'a>>, CodeGenError>>()
     }
 
     pub(crate) fn build_class_template_model<'a>(
         class_type: &'a ClassType,
-        type_aliases: &'a [TypeAlias],
+        hierarchy: &'a [ClassType],
+        document: &'a ClassType,
+        type_aliases: &ResolvedTypeAliases<'a>,
         options: &'a CodeGenOptions,
+        is_record: bool,
     ) -> Result<TemplateClassType<'a>, CodeGenError> {
-        let needs_destructor = class_type
-            .variables
-            .iter()
-            .any(|v| v.requires_free || !v.required);
+        let preserves_nodes =
+            options.serialization.preserve_document_nodes && class_type.name == DOCUMENT_NAME;
+        let collects_warnings = options.serialization.lenient_from_xml && !is_record;
+        let declares_warnings = collects_warnings && class_type.super_type.is_none();
+        let checks_unknown_nodes =
+            options.serialization.unknown_xml_nodes != UnknownXmlNodes::Ignore && !is_record;
+        let declares_unknown_nodes = checks_unknown_nodes
+            && options.serialization.unknown_xml_nodes == UnknownXmlNodes::Collect
+            && class_type.super_type.is_none();
 
-        let documentations = class_type
-            .documentations
-            .iter()
-            .flat_map(|d| d.lines())
-            .collect::<Vec<&str>>();
+        let needs_destructor = preserves_nodes
+            || declares_warnings
+            || declares_unknown_nodes
+            || class_type.wildcard.is_some()
+            || (!is_record
+                && class_type
+                    .variables
+                    .iter()
+                    .any(|v| v.requires_free || !v.required));
+
+        let documentations = Helper::documentation_lines(&class_type.documentations);
 
         let constant_variables = class_type
             .variables
             .iter()
             .filter(|v| v.is_const)
-            .map(|v| Self::build_standard_template_variable(v, options))
+            .map(|v| Self::build_standard_template_variable(v, type_aliases, options))
             .collect::<Vec<TemplateVariable>>();
 
         let optional_variables = class_type
@@ -133,19 +381,45 @@ impl ClassCodeGenerator {
             .iter()
             .filter(|v| v.needs_optional_wrapper(type_aliases))
             .flat_map(|v| match &v.data_type {
-                DataType::FixedSizeList(dt, size) => {
-                    Self::build_fixed_size_list_template_variable(v, dt, *size, options)
-                }
-                _ => vec![Self::build_standard_template_variable(v, options)],
+                DataType::FixedSizeList(dt, size) => Self::build_fixed_size_list_template_variable(
+                    v,
+                    dt,
+                    *size,
+                    type_aliases,
+                    options,
+                ),
+                _ => vec![Self::build_standard_template_variable(
+                    v,
+                    type_aliases,
+                    options,
+                )],
             })
             .collect::<Vec<TemplateVariable>>();
 
-        let variables = Self::build_template_variables(class_type, type_aliases, options)?;
+        let variables =
+            Self::build_template_variables(class_type, type_aliases, options, is_record)?;
 
-        let serialize_variables = Self::build_serialize_variables(class_type, type_aliases)?;
+        let serialize_variables = Self::build_serialize_variables(
+            class_type,
+            type_aliases,
+            options,
+            &XMLSource::Element,
+        )?;
 
-        let variable_initializer =
-            Self::build_variable_initializer(class_type, type_aliases, options)?;
+        let serialize_attribute_variables = Self::build_serialize_variables(
+            class_type,
+            type_aliases,
+            options,
+            &XMLSource::Attribute,
+        )?;
+
+        let variable_initializer = Self::build_variable_initializer(
+            class_type,
+            hierarchy,
+            type_aliases,
+            options,
+            is_record,
+        )?;
 
         let has_optional_element_variables = class_type
             .variables
@@ -153,37 +427,321 @@ impl ClassCodeGenerator {
             .any(|v| !v.required && !v.is_const && v.source == XMLSource::Element);
 
         let deserialize_element_variables =
-            Self::build_deserialize_element_variables(class_type, type_aliases, options);
+            Self::build_deserialize_element_variables(class_type, hierarchy, type_aliases, options);
+        let has_abstract_ancestor = Self::ancestors(class_type, hierarchy).any(|c| c.is_abstract);
+
+        let deserialize_attribute_variables = Self::build_deserialize_attribute_variables(
+            class_type,
+            type_aliases,
+            options,
+            is_record,
+        );
 
-        let deserialize_attribute_variables =
-            Self::build_deserialize_attribute_variables(class_type, type_aliases, options);
+        let constraint_checks = match options.serialization.generate_constraint_checks
+            && options.serialization.generate_to_xml
+            && !is_record
+        {
+            true => {
+                ConstraintCodeGenerator::build_checks(class_type, hierarchy, type_aliases, options)
+            }
+            false => ConstraintChecks::default(),
+        };
 
         Ok(TemplateClassType {
-            name: Helper::as_type_name(&class_type.name, &options.type_prefix),
+            name: Helper::as_type_name(&class_type.name, &options.naming.type_prefix),
             qualified_name: &class_type.qualified_name,
             super_type: class_type
                 .super_type
                 .as_ref()
-                .map(|(n, _)| Helper::as_type_name(n, &options.type_prefix)),
+                .map(|(n, _)| Helper::as_type_name(n, &options.naming.type_prefix)),
             has_optional_fields: !optional_variables.is_empty(),
             has_constant_fields: !constant_variables.is_empty(),
             documentations,
+            is_record,
+            preserves_nodes,
+            collects_warnings,
+            declares_warnings,
+            checks_unknown_nodes,
+            declares_unknown_nodes,
+            raises_on_unknown_nodes: options.serialization.unknown_xml_nodes
+                == UnknownXmlNodes::Raise,
+            known_elements: match checks_unknown_nodes {
+                true => Self::known_element_names(class_type, hierarchy),
+                false => vec![],
+            },
+            known_attributes: class_type
+                .variables
+                .iter()
+                .filter(|v| checks_unknown_nodes && v.source == XMLSource::Attribute)
+                .map(|v| &v.xml_name)
+                .collect(),
             needs_destructor,
             variables,
             constant_variables,
             optional_variables,
             serialize_variables,
+            serialize_attribute_variables,
             variable_initializer,
             has_optional_element_variables,
             deserialize_attribute_variables,
             deserialize_element_variables,
+            occurrence_checks: Self::build_occurrence_checks(class_type, options.output.list_type),
+            constraint_checks: constraint_checks.statements,
+            constraint_variables: constraint_checks.variables,
+            assertion_checks: AssertionCodeGenerator::build_checks(
+                class_type,
+                hierarchy,
+                type_aliases,
+            ),
+            overrides_assertion_checks: Self::ancestors(class_type, hierarchy)
+                .any(|c| AssertionCodeGenerator::has_checks(c, hierarchy, type_aliases)),
+            presence_flags: match options.serialization.generate_presence_flags
+                && options.serialization.generate_from_xml
+                && !is_record
+            {
+                true => Self::build_presence_flags(class_type),
+                false => vec![],
+            },
+            is_abstract: class_type.is_abstract,
+            declares_xml_type_name: class_type.is_abstract && !has_abstract_ancestor,
+            overrides_xml_type_name: !class_type.is_abstract && has_abstract_ancestor,
+            xml_type_name: Self::xml_type_name(class_type),
+            derived_classes: match class_type.is_abstract {
+                true => Self::build_derived_classes(class_type, hierarchy, options),
+                false => vec![],
+            },
+            any_elements: Self::build_any_elements(class_type, hierarchy, document),
         })
     }
 
+    /// Child elements of the `xs:any` wildcard of the class, which are kept as xml.
+    ///
+    /// Elements declared by the class or its ancestors are never matched by the wildcard.
+    /// `processContents="strict"` only allows the global elements of the schema, `lax` and
+    /// `skip` allow every element, as the content of the matched elements is not validated.
+    fn build_any_elements<'a>(
+        class_type: &'a ClassType,
+        hierarchy: &'a [ClassType],
+        document: &'a ClassType,
+    ) -> Option<AnyElements<'a>> {
+        let wildcard = class_type.wildcard.as_ref()?;
+
+        let mut declared_elements = std::iter::once(class_type)
+            .chain(Self::ancestors(class_type, hierarchy))
+            .flat_map(|c| Self::known_element_names(c, hierarchy))
+            .collect::<Vec<_>>();
+        declared_elements.sort();
+        declared_elements.dedup();
+
+        let (namespace_condition, namespaces) = match &wildcard.namespaces {
+            WildcardNamespaces::Any => (None, String::from("of any namespace")),
+            WildcardNamespaces::Other(target) if target.is_empty() => (
+                Some(String::from("vChild.NamespaceURI <> ''")),
+                String::from("of any namespace"),
+            ),
+            WildcardNamespaces::Other(target) => (
+                Some(format!(
+                    "(vChild.NamespaceURI <> '') and (vChild.NamespaceURI <> {})",
+                    as_pascal_literal(target)
+                )),
+                format!("of any namespace except '{target}'"),
+            ),
+            WildcardNamespaces::List(namespaces) => (
+                Some(format!(
+                    "MatchStr(vChild.NamespaceURI, [{}])",
+                    namespaces
+                        .iter()
+                        .map(|n| as_pascal_literal(n))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )),
+                format!(
+                    "of the namespaces {}",
+                    namespaces
+                        .iter()
+                        .map(|n| match n.is_empty() {
+                            true => String::from("(no namespace)"),
+                            false => format!("'{n}'"),
+                        })
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            ),
+        };
+
+        let (strict_elements, validation) = match wildcard.process_contents {
+            ProcessContents::Strict => (
+                Some(
+                    document
+                        .variables
+                        .iter()
+                        .filter(|v| v.source == XMLSource::Element)
+                        .map(|v| &v.xml_name)
+                        .collect(),
+                ),
+                "processContents=\"strict\": FromXml raises an exception for elements, which are not declared globally by the schema.",
+            ),
+            ProcessContents::Lax => (
+                None,
+                "processContents=\"lax\": The elements are kept without validation, as no declaration is known for them.",
+            ),
+            ProcessContents::Skip => (
+                None,
+                "processContents=\"skip\": The elements are kept without validation.",
+            ),
+        };
+
+        Some(AnyElements {
+            namespace_condition,
+            declared_elements,
+            strict_elements,
+            description: vec![
+                format!("Xml of the child elements {namespaces}, which are matched by xs:any."),
+                String::from(validation),
+            ],
+        })
+    }
+
+    /// Optional elements and attributes of the class, including those with a default value
+    fn build_presence_flags(class_type: &ClassType) -> Vec<PresenceFlag<'_>> {
+        class_type
+            .variables
+            .iter()
+            .filter(|v| !v.required && !v.is_const)
+            .filter(|v| matches!(v.source, XMLSource::Element | XMLSource::Attribute))
+            .map(|v| PresenceFlag {
+                name: Helper::as_variable_name(&v.name) + "WasPresentInXml",
+                xml_name: &v.xml_name,
+                is_attribute: v.source == XMLSource::Attribute,
+                namespace: v.namespace.as_ref(),
+            })
+            .collect()
+    }
+
+    /// Super types of the class, starting with the direct one
+    fn ancestors<'a>(
+        class_type: &'a ClassType,
+        hierarchy: &'a [ClassType],
+    ) -> impl Iterator<Item = &'a ClassType> {
+        std::iter::successors(Some(class_type), |c| {
+            let (name, _) = c.super_type.as_ref()?;
+
+            hierarchy.iter().find(|h| &h.name == name)
+        })
+        .skip(1)
+        .take(hierarchy.len())
+    }
+
+    /// Names of the child elements of the class, including the elements of its repeated groups,
+    /// which are written directly into the class
+    fn known_element_names<'a>(
+        class_type: &'a ClassType,
+        hierarchy: &'a [ClassType],
+    ) -> Vec<&'a String> {
+        let mut names = Vec::new();
+
+        for variable in &class_type.variables {
+            match (&variable.source, &variable.data_type) {
+                (XMLSource::Element, _) => names.push(&variable.xml_name),
+                (XMLSource::Group, DataType::List(item)) => {
+                    let group = match item.as_ref() {
+                        DataType::Custom(name) => hierarchy.iter().find(|c| &c.name == name),
+                        _ => None,
+                    };
+
+                    match group {
+                        Some(group) => names.extend(Self::known_element_names(group, hierarchy)),
+                        None => names.push(&variable.xml_name),
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// Concrete classes derived from the abstract class, which `FromXmlByType` can create
+    fn build_derived_classes<'a>(
+        class_type: &'a ClassType,
+        hierarchy: &'a [ClassType],
+        options: &CodeGenOptions,
+    ) -> Vec<DerivedClass<'a>> {
+        let mut derived_classes = hierarchy
+            .iter()
+            .filter(|c| !c.is_abstract)
+            .filter(|c| Self::ancestors(c, hierarchy).any(|a| a.name == class_type.name))
+            .map(|c| DerivedClass {
+                name: Helper::as_type_name(&c.name, &options.naming.type_prefix),
+                xml_type_name: Self::xml_type_name(c),
+            })
+            .collect::<Vec<_>>();
+        derived_classes.sort_by(|a, b| a.xml_type_name.cmp(b.xml_type_name));
+
+        derived_classes
+    }
+
+    /// Name of the type in the schema, as used by `xsi:type`
+    fn xml_type_name(class_type: &ClassType) -> &str {
+        class_type
+            .qualified_name
+            .rsplit('/')
+            .next()
+            .unwrap_or(&class_type.qualified_name)
+    }
+
+    /// Abstract classes are read by `FromXmlByType`, which creates the class named by `xsi:type`
+    fn from_xml_function(name: &str, hierarchy: &[ClassType]) -> &'static str {
+        match hierarchy.iter().any(|c| c.name == name && c.is_abstract) {
+            true => "FromXmlByType",
+            false => "FromXml",
+        }
+    }
+
+    fn build_occurrence_checks(
+        class_type: &ClassType,
+        list_type: ListType,
+    ) -> Vec<OccurrenceCheck<'_>> {
+        class_type
+            .variables
+            .iter()
+            .filter(|v| matches!(v.data_type, DataType::List(_)))
+            .filter_map(|v| {
+                let occurrence = v.occurrence.as_ref()?;
+                let name = Helper::as_variable_name(&v.name);
+                let count = Helper::list_count(&name, list_type);
+
+                let (condition, expected) = match occurrence.max {
+                    Some(max) if occurrence.min > 0 => (
+                        format!("({count} < {}) or ({count} > {max})", occurrence.min),
+                        format!("{} to {max} times", occurrence.min),
+                    ),
+                    Some(max) => (format!("{count} > {max}"), format!("at most {max} times")),
+                    None => (
+                        format!("{count} < {}", occurrence.min),
+                        format!("at least {} times", occurrence.min),
+                    ),
+                };
+
+                Some(OccurrenceCheck {
+                    name,
+                    xml_name: &v.xml_name,
+                    condition,
+                    count,
+                    is_array: list_type == ListType::Array,
+                    expected,
+                })
+            })
+            .collect()
+    }
+
     fn build_template_variables<'a>(
         class_type: &'a ClassType,
-        type_aliases: &'a [TypeAlias],
+        type_aliases: &ResolvedTypeAliases<'a>,
         options: &'a CodeGenOptions,
+        is_record: bool,
     ) -> Result<Vec<TemplateVariable<'a>>, CodeGenError> {
         let variables = class_type
             .variables
@@ -195,28 +753,37 @@ impl ClassCodeGenerator {
                         Helper::get_alias_data_type(n.as_str(), type_aliases)
                     {
                         let data_type_repr = if let DataType::InlineList(_) = data_type {
-                            Helper::as_type_name(n, &options.type_prefix)
+                            Helper::as_type_name(n, &options.naming.type_prefix)
                         } else {
                             Helper::get_datatype_language_representation(
                                 &v.data_type,
-                                &options.type_prefix,
+                                &options.naming.type_prefix,
+                                options.output.generics_free,
                             )
                         };
 
-                        let documentations = v
-                            .documentations
-                            .iter()
-                            .flat_map(|d| d.lines())
-                            .collect::<Vec<&str>>();
+                        let documentations = Helper::documentation_lines(&v.documentations);
 
                         Ok(vec![TemplateVariable {
                             name: Helper::as_variable_name(&v.name),
                             xml_name: &v.xml_name,
-                            default_value: &v.default_value,
+                            default_value: v
+                                .default_value
+                                .as_ref()
+                                .map(|d| Helper::as_default_value(d, &v.data_type, type_aliases)),
                             required: v.required,
-                            requires_free: v.requires_free,
+                            requires_free: v.requires_free && v.is_owned,
+                            frees_items: false,
+                            item_type_repr: None,
                             data_type_repr,
                             documentations,
+                            serializer_attribute: Self::serializer_attribute(v, options),
+                            pattern_validator: Self::pattern_validator(
+                                v,
+                                type_aliases,
+                                options,
+                                is_record,
+                            ),
                         }])
                     } else {
                         Err(CodeGenError::MissingDataType(
@@ -225,10 +792,20 @@ impl ClassCodeGenerator {
                         ))
                     }
                 }
-                DataType::FixedSizeList(dt, size) => Ok(
-                    Self::build_fixed_size_list_template_variable(v, dt, *size, options),
-                ),
-                _ => Ok(vec![Self::build_standard_template_variable(v, options)]),
+                DataType::FixedSizeList(dt, size) => {
+                    Ok(Self::build_fixed_size_list_template_variable(
+                        v,
+                        dt,
+                        *size,
+                        type_aliases,
+                        options,
+                    ))
+                }
+                _ => Ok(vec![Self::build_standard_template_variable(
+                    v,
+                    type_aliases,
+                    options,
+                )]),
             })
             .collect::<Result<Vec<Vec<TemplateVariable>>, CodeGenError>>()?
             .into_iter()
@@ -240,25 +817,51 @@ impl ClassCodeGenerator {
 
     fn build_standard_template_variable<'a>(
         variable: &'a Variable,
+        type_aliases: &ResolvedTypeAliases,
         options: &'a CodeGenOptions,
     ) -> TemplateVariable<'a> {
-        let documentations = variable
-            .documentations
-            .iter()
-            .flat_map(|d| d.lines())
-            .collect::<Vec<&str>>();
+        let documentations = Helper::documentation_lines(&variable.documentations);
+
+        let list_type = Helper::variable_list_type(variable, options.output.list_type);
+        let frees_items =
+            |item_type: &DataType| variable.is_owned && matches!(item_type, DataType::Custom(_));
+
+        // Only `TObjectList` owns the items of classes, the items of other lists are freed one by one.
+        // Lists of objects owned elsewhere are freed without their items.
+        let (requires_free, frees_items) = match (&variable.data_type, list_type) {
+            (DataType::List(lt), ListType::List) => (variable.requires_free, frees_items(lt)),
+            (DataType::List(lt), ListType::Array) => (false, frees_items(lt)),
+            (DataType::List(_), ListType::ObjectList) => (variable.requires_free, false),
+            _ => (variable.requires_free && variable.is_owned, false),
+        };
 
         TemplateVariable {
             name: Helper::as_variable_name(&variable.name),
             xml_name: &variable.xml_name,
-            data_type_repr: Helper::get_datatype_language_representation(
+            data_type_repr: Helper::get_variable_type_representation(
                 &variable.data_type,
-                &options.type_prefix,
+                list_type,
+                &options.naming.type_prefix,
+                options.output.generics_free,
             ),
-            default_value: &variable.default_value,
+            default_value: variable
+                .default_value
+                .as_ref()
+                .map(|d| Helper::as_default_value(d, &variable.data_type, type_aliases)),
             required: variable.required,
-            requires_free: variable.requires_free,
+            requires_free,
+            frees_items,
+            item_type_repr: match &variable.data_type {
+                DataType::List(lt) => Some(Helper::get_datatype_language_representation(
+                    lt,
+                    &options.naming.type_prefix,
+                    options.output.generics_free,
+                )),
+                _ => None,
+            },
             documentations,
+            serializer_attribute: Self::serializer_attribute(variable, options),
+            pattern_validator: None,
         }
     }
 
@@ -266,13 +869,10 @@ impl ClassCodeGenerator {
         variable: &'a Variable,
         data_type: &'a DataType,
         size: usize,
+        type_aliases: &ResolvedTypeAliases,
         options: &CodeGenOptions,
     ) -> Vec<TemplateVariable<'a>> {
-        let documentations = variable
-            .documentations
-            .iter()
-            .flat_map(|d| d.lines())
-            .collect::<Vec<&str>>();
+        let documentations = Helper::documentation_lines(&variable.documentations);
 
         (1..size + 1)
             .map(|i| TemplateVariable {
@@ -280,23 +880,85 @@ impl ClassCodeGenerator {
                 xml_name: &variable.xml_name,
                 data_type_repr: Helper::get_datatype_language_representation(
                     data_type,
-                    &options.type_prefix,
+                    &options.naming.type_prefix,
+                    options.output.generics_free,
                 ),
-                default_value: &variable.default_value,
+                default_value: variable
+                    .default_value
+                    .as_ref()
+                    .map(|d| Helper::as_default_value(d, &variable.data_type, type_aliases)),
                 required: variable.required,
-                requires_free: variable.requires_free,
+                requires_free: variable.requires_free && variable.is_owned,
+                frees_items: false,
+                item_type_repr: None,
                 documentations: documentations.clone(),
+                serializer_attribute: Self::serializer_attribute(variable, options),
+                pattern_validator: None,
             })
             .collect::<Vec<TemplateVariable>>()
     }
 
+    /// Custom attribute, which names the xml node of the variable for RTTI based serializers.
+    ///
+    /// Repeated sequences are marked as `XmlGroup`, whose items are written into the parent,
+    /// with the name of the element starting each repetition.
+    fn serializer_attribute(variable: &Variable, options: &CodeGenOptions) -> Option<String> {
+        if !options.serialization.generate_serializer_attributes || variable.is_const {
+            return None;
+        }
+
+        let attribute = match variable.source {
+            XMLSource::Element => "XmlElement",
+            XMLSource::Attribute => "XmlAttribute",
+            XMLSource::Group => "XmlGroup",
+        };
+
+        Some(format!(
+            "{attribute}({})",
+            as_pascal_literal(&variable.xml_name)
+        ))
+    }
+
+    /// Validation function called by the setter of a required string alias with xs:pattern,
+    /// if the patterns are validated. Fields of records have no setter.
+    fn pattern_validator(
+        variable: &Variable,
+        type_aliases: &ResolvedTypeAliases,
+        options: &CodeGenOptions,
+        is_record: bool,
+    ) -> Option<String> {
+        match &variable.data_type {
+            DataType::Alias(name)
+                if options.serialization.validate_patterns && variable.required && !is_record =>
+            {
+                TypeAliasCodeGenerator::pattern_validator(
+                    name,
+                    type_aliases,
+                    &options.naming.type_prefix,
+                )
+            }
+            _ => None,
+        }
+    }
+
+    /// Builds the serialization models for all variables of the given source.
+    ///
+    /// The order of the variables is kept as is, because elements must be written
+    /// in the order defined by the content model of the schema. Repeated sequences
+    /// are part of the content model and therefore collected together with elements.
     fn build_serialize_variables<'a>(
         class_type: &'a ClassType,
-        type_aliases: &'a [TypeAlias],
+        type_aliases: &ResolvedTypeAliases<'a>,
+        options: &CodeGenOptions,
+        source: &XMLSource,
     ) -> Result<Vec<TemplateSerializeVariable<'a>>, CodeGenError> {
         let variables = class_type
             .variables
             .iter()
+            .filter(|v| match source {
+                XMLSource::Attribute => v.source == XMLSource::Attribute,
+                _ => v.source != XMLSource::Attribute,
+            })
             .map(|v| {
                 let variable_name = Helper::as_variable_name(&v.name);
 
@@ -321,11 +983,14 @@ impl ClassCodeGenerator {
                             Ok(vec![TemplateSerializeVariable {
                                 name: variable_name,
                                 xml_name: &v.xml_name,
+                                namespace: v.namespace.as_ref(),
                                 is_required: v.required,
                                 is_class: false,
                                 is_enum: false,
                                 is_list: false,
                                 is_inline_list: matches!(data_type, DataType::InlineList(_)),
+                                is_group: false,
+                                item_type_repr: None,
                                 from_xml_code: String::new(),
                                 to_xml_code: Helper::get_variable_value_as_string(
                                     getter_data_type,
@@ -338,55 +1003,94 @@ impl ClassCodeGenerator {
                             Ok(vec![])
                         }
                     }
-                    DataType::Enumeration(_) => Ok(vec![TemplateSerializeVariable {
-                        name: variable_name,
-                        xml_name: &v.xml_name,
-                        is_required: v.required,
-                        is_class: false,
-                        is_enum: true,
-                        is_list: false,
-                        is_inline_list: false,
-                        has_optional_wrapper: v.needs_optional_wrapper(type_aliases),
-                        from_xml_code: String::new(),
-                        to_xml_code: String::new(),
-                    }]),
-                    DataType::Custom(_) => Ok(vec![TemplateSerializeVariable {
-                        name: variable_name,
-                        xml_name: &v.xml_name,
-                        is_required: v.required,
-                        is_class: true,
-                        is_enum: false,
-                        is_list: false,
-                        is_inline_list: false,
-                        has_optional_wrapper: v.needs_optional_wrapper(type_aliases),
-                        from_xml_code: String::new(),
-                        to_xml_code: String::new(),
-                    }]),
+                    DataType::Enumeration(_) | DataType::Union(_) => {
+                        Ok(vec![TemplateSerializeVariable {
+                            name: variable_name,
+                            xml_name: &v.xml_name,
+                            namespace: v.namespace.as_ref(),
+                            is_required: v.required,
+                            is_class: false,
+                            is_enum: true,
+                            is_list: false,
+                            is_inline_list: false,
+                            is_group: false,
+                            item_type_repr: None,
+                            has_optional_wrapper: v.needs_optional_wrapper(type_aliases),
+                            from_xml_code: String::new(),
+                            to_xml_code: String::new(),
+                        }])
+                    }
+                    DataType::Custom(_) | DataType::Record(_) => {
+                        Ok(vec![TemplateSerializeVariable {
+                            name: variable_name,
+                            xml_name: &v.xml_name,
+                            namespace: v.namespace.as_ref(),
+                            is_required: v.required,
+                            is_class: true,
+                            is_enum: false,
+                            is_list: false,
+                            is_inline_list: false,
+                            is_group: false,
+                            item_type_repr: None,
+                            has_optional_wrapper: v.needs_optional_wrapper(type_aliases),
+                            from_xml_code: String::new(),
+                            to_xml_code: String::new(),
+                        }])
+                    }
                     DataType::List(lt) => Ok(vec![TemplateSerializeVariable {
                         name: variable_name,
                         xml_name: &v.xml_name,
+                        namespace: v.namespace.as_ref(),
                         is_required: v.required,
-                        is_class: matches!(**lt, DataType::Custom(_)),
+                        is_class: matches!(**lt, DataType::Custom(_) | DataType::Record(_)),
                         is_enum: matches!(**lt, DataType::Enumeration(_)),
                         is_list: true,
                         is_inline_list: false,
+                        is_group: v.source == XMLSource::Group,
+                        item_type_repr: Some(Helper::get_datatype_language_representation(
+                            lt,
+                            &options.naming.type_prefix,
+                            options.output.generics_free,
+                        )),
                         has_optional_wrapper: v.needs_optional_wrapper(type_aliases),
                         from_xml_code: String::new(),
                         to_xml_code: Helper::get_variable_value_as_string(
                             lt,
-                            &String::from("__Item"),
+                            &format!("__{}Item", Helper::as_variable_name(&v.name)),
                             &None,
                         ),
                     }]),
+                    DataType::EnumSet(e) => Ok(vec![TemplateSerializeVariable {
+                        name: variable_name,
+                        xml_name: &v.xml_name,
+                        namespace: v.namespace.as_ref(),
+                        is_required: v.required,
+                        is_class: false,
+                        is_enum: true,
+                        is_list: true,
+                        is_inline_list: false,
+                        is_group: false,
+                        item_type_repr: Some(Helper::get_datatype_language_representation(
+                            &DataType::Enumeration(e.clone()),
+                            &options.naming.type_prefix,
+                            options.output.generics_free,
+                        )),
+                        has_optional_wrapper: false,
+                        from_xml_code: String::new(),
+                        to_xml_code: String::new(),
+                    }]),
                     DataType::FixedSizeList(dt, size) => Ok((1..size + 1)
                         .map(|i| TemplateSerializeVariable {
                             name: format!("{}{}", Helper::as_variable_name(&v.name), i),
                             xml_name: &v.xml_name,
+                            namespace: v.namespace.as_ref(),
                             is_required: v.required,
-                            is_class: matches!(**dt, DataType::Custom(_)),
+                            is_class: matches!(**dt, DataType::Custom(_) | DataType::Record(_)),
                             is_enum: matches!(**dt, DataType::Enumeration(_)),
                             is_list: false,
                             is_inline_list: false,
+                            is_group: false,
+                            item_type_repr: None,
                             has_optional_wrapper: v.needs_optional_wrapper(type_aliases),
                             from_xml_code: String::new(),
                             to_xml_code: Helper::get_variable_value_as_string(
@@ -408,11 +1112,14 @@ impl ClassCodeGenerator {
                         Ok(vec![TemplateSerializeVariable {
                             name: variable_name,
                             xml_name: &v.xml_name,
+                            namespace: v.namespace.as_ref(),
                             is_required: v.required,
                             is_class: false,
                             is_enum: false,
                             is_list: false,
                             is_inline_list: false,
+                            is_group: false,
+                            item_type_repr: None,
                             from_xml_code: String::new(),
                             to_xml_code: Helper::get_variable_value_as_string(
                                 &v.data_type,
@@ -434,14 +1141,24 @@ impl ClassCodeGenerator {
 
     fn build_variable_initializer<'a>(
         class_type: &'a ClassType,
-        type_aliases: &'a [TypeAlias],
+        hierarchy: &[ClassType],
+        type_aliases: &ResolvedTypeAliases<'a>,
         options: &'a CodeGenOptions,
+        is_record: bool,
     ) -> Result<Vec<String>, CodeGenError> {
         let serialize_variables = class_type
             .variables
             .iter()
             .map(|v| {
-                let variable_name = Helper::as_variable_name(&v.name);
+                let mut variable_name = Helper::as_variable_name(&v.name);
+                // The default value is assigned to the field, it doesn't need to match the pattern
+                if Self::pattern_validator(v, type_aliases, options, is_record).is_some() {
+                    variable_name.insert(0, 'F');
+                }
+                let default_value = v
+                    .default_value
+                    .as_ref()
+                    .map(|d| Helper::as_default_value(d, &v.data_type, type_aliases));
 
                 match &v.data_type {
                     DataType::Alias(name) => {
@@ -453,18 +1170,21 @@ impl ClassCodeGenerator {
                                     &variable_name,
                                     &Helper::get_datatype_language_representation(
                                         &data_type,
-                                        &options.type_prefix,
+                                        &options.naming.type_prefix,
+                                        options.output.generics_free,
                                     ),
                                     v.required,
                                     false,
                                     &v.default_value,
+                                    options.output.generics_free,
                                 ),
                                 _ => Self::get_variable_initialization_code(
                                     &variable_name,
-                                    &Helper::as_type_name(name, &options.type_prefix),
+                                    &Helper::as_type_name(name, &options.naming.type_prefix),
                                     v.required,
                                     true,
-                                    &v.default_value,
+                                    &default_value,
+                                    options.output.generics_free,
                                 ),
                             }])
                         } else {
@@ -472,30 +1192,56 @@ impl ClassCodeGenerator {
                         }
                     }
                     DataType::Enumeration(name) => {
+                        let type_name = Helper::as_type_name(name, &options.naming.type_prefix);
+                        // The variant names may differ from the values
+                        let default_value = v
+                            .default_value
+                            .as_ref()
+                            .map(|d| format!("{type_name}.FromXmlValue({})", as_pascal_literal(d)));
+
                         Ok(vec![Self::get_variable_initialization_code(
                             &variable_name,
-                            &Helper::as_type_name(name, &options.type_prefix),
+                            &type_name,
                             v.required,
                             true,
-                            &v.default_value,
+                            &default_value,
+                            options.output.generics_free,
                         )])
                     }
+                    // Abstract classes are never created, the derived class is assigned later
                     DataType::Custom(name) => Ok(vec![Self::get_variable_initialization_code(
                         &variable_name,
-                        &Helper::as_type_name(name, &options.type_prefix),
-                        v.required,
+                        &Helper::as_type_name(name, &options.naming.type_prefix),
+                        v.required
+                            && v.is_owned
+                            && !hierarchy.iter().any(|c| &c.name == name && c.is_abstract),
                         false,
                         &v.default_value,
+                        options.output.generics_free,
                     )]),
+                    DataType::Record(name) => Ok(vec![Self::get_variable_initialization_code(
+                        &variable_name,
+                        &Helper::as_type_name(name, &options.naming.type_prefix),
+                        v.required,
+                        true,
+                        &v.default_value,
+                        options.output.generics_free,
+                    )]),
+                    DataType::List(_) if options.output.list_type == ListType::Array => {
+                        Ok(vec![format!("{variable_name} := [];")])
+                    }
                     DataType::List(_) => Ok(vec![Self::get_variable_initialization_code(
                         &variable_name,
-                        &Helper::get_datatype_language_representation(
+                        &Helper::get_variable_type_representation(
                             &v.data_type,
-                            &options.type_prefix,
+                            Helper::variable_list_type(v, options.output.list_type),
+                            &options.naming.type_prefix,
+                            options.output.generics_free,
                         ),
                         true,
                         false,
                         &v.default_value,
+                        options.output.generics_free,
                     )]),
                     DataType::FixedSizeList(dt, size) => {
                         let rhs = match dt.as_ref() {
@@ -504,12 +1250,19 @@ impl ClassCodeGenerator {
                                     Helper::get_alias_data_type(name.as_str(), type_aliases)
                                 {
                                     let type_name =
-                                        Helper::as_type_name(name, &options.type_prefix);
+                                        Helper::as_type_name(name, &options.naming.type_prefix);
 
                                     match data_type {
                                         DataType::Custom(_) => String::from("nil"),
                                         _ if v.required => format!("Default({type_name})"),
-                                        _ => format!("TNone<{type_name}>.Create"),
+                                        _ => format!(
+                                            "{}.Create",
+                                            instance_name(
+                                                Generic::None,
+                                                &type_name,
+                                                options.output.generics_free
+                                            )
+                                        ),
                                     }
                                 } else {
                                     return Err(CodeGenError::MissingDataType(
@@ -519,19 +1272,27 @@ impl ClassCodeGenerator {
                                 }
                             }
                             DataType::Enumeration(name) => {
-                                let type_name = Helper::as_type_name(name, &options.type_prefix);
+                                let type_name =
+                                    Helper::as_type_name(name, &options.naming.type_prefix);
 
                                 if v.required {
                                     format!("Default({type_name})")
                                 } else {
-                                    format!("TNone<{type_name}>.Create")
+                                    format!(
+                                        "{}.Create",
+                                        instance_name(
+                                            Generic::None,
+                                            &type_name,
+                                            options.output.generics_free
+                                        )
+                                    )
                                 }
                             }
                             DataType::Custom(name) => {
-                                if v.required {
+                                if v.required && v.is_owned {
                                     format!(
                                         "{}.Create",
-                                        Helper::as_type_name(name, &options.type_prefix)
+                                        Helper::as_type_name(name, &options.naming.type_prefix)
                                     )
                                 } else {
                                     String::from("nil")
@@ -552,13 +1313,21 @@ impl ClassCodeGenerator {
                             _ => {
                                 let lang_rep = Helper::get_datatype_language_representation(
                                     dt.as_ref(),
-                                    &options.type_prefix,
+                                    &options.naming.type_prefix,
+                                    options.output.generics_free,
                                 );
 
                                 if v.required {
                                     format!("Default({lang_rep})")
                                 } else {
-                                    format!("TNone<{lang_rep}>.Create")
+                                    format!(
+                                        "{}.Create",
+                                        instance_name(
+                                            Generic::None,
+                                            &lang_rep,
+                                            options.output.generics_free
+                                        )
+                                    )
                                 }
                             }
                         };
@@ -568,29 +1337,37 @@ impl ClassCodeGenerator {
                             .collect::<Vec<String>>())
                     }
                     _ => Ok(vec![match &v.data_type {
+                        DataType::EnumSet(_) => format!("{variable_name} := [];"),
                         DataType::Uri if v.required => {
                             format!("{variable_name} := TURI.Create('');")
                         }
-                        DataType::Uri => format!("{variable_name} := TNone<TURI>.Create;"),
+                        DataType::Uri => format!(
+                            "{variable_name} := {}.Create;",
+                            instance_name(Generic::None, "TURI", options.output.generics_free)
+                        ),
                         DataType::InlineList(_) => Self::get_variable_initialization_code(
                             &variable_name,
                             &Helper::get_datatype_language_representation(
                                 &v.data_type,
-                                &options.type_prefix,
+                                &options.naming.type_prefix,
+                                options.output.generics_free,
                             ),
                             true,
                             false,
                             &v.default_value,
+                            options.output.generics_free,
                         ),
                         _ => Self::get_variable_initialization_code(
                             &variable_name,
                             &Helper::get_datatype_language_representation(
                                 &v.data_type,
-                                &options.type_prefix,
+                                &options.naming.type_prefix,
+                                options.output.generics_free,
                             ),
                             v.required,
                             true,
-                            &v.default_value,
+                            &default_value,
+                            options.output.generics_free,
                         ),
                     }]),
                 }
@@ -606,13 +1383,14 @@ impl ClassCodeGenerator {
 
     fn build_deserialize_element_variables<'a>(
         class_type: &'a ClassType,
-        type_aliases: &'a [TypeAlias],
+        hierarchy: &[ClassType],
+        type_aliases: &ResolvedTypeAliases<'a>,
         options: &'a CodeGenOptions,
     ) -> Vec<ElementDeserializeVariable<'a>> {
         class_type
             .variables
             .iter()
-            .filter(|v| !v.is_const && v.source == XMLSource::Element)
+            .filter(|v| !v.is_const && v.source != XMLSource::Attribute)
             .filter_map(|v| {
                 let variable_name = Helper::as_variable_name(&v.name);
 
@@ -635,7 +1413,7 @@ impl ClassCodeGenerator {
                                 DataType::Enumeration(name) | DataType::Union(name) => {
                                     format!(
                                         "{}Helper.FromXmlValue(vPart)",
-                                        Helper::as_type_name(name, &options.type_prefix)
+                                        Helper::as_type_name(name, &options.naming.type_prefix)
                                     )
                                 }
                                 DataType::Custom(_)
@@ -648,9 +1426,18 @@ impl ClassCodeGenerator {
                                     None,
                                 ),
                             },
+                            DataType::InlineEnumSet(_) => format!(
+                                "{}.FromXmlValue(node.ChildNodes[{}].Text)",
+                                Helper::get_datatype_language_representation(
+                                    &data_type,
+                                    &options.naming.type_prefix,
+                                    options.output.generics_free,
+                                ),
+                                as_pascal_literal(&v.xml_name),
+                            ),
                             _ => Self::generate_standard_type_from_xml(
                                 &data_type,
-                                format!("node.ChildNodes['{}'].Text", v.xml_name),
+                                format!("node.ChildNodes[{}].Text", as_pascal_literal(&v.xml_name)),
                                 pattern,
                             ),
                         };
@@ -660,25 +1447,33 @@ impl ClassCodeGenerator {
                             xml_name: &v.xml_name,
                             has_optional_wrapper: v.needs_optional_wrapper(type_aliases),
                             is_required: v.required,
+                            is_set: false,
                             is_list: false,
+                            is_array: false,
                             is_inline_list: matches!(data_type, DataType::InlineList(_)),
+                            is_group: false,
                             is_fixed_size_list: false,
                             fixed_size_list_size: None,
                             data_type_repr: Helper::get_datatype_language_representation(
                                 &data_type,
-                                &options.type_prefix,
+                                &options.naming.type_prefix,
+                                options.output.generics_free,
                             ),
                             from_xml_code,
                         })
                     }
-                    DataType::Custom(name) => {
-                        let type_name = Helper::as_type_name(name, &options.type_prefix);
+                    DataType::Custom(name) | DataType::Record(name) => {
+                        let type_name = Helper::as_type_name(name, &options.naming.type_prefix);
+
+                        let from_xml = Self::from_xml_function(name, hierarchy);
 
                         let from_xml_code = match v.required {
-                            true => {
-                                format!("{}.FromXml(node.ChildNodes['{}'])", type_name, v.xml_name,)
-                            }
-                            false => format!("{type_name}.FromXml(vOptionalNode)"),
+                            true => format!(
+                                "{}.{from_xml}(node.ChildNodes[{}])",
+                                type_name,
+                                as_pascal_literal(&v.xml_name),
+                            ),
+                            false => format!("{type_name}.{from_xml}(vOptionalNode)"),
                         };
 
                         Some(ElementDeserializeVariable {
@@ -686,8 +1481,11 @@ impl ClassCodeGenerator {
                             xml_name: &v.xml_name,
                             has_optional_wrapper: v.needs_optional_wrapper(type_aliases),
                             is_required: v.required,
+                            is_set: false,
                             is_list: false,
+                            is_array: false,
                             is_inline_list: false,
+                            is_group: false,
                             is_fixed_size_list: false,
                             fixed_size_list_size: None,
                             data_type_repr: type_name,
@@ -695,12 +1493,13 @@ impl ClassCodeGenerator {
                         })
                     }
                     DataType::Enumeration(name) => {
-                        let type_name = Helper::as_type_name(name, &options.type_prefix);
+                        let type_name = Helper::as_type_name(name, &options.naming.type_prefix);
 
                         let from_xml_code = match v.required {
                             true => format!(
-                                "{}.FromXmlValue(node.ChildNodes['{}'].Text)",
-                                type_name, v.xml_name,
+                                "{}.FromXmlValue(node.ChildNodes[{}].Text)",
+                                type_name,
+                                as_pascal_literal(&v.xml_name),
                             ),
                             false => format!("{type_name}.FromXmlValue(vOptionalNode.Text)"),
                         };
@@ -710,8 +1509,41 @@ impl ClassCodeGenerator {
                             xml_name: &v.xml_name,
                             has_optional_wrapper: v.needs_optional_wrapper(type_aliases),
                             is_required: v.required,
+                            is_set: false,
                             is_list: false,
+                            is_array: false,
                             is_inline_list: false,
+                            is_group: false,
+                            is_fixed_size_list: false,
+                            fixed_size_list_size: None,
+                            data_type_repr: type_name,
+                            from_xml_code,
+                        })
+                    }
+                    DataType::Union(name) => {
+                        let type_name = Helper::as_type_name(name, &options.naming.type_prefix);
+
+                        let from_xml_code = match v.required {
+                            true => {
+                                format!(
+                                    "{}.FromXml(node.ChildNodes[{}])",
+                                    type_name,
+                                    as_pascal_literal(&v.xml_name),
+                                )
+                            }
+                            false => format!("{type_name}.FromXml(vOptionalNode)"),
+                        };
+
+                        Some(ElementDeserializeVariable {
+                            name: variable_name,
+                            xml_name: &v.xml_name,
+                            has_optional_wrapper: v.needs_optional_wrapper(type_aliases),
+                            is_required: v.required,
+                            is_set: false,
+                            is_list: false,
+                            is_array: false,
+                            is_inline_list: false,
+                            is_group: false,
                             is_fixed_size_list: false,
                             fixed_size_list_size: None,
                             data_type_repr: type_name,
@@ -730,14 +1562,15 @@ impl ClassCodeGenerator {
                                     pattern,
                                 )
                             }
-                            DataType::Custom(name) => format!(
-                                "{}.FromXml(__{}Node);",
-                                Helper::as_type_name(name, &options.type_prefix),
+                            DataType::Custom(name) | DataType::Record(name) => format!(
+                                "{}.{}(__{}Node);",
+                                Helper::as_type_name(name, &options.naming.type_prefix),
+                                Self::from_xml_function(name, hierarchy),
                                 variable_name
                             ),
                             DataType::Enumeration(name) => format!(
                                 "{}.FromXmlValue(__{}Node.Text);",
-                                Helper::as_type_name(name, &options.type_prefix),
+                                Helper::as_type_name(name, &options.naming.type_prefix),
                                 variable_name
                             ),
                             _ => Self::generate_standard_type_from_xml(
@@ -752,13 +1585,17 @@ impl ClassCodeGenerator {
                             xml_name: &v.xml_name,
                             has_optional_wrapper: v.needs_optional_wrapper(type_aliases),
                             is_required: v.required,
+                            is_set: false,
                             is_list: false,
+                            is_array: false,
                             is_inline_list: false,
+                            is_group: false,
                             is_fixed_size_list: true,
                             fixed_size_list_size: Some(*size),
                             data_type_repr: Helper::get_datatype_language_representation(
                                 item_type,
-                                &options.type_prefix,
+                                &options.naming.type_prefix,
+                                options.output.generics_free,
                             ),
                             from_xml_code,
                         })
@@ -775,14 +1612,24 @@ impl ClassCodeGenerator {
                                     pattern,
                                 )
                             }
-                            DataType::Custom(name) => format!(
-                                "{}.FromXml(__{}Node)",
-                                Helper::as_type_name(name, &options.type_prefix),
+                            DataType::Custom(name) | DataType::Record(name)
+                                if v.source == XMLSource::Group =>
+                            {
+                                format!(
+                                    "{}.FromXml(__{}Root.ChildNodes[I])",
+                                    Helper::as_type_name(name, &options.naming.type_prefix),
+                                    variable_name
+                                )
+                            }
+                            DataType::Custom(name) | DataType::Record(name) => format!(
+                                "{}.{}(__{}Node)",
+                                Helper::as_type_name(name, &options.naming.type_prefix),
+                                Self::from_xml_function(name, hierarchy),
                                 variable_name
                             ),
                             DataType::Enumeration(name) => format!(
                                 "{}.FromXmlValue(__{}Node.Text)",
-                                Helper::as_type_name(name, &options.type_prefix),
+                                Helper::as_type_name(name, &options.naming.type_prefix),
                                 variable_name
                             ),
                             _ => Self::generate_standard_type_from_xml(
@@ -797,13 +1644,18 @@ impl ClassCodeGenerator {
                             xml_name: &v.xml_name,
                             has_optional_wrapper: v.needs_optional_wrapper(type_aliases),
                             is_required: v.required,
+                            is_set: false,
                             is_list: true,
+                            is_array: options.output.list_type == ListType::Array,
                             is_inline_list: false,
+                            is_group: v.source == XMLSource::Group,
                             is_fixed_size_list: false,
                             fixed_size_list_size: None,
-                            data_type_repr: Helper::get_datatype_language_representation(
+                            data_type_repr: Helper::get_variable_type_representation(
                                 &v.data_type,
-                                &options.type_prefix,
+                                Helper::variable_list_type(v, options.output.list_type),
+                                &options.naming.type_prefix,
+                                options.output.generics_free,
                             ),
                             from_xml_code,
                         })
@@ -822,7 +1674,7 @@ impl ClassCodeGenerator {
                             }
                             DataType::Enumeration(name) | DataType::Union(name) => format!(
                                 "{}Helper.FromXmlValue(vPart)",
-                                Helper::as_type_name(name, &options.type_prefix)
+                                Helper::as_type_name(name, &options.naming.type_prefix)
                             ),
                             DataType::Custom(_)
                             | DataType::List(_)
@@ -840,13 +1692,44 @@ impl ClassCodeGenerator {
                             xml_name: &v.xml_name,
                             has_optional_wrapper: v.needs_optional_wrapper(type_aliases),
                             is_required: v.required,
+                            is_set: false,
                             is_list: false,
+                            is_array: false,
                             is_inline_list: true,
+                            is_group: false,
+                            is_fixed_size_list: false,
+                            fixed_size_list_size: None,
+                            data_type_repr: Helper::get_datatype_language_representation(
+                                &v.data_type,
+                                &options.naming.type_prefix,
+                                options.output.generics_free,
+                            ),
+                            from_xml_code,
+                        })
+                    }
+                    DataType::EnumSet(name) => {
+                        let from_xml_code = format!(
+                            "{}.FromXmlValue(__{}Node.Text)",
+                            Helper::as_type_name(name, &options.naming.type_prefix),
+                            variable_name
+                        );
+
+                        Some(ElementDeserializeVariable {
+                            name: variable_name,
+                            xml_name: &v.xml_name,
+                            has_optional_wrapper: false,
+                            is_required: v.required,
+                            is_set: true,
+                            is_list: true,
+                            is_array: false,
+                            is_inline_list: false,
+                            is_group: false,
                             is_fixed_size_list: false,
                             fixed_size_list_size: None,
                             data_type_repr: Helper::get_datatype_language_representation(
                                 &v.data_type,
-                                &options.type_prefix,
+                                &options.naming.type_prefix,
+                                options.output.generics_free,
                             ),
                             from_xml_code,
                         })
@@ -856,18 +1739,22 @@ impl ClassCodeGenerator {
                         xml_name: &v.xml_name,
                         has_optional_wrapper: v.needs_optional_wrapper(type_aliases),
                         is_required: v.required,
+                        is_set: false,
                         is_list: false,
+                        is_array: false,
                         is_inline_list: false,
+                        is_group: false,
                         is_fixed_size_list: false,
                         fixed_size_list_size: None,
                         data_type_repr: Helper::get_datatype_language_representation(
                             &v.data_type,
-                            &options.type_prefix,
+                            &options.naming.type_prefix,
+                            options.output.generics_free,
                         ),
                         from_xml_code: match v.required {
                             true => Self::generate_standard_type_from_xml(
                                 &v.data_type,
-                                format!("node.ChildNodes['{}'].Text", v.xml_name),
+                                format!("node.ChildNodes[{}].Text", as_pascal_literal(&v.xml_name)),
                                 None,
                             ),
                             false => Self::generate_standard_type_from_xml(
@@ -884,9 +1771,12 @@ impl ClassCodeGenerator {
 
     fn build_deserialize_attribute_variables<'a>(
         class_type: &'a ClassType,
-        type_aliases: &'a [TypeAlias],
+        type_aliases: &ResolvedTypeAliases<'a>,
         options: &'a CodeGenOptions,
+        is_record: bool,
     ) -> Vec<AttributeDeserializeVariable<'a>> {
+        let is_lenient = options.serialization.lenient_from_xml && !is_record;
+
         class_type
             .variables
             .iter()
@@ -896,37 +1786,76 @@ impl ClassCodeGenerator {
                     DataType::Alias(name) => Helper::get_alias_data_type(name, type_aliases)?,
                     _ => (v.data_type.clone(), None),
                 };
+                let warn_if_missing = is_lenient && v.required && v.default_value.is_none();
+                // Type of the field, so the optional value is of the same type
+                let data_type_repr = Helper::get_datatype_language_representation(
+                    &v.data_type,
+                    &options.naming.type_prefix,
+                    options.output.generics_free,
+                );
 
                 Some(AttributeDeserializeVariable {
                     name: Helper::as_variable_name(&v.name),
                     xml_name: &v.xml_name,
                     has_optional_wrapper: v.needs_optional_wrapper(type_aliases),
-                    from_xml_code_available: Self::generate_standard_type_from_xml(
-                        &data_type,
-                        format!("node.Attributes['{}']", v.xml_name),
-                        pattern,
-                    ),
-                    from_xml_code_missing: match (v.required, &v.default_value) {
-                        (false, None) => {
-                            let lang_rep = Helper::get_datatype_language_representation(
+                    from_xml_code_available: match &data_type {
+                        DataType::InlineEnumSet(_) | DataType::Enumeration(_) => format!(
+                            "{}.FromXmlValue({})",
+                            Helper::get_datatype_language_representation(
                                 &data_type,
-                                &options.type_prefix,
-                            );
-
-                            format!("TNone<{lang_rep}>.Create")
-                        }
-                        (true, None) => {
-                            format!(
-                                "raise Exception.Create('Required attribute \"{}\" is missing');",
-                                v.xml_name
+                                &options.naming.type_prefix,
+                                options.output.generics_free,
+                            ),
+                            Self::attribute_value(v),
+                        ),
+                        _ => Self::generate_standard_type_from_xml(
+                            &data_type,
+                            Self::attribute_value(v),
+                            pattern,
+                        ),
+                    },
+                    from_xml_code_missing: match (v.required, &v.default_value) {
+                        (false, None) => Some(format!(
+                            "{}.Create",
+                            instance_name(
+                                Generic::None,
+                                &data_type_repr,
+                                options.output.generics_free
                             )
+                        )),
+                        (true, None) if warn_if_missing => {
+                            Some(format!("Default({data_type_repr})"))
                         }
-                        (_, Some(default_value)) => default_value.clone(),
+                        (true, None) => None,
+                        (_, Some(default_value)) => Some(match &data_type {
+                            DataType::Enumeration(name) => format!(
+                                "{}.FromXmlValue({})",
+                                Helper::as_type_name(name, &options.naming.type_prefix),
+                                as_pascal_literal(default_value),
+                            ),
+                            _ => Helper::as_default_value(default_value, &data_type, type_aliases),
+                        }),
                     },
+                    data_type_repr,
+                    warn_if_missing,
+                    namespace: v.namespace.as_ref(),
                 })
             })
             .collect::<Vec<AttributeDeserializeVariable>>()
     }
+
+    /// Value of the attribute of `node`, attributes with qualified form are read namespace-aware
+    fn attribute_value(variable: &Variable) -> String {
+        let xml_name = as_pascal_literal(&variable.xml_name);
+
+        match &variable.namespace {
+            Some(namespace) => format!(
+                "node.GetAttributeNS({xml_name}, {})",
+                as_pascal_literal(&namespace.uri)
+            ),
+            None => format!("node.Attributes[{xml_name}]"),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -1,15 +1,30 @@
-use std::io::{BufWriter, Write};
+use std::{
+    collections::{HashMap, HashSet},
+    io::{BufWriter, Write},
+    sync::{Arc, OnceLock, PoisonError, RwLock},
+};
 use tera::{Context, Tera};
 
 use crate::generator::{
-    code_generator_trait::{CodeGenError, CodeGenOptions, CodeGenerator},
-    internal_representation::InternalRepresentation,
-    types::{BinaryEncoding, DataType},
+    code_generator_trait::{
+        CodeGenError, CodeGenOptions, CodeGenerator, DateTimeHandling, SchemaMetadataOutput,
+    },
+    internal_representation::{InternalRepresentation, ValueType},
+    types::{BinaryEncoding, ClassType, DataType, ResolvedTypeAliases},
 };
 
 use super::{
-    alias_code_gen::TypeAliasCodeGenerator, class_code_gen::ClassCodeGenerator,
-    code_writer::CodeWriter, enum_code_gen::EnumCodeGenerator,
+    alias_code_gen::TypeAliasCodeGenerator,
+    class_code_gen::ClassCodeGenerator,
+    code_writer::CodeWriter,
+    enum_code_gen::EnumCodeGenerator,
+    generics_free::{instance_name, Generic, GenericInstances},
+    helper::Helper,
+    template_models::{
+        ExternalClass, GenericInstance, MetadataConstant, ModelsUnit,
+        TypeAlias as TemplateTypeAlias, UnionType as TemplateUnionType,
+        ValueType as TemplateValueType,
+    },
     union_type_code_gen::UnionTypeCodeGenerator,
 };
 
@@ -19,7 +34,7 @@ use super::{
 ///
 /// # Example
 ///
-/// ```rust
+/// ```ignore
 /// use std::io::BufWriter;
 ///
 /// use xsd_codegen::generator::{
@@ -28,10 +43,7 @@ use super::{
 ///     delphi::DelphiCodeGenerator,
 /// };
 ///
-/// let options = CodeGenOptions {
-///     unit_name: "TestUnit".to_string(),
-///     ..CodeGenOptions::default()
-/// };
+/// let options = CodeGenOptions::builder("TestUnit").build();
 ///
 /// let internal_representation = InternalRepresentation::default();
 ///
@@ -48,106 +60,438 @@ pub struct DelphiCodeGenerator<T: Write> {
     writer: CodeWriter<T>,
     options: CodeGenOptions,
     internal_representation: InternalRepresentation,
+    /// Classes, which are generated as records
+    records: Vec<ClassType>,
     documentations: Vec<String>,
     generate_date_time_helper: bool,
+    /// xs:dateTime is generated as `TXmlDateTime`
+    generate_date_time_offset: bool,
     generate_hex_binary_helper: bool,
     needs_net_encoding_unit_use_clause: bool,
+    needs_fmt_bcd_unit_use_clause: bool,
+    /// Enumerations, which are used as set
+    enum_sets: Vec<String>,
 }
 
-impl<T: Write> DelphiCodeGenerator<T> {
-    #[inline]
-    fn setup_tera(&self) -> Result<Tera, CodeGenError> {
-        let macros_template_str = include_str!("templates/macros.pas");
-        let template_str = include_str!("templates/models.pas");
-
-        let mut tera = Tera::default();
-        if let Err(e) = tera.add_raw_templates(vec![
-            ("macros.pas", macros_template_str),
-            ("models.pas", template_str),
-        ]) {
-            eprintln!("Failed to load templates due to {:?}", e);
-
-            return Err(CodeGenError::TemplateEngineError(format!(
-                "Failed to load templates due to {:?}",
-                e
-            )));
+/// Built-in templates, which are compiled once per process
+static BUILTIN_TEMPLATES: OnceLock<Arc<Tera>> = OnceLock::new();
+
+/// Built-in templates with the overrides of `override_templates`
+static OVERRIDDEN_TEMPLATES: RwLock<Option<Arc<Tera>>> = RwLock::new(None);
+
+/// Templates of the unit, the overridden ones if `override_templates` was called.
+///
+/// The templates are compiled by the first call and shared by all later generations,
+/// e.g. of the server mode, which generates repeatedly.
+fn load_templates() -> Result<Arc<Tera>, CodeGenError> {
+    if let Some(tera) = OVERRIDDEN_TEMPLATES
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .as_ref()
+    {
+        return Ok(Arc::clone(tera));
+    }
+
+    builtin_templates()
+}
+
+fn builtin_templates() -> Result<Arc<Tera>, CodeGenError> {
+    if let Some(tera) = BUILTIN_TEMPLATES.get() {
+        return Ok(Arc::clone(tera));
+    }
+
+    let mut tera = Tera::default();
+    tera.add_raw_templates(vec![
+        ("macros.pas", include_str!("templates/macros.pas")),
+        ("models.pas", include_str!("templates/models.pas")),
+    ])
+    .map_err(template_error)?;
+    tera.register_filter("pascal_literal", pascal::pascal_literal);
+    tera.register_filter("generic_instance", generic_instance);
+
+    // Concurrent first calls compile the templates twice, but share the first result
+    Ok(Arc::clone(BUILTIN_TEMPLATES.get_or_init(|| Arc::new(tera))))
+}
+
+/// Replaces the built-in templates `models.pas` and `macros.pas` for all following generations
+/// of the process. A template, which is not given, keeps its built-in version.
+/// An empty list restores the built-in templates.
+pub fn override_templates(templates: &[(&str, &str)]) -> Result<(), CodeGenError> {
+    let overridden = match templates.is_empty() {
+        true => None,
+        false => {
+            let mut tera = Tera::clone(builtin_templates()?.as_ref());
+            tera.add_raw_templates(templates.to_vec())
+                .map_err(template_error)?;
+
+            Some(Arc::new(tera))
         }
+    };
+
+    *OVERRIDDEN_TEMPLATES
+        .write()
+        .unwrap_or_else(PoisonError::into_inner) = overridden;
+
+    Ok(())
+}
 
-        Ok(tera)
+fn template_error(e: tera::Error) -> CodeGenError {
+    CodeGenError::TemplateEngineError(format!("Failed to load templates due to {:?}", e))
+}
+
+/// Filter writing the instance of the generic type `generic` for the item type,
+/// see [instance_name]
+fn generic_instance(
+    value: &tera::Value,
+    args: &HashMap<String, tera::Value>,
+) -> tera::Result<tera::Value> {
+    let generic = args
+        .get("generic")
+        .and_then(tera::Value::as_str)
+        .and_then(Generic::from_name)
+        .ok_or_else(|| tera::Error::msg("generic_instance requires a known generic type"))?;
+    let generics_free = args
+        .get("generics_free")
+        .and_then(tera::Value::as_bool)
+        .unwrap_or_default();
+
+    match value.as_str() {
+        Some(item_type) => Ok(tera::Value::from(instance_name(
+            generic,
+            item_type,
+            generics_free,
+        ))),
+        None => Err(tera::Error::msg("generic_instance requires a string")),
     }
+}
 
+impl<T: Write> DelphiCodeGenerator<T> {
     #[inline]
-    fn build_tera_context(&self) -> Result<Context, CodeGenError> {
-        let mut models_context = Context::new();
-        models_context.insert("unitName", &self.options.unit_name);
-        models_context.insert("crate_version", env!("CARGO_PKG_VERSION"));
-        models_context.insert("gen_from_xml", &self.options.generate_from_xml);
-        models_context.insert("gen_to_xml", &self.options.generate_to_xml);
-        models_context.insert("gen_datetime_helper", &self.generate_date_time_helper);
-        models_context.insert("gen_hex_binary_helper", &self.generate_hex_binary_helper);
-        models_context.insert(
-            "needs_net_encoding_unit_use_clause",
-            &self.needs_net_encoding_unit_use_clause,
-        );
-
-        // Add calculated fields
-        let gen_bool_consts = self.internal_representation.classes.iter().any(|c| {
-            c.variables
-                .iter()
-                .any(|v| matches!(v.data_type, DataType::Boolean))
-        });
-        models_context.insert("gen_bool_consts", &gen_bool_consts);
+    fn build_models_unit(&self) -> Result<ModelsUnit<'_>, CodeGenError> {
+        let resolved_aliases = self.internal_representation.resolved_type_aliases();
+        let (union_classes, union_types): (Vec<_>, Vec<_>) =
+            UnionTypeCodeGenerator::build_template_models(
+                &self.internal_representation.union_types,
+                &resolved_aliases,
+                &self.internal_representation.enumerations,
+                &self.options,
+            )
+            .into_iter()
+            .partition(|u| u.is_class_hierarchy);
 
-        models_context.insert(
-            "documentations",
-            &self
-                .documentations
-                .iter()
-                .flat_map(|s| s.lines())
-                .collect::<Vec<&str>>(),
-        );
-        models_context.insert(
-            "document",
-            &ClassCodeGenerator::build_class_template_model(
+        let external_classes = &self.internal_representation.external_classes;
+        let mut external_units = external_classes
+            .iter()
+            .map(|e| e.unit.as_str())
+            .collect::<Vec<_>>();
+        external_units.sort_unstable();
+        external_units.dedup();
+
+        let classes = ClassCodeGenerator::build_template_models(
+            &self.internal_representation.classes,
+            &self.internal_representation.classes,
+            &self.internal_representation.document,
+            &resolved_aliases,
+            &self.options,
+            false,
+        )?;
+
+        let type_aliases =
+            TypeAliasCodeGenerator::build_template_models(&resolved_aliases, &self.options);
+
+        let value_types = self.build_value_types(&type_aliases, &union_types);
+        let xml_formatting = &self.options.serialization.xml_formatting;
+
+        let mut models_unit = ModelsUnit {
+            unit_name: &self.options.output.unit_name,
+            header: self
+                .options
+                .output
+                .header
+                .as_ref()
+                .map(|h| h.replace("{unit}", &self.options.output.unit_name)),
+            crate_version: env!("CARGO_PKG_VERSION"),
+            documentations: Helper::documentation_lines(&self.documentations),
+            gen_from_xml: self.options.serialization.generate_from_xml,
+            gen_to_xml: self.options.serialization.generate_to_xml,
+            gen_datetime_helper: self.generate_date_time_helper,
+            gen_date_time_offset: self.generate_date_time_offset,
+            date_time_is_utc: self.options.serialization.date_time_handling
+                != DateTimeHandling::Local,
+            gen_hex_binary_helper: self.generate_hex_binary_helper,
+            gen_bool_consts: self.all_classes().any(|c| {
+                c.variables
+                    .iter()
+                    .any(|v| matches!(v.data_type, DataType::Boolean))
+            }),
+            needs_net_encoding_unit_use_clause: self.needs_net_encoding_unit_use_clause,
+            needs_fmt_bcd_unit_use_clause: self.needs_fmt_bcd_unit_use_clause,
+            gen_preserved_nodes: self.options.serialization.preserve_document_nodes,
+            xml_indentation: xml_formatting.indentation.map(|i| " ".repeat(i)),
+            xml_declaration: xml_formatting.include_declaration,
+            // The standalone attribute is part of the declaration
+            xml_standalone: xml_formatting
+                .standalone
+                .filter(|_| xml_formatting.include_declaration)
+                .map(|s| if s { "yes" } else { "no" }),
+            // The values are formatted like their xml representation
+            gen_debug_dump: self.options.output.generate_debug_dump
+                && self.options.serialization.generate_to_xml,
+            gen_serializer_attributes: self.options.serialization.generate_serializer_attributes,
+            gen_constraint_checks: classes.iter().any(|c| !c.constraint_checks.is_empty()),
+            gen_metadata_registry: self.options.output.schema_metadata
+                == SchemaMetadataOutput::Registry,
+            metadata_constants: self.build_metadata_constants(),
+            gen_pattern_validation: type_aliases.iter().any(|a| a.validator.is_some()),
+            include_file: self
+                .options
+                .output
+                .generate_include_file
+                .then(|| include_file_name(&self.options)),
+            generics_free: self.options.output.generics_free,
+            generic_instances: vec![],
+            document: ClassCodeGenerator::build_class_template_model(
                 &self.internal_representation.document,
-                &self.internal_representation.types_aliases,
+                &self.internal_representation.classes,
+                &self.internal_representation.document,
+                &resolved_aliases,
                 &self.options,
+                false,
             )?,
-        );
-        models_context.insert(
-            "classes",
-            &ClassCodeGenerator::build_template_models(
+            records: ClassCodeGenerator::build_template_models(
+                &self.records,
                 &self.internal_representation.classes,
-                &self.internal_representation.types_aliases,
+                &self.internal_representation.document,
+                &resolved_aliases,
                 &self.options,
+                true,
             )?,
-        );
-        models_context.insert(
-            "enumerations",
-            &EnumCodeGenerator::build_template_models(
-                &self.internal_representation.enumerations,
-                &self.options,
-            ),
-        );
-        models_context.insert(
-            "type_aliases",
-            &TypeAliasCodeGenerator::build_template_models(
-                &self.internal_representation.types_aliases,
-                &self.options,
-            ),
-        );
-        models_context.insert(
-            "union_types",
-            &UnionTypeCodeGenerator::build_template_models(
-                &self.internal_representation.union_types,
-                &self.internal_representation.types_aliases,
+            classes,
+            enumerations: EnumCodeGenerator::build_template_models(
                 &self.internal_representation.enumerations,
+                &self.enum_sets,
                 &self.options,
             ),
-        );
+            type_aliases,
+            union_types,
+            union_classes,
+            value_types,
+            external_classes: external_classes
+                .iter()
+                .map(|e| ExternalClass {
+                    name: Helper::as_type_name(&e.name, &self.options.naming.type_prefix),
+                    qualified_name: &e.qualified_name,
+                    delphi_type: &e.delphi_type,
+                    unit: &e.unit,
+                })
+                .collect(),
+            external_units,
+        };
 
-        Ok(models_context)
+        if self.options.output.generics_free {
+            models_unit.generic_instances =
+                self.build_generic_instances(&models_unit, &resolved_aliases);
+        }
+
+        Ok(models_unit)
+    }
+
+    fn render(tera: &Tera, models_unit: &ModelsUnit) -> Result<String, CodeGenError> {
+        let context = Context::from_serialize(models_unit).map_err(|e| {
+            CodeGenError::TemplateEngineError(format!(
+                "Failed to build template context due to {:?}",
+                e
+            ))
+        })?;
+
+        tera.render("models.pas", &context).map_err(|e| {
+            CodeGenError::TemplateEngineError(format!(
+                "Failed to render model template due to {:?}",
+                e
+            ))
+        })
+    }
+
+    /// Concrete types of the generic instances used by a unit without generics: the lists of
+    /// the templates, the types of the fields with their optional wrappers, the aliases and
+    /// the variants of the union types.
+    fn build_generic_instances(
+        &self,
+        models_unit: &ModelsUnit,
+        type_aliases: &ResolvedTypeAliases,
+    ) -> Vec<GenericInstance> {
+        let object_types = std::iter::once(&models_unit.document)
+            .chain(&models_unit.classes)
+            .map(|c| c.name.clone())
+            .chain(models_unit.union_classes.iter().map(|u| u.name.clone()))
+            .chain(models_unit.external_classes.iter().map(|e| e.name.clone()))
+            .chain(std::iter::once(String::from("TObject")))
+            .collect::<HashSet<_>>();
+        let late_types = models_unit
+            .type_aliases
+            .iter()
+            .map(|a| a.name.clone())
+            .chain(models_unit.records.iter().map(|r| r.name.clone()))
+            .chain(models_unit.union_types.iter().map(|u| u.name.clone()))
+            .collect::<HashSet<_>>();
+        let mut instances = GenericInstances::new(&self.options, object_types, late_types);
+        let classes = || std::iter::once(&models_unit.document).chain(&models_unit.classes);
+
+        if models_unit.gen_preserved_nodes {
+            instances.add(Generic::List, "TPreservedXmlNode");
+        }
+
+        if models_unit.gen_debug_dump {
+            instances.add(Generic::List, "TObject");
+        }
+
+        if models_unit.gen_constraint_checks
+            || classes().any(|c| {
+                c.declares_warnings || c.declares_unknown_nodes || c.any_elements.is_some()
+            })
+        {
+            instances.add(Generic::List, "String");
+        }
+
+        if models_unit.gen_constraint_checks
+            || models_unit.gen_metadata_registry
+            || classes().any(|c| !c.assertion_checks.is_empty())
+        {
+            instances.add(Generic::Array, "String");
+        }
+
+        let internal_representation = &self.internal_representation;
+
+        for variable in std::iter::once(&internal_representation.document)
+            .chain(self.all_classes())
+            .flat_map(|c| &c.variables)
+        {
+            instances.add_variable(variable, type_aliases);
+        }
+
+        for alias in &internal_representation.types_aliases {
+            instances.add_data_type(&alias.for_type);
+        }
+
+        for variant in internal_representation
+            .union_types
+            .iter()
+            .flat_map(|u| &u.variants)
+        {
+            instances.add_data_type(&variant.data_type);
+        }
+
+        instances.into_instances()
     }
+
+    /// Template models of the aliases and union types in the order of their declarations,
+    /// see `InternalRepresentation::value_types`
+    fn build_value_types<'a>(
+        &self,
+        type_aliases: &[TemplateTypeAlias<'a>],
+        union_types: &[TemplateUnionType<'a>],
+    ) -> Vec<TemplateValueType<'a>> {
+        let type_aliases = type_aliases
+            .iter()
+            .map(|a| (a.qualified_name.as_str(), a))
+            .collect::<HashMap<_, _>>();
+        let union_types = union_types
+            .iter()
+            .map(|u| (u.qualified_name.as_str(), u))
+            .collect::<HashMap<_, _>>();
+
+        self.internal_representation
+            .value_types()
+            .into_iter()
+            .filter_map(|v| match v {
+                ValueType::Alias(a) => type_aliases
+                    .get(a.qualified_name.as_str())
+                    .map(|a| TemplateValueType::Alias((*a).clone())),
+                ValueType::Union(u) => union_types
+                    .get(u.qualified_name.as_str())
+                    .map(|u| TemplateValueType::Union((*u).clone())),
+            })
+            .collect()
+    }
+
+    /// Constants of the xs:appinfo of the types and of the public and system identifiers
+    /// of the notations, empty if the metadata isn't generated
+    fn build_metadata_constants(&self) -> Vec<MetadataConstant> {
+        if self.options.output.schema_metadata == SchemaMetadataOutput::None {
+            return vec![];
+        }
+
+        let metadata = &self.internal_representation.metadata;
+        let app_infos = metadata.app_infos.iter().map(|a| {
+            let type_name = Helper::as_type_name(&a.name, &self.options.naming.type_prefix);
+
+            MetadataConstant {
+                name: format!("cn{}AppInfo", &type_name[1..]),
+                comment: format!("xs:appinfo of {}", a.qualified_name),
+                value: Helper::as_string_literal(&a.content),
+                type_name: Some(type_name),
+            }
+        });
+        let notations = metadata.notations.iter().flat_map(|n| {
+            let name = Helper::as_variable_name(&n.name).replace(['-', '.'], "_");
+
+            [("Public", &n.public), ("System", &n.system)]
+                .into_iter()
+                .filter_map(move |(kind, identifier)| {
+                    identifier.as_ref().map(|i| MetadataConstant {
+                        name: format!("cnNotation{name}{kind}"),
+                        type_name: None,
+                        comment: format!("{} identifier of the xs:notation {}", kind, n.name),
+                        value: Helper::as_string_literal(i),
+                    })
+                })
+        });
+
+        app_infos.chain(notations).collect()
+    }
+
+    fn all_classes(&self) -> impl Iterator<Item = &ClassType> {
+        self.internal_representation
+            .classes
+            .iter()
+            .chain(self.records.iter())
+    }
+}
+
+/// Name of the include file with the manual code of the unit
+pub(crate) fn include_file_name(options: &CodeGenOptions) -> String {
+    format!("{}.inc", options.output.unit_name)
+}
+
+/// Initial content of the include file with the manual code of the unit. The code of a class
+/// is placed into the block of its symbol, the implementations into `MANUAL_IMPLEMENTATION`.
+pub(crate) fn build_include_file(
+    internal_representation: &InternalRepresentation,
+    options: &CodeGenOptions,
+) -> String {
+    let mut class_names = std::iter::once(&internal_representation.document)
+        .chain(internal_representation.classes.iter())
+        .map(|c| Helper::as_type_name(&c.name, &options.naming.type_prefix))
+        .collect::<Vec<String>>();
+    class_names.sort();
+    class_names.dedup();
+
+    let mut content = format!(
+        "// Manual code of the unit {}, which is never overwritten by the generator.\n\
+         // Declarations of a class go into the block of its symbol, they are inserted\n\
+         // into the public section at the end of the class.\n",
+        options.output.unit_name
+    );
+
+    for name in class_names
+        .iter()
+        .map(String::as_str)
+        .chain(std::iter::once("IMPLEMENTATION"))
+    {
+        content.push_str(&format!("\n{{$IFDEF MANUAL_{name}}}\n{{$ENDIF}}\n"));
+    }
+
+    content
 }
 
 impl<T> CodeGenerator<T> for DelphiCodeGenerator<T>
@@ -157,29 +501,57 @@ where
     fn new(
         buffer: BufWriter<T>,
         options: CodeGenOptions,
-        internal_representation: InternalRepresentation,
+        mut internal_representation: InternalRepresentation,
         documentations: Vec<String>,
     ) -> Self {
+        TypeAliasCodeGenerator::apply_decimal_mapping(
+            &mut internal_representation.types_aliases,
+            options.output.decimal_mapping,
+        );
+        let generate_date_time_offset =
+            ClassCodeGenerator::apply_date_time_offsets(&mut internal_representation, &options);
+        ClassCodeGenerator::apply_uri_strings(&mut internal_representation, &options);
+        let enum_sets = EnumCodeGenerator::apply_enum_sets(&mut internal_representation, &options);
+        let records = ClassCodeGenerator::extract_records(&mut internal_representation, &options);
+        let all_classes = || internal_representation.classes.iter().chain(records.iter());
+
+        // Items of lists are converted like single values
+        let is_date_time = |data_type: &DataType| match data_type {
+            DataType::List(item)
+            | DataType::InlineList(item)
+            | DataType::FixedSizeList(item, _) => {
+                matches!(**item, DataType::DateTime | DataType::Date | DataType::Time)
+            }
+            _ => matches!(
+                data_type,
+                DataType::DateTime | DataType::Date | DataType::Time
+            ),
+        };
+
         Self {
             writer: CodeWriter { buffer },
             options,
             documentations,
-            generate_date_time_helper: internal_representation.classes.iter().any(|c| {
-                c.variables.iter().any(|v| {
-                    matches!(
-                        &v.data_type,
-                        DataType::DateTime | DataType::Date | DataType::Time
-                    )
-                })
-            }) || internal_representation.types_aliases.iter().any(
-                |a| {
-                    matches!(
-                        &a.for_type,
-                        DataType::DateTime | DataType::Date | DataType::Time
-                    )
-                },
-            ),
-            generate_hex_binary_helper: internal_representation.classes.iter().any(|c| {
+            generate_date_time_helper: std::iter::once(&internal_representation.document)
+                .chain(all_classes())
+                .flat_map(|c| &c.variables)
+                .map(|v| &v.data_type)
+                .chain(
+                    internal_representation
+                        .types_aliases
+                        .iter()
+                        .map(|a| &a.for_type),
+                )
+                .chain(
+                    internal_representation
+                        .union_types
+                        .iter()
+                        .flat_map(|u| &u.variants)
+                        .map(|v| &v.data_type),
+                )
+                .any(is_date_time),
+            generate_date_time_offset,
+            generate_hex_binary_helper: all_classes().any(|c| {
                 c.variables
                     .iter()
                     .any(|v| matches!(&v.data_type, DataType::Binary(BinaryEncoding::Hex)))
@@ -187,7 +559,7 @@ where
                 .types_aliases
                 .iter()
                 .any(|a| matches!(&a.for_type, DataType::Binary(BinaryEncoding::Hex))),
-            needs_net_encoding_unit_use_clause: internal_representation.classes.iter().any(|c| {
+            needs_net_encoding_unit_use_clause: all_classes().any(|c| {
                 c.variables
                     .iter()
                     .any(|v| matches!(v.data_type, DataType::Binary(BinaryEncoding::Base64)))
@@ -195,33 +567,1861 @@ where
                 .types_aliases
                 .iter()
                 .any(|a| matches!(a.for_type, DataType::Binary(BinaryEncoding::Base64))),
+            needs_fmt_bcd_unit_use_clause: internal_representation
+                .types_aliases
+                .iter()
+                .any(|a| matches!(a.for_type, DataType::Bcd)),
             internal_representation,
+            records,
+            enum_sets,
         }
     }
 
     fn generate(&mut self) -> Result<(), CodeGenError> {
-        let tera = self.setup_tera()?;
-        let models_context = self.build_tera_context()?;
-
-        match tera.render_to("models.pas", &models_context, &mut self.writer.buffer) {
-            Ok(_) => {}
-            Err(e) => {
-                return Err(CodeGenError::TemplateEngineError(format!(
-                    "Failed to render model template due to {:?}",
-                    e
-                )));
-            }
-        }
+        let tera = load_templates()?;
+        let models_unit = self.build_models_unit()?;
+        let code = Self::render(&tera, &models_unit)?;
 
-        Ok(())
+        self.writer
+            .buffer
+            .write_all(code.as_bytes())
+            .map_err(CodeGenError::IOError)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    // use pretty_assertions::assert_eq;
+    use pretty_assertions::assert_eq;
+
+    use crate::generator::{
+        code_generator_trait::{
+            DateTimeHandling, DecimalMapping, EnumLookup, ListType, NamingOptions, OutputOptions,
+            SchemaMetadataOutput, SerializationOptions, UnknownXmlNodes, XmlFormatting,
+        },
+        internal_representation::DOCUMENT_NAME,
+        types::{
+            AppInfo, AttributeNamespace, ClassType, Enumeration, EnumerationValue, ExternalClass,
+            IdentityConstraint, IdentityConstraintKind, Notation, Occurrence, ProcessContents,
+            SchemaMetadata, TypeAlias, UnionType, UnionVariant, Variable, Wildcard,
+            WildcardNamespaces, XMLSource,
+        },
+    };
+
+    use super::*;
+
+    fn variable(name: &str, source: XMLSource) -> Variable {
+        Variable {
+            name: name.to_owned(),
+            data_type: DataType::String,
+            xml_name: name.to_owned(),
+            requires_free: false,
+            required: true,
+            source,
+            default_value: None,
+            is_const: false,
+            occurrence: None,
+            documentations: vec![],
+            is_documented: false,
+            is_owned: true,
+            namespace: None,
+        }
+    }
+
+    fn class_type(name: &str, super_type: Option<&str>, variables: Vec<Variable>) -> ClassType {
+        ClassType {
+            name: name.to_owned(),
+            qualified_name: name.to_owned(),
+            super_type: super_type.map(|s| (s.to_owned(), s.to_owned())),
+            is_abstract: false,
+            variables,
+            documentations: vec![],
+            is_documented: false,
+            identity_constraints: vec![],
+            assertions: vec![],
+            wildcard: None,
+        }
+    }
+
+    fn generate(classes: Vec<ClassType>) -> String {
+        generate_with_options(
+            classes,
+            CodeGenOptions::builder("uTest")
+                .serialization(SerializationOptions {
+                    generate_from_xml: true,
+                    generate_to_xml: true,
+                    ..SerializationOptions::default()
+                })
+                .build(),
+        )
+    }
+
+    fn generate_with_options(classes: Vec<ClassType>, options: CodeGenOptions) -> String {
+        generate_representation(classes, vec![], options)
+    }
+
+    fn generate_representation(
+        classes: Vec<ClassType>,
+        union_types: Vec<UnionType>,
+        options: CodeGenOptions,
+    ) -> String {
+        let document = class_type(DOCUMENT_NAME, None, vec![]);
+        let internal_representation = InternalRepresentation {
+            document,
+            classes,
+            types_aliases: vec![],
+            enumerations: vec![],
+            union_types,
+            external_classes: vec![],
+            metadata: SchemaMetadata::default(),
+        };
+
+        generate_internal_representation(internal_representation, options)
+    }
+
+    fn generate_internal_representation(
+        internal_representation: InternalRepresentation,
+        options: CodeGenOptions,
+    ) -> String {
+        let mut generator = DelphiCodeGenerator::new(
+            BufWriter::new(Vec::new()),
+            options,
+            internal_representation,
+            vec![],
+        );
+        generator.generate().unwrap();
+
+        String::from_utf8(generator.writer.buffer.into_inner().unwrap()).unwrap()
+    }
+
+    /// Returns the body of the `AppendToXmlRaw` implementation of the given class.
+    fn append_to_xml_body<'a>(code: &'a str, class_name: &str) -> &'a str {
+        let start = code
+            .find(&format!("procedure {class_name}.AppendToXmlRaw"))
+            .unwrap();
+        let end = start + code[start..].find("\nend;").unwrap();
+
+        &code[start..end]
+    }
+
+    /// Returns the body of the `FromXml` constructor of the given class.
+    fn from_xml_body<'a>(code: &'a str, class_name: &str) -> &'a str {
+        let start = code
+            .find(&format!("constructor {class_name}.FromXml"))
+            .unwrap();
+        let end = start + code[start..].find("\nend;").unwrap();
+
+        &code[start..end]
+    }
+
+    fn written_children(body: &str) -> Vec<&str> {
+        body.lines()
+            .filter_map(|l| l.trim().strip_prefix("node := pParent.AddChild('"))
+            .map(|l| l.trim_end_matches("');"))
+            .collect()
+    }
+
+    #[test]
+    fn to_xml_writes_elements_in_sequence_order() {
+        let code = generate(vec![class_type(
+            "Person",
+            None,
+            vec![
+                variable("lastName", XMLSource::Element),
+                variable("id", XMLSource::Attribute),
+                variable("firstName", XMLSource::Element),
+                variable("birthday", XMLSource::Element),
+            ],
+        )]);
+
+        let body = append_to_xml_body(&code, "TPerson");
+
+        assert_eq!(
+            written_children(body),
+            vec!["lastName", "firstName", "birthday"]
+        );
+        assert!(body.contains("pParent.Attributes['id'] := Id;"));
+    }
+
+    #[test]
+    fn from_xml_reads_required_optional_and_default_attributes() {
+        let code = generate(vec![class_type(
+            "Item",
+            None,
+            vec![
+                variable("id", XMLSource::Attribute),
+                Variable {
+                    data_type: DataType::Integer,
+                    required: false,
+                    ..variable("count", XMLSource::Attribute)
+                },
+                Variable {
+                    required: false,
+                    default_value: Some(String::from("none")),
+                    ..variable("label", XMLSource::Attribute)
+                },
+            ],
+        )]);
+
+        let body = from_xml_body(&code, "TItem");
+
+        assert!(body.contains(concat!(
+            "  if node.HasAttribute('id') then begin\n",
+            "    Id := node.Attributes['id'];\n",
+            "  end else begin\n",
+            "    raise Exception.Create('Required attribute \"id\" is missing');\n",
+            "  end;",
+        )));
+        assert!(body.contains(concat!(
+            "  if node.HasAttribute('count') then begin\n",
+            "    FCount := TSome<Integer>.Create(StrToInt(node.Attributes['count']));\n",
+            "  end else begin\n",
+            "    FCount := TNone<Integer>.Create;\n",
+            "  end;",
+        )));
+        assert!(body.contains(concat!(
+            "  if node.HasAttribute('label') then begin\n",
+            "    Label_ := node.Attributes['label'];\n",
+            "  end else begin\n",
+            "    Label_ := 'none';\n",
+            "  end;",
+        )));
+        assert!(!body.contains(":= raise"));
+    }
+
+    #[test]
+    fn qualified_attributes_are_accessed_namespace_aware() {
+        let namespace = AttributeNamespace {
+            prefix: String::from("o"),
+            uri: String::from("urn:orders"),
+        };
+        let code = generate_with_options(
+            vec![class_type(
+                "Order",
+                None,
+                vec![
+                    Variable {
+                        namespace: Some(namespace.clone()),
+                        ..variable("id", XMLSource::Attribute)
+                    },
+                    Variable {
+                        required: false,
+                        namespace: Some(namespace),
+                        ..variable("note", XMLSource::Attribute)
+                    },
+                ],
+            )],
+            CodeGenOptions::builder("uTest")
+                .serialization(SerializationOptions {
+                    generate_from_xml: true,
+                    generate_to_xml: true,
+                    generate_presence_flags: true,
+                    ..SerializationOptions::default()
+                })
+                .build(),
+        );
+
+        let body = append_to_xml_body(&code, "TOrder");
+
+        assert!(body.contains("pParent.SetAttributeNS('o:id', 'urn:orders', Id);"));
+        assert!(body.contains("pParent.SetAttributeNS('o:note', 'urn:orders', Note.Unwrap);"));
+        let body = from_xml_body(&code, "TOrder");
+
+        assert!(body.contains(concat!(
+            "  if node.HasAttribute('id', 'urn:orders') then begin\n",
+            "    Id := node.GetAttributeNS('id', 'urn:orders');\n",
+            "  end else begin\n",
+            "    raise Exception.Create('Required attribute \"id\" is missing');\n",
+            "  end;",
+        )));
+        assert!(body.contains(concat!(
+            "  if node.HasAttribute('note', 'urn:orders') then begin\n",
+            "    FNote := TSome<String>.Create(node.GetAttributeNS('note', 'urn:orders'));\n",
+            "  end else begin\n",
+            "    FNote := TNone<String>.Create;\n",
+            "  end;",
+        )));
+        assert!(code.contains("FNoteWasPresentInXml := node.HasAttribute('note', 'urn:orders');"));
+        assert!(!code.contains("Attributes['"));
+    }
 
-    // use super::*;
+    #[test]
+    fn to_xml_writes_inherited_elements_first() {
+        let code = generate(vec![
+            class_type(
+                "Base",
+                None,
+                vec![
+                    variable("first", XMLSource::Element),
+                    variable("second", XMLSource::Element),
+                ],
+            ),
+            class_type(
+                "Derived",
+                Some("Base"),
+                vec![
+                    variable("third", XMLSource::Element),
+                    variable("version", XMLSource::Attribute),
+                    variable("fourth", XMLSource::Element),
+                ],
+            ),
+        ]);
+
+        let base_body = append_to_xml_body(&code, "TBase");
+        let derived_body = append_to_xml_body(&code, "TDerived");
+
+        assert_eq!(written_children(base_body), vec!["first", "second"]);
+        assert_eq!(written_children(derived_body), vec!["third", "fourth"]);
+
+        let inherited_position = derived_body.find("inherited;").unwrap();
+        let first_child_position = derived_body.find("pParent.AddChild").unwrap();
+
+        assert!(inherited_position < first_child_position);
+    }
+
+    #[test]
+    fn value_only_types_are_generated_as_records() {
+        let mut point = variable("point", XMLSource::Element);
+        point.data_type = DataType::Custom(String::from("Point"));
+        point.requires_free = true;
+
+        let mut code = variable("code", XMLSource::Element);
+        code.data_type = DataType::Uri;
+
+        let classes = vec![
+            class_type(
+                "Point",
+                None,
+                vec![
+                    variable("x", XMLSource::Element),
+                    variable("y", XMLSource::Element),
+                ],
+            ),
+            class_type("Link", None, vec![code]),
+            class_type("Shape", None, vec![point]),
+            class_type("Derived", Some("Shape"), vec![]),
+        ];
+
+        let code = generate_with_options(
+            classes,
+            CodeGenOptions::builder("uTest")
+                .serialization(SerializationOptions {
+                    generate_from_xml: true,
+                    generate_to_xml: true,
+                    ..SerializationOptions::default()
+                })
+                .output(OutputOptions {
+                    generate_records: true,
+                    ..OutputOptions::default()
+                })
+                .build(),
+        );
+
+        assert!(code.contains("TPoint = record"));
+        assert!(code.contains("TLink = class(TObject)"));
+        assert!(code.contains("TShape = class(TObject)"));
+        assert!(!code.contains("TPoint = class;"));
+        assert!(!code.contains("constructor TPoint.Create;"));
+        assert!(code.contains("Point := Default(TPoint);"));
+        assert!(!code.contains("Point.Free;"));
+    }
+
+    #[test]
+    fn fields_owned_elsewhere_are_neither_created_nor_freed() {
+        let mut lookup = variable("lookup", XMLSource::Element);
+        lookup.data_type = DataType::Custom(String::from("Lookup"));
+        lookup.requires_free = true;
+        lookup.is_owned = false;
+
+        let mut lookups = variable("lookups", XMLSource::Element);
+        lookups.data_type = DataType::List(Box::new(DataType::Custom(String::from("Lookup"))));
+        lookups.requires_free = true;
+        lookups.is_owned = false;
+
+        let mut lines = variable("lines", XMLSource::Element);
+        lines.data_type = DataType::List(Box::new(DataType::Custom(String::from("Lookup"))));
+        lines.requires_free = true;
+
+        let classes = vec![
+            class_type("Lookup", None, vec![variable("key", XMLSource::Element)]),
+            class_type("Order", None, vec![lookup, lookups, lines]),
+        ];
+
+        let code = generate(classes.clone());
+
+        assert!(code.contains("Lookup := nil;"));
+        assert!(code.contains("Lookups: TList<TLookup>;"));
+        assert!(code.contains("Lookups := TList<TLookup>.Create;"));
+        assert!(code.contains("Lines: TObjectList<TLookup>;"));
+        assert!(!code.contains("Lookup.Free;"));
+        assert!(code.contains("Lookups.Free;"));
+        assert!(code.contains("Lines.Free;"));
+
+        let code = generate_with_options(
+            classes,
+            CodeGenOptions::builder("uTest")
+                .serialization(SerializationOptions {
+                    generate_from_xml: false,
+                    generate_to_xml: false,
+                    ..SerializationOptions::default()
+                })
+                .output(OutputOptions {
+                    list_type: ListType::List,
+                    ..OutputOptions::default()
+                })
+                .build(),
+        );
+
+        assert!(!code.contains("__LookupsItem.Free;"));
+        assert!(code.contains("var\n  __LinesItem: TLookup;\nbegin"));
+        assert!(code.contains("for __LinesItem in Lines do begin\n    __LinesItem.Free;"));
+    }
+
+    #[test]
+    fn union_with_reference_variant_is_generated_as_class_hierarchy() {
+        let mut shape = variable("shape", XMLSource::Element);
+        shape.data_type = DataType::Custom(String::from("Shape"));
+        shape.requires_free = true;
+
+        let union_type = UnionType {
+            name: String::from("Shape"),
+            qualified_name: String::from("Shape"),
+            variants: vec![
+                UnionVariant {
+                    name: String::from("Circle"),
+                    data_type: DataType::Custom(String::from("Circle")),
+                },
+                UnionVariant {
+                    name: String::from("Variant1"),
+                    data_type: DataType::Integer,
+                },
+            ],
+            documentations: vec![],
+            is_documented: false,
+        };
+
+        let code = generate_representation(
+            vec![
+                class_type("Circle", None, vec![variable("radius", XMLSource::Element)]),
+                class_type("Drawing", None, vec![shape]),
+            ],
+            vec![union_type],
+            CodeGenOptions::builder("uTest")
+                .serialization(SerializationOptions {
+                    generate_from_xml: true,
+                    generate_to_xml: true,
+                    ..SerializationOptions::default()
+                })
+                .build(),
+        );
+
+        assert!(code.contains("TShape = class;"));
+        assert!(code.contains("TShape = class abstract"));
+        assert!(code.contains("TShapeCircle = class(TShape)"));
+        assert!(code.contains("TShapeVariant1 = class(TShape)"));
+        assert!(code.contains("Exit(TShapeCircle.FromXml(node));"));
+        assert!(code.contains("FValue := StrToInt(node.Text);"));
+        assert!(code.contains("Shape := TShape.FromXml(node.ChildNodes['shape']);"));
+        assert!(!code.contains("TShape = record"));
+    }
+
+    #[test]
+    fn union_record_reads_the_first_matching_variant() {
+        let mut value = variable("value", XMLSource::Element);
+        value.data_type = DataType::Union(String::from("Value"));
+
+        let union_type = UnionType {
+            name: String::from("Value"),
+            qualified_name: String::from("Value"),
+            variants: vec![
+                UnionVariant {
+                    name: String::from("Variant0"),
+                    data_type: DataType::Boolean,
+                },
+                UnionVariant {
+                    name: String::from("Variant1"),
+                    data_type: DataType::String,
+                },
+            ],
+            documentations: vec![],
+            is_documented: false,
+        };
+
+        let code = generate_representation(
+            vec![class_type("Setting", None, vec![value])],
+            vec![union_type],
+            CodeGenOptions::builder("uTest")
+                .serialization(SerializationOptions {
+                    generate_from_xml: true,
+                    generate_to_xml: true,
+                    ..SerializationOptions::default()
+                })
+                .build(),
+        );
+
+        assert!(code.contains("Variants.tvvVariant1: (Variant1: string[255]);"));
+        assert!(code.contains("Result.Variant0 := StrToBool(node.Text);"));
+        assert!(code.contains("Result.Variant := Variants.tvvVariant0;"));
+        assert!(code.contains("does not match any variant of TValue"));
+        assert!(code.contains("Value := TValue.FromXml(node.ChildNodes['value']);"));
+        assert!(code.contains("node.Text := Value.ToXmlValue;"));
+    }
+
+    #[test]
+    fn aliases_and_union_types_are_declared_after_their_dependencies() {
+        let alias = |name: &str, for_type| TypeAlias {
+            name: name.to_owned(),
+            qualified_name: name.to_owned(),
+            for_type,
+            pattern: None,
+            total_digits: None,
+            fraction_digits: None,
+            documentations: vec![],
+            is_documented: false,
+        };
+
+        let mut size = variable("size", XMLSource::Element);
+        size.data_type = DataType::Union(String::from("Size"));
+
+        let internal_representation = InternalRepresentation {
+            document: class_type(DOCUMENT_NAME, None, vec![]),
+            classes: vec![class_type("Item", None, vec![size])],
+            types_aliases: vec![
+                alias("SizeAlias", DataType::Custom(String::from("Size"))),
+                alias("Code", DataType::String),
+            ],
+            enumerations: vec![],
+            union_types: vec![UnionType {
+                name: String::from("Size"),
+                qualified_name: String::from("Size"),
+                variants: vec![
+                    UnionVariant {
+                        name: String::from("Variant0"),
+                        data_type: DataType::Integer,
+                    },
+                    UnionVariant {
+                        name: String::from("Variant1"),
+                        data_type: DataType::Alias(String::from("Code")),
+                    },
+                ],
+                documentations: vec![],
+                is_documented: false,
+            }],
+            external_classes: vec![],
+            metadata: SchemaMetadata::default(),
+        };
+
+        let code = generate_internal_representation(
+            internal_representation,
+            CodeGenOptions::builder("uTest").build(),
+        );
+        let position = |declaration: &str| code.find(declaration).unwrap();
+
+        assert!(code.contains("{$REGION 'Aliases and Union Types'}"));
+        assert!(position("TCode = String;") < position("TSize = record"));
+        assert!(position("TSize = record") < position("TSizeAlias = TSize;"));
+        assert!(position("TSizeAlias = TSize;") < position("TItem = class("));
+    }
 
-    // TODO: Write Test
+    #[test]
+    fn models_unit() {
+        let mut color = variable("color", XMLSource::Element);
+        color.data_type = DataType::Enumeration(String::from("Color"));
+
+        let mut tags = variable("tag", XMLSource::Element);
+        tags.data_type = DataType::List(Box::new(DataType::String));
+        tags.requires_free = true;
+
+        let mut size = variable("size", XMLSource::Attribute);
+        size.data_type = DataType::Integer;
+        size.required = false;
+
+        let mut created = variable("created", XMLSource::Element);
+        created.data_type = DataType::DateTime;
+        created.required = false;
+
+        let internal_representation = InternalRepresentation {
+            document: class_type(DOCUMENT_NAME, None, vec![]),
+            classes: vec![class_type(
+                "Item",
+                None,
+                vec![
+                    variable("name", XMLSource::Element),
+                    color,
+                    tags,
+                    size,
+                    created,
+                ],
+            )],
+            types_aliases: vec![],
+            enumerations: vec![Enumeration {
+                name: String::from("Color"),
+                qualified_name: String::from("Color"),
+                values: ["red", "green"]
+                    .into_iter()
+                    .map(|v| EnumerationValue {
+                        variant_name: v.to_owned(),
+                        xml_value: v.to_owned(),
+                        documentations: vec![],
+                        is_documented: false,
+                    })
+                    .collect(),
+                documentations: vec![],
+                is_documented: false,
+                is_flags: false,
+            }],
+            union_types: vec![],
+            external_classes: vec![],
+            metadata: SchemaMetadata::default(),
+        };
+
+        let code = generate_internal_representation(
+            internal_representation,
+            CodeGenOptions::builder("uTest")
+                .serialization(SerializationOptions {
+                    generate_from_xml: true,
+                    generate_to_xml: true,
+                    ..SerializationOptions::default()
+                })
+                .build(),
+        );
+
+        insta::assert_snapshot!(code
+            .lines()
+            .filter(|l| !l.contains("Timestamp:"))
+            .collect::<Vec<&str>>()
+            .join("\n"));
+    }
+
+    #[test]
+    fn decimals_with_digit_facets_are_mapped_to_currency_or_bcd() {
+        let decimal_alias = |name: &str, total_digits, fraction_digits| TypeAlias {
+            name: name.to_owned(),
+            qualified_name: name.to_owned(),
+            for_type: DataType::Double,
+            pattern: None,
+            total_digits,
+            fraction_digits,
+            documentations: vec![],
+            is_documented: false,
+        };
+
+        let mut amount = variable("amount", XMLSource::Element);
+        amount.data_type = DataType::Alias(String::from("Amount"));
+
+        let mut rate = variable("rate", XMLSource::Element);
+        rate.data_type = DataType::Alias(String::from("Rate"));
+
+        let internal_representation = InternalRepresentation {
+            document: class_type(DOCUMENT_NAME, None, vec![]),
+            classes: vec![class_type("Invoice", None, vec![amount, rate])],
+            types_aliases: vec![
+                decimal_alias("Amount", Some(12), Some(2)),
+                decimal_alias("Rate", None, Some(8)),
+                decimal_alias("Plain", None, None),
+            ],
+            enumerations: vec![],
+            union_types: vec![],
+            external_classes: vec![],
+            metadata: SchemaMetadata::default(),
+        };
+
+        let code = generate_internal_representation(
+            internal_representation,
+            CodeGenOptions::builder("uTest")
+                .serialization(SerializationOptions {
+                    generate_from_xml: true,
+                    generate_to_xml: true,
+                    ..SerializationOptions::default()
+                })
+                .output(OutputOptions {
+                    decimal_mapping: DecimalMapping::Currency,
+                    ..OutputOptions::default()
+                })
+                .build(),
+        );
+
+        assert!(code.contains("uses Data.FmtBcd,"));
+        assert!(code.contains("TAmount = Currency;"));
+        assert!(code.contains("TRate = TBcd;"));
+        assert!(code.contains("TPlain = Double;"));
+        assert!(code.contains(
+            "Amount := StrToCurr(node.ChildNodes['amount'].Text, TFormatSettings.Invariant);"
+        ));
+        assert!(code.contains("node.Text := BcdToStr(Rate, TFormatSettings.Invariant);"));
+    }
+
+    #[test]
+    fn pattern_aliases_are_validated_with_their_base_aliases() {
+        let string_alias = |name: &str, for_type, pattern: Option<&str>| TypeAlias {
+            name: name.to_owned(),
+            qualified_name: name.to_owned(),
+            for_type,
+            pattern: pattern.map(str::to_owned),
+            total_digits: None,
+            fraction_digits: None,
+            documentations: vec![],
+            is_documented: false,
+        };
+
+        let mut code = variable("code", XMLSource::Element);
+        code.data_type = DataType::Alias(String::from("ShortCode"));
+
+        let internal_representation = InternalRepresentation {
+            document: class_type(DOCUMENT_NAME, None, vec![]),
+            classes: vec![class_type("Item", None, vec![code])],
+            types_aliases: vec![
+                string_alias("Code", DataType::String, Some("[A-Z]+'")),
+                string_alias(
+                    "ShortCode",
+                    DataType::Custom(String::from("Code")),
+                    Some(".{3}"),
+                ),
+                string_alias("CodeRef", DataType::Custom(String::from("ShortCode")), None),
+                string_alias("Plain", DataType::String, None),
+            ],
+            enumerations: vec![],
+            union_types: vec![],
+            external_classes: vec![],
+            metadata: SchemaMetadata::default(),
+        };
+
+        let code = generate_internal_representation(
+            internal_representation,
+            CodeGenOptions::builder("uTest")
+                .serialization(SerializationOptions {
+                    generate_from_xml: true,
+                    generate_to_xml: true,
+                    validate_patterns: true,
+                    ..SerializationOptions::default()
+                })
+                .build(),
+        );
+
+        assert!(code.contains("uses System.RegularExpressions;"));
+        assert!(code.contains("cnCodePattern: String =\n    '[A-Z]+''';"));
+        assert!(code.contains(
+            r"Result := TRegEx.IsMatch(pValue, '\A(?:' + cnShortCodePattern + ')\z') and ValidateCode(pValue);"
+        ));
+        assert!(code.contains("Result := ValidateShortCode(pValue);"));
+        assert!(!code.contains("ValidatePlain"));
+        assert!(code.contains("property Code: TShortCode read FCode write SetCode;"));
+        assert!(code.contains("if not ValidateShortCode(pValue) then begin"));
+        assert!(code.contains("FCode := Default(TShortCode);"));
+    }
+
+    #[test]
+    fn repeated_enumerations_are_generated_as_sets() {
+        let enum_value = |name: &str| EnumerationValue {
+            variant_name: name.to_owned(),
+            xml_value: name.to_lowercase(),
+            documentations: vec![],
+            is_documented: false,
+        };
+
+        let mut colors = variable("color", XMLSource::Element);
+        colors.data_type = DataType::List(Box::new(DataType::Enumeration(String::from("Color"))));
+        colors.requires_free = true;
+
+        let mut mixed = variable("mixed", XMLSource::Element);
+        mixed.data_type = DataType::Alias(String::from("Colors"));
+
+        let internal_representation = InternalRepresentation {
+            document: class_type(DOCUMENT_NAME, None, vec![]),
+            classes: vec![class_type("Palette", None, vec![colors, mixed])],
+            types_aliases: vec![TypeAlias {
+                name: String::from("Colors"),
+                qualified_name: String::from("Colors"),
+                for_type: DataType::InlineList(Box::new(DataType::Enumeration(String::from(
+                    "Color",
+                )))),
+                pattern: None,
+                total_digits: None,
+                fraction_digits: None,
+                documentations: vec![],
+                is_documented: false,
+            }],
+            enumerations: vec![Enumeration {
+                name: String::from("Color"),
+                qualified_name: String::from("Color"),
+                values: vec![enum_value("Red"), enum_value("Green")],
+                documentations: vec![],
+                is_documented: false,
+                is_flags: false,
+            }],
+            union_types: vec![],
+            external_classes: vec![],
+            metadata: SchemaMetadata::default(),
+        };
+
+        let code = generate_internal_representation(
+            internal_representation,
+            CodeGenOptions::builder("uTest")
+                .serialization(SerializationOptions {
+                    generate_from_xml: true,
+                    generate_to_xml: true,
+                    ..SerializationOptions::default()
+                })
+                .output(OutputOptions {
+                    generate_enum_sets: true,
+                    ..OutputOptions::default()
+                })
+                .build(),
+        );
+
+        assert!(code.contains("TColorSet = set of TColor;"));
+        assert!(code.contains("TColorSetHelper = record helper for TColorSet"));
+        assert!(code.contains("TColors = TColorSet;"));
+        assert!(code.contains("Color: TColorSet;"));
+        assert!(code.contains("Include(Color, TColor.FromXmlValue(__ColorNode.Text));"));
+        assert!(code.contains("Mixed := TColorSet.FromXmlValue(node.ChildNodes['mixed'].Text);"));
+        assert!(!code.contains("Color.Free;"));
+    }
+
+    #[test]
+    fn flags_are_generated_as_sets_with_bitmask_helpers() {
+        let enum_value = |name: &str| EnumerationValue {
+            variant_name: name.to_owned(),
+            xml_value: name.to_lowercase(),
+            documentations: vec![],
+            is_documented: false,
+        };
+
+        let mut permissions = variable("permission", XMLSource::Element);
+        permissions.data_type =
+            DataType::List(Box::new(DataType::Enumeration(String::from("Permission"))));
+        permissions.requires_free = true;
+
+        let internal_representation = InternalRepresentation {
+            document: class_type(DOCUMENT_NAME, None, vec![]),
+            classes: vec![class_type("User", None, vec![permissions])],
+            types_aliases: vec![],
+            enumerations: vec![Enumeration {
+                name: String::from("Permission"),
+                qualified_name: String::from("Permission"),
+                values: vec![enum_value("Read"), enum_value("Write")],
+                documentations: vec![],
+                is_documented: false,
+                is_flags: true,
+            }],
+            union_types: vec![],
+            external_classes: vec![],
+            metadata: SchemaMetadata::default(),
+        };
+
+        let code = generate_internal_representation(
+            internal_representation,
+            CodeGenOptions::builder("uTest")
+                .serialization(SerializationOptions {
+                    generate_from_xml: true,
+                    generate_to_xml: true,
+                    ..SerializationOptions::default()
+                })
+                .build(),
+        );
+
+        assert!(code.contains("TPermissionSet = set of TPermission;"));
+        assert!(code.contains("Permission: TPermissionSet;"));
+        assert!(code
+            .contains("class function FromInteger(const pValue: UInt64): TPermissionSet; static;"));
+        assert!(code.contains("function TPermissionSetHelper.ToInteger: UInt64;"));
+        assert!(!code.contains("Permission.Free;"));
+    }
+
+    #[test]
+    fn document_preserves_comments_and_processing_instructions() {
+        let internal_representation = InternalRepresentation {
+            document: class_type(DOCUMENT_NAME, None, vec![]),
+            classes: vec![],
+            types_aliases: vec![],
+            enumerations: vec![],
+            union_types: vec![],
+            external_classes: vec![],
+            metadata: SchemaMetadata::default(),
+        };
+
+        let code = generate_internal_representation(
+            internal_representation,
+            CodeGenOptions::builder("uTest")
+                .serialization(SerializationOptions {
+                    generate_from_xml: true,
+                    generate_to_xml: true,
+                    preserve_document_nodes: true,
+                    ..SerializationOptions::default()
+                })
+                .build(),
+        );
+
+        assert!(code.contains("TPreservedXmlNode = record"));
+        assert!(code.contains("FPreservedNodes: TList<TPreservedXmlNode>;"));
+        assert!(code.contains("destructor TDocument.Destroy;"));
+        assert!(code.contains("FPreservedNodes.Add(vPreserved);"));
+        assert!(code.contains("vXmlDoc.Node.ChildNodes.Add(vNode);"));
+    }
+
+    #[test]
+    fn lenient_from_xml_collects_warnings_for_required_values() {
+        let base = class_type(
+            "Base",
+            None,
+            vec![
+                variable("id", XMLSource::Element),
+                variable("version", XMLSource::Attribute),
+            ],
+        );
+        let derived = class_type(
+            "Derived",
+            Some("Base"),
+            vec![variable("name", XMLSource::Element)],
+        );
+
+        let code = generate_with_options(
+            vec![base, derived],
+            CodeGenOptions::builder("uTest")
+                .serialization(SerializationOptions {
+                    generate_from_xml: true,
+                    generate_to_xml: true,
+                    lenient_from_xml: true,
+                    ..SerializationOptions::default()
+                })
+                .build(),
+        );
+
+        assert_eq!(code.matches("FFromXmlWarnings: TList<String>;").count(), 2);
+        assert!(code.contains("FFromXmlWarnings.Add('Required element \"id\" is missing');"));
+        assert!(code.contains("FFromXmlWarnings.Add('Required element \"name\" is missing');"));
+        assert!(code.contains("Version := Default(String);"));
+        assert!(code.contains("FFromXmlWarnings.Add('Required attribute \"version\" is missing');"));
+        assert!(code.contains("destructor TBase.Destroy;"));
+        assert!(!code.contains("destructor TDerived.Destroy;"));
+    }
+
+    #[test]
+    fn debug_dump_lists_fields_of_the_class_and_its_ancestors() {
+        let base = class_type(
+            "Base",
+            None,
+            vec![Variable {
+                data_type: DataType::List(Box::new(DataType::Custom(String::from("Base")))),
+                requires_free: true,
+                ..variable("child", XMLSource::Element)
+            }],
+        );
+        let derived = class_type(
+            "Derived",
+            Some("Base"),
+            vec![variable("name", XMLSource::Element)],
+        );
+
+        let code = generate_with_options(
+            vec![base, derived],
+            CodeGenOptions::builder("uTest")
+                .serialization(SerializationOptions {
+                    generate_from_xml: true,
+                    generate_to_xml: true,
+                    ..SerializationOptions::default()
+                })
+                .output(OutputOptions {
+                    generate_debug_dump: true,
+                    ..OutputOptions::default()
+                })
+                .build(),
+        );
+
+        assert!(code.contains("function TBase.ToString: String;"));
+        assert!(!code.contains("function TDerived.ToString: String;"));
+        assert!(code.contains("if pVisited.Contains(Self) then Exit(ClassName + ' (cycle)');"));
+        assert!(code.contains(
+            "if pObject is TBase then Exit(TBase(pObject).DebugDump(pVisited, pIndent));"
+        ));
+        assert!(!code.contains("if pObject is TDerived then"));
+        assert!(code.contains("DebugDumpObject(__ChildItem, pVisited, pIndent + 1);"));
+        assert!(code.contains("Result := inherited DumpFields(pVisited, pIndent);"));
+        assert!(code.contains("Result := Result + vIndent + 'Name: ' + Name;"));
+    }
+
+    #[test]
+    fn external_classes_are_referenced_from_their_unit() {
+        let customer = class_type(
+            "Customer",
+            None,
+            vec![Variable {
+                data_type: DataType::Custom(String::from("Address")),
+                requires_free: true,
+                ..variable("address", XMLSource::Element)
+            }],
+        );
+
+        let code = generate_internal_representation(
+            InternalRepresentation {
+                document: class_type(DOCUMENT_NAME, None, vec![]),
+                classes: vec![customer],
+                types_aliases: vec![],
+                enumerations: vec![],
+                union_types: vec![],
+                external_classes: vec![ExternalClass {
+                    name: String::from("Address"),
+                    qualified_name: String::from("Address"),
+                    delphi_type: String::from("TPostalAddress"),
+                    unit: String::from("uAddress"),
+                }],
+                metadata: SchemaMetadata::default(),
+            },
+            CodeGenOptions::builder("uTest")
+                .serialization(SerializationOptions {
+                    generate_from_xml: true,
+                    generate_to_xml: true,
+                    ..SerializationOptions::default()
+                })
+                .build(),
+        );
+
+        assert!(code.contains("uses uAddress,"));
+        assert!(code.contains("TAddress = uAddress.TPostalAddress;"));
+        assert!(!code.contains("TAddress = class"));
+        assert!(code.contains("Address := TAddress.FromXml(node.ChildNodes['address']);"));
+    }
+
+    #[test]
+    fn bounded_lists_check_their_number_of_items() {
+        let member = Variable {
+            data_type: DataType::List(Box::new(DataType::String)),
+            requires_free: true,
+            occurrence: Some(Occurrence {
+                min: 2,
+                max: Some(5),
+            }),
+            ..variable("member", XMLSource::Element)
+        };
+        let fan = Variable {
+            data_type: DataType::List(Box::new(DataType::String)),
+            requires_free: true,
+            occurrence: Some(Occurrence { min: 3, max: None }),
+            ..variable("fan", XMLSource::Element)
+        };
+
+        let code = generate_with_options(
+            vec![class_type("Team", None, vec![member, fan])],
+            CodeGenOptions::builder("uTest")
+                .serialization(SerializationOptions {
+                    generate_from_xml: true,
+                    generate_to_xml: true,
+                    lenient_from_xml: true,
+                    ..SerializationOptions::default()
+                })
+                .build(),
+        );
+
+        assert!(code.contains(
+            "if Assigned(Member) and ((Member.Count < 2) or (Member.Count > 5)) then begin"
+        ));
+        assert!(code.contains(
+            "raise Exception.Create('Element \"fan\" occurs ' + IntToStr(Fan.Count) + ' times, expected at least 3 times');"
+        ));
+        assert!(code.contains(
+            "FFromXmlWarnings.Add('Element \"member\" occurs ' + IntToStr(Member.Count) + ' times, expected 2 to 5 times');"
+        ));
+    }
+
+    #[test]
+    fn abstract_classes_are_created_by_xsi_type() {
+        let shape = ClassType {
+            is_abstract: true,
+            ..class_type("Shape", None, vec![variable("color", XMLSource::Element)])
+        };
+        let main = Variable {
+            data_type: DataType::Custom(String::from("Shape")),
+            requires_free: true,
+            ..variable("main", XMLSource::Element)
+        };
+
+        let code = generate(vec![
+            shape,
+            class_type("Circle", Some("Shape"), vec![]),
+            class_type("Drawing", None, vec![main]),
+        ]);
+
+        assert!(code.contains("TShape = class abstract(TObject)"));
+        assert!(code.contains("class function XmlTypeName: String; virtual; abstract;"));
+        assert!(code.contains("if vType = 'Circle' then Exit(TCircle.FromXml(node));"));
+        assert!(code.contains("Main := TShape.FromXmlByType(node.ChildNodes['main']);"));
+        assert!(code.contains("Main := nil;"));
+        assert!(code.contains("Result := 'Circle';"));
+    }
+
+    #[test]
+    fn enum_values_are_looked_up_by_binary_search() {
+        let value = |name: &str| EnumerationValue {
+            variant_name: name.to_lowercase(),
+            xml_value: name.to_owned(),
+            documentations: vec![],
+            is_documented: false,
+        };
+
+        let code = generate_internal_representation(
+            InternalRepresentation {
+                document: class_type(DOCUMENT_NAME, None, vec![]),
+                classes: vec![],
+                types_aliases: vec![],
+                enumerations: vec![Enumeration {
+                    name: String::from("Status"),
+                    qualified_name: String::from("Status"),
+                    values: vec![value("open"), value("Closed"), value("archived")],
+                    documentations: vec![],
+                    is_documented: false,
+                    is_flags: false,
+                }],
+                union_types: vec![],
+                external_classes: vec![],
+                metadata: SchemaMetadata::default(),
+            },
+            CodeGenOptions::builder("uTest")
+                .serialization(SerializationOptions {
+                    generate_from_xml: true,
+                    generate_to_xml: true,
+                    enum_lookup: EnumLookup::BinarySearch,
+                    ..SerializationOptions::default()
+                })
+                .build(),
+        );
+
+        assert!(code.contains(
+            "cXmlValues: array[0..2] of String = (\n    'Closed',\n    'archived',\n    'open'\n  );"
+        ));
+        assert!(code.contains(
+            "cValues: array[0..2] of TStatus = (\n    TStatus.sClosed,\n    TStatus.sArchived,\n    TStatus.sOpen\n  );"
+        ));
+        assert!(!code.contains("if pXmlValue = 'open' then begin"));
+    }
+
+    #[test]
+    fn fields_are_decorated_with_serializer_attributes() {
+        let code = generate_with_options(
+            vec![class_type(
+                "Order",
+                None,
+                vec![
+                    variable("id", XMLSource::Attribute),
+                    variable("note", XMLSource::Element),
+                ],
+            )],
+            CodeGenOptions::builder("uTest")
+                .serialization(SerializationOptions {
+                    generate_from_xml: true,
+                    generate_to_xml: true,
+                    generate_serializer_attributes: true,
+                    ..SerializationOptions::default()
+                })
+                .build(),
+        );
+
+        assert!(code.contains("XmlElementAttribute = class(XmlNodeAttribute);"));
+        assert!(code.contains("[XmlAttribute('id')]\n    Id: String;"));
+        assert!(code.contains("[XmlElement('note')]\n    Note: String;"));
+    }
+
+    #[test]
+    fn identity_constraints_are_checked_by_check_constraints() {
+        let constraint = |name: &str, kind, selector: &str, field: &str| IdentityConstraint {
+            element: String::from("library"),
+            name: name.to_owned(),
+            kind,
+            selector: selector.to_owned(),
+            fields: vec![field.to_owned()],
+        };
+        let book = class_type("Book", None, vec![variable("id", XMLSource::Attribute)]);
+        let library = ClassType {
+            identity_constraints: vec![
+                constraint(
+                    "loanBook",
+                    IdentityConstraintKind::KeyRef(String::from("bookId")),
+                    "loan",
+                    "@book",
+                ),
+                constraint("bookId", IdentityConstraintKind::Key, "./book", "@id"),
+                constraint("deep", IdentityConstraintKind::Unique, ".//book", "@id"),
+            ],
+            ..class_type(
+                "Library",
+                None,
+                vec![Variable {
+                    data_type: DataType::List(Box::new(DataType::Custom(String::from("Book")))),
+                    requires_free: true,
+                    ..variable("book", XMLSource::Element)
+                }],
+            )
+        };
+
+        let code = generate_with_options(
+            vec![book, library],
+            CodeGenOptions::builder("uTest")
+                .serialization(SerializationOptions {
+                    generate_from_xml: true,
+                    generate_to_xml: true,
+                    generate_constraint_checks: true,
+                    ..SerializationOptions::default()
+                })
+                .build(),
+        );
+
+        assert!(code.contains("TIdentityConstraints = class"));
+        assert!(code.contains("function TLibrary.CheckConstraints: TArray<String>;"));
+        assert!(!code.contains("function TBook.CheckConstraints"));
+        assert!(code.contains(
+            "for I1 := 0 to Self.Book.Count - 1 do begin\n      vPath1 := 'library' + '/book[' + IntToStr(I1 + 1) + ']';"
+        ));
+        assert!(code.contains("vValues := vValues + [Self.Book[I1].Id];"));
+        assert!(code.contains("vConstraints.AddKey('bookId', vPath1, vValues, vComplete);"));
+        // The key reference selects an element, which doesn't exist, and descendant paths are not supported
+        assert!(!code.contains("CheckReference('loanBook'"));
+        assert!(!code.contains("'deep'"));
+    }
+
+    #[test]
+    fn simple_assertions_are_checked_by_check_assertions() {
+        let range = ClassType {
+            assertions: vec![
+                String::from("@min le @max"),
+                String::from("title != 'it''s'"),
+                String::from("count(item) gt 0"),
+            ],
+            ..class_type(
+                "Range",
+                None,
+                vec![
+                    Variable {
+                        data_type: DataType::Integer,
+                        ..variable("min", XMLSource::Attribute)
+                    },
+                    Variable {
+                        data_type: DataType::Integer,
+                        required: false,
+                        ..variable("max", XMLSource::Attribute)
+                    },
+                    variable("title", XMLSource::Element),
+                ],
+            )
+        };
+        let percent_range = ClassType {
+            assertions: vec![String::from("@max <= 100")],
+            ..class_type("PercentRange", Some("Range"), vec![])
+        };
+
+        let code = generate(vec![range, percent_range]);
+
+        assert!(code.contains("function CheckAssertions: TArray<String>; virtual;"));
+        assert!(code.contains("function CheckAssertions: TArray<String>; override;"));
+        assert!(code.contains(
+            "if not (Self.Max.IsSome and (Self.Min <= Self.Max.Unwrap)) then Result := Result + ['@min le @max'];"
+        ));
+        assert!(code.contains(
+            "if not (Self.Title <> 'it''s') then Result := Result + ['title != ''it''''s'''];"
+        ));
+        assert!(code.contains("Result := inherited CheckAssertions;"));
+        assert!(code.contains(
+            "if not (Self.Max.IsSome and (Self.Max.Unwrap <= 100)) then Result := Result + ['@max <= 100'];"
+        ));
+        // Functions of XPath are not translated
+        assert!(!code.contains("count(item)"));
+    }
+
+    #[test]
+    fn schema_strings_are_written_as_escaped_literals() {
+        let item = ClassType {
+            documentations: vec![String::from("Über \"Item\"\rwith a carriage return")],
+            is_documented: false,
+            ..class_type(
+                "Item",
+                None,
+                vec![Variable {
+                    default_value: Some(String::from("it's Größe\t\u{1F600}")),
+                    ..variable("title", XMLSource::Attribute)
+                }],
+            )
+        };
+
+        let code = generate(vec![item]);
+
+        assert!(code.contains("  // Über \"Item\"\n  // with a carriage return\n"));
+        assert!(code.contains("Title := 'it''s Gr'#$00F6#$00DF'e'#9#$D83D#$DE00;"));
+    }
+
+    #[test]
+    fn presence_of_optional_nodes_is_recorded() {
+        let order = class_type(
+            "Order",
+            None,
+            vec![
+                variable("id", XMLSource::Element),
+                Variable {
+                    data_type: DataType::Integer,
+                    required: false,
+                    default_value: Some(String::from("1")),
+                    ..variable("quantity", XMLSource::Element)
+                },
+                Variable {
+                    required: false,
+                    ..variable("mode", XMLSource::Attribute)
+                },
+            ],
+        );
+
+        let code = generate_with_options(
+            vec![order],
+            CodeGenOptions::builder("uTest")
+                .serialization(SerializationOptions {
+                    generate_from_xml: true,
+                    generate_presence_flags: true,
+                    ..SerializationOptions::default()
+                })
+                .build(),
+        );
+
+        assert!(code
+            .contains("property QuantityWasPresentInXml: Boolean read FQuantityWasPresentInXml;"));
+        assert!(code.contains(
+            "FQuantityWasPresentInXml := Assigned(node.ChildNodes.FindNode('quantity'));"
+        ));
+        assert!(code.contains("FModeWasPresentInXml := node.HasAttribute('mode');"));
+        assert!(!code.contains("IdWasPresentInXml"));
+    }
+
+    #[test]
+    fn to_xml_is_formatted_by_the_options() {
+        let order = class_type("Order", None, vec![variable("id", XMLSource::Element)]);
+        let generate = |xml_formatting: XmlFormatting| {
+            generate_with_options(
+                vec![order.clone()],
+                CodeGenOptions::builder("uTest")
+                    .serialization(SerializationOptions {
+                        generate_to_xml: true,
+                        xml_formatting,
+                        ..SerializationOptions::default()
+                    })
+                    .build(),
+            )
+        };
+
+        let compact = generate(XmlFormatting::default());
+
+        assert!(!compact.contains("doNodeAutoIndent"));
+        assert!(!compact.contains("StandAlone"));
+        assert!(!compact.contains("FindNode('xml')"));
+
+        let pretty = generate(XmlFormatting {
+            indentation: Some(2),
+            standalone: Some(true),
+            ..XmlFormatting::default()
+        });
+
+        assert!(pretty.contains("vXmlDoc.Options := vXmlDoc.Options + [doNodeAutoIndent];"));
+        assert!(pretty.contains("vXmlDoc.NodeIndentStr := '  ';"));
+        assert!(pretty.contains("vXmlDoc.StandAlone := 'yes';"));
+
+        let without_declaration = generate(XmlFormatting {
+            include_declaration: false,
+            standalone: Some(false),
+            ..XmlFormatting::default()
+        });
+
+        assert!(without_declaration
+            .contains("vDeclaration := vXmlDoc.Node.ChildNodes.FindNode('xml');"));
+        assert!(!without_declaration.contains("StandAlone"));
+    }
+
+    #[test]
+    fn unknown_nodes_are_found_by_the_class_of_the_object() {
+        let base = class_type(
+            "Base",
+            None,
+            vec![
+                variable("id", XMLSource::Element),
+                variable("version", XMLSource::Attribute),
+            ],
+        );
+        let derived = class_type(
+            "Derived",
+            Some("Base"),
+            vec![variable("total", XMLSource::Element)],
+        );
+
+        let code = generate_with_options(
+            vec![base, derived],
+            CodeGenOptions::builder("uTest")
+                .serialization(SerializationOptions {
+                    generate_from_xml: true,
+                    generate_to_xml: true,
+                    unknown_xml_nodes: UnknownXmlNodes::Collect,
+                    ..SerializationOptions::default()
+                })
+                .build(),
+        );
+
+        assert!(code.contains("property UnknownElements: TList<String> read FUnknownElements;"));
+        assert_eq!(
+            code.matches("FUnknownElements.Add(vChild.LocalName);")
+                .count(),
+            2
+        );
+        assert!(code.contains("Result := MatchStr(pName, ['version']);"));
+        assert!(code.contains("Result := MatchStr(pName, ['total']);"));
+        assert!(code.contains("Result := Result or inherited IsKnownXmlNode(pName, pIsAttribute);"));
+
+        let code = generate_with_options(
+            vec![class_type("Base", None, vec![])],
+            CodeGenOptions::builder("uTest")
+                .serialization(SerializationOptions {
+                    generate_from_xml: true,
+                    unknown_xml_nodes: UnknownXmlNodes::Raise,
+                    ..SerializationOptions::default()
+                })
+                .build(),
+        );
+
+        assert!(code
+            .contains("raise Exception.Create('Unknown element \"' + vChild.LocalName + '\"');"));
+        assert!(!code.contains("UnknownElements"));
+    }
+
+    #[test]
+    fn wildcard_elements_are_kept_as_xml() {
+        let extension = ClassType {
+            wildcard: Some(Wildcard {
+                namespaces: WildcardNamespaces::Other(String::from("urn:orders")),
+                process_contents: ProcessContents::Lax,
+            }),
+            ..class_type(
+                "Extension",
+                Some("Base"),
+                vec![variable("note", XMLSource::Element)],
+            )
+        };
+
+        let code = generate_with_options(
+            vec![
+                class_type("Base", None, vec![variable("id", XMLSource::Element)]),
+                extension,
+            ],
+            CodeGenOptions::builder("uTest")
+                .serialization(SerializationOptions {
+                    generate_from_xml: true,
+                    generate_to_xml: true,
+                    unknown_xml_nodes: UnknownXmlNodes::Raise,
+                    ..SerializationOptions::default()
+                })
+                .build(),
+        );
+
+        assert!(code.contains(
+            "    // Xml of the child elements of any namespace except 'urn:orders', which are matched by xs:any.\n    \
+             // processContents=\"lax\": The elements are kept without validation, as no declaration is known for them.\n    \
+             AnyElements: TList<String>;"
+        ));
+        assert!(code.contains("if MatchStr(vChild.LocalName, ['id', 'note']) then continue;"));
+        assert!(code.contains(
+            "if not ((vChild.NamespaceURI <> '') and (vChild.NamespaceURI <> 'urn:orders')) then begin"
+        ));
+        assert!(code.contains("AnyElements.Add(vChild.XML);"));
+        assert!(
+            code.contains("pParent.ChildNodes.Add(vAnyDocument.DocumentElement.CloneNode(True));")
+        );
+        assert!(code.contains("AnyElements.Free;"));
+        assert!(!code.contains("is not declared by the schema"));
+
+        let strict = ClassType {
+            wildcard: Some(Wildcard {
+                namespaces: WildcardNamespaces::Any,
+                process_contents: ProcessContents::Strict,
+            }),
+            ..class_type("Envelope", None, vec![])
+        };
+        let internal_representation = InternalRepresentation {
+            document: class_type(
+                DOCUMENT_NAME,
+                None,
+                vec![
+                    variable("Order", XMLSource::Element),
+                    variable("Invoice", XMLSource::Element),
+                ],
+            ),
+            classes: vec![strict],
+            types_aliases: vec![],
+            enumerations: vec![],
+            union_types: vec![],
+            external_classes: vec![],
+            metadata: SchemaMetadata::default(),
+        };
+
+        let code = generate_internal_representation(
+            internal_representation,
+            CodeGenOptions::builder("uTest")
+                .serialization(SerializationOptions {
+                    generate_from_xml: true,
+                    ..SerializationOptions::default()
+                })
+                .build(),
+        );
+
+        assert!(code.contains("if not MatchStr(vChild.LocalName, ['Order', 'Invoice']) then begin"));
+        assert!(!code.contains("vChild.NamespaceURI"));
+    }
+
+    #[test]
+    fn date_times_keep_their_offset() {
+        let created = Variable {
+            data_type: DataType::DateTime,
+            ..variable("created", XMLSource::Element)
+        };
+        let slots = Variable {
+            data_type: DataType::List(Box::new(DataType::DateTime)),
+            requires_free: true,
+            ..variable("slot", XMLSource::Element)
+        };
+        let classes = || {
+            vec![class_type(
+                "Event",
+                None,
+                vec![created.clone(), slots.clone()],
+            )]
+        };
+
+        let code = generate_with_options(
+            classes(),
+            CodeGenOptions::builder("uTest")
+                .serialization(SerializationOptions {
+                    generate_from_xml: true,
+                    generate_to_xml: true,
+                    date_time_handling: DateTimeHandling::Local,
+                    ..SerializationOptions::default()
+                })
+                .build(),
+        );
+
+        assert!(code.contains("cnXmlDateTimeIsUtc: Boolean = False;"));
+        assert!(code.contains(
+            "Created := ISO8601ToDate(node.ChildNodes['created'].Text, cnXmlDateTimeIsUtc);"
+        ));
+        assert!(code.contains("node.Text := DateToISO8601(Created, cnXmlDateTimeIsUtc);"));
+        assert!(!code.contains("TXmlDateTime"));
+
+        let code = generate_with_options(
+            classes(),
+            CodeGenOptions::builder("uTest")
+                .serialization(SerializationOptions {
+                    generate_from_xml: true,
+                    generate_to_xml: true,
+                    date_time_handling: DateTimeHandling::Offset,
+                    ..SerializationOptions::default()
+                })
+                .build(),
+        );
+
+        assert!(code.contains("TXmlDateTime = record"));
+        assert!(code.contains("Created: TXmlDateTime;"));
+        assert!(code.contains("Slot: TList<TXmlDateTime>;"));
+        assert!(code.contains("Created := TXmlDateTime.FromXml(node.ChildNodes['created'].Text);"));
+        assert!(code.contains("node.Text := __SlotItem.ToXml;"));
+        assert!(!code.contains("cnXmlDateTimeIsUtc"));
+    }
+
+    #[test]
+    fn manual_code_is_included() {
+        let options = || {
+            CodeGenOptions::builder("uTest")
+                .serialization(SerializationOptions {
+                    generate_from_xml: true,
+                    generate_to_xml: true,
+                    ..SerializationOptions::default()
+                })
+                .output(OutputOptions {
+                    generate_include_file: true,
+                    ..OutputOptions::default()
+                })
+                .build()
+        };
+        let classes = || vec![class_type("Order", None, vec![])];
+
+        let code = generate_with_options(classes(), options());
+
+        assert!(code.contains(
+            "  public\n    // Manual code, which is declared in uTest.inc\n    {$DEFINE MANUAL_TOrder}\n    {$INCLUDE uTest.inc}\n    {$UNDEF MANUAL_TOrder}\n  end;"
+        ));
+        assert!(code.contains(
+            "{$DEFINE MANUAL_IMPLEMENTATION}\n{$INCLUDE uTest.inc}\n{$UNDEF MANUAL_IMPLEMENTATION}\n\nend."
+        ));
+
+        let include_file = build_include_file(
+            &InternalRepresentation {
+                document: class_type(DOCUMENT_NAME, None, vec![]),
+                classes: classes(),
+                types_aliases: vec![],
+                enumerations: vec![],
+                union_types: vec![],
+                external_classes: vec![],
+                metadata: SchemaMetadata::default(),
+            },
+            &options(),
+        );
+
+        assert!(include_file.ends_with(
+            "{$IFDEF MANUAL_TDocument}\n{$ENDIF}\n\n{$IFDEF MANUAL_TOrder}\n{$ENDIF}\n\n{$IFDEF MANUAL_IMPLEMENTATION}\n{$ENDIF}\n"
+        ));
+    }
+
+    #[test]
+    fn lists_are_generated_as_arrays() {
+        let items = Variable {
+            data_type: DataType::List(Box::new(DataType::Custom(String::from("Item")))),
+            requires_free: true,
+            occurrence: Some(Occurrence {
+                min: 0,
+                max: Some(5),
+            }),
+            ..variable("item", XMLSource::Element)
+        };
+        let tags = Variable {
+            data_type: DataType::List(Box::new(DataType::String)),
+            requires_free: true,
+            ..variable("tag", XMLSource::Element)
+        };
+
+        let code = generate_with_options(
+            vec![
+                class_type("Item", None, vec![variable("name", XMLSource::Element)]),
+                class_type("Order", None, vec![items, tags]),
+            ],
+            CodeGenOptions::builder("uTest")
+                .serialization(SerializationOptions {
+                    generate_from_xml: true,
+                    generate_to_xml: true,
+                    ..SerializationOptions::default()
+                })
+                .output(OutputOptions {
+                    list_type: ListType::Array,
+                    ..OutputOptions::default()
+                })
+                .build(),
+        );
+
+        assert!(code.contains("Item: TArray<TItem>;"));
+        assert!(code.contains("Tag: TArray<String>;"));
+        assert!(code.contains("Item := Item + [TItem.FromXml(__ItemNode)];"));
+        assert!(code.contains("if Length(Item) > 5 then begin"));
+        assert!(code.contains("for __ItemItem in Item do begin\n    __ItemItem.Free;"));
+        assert!(!code.contains("Tag.Free;"));
+    }
+
+    #[test]
+    fn generics_free_units_use_concrete_types() {
+        let items = Variable {
+            data_type: DataType::List(Box::new(DataType::Custom(String::from("Item")))),
+            requires_free: true,
+            ..variable("item", XMLSource::Element)
+        };
+        let tags = Variable {
+            data_type: DataType::List(Box::new(DataType::String)),
+            requires_free: true,
+            ..variable("tag", XMLSource::Element)
+        };
+        let quantity = Variable {
+            data_type: DataType::Integer,
+            required: false,
+            ..variable("quantity", XMLSource::Element)
+        };
+        let homepage = Variable {
+            data_type: DataType::Uri,
+            requires_free: true,
+            ..variable("homepage", XMLSource::Element)
+        };
+
+        let code = generate_with_options(
+            vec![
+                class_type("Item", None, vec![variable("name", XMLSource::Element)]),
+                class_type("Order", None, vec![items, tags, quantity, homepage]),
+            ],
+            CodeGenOptions::builder("uTest")
+                .serialization(SerializationOptions {
+                    generate_from_xml: true,
+                    generate_to_xml: true,
+                    unknown_xml_nodes: UnknownXmlNodes::Collect,
+                    preserve_document_nodes: true,
+                    ..SerializationOptions::default()
+                })
+                .output(OutputOptions {
+                    generics_free: true,
+                    generate_debug_dump: true,
+                    ..OutputOptions::default()
+                })
+                .build(),
+        );
+
+        for generic in [
+            "TList<",
+            "TObjectList<",
+            "TArray<",
+            "TOptional<",
+            "TSome<",
+            "TNone<",
+        ] {
+            assert!(!code.contains(generic), "{generic} is used");
+        }
+        // Older Delphi versions neither know inline variables nor scoped unit names
+        assert!(!code.contains("System."), "scoped unit is used");
+        assert!(!code.contains("Xml."), "scoped unit is used");
+        assert!(code.contains("uses DateUtils,"));
+        assert!(code.contains("     SysUtils,\n     XMLDoc,\n     XMLIntf;"));
+        for line in code.lines() {
+            assert!(!line.contains("for var "), "inline variable: {line}");
+            assert!(
+                !line.starts_with("  ") || !line.trim_start().starts_with("var "),
+                "inline variable: {line}"
+            );
+        }
+        assert!(code.contains(
+            "procedure TOrder.AppendToXmlRaw(pParent: IXMLNode);\nvar\n  node: IXMLNode;\n  __ItemItem: TItem;\n  __TagItem: String;\nbegin"
+        ));
+        assert!(code.contains("Homepage: String;"));
+        assert!(!code.contains("TURI"));
+        assert!(!code.contains("Homepage.Free;"));
+        assert!(code.contains("  TObjectListOfItem = class;\n"));
+        assert!(code.contains("Item: TObjectListOfItem;"));
+        assert!(code.contains("Tag: TListOfString;"));
+        assert!(code.contains("FQuantity: TOptionalInteger;"));
+        assert!(code.contains("  TSomeInteger = class(TOptionalInteger)\n"));
+        assert!(code.contains("function TListOfObject.Contains(const pValue: TObject): Boolean;"));
+        assert!(!code.contains("function TListOfString.Contains"));
+        assert!(code.contains(
+            "procedure TObjectListOfItem.Delete(pIndex: Integer);\nvar\n  I: Integer;\nbegin\n  CheckIndex(pIndex);\n  FItems[pIndex].Free;\n"
+        ));
+    }
+
+    #[test]
+    fn schema_metadata_is_generated_as_constants() {
+        let metadata = SchemaMetadata {
+            app_infos: vec![AppInfo {
+                name: String::from("Order"),
+                qualified_name: String::from("Order"),
+                content: String::from("<route path=\"/orders\"/>\n<owner>it's sales</owner>"),
+            }],
+            notations: vec![Notation {
+                name: String::from("jpeg"),
+                public: Some(String::from("image/jpeg")),
+                system: None,
+            }],
+        };
+        let generate = |schema_metadata| {
+            generate_internal_representation(
+                InternalRepresentation {
+                    document: class_type(DOCUMENT_NAME, None, vec![]),
+                    classes: vec![class_type(
+                        "Order",
+                        None,
+                        vec![variable("id", XMLSource::Element)],
+                    )],
+                    types_aliases: vec![],
+                    enumerations: vec![],
+                    union_types: vec![],
+                    external_classes: vec![],
+                    metadata: metadata.clone(),
+                },
+                CodeGenOptions::builder("uTest")
+                    .naming(NamingOptions {
+                        type_prefix: Some(String::from("Api")),
+                        ..NamingOptions::default()
+                    })
+                    .serialization(SerializationOptions {
+                        generate_from_xml: true,
+                        generate_to_xml: true,
+                        ..SerializationOptions::default()
+                    })
+                    .output(OutputOptions {
+                        schema_metadata,
+                        ..OutputOptions::default()
+                    })
+                    .build(),
+            )
+        };
+
+        let code = generate(SchemaMetadataOutput::None);
+
+        assert!(!code.contains("AppInfo"));
+
+        let code = generate(SchemaMetadataOutput::Constants);
+
+        assert!(code.contains(
+            "  cnApiOrderAppInfo: String =\n    '<route path=\"/orders\"/>' + sLineBreak +\n    '<owner>it''s sales</owner>';"
+        ));
+        assert!(code.contains("  cnNotationJpegPublic: String =\n    'image/jpeg';"));
+        assert!(!code.contains("cnNotationJpegSystem"));
+        assert!(!code.contains("TSchemaMetadata"));
+
+        let code = generate(SchemaMetadataOutput::Registry);
+
+        assert!(code.contains("  TSchemaMetadata = class abstract"));
+        assert!(code.contains(
+            "  case IndexStr(pTypeName, ['TApiOrder']) of\n    0: pAppInfo := cnApiOrderAppInfo;"
+        ));
+        assert!(code.contains("  Result := ['TApiOrder'];"));
+    }
+
+    #[test]
+    fn templates_are_compiled_once_and_can_be_overridden() {
+        // Other tests render concurrently, so only a template unknown to them is overridden
+        override_templates(&[("custom.pas", "unit {{ unitName }};")]).unwrap();
+
+        let tera = load_templates().unwrap();
+        let mut context = Context::new();
+        context.insert("unitName", "uCustom");
+
+        assert!(Arc::ptr_eq(&tera, &load_templates().unwrap()));
+        assert_eq!(
+            tera.render("custom.pas", &context).unwrap(),
+            "unit uCustom;"
+        );
+        assert!(tera.get_template_names().any(|n| n == "models.pas"));
+
+        override_templates(&[]).unwrap();
+
+        assert!(load_templates()
+            .unwrap()
+            .get_template_names()
+            .all(|n| n != "custom.pas"));
+    }
+
+    #[test]
+    fn repeated_sequences_are_lists_of_inner_classes() {
+        let schema = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/repeated_sequence.xsd");
+        let (_, representation) = crate::build_representation(
+            &[schema],
+            &CodeGenOptions::default(),
+            &mut crate::type_registry::TypeRegistry::new(),
+        )
+        .unwrap();
+
+        let code = generate_internal_representation(
+            representation,
+            CodeGenOptions::builder("uTest")
+                .serialization(SerializationOptions {
+                    generate_from_xml: true,
+                    generate_to_xml: true,
+                    ..SerializationOptions::default()
+                })
+                .build(),
+        );
+
+        assert!(code.contains("  TOrderItemSequence = class(TObject)"));
+        assert!(code.contains("    ItemSequence: TObjectList<TOrderItemSequence>;"));
+        assert!(code.contains("  TAddressPhoneSequence = class(TObject)"));
+        assert!(code.contains("    PhoneSequence: TObjectList<TAddressPhoneSequence>;"));
+
+        // Every occurrence of the leading element starts the next item of the list
+        let from_xml = from_xml_body(&code, "TOrder");
+
+        assert!(from_xml.contains("if node.ChildNodes[I].LocalName = 'item' then begin"));
+        assert!(from_xml.contains(
+            "ItemSequence.Add(TOrderItemSequence.FromXml(__ItemSequenceRoot.ChildNodes[I]));"
+        ));
+        assert!(append_to_xml_body(&code, "TOrder")
+            .contains("__ItemSequenceItem.AppendToXmlRaw(pParent);"));
+    }
 }
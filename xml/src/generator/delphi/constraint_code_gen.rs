@@ -0,0 +1,346 @@
+use crate::generator::{
+    code_generator_trait::{CodeGenOptions, ListType},
+    types::{
+        ClassType, DataType, IdentityConstraint, IdentityConstraintKind, ResolvedTypeAliases,
+        Variable, XMLSource,
+    },
+};
+use pascal::as_pascal_literal;
+
+use super::{
+    generics_free::{instance_name, Generic},
+    helper::Helper,
+};
+
+/// Node selected by a step of the selector of an identity constraint
+struct SelectedNode {
+    /// Delphi expression of the node, e.g. `Self.Book[I1]`
+    expr: String,
+    /// Delphi expression of the path of the node, which is reported with a violation
+    path: String,
+    data_type: DataType,
+}
+
+/// Statements of `CheckConstraints` and the local variables declared for them
+#[derive(Default)]
+pub(crate) struct ConstraintChecks {
+    /// Declarations of the local variables, e.g. `I1: Integer`
+    pub(crate) variables: Vec<String>,
+    pub(crate) statements: Vec<String>,
+}
+
+/// Code generator for `CheckConstraints`, which checks the identity constraints
+/// xs:key, xs:keyref and xs:unique of a class.
+///
+/// The selectors and fields support the paths of child elements and attributes,
+/// e.g. `book`, `./shelf/book`, `@id` or `author/@id`. Descendant paths (`//`), wildcards
+/// and predicates are not supported, constraints using them are skipped with a warning.
+pub struct ConstraintCodeGenerator;
+
+impl ConstraintCodeGenerator {
+    /// Builds the statements of `CheckConstraints`. The values of keys and unique constraints
+    /// are collected first, so key references can be checked against them afterwards.
+    pub(crate) fn build_checks(
+        class_type: &ClassType,
+        hierarchy: &[ClassType],
+        type_aliases: &ResolvedTypeAliases,
+        options: &CodeGenOptions,
+    ) -> ConstraintChecks {
+        let list_type = options.output.list_type;
+        let mut constraints = class_type.identity_constraints.iter().collect::<Vec<_>>();
+        constraints.sort_by_key(|c| matches!(c.kind, IdentityConstraintKind::KeyRef(_)));
+
+        let mut checked_keys = Vec::<&str>::new();
+        let mut lines = Vec::new();
+        let mut max_depth = 0;
+
+        for constraint in constraints {
+            let check = match &constraint.kind {
+                IdentityConstraintKind::KeyRef(refer)
+                    if !checked_keys.contains(&refer.as_str()) =>
+                {
+                    Err(format!(
+                        "the key \"{refer}\" is not checked by the same element"
+                    ))
+                }
+                _ => Self::build_check(class_type, constraint, hierarchy, type_aliases, list_type),
+            };
+
+            match check {
+                Ok((mut check, depth)) => {
+                    checked_keys.push(&constraint.name);
+                    lines.append(&mut check);
+                    max_depth = max_depth.max(depth);
+                }
+                Err(reason) => eprintln!(
+                    "Warning: identity constraint \"{}\" of element \"{}\" is not checked, because {reason}",
+                    constraint.name, constraint.element
+                ),
+            }
+        }
+
+        if lines.is_empty() {
+            return ConstraintChecks::default();
+        }
+
+        // The loops and paths of each selected level are shared by all constraints
+        let mut variables = (1..=max_depth)
+            .flat_map(|level| {
+                [
+                    format!("I{level}: Integer"),
+                    format!("vPath{level}: String"),
+                ]
+            })
+            .collect::<Vec<_>>();
+        variables.push(format!(
+            "vValues: {}",
+            instance_name(Generic::Array, "String", options.output.generics_free)
+        ));
+        variables.push(String::from("vComplete: Boolean"));
+
+        ConstraintChecks {
+            variables,
+            statements: lines,
+        }
+    }
+
+    fn build_check(
+        class_type: &ClassType,
+        constraint: &IdentityConstraint,
+        hierarchy: &[ClassType],
+        type_aliases: &ResolvedTypeAliases,
+        list_type: ListType,
+    ) -> Result<(Vec<String>, usize), String> {
+        let name = as_pascal_literal(&constraint.name);
+        let (kind, call) = match &constraint.kind {
+            IdentityConstraintKind::Key => ("key", format!("AddKey({name}, ")),
+            IdentityConstraintKind::Unique => ("unique", format!("AddUnique({name}, ")),
+            IdentityConstraintKind::KeyRef(refer) => (
+                "keyref",
+                format!("CheckReference({name}, {}, ", as_pascal_literal(refer)),
+            ),
+        };
+
+        if constraint.fields.is_empty() {
+            return Err(String::from("it has no fields"));
+        }
+
+        let mut lines = vec![format!("// xs:{kind} {}", constraint.name)];
+        let mut max_depth = 0;
+
+        for selector in constraint.selector.split('|') {
+            let mut node = SelectedNode {
+                expr: String::from("Self"),
+                path: as_pascal_literal(&constraint.element),
+                data_type: DataType::Custom(class_type.name.clone()),
+            };
+            let mut depth = 0;
+
+            for step in Self::path_steps(selector)? {
+                let variable = Self::child_variable(&node, step, &XMLSource::Element, hierarchy)?;
+                let name = Helper::as_variable_name(&variable.name);
+                let indent = "  ".repeat(depth);
+                let level = depth + 1;
+
+                let (opening, expr, path, data_type) = match &variable.data_type {
+                    DataType::List(item) => (
+                        format!(
+                            "for I{level} := 0 to {} - 1 do begin",
+                            Helper::list_count(&format!("{}.{name}", node.expr), list_type)
+                        ),
+                        format!("{}.{name}[I{level}]", node.expr),
+                        format!(
+                            "{} + {} + IntToStr(I{level} + 1) + ']'",
+                            node.path,
+                            as_pascal_literal(&format!("/{}[", variable.xml_name))
+                        ),
+                        item.as_ref().clone(),
+                    ),
+                    DataType::Custom(_) => (
+                        format!("if Assigned({}.{name}) then begin", node.expr),
+                        format!("{}.{name}", node.expr),
+                        format!(
+                            "{} + {}",
+                            node.path,
+                            as_pascal_literal(&format!("/{}", variable.xml_name))
+                        ),
+                        variable.data_type.clone(),
+                    ),
+                    _ => {
+                        return Err(format!(
+                            "the selected element \"{step}\" is neither a class nor a list"
+                        ))
+                    }
+                };
+
+                lines.push(format!("{indent}{opening}"));
+                lines.push(format!("{indent}  vPath{level} := {path};"));
+
+                node = SelectedNode {
+                    expr,
+                    path: format!("vPath{level}"),
+                    data_type,
+                };
+                depth += 1;
+            }
+
+            let indent = "  ".repeat(depth);
+            lines.push(format!("{indent}vValues := [];"));
+            lines.push(format!("{indent}vComplete := True;"));
+
+            for field in &constraint.fields {
+                let (conditions, value) = Self::field_value(&node, field, hierarchy, type_aliases)?;
+
+                lines.push(match conditions.is_empty() {
+                    true => format!("{indent}vValues := vValues + [{value}];"),
+                    false => format!(
+                        "{indent}if {} then vValues := vValues + [{value}] else vComplete := False;",
+                        conditions.join(" and ")
+                    ),
+                });
+            }
+
+            lines.push(format!(
+                "{indent}vConstraints.{call}{}, vValues, vComplete);",
+                node.path
+            ));
+
+            for level in (0..depth).rev() {
+                lines.push(format!("{}end;", "  ".repeat(level)));
+            }
+            max_depth = max_depth.max(depth);
+        }
+
+        Ok((lines, max_depth))
+    }
+
+    /// Conditions, which are true if the field is present, and the Delphi expression of its value
+    fn field_value(
+        node: &SelectedNode,
+        field: &str,
+        hierarchy: &[ClassType],
+        type_aliases: &ResolvedTypeAliases,
+    ) -> Result<(Vec<String>, String), String> {
+        let mut steps = Self::path_steps(field)?;
+
+        let Some(last) = steps.pop() else {
+            let value = Self::value_as_string(&node.data_type, &node.expr, type_aliases)
+                .ok_or_else(|| format!("the field \"{field}\" doesn't select a value"))?;
+
+            return Ok((vec![], value));
+        };
+
+        let mut conditions = vec![];
+        let mut current = SelectedNode {
+            expr: node.expr.clone(),
+            path: node.path.clone(),
+            data_type: node.data_type.clone(),
+        };
+
+        for step in steps {
+            let variable = Self::child_variable(&current, step, &XMLSource::Element, hierarchy)?;
+
+            if !matches!(variable.data_type, DataType::Custom(_)) {
+                return Err(format!("the field \"{field}\" selects several values"));
+            }
+
+            current.expr = format!(
+                "{}.{}",
+                current.expr,
+                Helper::as_variable_name(&variable.name)
+            );
+            current.data_type = variable.data_type.clone();
+            conditions.push(format!("Assigned({})", current.expr));
+        }
+
+        let variable = match last.strip_prefix('@') {
+            Some(attribute) => {
+                Self::child_variable(&current, attribute, &XMLSource::Attribute, hierarchy)?
+            }
+            None => Self::child_variable(&current, last, &XMLSource::Element, hierarchy)?,
+        };
+
+        let mut expr = format!(
+            "{}.{}",
+            current.expr,
+            Helper::as_variable_name(&variable.name)
+        );
+
+        if !variable.required && !variable.is_const && variable.default_value.is_none() {
+            match variable.data_type {
+                DataType::Uri => conditions.push(format!("Assigned({expr})")),
+                _ => {
+                    conditions.push(format!("{expr}.IsSome"));
+                    expr.push_str(".Unwrap");
+                }
+            }
+        }
+
+        let value = Self::value_as_string(&variable.data_type, &expr, type_aliases)
+            .ok_or_else(|| format!("the field \"{field}\" doesn't select a single value"))?;
+
+        Ok((conditions, value))
+    }
+
+    /// Steps of a path, whose namespace prefixes are removed
+    fn path_steps(path: &str) -> Result<Vec<&str>, String> {
+        let path = path.trim();
+
+        if path.contains("//") || path.contains(['*', '[', '(']) {
+            return Err(format!("the path \"{path}\" is not supported"));
+        }
+
+        Ok(path
+            .split('/')
+            .map(str::trim)
+            .filter(|s| !s.is_empty() && *s != ".")
+            .map(|s| match s.strip_prefix('@') {
+                Some(_) => s,
+                None => s.rsplit(':').next().unwrap_or(s),
+            })
+            .collect())
+    }
+
+    /// Variable of the element or attribute `xml_name` of the class of `node` or its ancestors
+    fn child_variable<'a>(
+        node: &SelectedNode,
+        xml_name: &str,
+        source: &XMLSource,
+        hierarchy: &'a [ClassType],
+    ) -> Result<&'a Variable, String> {
+        let xml_name = xml_name.rsplit(':').next().unwrap_or(xml_name);
+
+        let DataType::Custom(class_name) = &node.data_type else {
+            return Err(format!("\"{xml_name}\" is not a child of a class"));
+        };
+
+        std::iter::successors(hierarchy.iter().find(|c| &c.name == class_name), |c| {
+            let (name, _) = c.super_type.as_ref()?;
+
+            hierarchy.iter().find(|h| &h.name == name)
+        })
+        .take(hierarchy.len())
+        .flat_map(|c| &c.variables)
+        .find(|v| v.xml_name == xml_name && &v.source == source)
+        .ok_or_else(|| format!("\"{xml_name}\" is not a child of \"{class_name}\""))
+    }
+
+    /// Delphi expression of the value as in the xml document, if it is a single value
+    fn value_as_string(
+        data_type: &DataType,
+        expr: &String,
+        type_aliases: &ResolvedTypeAliases,
+    ) -> Option<String> {
+        let value = match data_type {
+            DataType::Enumeration(_) => format!("{expr}.ToXmlValue"),
+            DataType::Alias(name) => {
+                let (data_type, pattern) = Helper::get_alias_data_type(name, type_aliases)?;
+
+                Helper::get_variable_value_as_string(&data_type, expr, &pattern)
+            }
+            _ => Helper::get_variable_value_as_string(data_type, expr, &None),
+        };
+
+        (value != "''").then_some(value)
+    }
+}
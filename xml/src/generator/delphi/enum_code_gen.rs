@@ -1,9 +1,10 @@
 use crate::generator::{
-    code_generator_trait::CodeGenOptions,
+    code_generator_trait::{CodeGenOptions, EnumLookup},
     delphi::template_models::{
         Enumeration as TemplateEnumeration, EnumerationValue as TemplateEnumerationValue,
     },
-    types::Enumeration,
+    internal_representation::InternalRepresentation,
+    types::{DataType, Enumeration},
 };
 
 use super::helper::Helper;
@@ -11,30 +12,97 @@ use super::helper::Helper;
 pub struct EnumCodeGenerator;
 
 impl EnumCodeGenerator {
+    /// Maximum number of values of an enumeration, which can be part of a Delphi set
+    const MAX_SET_VALUES: usize = 256;
+    /// Maximum number of values of an enumeration, whose set can be converted to an `UInt64`
+    const MAX_BITMASK_VALUES: usize = 64;
+
+    /// Replaces lists of enumerations with sets, if `generate_enum_sets` is enabled or
+    /// the enumeration is marked as flags.
+    ///
+    /// Repeated elements become an `EnumSet`, type aliases for a xs:list become an
+    /// `InlineEnumSet`. Enumerations with more values than a set can hold are kept as list.
+    ///
+    /// Returns the names of the enumerations, for which a set type is required.
+    pub(crate) fn apply_enum_sets(
+        internal_representation: &mut InternalRepresentation,
+        options: &CodeGenOptions,
+    ) -> Vec<String> {
+        let fits_into_set = |name: &String| {
+            internal_representation.enumerations.iter().any(|e| {
+                &e.name == name
+                    && (options.output.generate_enum_sets || e.is_flags)
+                    && e.values.len() <= Self::MAX_SET_VALUES
+            })
+        };
+
+        // Flags always get a set type, even if no list of them exists
+        let mut set_names = internal_representation
+            .enumerations
+            .iter()
+            .filter(|e| e.is_flags && e.values.len() <= Self::MAX_SET_VALUES)
+            .map(|e| e.name.clone())
+            .collect::<Vec<_>>();
+        let mut set_aliases = Vec::new();
+
+        for alias in &mut internal_representation.types_aliases {
+            if let DataType::InlineList(item_type) = &alias.for_type {
+                if let DataType::Enumeration(name) = item_type.as_ref() {
+                    if fits_into_set(name) {
+                        set_names.push(name.clone());
+                        set_aliases.push(alias.name.clone());
+                        alias.for_type = DataType::InlineEnumSet(name.clone());
+                    }
+                }
+            }
+        }
+
+        for variable in internal_representation
+            .classes
+            .iter_mut()
+            .chain(std::iter::once(&mut internal_representation.document))
+            .flat_map(|c| c.variables.iter_mut())
+        {
+            match &variable.data_type {
+                DataType::List(item_type) => {
+                    if let DataType::Enumeration(name) = item_type.as_ref() {
+                        if fits_into_set(name) {
+                            set_names.push(name.clone());
+                            variable.data_type = DataType::EnumSet(name.clone());
+                            variable.requires_free = false;
+                        }
+                    }
+                }
+                DataType::Alias(name) if set_aliases.contains(name) => {
+                    variable.requires_free = false;
+                }
+                _ => (),
+            }
+        }
+
+        set_names.sort();
+        set_names.dedup();
+
+        set_names
+    }
+
     pub fn build_template_models<'a>(
         enumerations: &'a [Enumeration],
+        enum_sets: &[String],
         options: &'a CodeGenOptions,
     ) -> Vec<TemplateEnumeration<'a>> {
         enumerations
             .iter()
             .map(|e| {
                 let prefix = Helper::get_enum_variant_prefix(&e.name);
-                let documentations = e
-                    .documentations
-                    .iter()
-                    .flat_map(|d| d.lines())
-                    .collect::<Vec<&str>>();
+                let documentations = Helper::documentation_lines(&e.documentations);
                 let line_per_variant = e.values.iter().any(|v| !v.documentations.is_empty());
 
                 let values = e
                     .values
                     .iter()
                     .map(|v| {
-                        let documentations = v
-                            .documentations
-                            .iter()
-                            .flat_map(|d| d.lines())
-                            .collect::<Vec<&str>>();
+                        let documentations = Helper::documentation_lines(&v.documentations);
 
                         TemplateEnumerationValue {
                             variant_name: prefix.clone()
@@ -45,13 +113,32 @@ impl EnumCodeGenerator {
                     })
                     .collect::<Vec<TemplateEnumerationValue<'a>>>();
 
+                let lookup_values = match options.serialization.enum_lookup {
+                    EnumLookup::IfChain => vec![],
+                    EnumLookup::BinarySearch => {
+                        let mut lookup_values = values.clone();
+                        // CompareStr compares the UTF-16 code units
+                        lookup_values.sort_by(|a, b| {
+                            a.xml_value.encode_utf16().cmp(b.xml_value.encode_utf16())
+                        });
+
+                        lookup_values
+                    }
+                };
+
                 TemplateEnumeration {
-                    name: Helper::as_type_name(&e.name, &options.type_prefix),
+                    name: Helper::as_type_name(&e.name, &options.naming.type_prefix),
                     qualified_name: &e.qualified_name,
                     variant_prefix: prefix,
                     values,
                     documentations,
                     line_per_variant,
+                    set_name: enum_sets.contains(&e.name).then(|| {
+                        Helper::as_type_name(&format!("{}Set", e.name), &options.naming.type_prefix)
+                    }),
+                    lookup_values,
+                    is_flags: e.is_flags,
+                    fits_into_bitmask: e.values.len() <= Self::MAX_BITMASK_VALUES,
                 }
             })
             .collect::<Vec<TemplateEnumeration<'a>>>()
@@ -0,0 +1,248 @@
+use std::collections::HashSet;
+
+use crate::generator::{
+    code_generator_trait::{CodeGenOptions, ListType},
+    types::{DataType, ResolvedTypeAliases, Variable},
+};
+
+use super::{
+    helper::Helper,
+    template_models::{GenericInstance, GenericKind},
+};
+
+/// Generic types used by the generated units. `TSome<T>` and `TNone<T>` are the subclasses
+/// of `TOptional<T>` and declared together with it.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Generic {
+    ObjectList,
+    List,
+    Array,
+    Optional,
+    Some,
+    None,
+}
+
+impl Generic {
+    /// Generic type named `name` in the templates, e.g. `TList`
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "TObjectList" => Some(Self::ObjectList),
+            "TList" => Some(Self::List),
+            "TArray" => Some(Self::Array),
+            "TOptional" => Some(Self::Optional),
+            "TSome" => Some(Self::Some),
+            "TNone" => Some(Self::None),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::ObjectList => "TObjectList",
+            Self::List => "TList",
+            Self::Array => "TArray",
+            Self::Optional => "TOptional",
+            Self::Some => "TSome",
+            Self::None => "TNone",
+        }
+    }
+
+    /// Prefix of the name of the concrete type, which is followed by the suffix of the item type
+    fn prefix(self) -> &'static str {
+        match self {
+            Self::ObjectList => "TObjectListOf",
+            Self::List => "TListOf",
+            Self::Array => "TArrayOf",
+            _ => self.name(),
+        }
+    }
+
+    fn kind(self) -> GenericKind {
+        match self {
+            Self::ObjectList | Self::List => GenericKind::List,
+            Self::Array => GenericKind::Array,
+            Self::Optional | Self::Some | Self::None => GenericKind::Optional,
+        }
+    }
+}
+
+/// Instance of `generic` for `item_type`, e.g. `TList<Integer>`, or its concrete type
+/// `TListOfInteger`, if the unit is generated without generics
+pub(crate) fn instance_name(generic: Generic, item_type: &str, generics_free: bool) -> String {
+    match generics_free {
+        true => format!("{}{}", generic.prefix(), type_suffix(item_type)),
+        false => format!("{}<{item_type}>", generic.name()),
+    }
+}
+
+/// Concrete types of the generic instances used by a unit without generics in the order
+/// of their declaration, so the instances of item types are declared before their lists.
+pub(crate) struct GenericInstances<'a> {
+    options: &'a CodeGenOptions,
+    /// Item types, which are compared by reference
+    object_types: HashSet<String>,
+    /// Aliases and records, whose instances are declared after them
+    late_types: HashSet<String>,
+    instances: Vec<GenericInstance>,
+}
+
+impl<'a> GenericInstances<'a> {
+    pub(crate) fn new(
+        options: &'a CodeGenOptions,
+        object_types: HashSet<String>,
+        late_types: HashSet<String>,
+    ) -> Self {
+        Self {
+            options,
+            object_types,
+            late_types,
+            instances: vec![],
+        }
+    }
+
+    /// Adds the instance of `generic` for `item_type` and returns its name
+    pub(crate) fn add(&mut self, generic: Generic, item_type: &str) -> String {
+        // `TSome<T>` and `TNone<T>` are declared by the instance of `TOptional<T>`
+        let declared = match generic.kind() {
+            GenericKind::Optional => Generic::Optional,
+            _ => generic,
+        };
+        let name = instance_name(declared, item_type, true);
+
+        if !self.instances.iter().any(|i| i.name == name) {
+            let owns_items = generic == Generic::ObjectList;
+
+            self.instances.push(GenericInstance {
+                kind: generic.kind(),
+                name: name.clone(),
+                suffix: type_suffix(item_type),
+                item_type: item_type.to_owned(),
+                owns_items,
+                is_object: owns_items || self.object_types.contains(item_type),
+                after_aliases: self.late_types.contains(item_type)
+                    || self
+                        .instances
+                        .iter()
+                        .any(|i| i.name == item_type && i.after_aliases),
+            });
+        }
+
+        instance_name(generic, item_type, true)
+    }
+
+    /// Adds the instances of the lists of `data_type`, inner lists first,
+    /// and returns its Delphi type
+    pub(crate) fn add_data_type(&mut self, data_type: &DataType) -> String {
+        match data_type {
+            DataType::List(item) | DataType::InlineList(item) => {
+                let item_type = self.add_data_type(item);
+
+                match **item {
+                    DataType::Custom(_) => self.add(Generic::ObjectList, &item_type),
+                    _ => self.add(Generic::List, &item_type),
+                }
+            }
+            DataType::FixedSizeList(item, _) => self.add_data_type(item),
+            _ => Helper::get_datatype_language_representation(
+                data_type,
+                &self.options.naming.type_prefix,
+                true,
+            ),
+        }
+    }
+
+    /// Adds the instances of the type of a field, see `Helper::get_variable_type_representation`,
+    /// and of its optional wrapper
+    pub(crate) fn add_variable(&mut self, variable: &Variable, type_aliases: &ResolvedTypeAliases) {
+        let list_type = Helper::variable_list_type(variable, self.options.output.list_type);
+        let data_type = match (&variable.data_type, list_type) {
+            (DataType::List(item), ListType::List) => {
+                let item_type = self.add_data_type(item);
+                self.add(Generic::List, &item_type)
+            }
+            (DataType::List(item), ListType::Array) => {
+                let item_type = self.add_data_type(item);
+                self.add(Generic::Array, &item_type)
+            }
+            _ => self.add_data_type(&variable.data_type),
+        };
+
+        if variable.needs_optional_wrapper(type_aliases) {
+            self.add(Generic::Optional, &data_type);
+        }
+    }
+
+    pub(crate) fn into_instances(self) -> Vec<GenericInstance> {
+        self.instances
+    }
+}
+
+/// Name of the item type without its unit and the `T` prefix, e.g. `Integer` or `Person`
+/// for `TPerson`
+fn type_suffix(item_type: &str) -> String {
+    let name = item_type.rsplit('.').next().unwrap_or(item_type);
+
+    match name.strip_prefix('T') {
+        Some(rest) if rest.starts_with(|c: char| c.is_ascii_uppercase()) => rest.to_owned(),
+        _ => name.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn instances_of_inner_lists_are_added_first() {
+        let options = CodeGenOptions::default();
+        let mut instances = GenericInstances::new(
+            &options,
+            HashSet::new(),
+            HashSet::from([String::from("TDateTime")]),
+        );
+
+        let items = instances.add_data_type(&DataType::List(Box::new(DataType::Custom(
+            String::from("Item"),
+        ))));
+        let matrix = instances.add_data_type(&DataType::List(Box::new(DataType::InlineList(
+            Box::new(DataType::Integer),
+        ))));
+        let none = instances.add(Generic::None, "TDateTime");
+        instances.add(Generic::Optional, "TDateTime");
+
+        assert_eq!(
+            (items.as_str(), matrix.as_str(), none.as_str()),
+            ("TObjectListOfItem", "TListOfListOfInteger", "TNoneDateTime")
+        );
+
+        let names = instances
+            .into_instances()
+            .into_iter()
+            .map(|i| (i.name, i.owns_items, i.after_aliases))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            names,
+            [
+                (String::from("TObjectListOfItem"), true, false),
+                (String::from("TListOfInteger"), false, false),
+                (String::from("TListOfListOfInteger"), false, false),
+                (String::from("TOptionalDateTime"), false, true),
+            ]
+        );
+    }
+
+    #[test]
+    fn instance_name_keeps_generics_by_default() {
+        assert_eq!(
+            instance_name(Generic::ObjectList, "TItem", false),
+            "TObjectList<TItem>"
+        );
+        assert_eq!(
+            instance_name(Generic::ObjectList, "TItem", true),
+            "TObjectListOfItem"
+        );
+    }
+}
@@ -1,6 +1,12 @@
+use pascal::as_pascal_literal;
 use unicode_segmentation::UnicodeSegmentation;
 
-use crate::generator::types::{BinaryEncoding, DataType, TypeAlias};
+use crate::generator::{
+    code_generator_trait::ListType,
+    types::{BinaryEncoding, DataType, ResolvedTypeAliases, Variable},
+};
+
+use super::generics_free::{instance_name, Generic};
 
 pub struct Helper;
 
@@ -15,7 +21,7 @@ impl Helper {
     ];
 
     #[inline]
-    pub(crate) fn first_char_uppercase(name: &String) -> String {
+    pub(crate) fn first_char_uppercase(name: &str) -> String {
         let mut graphemes = name.graphemes(true);
 
         graphemes
@@ -25,7 +31,7 @@ impl Helper {
 
     #[allow(dead_code)]
     #[inline]
-    pub(crate) fn first_char_lowercase(name: &String) -> String {
+    pub(crate) fn first_char_lowercase(name: &str) -> String {
         let mut graphemes = name.graphemes(true);
 
         graphemes
@@ -34,7 +40,7 @@ impl Helper {
     }
 
     #[inline]
-    pub(crate) fn as_type_name(name: &String, prefix: &Option<String>) -> String {
+    pub(crate) fn as_type_name(name: &str, prefix: &Option<String>) -> String {
         if name.is_empty() {
             return String::new();
         }
@@ -87,31 +93,42 @@ impl Helper {
         String::from_iter(prefix)
     }
 
+    /// Delphi type of the data type. Lists are generated as their concrete types,
+    /// if the unit is `generics_free`.
     pub(crate) fn get_datatype_language_representation(
         datatype: &DataType,
         prefix: &Option<String>,
+        generics_free: bool,
     ) -> String {
         match datatype {
             DataType::Boolean => String::from("Boolean"),
             DataType::DateTime => String::from("TDateTime"),
+            DataType::DateTimeOffset => String::from("TXmlDateTime"),
             DataType::Date => String::from("TDate"),
             DataType::Double => String::from("Double"),
+            DataType::Currency => String::from("Currency"),
+            DataType::Bcd => String::from("TBcd"),
             DataType::Binary(_) => String::from("TBytes"),
             DataType::String => String::from("String"),
             DataType::Time => String::from("TTime"),
             DataType::Uri => String::from("TURI"),
             DataType::Alias(a) => Self::as_type_name(a, prefix),
             DataType::Enumeration(e) => Self::as_type_name(e, prefix),
-            DataType::Custom(c) => Self::as_type_name(c, prefix),
+            DataType::EnumSet(e) | DataType::InlineEnumSet(e) => {
+                Self::as_type_name(&format!("{e}Set"), prefix)
+            }
+            DataType::Custom(c) | DataType::Record(c) => Self::as_type_name(c, prefix),
             DataType::Union(u) => Self::as_type_name(u, prefix),
-            DataType::FixedSizeList(t, _) => Self::get_datatype_language_representation(t, prefix),
+            DataType::FixedSizeList(t, _) => {
+                Self::get_datatype_language_representation(t, prefix, generics_free)
+            }
             DataType::List(lt) | DataType::InlineList(lt) => {
-                let gt = Self::get_datatype_language_representation(lt, prefix);
+                let gt = Self::get_datatype_language_representation(lt, prefix, generics_free);
 
                 if let DataType::Custom(_) = **lt {
-                    format!("TObjectList<{gt}>")
+                    instance_name(Generic::ObjectList, &gt, generics_free)
                 } else {
-                    format!("TList<{gt}>")
+                    instance_name(Generic::List, &gt, generics_free)
                 }
             }
             DataType::ShortInteger => String::from("ShortInt"),
@@ -125,6 +142,48 @@ impl Helper {
         }
     }
 
+    /// Delphi type of a variable, whose lists of repeated elements are generated as `list_type`
+    pub(crate) fn get_variable_type_representation(
+        data_type: &DataType,
+        list_type: ListType,
+        prefix: &Option<String>,
+        generics_free: bool,
+    ) -> String {
+        let generic = match list_type {
+            ListType::List => Generic::List,
+            ListType::Array => Generic::Array,
+            ListType::ObjectList => {
+                return Self::get_datatype_language_representation(data_type, prefix, generics_free)
+            }
+        };
+
+        match data_type {
+            DataType::List(lt) => instance_name(
+                generic,
+                &Self::get_datatype_language_representation(lt, prefix, generics_free),
+                generics_free,
+            ),
+            _ => Self::get_datatype_language_representation(data_type, prefix, generics_free),
+        }
+    }
+
+    /// List type of the variable. Lists of objects, which the class doesn't own, are generated
+    /// as `TList` instead of `TObjectList`, so their items aren't freed with the list.
+    pub(crate) fn variable_list_type(variable: &Variable, list_type: ListType) -> ListType {
+        match (variable.is_owned, list_type) {
+            (false, ListType::ObjectList) => ListType::List,
+            _ => list_type,
+        }
+    }
+
+    /// Delphi expression of the number of items of a list of repeated elements
+    pub(crate) fn list_count(name: &str, list_type: ListType) -> String {
+        match list_type {
+            ListType::Array => format!("Length({name})"),
+            ListType::ObjectList | ListType::List => format!("{name}.Count"),
+        }
+    }
+
     pub(crate) fn get_variable_value_as_string(
         data_type: &DataType,
         variable_name: &String,
@@ -135,24 +194,32 @@ impl Helper {
                 format!("IfThen({variable_name}, cnXmlTrueValue, cnXmlFalseValue)")
             }
             DataType::DateTime | DataType::Date if pattern.is_some() => format!(
-                "FormatDateTime('{}', {})",
-                pattern.clone().unwrap_or_default(),
+                "FormatDateTime({}, {})",
+                as_pascal_literal(&pattern.clone().unwrap_or_default()),
                 variable_name,
             ),
-            DataType::DateTime | DataType::Date => format!("DateToISO8601({variable_name})"),
+            DataType::DateTime | DataType::Date => {
+                format!("DateToISO8601({variable_name}, cnXmlDateTimeIsUtc)")
+            }
+            DataType::DateTimeOffset => format!("{variable_name}.ToXml"),
             DataType::Double => format!("FloatToStr({variable_name})"),
+            DataType::Currency => {
+                format!("CurrToStr({variable_name}, TFormatSettings.Invariant)")
+            }
+            DataType::Bcd => format!("BcdToStr({variable_name}, TFormatSettings.Invariant)"),
             DataType::Binary(BinaryEncoding::Base64) => {
                 format!("TNetEncoding.Base64.EncodeBytesToString({variable_name})")
             }
             DataType::Binary(BinaryEncoding::Hex) => format!("BinToHexStr({variable_name})"),
             DataType::String => variable_name.to_string(),
             DataType::Time if pattern.is_some() => format!(
-                "EncodeTime({}, '{}')",
+                "EncodeTime({}, {})",
                 variable_name,
-                pattern.clone().unwrap_or_default(),
+                as_pascal_literal(&pattern.clone().unwrap_or_default()),
             ),
             DataType::Time => format!("TimeToStr({variable_name})"),
             DataType::Uri => format!("{variable_name}.ToString"),
+            DataType::InlineEnumSet(_) => format!("{variable_name}.ToXmlValue"),
             DataType::SmallInteger
             | DataType::ShortInteger
             | DataType::Integer
@@ -167,28 +234,63 @@ impl Helper {
 
     pub(crate) fn get_alias_data_type(
         alias: &str,
-        type_aliases: &[TypeAlias],
+        type_aliases: &ResolvedTypeAliases,
     ) -> Option<(DataType, Option<String>)> {
-        if let Some(t) = type_aliases.iter().find(|t| t.name == alias) {
-            let mut pattern = t.pattern.clone();
-            let mut data_type = t.for_type.clone();
+        type_aliases.get(alias).cloned()
+    }
 
-            while let DataType::Custom(n) = &data_type {
-                if let Some(alias) = type_aliases.iter().find(|t| t.name == n.as_str()) {
-                    if pattern.is_none() {
-                        pattern = alias.pattern.clone();
-                    }
+    /// Returns the source lines of a Delphi string expression for `value`. Lines are joined
+    /// by `sLineBreak`, see [as_pascal_literal] for the escaping of a line.
+    pub(crate) fn as_string_literal(value: &str) -> Vec<String> {
+        let lines = value.lines().collect::<Vec<_>>();
 
-                    data_type = alias.for_type.clone();
-                } else {
-                    break;
+        if lines.is_empty() {
+            return vec![String::from("''")];
+        }
+
+        lines
+            .iter()
+            .enumerate()
+            .map(|(i, line)| {
+                let mut literal = as_pascal_literal(line);
+
+                if i + 1 < lines.len() {
+                    literal.push_str(" + sLineBreak +");
                 }
-            }
 
-            return Some((data_type, pattern));
-        }
+                literal
+            })
+            .collect()
+    }
+
+    /// Lines of the documentations for `//` comments. Besides line feeds, carriage returns
+    /// end a line, because Delphi treats them as line break as well.
+    pub(crate) fn documentation_lines(documentations: &[String]) -> Vec<&str> {
+        documentations
+            .iter()
+            .flat_map(|d| d.lines())
+            .flat_map(|l| l.split('\r'))
+            .collect()
+    }
+
+    /// Delphi expression of the xs:default or xs:fixed value of a variable. Values of strings
+    /// are written as literal, the values of other types are valid Delphi expressions as is.
+    pub(crate) fn as_default_value(
+        value: &str,
+        data_type: &DataType,
+        type_aliases: &ResolvedTypeAliases,
+    ) -> String {
+        let data_type = match data_type {
+            DataType::Alias(name) => Self::get_alias_data_type(name, type_aliases)
+                .map(|(data_type, _)| data_type)
+                .unwrap_or_else(|| data_type.clone()),
+            _ => data_type.clone(),
+        };
 
-        None
+        match data_type {
+            DataType::String => as_pascal_literal(value),
+            _ => value.to_owned(),
+        }
     }
 }
 
@@ -196,13 +298,96 @@ impl Helper {
 mod tests {
     use pretty_assertions::assert_eq;
 
-    use crate::generator::types::BinaryEncoding;
+    use crate::generator::types::{BinaryEncoding, TypeAlias};
 
     use super::*;
 
+    fn alias(name: &str, for_type: DataType, pattern: Option<&str>) -> TypeAlias {
+        TypeAlias {
+            name: name.to_owned(),
+            qualified_name: name.to_owned(),
+            for_type,
+            pattern: pattern.map(str::to_owned),
+            total_digits: None,
+            fraction_digits: None,
+            documentations: vec![],
+            is_documented: false,
+        }
+    }
+
+    #[test]
+    fn string_literal_is_split_into_lines_and_literals() {
+        let long_line = "a".repeat(300);
+        let res = Helper::as_string_literal(&format!("it's\n\n{long_line}"));
+
+        assert_eq!(
+            res,
+            vec![
+                String::from("'it''s' + sLineBreak +"),
+                String::from("'' + sLineBreak +"),
+                format!("'{}' + '{}'", "a".repeat(255), "a".repeat(45)),
+            ]
+        );
+    }
+
+    #[test]
+    fn documentation_lines_end_at_carriage_returns() {
+        let documentations = [
+            String::from("first\r\nsecond\rthird"),
+            String::from("fourth"),
+        ];
+        let res = Helper::documentation_lines(&documentations);
+
+        assert_eq!(res, vec!["first", "second", "third", "fourth"]);
+    }
+
+    #[test]
+    fn default_value_of_string_is_written_as_literal() {
+        let aliases = [alias("Code", DataType::String, None)];
+        let aliases = ResolvedTypeAliases::new(&aliases);
+
+        assert_eq!(
+            Helper::as_default_value("it's", &DataType::String, &aliases),
+            "'it''s'"
+        );
+        assert_eq!(
+            Helper::as_default_value("A-1", &DataType::Alias(String::from("Code")), &aliases),
+            "'A-1'"
+        );
+        assert_eq!(
+            Helper::as_default_value("42", &DataType::Integer, &aliases),
+            "42"
+        );
+    }
+
+    #[test]
+    fn alias_data_type_is_resolved_through_its_base_aliases() {
+        let aliases = [
+            alias("PostalCode", DataType::Custom(String::from("Code")), None),
+            alias(
+                "Code",
+                DataType::Custom(String::from("Text")),
+                Some("[A-Z]+"),
+            ),
+            alias("Text", DataType::String, Some(".*")),
+            alias("Amount", DataType::Double, None),
+        ];
+        let aliases = ResolvedTypeAliases::new(&aliases);
+
+        assert_eq!(
+            Helper::get_alias_data_type("PostalCode", &aliases),
+            Some((DataType::String, Some(String::from("[A-Z]+"))))
+        );
+        assert_eq!(
+            Helper::get_alias_data_type("Amount", &aliases),
+            Some((DataType::Double, None))
+        );
+        assert_eq!(Helper::get_alias_data_type("Unknown", &aliases), None);
+    }
+
     #[test]
     fn first_char_uppercase_with_empty_string() {
-        let res = Helper::first_char_uppercase(&String::new());
+        let res = Helper::first_char_uppercase("");
 
         assert_eq!(res, "");
     }
@@ -216,7 +401,7 @@ mod tests {
 
     #[test]
     fn first_char_lowercase_with_empty_string() {
-        let res = Helper::first_char_lowercase(&String::new());
+        let res = Helper::first_char_lowercase("");
 
         assert_eq!(res, "");
     }
@@ -230,7 +415,7 @@ mod tests {
 
     #[test]
     fn as_type_name_with_empty_string() {
-        let res = Helper::as_type_name(&String::new(), &None);
+        let res = Helper::as_type_name("", &None);
 
         assert_eq!(res, "");
     }
@@ -244,21 +429,21 @@ mod tests {
 
     #[test]
     fn as_variable_name_with_empty_string() {
-        let res = Helper::as_variable_name(&String::new());
+        let res = Helper::as_variable_name("");
 
         assert_eq!(res, "");
     }
 
     #[test]
     fn as_variable_name_with_nonempty_string() {
-        let res = Helper::as_variable_name(&"vorname".to_owned());
+        let res = Helper::as_variable_name("vorname");
 
         assert_eq!(res, "Vorname");
     }
 
     #[test]
     fn as_variable_name_with_reserved_word() {
-        let res = Helper::as_variable_name(&"label".to_owned());
+        let res = Helper::as_variable_name("label");
 
         assert_eq!(res, "Label_");
     }
@@ -292,7 +477,7 @@ mod tests {
 
         let lr = types
             .into_iter()
-            .map(|dt| Helper::get_datatype_language_representation(&dt, &None))
+            .map(|dt| Helper::get_datatype_language_representation(&dt, &None, false))
             .collect::<Vec<String>>();
 
         let expected = vec![
@@ -1,8 +1,11 @@
 mod alias_code_gen;
+mod assertion_code_gen;
 mod class_code_gen;
 pub mod code_generator;
 mod code_writer;
+mod constraint_code_gen;
 mod enum_code_gen;
-mod helper;
+mod generics_free;
+pub(crate) mod helper;
 mod template_models;
 mod union_type_code_gen;
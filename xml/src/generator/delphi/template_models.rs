@@ -1,5 +1,73 @@
 use serde::Serialize;
 
+use crate::generator::types::AttributeNamespace;
+
+/// Context of the `models.pas` template
+#[derive(Clone, Debug, Serialize, Eq, PartialEq)]
+pub struct ModelsUnit<'a> {
+    #[serde(rename = "unitName")]
+    pub unit_name: &'a String,
+    pub header: Option<String>,
+    pub crate_version: &'static str,
+    pub documentations: Vec<&'a str>,
+    // helper
+    pub gen_from_xml: bool,
+    pub gen_to_xml: bool,
+    pub gen_datetime_helper: bool,
+    /// xs:dateTime is generated as `TXmlDateTime`, which keeps the offset
+    pub gen_date_time_offset: bool,
+    /// `TDateTime` values are in UTC instead of the local time zone
+    pub date_time_is_utc: bool,
+    pub gen_hex_binary_helper: bool,
+    pub gen_bool_consts: bool,
+    pub needs_net_encoding_unit_use_clause: bool,
+    pub needs_fmt_bcd_unit_use_clause: bool,
+    pub gen_preserved_nodes: bool,
+    /// Indentation of the nested elements written by `ToXml`, missing for compact xml
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub xml_indentation: Option<String>,
+    /// `ToXml` writes the xml declaration
+    pub xml_declaration: bool,
+    /// Value of the standalone attribute of the xml declaration written by `ToXml`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub xml_standalone: Option<&'static str>,
+    pub gen_debug_dump: bool,
+    pub gen_serializer_attributes: bool,
+    pub gen_constraint_checks: bool,
+    /// Declares `TSchemaMetadata`, which looks up the xs:appinfo of the types
+    pub gen_metadata_registry: bool,
+    /// Constants of the xs:appinfo of the types and the xs:notation elements
+    pub metadata_constants: Vec<MetadataConstant>,
+    /// Declares the pattern constants and validation functions of the string aliases
+    pub gen_pattern_validation: bool,
+    /// Include file with manual code, which is included into every class and the implementation
+    pub include_file: Option<String>,
+    /// Generic types are replaced by the concrete `generic_instances`
+    pub generics_free: bool,
+    pub generic_instances: Vec<GenericInstance>,
+    // types
+    pub document: ClassType<'a>,
+    pub records: Vec<ClassType<'a>>,
+    pub classes: Vec<ClassType<'a>>,
+    pub enumerations: Vec<Enumeration<'a>>,
+    pub type_aliases: Vec<TypeAlias<'a>>,
+    pub union_types: Vec<UnionType<'a>>,
+    pub union_classes: Vec<UnionType<'a>>,
+    /// `type_aliases` and `union_types` in the order of their declarations
+    pub value_types: Vec<ValueType<'a>>,
+    pub external_classes: Vec<ExternalClass<'a>>,
+    /// Units declaring the external classes
+    pub external_units: Vec<&'a str>,
+}
+
+#[derive(Clone, Debug, Serialize, Eq, PartialEq)]
+pub struct ExternalClass<'a> {
+    pub name: String,
+    pub qualified_name: &'a String,
+    pub delphi_type: &'a String,
+    pub unit: &'a String,
+}
+
 #[derive(Clone, Debug, Serialize, Eq, PartialEq)]
 pub struct ClassType<'a> {
     pub name: String,
@@ -11,17 +79,103 @@ pub struct ClassType<'a> {
     pub variables: Vec<Variable<'a>>,
     pub optional_variables: Vec<Variable<'a>>,
     pub constant_variables: Vec<Variable<'a>>,
+    /// Element variables in content model order
     pub serialize_variables: Vec<SerializeVariable<'a>>,
+    pub serialize_attribute_variables: Vec<SerializeVariable<'a>>,
     // initializer
     pub variable_initializer: Vec<String>,
     // deserialize
     pub has_optional_element_variables: bool,
     pub deserialize_attribute_variables: Vec<AttributeDeserializeVariable<'a>>,
     pub deserialize_element_variables: Vec<ElementDeserializeVariable<'a>>,
+    /// Lists, whose number of items is checked against the bounds of the schema
+    pub occurrence_checks: Vec<OccurrenceCheck<'a>>,
+    /// Statements of `CheckConstraints`, empty if the class checks no identity constraints
+    pub constraint_checks: Vec<String>,
+    /// Declarations of the local variables of `CheckConstraints`, e.g. `I1: Integer`
+    pub constraint_variables: Vec<String>,
+    /// Statements of `CheckAssertions`, empty if the class checks no xs:assert of its own
+    pub assertion_checks: Vec<String>,
+    /// An ancestor declares `CheckAssertions`, whose result is extended
+    pub overrides_assertion_checks: bool,
+    /// Optional elements and attributes, whose presence in the xml is recorded by `FromXml`
+    pub presence_flags: Vec<PresenceFlag<'a>>,
     //
+    /// Generated as value type instead of class
+    pub is_record: bool,
+    /// Document, which keeps its comments and processing instructions
+    pub preserves_nodes: bool,
+    /// Missing or invalid required values are collected as warnings by `FromXml`
+    pub collects_warnings: bool,
+    /// Declares the list of warnings, descendants use the list of their ancestor
+    pub declares_warnings: bool,
+    /// Declares `IsKnownXmlNode`, which `FromXml` of the topmost ancestor uses to find unknown nodes
+    pub checks_unknown_nodes: bool,
+    /// Declares the list of unknown nodes, descendants use the list of their ancestor
+    pub declares_unknown_nodes: bool,
+    /// Raises an exception for unknown nodes instead of collecting them
+    pub raises_on_unknown_nodes: bool,
+    /// Names of the child elements and attributes read by `FromXml` of the class itself
+    pub known_elements: Vec<&'a String>,
+    pub known_attributes: Vec<&'a String>,
     pub needs_destructor: bool,
     pub has_optional_fields: bool,
     pub has_constant_fields: bool,
+    /// `abstract` complex type, instances are created by `FromXmlByType`
+    pub is_abstract: bool,
+    /// Topmost abstract class, which declares the abstract `XmlTypeName`
+    pub declares_xml_type_name: bool,
+    /// Concrete class derived from an abstract class, which writes its `XmlTypeName` as `xsi:type`
+    pub overrides_xml_type_name: bool,
+    pub xml_type_name: &'a str,
+    /// Concrete classes derived from an abstract class
+    pub derived_classes: Vec<DerivedClass<'a>>,
+    /// Child elements matched by the `xs:any` wildcard of the class
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub any_elements: Option<AnyElements<'a>>,
+}
+
+#[derive(Clone, Debug, Serialize, Eq, PartialEq)]
+pub struct AnyElements<'a> {
+    /// Delphi expression, which is true if the namespace of `vChild` is allowed by the wildcard.
+    /// Missing, if elements of any namespace are allowed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub namespace_condition: Option<String>,
+    /// Names of the elements declared by the class and its ancestors, which are not matched
+    pub declared_elements: Vec<&'a String>,
+    /// Global elements of the schema, which are allowed by `processContents="strict"`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strict_elements: Option<Vec<&'a String>>,
+    /// Lines of the comment of the field, which describe the wildcard
+    pub description: Vec<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Eq, PartialEq)]
+pub struct DerivedClass<'a> {
+    pub name: String,
+    pub xml_type_name: &'a str,
+}
+
+#[derive(Clone, Debug, Serialize, Eq, PartialEq)]
+pub struct OccurrenceCheck<'a> {
+    pub name: String,
+    pub xml_name: &'a String,
+    /// Delphi expression, which is true if the number of items is out of bounds
+    pub condition: String,
+    /// Delphi expression of the number of items
+    pub count: String,
+    /// Dynamic array, which is never `nil`
+    pub is_array: bool,
+    /// Expected number of items, e.g. `2 to 5 times`
+    pub expected: String,
+}
+
+#[derive(Clone, Debug, Serialize, Eq, PartialEq)]
+pub struct PresenceFlag<'a> {
+    pub name: String,
+    pub xml_name: &'a String,
+    pub is_attribute: bool,
+    pub namespace: Option<&'a AttributeNamespace>,
 }
 
 #[derive(Clone, Debug, Serialize, Eq, PartialEq)]
@@ -30,9 +184,17 @@ pub struct Variable<'a> {
     pub data_type_repr: String,
     pub xml_name: &'a String,
     pub requires_free: bool,
+    /// Items of classes, which are not owned by the list and freed by the destructor
+    pub frees_items: bool,
+    /// Type of the items of a list, which are iterated by the local `__{name}Item`
+    pub item_type_repr: Option<String>,
     pub required: bool,
-    pub default_value: &'a Option<String>,
+    pub default_value: Option<String>,
     pub documentations: Vec<&'a str>,
+    /// Custom attribute naming the xml node of the field, e.g. `XmlElement('name')`
+    pub serializer_attribute: Option<String>,
+    /// Validation function of the pattern, which is called by the setter of the property
+    pub pattern_validator: Option<String>,
 }
 
 #[derive(Clone, Debug, Serialize, Eq, PartialEq)]
@@ -44,10 +206,16 @@ pub struct SerializeVariable<'a> {
     pub is_enum: bool,
     pub is_list: bool,
     pub is_inline_list: bool,
+    /// Repeated sequence, whose items are written into the parent node
+    pub is_group: bool,
+    /// Type of the items of a list, which are iterated by the local `__{name}Item`
+    pub item_type_repr: Option<String>,
     pub is_required: bool,
     pub has_optional_wrapper: bool,
     pub from_xml_code: String,
     pub to_xml_code: String,
+    /// Namespace of an attribute with qualified form, which is written with `SetAttributeNS`
+    pub namespace: Option<&'a AttributeNamespace>,
 }
 
 #[derive(Clone, Debug, Serialize, Eq, PartialEq)]
@@ -57,7 +225,13 @@ pub struct AttributeDeserializeVariable<'a> {
     //
     pub has_optional_wrapper: bool,
     pub from_xml_code_available: String,
-    pub from_xml_code_missing: String,
+    /// `None`, if a missing attribute raises an exception
+    pub from_xml_code_missing: Option<String>,
+    pub data_type_repr: String,
+    /// Add a warning, when the required attribute is missing
+    pub warn_if_missing: bool,
+    /// Namespace of an attribute with qualified form, which is read with `GetAttributeNS`
+    pub namespace: Option<&'a AttributeNamespace>,
 }
 
 #[derive(Clone, Debug, Serialize, Eq, PartialEq)]
@@ -66,8 +240,14 @@ pub struct ElementDeserializeVariable<'a> {
     pub xml_name: &'a String,
     //
     pub is_required: bool,
+    /// Values are included into a set instead of added to a list
+    pub is_set: bool,
     pub is_list: bool,
+    /// Dynamic array, whose items are appended instead of added
+    pub is_array: bool,
     pub is_inline_list: bool,
+    /// Repeated sequence, whose items start at each `xml_name` child node
+    pub is_group: bool,
     pub is_fixed_size_list: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub fixed_size_list_size: Option<usize>,
@@ -85,6 +265,14 @@ pub struct Enumeration<'a> {
     //
     pub variant_prefix: String,
     pub line_per_variant: bool,
+    /// Name of the set type, if the enumeration is used as set
+    pub set_name: Option<String>,
+    /// Values sorted by their xml value for the binary search of `FromXmlValue`,
+    /// empty if the values are compared in turn
+    pub lookup_values: Vec<EnumerationValue<'a>>,
+    pub is_flags: bool,
+    /// Whether the set helper converts from and to an `UInt64` bitmask
+    pub fits_into_bitmask: bool,
 }
 
 #[derive(Clone, Debug, Serialize, Eq, PartialEq)]
@@ -101,16 +289,33 @@ pub struct TypeAlias<'a> {
     pub data_type_repr: String,
     pub pattern: &'a Option<String>,
     pub documentations: Vec<&'a str>,
+    /// Constant of the own xs:pattern of a string alias, e.g. `cnPostalCodePattern`
+    pub pattern_constant: Option<String>,
+    /// Source lines of the string expression of the pattern
+    pub pattern_value: Vec<String>,
+    /// Function checking the patterns of the alias and of the aliases it is derived from
+    pub validator: Option<String>,
+    /// Validation function of the alias this alias is derived from
+    pub base_validator: Option<String>,
 }
 
 #[derive(Clone, Debug, Serialize, Eq, PartialEq)]
 pub struct UnionType<'a> {
     pub name: String,
     pub qualified_name: &'a String,
+    pub is_class_hierarchy: bool,
     pub variants: Vec<UnionVariant>,
     pub documentations: Vec<&'a str>,
 }
 
+/// Alias or union type, which is declared in the aliases region
+#[derive(Clone, Debug, Serialize, Eq, PartialEq)]
+#[serde(tag = "kind")]
+pub enum ValueType<'a> {
+    Alias(TypeAlias<'a>),
+    Union(UnionType<'a>),
+}
+
 #[derive(Clone, Debug, Serialize, Eq, PartialEq)]
 pub struct UnionVariant {
     pub name: String,
@@ -121,4 +326,45 @@ pub struct UnionVariant {
     pub is_inline_list: bool,
     pub use_to_xml_func: bool,
     pub value_as_str_repr: String,
+    // Class hierarchy
+    pub class_name: String,
+    pub is_class: bool,
+    pub from_xml_code: String,
+}
+
+/// String constant holding the xs:appinfo of a type or an identifier of a notation
+#[derive(Clone, Debug, Serialize, Eq, PartialEq)]
+pub struct MetadataConstant {
+    pub name: String,
+    /// Generated type, whose xs:appinfo is held, `None` for notations
+    pub type_name: Option<String>,
+    pub comment: String,
+    /// Source lines of the string expression
+    pub value: Vec<String>,
+}
+
+/// Concrete type, which replaces the instances of a generic type with the same type argument
+#[derive(Clone, Debug, Serialize, Eq, PartialEq)]
+pub struct GenericInstance {
+    pub kind: GenericKind,
+    /// Name of the concrete type, e.g. `TListOfInteger` for `TList<Integer>`
+    pub name: String,
+    /// Part of the name derived from the item type, e.g. `Integer`
+    pub suffix: String,
+    pub item_type: String,
+    /// The list frees its items, like `TObjectList<T>`
+    pub owns_items: bool,
+    /// Items are objects, which are compared by reference
+    pub is_object: bool,
+    /// The item type is an alias or a record, so the type is declared after them
+    pub after_aliases: bool,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum GenericKind {
+    List,
+    Array,
+    /// `TOptional<T>` with its subclasses `TSome<T>` and `TNone<T>`
+    Optional,
 }
@@ -9,20 +9,130 @@
   {%- endif -%}
 {% endmacro fixed_size_line -%}
 
+{#- Instance of a generic type, e.g. `TList<String>`, or its concrete type in units without generics #}
+{% macro instance(generic, item) -%}
+{{ item | generic_instance(generic=generic, generics_free=generics_free) }}
+{%- endmacro instance -%}
+
+{#- Section of the local variables of a method, which is omitted if it declares none #}
+{% macro var_section(variables) -%}
+{%- if variables | length > 0 %}
+var
+{%- for variable in variables %}
+  {{variable}};
+{%- endfor %}
+{%- endif %}
+{%- endmacro var_section -%}
+
+{#- Local variables of `FromXml`, which are used by the statements of the class #}
+{% macro from_xml_variables(class) -%}
+{%- set_global variables = [] -%}
+{%- set_global uses_part = false -%}
+{%- set checks_unknown_nodes = class.checks_unknown_nodes and not class.super_type -%}
+{%- set reads_children = class.preserves_nodes or class.any_elements or checks_unknown_nodes -%}
+{%- set_global uses_index = reads_children -%}
+{%- for element in class.deserialize_element_variables -%}
+{%- if element.is_group or element.is_list or element.is_fixed_size_list -%}
+{%- set_global uses_index = true -%}
+{%- elif element.is_inline_list -%}
+{%- set_global uses_part = true -%}
+{%- endif -%}
+{%- endfor -%}
+{%- if uses_index %}{% set_global variables = variables | concat(with="I: Integer") %}{% endif -%}
+{%- if reads_children %}{% set_global variables = variables | concat(with="vChild: IXMLNode") %}{% endif -%}
+{%- if class.preserves_nodes -%}
+{%- set_global variables = variables | concat(with="vBeforeRoot: Boolean") | concat(with="vPreserved: TPreservedXmlNode") -%}
+{%- endif -%}
+{%- if checks_unknown_nodes %}{% set_global variables = variables | concat(with="vAttribute: IXMLNode") %}{% endif -%}
+{%- if class.has_optional_element_variables %}{% set_global variables = variables | concat(with="vOptionalNode: IXMLNode") %}{% endif -%}
+{%- if uses_part %}{% set_global variables = variables | concat(with="vPart: String") %}{% endif -%}
+{%- for element in class.deserialize_element_variables -%}
+{%- if element.is_group -%}
+{%- set_global variables = variables | concat(with="__" ~ element.name ~ "Doc: IXMLDocument") | concat(with="__" ~ element.name ~ "Root: IXMLNode") | concat(with="__" ~ element.name ~ "Node: IXMLNode") -%}
+{%- elif element.is_list or element.is_fixed_size_list -%}
+{%- set_global variables = variables | concat(with="__" ~ element.name ~ "Index: Integer") | concat(with="__" ~ element.name ~ "Node: IXMLNode") -%}
+{%- endif -%}
+{%- endfor -%}
+{{ self::var_section(variables=variables) }}
+{%- endmacro from_xml_variables -%}
+
+{#- Local variables of `AppendToXmlRaw`, the items of lists are iterated by `__{name}Item` #}
+{% macro append_to_xml_variables(class) -%}
+{%- set_global variables = ["node: IXMLNode"] -%}
+{%- set_global uses_index = false -%}
+{%- for attribute in class.serialize_attribute_variables | filter(attribute="is_inline_list", value=true) -%}
+{%- set_global uses_index = true -%}
+{%- set_global variables = variables | concat(with="__" ~ attribute.name ~ "Value: String") -%}
+{%- endfor -%}
+{%- for variable in class.serialize_variables -%}
+{%- if variable.is_inline_list -%}
+{%- set_global uses_index = true -%}
+{%- elif variable.item_type_repr -%}
+{%- set_global variables = variables | concat(with="__" ~ variable.name ~ "Item: " ~ variable.item_type_repr) -%}
+{%- endif -%}
+{%- endfor -%}
+{%- if uses_index %}{% set_global variables = variables | concat(with="I: Integer") %}{% endif -%}
+{%- if class.any_elements -%}
+{%- set_global variables = variables | concat(with="vAnyElement: String") | concat(with="vAnyDocument: IXMLDocument") -%}
+{%- endif -%}
+{{ self::var_section(variables=variables) }}
+{%- endmacro append_to_xml_variables -%}
+
+{#- Local variables of `DumpFields`, see `dump_variable` #}
+{% macro dump_fields_variables(class) -%}
+{%- set_global variables = ["vIndent: String"] -%}
+{%- set_global uses_index = false -%}
+{%- for variable in class.serialize_attribute_variables | concat(with=class.serialize_variables) -%}
+{%- if variable.is_inline_list -%}
+{%- set_global uses_index = true -%}
+{%- elif variable.item_type_repr -%}
+{%- set_global variables = variables | concat(with="__" ~ variable.name ~ "Item: " ~ variable.item_type_repr) -%}
+{%- endif -%}
+{%- endfor -%}
+{%- if uses_index %}{% set_global variables = variables | concat(with="I: Integer") %}{% endif -%}
+{{ self::var_section(variables=variables) }}
+{%- endmacro dump_fields_variables -%}
+
+{#- Local variables of the destructor, which frees the items of lists not owning them #}
+{% macro destructor_variables(class) -%}
+{%- set_global variables = [] -%}
+{%- for variable in class.variables | filter(attribute="frees_items", value=true) -%}
+{%- set_global variables = variables | concat(with="__" ~ variable.name ~ "Item: " ~ variable.item_type_repr) -%}
+{%- endfor -%}
+{{ self::var_section(variables=variables) }}
+{%- endmacro destructor_variables -%}
+
 {% macro class_declaration(class) -%}
   // XML Qualified Name: {{class.qualified_name}}
   {% for line in class.documentations -%}
   // {{line}}
   {% endfor -%}
-  {{class.name}} = class({{class.super_type | default(value="TObject") }})
+  {{class.name}} = class{% if class.is_abstract %} abstract{% endif %}({{class.super_type | default(value="TObject") }})
   {%- if class.has_optional_fields %}
   strict private
     {% for variable in class.optional_variables -%}
-    F{{variable.name}}: TOptional<{{variable.data_type_repr}}>;
+    {% if variable.serializer_attribute -%}
+    [{{variable.serializer_attribute}}]
+    {% endif -%}
+    F{{variable.name}}: {{ self::instance(generic="TOptional", item=variable.data_type_repr) }};
     {% endfor -%}
     {{""}}
     {% for variable in class.optional_variables -%}
-    procedure Set{{variable.name}}(pValue: TOptional<{{variable.data_type_repr}}>);
+    procedure Set{{variable.name}}(pValue: {{ self::instance(generic="TOptional", item=variable.data_type_repr) }});
+    {% endfor -%}
+  {%- endif %}
+  {%- set validated_variables = class.variables | filter(attribute="pattern_validator") %}
+  {%- if validated_variables | length > 0 %}
+  strict private
+    {% for variable in validated_variables -%}
+    {% if variable.serializer_attribute -%}
+    [{{variable.serializer_attribute}}]
+    {% endif -%}
+    F{{variable.name}}: {{variable.data_type_repr}};
+    {% endfor -%}
+    {{""}}
+    {% for variable in validated_variables -%}
+    procedure Set{{variable.name}}(const pValue: {{variable.data_type_repr}});
     {% endfor -%}
   {%- endif %}
   public
@@ -32,14 +142,18 @@
       {% endfor -%}
       var
     {% endif -%}
-    {% if class.variables | length > 0 -%}
+    {% if class.variables | length > validated_variables | length -%}
     {% for variable in class.variables -%}
+    {% if variable.pattern_validator %}{% continue %}{% endif -%}
     {% if variable.required -%}
     /// <summary>Required</summary>
     {% endif -%}
     {% for line in variable.documentations -%}
     // {{line}}
     {% endfor -%}
+    {% if variable.serializer_attribute -%}
+    [{{variable.serializer_attribute}}]
+    {% endif -%}
     {{variable.name}}: {{variable.data_type_repr}};
     {% endfor %}
     {% endif -%}
@@ -48,6 +162,10 @@
     {% endif -%}
     {% if gen_from_xml -%}
     constructor FromXml(node: IXMLNode); {% if class.super_type %}override;{% else %}virtual;{% endif %}
+    {% if class.is_abstract -%}
+    /// <summary>Creates the derived class named by the xsi:type attribute of the node</summary>
+    class function FromXmlByType(node: IXMLNode): {{class.name}}; static;
+    {% endif -%}
     {% endif -%}
     {% if class.needs_destructor -%}
     destructor Destroy; override;
@@ -56,21 +174,143 @@
     {% if gen_to_xml -%}
     procedure AppendToXmlRaw(pParent: IXMLNode); {% if class.super_type %}override;{% else %}virtual;{% endif %}
     function ToXml: String; {% if class.super_type %}override;{% else %}virtual;{% endif %}
+    {%- if class.declares_xml_type_name %}
+    /// <summary>Name of the schema type, which is written as xsi:type</summary>
+    class function XmlTypeName: String; virtual; abstract;
+    {%- elif class.overrides_xml_type_name %}
+    class function XmlTypeName: String; override;
+    {%- endif %}
+    {%- endif %}
+    {%- if gen_debug_dump %}
+    {%- if not class.super_type %}
+    /// <summary>Names and values of the fields, nested objects are indented</summary>
+    function ToString: String; override;
+    function DebugDump(pVisited: {{ self::instance(generic="TList", item="TObject") }}; pIndent: Integer): String;
+    {%- endif %}
+    function DumpFields(pVisited: {{ self::instance(generic="TList", item="TObject") }}; pIndent: Integer): String; {% if class.super_type %}override;{% else %}virtual;{% endif %}
+    {%- endif %}
+    {%- if class.checks_unknown_nodes %}
+    /// <summary>Whether the child element or attribute is defined by the schema</summary>
+    class function IsKnownXmlNode(const pName: String; pIsAttribute: Boolean): Boolean; {% if class.super_type %}override;{% else %}virtual;{% endif %}
+    {%- endif %}
+    {%- if class.constraint_checks %}
+    /// <summary>Checks the xs:key, xs:keyref and xs:unique constraints, e.g. after FromXml.
+    /// Returns the violations with the paths of the offending nodes.</summary>
+    function CheckConstraints: {{ self::instance(generic="TArray", item="String") }};
+    {%- endif %}
+    {%- if class.assertion_checks %}
+    /// <summary>Checks the xs:assert of the type, e.g. after FromXml.
+    /// Returns the tests of the failed assertions.</summary>
+    function CheckAssertions: {{ self::instance(generic="TArray", item="String") }};{% if class.is_record %}{% elif class.overrides_assertion_checks %} override;{% else %} virtual;{% endif %}
     {%- endif %}
     {%- if class.has_optional_fields %}
     {% for variable in class.optional_variables %}
     {%- for line in variable.documentations %}
     // {{line}}
     {%- endfor %}
-    property {{variable.name}}: TOptional<{{variable.data_type_repr}}> read F{{variable.name}} write Set{{variable.name}};
+    property {{variable.name}}: {{ self::instance(generic="TOptional", item=variable.data_type_repr) }} read F{{variable.name}} write Set{{variable.name}};
     {%- endfor %}
     {%- endif %}
+    {%- for variable in validated_variables %}
+    /// <summary>Required, values not matching the pattern are rejected by {{variable.pattern_validator}}</summary>
+    {%- for line in variable.documentations %}
+    // {{line}}
+    {%- endfor %}
+    property {{variable.name}}: {{variable.data_type_repr}} read F{{variable.name}} write Set{{variable.name}};
+    {%- endfor %}
+    {%- if class.declares_warnings %}
+    /// <summary>Missing or invalid required values, which were read as default value by FromXml</summary>
+    property FromXmlWarnings: {{ self::instance(generic="TList", item="String") }} read FFromXmlWarnings;
+  strict protected
+    FFromXmlWarnings: {{ self::instance(generic="TList", item="String") }};
+  {%- endif %}
+  {%- if class.declares_unknown_nodes %}
+  public
+    /// <summary>Child elements and attributes (prefixed by @), which are not defined by the schema and were skipped by FromXml</summary>
+    property UnknownElements: {{ self::instance(generic="TList", item="String") }} read FUnknownElements;
+  strict protected
+    FUnknownElements: {{ self::instance(generic="TList", item="String") }};
+  {%- endif %}
+  {%- if class.preserves_nodes %}
+  strict private
+    FPreservedNodes: {{ self::instance(generic="TList", item="TPreservedXmlNode") }};
+  {%- endif %}
+  {%- if class.presence_flags | length > 0 %}
+  public
+    {%- for flag in class.presence_flags %}
+    /// <summary>Whether FromXml found the {% if flag.is_attribute %}attribute{% else %}element{% endif %} "{{flag.xml_name}}", even if its value equals the default</summary>
+    property {{flag.name}}: Boolean read F{{flag.name}};
+    {%- endfor %}
+  strict private
+    {%- for flag in class.presence_flags %}
+    F{{flag.name}}: Boolean;
+    {%- endfor %}
+  {%- endif %}
+  {%- if class.any_elements %}
+  public
+    {%- for line in class.any_elements.description %}
+    // {{line}}
+    {%- endfor %}
+    AnyElements: {{ self::instance(generic="TList", item="String") }};
+  {%- endif %}
+  {%- if include_file %}
+  public
+    // Manual code, which is declared in {{include_file}}
+    {$DEFINE MANUAL_{{class.name}}}
+    {$INCLUDE {{include_file}}}
+    {$UNDEF MANUAL_{{class.name}}}
+  {%- endif %}
   end;
 {%- endmacro class_declaration -%}
 
+{% macro record_declaration(class) -%}
+  // XML Qualified Name: {{class.qualified_name}}
+  {% for line in class.documentations -%}
+  // {{line}}
+  {% endfor -%}
+  {{class.name}} = record
+  public
+    {% if class.has_constant_fields -%}
+      {% for variable in class.constant_variables -%}
+      const {{variable.name}}: {{variable.data_type_repr}} = {{variable.default_value}};
+      {% endfor -%}
+      var
+    {% endif -%}
+    {% for variable in class.variables -%}
+    {% for line in variable.documentations -%}
+    // {{line}}
+    {% endfor -%}
+    {% if variable.serializer_attribute -%}
+    [{{variable.serializer_attribute}}]
+    {% endif -%}
+    {{variable.name}}: {{variable.data_type_repr}};
+    {% endfor %}
+    {% if gen_from_xml -%}
+    constructor FromXml(node: IXMLNode);
+    {% endif -%}
+    {% if gen_to_xml -%}
+    procedure AppendToXmlRaw(pParent: IXMLNode);
+    function ToXml: String;
+    {%- endif %}
+    {%- if gen_debug_dump %}
+    /// <summary>Names and values of the fields</summary>
+    function ToString: String;
+    function DebugDump(pVisited: {{ self::instance(generic="TList", item="TObject") }}; pIndent: Integer): String;
+    function DumpFields(pVisited: {{ self::instance(generic="TList", item="TObject") }}; pIndent: Integer): String;
+    {%- endif %}
+  {%- if include_file %}
+  public
+    // Manual code, which is declared in {{include_file}}
+    {$DEFINE MANUAL_{{class.name}}}
+    {$INCLUDE {{include_file}}}
+    {$UNDEF MANUAL_{{class.name}}}
+  {%- endif %}
+  end;
+{%- endmacro record_declaration -%}
+
 {% macro class_implementation(class) -%}
 {{"{"}} {{class.name}} {{"}"}}
-{% if gen_to_xml -%}
+{% if gen_to_xml and not class.is_record -%}
 constructor {{class.name}}.Create;
 begin
   {%- if class.super_type %}
@@ -79,46 +319,136 @@ begin
   {%- for initializer in class.variable_initializer %}
   {{initializer}}
   {%- endfor %}
+  {%- if class.preserves_nodes %}
+  FPreservedNodes := {{ self::instance(generic="TList", item="TPreservedXmlNode") }}.Create;
+  {%- endif %}
+  {%- if class.declares_warnings %}
+  FFromXmlWarnings := {{ self::instance(generic="TList", item="String") }}.Create;
+  {%- endif %}
+  {%- if class.declares_unknown_nodes %}
+  FUnknownElements := {{ self::instance(generic="TList", item="String") }}.Create;
+  {%- endif %}
+  {%- if class.any_elements %}
+  AnyElements := {{ self::instance(generic="TList", item="String") }}.Create;
+  {%- endif %}
 end;
 {%- endif %}
 
 {% if gen_from_xml -%}
 constructor {{class.name}}.FromXml(node: IXMLNode);
+{{- self::from_xml_variables(class=class) }}
 begin
   {%- if class.super_type %}
   inherited;
   {%- endif %}
+  {%- if class.declares_warnings %}
+  FFromXmlWarnings := {{ self::instance(generic="TList", item="String") }}.Create;
+  {%- endif %}
+  {%- if class.declares_unknown_nodes %}
+  FUnknownElements := {{ self::instance(generic="TList", item="String") }}.Create;
+  {%- endif %}
+  {%- if class.any_elements %}
+  AnyElements := {{ self::instance(generic="TList", item="String") }}.Create;
+  {%- endif %}
+  {%- if class.preserves_nodes %}
+  FPreservedNodes := {{ self::instance(generic="TList", item="TPreservedXmlNode") }}.Create;
+
+  vBeforeRoot := True;
+  for I := 0 to node.ChildNodes.Count - 1 do begin
+    vChild := node.ChildNodes[I];
+
+    if vChild.NodeType = ntElement then begin
+      vBeforeRoot := False;
+    end else if (vChild.NodeType in [ntComment, ntProcessingInstr]) and (vChild.NodeName <> 'xml') then begin
+      vPreserved.NodeType := vChild.NodeType;
+      vPreserved.Name := vChild.NodeName;
+      vPreserved.Value := vChild.Text;
+      vPreserved.BeforeRoot := vBeforeRoot;
+
+      FPreservedNodes.Add(vPreserved);
+    end;
+  end;
+  {%- endif %}
 
   {%- if class.deserialize_element_variables | length > 0 %}
   // Variables
-  {%- if class.has_optional_element_variables %}
-  var vOptionalNode: IXMLNode;
-  {%- endif %}
   {% for element in class.deserialize_element_variables %}
-  {%- if element.is_list %}
+  {%- if element.is_group %}
+  {%- if element.is_array %}
+  {{element.name}} := [];
+  {%- else %}
   {{element.name}} := {{element.data_type_repr}}.Create;
+  {%- endif %}
+
+  __{{element.name}}Doc := NewXMLDocument;
+  __{{element.name}}Root := __{{element.name}}Doc.AddChild({{element.xml_name | pascal_literal}});
+  __{{element.name}}Node := nil;
+  for I := 0 to node.ChildNodes.Count - 1 do begin
+    if node.ChildNodes[I].LocalName = {{element.xml_name | pascal_literal}} then begin
+      __{{element.name}}Node := __{{element.name}}Root.AddChild({{element.xml_name | pascal_literal}});
+    end;
+
+    if Assigned(__{{element.name}}Node) then begin
+      __{{element.name}}Node.ChildNodes.Add(node.ChildNodes[I].CloneNode(True));
+    end;
+  end;
+
+  for I := 0 to __{{element.name}}Root.ChildNodes.Count - 1 do begin
+    {%- if element.is_array %}
+    {{element.name}} := {{element.name}} + [{{element.from_xml_code}}];
+    {%- else %}
+    {{element.name}}.Add({{element.from_xml_code}});
+    {%- endif %}
+  end;
+  {% elif element.is_list %}
+  {%- if element.is_set or element.is_array %}
+  {{element.name}} := [];
+  {%- else %}
+  {{element.name}} := {{element.data_type_repr}}.Create;
+  {%- endif %}
 
-  var __{{element.name}}Index := node.ChildNodes.IndexOf('{{element.xml_name}}');
+  __{{element.name}}Index := node.ChildNodes.IndexOf({{element.xml_name | pascal_literal}});
   if __{{element.name}}Index >= 0 then begin
-    for var I := 0 to node.ChildNodes.Count - __{{element.name}}Index - 1 do begin
-      var __{{element.name}}Node := node.ChildNodes[__{{element.name}}Index + I];
+    for I := 0 to node.ChildNodes.Count - __{{element.name}}Index - 1 do begin
+      __{{element.name}}Node := node.ChildNodes[__{{element.name}}Index + I];
 
-      if __{{element.name}}Node.LocalName <> '{{element.xml_name}}' then continue;
+      if __{{element.name}}Node.LocalName <> {{element.xml_name | pascal_literal}} then continue;
 
+      {% if element.is_set -%}
+      Include({{element.name}}, {{element.from_xml_code}});
+      {%- elif element.is_array -%}
+      {{element.name}} := {{element.name}} + [{{element.from_xml_code}}];
+      {%- else -%}
       {{element.name}}.Add({{element.from_xml_code}});
+      {%- endif %}
     end;
   end;
   {% elif element.is_inline_list %}
   {{element.name}} := {{element.data_type_repr}}.Create;
 
-  {%- if element.is_required %}
-  for var vPart in node.ChildNodes['{{element.xml_name}}'].Text.Split([' ']) do begin
+  {%- if element.is_required and class.collects_warnings %}
+  if Assigned(node.ChildNodes.FindNode({{element.xml_name | pascal_literal}})) then begin
+    try
+      for vPart in node.ChildNodes[{{element.xml_name | pascal_literal}}].Text.Split([' ']) do begin
+        {{element.name}}.Add({{element.from_xml_code}});
+      end;
+    except
+      on E: Exception do begin
+        {{element.name}}.Clear;
+        FFromXmlWarnings.Add('Required element "{{element.xml_name}}" is invalid: ' + E.Message);
+      end;
+    end;
+  end else begin
+    FFromXmlWarnings.Add('Required element "{{element.xml_name}}" is missing');
+  end;
+  {% elif element.is_required %}
+  for vPart in node.ChildNodes[{{element.xml_name | pascal_literal}}].Text.Split([' ']) do begin
     {{element.name}}.Add({{element.from_xml_code}});
   end;
   {% else %}
-  vOptionalNode := node.ChildNodes.FindNode('{{element.xml_name}}');
+  vOptionalNode := node.ChildNodes.FindNode({{element.xml_name | pascal_literal}});
   if Assigned(vOptionalNode) then begin
-    for var vPart in vOptionalNode.Text.Split([' ']) do begin
+    for vPart in vOptionalNode.Text.Split([' ']) do begin
       {{element.name}}.Add({{element.from_xml_code}});
     end;
   end;
@@ -128,12 +458,12 @@ begin
   {{element.name}}{{ i + 1 }} := Default({{element.data_type_repr}});
   {%- endfor %}
 
-  var __{{element.name}}Index := node.ChildNodes.IndexOf('{{element.xml_name}}');
+  __{{element.name}}Index := node.ChildNodes.IndexOf({{element.xml_name | pascal_literal}});
   if __{{element.name}}Index >= 0 then begin
-    for var I := 0 to {{element.fixed_size_list_size - 1}} do begin
-      var __{{element.name}}Node := node.ChildNodes[__{{element.name}}Index + I];
+    for I := 0 to {{element.fixed_size_list_size - 1}} do begin
+      __{{element.name}}Node := node.ChildNodes[__{{element.name}}Index + I];
 
-      if __{{element.name}}Node.LocalName <> '{{element.xml_name}}' then break;
+      if __{{element.name}}Node.LocalName <> {{element.xml_name | pascal_literal}} then break;
 
       case I of
       {%- for i in range(end=element.fixed_size_list_size) %}
@@ -142,17 +472,31 @@ begin
       end;
     end;
   end;
+  {% elif element.is_required and class.collects_warnings %}
+  if Assigned(node.ChildNodes.FindNode({{element.xml_name | pascal_literal}})) then begin
+    try
+      {{element.name}} := {{element.from_xml_code}};
+    except
+      on E: Exception do begin
+        {{element.name}} := Default({{element.data_type_repr}});
+        FFromXmlWarnings.Add('Required element "{{element.xml_name}}" is invalid: ' + E.Message);
+      end;
+    end;
+  end else begin
+    {{element.name}} := Default({{element.data_type_repr}});
+    FFromXmlWarnings.Add('Required element "{{element.xml_name}}" is missing');
+  end;
   {% elif element.is_required %}
   {{element.name}} := {{element.from_xml_code}};
   {%- elif element.has_optional_wrapper %}
-  vOptionalNode := node.ChildNodes.FindNode('{{element.xml_name}}');
+  vOptionalNode := node.ChildNodes.FindNode({{element.xml_name | pascal_literal}});
   if Assigned(vOptionalNode) then begin
-    F{{element.name}} := TSome<{{element.data_type_repr}}>.Create({{element.from_xml_code}});
+    F{{element.name}} := {{ self::instance(generic="TSome", item=element.data_type_repr) }}.Create({{element.from_xml_code}});
   end else begin
-    F{{element.name}} := TNone<{{element.data_type_repr}}>.Create;
+    F{{element.name}} := {{ self::instance(generic="TNone", item=element.data_type_repr) }}.Create;
   end;
   {% else %}
-  vOptionalNode := node.ChildNodes.FindNode('{{element.xml_name}}');
+  vOptionalNode := node.ChildNodes.FindNode({{element.xml_name | pascal_literal}});
   if Assigned(vOptionalNode) then begin
     {{element.name}} := {{element.from_xml_code}};
   end else begin
@@ -164,42 +508,200 @@ begin
 
   {%- if class.deserialize_attribute_variables | length > 0 %}
   // Attributes
-  {%- for attr in deserialize_attribute_variables %}
-  if node.HasAttribute('{{attr.xml_value}}') then begin
-    {% if attr.has_optional_wrapper %}F{% endif %}{{attr.name}} := {{attr.from_xml_code_available}};
+  {%- for attr in class.deserialize_attribute_variables %}
+  if {{ self::has_attribute(attribute=attr) }} then begin
+    {%- if attr.has_optional_wrapper %}
+    F{{attr.name}} := {{ self::instance(generic="TSome", item=attr.data_type_repr) }}.Create({{attr.from_xml_code_available}});
+    {%- else %}
+    {{attr.name}} := {{attr.from_xml_code_available}};
+    {%- endif %}
   end else begin
-    {% if attr.has_optional_wrapper %}F{% endif %}{{attr.name}} := {{attr.from_xml_code_missing}};
+    {%- if not attr.from_xml_code_missing %}
+    raise Exception.Create('Required attribute "{{attr.xml_name}}" is missing');
+    {%- elif attr.has_optional_wrapper %}
+    F{{attr.name}} := {{attr.from_xml_code_missing}};
+    {%- else %}
+    {{attr.name}} := {{attr.from_xml_code_missing}};
+    {%- endif %}
+    {%- if attr.warn_if_missing %}
+    FFromXmlWarnings.Add('Required attribute "{{attr.xml_name}}" is missing');
+    {%- endif %}
   end;
   {%- endfor %}
   {%- endif %}
+
+  {%- if class.presence_flags | length > 0 %}
+  // Presence
+  {%- for flag in class.presence_flags %}
+  {%- if flag.is_attribute %}
+  F{{flag.name}} := {{ self::has_attribute(attribute=flag) }};
+  {%- else %}
+  F{{flag.name}} := Assigned(node.ChildNodes.FindNode({{flag.xml_name | pascal_literal}}));
+  {%- endif %}
+  {%- endfor %}
+  {%- endif %}
+
+  {%- if class.collects_warnings and class.occurrence_checks | length > 0 %}
+  // Occurrences
+  {%- for check in class.occurrence_checks %}
+  if {{check.condition}} then begin
+    FFromXmlWarnings.Add('Element "{{check.xml_name}}" occurs ' + IntToStr({{check.count}}) + ' times, expected {{check.expected}}');
+  end;
+  {%- endfor %}
+  {%- endif %}
+
+  {%- if class.any_elements %}
+
+  // Elements of xs:any
+  for I := 0 to node.ChildNodes.Count - 1 do begin
+    vChild := node.ChildNodes[I];
+
+    if vChild.NodeType <> ntElement then continue;
+    {%- if class.any_elements.declared_elements %}
+    if MatchStr(vChild.LocalName, [{% for name in class.any_elements.declared_elements %}{{name | pascal_literal}}{% if not loop.last %}, {% endif %}{% endfor %}]) then continue;
+    {%- endif %}
+    {%- if class.any_elements.namespace_condition %}
+
+    if not ({{class.any_elements.namespace_condition}}) then begin
+      {%- if class.checks_unknown_nodes and class.raises_on_unknown_nodes %}
+      raise Exception.Create('Unknown element "' + vChild.LocalName + '"');
+      {%- elif class.checks_unknown_nodes %}
+      FUnknownElements.Add(vChild.LocalName);
+      {%- endif %}
+      continue;
+    end;
+    {%- endif %}
+    {%- if class.any_elements.strict_elements is defined %}
+
+    if not MatchStr(vChild.LocalName, [{% for name in class.any_elements.strict_elements %}{{name | pascal_literal}}{% if not loop.last %}, {% endif %}{% endfor %}]) then begin
+      raise Exception.Create('Element "' + vChild.LocalName + '" is not declared by the schema');
+    end;
+    {%- endif %}
+
+    AnyElements.Add(vChild.XML);
+  end;
+  {%- endif %}
+
+  {%- if class.checks_unknown_nodes and not class.super_type %}
+
+  // Unknown nodes, the names are checked by the class of the object
+  for I := 0 to node.ChildNodes.Count - 1 do begin
+    vChild := node.ChildNodes[I];
+
+    if (vChild.NodeType = ntElement) and not IsKnownXmlNode(vChild.LocalName, False) then begin
+      {%- if class.raises_on_unknown_nodes %}
+      raise Exception.Create('Unknown element "' + vChild.LocalName + '"');
+      {%- else %}
+      FUnknownElements.Add(vChild.LocalName);
+      {%- endif %}
+    end;
+  end;
+
+  for I := 0 to node.AttributeNodes.Count - 1 do begin
+    vAttribute := node.AttributeNodes[I];
+
+    // Namespace declarations and xsi:type, xsi:nil etc. are not defined by the schema
+    if (vAttribute.Prefix = 'xmlns') or (vAttribute.NodeName = 'xmlns') or (vAttribute.NamespaceURI = 'http://www.w3.org/2001/XMLSchema-instance') then continue;
+
+    if not IsKnownXmlNode(vAttribute.LocalName, True) then begin
+      {%- if class.raises_on_unknown_nodes %}
+      raise Exception.Create('Unknown attribute "' + vAttribute.LocalName + '"');
+      {%- else %}
+      FUnknownElements.Add('@' + vAttribute.LocalName);
+      {%- endif %}
+    end;
+  end;
+  {%- endif %}
+end;
+{%- if class.is_abstract %}
+
+class function {{class.name}}.FromXmlByType(node: IXMLNode): {{class.name}};
+var
+  vType: String;
+  vTypeNode: IXMLNode;
+begin
+  vType := '';
+  vTypeNode := node.AttributeNodes.FindNode('type', 'http://www.w3.org/2001/XMLSchema-instance');
+
+  if Assigned(vTypeNode) then begin
+    // Drop the namespace prefix of the type name
+    vType := vTypeNode.Text.Substring(vTypeNode.Text.IndexOf(':') + 1);
+  end;
+{% for derived in class.derived_classes %}
+  if vType = {{derived.xml_type_name | pascal_literal}} then Exit({{derived.name}}.FromXml(node));
+  {%- endfor %}
+
+  raise Exception.Create('Abstract type "{{class.xml_type_name}}" can''t be created for xsi:type "' + vType + '"');
 end;
 {%- endif %}
+{%- endif %}
 
 {% if gen_to_xml -%}
 procedure {{class.name}}.AppendToXmlRaw(pParent: IXMLNode);
+{{- self::append_to_xml_variables(class=class) }}
 begin
   {%- if class.super_type %}
   inherited;
   {% endif %}
-  var node: IXMLNode;
+{%- if class.declares_xml_type_name %}
+  if pParent.NodeType = ntElement then begin
+    pParent.SetAttributeNS('xsi:type', 'http://www.w3.org/2001/XMLSchema-instance', XmlTypeName);
+  end;
+{%- endif %}
+{%- for check in class.occurrence_checks %}
+  if {% if check.is_array %}{{check.condition}}{% else %}Assigned({{check.name}}) and ({{check.condition}}){% endif %} then begin
+    raise Exception.Create('Element "{{check.xml_name}}" occurs ' + IntToStr({{check.count}}) + ' times, expected {{check.expected}}');
+  end;
+{%- endfor %}
+{%- for attribute in class.serialize_attribute_variables %}
+{%- if attribute.is_inline_list %}
+  if Assigned({{attribute.name}}) then begin
+    __{{attribute.name}}Value := '';
+    for I := 0 to {{attribute.name}}.Count - 1 do begin
+      __{{attribute.name}}Value := __{{attribute.name}}Value + {{attribute.to_xml_code}};
+
+      if I < {{attribute.name}}.Count - 1 then begin
+        __{{attribute.name}}Value := __{{attribute.name}}Value + ' ';
+      end;
+    end;
+    {{ self::set_attribute(attribute=attribute, value="__" ~ attribute.name ~ "Value") }}
+  end;
+{%- elif attribute.has_optional_wrapper %}
+  if F{{attribute.name}}.IsSome then begin
+    {%- if attribute.is_enum %}
+    {{ self::set_attribute(attribute=attribute, value="F" ~ attribute.name ~ ".Unwrap.ToXmlValue") }}
+    {%- else %}
+    {{ self::set_attribute(attribute=attribute, value=attribute.to_xml_code) }}
+    {%- endif %}
+  end;
+{%- elif attribute.is_enum %}
+  {{ self::set_attribute(attribute=attribute, value=attribute.name ~ ".ToXmlValue") }}
+{%- else %}
+  {{ self::set_attribute(attribute=attribute, value=attribute.to_xml_code) }}
+{%- endif %}
+{%- endfor %}
 {% for variable in class.serialize_variables -%}
-{%- if variables.is_list %}
-  for var __Item in {{variable.name}} do begin
+{%- if variable.is_group %}
+  for __{{variable.name}}Item in {{variable.name}} do begin
+    __{{variable.name}}Item.AppendToXmlRaw(pParent);
+  end;
+{%- elif variable.is_list %}
+  for __{{variable.name}}Item in {{variable.name}} do begin
   {%- if variable.is_class %}
-    node := pParent.AddChild('{{variable.xml_name}}');
-    __Item.AppendToXmlRaw(node);
+    node := pParent.AddChild({{variable.xml_name | pascal_literal}});
+    __{{variable.name}}Item.AppendToXmlRaw(node);
   {%- elif variable.is_enum %}
-    node := pParent.AddChild('{{variable.xml_name}}');
-    node.Text := __Item.ToXmlValue;
+    node := pParent.AddChild({{variable.xml_name | pascal_literal}});
+    node.Text := __{{variable.name}}Item.ToXmlValue;
   {%- else %}
-    node := pParent.AddChild('{{variable.xml_name}}');
+    node := pParent.AddChild({{variable.xml_name | pascal_literal}});
     node.Text := {{variable.to_xml_code}};
   {%- endif %}
   end;
 {%- elif variable.is_inline_list %}
   {%- if variable.is_required %}
-  node := pParent.AddChild('{{variable.xml_name}}');
-  for var I := 0 to {{variable.name}}.Count - 1 do begin
+  node := pParent.AddChild({{variable.xml_name | pascal_literal}});
+  for I := 0 to {{variable.name}}.Count - 1 do begin
     node.Text := node.Text + {{variable.to_xml_code}};
 
     if I < {{variable.name}}.Count - 1 then begin
@@ -208,8 +710,8 @@ begin
   end;
   {%- else %}
   if Assigned({{variable.name}}) then begin
-    node := pParent.AddChild('{{variable.xml_name}}');
-    for var I := 0 to {{variable.name}}.Count - 1 do begin
+    node := pParent.AddChild({{variable.xml_name | pascal_literal}});
+    for I := 0 to {{variable.name}}.Count - 1 do begin
       node.Text := node.Text + {{variable.to_xml_code}};
 
       if I < {{variable.name}}.Count - 1 then begin
@@ -219,66 +721,156 @@ begin
   end;
   {%- endif %}
 {%- elif variable.is_class %}
-  {%- if variable.is_required %}
-  node := pParent.AddChild('{{variable.xml_name}}');
+  {%- if variable.has_optional_wrapper %}
+  if F{{variable.name}}.IsSome then begin
+    node := pParent.AddChild({{variable.xml_name | pascal_literal}});
+    F{{variable.name}}.Unwrap.AppendToXmlRaw(node);
+  end;
+  {%- elif variable.is_required %}
+  node := pParent.AddChild({{variable.xml_name | pascal_literal}});
   {{variable.name}}.AppendToXmlRaw(node);
   {%- else %}
   if Assigned({{variable.name}}) then begin
-    node := pParent.AddChild('{{variable.xml_name}}');
+    node := pParent.AddChild({{variable.xml_name | pascal_literal}});
     {{variable.name}}.AppendToXmlRaw(node);
   end;
   {%- endif %}
 {%- elif variable.is_enum %}
   {% if variable.has_optional_wrapper %}
   if F{{variable.name}}.IsSome then begin
-    node := pParent.AddChild('{{variable.xml_name}}');
+    node := pParent.AddChild({{variable.xml_name | pascal_literal}});
     node.Text := F{{variable.name}}.Unwrap.ToXmlValue;
   end;
   {%- else %}
-  node := pParent.AddChild('{{variable.xml_name}}');
+  node := pParent.AddChild({{variable.xml_name | pascal_literal}});
   node.Text := {{variable.name}}.ToXmlValue;
   {%- endif %}
 {%- elif variable.has_optional_wrapper %}
   if F{{variable.name}}.IsSome then begin
-    node := pParent.AddChild('{{variable.xml_name}}');
+    node := pParent.AddChild({{variable.xml_name | pascal_literal}});
     node.Text := {{variable.to_xml_code}};
   end;
 {%- else %}
-  node := pParent.AddChild('{{variable.xml_name}}');
+  node := pParent.AddChild({{variable.xml_name | pascal_literal}});
   node.Text := {{variable.to_xml_code}};
 {% endif %}
 {%- endfor %}
+{%- if class.any_elements %}
+  for vAnyElement in AnyElements do begin
+    vAnyDocument := LoadXMLData(vAnyElement);
+    pParent.ChildNodes.Add(vAnyDocument.DocumentElement.CloneNode(True));
+  end;
+{%- endif %}
 end;
 
+{% if class.overrides_xml_type_name -%}
+class function {{class.name}}.XmlTypeName: String;
+begin
+  Result := {{class.xml_type_name | pascal_literal}};
+end;
+
+{% endif -%}
 function {{class.name}}.ToXml: String;
+var
+  vXmlDoc: IXMLDocument;
+  {%- if class.preserves_nodes %}
+  vPreserved: TPreservedXmlNode;
+  vNode: IXMLNode;
+  {%- endif %}
+  {%- if not xml_declaration %}
+  vDeclaration: IXMLNode;
+  {%- endif %}
 begin
-  var vXmlDoc := NewXMLDocument;
+  vXmlDoc := NewXMLDocument;
+  {%- if xml_indentation is defined %}
+  vXmlDoc.Options := vXmlDoc.Options + [doNodeAutoIndent];
+  vXmlDoc.NodeIndentStr := {{xml_indentation | pascal_literal}};
+  {%- endif %}
+  {%- if xml_standalone is defined %}
+  vXmlDoc.StandAlone := {{xml_standalone | pascal_literal}};
+  {%- endif %}
 
   AppendToXmlRaw(vXmlDoc.Node);
+  {%- if class.preserves_nodes %}
+
+  for vPreserved in FPreservedNodes do begin
+    if vPreserved.NodeType = ntComment then begin
+      vNode := vXmlDoc.CreateNode(vPreserved.Value, ntComment);
+    end else begin
+      vNode := vXmlDoc.CreateNode(vPreserved.Name, ntProcessingInstr, vPreserved.Value);
+    end;
+
+    if vPreserved.BeforeRoot and Assigned(vXmlDoc.DocumentElement) then begin
+      vXmlDoc.Node.ChildNodes.Insert(vXmlDoc.Node.ChildNodes.IndexOf(vXmlDoc.DocumentElement), vNode);
+    end else begin
+      vXmlDoc.Node.ChildNodes.Add(vNode);
+    end;
+  end;
+  {%- endif %}
+  {%- if not xml_declaration %}
+
+  vDeclaration := vXmlDoc.Node.ChildNodes.FindNode('xml');
+  if Assigned(vDeclaration) then begin
+    vXmlDoc.Node.ChildNodes.Remove(vDeclaration);
+  end;
+  {%- endif %}
 
   vXmlDoc.SaveToXML(Result);
 end;
 {% endif -%}
 {% if class.optional_variables | length > 0 -%}
 {% for variable in class.optional_variables %}
-procedure {{class.name}}.Set{{variable.name}}(pValue: TOptional<{{variable.data_type_repr}}>);
+procedure {{class.name}}.Set{{variable.name}}(pValue: {{ self::instance(generic="TOptional", item=variable.data_type_repr) }});
 begin
   if F{{variable.name}} <> pValue then F{{variable.name}}.Free;
 
   if (not Assigned(pValue)) or (pValue = nil) then begin
-    F{{variable.name}} := TNone<{{variable.data_type_repr}}>.Create;
+    F{{variable.name}} := {{ self::instance(generic="TNone", item=variable.data_type_repr) }}.Create;
   end else begin
     F{{variable.name}} := pValue;
   end;
 end;
 {% endfor -%}
 {%- endif %}
+{%- set validated_variables = class.variables | filter(attribute="pattern_validator") %}
+{%- if validated_variables | length > 0 -%}
+{% for variable in validated_variables %}
+procedure {{class.name}}.Set{{variable.name}}(const pValue: {{variable.data_type_repr}});
+begin
+  if not {{variable.pattern_validator}}(pValue) then begin
+    raise EArgumentException.Create('"' + pValue + '" does not match the pattern of {{class.name}}.{{variable.name}}');
+  end;
+
+  F{{variable.name}} := pValue;
+end;
+{% endfor -%}
+{%- endif %}
 
 {% if class.needs_destructor -%}
 destructor {{class.name}}.Destroy;
+{{- self::destructor_variables(class=class) }}
 begin
-  {%- for variable in class.variables | filter(attribute="requires_free", value=true) %}
+  {%- if class.declares_warnings %}
+  FFromXmlWarnings.Free;
+  {%- endif %}
+  {%- if class.declares_unknown_nodes %}
+  FUnknownElements.Free;
+  {%- endif %}
+  {%- if class.preserves_nodes %}
+  FPreservedNodes.Free;
+  {%- endif %}
+  {%- if class.any_elements %}
+  AnyElements.Free;
+  {%- endif %}
+  {%- for variable in class.variables %}
+  {%- if variable.frees_items %}
+  for __{{variable.name}}Item in {{variable.name}} do begin
+    __{{variable.name}}Item.Free;
+  end;
+  {%- endif %}
+  {%- if variable.requires_free %}
   {{variable.name}}.Free;
+  {%- endif %}
   {%- endfor %}
   {%- for variable in class.optional_variables %}
   F{{variable.name}}.Free;
@@ -287,4 +879,581 @@ begin
   inherited;
 end;
 {%- endif %}
-{%- endmacro class_implementation -%}
\ No newline at end of file
+{%- if gen_debug_dump %}
+{% if class.is_record %}
+function {{class.name}}.ToString: String;
+begin
+  Result := DebugDump(nil, 0);
+end;
+
+function {{class.name}}.DebugDump(pVisited: {{ self::instance(generic="TList", item="TObject") }}; pIndent: Integer): String;
+begin
+  Result := '{{class.name}} (' + DumpFields(pVisited, pIndent + 1) + sLineBreak + StringOfChar(' ', pIndent * 2) + ')';
+end;
+{% elif not class.super_type %}
+function {{class.name}}.ToString: String;
+var
+  vVisited: {{ self::instance(generic="TList", item="TObject") }};
+begin
+  vVisited := {{ self::instance(generic="TList", item="TObject") }}.Create;
+  try
+    Result := DebugDump(vVisited, 0);
+  finally
+    vVisited.Free;
+  end;
+end;
+
+function {{class.name}}.DebugDump(pVisited: {{ self::instance(generic="TList", item="TObject") }}; pIndent: Integer): String;
+begin
+  if pVisited.Contains(Self) then Exit(ClassName + ' (cycle)');
+
+  pVisited.Add(Self);
+  try
+    Result := ClassName + ' (' + DumpFields(pVisited, pIndent + 1) + sLineBreak + StringOfChar(' ', pIndent * 2) + ')';
+  finally
+    pVisited.Remove(Self);
+  end;
+end;
+{% endif %}
+function {{class.name}}.DumpFields(pVisited: {{ self::instance(generic="TList", item="TObject") }}; pIndent: Integer): String;
+{{- self::dump_fields_variables(class=class) }}
+begin
+  {%- if class.super_type %}
+  Result := inherited DumpFields(pVisited, pIndent);
+  {%- else %}
+  Result := '';
+  {%- endif %}
+  vIndent := sLineBreak + StringOfChar(' ', pIndent * 2);
+  {%- for variable in class.serialize_attribute_variables %}
+  {{- self::dump_variable(variable=variable) }}
+  {%- endfor %}
+  {%- for variable in class.serialize_variables %}
+  {{- self::dump_variable(variable=variable) }}
+  {%- endfor %}
+end;
+{%- endif %}
+{%- if class.checks_unknown_nodes %}
+
+class function {{class.name}}.IsKnownXmlNode(const pName: String; pIsAttribute: Boolean): Boolean;
+begin
+  if pIsAttribute then begin
+    Result := {% if class.known_attributes %}MatchStr(pName, [{% for name in class.known_attributes %}{{name | pascal_literal}}{% if not loop.last %}, {% endif %}{% endfor %}]){% else %}False{% endif %};
+  end else begin
+    {%- if class.any_elements %}
+    // Elements of xs:any are checked by FromXml
+    Result := True;
+    {%- else %}
+    Result := {% if class.known_elements %}MatchStr(pName, [{% for name in class.known_elements %}{{name | pascal_literal}}{% if not loop.last %}, {% endif %}{% endfor %}]){% else %}False{% endif %};
+    {%- endif %}
+  end;
+  {%- if class.super_type %}
+
+  Result := Result or inherited IsKnownXmlNode(pName, pIsAttribute);
+  {%- endif %}
+end;
+{%- endif %}
+{%- if class.constraint_checks %}
+
+function {{class.name}}.CheckConstraints: {{ self::instance(generic="TArray", item="String") }};
+var
+  vConstraints: TIdentityConstraints;
+  {%- for variable in class.constraint_variables %}
+  {{variable}};
+  {%- endfor %}
+begin
+  vConstraints := TIdentityConstraints.Create;
+  try
+    {%- for line in class.constraint_checks %}
+    {{line}}
+    {%- endfor %}
+
+    Result := vConstraints.Violations;
+  finally
+    vConstraints.Free;
+  end;
+end;
+{%- endif %}
+{%- if class.assertion_checks %}
+
+function {{class.name}}.CheckAssertions: {{ self::instance(generic="TArray", item="String") }};
+begin
+  {%- if class.overrides_assertion_checks %}
+  Result := inherited CheckAssertions;
+  {%- else %}
+  Result := [];
+  {%- endif %}
+  {%- for line in class.assertion_checks %}
+  {{line}}
+  {%- endfor %}
+end;
+{%- endif %}
+{%- endmacro class_implementation -%}
+
+{#- Attributes with qualified form are accessed namespace-aware, as they are prefixed in the xml -#}
+{% macro has_attribute(attribute) -%}
+node.HasAttribute({{attribute.xml_name | pascal_literal}}{% if attribute.namespace %}, {{attribute.namespace.uri | pascal_literal}}{% endif %})
+{%- endmacro has_attribute %}
+
+{% macro set_attribute(attribute, value) -%}
+{%- if attribute.namespace -%}
+{%- set qualified_name = attribute.namespace.prefix ~ ":" ~ attribute.xml_name -%}
+pParent.SetAttributeNS({{qualified_name | pascal_literal}}, {{attribute.namespace.uri | pascal_literal}}, {{value}});
+{%- else -%}
+pParent.Attributes[{{attribute.xml_name | pascal_literal}}] := {{value}};
+{%- endif -%}
+{%- endmacro set_attribute %}
+
+{% macro dump_variable(variable) %}
+{%- if variable.is_group or variable.is_list %}
+  Result := Result + vIndent + '{{variable.name}}: [';
+  for __{{variable.name}}Item in {{variable.name}} do begin
+    {%- if variable.is_class %}
+    Result := Result + vIndent + '  ' + DebugDumpObject(__{{variable.name}}Item, pVisited, pIndent + 1);
+    {%- elif variable.is_enum %}
+    Result := Result + vIndent + '  ' + __{{variable.name}}Item.ToXmlValue;
+    {%- else %}
+    Result := Result + vIndent + '  ' + {{variable.to_xml_code}};
+    {%- endif %}
+  end;
+  Result := Result + vIndent + ']';
+{%- elif variable.is_inline_list %}
+  Result := Result + vIndent + '{{variable.name}}: [';
+  if Assigned({{variable.name}}) then begin
+    for I := 0 to {{variable.name}}.Count - 1 do begin
+      Result := Result + ' ' + {{variable.to_xml_code}};
+    end;
+  end;
+  Result := Result + ' ]';
+{%- elif variable.has_optional_wrapper %}
+  if F{{variable.name}}.IsSome then begin
+    {%- if variable.is_class %}
+    Result := Result + vIndent + '{{variable.name}}: ' + DebugDumpObject(F{{variable.name}}.Unwrap, pVisited, pIndent);
+    {%- elif variable.is_enum %}
+    Result := Result + vIndent + '{{variable.name}}: ' + F{{variable.name}}.Unwrap.ToXmlValue;
+    {%- else %}
+    Result := Result + vIndent + '{{variable.name}}: ' + {{variable.to_xml_code}};
+    {%- endif %}
+  end else begin
+    Result := Result + vIndent + '{{variable.name}}: None';
+  end;
+{%- elif variable.is_class %}
+  Result := Result + vIndent + '{{variable.name}}: ' + DebugDumpObject({{variable.name}}, pVisited, pIndent);
+{%- elif variable.is_enum %}
+  Result := Result + vIndent + '{{variable.name}}: ' + {{variable.name}}.ToXmlValue;
+{%- else %}
+  Result := Result + vIndent + '{{variable.name}}: ' + {{variable.to_xml_code}};
+{%- endif %}
+{%- endmacro dump_variable -%}
+{% macro union_class_declaration(union) -%}
+// XML Qualified Name: {{union.qualified_name}}
+  {% for line in union.documentations -%}
+  // {{line}}
+  {% endfor -%}
+  {{union.name}} = class abstract
+  public
+    {%- if gen_from_xml %}
+    class function FromXml(node: IXMLNode): {{union.name}}; static;
+    {%- endif %}
+    {%- if gen_to_xml %}
+    procedure AppendToXmlRaw(pParent: IXMLNode); virtual; abstract;
+    {%- endif %}
+  end;
+  {%- for variant in union.variants %}
+
+  {{variant.class_name}} = class({{union.name}})
+  strict private
+    FValue: {{variant.data_type_repr}};
+  public
+    constructor Create(pValue: {{variant.data_type_repr}});
+    {%- if gen_from_xml %}
+    constructor FromXml(node: IXMLNode);
+    {%- endif %}
+    {%- if variant.is_class or variant.is_inline_list %}
+    destructor Destroy; override;
+    {%- endif %}
+    {%- if gen_to_xml %}
+    procedure AppendToXmlRaw(pParent: IXMLNode); override;
+    {%- endif %}
+
+    property Value: {{variant.data_type_repr}} read FValue;
+  end;
+  {%- endfor %}
+{%- endmacro union_class_declaration -%}
+
+{% macro union_class_implementation(union) -%}
+{{"{"}} {{union.name}} {{"}"}}
+{% if gen_from_xml -%}
+class function {{union.name}}.FromXml(node: IXMLNode): {{union.name}};
+begin
+  // The first variant, which can be read from the node, is used
+  {%- for variant in union.variants %}
+  try
+    Exit({{variant.class_name}}.FromXml(node));
+  except
+    on Exception do ;
+  end;
+  {%- endfor %}
+
+  raise Exception.Create('Node "' + node.NodeName + '" does not match any variant of {{union.name}}');
+end;
+{%- endif %}
+{% for variant in union.variants %}
+{{"{"}} {{variant.class_name}} {{"}"}}
+constructor {{variant.class_name}}.Create(pValue: {{variant.data_type_repr}});
+begin
+  FValue := pValue;
+end;
+{% if gen_from_xml %}
+constructor {{variant.class_name}}.FromXml(node: IXMLNode);
+{%- if variant.is_inline_list %}
+var
+  vPart: String;
+{%- endif %}
+begin
+  {%- if variant.is_inline_list %}
+  FValue := {{variant.data_type_repr}}.Create;
+
+  for vPart in node.Text.Split([' ']) do begin
+    FValue.Add({{variant.from_xml_code}});
+  end;
+  {%- else %}
+  FValue := {{variant.from_xml_code}};
+  {%- endif %}
+end;
+{% endif -%}
+{% if variant.is_class or variant.is_inline_list %}
+destructor {{variant.class_name}}.Destroy;
+begin
+  FValue.Free;
+
+  inherited;
+end;
+{% endif -%}
+{% if gen_to_xml %}
+procedure {{variant.class_name}}.AppendToXmlRaw(pParent: IXMLNode);
+{%- if variant.is_inline_list %}
+var
+  vValue: String;
+  I: Integer;
+{%- endif %}
+begin
+  {%- if variant.is_class %}
+  FValue.AppendToXmlRaw(pParent);
+  {%- elif variant.is_inline_list %}
+  vValue := '';
+
+  for I := 0 to FValue.Count - 1 do begin
+    vValue := vValue + {{variant.value_as_str_repr}};
+
+    if I < FValue.Count - 1 then begin
+      vValue := vValue + ' ';
+    end;
+  end;
+
+  pParent.Text := vValue;
+  {%- elif variant.use_to_xml_func %}
+  pParent.Text := FValue.ToXmlValue;
+  {%- else %}
+  pParent.Text := {{variant.value_as_str_repr}};
+  {%- endif %}
+end;
+{% endif -%}
+{% endfor -%}
+{%- endmacro union_class_implementation -%}
+
+{% macro generic_instance_declaration(instance) -%}
+{%- set item = instance.item_type -%}
+{%- if instance.kind == "array" -%}
+{{instance.name}} = array of {{item}};
+{%- elif instance.kind == "optional" -%}
+{{instance.name}} = class
+  strict protected
+    FOwns: Boolean;
+  public
+    function Unwrap: {{item}}; virtual;
+    function UnwrapOr(pDefault: {{item}}): {{item}}; virtual; abstract;
+    function IsSome: Boolean; virtual; abstract;
+    function IsNone: Boolean; virtual; abstract;
+    function CopyWith(pValue: {{item}}): {{instance.name}}; virtual; abstract;
+
+    property Owns: Boolean read FOwns write FOwns;
+  end;
+
+  TSome{{instance.suffix}} = class({{instance.name}})
+  strict private
+    FValue: {{item}};
+  public
+    constructor Create(pValue: {{item}});
+
+    function Unwrap: {{item}}; override;
+    function UnwrapOr(pDefault: {{item}}): {{item}}; override;
+    function IsSome: Boolean; override;
+    function IsNone: Boolean; override;
+    function CopyWith(pValue: {{item}}): {{instance.name}}; override;
+  end;
+
+  TNone{{instance.suffix}} = class({{instance.name}})
+  public
+    function UnwrapOr(pDefault: {{item}}): {{item}}; override;
+    function IsSome: Boolean; override;
+    function IsNone: Boolean; override;
+    function CopyWith(pValue: {{item}}): {{instance.name}}; override;
+  end;
+{%- else -%}
+{{instance.name}}Enumerator = class
+  strict private
+    FList: {{instance.name}};
+    FIndex: Integer;
+  public
+    constructor Create(pList: {{instance.name}});
+
+    function GetCurrent: {{item}};
+    function MoveNext: Boolean;
+
+    property Current: {{item}} read GetCurrent;
+  end;
+
+  /// <summary>List of {{item}}{% if instance.owns_items %}, which frees its items{% endif %}</summary>
+  {{instance.name}} = class
+  strict private
+    FItems: array of {{item}};
+    FCount: Integer;
+
+    procedure CheckIndex(pIndex: Integer);
+    function GetItem(pIndex: Integer): {{item}};
+    procedure SetItem(pIndex: Integer; const pValue: {{item}});
+  public
+    destructor Destroy; override;
+
+    function Add(const pValue: {{item}}): Integer;
+    procedure Insert(pIndex: Integer; const pValue: {{item}});
+    procedure Delete(pIndex: Integer);
+    procedure Clear;
+    {%- if instance.is_object %}
+    function IndexOf(const pValue: {{item}}): Integer;
+    function Contains(const pValue: {{item}}): Boolean;
+    function Remove(const pValue: {{item}}): Integer;
+    {%- endif %}
+    function GetEnumerator: {{instance.name}}Enumerator;
+
+    property Count: Integer read FCount;
+    property Items[pIndex: Integer]: {{item}} read GetItem write SetItem; default;
+  end;
+{%- endif %}
+{%- endmacro generic_instance_declaration %}
+
+{% macro generic_instance_implementation(instance) -%}
+{%- set item = instance.item_type -%}
+{%- set name = instance.name -%}
+{%- if instance.kind == "optional" %}
+
+{{"{"}} {{name}} {{"}"}}
+function {{name}}.Unwrap: {{item}};
+begin
+  raise Exception.Create('Not Implemented');
+end;
+
+{{"{"}} TSome{{instance.suffix}} {{"}"}}
+constructor TSome{{instance.suffix}}.Create(pValue: {{item}});
+begin
+  FValue := pValue;
+end;
+
+function TSome{{instance.suffix}}.IsNone: Boolean;
+begin
+  Result := False;
+end;
+
+function TSome{{instance.suffix}}.IsSome: Boolean;
+begin
+  Result := True;
+end;
+
+function TSome{{instance.suffix}}.Unwrap: {{item}};
+begin
+  Result := FValue;
+end;
+
+function TSome{{instance.suffix}}.UnwrapOr(pDefault: {{item}}): {{item}};
+begin
+  Result := FValue;
+end;
+
+function TSome{{instance.suffix}}.CopyWith(pValue: {{item}}): {{name}};
+begin
+  FValue := pValue;
+  Result := Self;
+end;
+
+{{"{"}} TNone{{instance.suffix}} {{"}"}}
+function TNone{{instance.suffix}}.IsNone: Boolean;
+begin
+  Result := True;
+end;
+
+function TNone{{instance.suffix}}.IsSome: Boolean;
+begin
+  Result := False;
+end;
+
+function TNone{{instance.suffix}}.UnwrapOr(pDefault: {{item}}): {{item}};
+begin
+  Result := pDefault;
+end;
+
+function TNone{{instance.suffix}}.CopyWith(pValue: {{item}}): {{name}};
+begin
+  Result := TSome{{instance.suffix}}.Create(pValue);
+  Self.Free;
+end;
+{%- elif instance.kind == "list" %}
+
+{{"{"}} {{name}}Enumerator {{"}"}}
+constructor {{name}}Enumerator.Create(pList: {{name}});
+begin
+  inherited Create;
+  FList := pList;
+  FIndex := -1;
+end;
+
+function {{name}}Enumerator.GetCurrent: {{item}};
+begin
+  Result := FList[FIndex];
+end;
+
+function {{name}}Enumerator.MoveNext: Boolean;
+begin
+  Inc(FIndex);
+  Result := FIndex < FList.Count;
+end;
+
+{{"{"}} {{name}} {{"}"}}
+destructor {{name}}.Destroy;
+begin
+  Clear;
+  inherited;
+end;
+
+procedure {{name}}.CheckIndex(pIndex: Integer);
+begin
+  if (pIndex < 0) or (pIndex >= FCount) then begin
+    raise Exception.CreateFmt('List index out of bounds (%d)', [pIndex]);
+  end;
+end;
+
+function {{name}}.GetItem(pIndex: Integer): {{item}};
+begin
+  CheckIndex(pIndex);
+  Result := FItems[pIndex];
+end;
+
+procedure {{name}}.SetItem(pIndex: Integer; const pValue: {{item}});
+begin
+  CheckIndex(pIndex);
+  {%- if instance.owns_items %}
+
+  if FItems[pIndex] <> pValue then begin
+    FItems[pIndex].Free;
+  end;
+{{""}}
+  {%- endif %}
+  FItems[pIndex] := pValue;
+end;
+
+function {{name}}.Add(const pValue: {{item}}): Integer;
+begin
+  Result := FCount;
+  Insert(FCount, pValue);
+end;
+
+procedure {{name}}.Insert(pIndex: Integer; const pValue: {{item}});
+var
+  I: Integer;
+begin
+  if (pIndex < 0) or (pIndex > FCount) then begin
+    raise Exception.CreateFmt('List index out of bounds (%d)', [pIndex]);
+  end;
+
+  if FCount = Length(FItems) then begin
+    SetLength(FItems, FCount * 2 + 4);
+  end;
+
+  for I := FCount downto pIndex + 1 do begin
+    FItems[I] := FItems[I - 1];
+  end;
+
+  FItems[pIndex] := pValue;
+  Inc(FCount);
+end;
+
+procedure {{name}}.Delete(pIndex: Integer);
+var
+  I: Integer;
+begin
+  CheckIndex(pIndex);
+  {%- if instance.owns_items %}
+  FItems[pIndex].Free;
+  {%- endif %}
+
+  for I := pIndex to FCount - 2 do begin
+    FItems[I] := FItems[I + 1];
+  end;
+
+  Dec(FCount);
+  // Releases a managed value of the unused slot
+  Finalize(FItems[FCount]);
+  FillChar(FItems[FCount], SizeOf({{item}}), 0);
+end;
+
+procedure {{name}}.Clear;
+{%- if instance.owns_items %}
+var
+  I: Integer;
+begin
+  for I := 0 to FCount - 1 do begin
+    FItems[I].Free;
+  end;
+{{""}}
+{%- else %}
+begin
+{%- endif %}
+  FItems := nil;
+  FCount := 0;
+end;
+{%- if instance.is_object %}
+
+function {{name}}.IndexOf(const pValue: {{item}}): Integer;
+var
+  I: Integer;
+begin
+  Result := -1;
+
+  for I := 0 to FCount - 1 do begin
+    if FItems[I] = pValue then begin
+      Result := I;
+      Break;
+    end;
+  end;
+end;
+
+function {{name}}.Contains(const pValue: {{item}}): Boolean;
+begin
+  Result := IndexOf(pValue) >= 0;
+end;
+
+function {{name}}.Remove(const pValue: {{item}}): Integer;
+begin
+  Result := IndexOf(pValue);
+
+  if Result >= 0 then begin
+    Delete(Result);
+  end;
+end;
+{%- endif %}
+
+function {{name}}.GetEnumerator: {{name}}Enumerator;
+begin
+  Result := {{name}}Enumerator.Create(Self);
+end;
+{%- endif %}
+{%- endmacro generic_instance_implementation %}
@@ -1,6 +1,9 @@
 {% import "macros.pas" as macros %}
 
 {%- set timestamp = now() | date(format="%d.%m.%Y %H:%m:%S") -%}
+{% if header -%}
+{{ header }}
+{% endif -%}
 // ========================================================================== //
 // Generated by Delphi Code Gen - Mode XSD2Delphi                             //
 // {{ macros::fixed_size_line(content="Version: " ~ crate_version, size=74) }} //
@@ -14,17 +17,37 @@ unit {{unitName}};
 
 interface
 
-uses System.DateUtils,
+{#- Units without generics name the units without their scope, e.g. `SysUtils` #}
+{%- if generics_free -%}
+{%- set system_scope = "" -%}
+{%- set xml_scope = "" -%}
+{%- set data_scope = "" -%}
+{%- else -%}
+{%- set system_scope = "System." -%}
+{%- set xml_scope = "Xml." -%}
+{%- set data_scope = "Data." -%}
+{%- endif %}
+
+uses {% if needs_fmt_bcd_unit_use_clause -%}
+     {{data_scope}}FmtBcd,
+     {% endif -%}
+     {% for unit in external_units -%}
+     {{unit}},
+     {% endfor -%}
+     {{system_scope}}DateUtils,
+     {% if not generics_free -%}
      System.Generics.Collections,
      System.Net.URLClient,
-     System.Types,
-     System.TypInfo,
-     System.StrUtils,
-     System.SysUtils,
-     Xml.XMLDoc,
-     Xml.XMLIntf;
+     {% endif -%}
+     {{system_scope}}Types,
+     {{system_scope}}TypInfo,
+     {{system_scope}}StrUtils,
+     {{system_scope}}SysUtils,
+     {{xml_scope}}XMLDoc,
+     {{xml_scope}}XMLIntf;
 
 type
+  {% if not generics_free -%}
   {$REGION 'Optional Helper'}
   TOptional<T> = class abstract
   strict protected
@@ -62,6 +85,57 @@ type
   end;
   {$ENDREGION}
 
+  {% endif -%}
+  {% if gen_date_time_offset -%}
+  {$REGION 'Date Time Offset'}
+  /// <summary>xs:dateTime, which keeps the time zone offset of the document</summary>
+  TXmlDateTime = record
+    /// <summary>Date and time as written in the document</summary>
+    Value: TDateTime;
+    /// <summary>Offset to UTC in minutes, e.g. 60 for +01:00</summary>
+    Offset: Integer;
+    /// <summary>False, if the value has no time zone</summary>
+    HasOffset: Boolean;
+
+    class function FromXml(const pValue: String): TXmlDateTime; static;
+    function ToXml: String;
+    /// <summary>Value converted to UTC, values without time zone are returned unchanged</summary>
+    function ToUtc: TDateTime;
+  end;
+  {$ENDREGION}
+
+  {% endif -%}
+  {% if gen_preserved_nodes -%}
+  {$REGION 'Preserved Nodes'}
+  /// <summary>Comment or processing instruction on document level</summary>
+  TPreservedXmlNode = record
+    NodeType: TNodeType;
+    Name: String;
+    Value: String;
+    BeforeRoot: Boolean;
+  end;
+  {$ENDREGION}
+
+  {% endif -%}
+  {% if gen_serializer_attributes -%}
+  {$REGION 'Serializer Attributes'}
+  /// <summary>Name of the xml node, which is read into and written from a field</summary>
+  XmlNodeAttribute = class abstract(TCustomAttribute)
+  strict private
+    FName: String;
+  public
+    constructor Create(const pName: String);
+
+    property Name: String read FName;
+  end;
+
+  XmlElementAttribute = class(XmlNodeAttribute);
+  XmlAttributeAttribute = class(XmlNodeAttribute);
+  /// <summary>Repeated sequence, whose items are written into the parent. Name is the element starting each item.</summary>
+  XmlGroupAttribute = class(XmlNodeAttribute);
+  {$ENDREGION}
+
+  {% endif -%}
   {% if enumerations | length > 0 -%}
   {$REGION 'Enumerations'}
   {%- for enum in enumerations %}
@@ -82,6 +156,9 @@ type
   {% else -%}
   {{enum.name}} = ({{enum.values | map(attribute="variant_name") | join(sep=", ")}});
   {% endif -%}
+  {% if enum.set_name -%}
+  {{enum.set_name}} = set of {{enum.name}};
+  {% endif -%}
   {% endfor -%}
   {$ENDREGION}
 
@@ -95,31 +172,139 @@ type
     function ToXmlValue: String;
   {%- endif %}
   end;
+  {% if enum.set_name %}
+  {{enum.set_name}}Helper = record helper for {{enum.set_name}}
+  {%- if gen_from_xml %}
+    class function FromXmlValue(const pXmlValue: String): {{enum.set_name}}; static;
+  {%- endif %}
+  {%- if gen_to_xml %}
+    function ToXmlValue: String;
+  {%- endif %}
+  {%- if enum.is_flags and enum.fits_into_bitmask %}
+    /// <summary>The flag with the ordinal value n is bit n of the mask</summary>
+    class function FromInteger(const pValue: UInt64): {{enum.set_name}}; static;
+    function ToInteger: UInt64;
+  {%- endif %}
+  end;
+  {% endif -%}
   {% endfor -%}
   {$ENDREGION}
   {%- endif %}
 
-  {% if classes | length > 0 -%}
+  {% if external_classes | length > 0 -%}
+  {$REGION 'External Types'}
+  {%- for external in external_classes %}
+  // XML Qualified Name: {{external.qualified_name}}
+  {{external.name}} = {{external.unit}}.{{external.delphi_type}};
+  {%- endfor %}
+  {$ENDREGION}
+
+  {% endif -%}
+  {% set generic_classes = generic_instances | filter(attribute="kind", value="list") | concat(with=generic_instances | filter(attribute="kind", value="optional")) -%}
+  {% if classes | length > 0 or union_classes | length > 0 or generic_classes | length > 0 -%}
   {$REGION 'Forward Declarations}
   {{""}}{# Requried to get a newline here #}
   {%- for class in classes -%}
   {{class.name}} = class;
   {% endfor -%}
+  {%- for union in union_classes -%}
+  {{union.name}} = class;
+  {% endfor -%}
+  {%- for instance in generic_classes -%}
+  {{instance.name}} = class;
+  {% endfor -%}
   {$ENDREGION}
   {%- endif %}
+  {%- set early_instances = generic_instances | filter(attribute="after_aliases", value=false) %}
+  {%- if early_instances | length > 0 %}
 
-  {% if type_aliases | length > 0 -%}
+  {$REGION 'Generic Instances'}
+  {%- for instance in early_instances %}
+  {{ macros::generic_instance_declaration(instance=instance) }}
+  {% endfor -%}
+  {$ENDREGION}
+  {%- endif %}
+
+  {%- set late_instances = generic_instances | filter(attribute="after_aliases", value=true) %}
+  {%- set late_instance_names = late_instances | map(attribute="name") %}
+  {#- Aliases can't be declared before the concrete lists of units without generics they name #}
+  {%- set_global instance_aliases = [] %}
+  {%- for value_type in value_types %}
+  {%- if value_type.kind == "Alias" and value_type.data_type_repr in late_instance_names %}
+  {%- set_global instance_aliases = instance_aliases | concat(with=value_type) %}
+  {%- endif %}
+  {%- endfor %}
+
+  {% if value_types | length > instance_aliases | length -%}
+  {% if union_types | length > 0 -%}
+  {$REGION 'Aliases and Union Types'}
+  {%- else -%}
   {$REGION 'Aliases'}
-  {%- for alias in type_aliases %}
-  // XML Qualified Name: {{alias.qualified_name}}
-  {% for line in alias.documentations -%}
+  {%- endif %}
+  {%- for value_type in value_types %}
+  {%- if value_type in instance_aliases %}{% continue %}{% endif %}
+  // XML Qualified Name: {{value_type.qualified_name}}
+  {% for line in value_type.documentations -%}
+  // {{line}}
+  {% endfor -%}
+  {% if value_type.kind == "Alias" -%}
+  {{value_type.name}} = {{value_type.data_type_repr}};
+  {% else -%}
+  {{value_type.name}} = record
+    type Variants = ({{value_type.variants | map(attribute="name") | join(sep=", ")}});
+
+    case Variant: Variants of
+    {%- for variant in value_type.variants %}
+      Variants.{{variant.name}}: ({{variant.variable_name}}: {{variant.data_type_repr}});
+    {%- endfor %}
+    end;
+  end;
+  {% endif -%}
+  {% endfor -%}
+  {$ENDREGION}
+  {%- endif %}
+
+  {% if records | length > 0 -%}
+  {$REGION 'Records'}
+  {%- for record in records %}
+  {{ macros::record_declaration(class=record) }}
+  {% endfor -%}
+  {$ENDREGION}
+  {%- endif %}
+  {%- if late_instances | length > 0 %}
+
+  {$REGION 'Generic Instances of Aliases and Records'}
+  {%- for instance in late_instances %}
+  {{ macros::generic_instance_declaration(instance=instance) }}
+  {% endfor -%}
+  {$ENDREGION}
+  {%- endif %}
+  {%- if instance_aliases | length > 0 %}
+
+  {$REGION 'Aliases of Generic Instances'}
+  {%- for value_type in instance_aliases %}
+  // XML Qualified Name: {{value_type.qualified_name}}
+  {% for line in value_type.documentations -%}
   // {{line}}
   {% endfor -%}
-  {{alias.name}} = {{alias.data_type_repr}};
+  {{value_type.name}} = {{value_type.data_type_repr}};
   {% endfor -%}
   {$ENDREGION}
   {%- endif %}
 
+  {% if gen_metadata_registry -%}
+  {$REGION 'Schema Metadata'}
+  /// <summary>xs:appinfo of the schema types by the names of their generated types</summary>
+  TSchemaMetadata = class abstract
+  public
+    /// <summary>Returns False, if the type has no xs:appinfo</summary>
+    class function TryGetAppInfo(const pTypeName: String; out pAppInfo: String): Boolean; static;
+    /// <summary>Names of the generated types, which have xs:appinfo</summary>
+    class function TypeNames: {{ macros::instance(generic="TArray", item="String") }}; static;
+  end;
+  {$ENDREGION}
+
+  {% endif -%}
   {$REGION 'Declarations}
   {{ macros::class_declaration(class=document) }}
   {{""}}
@@ -128,24 +313,16 @@ type
   {% endfor -%}
   {$ENDREGION}
 
-  {%- if union_types | length > 0 %}
-  {$REGION 'Union Types'}
-  {%- for union in union_types %}
-    // XML Qualified Name: {{alias.qualified_name}}
-    {% for line in union.documentations -%}
-    // {{line}}
-    {% endfor -%}
-    {{union.name}} = record
-      type Variants = ({{union.variants | map(attribute="name") | join(sep=", ")}});
-
-      case Variant: Variants of
-      {% for variant in union.variants %}
-        Variants.{{variant.name}}: {{variant.variable_name}}: {{variant.data_type_repr}};
-      {% endfor %}
-      end;
-    end;
+  {%- if union_classes | length > 0 %}
+
+  {$REGION 'Union Classes'}
+  {%- for union in union_classes %}
+  {{ macros::union_class_declaration(union=union) }}
   {% endfor -%}
   {$ENDREGION}
+  {%- endif %}
+
+  {%- if union_types | length > 0 %}
 
   {$REGION 'Union Types Helper'}
   {%- for union in union_types %}
@@ -160,31 +337,75 @@ type
   {% endfor -%}
   {$ENDREGION}
   {%- endif %}
+{%- if metadata_constants | length > 0 %}
+
+const
+  {$REGION 'Schema Metadata'}
+  {%- for constant in metadata_constants %}
+  /// <summary>{{constant.comment}}</summary>
+  {{constant.name}}: String =
+  {%- for line in constant.value %}
+    {{line}}{% if loop.last %};{% endif %}
+  {%- endfor %}
+  {%- endfor %}
+  {$ENDREGION}
+{%- endif %}
+{%- if gen_pattern_validation %}
+
+const
+  {$REGION 'Patterns'}
+  {%- for alias in type_aliases | filter(attribute="pattern_constant") %}
+  /// <summary>xs:pattern of {{alias.qualified_name}}</summary>
+  {{alias.pattern_constant}}: String =
+  {%- for line in alias.pattern_value %}
+    {{line}}{% if loop.last %};{% endif %}
+  {%- endfor %}
+  {%- endfor %}
+  {$ENDREGION}
+
+{$REGION 'Pattern Validation'}
+{%- for alias in type_aliases | filter(attribute="validator") %}
+/// <summary>Whether the value matches the xs:pattern of {{alias.name}}</summary>
+function {{alias.validator}}(const pValue: String): Boolean;
+{%- endfor %}
+{$ENDREGION}
+{%- endif %}
 
 implementation
-{% if needs_net_encoding_unit_use_clause -%}
-uses System.NetEncoding;
+{% if needs_net_encoding_unit_use_clause and gen_pattern_validation -%}
+uses {{system_scope}}NetEncoding,
+     {{system_scope}}RegularExpressions;
+{%- elif needs_net_encoding_unit_use_clause -%}
+uses {{system_scope}}NetEncoding;
+{%- elif gen_pattern_validation -%}
+uses {{system_scope}}RegularExpressions;
 {%- endif %}
 
 const
   cnXmlTrueValue: string = 'true';
   cnXmlFalseValue: string = 'false';
+  {%- if gen_datetime_helper %}
+  /// <summary>TDateTime values are in UTC, otherwise in the local time zone</summary>
+  cnXmlDateTimeIsUtc: Boolean = {% if date_time_is_utc %}True{% else %}False{% endif %};
+  {%- endif %}
 
 {% if gen_datetime_helper or gen_hex_binary_helper -%}
 {$REGION 'Helper'}
 {% if gen_datetime_helper and gen_from_xml -%}
 function DecodeDateTime(const pDateStr: String; const pFormat: String = ''): TDateTime;
 begin
-  if pFormat = '' then Exit(ISO8601ToDate(pDateStr));
+  if pFormat = '' then Exit(ISO8601ToDate(pDateStr, cnXmlDateTimeIsUtc));
 
-  Result := ISO8601ToDate(pDateStr);
+  Result := ISO8601ToDate(pDateStr, cnXmlDateTimeIsUtc);
 end;
 {%- endif %}
 
 {% if gen_datetime_helper and gen_to_xml  -%}
 function EncodeTime(const pTime: TTime; const pFormat: String): String;
+var
+  vFormatSettings: TFormatSettings;
 begin
-  var vFormatSettings := TFormatSettings.Create;
+  vFormatSettings := TFormatSettings.Create;
   vFormatSettings.LongTimeFormat := pFormat;
 
   Result := TimeToStr(pTime, vFormatSettings);
@@ -200,8 +421,9 @@ end;
 
 {% if gen_hex_binary_helper and gen_to_xml -%}
 function BinToHexStr(const pBin: TBytes): String;
+var
+  vTemp: TBytes;
 begin
-  var vTemp: TBytes;
   BinToHex(pBin, 0, vTemp, Length(pBin));
 
   Result := TEncoding.GetString(vTemp);
@@ -209,19 +431,75 @@ end;
 {%- endif %}
 {$ENDREGION}
 {%- endif %}
+{%- if gen_pattern_validation %}
+
+{$REGION 'Pattern Validation'}
+{%- for alias in type_aliases | filter(attribute="validator") %}
+function {{alias.validator}}(const pValue: String): Boolean;
+begin
+  {%- if alias.pattern_constant %}
+  // xs:pattern always matches the whole value
+  {%- endif %}
+  Result := {% if alias.pattern_constant -%}
+  TRegEx.IsMatch(pValue, '\A(?:' + {{alias.pattern_constant}} + ')\z')
+  {%- if alias.base_validator %} and {% endif -%}
+  {%- endif -%}
+  {%- if alias.base_validator %}{{alias.base_validator}}(pValue){% endif %};
+end;
+{%- if not loop.last %}
+{% endif %}
+{%- endfor %}
+{$ENDREGION}
+{%- endif %}
 
 {% if enumerations | length > 0 -%}
 {$REGION 'Enumerations Helper'}
 {%- for enum in enumerations %}
 {%- if gen_from_xml %}
 class function {{enum.name}}Helper.FromXmlValue(const pXmlValue: String): {{enum.name}};
+{%- if enum.lookup_values %}
+// Binary search over the xml values sorted by CompareStr. Needs about log2(n) comparisons
+// instead of up to n comparisons of an if chain, which pays off from about 20 values on.
+const
+  cXmlValues: array[0..{{enum.lookup_values | length - 1}}] of String = (
+    {%- for value in enum.lookup_values %}
+    {{value.xml_value | pascal_literal}}{%- if not loop.last -%}{{","}}{%- endif -%}
+    {%- endfor %}
+  );
+  cValues: array[0..{{enum.lookup_values | length - 1}}] of {{enum.name}} = (
+    {%- for value in enum.lookup_values %}
+    {{enum.name}}.{{value.variant_name}}{%- if not loop.last -%}{{","}}{%- endif -%}
+    {%- endfor %}
+  );
+var
+  vLow, vHigh, vMiddle, vComparison: Integer;
+begin
+  vLow := 0;
+  vHigh := High(cXmlValues);
+
+  while vLow <= vHigh do begin
+    vMiddle := (vLow + vHigh) div 2;
+    vComparison := CompareStr(cXmlValues[vMiddle], pXmlValue);
+
+    if vComparison = 0 then Exit(cValues[vMiddle]);
+
+    if vComparison < 0 then begin
+      vLow := vMiddle + 1;
+    end else begin
+      vHigh := vMiddle - 1;
+    end;
+  end;
+
+  raise Exception.Create('\"' + pXmlValue + '\" is a unknown value for {{enum.name}}');
+end;
+{%- else %}
 begin
   {{""}} {# Required to get newline between first if and the function begin #}
   {%- for value in enum.values %}
   {%- if loop.first -%}
-  if pXmlValue = '{{value.xml_value}}' then begin
+  if pXmlValue = {{value.xml_value | pascal_literal}} then begin
   {%- else -%}
-  {{" if"}} pXmlValue = '{{value.xml_value}}' then begin
+  {{" if"}} pXmlValue = {{value.xml_value | pascal_literal}} then begin
   {%- endif %}
     Result := {{enum.name}}.{{value.variant_name}};
   end else
@@ -230,17 +508,335 @@ begin
   end;
 end;
 {%- endif %}
+{%- endif %}
 
 {% if gen_to_xml -%}
 function {{enum.name}}Helper.ToXmlValue: String;
 begin
   case Self of
     {%- for value in enum.values %}
-    {{enum.name}}.{{value.variant_name}}: Result := '{{value.xml_value}}';
+    {{enum.name}}.{{value.variant_name}}: Result := {{value.xml_value | pascal_literal}};
+    {%- endfor %}
+  end;
+end;
+{%- endif %}
+{% if enum.set_name -%}
+{%- if gen_from_xml %}
+class function {{enum.set_name}}Helper.FromXmlValue(const pXmlValue: String): {{enum.set_name}};
+var
+  vPart: String;
+begin
+  Result := [];
+
+  for vPart in pXmlValue.Split([' '], TStringSplitOptions.ExcludeEmpty) do begin
+    Include(Result, {{enum.name}}.FromXmlValue(vPart));
+  end;
+end;
+{%- endif %}
+
+{% if gen_to_xml -%}
+function {{enum.set_name}}Helper.ToXmlValue: String;
+var
+  vValue: {{enum.name}};
+begin
+  Result := '';
+
+  for vValue in Self do begin
+    if Result <> '' then Result := Result + ' ';
+
+    Result := Result + vValue.ToXmlValue;
+  end;
+end;
+{%- endif %}
+{%- if enum.is_flags and enum.fits_into_bitmask %}
+
+class function {{enum.set_name}}Helper.FromInteger(const pValue: UInt64): {{enum.set_name}};
+var
+  vValue: {{enum.name}};
+begin
+  Result := [];
+
+  for vValue := Low({{enum.name}}) to High({{enum.name}}) do begin
+    if pValue and (UInt64(1) shl Ord(vValue)) <> 0 then begin
+      Include(Result, vValue);
+    end;
+  end;
+end;
+
+function {{enum.set_name}}Helper.ToInteger: UInt64;
+var
+  vValue: {{enum.name}};
+begin
+  Result := 0;
+
+  for vValue in Self do begin
+    Result := Result or (UInt64(1) shl Ord(vValue));
+  end;
+end;
+{%- endif %}
+{% endif -%}
+{% endfor -%}
+{$ENDREGION}
+{%- endif %}
+
+{% if gen_date_time_offset -%}
+{$REGION 'Date Time Offset'}
+class function TXmlDateTime.FromXml(const pValue: String): TXmlDateTime;
+var
+  vValue: String;
+begin
+  vValue := pValue.Trim;
+
+  Result.Offset := 0;
+  Result.HasOffset := False;
+
+  if vValue.EndsWith('Z') then begin
+    Result.HasOffset := True;
+    vValue := vValue.Substring(0, vValue.Length - 1);
+  end else if (vValue.Length > 6) and (vValue.Chars[vValue.Length - 3] = ':') and CharInSet(vValue.Chars[vValue.Length - 6], ['+', '-']) then begin
+    Result.HasOffset := True;
+    Result.Offset := StrToInt(vValue.Substring(vValue.Length - 5, 2)) * 60 + StrToInt(vValue.Substring(vValue.Length - 2, 2));
+
+    if vValue.Chars[vValue.Length - 6] = '-' then Result.Offset := -Result.Offset;
+
+    vValue := vValue.Substring(0, vValue.Length - 6);
+  end;
+
+  // Read as UTC, so the value is not converted
+  Result.Value := ISO8601ToDate(vValue + 'Z', True);
+end;
+
+function TXmlDateTime.ToXml: String;
+begin
+  Result := FormatDateTime('yyyy"-"mm"-"dd"T"hh":"nn":"ss', Value);
+
+  if MilliSecondOf(Value) <> 0 then Result := Result + FormatDateTime('"."zzz', Value);
+
+  if not HasOffset then Exit;
+
+  if Offset = 0 then Exit(Result + 'Z');
+
+  Result := Result + IfThen(Offset < 0, '-', '+') + Format('%.2d:%.2d', [Abs(Offset) div 60, Abs(Offset) mod 60]);
+end;
+
+function TXmlDateTime.ToUtc: TDateTime;
+begin
+  Result := IncMinute(Value, -Offset);
+end;
+{$ENDREGION}
+
+{% endif -%}
+{% if gen_debug_dump -%}
+{$REGION 'Debug Dump'}
+function DebugDumpObject(pObject: TObject; pVisited: {{ macros::instance(generic="TList", item="TObject") }}; pIndent: Integer): String; overload;
+begin
+  if not Assigned(pObject) then Exit('nil');
+  {%- for class in classes %}
+  {%- if not class.super_type %}
+  if pObject is {{class.name}} then Exit({{class.name}}(pObject).DebugDump(pVisited, pIndent));
+  {%- endif %}
+  {%- endfor %}
+
+  Result := pObject.ToString;
+end;
+{%- for record in records %}
+
+function DebugDumpObject(const pRecord: {{record.name}}; pVisited: {{ macros::instance(generic="TList", item="TObject") }}; pIndent: Integer): String; overload;
+begin
+  Result := pRecord.DebugDump(pVisited, pIndent);
+end;
+{%- endfor %}
+{$ENDREGION}
+
+{% endif -%}
+{% if gen_metadata_registry -%}
+{%- set app_infos = metadata_constants | filter(attribute="type_name") -%}
+{$REGION 'Schema Metadata'}
+class function TSchemaMetadata.TryGetAppInfo(const pTypeName: String; out pAppInfo: String): Boolean;
+begin
+  {%- if app_infos | length > 0 %}
+  Result := True;
+
+  case IndexStr(pTypeName, [{% for constant in app_infos %}'{{constant.type_name}}'{% if not loop.last %}, {% endif %}{% endfor %}]) of
+    {%- for constant in app_infos %}
+    {{loop.index0}}: pAppInfo := {{constant.name}};
     {%- endfor %}
+  else
+    pAppInfo := '';
+    Result := False;
+  end;
+  {%- else %}
+  pAppInfo := '';
+  Result := False;
+  {%- endif %}
+end;
+
+class function TSchemaMetadata.TypeNames: {{ macros::instance(generic="TArray", item="String") }};
+begin
+  Result := [{% for constant in app_infos %}'{{constant.type_name}}'{% if not loop.last %}, {% endif %}{% endfor %}];
+end;
+{$ENDREGION}
+
+{% endif -%}
+{% if gen_constraint_checks -%}
+{$REGION 'Identity Constraints'}
+type
+  /// <summary>Values of the xs:key and xs:unique constraints, which are checked by CheckConstraints</summary>
+  TIdentityConstraints = class
+  strict private
+    {%- if generics_free %}
+    /// <summary>Constraints and values of the added nodes, joined by #31, and the paths of the nodes</summary>
+    FKeys: {{ macros::instance(generic="TList", item="String") }};
+    FPaths: {{ macros::instance(generic="TList", item="String") }};
+    {%- else %}
+    FValues: TObjectDictionary<String, TDictionary<String, String>>;
+    {%- endif %}
+    FViolations: {{ macros::instance(generic="TList", item="String") }};
+
+    {% if generics_free -%}
+    function IndexOfKey(const pConstraint: String; const pValues: {{ macros::instance(generic="TArray", item="String") }}): Integer;
+    {%- else -%}
+    function ValuesOf(const pConstraint: String): TDictionary<String, String>;
+    {%- endif %}
+    procedure Add(const pConstraint, pPath: String; const pValues: {{ macros::instance(generic="TArray", item="String") }});
+  public
+    constructor Create;
+    destructor Destroy; override;
+
+    /// <summary>Adds the values of a node selected by xs:key, whose fields are required</summary>
+    procedure AddKey(const pConstraint, pPath: String; const pValues: {{ macros::instance(generic="TArray", item="String") }}; pComplete: Boolean);
+    /// <summary>Adds the values of a node selected by xs:unique, nodes with missing fields are ignored</summary>
+    procedure AddUnique(const pConstraint, pPath: String; const pValues: {{ macros::instance(generic="TArray", item="String") }}; pComplete: Boolean);
+    /// <summary>Checks, that the values of a node selected by xs:keyref are values of the referenced key</summary>
+    procedure CheckReference(const pConstraint, pRefer, pPath: String; const pValues: {{ macros::instance(generic="TArray", item="String") }}; pComplete: Boolean);
+    function Violations: {{ macros::instance(generic="TArray", item="String") }};
+  end;
+
+constructor TIdentityConstraints.Create;
+begin
+  inherited;
+  {%- if generics_free %}
+  FKeys := {{ macros::instance(generic="TList", item="String") }}.Create;
+  FPaths := {{ macros::instance(generic="TList", item="String") }}.Create;
+  {%- else %}
+  FValues := TObjectDictionary<String, TDictionary<String, String>>.Create([doOwnsValues]);
+  {%- endif %}
+  FViolations := {{ macros::instance(generic="TList", item="String") }}.Create;
+end;
+
+destructor TIdentityConstraints.Destroy;
+begin
+  {%- if generics_free %}
+  FKeys.Free;
+  FPaths.Free;
+  {%- else %}
+  FValues.Free;
+  {%- endif %}
+  FViolations.Free;
+  inherited;
+end;
+{%- if generics_free %}
+
+function TIdentityConstraints.IndexOfKey(const pConstraint: String; const pValues: {{ macros::instance(generic="TArray", item="String") }}): Integer;
+var
+  vKey: String;
+  I: Integer;
+begin
+  vKey := pConstraint + #31 + String.Join(#31, pValues);
+  Result := -1;
+
+  for I := 0 to FKeys.Count - 1 do begin
+    if FKeys[I] = vKey then begin
+      Result := I;
+      Break;
+    end;
+  end;
+end;
+
+procedure TIdentityConstraints.Add(const pConstraint, pPath: String; const pValues: {{ macros::instance(generic="TArray", item="String") }});
+var
+  vIndex: Integer;
+begin
+  vIndex := IndexOfKey(pConstraint, pValues);
+
+  if vIndex >= 0 then begin
+    FViolations.Add(Format('%s: Value "%s" of "%s" is already used by %s', [pPath, String.Join(', ', pValues), pConstraint, FPaths[vIndex]]));
+  end else begin
+    FKeys.Add(pConstraint + #31 + String.Join(#31, pValues));
+    FPaths.Add(pPath);
+  end;
+end;
+{%- else %}
+
+function TIdentityConstraints.ValuesOf(const pConstraint: String): TDictionary<String, String>;
+begin
+  if not FValues.TryGetValue(pConstraint, Result) then begin
+    Result := TDictionary<String, String>.Create;
+    FValues.Add(pConstraint, Result);
+  end;
+end;
+
+procedure TIdentityConstraints.Add(const pConstraint, pPath: String; const pValues: {{ macros::instance(generic="TArray", item="String") }});
+var
+  vFirstPath: String;
+begin
+  if ValuesOf(pConstraint).TryGetValue(String.Join(#31, pValues), vFirstPath) then begin
+    FViolations.Add(Format('%s: Value "%s" of "%s" is already used by %s', [pPath, String.Join(', ', pValues), pConstraint, vFirstPath]));
+  end else begin
+    ValuesOf(pConstraint).Add(String.Join(#31, pValues), pPath);
   end;
 end;
 {%- endif %}
+
+procedure TIdentityConstraints.AddKey(const pConstraint, pPath: String; const pValues: {{ macros::instance(generic="TArray", item="String") }}; pComplete: Boolean);
+begin
+  if pComplete then begin
+    Add(pConstraint, pPath, pValues);
+  end else begin
+    FViolations.Add(Format('%s: Field of key "%s" is missing', [pPath, pConstraint]));
+  end;
+end;
+
+procedure TIdentityConstraints.AddUnique(const pConstraint, pPath: String; const pValues: {{ macros::instance(generic="TArray", item="String") }}; pComplete: Boolean);
+begin
+  if pComplete then begin
+    Add(pConstraint, pPath, pValues);
+  end;
+end;
+
+procedure TIdentityConstraints.CheckReference(const pConstraint, pRefer, pPath: String; const pValues: {{ macros::instance(generic="TArray", item="String") }}; pComplete: Boolean);
+begin
+  {% if generics_free -%}
+  if pComplete and (IndexOfKey(pRefer, pValues) < 0) then begin
+  {%- else -%}
+  if pComplete and not ValuesOf(pRefer).ContainsKey(String.Join(#31, pValues)) then begin
+  {%- endif %}
+    FViolations.Add(Format('%s: Value "%s" of "%s" does not match a value of "%s"', [pPath, String.Join(', ', pValues), pConstraint, pRefer]));
+  end;
+end;
+
+function TIdentityConstraints.Violations: {{ macros::instance(generic="TArray", item="String") }};
+{%- if generics_free %}
+var
+  I: Integer;
+begin
+  SetLength(Result, FViolations.Count);
+
+  for I := 0 to FViolations.Count - 1 do begin
+    Result[I] := FViolations[I];
+  end;
+{%- else %}
+begin
+  Result := FViolations.ToArray;
+{%- endif %}
+end;
+{$ENDREGION}
+
+{% endif -%}
+{% if records | length > 0 -%}
+{$REGION 'Records'}
+{%- for record in records %}
+{{  macros::class_implementation(class=record)  }}
 {% endfor -%}
 {$ENDREGION}
 {%- endif %}
@@ -253,18 +849,42 @@ end;
 {% endfor -%}
 {$ENDREGION}
 
+{%- if union_classes | length > 0 %}
+
+{$REGION 'Union Classes'}
+{%- for union in union_classes %}
+{{  macros::union_class_implementation(union=union)  }}
+{% endfor -%}
+{$ENDREGION}
+{%- endif %}
+
 {%- if union_types | length > 0 %}
 {$REGION 'Union Types Helper'}
 {%- for union in union_types %}
-{{union.name}}Helper = record helper for {{union.name}}
+{{"{"}} {{union.name}}Helper {{"}"}}
 {%- if gen_from_xml %}
 class function {{union.name}}Helper.FromXml(node: IXMLNode): {{union.name}};
 begin
-  // TODO: CodeGen for this is currently not supported. Manual implementation required
+  // The first variant, which can be read from the node, is used
+  {%- for variant in union.variants %}
+  try
+    Result.{{variant.variable_name}} := {{variant.from_xml_code}};
+    Result.Variant := Variants.{{variant.name}};
+    Exit;
+  except
+    on Exception do ;
+  end;
+  {%- endfor %}
+
+  raise Exception.Create('Node "' + node.NodeName + '" does not match any variant of {{union.name}}');
 end;
 {%- endif %}
 {%- if gen_to_xml %}
 function {{union.name}}Helper.ToXmlValue: String;
+{%- if union.variants | filter(attribute="is_inline_list", value=true) | length > 0 %}
+var
+  I: Integer;
+{%- endif %}
 begin
   case Self.Variant of
   {% for variant in union.variants %}
@@ -274,7 +894,7 @@ begin
     Variants.{{variant.name}}: begin
       Result := '';
 
-      for var I := Low({{variant.variable_name}}) to High({{variant.variable_name}}) do begin
+      for I := Low({{variant.variable_name}}) to High({{variant.variable_name}}) do begin
         Result := Result + {{variant.value_as_str_repr}};
 
         if I < High({{variant.variable_name}}) then begin
@@ -291,11 +911,30 @@ begin
   end;
 end;
 {%- endif %}
-end;
 {%- endfor %}
 {$ENDREGION}
 {%- endif %}
 
+{% if gen_serializer_attributes -%}
+{$REGION 'Serializer Attributes'}
+{ XmlNodeAttribute }
+constructor XmlNodeAttribute.Create(const pName: String);
+begin
+  inherited Create;
+  FName := pName;
+end;
+{$ENDREGION}
+
+{% endif -%}
+{% if generics_free -%}
+{%- if generic_instances | filter(attribute="kind", value="array") | length < generic_instances | length -%}
+{$REGION 'Generic Instances'}
+{%- for instance in generic_instances %}
+{{- macros::generic_instance_implementation(instance=instance) }}
+{%- endfor %}
+{$ENDREGION}
+{%- endif %}
+{%- else -%}
 {$REGION 'Optional Helper'}
 { TOptional<T> }
 function TOptional<T>.Unwrap: T;
@@ -366,5 +1005,13 @@ begin
   Self.Free;
 end;
 {$ENDREGION}
+{%- endif %}
+{%- if include_file %}
+
+// Manual code, which is implemented in {{include_file}}
+{$DEFINE MANUAL_IMPLEMENTATION}
+{$INCLUDE {{include_file}}}
+{$UNDEF MANUAL_IMPLEMENTATION}
+{%- endif %}
 
 end.
\ No newline at end of file
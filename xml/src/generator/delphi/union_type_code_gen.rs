@@ -3,10 +3,10 @@ use crate::generator::{
     delphi::template_models::{
         UnionType as TemplateUnionType, UnionVariant as TemplateUnionVariant,
     },
-    types::{DataType, Enumeration, TypeAlias, UnionType},
+    types::{DataType, Enumeration, ResolvedTypeAliases, UnionType},
 };
 
-use super::helper::Helper;
+use super::{class_code_gen::ClassCodeGenerator, helper::Helper};
 
 /// Code generator for union types.
 ///
@@ -14,7 +14,7 @@ use super::helper::Helper;
 ///
 /// ## Input
 ///
-/// ```rust
+/// ```ignore
 /// use xsd_types::generator::types::{DataType, UnionType, UnionTypeVariant};
 ///
 /// let union_type = UnionType {
@@ -36,7 +36,7 @@ use super::helper::Helper;
 /// ## Output
 ///
 /// ```delphi
-/// {$REGION 'Union Types'}
+/// {$REGION 'Aliases and Union Types'}
 /// /// <summary>
 /// /// UnionType
 /// /// </summary>
@@ -55,24 +55,72 @@ use super::helper::Helper;
 /// end;
 /// {$ENDREGION}
 /// ```
+///
+/// If a variant is a reference type, the union is generated as an abstract class
+/// with one descendant per variant instead. `FromXml` of the abstract class creates
+/// the first variant, which can be read from the node.
+///
+/// ```delphi
+/// TUnionType = class abstract
+/// public
+///   class function FromXml(node: IXMLNode): TUnionType; static;
+///   procedure AppendToXmlRaw(pParent: IXMLNode); virtual; abstract;
+/// end;
+///
+/// TUnionTypeShape = class(TUnionType)
+/// strict private
+///   FValue: TShape;
+/// public
+///   constructor Create(pValue: TShape);
+///   constructor FromXml(node: IXMLNode);
+///   destructor Destroy; override;
+///   procedure AppendToXmlRaw(pParent: IXMLNode); override;
+///
+///   property Value: TShape read FValue;
+/// end;
+/// ```
 pub struct UnionTypeCodeGenerator {}
 
 impl UnionTypeCodeGenerator {
     pub(crate) fn build_template_models<'a>(
         union_types: &'a [UnionType],
-        type_aliases: &'a [TypeAlias],
+        type_aliases: &ResolvedTypeAliases<'a>,
         enumerations: &[Enumeration],
         options: &'a CodeGenOptions,
     ) -> Vec<TemplateUnionType<'a>> {
         union_types
             .iter()
             .map(|u| {
+                let name = Helper::as_type_name(&u.name, &options.naming.type_prefix);
                 let variant_prefix = Self::get_enum_variant_prefix(&u.name, options);
-                let documentations = u
-                    .documentations
-                    .iter()
-                    .flat_map(|d| d.lines())
-                    .collect::<Vec<&str>>();
+                let documentations = Helper::documentation_lines(&u.documentations);
+
+                if u.is_class_hierarchy() {
+                    return TemplateUnionType {
+                        variants: u
+                            .variants
+                            .iter()
+                            .enumerate()
+                            .map(|(i, v)| {
+                                Self::build_class_variant(
+                                    Self::get_variant_enum_variant_name(
+                                        &variant_prefix,
+                                        &v.name,
+                                        i,
+                                    ),
+                                    Self::get_variant_enum_variant_name(&name, &v.name, i),
+                                    &v.data_type,
+                                    type_aliases,
+                                    options,
+                                )
+                            })
+                            .collect::<Vec<TemplateUnionVariant>>(),
+                        name,
+                        qualified_name: &u.qualified_name,
+                        is_class_hierarchy: true,
+                        documentations,
+                    };
+                }
 
                 let variants = u
                     .variants
@@ -161,36 +209,49 @@ impl UnionTypeCodeGenerator {
                                             DataType::String => "string[255]".to_owned(),
                                             _ => Helper::get_datatype_language_representation(
                                                 &v.data_type,
-                                                &options.type_prefix,
+                                                &options.naming.type_prefix,
+                                                options.output.generics_free,
                                             ),
                                         }
                                     } else {
                                         "Unknown".to_owned()
                                     }
                                 }
+                                // Managed types aren't allowed in the variant part of records
+                                DataType::String => "string[255]".to_owned(),
                                 DataType::InlineList(lt) => format!(
                                     "array[1..256] of {}",
                                     Helper::get_datatype_language_representation(
                                         lt.as_ref(),
-                                        &options.type_prefix,
+                                        &options.naming.type_prefix,
+                                        options.output.generics_free,
                                     ),
                                 ),
                                 _ => Helper::get_datatype_language_representation(
                                     &v.data_type,
-                                    &options.type_prefix,
+                                    &options.naming.type_prefix,
+                                    options.output.generics_free,
                                 ),
                             },
                             use_to_xml_func,
                             is_inline_list,
                             is_list_type,
                             value_as_str_repr,
+                            class_name: String::new(),
+                            is_class: false,
+                            from_xml_code: Self::value_from_xml_code(
+                                &v.data_type,
+                                type_aliases,
+                                options,
+                            ),
                         }
                     })
                     .collect::<Vec<TemplateUnionVariant>>();
 
                 TemplateUnionType {
-                    name: Helper::as_type_name(&u.name, &options.type_prefix),
+                    name,
                     qualified_name: &u.qualified_name,
+                    is_class_hierarchy: false,
                     documentations,
                     variants,
                 }
@@ -198,16 +259,143 @@ impl UnionTypeCodeGenerator {
             .collect::<Vec<TemplateUnionType<'a>>>()
     }
 
-    fn get_enum_variant_prefix(name: &String, options: &CodeGenOptions) -> String {
+    /// Builds the template model for the descendant class of a variant,
+    /// which holds the value in `FValue` and is read from `node`.
+    fn build_class_variant(
+        name: String,
+        class_name: String,
+        data_type: &DataType,
+        type_aliases: &ResolvedTypeAliases,
+        options: &CodeGenOptions,
+    ) -> TemplateUnionVariant {
+        let value = String::from("FValue");
+        let data_type_repr = Helper::get_datatype_language_representation(
+            data_type,
+            &options.naming.type_prefix,
+            options.output.generics_free,
+        );
+        let mut variant = TemplateUnionVariant {
+            variable_name: value.clone(),
+            name,
+            data_type_repr: data_type_repr.clone(),
+            is_list_type: false,
+            is_inline_list: false,
+            use_to_xml_func: false,
+            value_as_str_repr: String::new(),
+            class_name,
+            is_class: false,
+            from_xml_code: String::new(),
+        };
+
+        match data_type {
+            DataType::Custom(_) | DataType::Record(_) => {
+                variant.is_class = true;
+                variant.from_xml_code = Self::value_from_xml_code(data_type, type_aliases, options);
+            }
+            DataType::Enumeration(_) | DataType::Union(_) => {
+                variant.use_to_xml_func = true;
+                variant.from_xml_code = Self::value_from_xml_code(data_type, type_aliases, options);
+            }
+            DataType::InlineList(item_type) => {
+                let (item_type, pattern) = match item_type.as_ref() {
+                    DataType::Alias(n) => Helper::get_alias_data_type(n, type_aliases)
+                        .unwrap_or((item_type.as_ref().clone(), None)),
+                    _ => (item_type.as_ref().clone(), None),
+                };
+
+                variant.is_inline_list = true;
+                (variant.from_xml_code, variant.value_as_str_repr) = match &item_type {
+                    DataType::Enumeration(n) => {
+                        let type_name = Helper::as_type_name(n, &options.naming.type_prefix);
+
+                        (
+                            format!("{type_name}.FromXmlValue(vPart)"),
+                            format!("{value}[I].ToXmlValue"),
+                        )
+                    }
+                    _ => (
+                        ClassCodeGenerator::generate_standard_type_from_xml(
+                            &item_type,
+                            String::from("vPart"),
+                            pattern.clone(),
+                        ),
+                        Helper::get_variable_value_as_string(
+                            &item_type,
+                            &format!("{value}[I]"),
+                            &pattern,
+                        ),
+                    ),
+                };
+            }
+            DataType::Alias(n) => {
+                let (alias_type, pattern) = Helper::get_alias_data_type(n, type_aliases)
+                    .unwrap_or((DataType::String, None));
+
+                variant.from_xml_code = Self::value_from_xml_code(data_type, type_aliases, options);
+                variant.value_as_str_repr =
+                    Helper::get_variable_value_as_string(&alias_type, &value, &pattern);
+            }
+            _ => {
+                variant.from_xml_code = Self::value_from_xml_code(data_type, type_aliases, options);
+                variant.value_as_str_repr =
+                    Helper::get_variable_value_as_string(data_type, &value, &None);
+            }
+        }
+
+        variant
+    }
+
+    /// Code, which reads a value of a variant from `node` or raises an exception, so
+    /// `FromXml` of the union can try the next variant. Therefore booleans only accept
+    /// boolean values, unlike the elements of classes.
+    fn value_from_xml_code(
+        data_type: &DataType,
+        type_aliases: &ResolvedTypeAliases,
+        options: &CodeGenOptions,
+    ) -> String {
+        let data_type_repr = Helper::get_datatype_language_representation(
+            data_type,
+            &options.naming.type_prefix,
+            options.output.generics_free,
+        );
+
+        match data_type {
+            DataType::Custom(_) | DataType::Record(_) | DataType::Union(_) => {
+                format!("{data_type_repr}.FromXml(node)")
+            }
+            DataType::Enumeration(_) => format!("{data_type_repr}.FromXmlValue(node.Text)"),
+            DataType::Alias(n) => {
+                let (alias_type, pattern) = Helper::get_alias_data_type(n, type_aliases)
+                    .unwrap_or((DataType::String, None));
+
+                match alias_type {
+                    DataType::Boolean => String::from("StrToBool(node.Text)"),
+                    _ => ClassCodeGenerator::generate_standard_type_from_xml(
+                        &alias_type,
+                        String::from("node.Text"),
+                        pattern,
+                    ),
+                }
+            }
+            DataType::Boolean => String::from("StrToBool(node.Text)"),
+            _ => ClassCodeGenerator::generate_standard_type_from_xml(
+                data_type,
+                String::from("node.Text"),
+                None,
+            ),
+        }
+    }
+
+    fn get_enum_variant_prefix(name: &str, options: &CodeGenOptions) -> String {
         let enum_type_name = format!(
             "{}Variants",
-            Helper::as_type_name(name, &options.type_prefix)
+            Helper::as_type_name(name, &options.naming.type_prefix)
         );
 
         Helper::get_enum_variant_prefix(enum_type_name.as_str())
     }
 
-    fn get_variant_enum_variant_name(prefix: &String, name: &String, index: usize) -> String {
+    fn get_variant_enum_variant_name(prefix: &str, name: &str, index: usize) -> String {
         if name.is_empty() {
             format!("{}{}", prefix, index + 1)
         } else {
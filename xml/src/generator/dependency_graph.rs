@@ -17,7 +17,7 @@ where
 ///
 /// # Example
 ///
-/// ```
+/// ```text
 /// CustomNumber
 /// Alias1 -> Alias2 -> CustomNumber
 /// Alias3 -> CustomNumber
@@ -34,13 +34,18 @@ where
 /// => List
 /// CustomNumber, Alias3, Alias2, Alias1, Alias4, Alias5
 /// ```
-
 pub struct DependencyGraph<K, T>
 where
     K: Eq + PartialEq + Hash + Clone,
-    T: Clone + Dependable<K>,
+    T: Dependable<K>,
 {
     dependencies: HashMap<K, Node<K, T>>,
+    /// Keys of the items in the order they were pushed, which decides the order of
+    /// items, which don't depend on each other
+    order: Vec<K>,
+    /// Keys of the items depending on a key. Without the index every push scans all items,
+    /// which needs less memory, but is quadratic in the number of items.
+    dependents: Option<HashMap<K, Vec<K>>>,
 }
 
 #[derive(Debug)]
@@ -64,11 +69,19 @@ impl<K, T> DependencyGraph<K, T>
 where
     // K: Sized,
     K: Eq + PartialEq + Hash + Clone,
-    T: Clone + Dependable<K>,
+    T: Dependable<K>,
 {
     pub fn new() -> Self {
+        Self::with_index(true)
+    }
+
+    /// Creates a graph, which finds the items depending on a pushed item by an index or
+    /// by scanning all items
+    pub fn with_index(indexed: bool) -> Self {
         Self {
             dependencies: HashMap::new(),
+            order: Vec::new(),
+            dependents: indexed.then(HashMap::new),
         }
     }
 
@@ -84,43 +97,101 @@ where
                     dependency.children.push(item_key.clone());
                 }
 
-                node.parents.push(dep_key.clone());
+                if let Some(dependents) = &mut self.dependents {
+                    dependents
+                        .entry(dep_key.clone())
+                        .or_default()
+                        .push(item_key.clone());
+                }
+
+                node.parents.push(dep_key);
             }
         }
 
-        for value in self.dependencies.values() {
-            if value.parents.contains(item_key) {
-                let value_key = value.item.key();
+        match &self.dependents {
+            Some(dependents) => {
+                // The index contains the item itself, if it depends on itself
+                let pushed_dependents = dependents
+                    .get(item_key)
+                    .into_iter()
+                    .flatten()
+                    .filter(|k| *k != item_key && self.dependencies.contains_key(k));
+
+                for key in pushed_dependents {
+                    if !node.children.contains(key) {
+                        node.children.push(key.clone());
+                    }
+                }
+            }
+            None => {
+                for value in self.dependencies.values() {
+                    if value.parents.contains(item_key) {
+                        let value_key = value.item.key();
 
-                if !node.children.contains(value_key) {
-                    node.children.push(value_key.clone());
+                        if !node.children.contains(value_key) {
+                            node.children.push(value_key.clone());
+                        }
+                    }
                 }
             }
         }
 
+        if !self.dependencies.contains_key(item_key) {
+            self.order.push(item_key.clone());
+        }
+
         self.dependencies.insert(item_key.clone(), node);
     }
 
-    /// Returns a list of the items of the dependency graph sorted by their dependencies
-    pub fn get_sorted_elements(&self) -> Vec<T> {
-        let mut unique = HashSet::new();
+    /// Returns a list of the items of the dependency graph sorted by their dependencies.
+    /// Items, which don't depend on each other, keep the order they were pushed in, so the
+    /// same items always result in the same list.
+    ///
+    /// Items of a cycle, which can't be sorted, are added in the order they were pushed in.
+    ///
+    /// The graph is consumed, so every item is moved into the list instead of being cloned.
+    pub fn into_sorted_elements(mut self) -> Vec<T> {
+        let mut visited = HashSet::new();
+        let mut keys = Vec::with_capacity(self.dependencies.len());
+
+        for key in &self.order {
+            if self.dependencies[key].children.is_empty() {
+                self.collect_creation_order(key, &mut visited, &mut keys);
+            }
+        }
+
+        // Items of cycles always have children
+        for key in &self.order {
+            self.collect_creation_order(key, &mut visited, &mut keys);
+        }
 
-        self.dependencies
-            .values()
-            .filter(|i| i.children.is_empty())
-            .flat_map(|node| self.get_node_creation_order(node))
-            .filter(|i| unique.insert(i.key().clone()))
-            .collect::<Vec<T>>()
+        keys.into_iter()
+            .filter_map(|k| self.dependencies.remove(&k))
+            .map(|n| n.item)
+            .collect()
     }
 
-    fn get_node_creation_order(&self, node: &Node<K, T>) -> Vec<T> {
-        node.parents
-            .iter()
-            .map(|i| self.dependencies.get(i))
-            .filter_map(|v| v.map(|i| self.get_node_creation_order(i)))
-            .flatten()
-            .chain(vec![node.item.clone()])
-            .collect::<Vec<T>>()
+    /// Collects the keys of the parents of a node followed by its own key.
+    /// Nodes, which were already visited, are skipped, so shared parents are added only once.
+    fn collect_creation_order<'a>(
+        &'a self,
+        key: &'a K,
+        visited: &mut HashSet<&'a K>,
+        keys: &mut Vec<K>,
+    ) {
+        let Some(node) = self.dependencies.get(key) else {
+            return;
+        };
+
+        if !visited.insert(key) {
+            return;
+        }
+
+        for parent in &node.parents {
+            self.collect_creation_order(parent, visited, keys);
+        }
+
+        keys.push(key.clone());
     }
 }
 
@@ -128,7 +199,7 @@ impl<K, T> Default for DependencyGraph<K, T>
 where
     // K: Sized,
     K: Eq + PartialEq + Hash + Clone,
-    T: Clone + Dependable<K>,
+    T: Dependable<K>,
 {
     fn default() -> Self {
         Self::new()
@@ -156,18 +227,53 @@ mod tests {
     }
 
     #[test]
-    fn get_sorted_elements_with_empty_graph() {
+    fn into_sorted_elements_with_empty_graph() {
         let graph = DependencyGraph::<String, GraphItem>::new();
 
-        let items = graph.get_sorted_elements();
+        let items = graph.into_sorted_elements();
 
         assert_eq!(items.len(), 0);
     }
 
     #[test]
-    fn get_sorted_elements_with_duplicates() {
-        let mut graph = DependencyGraph::<String, GraphItem>::new();
+    fn into_sorted_elements_with_duplicates() {
+        assert_sorted_by_dependencies(DependencyGraph::new());
+    }
+
+    #[test]
+    fn into_sorted_elements_without_index() {
+        assert_sorted_by_dependencies(DependencyGraph::with_index(false));
+    }
+
+    #[test]
+    fn into_sorted_elements_keeps_push_order_and_cycles() {
+        for indexed in [true, false] {
+            let mut graph = DependencyGraph::with_index(indexed);
+
+            for (key, dep) in [
+                ("Zeta", None),
+                ("CycleA", Some("CycleB")),
+                ("Alpha", Some("Zeta")),
+                ("CycleB", Some("CycleA")),
+                ("Beta", None),
+            ] {
+                graph.push(GraphItem {
+                    key: key.to_owned(),
+                    dep: dep.map(str::to_owned),
+                });
+            }
+
+            let keys = graph
+                .into_sorted_elements()
+                .into_iter()
+                .map(|i| i.key)
+                .collect::<Vec<_>>();
+
+            assert_eq!(keys, ["Zeta", "Alpha", "Beta", "CycleB", "CycleA"]);
+        }
+    }
 
+    fn assert_sorted_by_dependencies(mut graph: DependencyGraph<String, GraphItem>) {
         graph.push(GraphItem {
             key: "Alias3".to_owned(),
             dep: Some("CustomNumber".to_owned()),
@@ -197,7 +303,7 @@ mod tests {
             dep: None,
         });
 
-        let items = graph.get_sorted_elements();
+        let items = graph.into_sorted_elements();
 
         let cni = items.iter().position(|i| i.key == "CustomNumber").unwrap();
         let a3i = items.iter().position(|i| i.key == "Alias3").unwrap();
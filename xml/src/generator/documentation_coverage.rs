@@ -0,0 +1,230 @@
+use std::collections::HashSet;
+
+use serde::Serialize;
+use serde_json::json;
+
+use super::{
+    delphi::helper::Helper, internal_representation::InternalRepresentation, types::XMLSource,
+};
+
+/// Format of the documentation coverage report
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CoverageFormat {
+    /// Object with the counts of the documented and all entries and the missing entries
+    #[default]
+    Json,
+    /// One line per missing entry with the columns `kind`, `type`, `member` and `delphi_name`
+    Csv,
+}
+
+/// Type, field or enumeration value without xs:documentation in the schema
+#[derive(Debug, PartialEq, Eq, Serialize)]
+struct MissingDocumentation {
+    /// `class`, `enumeration`, `alias`, `union`, `element`, `attribute` or `value`
+    kind: &'static str,
+    /// Qualified name of the type, empty for the root elements
+    #[serde(rename = "type")]
+    type_name: String,
+    /// Name of the element or attribute, or the value of the enumeration. Empty for types.
+    member: String,
+    /// Name of the generated type or member
+    delphi_name: String,
+}
+
+impl InternalRepresentation {
+    /// Reports the types, elements, attributes and enumeration values generated from the
+    /// schemas, which aren't documented by xs:documentation, so the schemas can be improved.
+    ///
+    /// Repeated sequences are left out, because they have no annotation of their own.
+    pub fn to_documentation_coverage(
+        &self,
+        type_prefix: &Option<String>,
+        format: CoverageFormat,
+    ) -> String {
+        let mut total = 0;
+        let mut missing = Vec::new();
+        let mut entry = |is_documented: bool, kind, type_name: &str, member: &str, delphi_name| {
+            total += 1;
+
+            if !is_documented {
+                missing.push(MissingDocumentation {
+                    kind,
+                    type_name: type_name.to_owned(),
+                    member: member.to_owned(),
+                    delphi_name,
+                });
+            }
+        };
+
+        let sequences = self
+            .classes
+            .iter()
+            .flat_map(|c| &c.variables)
+            .filter(|v| v.source == XMLSource::Group)
+            .filter_map(|v| v.data_type.referenced_type_name())
+            .collect::<HashSet<_>>();
+
+        for class in self.classes.iter().filter(|c| !sequences.contains(&c.name)) {
+            let is_document = class.qualified_name == self.document.qualified_name;
+            let type_name = match is_document {
+                true => "",
+                false => class.qualified_name.as_str(),
+            };
+            let delphi_type = Helper::as_type_name(&class.name, type_prefix);
+
+            if !is_document {
+                entry(
+                    class.is_documented,
+                    "class",
+                    type_name,
+                    "",
+                    delphi_type.clone(),
+                );
+            }
+
+            for variable in &class.variables {
+                let (kind, member) = match variable.source {
+                    XMLSource::Element => ("element", variable.xml_name.clone()),
+                    XMLSource::Attribute => ("attribute", format!("@{}", variable.xml_name)),
+                    XMLSource::Group => continue,
+                };
+
+                entry(
+                    variable.is_documented,
+                    kind,
+                    type_name,
+                    &member,
+                    format!("{delphi_type}.{}", Helper::as_variable_name(&variable.name)),
+                );
+            }
+        }
+
+        for enumeration in &self.enumerations {
+            let delphi_type = Helper::as_type_name(&enumeration.name, type_prefix);
+            let variant_prefix = Helper::get_enum_variant_prefix(&enumeration.name);
+
+            entry(
+                enumeration.is_documented,
+                "enumeration",
+                &enumeration.qualified_name,
+                "",
+                delphi_type.clone(),
+            );
+
+            for value in &enumeration.values {
+                entry(
+                    value.is_documented,
+                    "value",
+                    &enumeration.qualified_name,
+                    &value.xml_value,
+                    format!(
+                        "{delphi_type}.{variant_prefix}{}",
+                        Helper::first_char_uppercase(&value.variant_name)
+                    ),
+                );
+            }
+        }
+
+        for alias in &self.types_aliases {
+            entry(
+                alias.is_documented,
+                "alias",
+                &alias.qualified_name,
+                "",
+                Helper::as_type_name(&alias.name, type_prefix),
+            );
+        }
+
+        for union_type in &self.union_types {
+            entry(
+                union_type.is_documented,
+                "union",
+                &union_type.qualified_name,
+                "",
+                Helper::as_type_name(&union_type.name, type_prefix),
+            );
+        }
+
+        match format {
+            CoverageFormat::Json => serde_json::to_string_pretty(&json!({
+                "total": total,
+                "documented": total - missing.len(),
+                "missing": missing,
+            }))
+            .unwrap_or_default(),
+            CoverageFormat::Csv => missing.iter().fold(
+                String::from("kind,type,member,delphi_name\n"),
+                |mut csv, m| {
+                    let line = [m.kind, &m.type_name, &m.member, &m.delphi_name]
+                        .map(csv_field)
+                        .join(",");
+
+                    csv.push_str(&line);
+                    csv.push('\n');
+                    csv
+                },
+            ),
+        }
+    }
+}
+
+/// Quotes fields with separators, quotes or line breaks
+fn csv_field(value: &str) -> String {
+    match value.contains([',', '"', '\n', '\r']) {
+        true => format!("\"{}\"", value.replace('"', "\"\"")),
+        false => value.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{
+        build_representation, generator::code_generator_trait::CodeGenOptions,
+        type_registry::TypeRegistry,
+    };
+
+    fn build() -> InternalRepresentation {
+        let (_, mut representation) = build_representation(
+            &[PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/documentation.xsd")],
+            &CodeGenOptions::default(),
+            &mut TypeRegistry::new(),
+        )
+        .unwrap();
+
+        representation.classes.sort_by(|a, b| a.name.cmp(&b.name));
+        representation
+    }
+
+    #[test]
+    fn lists_the_entries_without_documentation() {
+        let report = build().to_documentation_coverage(&None, CoverageFormat::Csv);
+
+        assert_eq!(
+            report,
+            "kind,type,member,delphi_name\n\
+             element,,status,TDocument.Status\n\
+             element,Order,number,TOrder.Number\n\
+             attribute,Order,@note,TOrder.Note\n\
+             class,Order.item,,TItem\n\
+             element,Order.item,article,TItem.Article\n\
+             enumeration,Status,,TStatus\n\
+             value,Status,closed,TStatus.sClosed\n"
+        );
+    }
+
+    #[test]
+    fn counts_documented_entries() {
+        let report =
+            build().to_documentation_coverage(&Some(String::from("X")), CoverageFormat::Json);
+        let report = serde_json::from_str::<serde_json::Value>(&report).unwrap();
+
+        assert_eq!(report["total"], 12);
+        assert_eq!(report["documented"], 5);
+        assert_eq!(report["missing"][3]["delphi_name"], "TXItem");
+    }
+}
@@ -0,0 +1,186 @@
+use std::fmt::Write;
+
+use super::{
+    internal_representation::InternalRepresentation,
+    types::{DataType, XMLSource},
+};
+
+impl DataType {
+    /// Returns the name of the generated type this data type refers to, if any.
+    pub(crate) fn referenced_type_name(&self) -> Option<&String> {
+        match self {
+            Self::Alias(n)
+            | Self::Custom(n)
+            | Self::Record(n)
+            | Self::Enumeration(n)
+            | Self::EnumSet(n)
+            | Self::InlineEnumSet(n)
+            | Self::Union(n) => Some(n),
+            Self::List(dt) | Self::FixedSizeList(dt, _) | Self::InlineList(dt) => {
+                dt.referenced_type_name()
+            }
+            _ => None,
+        }
+    }
+}
+
+impl InternalRepresentation {
+    /// Renders the generated types and their relations as a Graphviz DOT graph.
+    ///
+    /// Inheritance is drawn with an empty arrow head, references from variables
+    /// and aliases are labeled with the name of the variable.
+    pub fn to_dot(&self, unit_name: &str) -> String {
+        let mut nodes = String::new();
+        let mut edges = String::new();
+
+        for class in &self.classes {
+            let _ = writeln!(nodes, "    \"{}\" [shape=box];", class.name);
+
+            if let Some((super_type, _)) = &class.super_type {
+                let _ = writeln!(
+                    edges,
+                    "  \"{}\" -> \"{}\" [arrowhead=empty];",
+                    class.name, super_type
+                );
+            }
+
+            for variable in &class.variables {
+                if let Some(target) = variable.data_type.referenced_type_name() {
+                    let style = match variable.source {
+                        XMLSource::Attribute => ", style=dashed",
+                        _ => "",
+                    };
+
+                    let _ = writeln!(
+                        edges,
+                        "  \"{}\" -> \"{}\" [label=\"{}\"{}];",
+                        class.name, target, variable.name, style
+                    );
+                }
+            }
+        }
+
+        for enumeration in &self.enumerations {
+            let _ = writeln!(nodes, "    \"{}\" [shape=ellipse];", enumeration.name);
+        }
+
+        for alias in &self.types_aliases {
+            let _ = writeln!(nodes, "    \"{}\" [shape=note];", alias.name);
+
+            if let Some(target) = alias.for_type.referenced_type_name() {
+                let _ = writeln!(edges, "  \"{}\" -> \"{}\";", alias.name, target);
+            }
+        }
+
+        for union_type in &self.union_types {
+            let _ = writeln!(nodes, "    \"{}\" [shape=diamond];", union_type.name);
+
+            for variant in &union_type.variants {
+                if let Some(target) = variant.data_type.referenced_type_name() {
+                    let _ = writeln!(
+                        edges,
+                        "  \"{}\" -> \"{}\" [label=\"{}\"];",
+                        union_type.name, target, variant.name
+                    );
+                }
+            }
+        }
+
+        format!(
+            "digraph \"{unit_name}\" {{\n  rankdir=LR;\n\n  subgraph \"cluster_{unit_name}\" {{\n    label=\"{unit_name}\";\n{nodes}  }}\n\n{edges}}}\n"
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::generator::types::{ClassType, Enumeration, SchemaMetadata, Variable};
+
+    fn variable(name: &str, data_type: DataType) -> Variable {
+        Variable {
+            name: name.to_owned(),
+            data_type,
+            xml_name: name.to_owned(),
+            requires_free: false,
+            required: true,
+            source: XMLSource::Element,
+            default_value: None,
+            is_const: false,
+            occurrence: None,
+            documentations: vec![],
+            is_documented: false,
+            is_owned: true,
+            namespace: None,
+        }
+    }
+
+    fn class_type(name: &str, super_type: Option<&str>, variables: Vec<Variable>) -> ClassType {
+        ClassType {
+            name: name.to_owned(),
+            qualified_name: name.to_owned(),
+            super_type: super_type.map(|s| (s.to_owned(), s.to_owned())),
+            is_abstract: false,
+            variables,
+            documentations: vec![],
+            is_documented: false,
+            identity_constraints: vec![],
+            assertions: vec![],
+            wildcard: None,
+        }
+    }
+
+    #[test]
+    fn to_dot_contains_inheritance_and_reference_edges() {
+        let base = class_type("Base", None, vec![]);
+        let derived = class_type(
+            "Derived",
+            Some("Base"),
+            vec![
+                variable("color", DataType::Enumeration(String::from("Color"))),
+                variable(
+                    "children",
+                    DataType::List(Box::new(DataType::Custom(String::from("Base")))),
+                ),
+                variable("label", DataType::String),
+            ],
+        );
+
+        let ir = InternalRepresentation {
+            document: class_type("Document", None, vec![]),
+            classes: vec![base, derived],
+            types_aliases: vec![],
+            enumerations: vec![Enumeration {
+                name: String::from("Color"),
+                qualified_name: String::from("Color"),
+                values: vec![],
+                documentations: vec![],
+                is_documented: false,
+                is_flags: false,
+            }],
+            union_types: vec![],
+            external_classes: vec![],
+            metadata: SchemaMetadata::default(),
+        };
+
+        let expected = r#"digraph "uTest" {
+  rankdir=LR;
+
+  subgraph "cluster_uTest" {
+    label="uTest";
+    "Base" [shape=box];
+    "Derived" [shape=box];
+    "Color" [shape=ellipse];
+  }
+
+  "Derived" -> "Base" [arrowhead=empty];
+  "Derived" -> "Color" [label="color"];
+  "Derived" -> "Base" [label="children"];
+}
+"#;
+
+        assert_eq!(ir.to_dot("uTest"), expected);
+    }
+}
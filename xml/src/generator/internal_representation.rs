@@ -5,20 +5,50 @@ mod type_alias;
 mod union_type;
 
 use crate::{
-    parser::types::{CustomTypeDefinition, OrderIndicator, ParsedData},
+    parser::types::{BaseAttributes, CustomTypeDefinition, OrderIndicator, ParsedData},
     type_registry::TypeRegistry,
 };
 
-use self::class_type::collect_variables;
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use self::class_type::{collect_identity_constraints, collect_variables};
 
 pub use super::{
-    dependency_graph::DependencyGraph,
-    types::{ClassType, DataType, Enumeration, TypeAlias, UnionType, Variable, XMLSource},
+    dependency_graph::{Dependable, DependencyGraph},
+    types::{
+        AppInfo, ClassType, DataType, Enumeration, ExternalClass, Notation, ResolvedTypeAliases,
+        SchemaMetadata, TypeAlias, UnionType, Variable, XMLSource,
+    },
 };
 
 /// The name of the document class type.
 pub const DOCUMENT_NAME: &str = "Document";
 
+/// Type, which is declared as value type, see `InternalRepresentation::value_types`
+#[derive(Clone, Copy, Debug)]
+pub enum ValueType<'a> {
+    Alias(&'a TypeAlias),
+    Union(&'a UnionType),
+}
+
+impl Dependable<String> for ValueType<'_> {
+    fn key(&self) -> &String {
+        match self {
+            Self::Alias(a) => a.key(),
+            Self::Union(u) => u.key(),
+        }
+    }
+
+    fn key_and_deps(&self) -> (&String, Option<Vec<String>>) {
+        match self {
+            Self::Alias(a) => a.key_and_deps(),
+            Self::Union(u) => u.key_and_deps(),
+        }
+    }
+}
+
 /// This is the internal representation of the XML Schema.
 /// It contains the classes, enumerations, type aliases and union types.
 /// It also contains the document class type.
@@ -31,10 +61,12 @@ pub const DOCUMENT_NAME: &str = "Document";
 /// * `types_aliases` - The type aliases.
 /// * `enumerations` - The enumerations.
 /// * `union_types` - The union types.
+/// * `external_classes` - The complex types implemented by hand.
+/// * `metadata` - The xs:appinfo of the types and the xs:notation elements.
 ///
 /// # Examples
 ///
-/// ```rust
+/// ```ignore
 /// use generator::internal_representation::InternalRepresentation;
 /// use generator::parser::types::ParsedData;
 /// use generator::parser::xml::XmlParser;
@@ -62,13 +94,15 @@ pub const DOCUMENT_NAME: &str = "Document";
 ///
 /// let ir = InternalRepresentation::build(&data, &type_registry);
 /// ```
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct InternalRepresentation {
     pub document: ClassType,
     pub classes: Vec<ClassType>,
     pub types_aliases: Vec<TypeAlias>,
     pub enumerations: Vec<Enumeration>,
     pub union_types: Vec<UnionType>,
+    pub external_classes: Vec<ExternalClass>,
+    pub metadata: SchemaMetadata,
 }
 
 impl InternalRepresentation {
@@ -86,7 +120,7 @@ impl InternalRepresentation {
     ///
     /// # Examples
     ///
-    /// ```
+    /// ```ignore
     /// use generator::{
     ///     internal_representation::InternalRepresentation,
     ///     parser::{
@@ -122,13 +156,46 @@ impl InternalRepresentation {
     /// let ir = InternalRepresentation::build(&data, &type_registry);
     /// ```
     pub fn build(data: &ParsedData, registry: &TypeRegistry) -> Self {
-        let mut classes_dep_graph = DependencyGraph::<String, ClassType>::new();
-        let mut aliases_dep_graph = DependencyGraph::<String, TypeAlias>::new();
-        let mut union_types_dep_graph = DependencyGraph::<String, UnionType>::new();
+        Self::build_with_index(data, registry, true)
+    }
+
+    /// Builds the internal representation like `build`, but sorts the types by their
+    /// dependencies without an index. This needs less memory, but takes considerably longer
+    /// for schemas with many types.
+    pub fn build_low_memory(data: &ParsedData, registry: &TypeRegistry) -> Self {
+        Self::build_with_index(data, registry, false)
+    }
+
+    fn build_with_index(data: &ParsedData, registry: &TypeRegistry, indexed: bool) -> Self {
+        let mut classes_dep_graph = DependencyGraph::<String, ClassType>::with_index(indexed);
+        let mut aliases_dep_graph = DependencyGraph::<String, TypeAlias>::with_index(indexed);
+        let mut union_types_dep_graph = DependencyGraph::<String, UnionType>::with_index(indexed);
 
         let mut enumerations = Vec::new();
+        let mut external_classes = Vec::new();
+
+        let mut identity_constraints = HashMap::new();
+        collect_identity_constraints(&data.nodes, &mut identity_constraints);
 
-        for c_type in registry.types.values() {
+        let mut complex_types = registry
+            .types
+            .values()
+            .filter_map(|t| match t {
+                CustomTypeDefinition::Complex(ct) => Some(ct),
+                CustomTypeDefinition::Simple(_) => None,
+            })
+            .collect::<Vec<_>>();
+        complex_types.sort_by(|a, b| a.qualified_name.cmp(&b.qualified_name));
+
+        for ct in complex_types {
+            collect_identity_constraints(&ct.children, &mut identity_constraints);
+        }
+
+        // Types, which don't depend on each other, are generated in the order they are pushed
+        let mut custom_types = registry.types.iter().collect::<Vec<_>>();
+        custom_types.sort_by_key(|(name, _)| *name);
+
+        for (_, c_type) in custom_types {
             match c_type {
                 CustomTypeDefinition::Simple(st) if st.enumeration.is_some() => {
                     let enumeration = enumeration::build_enumeration_ir(st);
@@ -136,7 +203,7 @@ impl InternalRepresentation {
                     enumerations.push(enumeration);
                 }
                 CustomTypeDefinition::Simple(st) if st.base_type.is_some() => {
-                    let alias = type_alias::build_type_alias_ir(st);
+                    let alias = type_alias::build_type_alias_ir(st, registry);
 
                     aliases_dep_graph.push(alias);
                 }
@@ -148,7 +215,10 @@ impl InternalRepresentation {
                                 qualified_name: st.qualified_name.clone(),
                                 for_type: DataType::InlineList(Box::new(d_type)),
                                 pattern: None,
+                                total_digits: None,
+                                fraction_digits: None,
                                 documentations: st.documentations.clone(),
+                                is_documented: st.is_documented,
                             };
 
                             aliases_dep_graph.push(type_alias);
@@ -161,33 +231,132 @@ impl InternalRepresentation {
                     union_types_dep_graph.push(union_type);
                 }
                 CustomTypeDefinition::Simple(_) => (),
+                CustomTypeDefinition::Complex(ct) if ct.overrides.skip => (),
                 CustomTypeDefinition::Complex(ct) => {
-                    let class_type = class_type::build_class_type_ir(ct, registry);
+                    if let Some(external_type) = registry.external_type(ct) {
+                        external_classes.push(ExternalClass {
+                            name: ct.name.clone(),
+                            qualified_name: ct.qualified_name.clone(),
+                            delphi_type: external_type.delphi_type.clone(),
+                            unit: external_type.unit.clone(),
+                        });
+
+                        continue;
+                    }
+
+                    let mut inner_classes = vec![];
+                    let mut class_type =
+                        class_type::build_class_type_ir(ct, registry, &mut inner_classes);
+                    class_type.identity_constraints = identity_constraints
+                        .remove(&ct.qualified_name)
+                        .unwrap_or_default();
 
                     classes_dep_graph.push(class_type);
+
+                    for inner_class in inner_classes {
+                        classes_dep_graph.push(inner_class);
+                    }
                 }
             }
         }
 
-        let document_variables =
-            collect_variables(&data.nodes, registry, &OrderIndicator::Sequence);
+        let mut inner_classes = vec![];
+        let document_variables = collect_variables(
+            &data.nodes,
+            registry,
+            &OrderIndicator::Sequence(BaseAttributes::default()),
+            (DOCUMENT_NAME, DOCUMENT_NAME),
+            &mut inner_classes,
+        );
+
+        for inner_class in inner_classes {
+            classes_dep_graph.push(inner_class);
+        }
 
         let document_type = ClassType {
             super_type: None,
+            is_abstract: false,
             name: String::from(DOCUMENT_NAME),
             qualified_name: String::from(DOCUMENT_NAME),
             variables: document_variables,
             documentations: vec![],
+            is_documented: false,
+            identity_constraints: vec![],
+            assertions: vec![],
+            wildcard: None,
         };
 
         classes_dep_graph.push(document_type.clone());
 
         Self {
             document: document_type,
-            classes: classes_dep_graph.get_sorted_elements(),
-            types_aliases: aliases_dep_graph.get_sorted_elements(),
-            union_types: union_types_dep_graph.get_sorted_elements(),
+            classes: classes_dep_graph.into_sorted_elements(),
+            types_aliases: aliases_dep_graph.into_sorted_elements(),
+            union_types: union_types_dep_graph.into_sorted_elements(),
             enumerations,
+            external_classes,
+            metadata: Self::build_metadata(data, registry),
+        }
+    }
+
+    /// Resolves the data types and patterns of the type aliases once, see [ResolvedTypeAliases].
+    /// Has to be called again after the aliases have been changed.
+    pub fn resolved_type_aliases(&self) -> ResolvedTypeAliases<'_> {
+        ResolvedTypeAliases::new(&self.types_aliases)
+    }
+
+    /// Returns the type aliases and the union types, which are generated as variant records,
+    /// sorted by the dependencies between both kinds, e.g. an alias of a union type after
+    /// the union type and a union type with a variant of an alias after the alias.
+    ///
+    /// Unlike classes, these types can't be forward declared.
+    pub fn value_types(&self) -> Vec<ValueType<'_>> {
+        let mut graph = DependencyGraph::new();
+
+        for alias in &self.types_aliases {
+            graph.push(ValueType::Alias(alias));
+        }
+
+        for union_type in self.union_types.iter().filter(|u| !u.is_class_hierarchy()) {
+            graph.push(ValueType::Union(union_type));
+        }
+
+        graph.into_sorted_elements()
+    }
+
+    /// Collects the xs:appinfo of the generated types, ordered by their qualified names,
+    /// and the xs:notation elements of the schemas.
+    fn build_metadata(data: &ParsedData, registry: &TypeRegistry) -> SchemaMetadata {
+        let mut app_infos = registry
+            .types
+            .values()
+            .filter(|t| !t.is_skipped())
+            .filter_map(|t| {
+                let (qualified_name, appinfo) = match t {
+                    CustomTypeDefinition::Simple(st) => (&st.qualified_name, &st.appinfo),
+                    CustomTypeDefinition::Complex(ct) => (&ct.qualified_name, &ct.appinfo),
+                };
+
+                (!appinfo.is_empty()).then(|| AppInfo {
+                    name: t.get_name(),
+                    qualified_name: qualified_name.clone(),
+                    content: appinfo.join("\n"),
+                })
+            })
+            .collect::<Vec<_>>();
+        app_infos.sort_by(|a, b| a.qualified_name.cmp(&b.qualified_name));
+
+        SchemaMetadata {
+            app_infos,
+            notations: data
+                .notations
+                .iter()
+                .map(|n| Notation {
+                    name: n.name.clone(),
+                    public: n.public.clone(),
+                    system: n.system.clone(),
+                })
+                .collect(),
         }
     }
 }
@@ -1,19 +1,26 @@
+use std::collections::HashMap;
+
 use crate::{
-    generator::types::{ClassType, DataType, Variable, XMLSource},
+    generator::types::{
+        ClassType, DataType, IdentityConstraint, IdentityConstraintKind, Occurrence, Variable,
+        XMLSource,
+    },
     parser::types::{
-        CustomTypeDefinition, Node, NodeType, OrderIndicator, SingleNode, DEFAULT_OCCURANCE,
-        UNBOUNDED_OCCURANCE,
+        self as parser_types, BaseAttributes, CustomTypeDefinition, GenerationOverrides, Node,
+        NodeType, OrderIndicator, SingleNode, DEFAULT_OCCURANCE, UNBOUNDED_OCCURANCE,
     },
     type_registry::TypeRegistry,
 };
 
-use super::helper::*;
+use super::{helper::*, union_type::union_data_type};
 
 /// Builds the internal representation for a class type.
 ///
 /// # Arguments
 ///
 /// * `ct` - The complex type definition of the class type.
+/// * `registry` - The type registry.
+/// * `inner_classes` - Receives the classes generated for repeated sequences.
 ///
 /// # Returns
 ///
@@ -21,7 +28,7 @@ use super::helper::*;
 ///
 /// # Examples
 ///
-/// ```
+/// ```ignore
 /// use generator::{
 ///     internal_representation::InternalRepresentation,
 ///     parser::{
@@ -59,11 +66,26 @@ use super::helper::*;
 pub fn build_class_type_ir(
     ct: &crate::parser::types::ComplexType,
     registry: &TypeRegistry,
+    inner_classes: &mut Vec<ClassType>,
 ) -> ClassType {
-    let mut variables = collect_variables(&ct.children, registry, &ct.order);
+    let name = ct.get_name();
+
+    let mut variables = collect_variables(
+        &ct.children,
+        registry,
+        &ct.order,
+        (&name, &ct.qualified_name),
+        inner_classes,
+    );
 
-    for attr in &ct.custom_attributes {
-        match &attr.base_type {
+    for attr in ct.custom_attributes.iter().filter(|a| !a.overrides.skip) {
+        let base_type = attr
+            .overrides
+            .data_type
+            .clone()
+            .map_or_else(|| attr.base_type.clone(), NodeType::Standard);
+
+        match &base_type {
             NodeType::Standard(s) => {
                 let d_type = node_base_type_to_datatype(s);
 
@@ -77,15 +99,19 @@ pub fn build_class_type_ir(
                     data_type: d_type,
                     required: attr.required,
                     is_const: attr.fixed_value.is_some(),
+                    occurrence: None,
                     default_value: attr.fixed_value.clone().or(attr.default_value.clone()),
                     source: XMLSource::Attribute,
                     documentations: vec![],
+                    is_documented: attr.is_documented,
+                    is_owned: true,
+                    namespace: attr.namespace.clone(),
                 };
 
-                variables.push(variable);
+                variables.push(apply_overrides(variable, &attr.overrides));
             }
             NodeType::Custom(c) => {
-                let c_type = registry.types.get(c);
+                let c_type = registry.types.get(c).filter(|t| !t.is_skipped());
 
                 if let Some(c_type) = c_type {
                     let data_type = match c_type {
@@ -98,13 +124,15 @@ pub fn build_class_type_ir(
                             DataType::Alias(s.name.clone())
                         }
                         CustomTypeDefinition::Simple(s) if s.variants.is_some() => {
-                            DataType::Union(s.name.clone())
+                            union_data_type(s, registry)
                         }
                         _ => DataType::Custom(c_type.get_name()),
                     };
 
                     let requires_free = match c_type {
-                        CustomTypeDefinition::Simple(s) => s.list_type.is_some(),
+                        CustomTypeDefinition::Simple(s) => {
+                            s.list_type.is_some() || matches!(data_type, DataType::Custom(_))
+                        }
                         CustomTypeDefinition::Complex(_) => true,
                     };
 
@@ -119,12 +147,16 @@ pub fn build_class_type_ir(
                         data_type,
                         required: attr.required,
                         is_const: attr.fixed_value.is_some(),
+                        occurrence: None,
                         default_value: attr.fixed_value.clone().or(attr.default_value.clone()),
                         source: XMLSource::Attribute,
                         documentations: vec![],
+                        is_documented: attr.is_documented,
+                        is_owned: true,
+                        namespace: attr.namespace.clone(),
                     };
 
-                    variables.push(variable);
+                    variables.push(apply_overrides(variable, &attr.overrides));
                 }
             }
         }
@@ -134,38 +166,198 @@ pub fn build_class_type_ir(
         registry
             .types
             .get(t)
+            .filter(|ct| !ct.is_skipped())
             .map(|ct| (ct.get_name(), ct.get_qualified_name()))
     });
 
     ClassType {
-        name: ct.name.clone(),
+        name,
         qualified_name: ct.qualified_name.clone(),
         super_type,
+        is_abstract: ct.is_abstract,
         variables,
         documentations: ct.documentations.clone(),
+        is_documented: ct.is_documented,
+        identity_constraints: vec![],
+        wildcard: ct.wildcard.clone(),
+        assertions: ct.assertions.clone(),
     }
 }
 
+/// Collects the variables for the given nodes.
+///
+/// A sequence with `maxOccurs` greater than one is turned into an inner class,
+/// which is pushed to `inner_classes`, and a single list variable of that class.
 pub fn collect_variables(
     nodes: &[Node],
     registry: &TypeRegistry,
     order: &OrderIndicator,
+    parent: (&str, &str),
+    inner_classes: &mut Vec<ClassType>,
 ) -> Vec<Variable> {
+    if let OrderIndicator::Sequence(base_attributes) = order {
+        if is_repeated(base_attributes) {
+            return build_sequence_group(nodes, registry, base_attributes, parent, inner_classes)
+                .into_iter()
+                .collect();
+        }
+    }
+
     nodes
         .iter()
         .filter_map(|n| match n {
             Node::Single(e) => single_node_to_variable(e, registry, order).map(|v| vec![v]),
-            Node::Group(g) => Some(collect_variables(&g.nodes, registry, &g.order)),
+            Node::Group(g) => Some(collect_variables(
+                &g.nodes,
+                registry,
+                &g.order,
+                parent,
+                inner_classes,
+            )),
         })
         .flatten()
         .collect::<Vec<Variable>>()
 }
 
+/// Collects the identity constraints of the elements in `nodes` by the qualified name of the
+/// element type, whose class checks them. Constraints are taken once per name, when the
+/// type is used by several elements.
+pub fn collect_identity_constraints(
+    nodes: &[Node],
+    constraints: &mut HashMap<String, Vec<IdentityConstraint>>,
+) {
+    for node in nodes {
+        let element = match node {
+            Node::Single(e) => e,
+            Node::Group(g) => {
+                collect_identity_constraints(&g.nodes, constraints);
+                continue;
+            }
+        };
+
+        let NodeType::Custom(type_name) = &element.node_type else {
+            continue;
+        };
+
+        for constraint in &element.identity_constraints {
+            let type_constraints = constraints.entry(type_name.clone()).or_default();
+
+            if type_constraints.iter().any(|c| c.name == constraint.name) {
+                continue;
+            }
+
+            type_constraints.push(IdentityConstraint {
+                element: element.name.clone(),
+                name: constraint.name.clone(),
+                kind: match &constraint.kind {
+                    parser_types::IdentityConstraintKind::Key => IdentityConstraintKind::Key,
+                    parser_types::IdentityConstraintKind::Unique => IdentityConstraintKind::Unique,
+                    parser_types::IdentityConstraintKind::KeyRef(refer) => {
+                        IdentityConstraintKind::KeyRef(refer.clone())
+                    }
+                },
+                selector: constraint.selector.clone(),
+                fields: constraint.fields.clone(),
+            });
+        }
+    }
+}
+
+fn is_repeated(base_attributes: &BaseAttributes) -> bool {
+    let max_occurs = base_attributes.max_occurs.unwrap_or(DEFAULT_OCCURANCE);
+
+    max_occurs == UNBOUNDED_OCCURANCE || max_occurs > DEFAULT_OCCURANCE
+}
+
+fn build_sequence_group(
+    nodes: &[Node],
+    registry: &TypeRegistry,
+    base_attributes: &BaseAttributes,
+    (parent_name, parent_qualified_name): (&str, &str),
+    inner_classes: &mut Vec<ClassType>,
+) -> Option<Variable> {
+    let leading_element = first_element_name(nodes)?;
+
+    let mut chars = leading_element.chars();
+    let capitalized = chars
+        .next()
+        .map(|c| c.to_uppercase().collect::<String>() + chars.as_str())
+        .unwrap_or_default();
+
+    let name = format!("{parent_name}{capitalized}Sequence");
+    let qualified_name = format!("{parent_qualified_name}.{name}");
+
+    let variables = collect_variables(
+        nodes,
+        registry,
+        &OrderIndicator::Sequence(BaseAttributes::default()),
+        (&name, &qualified_name),
+        inner_classes,
+    );
+
+    inner_classes.push(ClassType {
+        name: name.clone(),
+        qualified_name,
+        super_type: None,
+        is_abstract: false,
+        variables,
+        documentations: vec![],
+        is_documented: false,
+        identity_constraints: vec![],
+        assertions: vec![],
+        wildcard: None,
+    });
+
+    Some(Variable {
+        name: format!("{leading_element}Sequence"),
+        xml_name: leading_element,
+        requires_free: true,
+        data_type: DataType::List(Box::new(DataType::Custom(name))),
+        required: base_attributes.min_occurs.unwrap_or(DEFAULT_OCCURANCE) > 0,
+        default_value: None,
+        is_const: false,
+        occurrence: None,
+        source: XMLSource::Group,
+        documentations: vec![],
+        is_documented: false,
+        is_owned: true,
+        namespace: None,
+    })
+}
+
+fn first_element_name(nodes: &[Node]) -> Option<String> {
+    nodes.iter().find_map(|n| match n {
+        Node::Single(e) => Some(e.name.clone()),
+        Node::Group(g) => first_element_name(&g.nodes),
+    })
+}
+
+/// Applies the rename, optional and owned overrides of a field to its variable.
+fn apply_overrides(mut variable: Variable, overrides: &GenerationOverrides) -> Variable {
+    if let Some(name) = &overrides.rename {
+        variable.name.clone_from(name);
+    }
+
+    if overrides.optional {
+        variable.required = false;
+    }
+
+    if let Some(owned) = overrides.owned {
+        variable.is_owned = owned;
+    }
+
+    variable
+}
+
 fn single_node_to_variable(
     node: &SingleNode,
     registry: &TypeRegistry,
     order: &OrderIndicator,
 ) -> Option<Variable> {
+    if node.overrides.skip {
+        return None;
+    }
+
     let min_occurs = match order {
         OrderIndicator::All => node
             .base_attributes
@@ -194,7 +386,13 @@ fn single_node_to_variable(
         _ => min_occurs > 0,
     };
 
-    match &node.node_type {
+    let node_type = node
+        .overrides
+        .data_type
+        .clone()
+        .map_or_else(|| node.node_type.clone(), NodeType::Standard);
+
+    let variable = match &node_type {
         NodeType::Standard(s) => {
             let d_type = node_base_type_to_datatype(s);
 
@@ -205,7 +403,7 @@ fn single_node_to_variable(
             } else if min_occurs == max_occurs && max_occurs > DEFAULT_OCCURANCE {
                 let size = usize::try_from(max_occurs).unwrap();
 
-                DataType::FixedSizeList(Box::new(d_type.clone()), size)
+                DataType::FixedSizeList(Box::new(d_type), size)
             } else {
                 d_type
             };
@@ -218,12 +416,16 @@ fn single_node_to_variable(
                 required,
                 default_value: None,
                 is_const: false,
+                occurrence: None,
                 source: XMLSource::Element,
                 documentations: node.documentations.as_ref().cloned().unwrap_or_default(),
+                is_documented: node.is_documented,
+                is_owned: true,
+                namespace: None,
             })
         }
         NodeType::Custom(c) => {
-            let c_type = registry.types.get(c)?;
+            let c_type = registry.types.get(c).filter(|t| !t.is_skipped())?;
 
             let data_type = match c_type {
                 CustomTypeDefinition::Simple(s) if s.enumeration.is_some() => {
@@ -235,13 +437,15 @@ fn single_node_to_variable(
                     DataType::Alias(s.name.clone())
                 }
                 CustomTypeDefinition::Simple(s) if s.variants.is_some() => {
-                    DataType::Union(s.name.clone())
+                    union_data_type(s, registry)
                 }
                 _ => DataType::Custom(c_type.get_name()),
             };
 
             let requires_free = match c_type {
-                CustomTypeDefinition::Simple(s) => s.list_type.is_some(),
+                CustomTypeDefinition::Simple(s) => {
+                    s.list_type.is_some() || matches!(data_type, DataType::Custom(_))
+                }
                 CustomTypeDefinition::Complex(_) => true,
             };
 
@@ -269,9 +473,40 @@ fn single_node_to_variable(
                 required,
                 default_value: None,
                 is_const: false,
+                occurrence: None,
                 source: XMLSource::Element,
                 documentations: node.documentations.as_ref().cloned().unwrap_or_default(),
+                is_documented: node.is_documented,
+                is_owned: true,
+                namespace: None,
             })
         }
+    };
+
+    variable
+        .map(|v| with_occurrence(v, min_occurs, max_occurs))
+        .map(|v| apply_overrides(v, &node.overrides))
+}
+
+/// Records the bounds of lists, which must occur more than once or a limited number of times,
+/// and documents them for the generated property.
+fn with_occurrence(mut variable: Variable, min_occurs: i64, max_occurs: i64) -> Variable {
+    if !matches!(variable.data_type, DataType::List(_)) {
+        return variable;
     }
+
+    let min = usize::try_from(min_occurs).unwrap_or_default();
+    let max = usize::try_from(max_occurs).ok();
+
+    let documentation = match max {
+        Some(max) if min > 0 => format!("Occurs {min} to {max} times."),
+        Some(max) => format!("Occurs at most {max} times."),
+        None if min > 1 => format!("Occurs at least {min} times."),
+        None => return variable,
+    };
+
+    variable.documentations.push(documentation);
+    variable.occurrence = Some(Occurrence { min, max });
+
+    variable
 }
@@ -1,3 +1,5 @@
+use std::collections::HashSet;
+
 use crate::{
     generator::types::{Enumeration, EnumerationValue},
     parser::types::SimpleType,
@@ -15,7 +17,7 @@ use crate::{
 ///
 /// # Examples
 ///
-/// ```
+/// ```ignore
 /// use generator::{
 ///     internal_representation::InternalRepresentation,
 ///     parser::{
@@ -51,15 +53,17 @@ use crate::{
 /// assert_eq!(ir.enumerations.len(), 1);
 /// ```
 pub fn build_enumeration_ir(st: &SimpleType) -> Enumeration {
-    let values = st
-        .enumeration
-        .as_ref()
-        .unwrap()
+    let definitions = st.enumeration.as_ref().unwrap();
+    let variant_names = unique_variant_names(&st.name, definitions.iter().map(|v| v.name.as_str()));
+
+    let values = definitions
         .iter()
-        .map(|v| EnumerationValue {
-            variant_name: v.name.clone(),
+        .zip(variant_names)
+        .map(|(v, variant_name)| EnumerationValue {
+            variant_name,
             xml_value: v.name.clone(),
             documentations: v.documentations.clone(),
+            is_documented: v.is_documented,
         })
         .collect::<Vec<EnumerationValue>>();
 
@@ -68,5 +72,151 @@ pub fn build_enumeration_ir(st: &SimpleType) -> Enumeration {
         qualified_name: st.qualified_name.clone(),
         values,
         documentations: st.documentations.clone(),
+        is_documented: st.is_documented,
+        is_flags: st.is_flags,
+    }
+}
+
+/// Replacements of characters, which are not allowed in identifiers, but distinguish values
+const SYMBOL_REPLACEMENTS: [(char, &str); 8] = [
+    ('+', "Plus"),
+    ('%', "Percent"),
+    ('&', "And"),
+    ('@', "At"),
+    ('#', "Hash"),
+    ('<', "Lt"),
+    ('>', "Gt"),
+    ('=', "Eq"),
+];
+
+/// Names of the variants for the values of an enumeration, which are valid and unique
+/// identifiers, once they are prefixed.
+///
+/// Values, which are valid identifiers, are kept. A value with a collision after the
+/// derivation gets a number appended and a warning is printed. Names are compared
+/// case-insensitive like identifiers in Delphi.
+fn unique_variant_names<'a>(
+    enumeration: &str,
+    values: impl Iterator<Item = &'a str> + Clone,
+) -> Vec<String> {
+    let mut used_names = values
+        .clone()
+        .filter(|v| is_identifier_part(v))
+        .map(str::to_lowercase)
+        .collect::<HashSet<_>>();
+
+    values
+        .map(|value| {
+            if is_identifier_part(value) {
+                return value.to_owned();
+            }
+
+            let derived = variant_name(value);
+
+            let mut unique_name = derived.clone();
+            let mut counter = 2;
+            while !used_names.insert(unique_name.to_lowercase()) {
+                unique_name = format!("{derived}{counter}");
+                counter += 1;
+            }
+
+            if unique_name != derived {
+                eprintln!(
+                    "Warning: value \"{value}\" of {enumeration} is named \"{unique_name}\", because \"{derived}\" is already used"
+                );
+            }
+
+            unique_name
+        })
+        .collect()
+}
+
+/// The value can follow the prefix of the variants without changes
+fn is_identifier_part(value: &str) -> bool {
+    !value.is_empty() && value.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+/// Name of the variant for a value, which is not a valid identifier, e.g. `2Wire` for `2-wire`
+/// or `NA` for `N/A`.
+///
+/// Characters, which are not allowed in identifiers, separate the words of the name, which
+/// start with an uppercase letter. Digits on both sides of a separator are joined by `_`,
+/// so `1.5` and `15` get different names. Some symbols are replaced by words.
+fn variant_name(value: &str) -> String {
+    let mut name = String::with_capacity(value.len());
+    let mut new_word = false;
+
+    for (i, c) in value.char_indices() {
+        let replacement = SYMBOL_REPLACEMENTS
+            .iter()
+            .find(|(symbol, _)| *symbol == c)
+            .map(|(_, word)| *word)
+            .or_else(|| {
+                let is_sign = c == '-' && i == 0;
+                let before_digit = value[i + 1..].starts_with(|c: char| c.is_ascii_digit());
+
+                (is_sign && before_digit).then_some("Minus")
+            });
+
+        if let Some(word) = replacement {
+            name.push_str(word);
+            new_word = true;
+        } else if c.is_alphanumeric() || c == '_' {
+            if new_word && c.is_ascii_digit() && name.ends_with(|c: char| c.is_ascii_digit()) {
+                name.push('_');
+            }
+
+            match new_word {
+                true => name.extend(c.to_uppercase()),
+                false => name.push(c),
+            }
+            new_word = false;
+        } else {
+            new_word = true;
+        }
+    }
+
+    match name.is_empty() {
+        true => String::from("Empty"),
+        false => name,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn invalid_identifiers_get_unique_variant_names() {
+        let values = [
+            "2-wire",
+            "N/A",
+            "NA",
+            "1.5",
+            "15",
+            "-1",
+            "in progress",
+            "",
+            "a+b",
+            "Valid_Name",
+        ];
+
+        assert_eq!(
+            unique_variant_names("Test", values.into_iter()),
+            vec![
+                "2Wire",
+                "NA2",
+                "NA",
+                "1_5",
+                "15",
+                "Minus1",
+                "inProgress",
+                "Empty",
+                "aPlusB",
+                "Valid_Name",
+            ]
+        );
     }
 }
@@ -55,7 +55,7 @@ pub fn list_type_to_data_type(list_type: &NodeType, registry: &TypeRegistry) ->
                         Some(DataType::Enumeration(s.name.clone()))
                     }
                     CustomTypeDefinition::Simple(s) if s.variants.is_some() => {
-                        Some(DataType::Union(s.name.clone()))
+                        Some(super::union_type::union_data_type(s, registry))
                     }
                     CustomTypeDefinition::Simple(s) if s.base_type.is_some() => {
                         Some(DataType::Alias(s.name.clone()))
@@ -1,6 +1,7 @@
 use crate::{
     generator::types::{DataType, TypeAlias},
     parser::types::{NodeType, SimpleType},
+    type_registry::TypeRegistry,
 };
 
 /// Builds the internal representation for a type alias.
@@ -8,6 +9,7 @@ use crate::{
 /// # Arguments
 ///
 /// * `st` - The simple type definition of the type alias.
+/// * `registry` - The type registry.
 ///
 /// # Returns
 ///
@@ -15,7 +17,7 @@ use crate::{
 ///
 /// # Examples
 ///
-/// ```
+/// ```ignore
 /// use generator::{
 ///     internal_representation::InternalRepresentation,
 ///     parser::{
@@ -46,21 +48,24 @@ use crate::{
 ///
 /// assert_eq!(ir.types_aliases.len(), 1);
 /// ```
-pub fn build_type_alias_ir(st: &SimpleType) -> TypeAlias {
+pub fn build_type_alias_ir(st: &SimpleType, registry: &TypeRegistry) -> TypeAlias {
     let for_type = match st.base_type.as_ref().unwrap() {
         NodeType::Standard(t) => super::helper::node_base_type_to_datatype(t),
-        NodeType::Custom(n) => {
-            let name = n.split('/').last().unwrap_or(n.as_str());
-
-            DataType::Custom(name.to_owned())
-        }
+        // The name of the base type may have been changed by the registry
+        NodeType::Custom(n) => match registry.types.get(n) {
+            Some(base_type) => DataType::Custom(base_type.get_name()),
+            None => DataType::Custom(n.split('/').next_back().unwrap_or(n.as_str()).to_owned()),
+        },
     };
 
     TypeAlias {
         name: st.name.clone(),
         qualified_name: st.qualified_name.clone(),
         pattern: st.pattern.clone(),
+        total_digits: st.total_digits,
+        fraction_digits: st.fraction_digits,
         for_type,
         documentations: st.documentations.clone(),
+        is_documented: st.is_documented,
     }
 }
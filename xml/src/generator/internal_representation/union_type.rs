@@ -16,7 +16,7 @@ use crate::{
 ///
 /// # Examples
 ///
-/// ```
+/// ```ignore
 /// use generator::{
 ///     internal_representation::InternalRepresentation,
 ///     parser::{
@@ -48,53 +48,107 @@ use crate::{
 /// assert_eq!(ir.union_types.len(), 1);
 /// ```
 pub fn build_union_type_ir(st: &SimpleType, registry: &TypeRegistry) -> UnionType {
+    let mut variants = Vec::<UnionVariant>::new();
+    visit_variants(
+        st,
+        registry,
+        &mut vec![st.qualified_name.as_str()],
+        &mut |name, data_type| {
+            if variants.iter().any(|v| v.data_type == data_type) {
+                return;
+            }
+
+            let name = match name {
+                Some(n) => n.to_owned(),
+                None => format!("Variant{}", variants.len()),
+            };
+            variants.push(UnionVariant { name, data_type });
+        },
+    );
+
     UnionType {
         name: st.name.clone(),
         qualified_name: st.qualified_name.clone(),
         documentations: st.documentations.clone(),
-        variants: st
-            .variants
-            .as_ref()
-            .unwrap()
-            .iter()
-            .enumerate()
-            .filter_map(|(i, v)| {
-                let d_type = match v {
-                    crate::parser::types::UnionVariant::Named(n) => {
-                        let Some(CustomTypeDefinition::Simple(st)) = registry.types.get(n) else {
-                            return None;
-                        };
+        is_documented: st.is_documented,
+        variants,
+    }
+}
+
+/// Calls `visit` with the name and data type of every variant of the union `st`.
+///
+/// Member types, which are unions themselves, are replaced by their variants, so a value is
+/// read by the member types of all nested unions in the order of their declaration. Variants
+/// of standard types have no name, they are named by their position in the flattened union.
+/// `visited` holds the qualified names of the unions being flattened to stop at cyclic
+/// references.
+fn visit_variants<'a>(
+    st: &'a SimpleType,
+    registry: &'a TypeRegistry,
+    visited: &mut Vec<&'a str>,
+    visit: &mut dyn FnMut(Option<&str>, DataType),
+) {
+    let Some(members) = &st.variants else {
+        return;
+    };
 
-                        if let Some(lt) = &st.list_type {
-                            super::helper::list_type_to_data_type(lt, registry)
-                                .map(|d| (DataType::InlineList(Box::new(d)), st.name.clone()))
-                        } else if st.enumeration.is_some() {
-                            Some((DataType::Enumeration(st.name.clone()), st.name.clone()))
-                        } else {
-                            Some((DataType::Alias(st.name.clone()), st.name.clone()))
-                        }
-                    }
-                    crate::parser::types::UnionVariant::Simple(st) => {
-                        if let Some(lt) = &st.list_type {
-                            super::helper::list_type_to_data_type(lt, registry)
-                                .map(|d| (DataType::InlineList(Box::new(d)), st.name.clone()))
-                        } else if st.enumeration.is_some() {
-                            Some((DataType::Enumeration(st.name.clone()), st.name.clone()))
-                        } else {
-                            Some((DataType::Alias(st.name.clone()), st.name.clone()))
-                        }
-                    }
-                    crate::parser::types::UnionVariant::Standard(t) => Some((
-                        super::helper::node_base_type_to_datatype(t),
-                        format!("Variant{i}"),
-                    )),
-                };
+    for member in members {
+        let st = match member {
+            crate::parser::types::UnionVariant::Named(n) => match registry.types.get(n) {
+                Some(CustomTypeDefinition::Simple(st)) => st,
+                Some(c_type @ CustomTypeDefinition::Complex(_)) if !c_type.is_skipped() => {
+                    let name = c_type.get_name();
+                    visit(Some(&name), DataType::Custom(name.clone()));
+                    continue;
+                }
+                _ => continue,
+            },
+            crate::parser::types::UnionVariant::Simple(st) => st.as_ref(),
+            crate::parser::types::UnionVariant::Standard(t) => {
+                visit(None, super::helper::node_base_type_to_datatype(t));
+                continue;
+            }
+        };
+
+        let data_type = if let Some(lt) = &st.list_type {
+            match super::helper::list_type_to_data_type(lt, registry) {
+                Some(d) => DataType::InlineList(Box::new(d)),
+                None => continue,
+            }
+        } else if st.variants.is_some() {
+            if !visited.contains(&st.qualified_name.as_str()) {
+                visited.push(&st.qualified_name);
+                visit_variants(st, registry, visited, visit);
+                visited.pop();
+            }
+            continue;
+        } else if st.enumeration.is_some() {
+            DataType::Enumeration(st.name.clone())
+        } else {
+            DataType::Alias(st.name.clone())
+        };
+
+        visit(Some(&st.name), data_type);
+    }
+}
+
+/// Returns the data type for references to the given union type.
+///
+/// Unions, which are generated as class hierarchy, are referenced like classes. The
+/// variants are only visited, not built, as nested unions would otherwise be built once
+/// per reference.
+pub fn union_data_type(st: &SimpleType, registry: &TypeRegistry) -> DataType {
+    let mut is_class_hierarchy = false;
+    visit_variants(
+        st,
+        registry,
+        &mut vec![st.qualified_name.as_str()],
+        &mut |_, data_type| is_class_hierarchy |= UnionType::is_class_variant(&data_type),
+    );
 
-                d_type.map(|(dt, name)| UnionVariant {
-                    name,
-                    data_type: dt,
-                })
-            })
-            .collect::<Vec<UnionVariant>>(),
+    if is_class_hierarchy {
+        DataType::Custom(st.name.clone())
+    } else {
+        DataType::Union(st.name.clone())
     }
 }
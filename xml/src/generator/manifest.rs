@@ -0,0 +1,63 @@
+use serde_json::{json, Map, Value};
+
+use super::{delphi::helper::Helper, internal_representation::InternalRepresentation};
+
+impl InternalRepresentation {
+    /// Describes the generated types as JSON, so the next generation can report the changes.
+    ///
+    /// Every type is listed by its Delphi name with its kind and its members, which are
+    /// the fields of classes, the values of enumerations, the variants of unions and the
+    /// type of aliases, each mapped to their Delphi type or xml value.
+    pub fn to_manifest(&self, type_prefix: &Option<String>) -> String {
+        let data_type = |dt| Helper::get_datatype_language_representation(dt, type_prefix, false);
+        let mut types = Map::new();
+
+        for class in &self.classes {
+            let members = class
+                .variables
+                .iter()
+                .map(|v| (v.name.clone(), Value::from(data_type(&v.data_type))))
+                .collect::<Map<_, _>>();
+
+            types.insert(
+                Helper::as_type_name(&class.name, type_prefix),
+                json!({ "kind": "class", "members": members }),
+            );
+        }
+
+        for enumeration in &self.enumerations {
+            let members = enumeration
+                .values
+                .iter()
+                .map(|v| (v.variant_name.clone(), Value::from(v.xml_value.clone())))
+                .collect::<Map<_, _>>();
+
+            types.insert(
+                Helper::as_type_name(&enumeration.name, type_prefix),
+                json!({ "kind": "enum", "members": members }),
+            );
+        }
+
+        for alias in &self.types_aliases {
+            types.insert(
+                Helper::as_type_name(&alias.name, type_prefix),
+                json!({ "kind": "alias", "members": { "type": data_type(&alias.for_type) } }),
+            );
+        }
+
+        for union_type in &self.union_types {
+            let members = union_type
+                .variants
+                .iter()
+                .map(|v| (v.name.clone(), Value::from(data_type(&v.data_type))))
+                .collect::<Map<_, _>>();
+
+            types.insert(
+                Helper::as_type_name(&union_type.name, type_prefix),
+                json!({ "kind": "union", "members": members }),
+            );
+        }
+
+        serde_json::to_string_pretty(&json!({ "types": types })).unwrap_or_default()
+    }
+}
@@ -1,5 +1,15 @@
 pub mod code_generator_trait;
 pub mod delphi;
 pub mod dependency_graph;
+pub mod documentation_coverage;
+pub mod graph_export;
 pub mod internal_representation;
+pub mod manifest;
+pub mod name_map;
+pub mod ownership;
+pub mod passes;
+pub mod pruning;
+pub mod rename_map;
+pub mod sample;
+pub mod transliteration;
 pub mod types;
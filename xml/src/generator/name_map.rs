@@ -0,0 +1,40 @@
+use std::fmt::Write;
+
+use super::{delphi::helper::Helper, internal_representation::InternalRepresentation};
+
+impl InternalRepresentation {
+    /// Lists the name of every generated type next to the schema path it is generated from,
+    /// one `TName = path` line per type, sorted by the name of the type.
+    pub fn to_name_map(&self, type_prefix: &Option<String>) -> String {
+        let mut entries = self
+            .classes
+            .iter()
+            .map(|c| (&c.name, &c.qualified_name))
+            .chain(
+                self.enumerations
+                    .iter()
+                    .map(|e| (&e.name, &e.qualified_name)),
+            )
+            .chain(
+                self.types_aliases
+                    .iter()
+                    .map(|a| (&a.name, &a.qualified_name)),
+            )
+            .chain(
+                self.union_types
+                    .iter()
+                    .map(|u| (&u.name, &u.qualified_name)),
+            )
+            .map(|(name, qualified_name)| (Helper::as_type_name(name, type_prefix), qualified_name))
+            .collect::<Vec<_>>();
+
+        entries.sort();
+
+        entries
+            .into_iter()
+            .fold(String::new(), |mut map, (name, qualified_name)| {
+                let _ = writeln!(map, "{name} = {qualified_name}");
+                map
+            })
+    }
+}
@@ -0,0 +1,122 @@
+use super::{code_generator_trait::CodeGenError, internal_representation::InternalRepresentation};
+
+impl InternalRepresentation {
+    /// Marks the fields as not owned by their class, so the destructor doesn't free them,
+    /// e.g. because they reference shared lookups.
+    ///
+    /// Every field is given as `<TYPE>.<FIELD>`, the type by its name or qualified name and
+    /// the field by the name of its element or attribute. Every field has to be generated,
+    /// otherwise an error is returned.
+    pub fn disown_fields(&mut self, fields: &[String]) -> Result<(), CodeGenError> {
+        for field in fields {
+            let Some((type_name, xml_name)) = field.rsplit_once('.') else {
+                return Err(CodeGenError::InvalidUnownedField(format!(
+                    "\"{field}\" is not of the form <TYPE>.<FIELD>"
+                )));
+            };
+
+            let mut classes = self
+                .classes
+                .iter_mut()
+                .filter(|c| c.qualified_name == type_name || c.name == type_name)
+                .peekable();
+
+            if classes.peek().is_none() {
+                return Err(CodeGenError::InvalidUnownedField(format!(
+                    "Type \"{type_name}\" is not generated"
+                )));
+            }
+
+            let mut is_matched = false;
+
+            for variable in classes
+                .flat_map(|c| c.variables.iter_mut())
+                .filter(|v| v.xml_name == xml_name)
+            {
+                variable.is_owned = false;
+                is_matched = true;
+            }
+
+            if !is_matched {
+                return Err(CodeGenError::InvalidUnownedField(format!(
+                    "Type \"{type_name}\" has no field \"{xml_name}\""
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{
+        build_representation, generator::code_generator_trait::CodeGenOptions,
+        type_registry::TypeRegistry,
+    };
+
+    fn build() -> InternalRepresentation {
+        let (_, representation) = build_representation(
+            &[PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/documentation.xsd")],
+            &CodeGenOptions::default(),
+            &mut TypeRegistry::new(),
+        )
+        .unwrap();
+
+        representation
+    }
+
+    #[test]
+    fn disowns_the_fields() {
+        let mut representation = build();
+
+        representation
+            .disown_fields(&[String::from("Order.item")])
+            .unwrap();
+
+        let order = representation
+            .classes
+            .iter()
+            .find(|c| c.qualified_name == "Order")
+            .unwrap();
+        let owned = order
+            .variables
+            .iter()
+            .map(|v| (v.xml_name.as_str(), v.is_owned))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            owned,
+            vec![
+                ("number", true),
+                ("item", false),
+                ("id", true),
+                ("note", true)
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_fields() {
+        let errors = ["Order.article", "Customer.item", "item"].map(|field| {
+            build()
+                .disown_fields(&[String::from(field)])
+                .map_err(|e| format!("{e:?}"))
+                .unwrap_err()
+        });
+
+        assert_eq!(
+            errors,
+            [
+                "Invalid unowned field: Type \"Order\" has no field \"article\"",
+                "Invalid unowned field: Type \"Customer\" is not generated",
+                "Invalid unowned field: \"item\" is not of the form <TYPE>.<FIELD>",
+            ]
+        );
+    }
+}
@@ -0,0 +1,589 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Debug,
+    sync::Arc,
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    internal_representation::InternalRepresentation,
+    types::{DataType, TypeAlias},
+};
+
+/// Transformation of the internal representation, which runs after the schemas are parsed
+/// and before the unit is generated.
+///
+/// Custom passes are registered with `CodeGenOptionsBuilder::register_pass` and run after
+/// the built-in passes in the order of their registration.
+pub trait IrPass: Send + Sync {
+    /// Name of the pass, e.g. to tell the passes of two options apart
+    fn name(&self) -> &str;
+
+    fn run(&self, internal_representation: &mut InternalRepresentation);
+}
+
+/// Passes shipped with the generator
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BuiltinPass {
+    /// Replaces type aliases, which wrap another type without restricting it by a pattern
+    /// or digits, by the wrapped type. Aliases of lists and unions are kept.
+    FlattenAliases,
+    /// Merges anonymous classes and enumerations, which are declared with the same content
+    /// by several elements, into the type of the first element.
+    MergeIdenticalAnonymousTypes,
+    /// Removes the types, which are not used by a root element, directly or through other types.
+    /// Derived classes only used through `xsi:type` are removed as well.
+    StripUnusedTypes,
+}
+
+impl IrPass for BuiltinPass {
+    fn name(&self) -> &str {
+        match self {
+            Self::FlattenAliases => "flatten-aliases",
+            Self::MergeIdenticalAnonymousTypes => "merge-identical-anonymous-types",
+            Self::StripUnusedTypes => "strip-unused-types",
+        }
+    }
+
+    fn run(&self, internal_representation: &mut InternalRepresentation) {
+        match self {
+            Self::FlattenAliases => internal_representation.flatten_aliases(),
+            Self::MergeIdenticalAnonymousTypes => {
+                internal_representation.merge_identical_anonymous_types();
+            }
+            Self::StripUnusedTypes => internal_representation.retain_reachable_types(),
+        }
+    }
+}
+
+/// Custom passes of the options, which are compared by their names
+#[derive(Clone, Default)]
+pub struct CustomPasses(pub(crate) Vec<Arc<dyn IrPass>>);
+
+impl Debug for CustomPasses {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.0.iter().map(|p| p.name()))
+            .finish()
+    }
+}
+
+impl PartialEq for CustomPasses {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.len() == other.0.len()
+            && self
+                .0
+                .iter()
+                .zip(&other.0)
+                .all(|(a, b)| a.name() == b.name())
+    }
+}
+
+impl Eq for CustomPasses {}
+
+impl InternalRepresentation {
+    /// Runs the built-in passes and afterwards the custom passes in the given order
+    pub fn run_passes(&mut self, passes: &[BuiltinPass], custom_passes: &CustomPasses) {
+        for pass in passes {
+            pass.run(self);
+        }
+
+        for pass in &custom_passes.0 {
+            pass.run(self);
+        }
+    }
+
+    fn flatten_aliases(&mut self) {
+        let is_wrapper = |alias: &TypeAlias| {
+            alias.pattern.is_none()
+                && alias.total_digits.is_none()
+                && alias.fraction_digits.is_none()
+                && !matches!(
+                    alias.for_type,
+                    DataType::List(_)
+                        | DataType::FixedSizeList(_, _)
+                        | DataType::InlineList(_)
+                        | DataType::EnumSet(_)
+                        | DataType::InlineEnumSet(_)
+                        | DataType::Union(_)
+                )
+        };
+
+        let mut wrapped_types = self
+            .types_aliases
+            .iter()
+            .filter(|a| is_wrapper(a))
+            .map(|a| (a.name.clone(), a.for_type.clone()))
+            .collect::<HashMap<_, _>>();
+
+        if wrapped_types.is_empty() {
+            return;
+        }
+
+        // Aliases of aliases are resolved to the innermost type, which isn't flattened
+        let names = wrapped_types.keys().cloned().collect::<Vec<_>>();
+        for name in names {
+            let mut data_type = wrapped_types[&name].clone();
+            let mut depth = 0;
+
+            while let DataType::Alias(inner) = &data_type {
+                match wrapped_types.get(inner) {
+                    Some(inner_type) if depth < wrapped_types.len() => {
+                        data_type = inner_type.clone();
+                        depth += 1;
+                    }
+                    _ => break,
+                }
+            }
+
+            wrapped_types.insert(name, data_type);
+        }
+
+        self.types_aliases
+            .retain(|a| !wrapped_types.contains_key(&a.name));
+        self.metadata
+            .app_infos
+            .retain(|a| !wrapped_types.contains_key(&a.name));
+
+        self.replace_data_types(&|data_type| match data_type {
+            DataType::Alias(name) => wrapped_types.get(name).cloned(),
+            _ => None,
+        });
+    }
+
+    fn merge_identical_anonymous_types(&mut self) {
+        loop {
+            let merged = self.identical_anonymous_types();
+
+            if merged.is_empty() {
+                return;
+            }
+
+            self.classes.retain(|c| !merged.contains_key(&c.name));
+            self.enumerations.retain(|e| !merged.contains_key(&e.name));
+            self.metadata
+                .app_infos
+                .retain(|a| !merged.contains_key(&a.name));
+
+            for class in &mut self.classes {
+                if let Some((super_type, _)) = &mut class.super_type {
+                    if let Some(name) = merged.get(super_type) {
+                        super_type.clone_from(name);
+                    }
+                }
+            }
+
+            self.replace_data_types(&|data_type| {
+                let rename = |name: &String| merged.get(name).cloned();
+
+                match data_type {
+                    DataType::Custom(n) => rename(n).map(DataType::Custom),
+                    DataType::Record(n) => rename(n).map(DataType::Record),
+                    DataType::Enumeration(n) => rename(n).map(DataType::Enumeration),
+                    DataType::EnumSet(n) => rename(n).map(DataType::EnumSet),
+                    DataType::InlineEnumSet(n) => rename(n).map(DataType::InlineEnumSet),
+                    _ => None,
+                }
+            });
+        }
+    }
+
+    /// Groups the anonymous types by their content and maps the names of the types of each group
+    /// to the shortest name of the group, of names with the same length the first in alphabetical
+    /// order. So the name doesn't depend on the order of the elements in the schema.
+    ///
+    /// The type with this name is moved to the position of the first type of its group,
+    /// as the types are sorted by their dependencies.
+    fn identical_anonymous_types(&mut self) -> HashMap<String, String> {
+        let derived_names = self
+            .classes
+            .iter()
+            .filter_map(|c| c.super_type.as_ref().map(|(s, _)| s.clone()))
+            .collect::<HashSet<_>>();
+
+        let mut class_groups = HashMap::<_, Vec<usize>>::new();
+        for (i, class) in self.classes.iter().enumerate() {
+            // Base classes are kept apart, as the derived classes extend a specific one
+            if self.is_anonymous(&class.qualified_name) && !derived_names.contains(&class.name) {
+                class_groups
+                    .entry((
+                        &class.super_type,
+                        class.is_abstract,
+                        &class.variables,
+                        &class.identity_constraints,
+                        &class.wildcard,
+                    ))
+                    .or_default()
+                    .push(i);
+            }
+        }
+        let class_groups = class_groups.into_values().collect::<Vec<_>>();
+
+        let mut enumeration_groups = HashMap::<_, Vec<usize>>::new();
+        for (i, enumeration) in self.enumerations.iter().enumerate() {
+            if self.is_anonymous(&enumeration.qualified_name) {
+                let values = enumeration
+                    .values
+                    .iter()
+                    .map(|v| (&v.variant_name, &v.xml_value))
+                    .collect::<Vec<_>>();
+
+                enumeration_groups
+                    .entry((enumeration.is_flags, values))
+                    .or_default()
+                    .push(i);
+            }
+        }
+        let enumeration_groups = enumeration_groups.into_values().collect::<Vec<_>>();
+
+        let mut merged = HashMap::new();
+
+        for group in class_groups.iter().filter(|g| g.len() > 1) {
+            let names = group.iter().map(|i| &self.classes[*i].name);
+            let canonical = Self::canonical_member(group, names);
+
+            self.classes.swap(group[0], canonical);
+            Self::map_to_canonical(group, &mut merged, |i| &self.classes[i].name);
+        }
+
+        for group in enumeration_groups.iter().filter(|g| g.len() > 1) {
+            let names = group.iter().map(|i| &self.enumerations[*i].name);
+            let canonical = Self::canonical_member(group, names);
+
+            self.enumerations.swap(group[0], canonical);
+            Self::map_to_canonical(group, &mut merged, |i| &self.enumerations[i].name);
+        }
+
+        merged
+    }
+
+    /// Index of the type with the shortest name of the group, whose indexes are ascending
+    fn canonical_member<'a>(group: &[usize], names: impl Iterator<Item = &'a String>) -> usize {
+        group
+            .iter()
+            .zip(names)
+            .min_by_key(|(_, name)| (name.len(), *name))
+            .map_or(group[0], |(i, _)| *i)
+    }
+
+    /// Maps the names of the types of the group to the name of the type at the first index
+    fn map_to_canonical<'a>(
+        group: &[usize],
+        merged: &mut HashMap<String, String>,
+        name_of: impl Fn(usize) -> &'a String,
+    ) {
+        let canonical = name_of(group[0]);
+
+        for i in &group[1..] {
+            merged.insert(name_of(*i).clone(), canonical.clone());
+        }
+    }
+
+    /// Anonymous types are qualified by the qualified name of the type, in which they are declared.
+    fn is_anonymous(&self, qualified_name: &str) -> bool {
+        qualified_name.rsplit_once('.').is_some_and(|(parent, _)| {
+            self.classes.iter().any(|c| c.qualified_name == parent)
+                || self.document.qualified_name == parent
+        })
+    }
+
+    /// Replaces the data types of the variables, aliases and union variants, for which
+    /// `replacement` returns a type, including the item types of lists.
+    fn replace_data_types(&mut self, replacement: &impl Fn(&DataType) -> Option<DataType>) {
+        fn replace(data_type: &mut DataType, replacement: &impl Fn(&DataType) -> Option<DataType>) {
+            if let Some(new_type) = replacement(data_type) {
+                *data_type = new_type;
+                return;
+            }
+
+            if let DataType::List(item_type)
+            | DataType::FixedSizeList(item_type, _)
+            | DataType::InlineList(item_type) = data_type
+            {
+                replace(item_type, replacement);
+            }
+        }
+
+        for class in std::iter::once(&mut self.document).chain(self.classes.iter_mut()) {
+            for variable in &mut class.variables {
+                replace(&mut variable.data_type, replacement);
+            }
+        }
+
+        for alias in &mut self.types_aliases {
+            replace(&mut alias.for_type, replacement);
+        }
+
+        for union_type in &mut self.union_types {
+            for variant in &mut union_type.variants {
+                replace(&mut variant.data_type, replacement);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::generator::{
+        internal_representation::DOCUMENT_NAME,
+        types::{ClassType, Enumeration, EnumerationValue, SchemaMetadata, Variable, XMLSource},
+    };
+
+    fn variable(name: &str, data_type: DataType) -> Variable {
+        Variable {
+            name: name.to_owned(),
+            data_type,
+            xml_name: name.to_owned(),
+            requires_free: false,
+            required: true,
+            source: XMLSource::Element,
+            default_value: None,
+            is_const: false,
+            occurrence: None,
+            documentations: vec![],
+            is_documented: false,
+            is_owned: true,
+            namespace: None,
+        }
+    }
+
+    fn class_type(name: &str, qualified_name: &str, variables: Vec<Variable>) -> ClassType {
+        ClassType {
+            name: name.to_owned(),
+            qualified_name: qualified_name.to_owned(),
+            super_type: None,
+            is_abstract: false,
+            variables,
+            documentations: vec![],
+            is_documented: false,
+            identity_constraints: vec![],
+            assertions: vec![],
+            wildcard: None,
+        }
+    }
+
+    fn alias(name: &str, for_type: DataType, pattern: Option<&str>) -> TypeAlias {
+        TypeAlias {
+            name: name.to_owned(),
+            qualified_name: name.to_owned(),
+            for_type,
+            pattern: pattern.map(ToOwned::to_owned),
+            total_digits: None,
+            fraction_digits: None,
+            documentations: vec![],
+            is_documented: false,
+        }
+    }
+
+    fn representation(
+        classes: Vec<ClassType>,
+        types_aliases: Vec<TypeAlias>,
+        enumerations: Vec<Enumeration>,
+    ) -> InternalRepresentation {
+        InternalRepresentation {
+            document: class_type(
+                DOCUMENT_NAME,
+                DOCUMENT_NAME,
+                vec![variable("order", DataType::Custom(String::from("Order")))],
+            ),
+            classes,
+            types_aliases,
+            enumerations,
+            union_types: vec![],
+            external_classes: vec![],
+            metadata: SchemaMetadata::default(),
+        }
+    }
+
+    #[test]
+    fn aliases_without_restrictions_are_flattened() {
+        let mut internal_representation = representation(
+            vec![class_type(
+                "Order",
+                "Order",
+                vec![
+                    variable("id", DataType::Alias(String::from("OrderId"))),
+                    variable("code", DataType::Alias(String::from("Code"))),
+                ],
+            )],
+            vec![
+                alias("Id", DataType::Integer, None),
+                alias("OrderId", DataType::Alias(String::from("Id")), None),
+                alias("Code", DataType::String, Some("[A-Z]+")),
+            ],
+            vec![],
+        );
+
+        internal_representation
+            .run_passes(&[BuiltinPass::FlattenAliases], &CustomPasses::default());
+
+        let data_types = internal_representation.classes[0]
+            .variables
+            .iter()
+            .map(|v| v.data_type.clone())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            data_types,
+            vec![DataType::Integer, DataType::Alias(String::from("Code"))]
+        );
+        assert_eq!(internal_representation.types_aliases.len(), 1);
+    }
+
+    #[test]
+    fn identical_anonymous_types_are_merged() {
+        let enumeration = |name: &str| Enumeration {
+            name: name.to_owned(),
+            qualified_name: format!("Order.{}", name.to_lowercase()),
+            values: vec![EnumerationValue {
+                variant_name: String::from("open"),
+                xml_value: String::from("open"),
+                documentations: vec![],
+                is_documented: false,
+            }],
+            documentations: vec![],
+            is_documented: false,
+            is_flags: false,
+        };
+        let address = |name: &str| {
+            class_type(
+                name,
+                &format!("Order.{}", name.to_lowercase()),
+                vec![variable("street", DataType::String)],
+            )
+        };
+
+        let mut internal_representation = representation(
+            vec![
+                address("Billing"),
+                address("Shipping"),
+                class_type(
+                    "Order",
+                    "Order",
+                    vec![
+                        variable("billing", DataType::Custom(String::from("Billing"))),
+                        variable(
+                            "shipping",
+                            DataType::List(Box::new(DataType::Custom(String::from("Shipping")))),
+                        ),
+                        variable("state", DataType::Enumeration(String::from("State"))),
+                        variable("status", DataType::Enumeration(String::from("Status"))),
+                    ],
+                ),
+            ],
+            vec![],
+            vec![enumeration("State"), enumeration("Status")],
+        );
+
+        internal_representation.run_passes(
+            &[BuiltinPass::MergeIdenticalAnonymousTypes],
+            &CustomPasses::default(),
+        );
+
+        let class_names = internal_representation
+            .classes
+            .iter()
+            .map(|c| c.name.as_str())
+            .collect::<Vec<_>>();
+
+        assert_eq!(class_names, vec!["Billing", "Order"]);
+        assert_eq!(internal_representation.enumerations.len(), 1);
+        assert_eq!(
+            internal_representation.classes[1].variables[1].data_type,
+            DataType::List(Box::new(DataType::Custom(String::from("Billing"))))
+        );
+        assert_eq!(
+            internal_representation.classes[1].variables[3].data_type,
+            DataType::Enumeration(String::from("State"))
+        );
+    }
+
+    #[test]
+    fn merged_types_get_the_shortest_name_at_the_first_position() {
+        let address = |name: &str| {
+            class_type(
+                name,
+                &format!("Order.{name}"),
+                vec![variable("street", DataType::String)],
+            )
+        };
+
+        let mut internal_representation = representation(
+            vec![
+                address("Address2"),
+                address("Address"),
+                class_type(
+                    "Order",
+                    "Order",
+                    vec![
+                        variable("billing", DataType::Custom(String::from("Address2"))),
+                        variable("shipping", DataType::Custom(String::from("Address"))),
+                    ],
+                ),
+            ],
+            vec![],
+            vec![],
+        );
+
+        internal_representation.run_passes(
+            &[BuiltinPass::MergeIdenticalAnonymousTypes],
+            &CustomPasses::default(),
+        );
+
+        let class_names = internal_representation
+            .classes
+            .iter()
+            .map(|c| c.name.as_str())
+            .collect::<Vec<_>>();
+
+        assert_eq!(class_names, vec!["Address", "Order"]);
+        assert_eq!(
+            internal_representation.classes[1].variables[0].data_type,
+            DataType::Custom(String::from("Address"))
+        );
+    }
+
+    #[test]
+    fn custom_passes_run_after_builtin_passes() {
+        struct RenameOrder;
+
+        impl IrPass for RenameOrder {
+            fn name(&self) -> &str {
+                "rename-order"
+            }
+
+            fn run(&self, internal_representation: &mut InternalRepresentation) {
+                for class in &mut internal_representation.classes {
+                    class.name = format!("{}Dto", class.name);
+                }
+            }
+        }
+
+        let mut internal_representation = representation(
+            vec![
+                class_type("Order", "Order", vec![]),
+                class_type("Unused", "Unused", vec![]),
+            ],
+            vec![],
+            vec![],
+        );
+
+        internal_representation.run_passes(
+            &[BuiltinPass::StripUnusedTypes],
+            &CustomPasses(vec![Arc::new(RenameOrder)]),
+        );
+
+        let class_names = internal_representation
+            .classes
+            .iter()
+            .map(|c| c.name.as_str())
+            .collect::<Vec<_>>();
+
+        assert_eq!(class_names, vec!["OrderDto"]);
+    }
+}
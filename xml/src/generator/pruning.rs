@@ -0,0 +1,204 @@
+use std::collections::HashSet;
+
+use super::{
+    code_generator_trait::CodeGenError, internal_representation::InternalRepresentation,
+    types::DataType,
+};
+
+impl InternalRepresentation {
+    /// Keeps only the root elements listed in `roots` and the types, which are reachable from them.
+    ///
+    /// Roots are matched against the xml names of the root elements. Without roots every type is kept.
+    pub fn prune(&mut self, roots: &[String]) -> Result<(), CodeGenError> {
+        if roots.is_empty() {
+            return Ok(());
+        }
+
+        if let Some(root) = roots
+            .iter()
+            .find(|r| !self.document.variables.iter().any(|v| &v.xml_name == *r))
+        {
+            return Err(CodeGenError::UnknownRootElement(root.clone()));
+        }
+
+        self.document
+            .variables
+            .retain(|v| roots.contains(&v.xml_name));
+
+        for class in &mut self.classes {
+            if class.name == self.document.name {
+                class.variables.clone_from(&self.document.variables);
+            }
+        }
+
+        self.retain_reachable_types();
+
+        Ok(())
+    }
+
+    /// Removes the types, which are not used by the document, directly or through other types.
+    pub(crate) fn retain_reachable_types(&mut self) {
+        let reachable = self.reachable_types();
+
+        self.classes
+            .retain(|c| c.name == self.document.name || reachable.contains(&c.name));
+        self.types_aliases.retain(|a| reachable.contains(&a.name));
+        self.enumerations.retain(|e| reachable.contains(&e.name));
+        self.union_types.retain(|u| reachable.contains(&u.name));
+        self.external_classes
+            .retain(|e| reachable.contains(&e.name));
+        self.metadata
+            .app_infos
+            .retain(|a| reachable.contains(&a.name));
+    }
+
+    /// Names of the types used by the document, directly or through other types.
+    fn reachable_types(&self) -> HashSet<String> {
+        let mut reachable = HashSet::new();
+        let mut pending = self
+            .document
+            .variables
+            .iter()
+            .filter_map(|v| v.data_type.referenced_type_name())
+            .cloned()
+            .collect::<Vec<_>>();
+
+        while let Some(name) = pending.pop() {
+            if !reachable.insert(name.clone()) {
+                continue;
+            }
+
+            let mut data_types = Vec::<&DataType>::new();
+
+            if let Some(class) = self.classes.iter().find(|c| c.name == name) {
+                if let Some((super_type, _)) = &class.super_type {
+                    pending.push(super_type.clone());
+                }
+
+                data_types.extend(class.variables.iter().map(|v| &v.data_type));
+            }
+
+            if let Some(alias) = self.types_aliases.iter().find(|a| a.name == name) {
+                data_types.push(&alias.for_type);
+            }
+
+            if let Some(union_type) = self.union_types.iter().find(|u| u.name == name) {
+                data_types.extend(union_type.variants.iter().map(|v| &v.data_type));
+            }
+
+            pending.extend(
+                data_types
+                    .into_iter()
+                    .filter_map(DataType::referenced_type_name)
+                    .cloned(),
+            );
+        }
+
+        reachable
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::types::{ClassType, Enumeration, SchemaMetadata, Variable, XMLSource};
+
+    fn variable(name: &str, data_type: DataType) -> Variable {
+        Variable {
+            name: name.to_owned(),
+            data_type,
+            xml_name: name.to_owned(),
+            requires_free: false,
+            required: true,
+            source: XMLSource::Element,
+            default_value: None,
+            is_const: false,
+            occurrence: None,
+            documentations: vec![],
+            is_documented: false,
+            is_owned: true,
+            namespace: None,
+        }
+    }
+
+    fn class_type(name: &str, super_type: Option<&str>, variables: Vec<Variable>) -> ClassType {
+        ClassType {
+            name: name.to_owned(),
+            qualified_name: name.to_owned(),
+            super_type: super_type.map(|s| (s.to_owned(), s.to_owned())),
+            is_abstract: false,
+            variables,
+            documentations: vec![],
+            is_documented: false,
+            identity_constraints: vec![],
+            assertions: vec![],
+            wildcard: None,
+        }
+    }
+
+    fn enumeration(name: &str) -> Enumeration {
+        Enumeration {
+            name: name.to_owned(),
+            qualified_name: name.to_owned(),
+            values: vec![],
+            documentations: vec![],
+            is_documented: false,
+            is_flags: false,
+        }
+    }
+
+    #[test]
+    fn types_not_reachable_from_the_roots_are_removed() {
+        let document = class_type(
+            "Document",
+            None,
+            vec![
+                variable("order", DataType::Custom(String::from("Order"))),
+                variable("invoice", DataType::Custom(String::from("Invoice"))),
+            ],
+        );
+        let mut ir = InternalRepresentation {
+            document: document.clone(),
+            classes: vec![
+                class_type("Entity", None, vec![]),
+                class_type(
+                    "Order",
+                    Some("Entity"),
+                    vec![variable(
+                        "status",
+                        DataType::List(Box::new(DataType::Enumeration(String::from("Status")))),
+                    )],
+                ),
+                class_type("Invoice", None, vec![]),
+                document,
+            ],
+            types_aliases: vec![],
+            enumerations: vec![enumeration("Status"), enumeration("Currency")],
+            union_types: vec![],
+            external_classes: vec![],
+            metadata: SchemaMetadata::default(),
+        };
+
+        ir.prune(&[String::from("order")]).unwrap();
+
+        assert_eq!(
+            ir.classes
+                .iter()
+                .map(|c| c.name.as_str())
+                .collect::<Vec<_>>(),
+            ["Entity", "Order", "Document"]
+        );
+        assert_eq!(
+            ir.enumerations
+                .iter()
+                .map(|e| e.name.as_str())
+                .collect::<Vec<_>>(),
+            ["Status"]
+        );
+        assert_eq!(ir.document.variables.len(), 1);
+        assert!(matches!(
+            ir.prune(&[String::from("invoice")]),
+            Err(CodeGenError::UnknownRootElement(_))
+        ));
+    }
+}
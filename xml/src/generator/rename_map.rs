@@ -0,0 +1,316 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{parser::types::CustomTypeDefinition, type_registry::TypeRegistry};
+
+use super::{code_generator_trait::CodeGenError, internal_representation::InternalRepresentation};
+
+/// Names, which replace the generated names of specific types, fields and enumeration values,
+/// e.g. to keep business-friendly names across regenerations without editing the unit.
+///
+/// Types are identified by their name in the schema or by their qualified name like in the
+/// name map, fields by the name of their element or attribute and enumeration values by their
+/// value in the schema. Every entry has to match the schema, otherwise the generation fails.
+///
+/// ```json
+/// {
+///   "types": { "Addr": "Address" },
+///   "fields": { "Addr": { "str": "Street" } },
+///   "values": { "Status": { "o": "Open" } }
+/// }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RenameMap {
+    /// New names of types, without the type prefix
+    pub types: BTreeMap<String, String>,
+    /// New names of the fields of complex types, by the name of their element or attribute
+    pub fields: BTreeMap<String, BTreeMap<String, String>>,
+    /// New names of the values of enumerations, by their value in the schema
+    pub values: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+/// Rename map, whose types are identified by their qualified name
+#[derive(Debug, Default)]
+pub(crate) struct ResolvedRenameMap {
+    types: BTreeMap<String, String>,
+    fields: BTreeMap<String, BTreeMap<String, String>>,
+    values: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl RenameMap {
+    /// Resolves the types of the entries to their qualified names. Has to be called before
+    /// the types are renamed, as simple types lose their schema name by the renaming.
+    pub(crate) fn resolve(
+        &self,
+        type_registry: &TypeRegistry,
+    ) -> Result<ResolvedRenameMap, CodeGenError> {
+        let mut resolved = ResolvedRenameMap::default();
+
+        for (key, name) in &self.types {
+            let qualified_name = Self::qualified_name(type_registry, key, "a type", |_| true)?;
+
+            resolved.types.insert(qualified_name, name.clone());
+        }
+
+        for (key, fields) in &self.fields {
+            let qualified_name = Self::qualified_name(type_registry, key, "a complex type", |t| {
+                matches!(t, CustomTypeDefinition::Complex(_))
+            })?;
+
+            resolved.fields.insert(qualified_name, fields.clone());
+        }
+
+        for (key, values) in &self.values {
+            let qualified_name = Self::qualified_name(
+                type_registry,
+                key,
+                "an enumeration",
+                |t| matches!(t, CustomTypeDefinition::Simple(s) if s.enumeration.is_some()),
+            )?;
+
+            resolved.values.insert(qualified_name, values.clone());
+        }
+
+        Ok(resolved)
+    }
+
+    /// Qualified name of the type `key`, which has to be the qualified name or the unique
+    /// schema name of a type of the expected `kind`
+    fn qualified_name(
+        type_registry: &TypeRegistry,
+        key: &str,
+        kind: &str,
+        is_expected_kind: impl Fn(&CustomTypeDefinition) -> bool,
+    ) -> Result<String, CodeGenError> {
+        let mut candidates = match type_registry.types.get_key_value(key) {
+            Some((qualified_name, t)) => vec![(qualified_name, t)],
+            None => type_registry
+                .types
+                .iter()
+                .filter(|(_, t)| match t {
+                    CustomTypeDefinition::Simple(s) => s.name == key,
+                    CustomTypeDefinition::Complex(c) => c.name == key,
+                })
+                .collect(),
+        };
+        candidates.sort_by(|a, b| a.0.cmp(b.0));
+
+        match candidates.as_slice() {
+            [] => Err(CodeGenError::InvalidRenameMap(format!(
+                "Type \"{key}\" is not declared in the schema"
+            ))),
+            [(qualified_name, t)] if is_expected_kind(t) => Ok((*qualified_name).clone()),
+            [_] => Err(CodeGenError::InvalidRenameMap(format!(
+                "Type \"{key}\" is not {kind}"
+            ))),
+            _ => Err(CodeGenError::InvalidRenameMap(format!(
+                "Type \"{key}\" is ambiguous, use one of the qualified names {}",
+                candidates
+                    .iter()
+                    .map(|(qualified_name, _)| format!("\"{qualified_name}\""))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ))),
+        }
+    }
+}
+
+impl TypeRegistry {
+    /// Renames the types of the rename map, before the internal representation is built,
+    /// so the references to the types use the new names.
+    pub(crate) fn rename_types(&mut self, rename_map: &ResolvedRenameMap) {
+        for (qualified_name, name) in &rename_map.types {
+            match self.types.get_mut(qualified_name) {
+                Some(CustomTypeDefinition::Simple(t)) => t.name.clone_from(name),
+                Some(CustomTypeDefinition::Complex(t)) => t.overrides.rename = Some(name.clone()),
+                None => (),
+            }
+        }
+    }
+}
+
+impl InternalRepresentation {
+    /// Renames the fields and enumeration values of the rename map. Types, which are not
+    /// generated, e.g. because they are not reachable from the root elements, are skipped.
+    pub(crate) fn rename_members(
+        &mut self,
+        rename_map: &ResolvedRenameMap,
+    ) -> Result<(), CodeGenError> {
+        for class in &mut self.classes {
+            let Some(fields) = rename_map.fields.get(&class.qualified_name) else {
+                continue;
+            };
+
+            for (xml_name, name) in fields {
+                let Some(variable) = class.variables.iter_mut().find(|v| &v.xml_name == xml_name)
+                else {
+                    return Err(CodeGenError::InvalidRenameMap(format!(
+                        "Type \"{}\" has no field \"{xml_name}\"",
+                        class.qualified_name
+                    )));
+                };
+
+                variable.name.clone_from(name);
+            }
+        }
+
+        for enumeration in &mut self.enumerations {
+            let Some(values) = rename_map.values.get(&enumeration.qualified_name) else {
+                continue;
+            };
+
+            for (xml_value, name) in values {
+                let Some(value) = enumeration
+                    .values
+                    .iter_mut()
+                    .find(|v| &v.xml_value == xml_value)
+                else {
+                    return Err(CodeGenError::InvalidRenameMap(format!(
+                        "Enumeration \"{}\" has no value \"{xml_value}\"",
+                        enumeration.qualified_name
+                    )));
+                };
+
+                value.variant_name.clone_from(name);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{
+        build_representation,
+        generator::{
+            code_generator_trait::{CodeGenOptions, NamingOptions},
+            types::DataType,
+        },
+    };
+
+    fn build(fixture: &str, rename_map: RenameMap) -> Result<InternalRepresentation, String> {
+        let options = CodeGenOptions::builder("uTest")
+            .naming(NamingOptions {
+                rename_map: Some(rename_map),
+                ..NamingOptions::default()
+            })
+            .build();
+
+        build_representation(
+            &[PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+                .join("tests/fixtures")
+                .join(fixture)],
+            &options,
+            &mut TypeRegistry::new(),
+        )
+        .map(|(_, representation)| representation)
+        .map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn types_fields_and_values_are_renamed() {
+        let rename_map = serde_json::from_str::<RenameMap>(
+            r#"{
+                "types": { "Status": "TicketState" },
+                "values": { "Status": { "open": "Pending" } }
+            }"#,
+        )
+        .unwrap();
+
+        let representation = build("simple_types.xsd", rename_map).unwrap();
+        let status = representation
+            .enumerations
+            .iter()
+            .find(|e| e.qualified_name == "Status")
+            .unwrap();
+        let state = representation
+            .classes
+            .iter()
+            .find(|c| c.name == "state")
+            .unwrap();
+
+        assert_eq!(status.name, "TicketState");
+        assert_eq!(status.values[0].variant_name, "Pending");
+        assert_eq!(status.values[1].variant_name, "closed");
+        assert_eq!(
+            state.variables[0].data_type,
+            DataType::Enumeration(String::from("TicketState"))
+        );
+
+        let rename_map = serde_json::from_str::<RenameMap>(
+            r#"{
+                "types": { "Circle": "Round" },
+                "fields": { "Circle": { "radius": "Size" } }
+            }"#,
+        )
+        .unwrap();
+        let representation = build("inheritance.xsd", rename_map).unwrap();
+        let circle = representation
+            .classes
+            .iter()
+            .find(|c| c.qualified_name == "Circle")
+            .unwrap();
+
+        assert_eq!(circle.name, "Round");
+        assert_eq!(circle.variables[0].name, "Size");
+    }
+
+    #[test]
+    fn unknown_entries_are_rejected() {
+        let unknown_type = RenameMap {
+            types: BTreeMap::from([(String::from("Missing"), String::from("Found"))]),
+            ..RenameMap::default()
+        };
+        let unknown_field = RenameMap {
+            fields: BTreeMap::from([(
+                String::from("Circle"),
+                BTreeMap::from([(String::from("diameter"), String::from("Size"))]),
+            )]),
+            ..RenameMap::default()
+        };
+        let unknown_value = RenameMap {
+            values: BTreeMap::from([(
+                String::from("Status"),
+                BTreeMap::from([(String::from("pending"), String::from("Pending"))]),
+            )]),
+            ..RenameMap::default()
+        };
+        let values_of_class = RenameMap {
+            values: BTreeMap::from([(String::from("Circle"), BTreeMap::new())]),
+            ..RenameMap::default()
+        };
+
+        assert_eq!(
+            build("simple_types.xsd", unknown_type).err(),
+            Some(String::from(
+                "Failed to generate code: Invalid rename map: Type \"Missing\" is not declared in the schema"
+            ))
+        );
+        assert_eq!(
+            build("inheritance.xsd", unknown_field).err(),
+            Some(String::from(
+                "Failed to generate code: Invalid rename map: Type \"Circle\" has no field \"diameter\""
+            ))
+        );
+        assert_eq!(
+            build("simple_types.xsd", unknown_value).err(),
+            Some(String::from(
+                "Failed to generate code: Invalid rename map: Enumeration \"Status\" has no value \"pending\""
+            ))
+        );
+        assert_eq!(
+            build("inheritance.xsd", values_of_class).err(),
+            Some(String::from(
+                "Failed to generate code: Invalid rename map: Type \"Circle\" is not an enumeration"
+            ))
+        );
+    }
+}
@@ -0,0 +1,410 @@
+use std::fmt::Write;
+
+use super::{
+    code_generator_trait::CodeGenError,
+    internal_representation::InternalRepresentation,
+    types::{BinaryEncoding, ClassType, DataType, TypeAlias, Variable, XMLSource},
+};
+
+impl InternalRepresentation {
+    /// Writes an example document for the root element `root`.
+    ///
+    /// Every element and attribute is filled with a value of its type: the first value of
+    /// enumerations and unions, a value matching the pattern of an alias, if the pattern is
+    /// simple enough, and a value fitting into the digits of restricted decimals.
+    /// Repeated elements are written once, fixed size lists with all of their items.
+    pub fn to_sample(&self, root: &str) -> Result<String, CodeGenError> {
+        let Some(root) = self.document.variables.iter().find(|v| v.xml_name == root) else {
+            return Err(CodeGenError::UnknownRootElement(root.to_owned()));
+        };
+
+        let mut writer = SampleWriter {
+            ir: self,
+            xml: String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n"),
+            open_classes: vec![],
+        };
+        writer.write_variable(root, 0);
+
+        Ok(writer.xml)
+    }
+}
+
+struct SampleWriter<'a> {
+    ir: &'a InternalRepresentation,
+    xml: String,
+    /// Classes of the enclosing elements, a recursive element is written empty
+    open_classes: Vec<&'a str>,
+}
+
+impl<'a> SampleWriter<'a> {
+    fn write_variable(&mut self, variable: &'a Variable, depth: usize) {
+        if variable.source == XMLSource::Group {
+            if let Some(class) = self.class(&variable.data_type) {
+                for variable in self.elements(class) {
+                    self.write_variable(variable, depth);
+                }
+            }
+
+            return;
+        }
+
+        let (data_type, count) = match &variable.data_type {
+            DataType::List(inner) => (inner.as_ref(), 1),
+            DataType::FixedSizeList(inner, size) => (inner.as_ref(), *size),
+            DataType::EnumSet(name) => (&DataType::Enumeration(name.clone()), 1),
+            data_type => (data_type, 1),
+        };
+
+        for _ in 0..count {
+            match self.class(data_type) {
+                Some(class) => self.write_class(&variable.xml_name, class, depth),
+                None => {
+                    let value = variable
+                        .default_value
+                        .clone()
+                        .unwrap_or_else(|| self.value(data_type, &variable.xml_name));
+
+                    let _ = writeln!(
+                        self.xml,
+                        "{}<{name}>{}</{name}>",
+                        indent(depth),
+                        escape(&value),
+                        name = variable.xml_name,
+                    );
+                }
+            }
+        }
+    }
+
+    fn write_class(&mut self, name: &str, class: &'a ClassType, depth: usize) {
+        let attributes = self
+            .variables(class)
+            .into_iter()
+            .filter(|v| v.source == XMLSource::Attribute)
+            .map(|v| {
+                let value = v
+                    .default_value
+                    .clone()
+                    .unwrap_or_else(|| self.value(&v.data_type, &v.xml_name));
+
+                format!(" {}=\"{}\"", v.xml_name, escape(&value))
+            })
+            .collect::<String>();
+
+        let elements = self.elements(class);
+
+        if elements.is_empty() || self.open_classes.contains(&class.name.as_str()) {
+            let _ = writeln!(self.xml, "{}<{name}{attributes}/>", indent(depth));
+            return;
+        }
+
+        let _ = writeln!(self.xml, "{}<{name}{attributes}>", indent(depth));
+        self.open_classes.push(&class.name);
+
+        for variable in elements {
+            self.write_variable(variable, depth + 1);
+        }
+
+        self.open_classes.pop();
+        let _ = writeln!(self.xml, "{}</{name}>", indent(depth));
+    }
+
+    /// Variables of the class including those of its super types, which come first
+    fn variables(&self, class: &'a ClassType) -> Vec<&'a Variable> {
+        let mut variables = class
+            .super_type
+            .as_ref()
+            .and_then(|(name, _)| self.ir.classes.iter().find(|c| &c.name == name))
+            .map(|super_type| self.variables(super_type))
+            .unwrap_or_default();
+        variables.extend(&class.variables);
+
+        variables
+    }
+
+    fn elements(&self, class: &'a ClassType) -> Vec<&'a Variable> {
+        self.variables(class)
+            .into_iter()
+            .filter(|v| v.source != XMLSource::Attribute)
+            .collect()
+    }
+
+    /// Class written for the data type, the first class variant for unions of classes
+    fn class(&self, data_type: &DataType) -> Option<&'a ClassType> {
+        match data_type {
+            DataType::Custom(name) | DataType::Record(name) => self
+                .ir
+                .classes
+                .iter()
+                .find(|c| &c.name == name)
+                .or_else(|| {
+                    self.ir
+                        .union_types
+                        .iter()
+                        .find(|u| &u.name == name)
+                        .and_then(|u| u.variants.first())
+                        .and_then(|v| self.class(&v.data_type))
+                }),
+            DataType::List(inner) => self.class(inner),
+            _ => None,
+        }
+    }
+
+    /// Value of a simple type, `name` is used as value of strings
+    fn value(&self, data_type: &DataType, name: &str) -> String {
+        match data_type {
+            DataType::Boolean => String::from("true"),
+            DataType::DateTime | DataType::DateTimeOffset => String::from("2024-01-31T12:00:00"),
+            DataType::Date => String::from("2024-01-31"),
+            DataType::Time => String::from("12:00:00"),
+            DataType::Double | DataType::Currency | DataType::Bcd => String::from("1.5"),
+            DataType::Binary(BinaryEncoding::Hex) => String::from("48656C6C6F"),
+            DataType::Binary(BinaryEncoding::Base64) => String::from("SGVsbG8="),
+            DataType::ShortInteger
+            | DataType::SmallInteger
+            | DataType::Integer
+            | DataType::LongInteger
+            | DataType::UnsignedShortInteger
+            | DataType::UnsignedSmallInteger
+            | DataType::UnsignedInteger
+            | DataType::UnsignedLongInteger => String::from("1"),
+            DataType::String => name.to_owned(),
+            DataType::Uri => String::from("https://example.com"),
+            DataType::Enumeration(enum_name)
+            | DataType::EnumSet(enum_name)
+            | DataType::InlineEnumSet(enum_name) => self
+                .ir
+                .enumerations
+                .iter()
+                .find(|e| &e.name == enum_name)
+                .and_then(|e| e.values.first())
+                .map(|v| v.xml_value.clone())
+                .unwrap_or_default(),
+            DataType::Alias(type_name)
+            | DataType::Custom(type_name)
+            | DataType::Record(type_name)
+            | DataType::Union(type_name) => {
+                if let Some(alias) = self.ir.types_aliases.iter().find(|a| &a.name == type_name) {
+                    self.alias_value(alias, name)
+                } else if let Some(variant) = self
+                    .ir
+                    .union_types
+                    .iter()
+                    .find(|u| &u.name == type_name)
+                    .and_then(|u| u.variants.first())
+                {
+                    self.value(&variant.data_type, name)
+                } else {
+                    name.to_owned()
+                }
+            }
+            DataType::List(inner)
+            | DataType::FixedSizeList(inner, _)
+            | DataType::InlineList(inner) => self.value(inner, name),
+        }
+    }
+
+    fn alias_value(&self, alias: &TypeAlias, name: &str) -> String {
+        if let Some(value) = alias.pattern.as_deref().and_then(sample_pattern) {
+            return value;
+        }
+
+        if alias.total_digits.is_some() || alias.fraction_digits.is_some() {
+            let fraction_digits = alias
+                .fraction_digits
+                .unwrap_or(1)
+                .min(alias.total_digits.map_or(1, |t| t.saturating_sub(1)));
+
+            return match fraction_digits {
+                0 => String::from("1"),
+                n => format!("1.{}", "5".repeat(n)),
+            };
+        }
+
+        self.value(&alias.for_type, name)
+    }
+}
+
+/// Builds a value matching a pattern of literals, character classes and quantifiers.
+///
+/// Returns `None` for patterns with groups, alternatives or negated classes.
+fn sample_pattern(pattern: &str) -> Option<String> {
+    let mut value = String::new();
+    let mut chars = pattern.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        let atom = match c {
+            '\\' => match chars.next()? {
+                'd' => '1',
+                'w' | 'i' | 'c' => 'a',
+                's' => ' ',
+                c if c.is_ascii_alphanumeric() => return None,
+                c => c,
+            },
+            '[' => {
+                let first = chars.next()?;
+                if first == '^' || first == '\\' {
+                    return None;
+                }
+                chars.by_ref().find(|c| *c == ']')?;
+
+                first
+            }
+            '.' => 'a',
+            '(' | ')' | '|' | '{' | '}' | '*' | '+' | '?' | ']' => return None,
+            c => c,
+        };
+
+        let count = match chars.peek() {
+            Some('?' | '*' | '+') => {
+                chars.next();
+                1
+            }
+            Some('{') => {
+                chars.next();
+                let quantifier = chars.by_ref().take_while(|c| *c != '}').collect::<String>();
+                quantifier.split(',').next()?.trim().parse().ok()?
+            }
+            _ => 1,
+        };
+
+        value.extend(std::iter::repeat_n(atom, count));
+    }
+
+    Some(value)
+}
+
+fn indent(depth: usize) -> String {
+    "  ".repeat(depth)
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::types::{Enumeration, EnumerationValue, SchemaMetadata};
+
+    fn variable(name: &str, data_type: DataType, source: XMLSource) -> Variable {
+        Variable {
+            name: name.to_owned(),
+            data_type,
+            xml_name: name.to_owned(),
+            requires_free: false,
+            required: true,
+            source,
+            default_value: None,
+            is_const: false,
+            occurrence: None,
+            documentations: vec![],
+            is_documented: false,
+            is_owned: true,
+            namespace: None,
+        }
+    }
+
+    fn class(name: &str, variables: Vec<Variable>) -> ClassType {
+        ClassType {
+            name: name.to_owned(),
+            qualified_name: name.to_owned(),
+            super_type: None,
+            is_abstract: false,
+            variables,
+            documentations: vec![],
+            is_documented: false,
+            identity_constraints: vec![],
+            assertions: vec![],
+            wildcard: None,
+        }
+    }
+
+    #[test]
+    fn sample_honors_enumerations_patterns_and_digits() {
+        let order = class(
+            "Order",
+            vec![
+                variable(
+                    "id",
+                    DataType::Alias(String::from("OrderId")),
+                    XMLSource::Attribute,
+                ),
+                variable(
+                    "status",
+                    DataType::Enumeration(String::from("Status")),
+                    XMLSource::Element,
+                ),
+                variable(
+                    "total",
+                    DataType::Alias(String::from("Amount")),
+                    XMLSource::Element,
+                ),
+                variable(
+                    "item",
+                    DataType::List(Box::new(DataType::Custom(String::from("Order")))),
+                    XMLSource::Element,
+                ),
+            ],
+        );
+        let alias = |name: &str, pattern: Option<&str>, digits: Option<(usize, usize)>| TypeAlias {
+            name: name.to_owned(),
+            qualified_name: name.to_owned(),
+            for_type: DataType::String,
+            pattern: pattern.map(str::to_owned),
+            total_digits: digits.map(|d| d.0),
+            fraction_digits: digits.map(|d| d.1),
+            documentations: vec![],
+            is_documented: false,
+        };
+        let ir = InternalRepresentation {
+            document: class(
+                "Document",
+                vec![variable(
+                    "order",
+                    DataType::Custom(String::from("Order")),
+                    XMLSource::Element,
+                )],
+            ),
+            classes: vec![order],
+            types_aliases: vec![
+                alias("OrderId", Some("[A-Z]{2}-\\d{4}"), None),
+                alias("Amount", None, Some((5, 2))),
+            ],
+            enumerations: vec![Enumeration {
+                name: String::from("Status"),
+                qualified_name: String::from("Status"),
+                values: vec![EnumerationValue {
+                    variant_name: String::from("sOpen"),
+                    xml_value: String::from("open"),
+                    documentations: vec![],
+                    is_documented: false,
+                }],
+                documentations: vec![],
+                is_documented: false,
+                is_flags: false,
+            }],
+            union_types: vec![],
+            external_classes: vec![],
+            metadata: SchemaMetadata::default(),
+        };
+
+        assert_eq!(
+            ir.to_sample("order").unwrap(),
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <order id=\"AA-1111\">\n  \
+               <status>open</status>\n  \
+               <total>1.55</total>\n  \
+               <item id=\"AA-1111\"/>\n\
+             </order>\n"
+        );
+        assert!(matches!(
+            ir.to_sample("invoice"),
+            Err(CodeGenError::UnknownRootElement(_))
+        ));
+    }
+}
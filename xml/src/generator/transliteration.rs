@@ -0,0 +1,200 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{parser::types::CustomTypeDefinition, type_registry::TypeRegistry};
+
+use super::internal_representation::InternalRepresentation;
+
+/// Replaces the non-ASCII characters of the identifiers in the generated code,
+/// e.g. `Größe` becomes `Groesse`. The names in the xml documents are kept.
+///
+/// Characters without a replacement are replaced by `_`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Transliteration {
+    /// Replacements, which take precedence over the built-in ones
+    replacements: Vec<(char, String)>,
+}
+
+impl Transliteration {
+    /// Replaces `from` with `to` instead of the built-in replacement
+    #[must_use]
+    pub fn with_replacement(mut self, from: char, to: String) -> Self {
+        self.replacements.retain(|(c, _)| *c != from);
+        self.replacements.push((from, to));
+
+        self
+    }
+
+    pub fn transliterate(&self, name: &str) -> String {
+        let mut result = String::with_capacity(name.len());
+
+        for c in name.chars() {
+            if c.is_ascii() {
+                result.push(c);
+            } else if let Some((_, to)) = self.replacements.iter().find(|(from, _)| *from == c) {
+                result.push_str(to);
+            } else {
+                result.push_str(Self::builtin_replacement(c));
+            }
+        }
+
+        result
+    }
+
+    fn builtin_replacement(c: char) -> &'static str {
+        match c {
+            'ä' => "ae",
+            'ö' => "oe",
+            'ü' => "ue",
+            'Ä' => "Ae",
+            'Ö' => "Oe",
+            'Ü' => "Ue",
+            'ß' => "ss",
+            'ẞ' => "SS",
+            'æ' => "ae",
+            'Æ' => "Ae",
+            'œ' => "oe",
+            'Œ' => "Oe",
+            'ø' => "o",
+            'Ø' => "O",
+            'à' | 'á' | 'â' | 'ã' | 'å' | 'ā' | 'ą' => "a",
+            'À' | 'Á' | 'Â' | 'Ã' | 'Å' | 'Ā' | 'Ą' => "A",
+            'ç' | 'ć' | 'č' => "c",
+            'Ç' | 'Ć' | 'Č' => "C",
+            'ď' | 'đ' => "d",
+            'Ď' | 'Đ' => "D",
+            'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ę' | 'ě' => "e",
+            'È' | 'É' | 'Ê' | 'Ë' | 'Ē' | 'Ę' | 'Ě' => "E",
+            'ì' | 'í' | 'î' | 'ï' | 'ī' => "i",
+            'Ì' | 'Í' | 'Î' | 'Ï' | 'Ī' => "I",
+            'ł' => "l",
+            'Ł' => "L",
+            'ñ' | 'ń' | 'ň' => "n",
+            'Ñ' | 'Ń' | 'Ň' => "N",
+            'ò' | 'ó' | 'ô' | 'õ' | 'ō' | 'ő' => "o",
+            'Ò' | 'Ó' | 'Ô' | 'Õ' | 'Ō' | 'Ő' => "O",
+            'ř' => "r",
+            'Ř' => "R",
+            'ś' | 'š' => "s",
+            'Ś' | 'Š' => "S",
+            'ť' => "t",
+            'Ť' => "T",
+            'ù' | 'ú' | 'û' | 'ū' | 'ů' | 'ű' => "u",
+            'Ù' | 'Ú' | 'Û' | 'Ū' | 'Ů' | 'Ű' => "U",
+            'ý' | 'ÿ' => "y",
+            'Ý' | 'Ÿ' => "Y",
+            'ź' | 'ż' | 'ž' => "z",
+            'Ź' | 'Ż' | 'Ž' => "Z",
+            _ => "_",
+        }
+    }
+
+    /// Transliterates the names, which contain non-ASCII characters. A name, which collides
+    /// with another name after the transliteration, gets a number appended and a warning is printed.
+    ///
+    /// Names are compared case-insensitive like identifiers in Delphi.
+    fn transliterate_unique<'a>(&self, names: impl Iterator<Item = &'a mut String>, kind: &str) {
+        let mut names = names.collect::<Vec<_>>();
+        let mut used_names = names
+            .iter()
+            .filter(|n| n.is_ascii())
+            .map(|n| n.to_lowercase())
+            .collect::<HashSet<_>>();
+
+        for name in names.iter_mut().filter(|n| !n.is_ascii()) {
+            let transliterated = self.transliterate(name);
+
+            let mut unique_name = transliterated.clone();
+            let mut counter = 2;
+            while !used_names.insert(unique_name.to_lowercase()) {
+                unique_name = format!("{transliterated}{counter}");
+                counter += 1;
+            }
+
+            if unique_name != transliterated {
+                eprintln!(
+                    "Warning: {kind} \"{name}\" is named \"{unique_name}\", because \"{transliterated}\" is already used"
+                );
+            }
+
+            **name = unique_name;
+        }
+    }
+}
+
+impl TypeRegistry {
+    /// Transliterates the names of the types, before the internal representation is built,
+    /// so the references to the types use the new names.
+    pub fn transliterate_type_names(&mut self, transliteration: &Transliteration) {
+        let mut types = self.types.iter_mut().collect::<Vec<_>>();
+        // Collisions are resolved in a stable order
+        types.sort_by(|a, b| a.0.cmp(b.0));
+
+        let names = types.into_iter().map(|(_, custom_type)| match custom_type {
+            CustomTypeDefinition::Simple(t) => &mut t.name,
+            CustomTypeDefinition::Complex(t) => t.overrides.rename.as_mut().unwrap_or(&mut t.name),
+        });
+
+        transliteration.transliterate_unique(names, "type");
+    }
+}
+
+impl InternalRepresentation {
+    /// Transliterates the names of the fields and enumeration values, whose xml names are kept.
+    pub fn transliterate_identifiers(&mut self, transliteration: &Transliteration) {
+        for class in std::iter::once(&mut self.document).chain(self.classes.iter_mut()) {
+            let names = class.variables.iter_mut().map(|v| &mut v.name);
+
+            transliteration.transliterate_unique(names, &format!("field of {}", class.name));
+        }
+
+        for enumeration in &mut self.enumerations {
+            let names = enumeration.values.iter_mut().map(|v| &mut v.variant_name);
+
+            transliteration.transliterate_unique(names, &format!("value of {}", enumeration.name));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn umlauts_are_transliterated() {
+        let transliteration = Transliteration::default();
+
+        assert_eq!(
+            transliteration.transliterate("GrößeÄnderung"),
+            "GroesseAenderung"
+        );
+        assert_eq!(transliteration.transliterate("Café"), "Cafe");
+        assert_eq!(transliteration.transliterate("Preis€"), "Preis_");
+    }
+
+    #[test]
+    fn custom_replacements_take_precedence() {
+        let transliteration = Transliteration::default()
+            .with_replacement('ä', String::from("a"))
+            .with_replacement('€', String::from("Euro"));
+
+        assert_eq!(transliteration.transliterate("Währung€"), "WahrungEuro");
+    }
+
+    #[test]
+    fn colliding_names_get_a_number() {
+        let mut names = vec![
+            String::from("Strasse"),
+            String::from("Straße"),
+            String::from("STRAßE"),
+        ];
+
+        Transliteration::default().transliterate_unique(names.iter_mut(), "field");
+
+        assert_eq!(names, vec!["Strasse", "Strasse2", "STRAssE3"]);
+    }
+}
@@ -1,11 +1,23 @@
+use std::{collections::HashMap, ops::Deref};
+
+use serde::Serialize;
+
 use super::dependency_graph::Dependable;
 
-#[derive(Clone, Debug)]
+pub use crate::parser::types::{AttributeNamespace, ProcessContents, Wildcard, WildcardNamespaces};
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize)]
 pub enum DataType {
     Boolean,
     DateTime,
+    /// xs:dateTime, which keeps the time zone offset of the document
+    DateTimeOffset,
     Date,
     Double,
+    /// Fixed-point decimal with four decimal places
+    Currency,
+    /// Binary coded decimal
+    Bcd,
     Binary(BinaryEncoding),
     /// i8: -127 to 128
     ShortInteger,
@@ -28,7 +40,13 @@ pub enum DataType {
     Uri,
     Alias(String),
     Custom(String),
+    /// Complex type, which is generated as value type
+    Record(String),
     Enumeration(String),
+    /// Set of an enumeration, whose values are repeated elements
+    EnumSet(String),
+    /// Set of an enumeration, whose values are separated by spaces
+    InlineEnumSet(String),
     List(Box<DataType>),
     FixedSizeList(Box<DataType>, usize),
     InlineList(Box<DataType>),
@@ -37,52 +55,169 @@ pub enum DataType {
     Union(String),
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize)]
 pub enum BinaryEncoding {
     Hex,
     Base64,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize)]
 pub enum XMLSource {
     Element,
     Attribute,
+    /// Repeated sequence, whose elements are written directly into the parent.
+    /// `xml_name` holds the name of the element that starts each repetition.
+    Group,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct Enumeration {
     pub name: String,
     pub qualified_name: String,
     pub values: Vec<EnumerationValue>,
     pub documentations: Vec<String>,
+    /// The schema documents the type with xs:documentation
+    pub is_documented: bool,
+    /// Marked as flags, lists of the enumeration are always generated as set
+    pub is_flags: bool,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct EnumerationValue {
     pub variant_name: String,
     pub xml_value: String,
     pub documentations: Vec<String>,
+    /// The schema documents the value with xs:documentation
+    pub is_documented: bool,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct TypeAlias {
     pub name: String,
     pub qualified_name: String,
     pub for_type: DataType,
     pub pattern: Option<String>,
+    pub total_digits: Option<usize>,
+    pub fraction_digits: Option<usize>,
     pub documentations: Vec<String>,
+    /// The schema documents the type with xs:documentation
+    pub is_documented: bool,
+}
+
+/// Type aliases with the data type and pattern of every alias resolved through the aliases
+/// it is based on. Built once per generation, so the code generators look up an alias by
+/// its name instead of scanning the aliases again and again.
+#[derive(Clone, Debug, Default)]
+pub struct ResolvedTypeAliases<'a> {
+    aliases: &'a [TypeAlias],
+    resolved: HashMap<&'a str, (DataType, Option<String>)>,
+}
+
+impl<'a> ResolvedTypeAliases<'a> {
+    pub fn new(aliases: &'a [TypeAlias]) -> Self {
+        let by_name = aliases.iter().fold(HashMap::new(), |mut by_name, a| {
+            by_name.entry(a.name.as_str()).or_insert(a);
+            by_name
+        });
+
+        let resolved = by_name
+            .iter()
+            .map(|(name, alias)| {
+                let mut pattern = alias.pattern.clone();
+                let mut data_type = alias.for_type.clone();
+
+                // bounded by the number of aliases, so cyclic aliases don't loop forever
+                for _ in 0..by_name.len() {
+                    let Some(base) = (match &data_type {
+                        DataType::Custom(n) => by_name.get(n.as_str()),
+                        _ => None,
+                    }) else {
+                        break;
+                    };
+
+                    if pattern.is_none() {
+                        pattern.clone_from(&base.pattern);
+                    }
+
+                    data_type = base.for_type.clone();
+                }
+
+                (*name, (data_type, pattern))
+            })
+            .collect();
+
+        Self { aliases, resolved }
+    }
+
+    /// Data type and pattern of the alias `name`, the first pattern of the aliases it is
+    /// based on, if it has none of its own
+    pub fn get(&self, name: &str) -> Option<&(DataType, Option<String>)> {
+        self.resolved.get(name)
+    }
+
+    pub fn aliases(&self) -> &'a [TypeAlias] {
+        self.aliases
+    }
 }
 
-#[derive(Clone, Debug)]
+impl Deref for ResolvedTypeAliases<'_> {
+    type Target = [TypeAlias];
+
+    fn deref(&self) -> &Self::Target {
+        self.aliases
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
 pub struct ClassType {
     pub name: String,
     pub qualified_name: String,
     pub super_type: Option<(String, String)>,
+    /// Only instances of derived classes are created
+    pub is_abstract: bool,
     pub variables: Vec<Variable>,
     pub documentations: Vec<String>,
+    /// The schema documents the type with xs:documentation
+    pub is_documented: bool,
+    /// xs:key, xs:keyref and xs:unique of the elements of this type
+    pub identity_constraints: Vec<IdentityConstraint>,
+    /// xs:any, which takes the child elements not declared by the type
+    pub wildcard: Option<Wildcard>,
+    /// test-attributes of the xs:assert elements of the type
+    pub assertions: Vec<String>,
+}
+
+/// Identity constraint of an element, i.e. xs:key, xs:keyref or xs:unique
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize)]
+pub struct IdentityConstraint {
+    /// Name of the element declaring the constraint, which starts the paths of violations
+    pub element: String,
+    pub name: String,
+    pub kind: IdentityConstraintKind,
+    /// Path of the selected nodes, relative to the element
+    pub selector: String,
+    /// Paths of the values of each selected node
+    pub fields: Vec<String>,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize)]
+pub enum IdentityConstraintKind {
+    Key,
+    Unique,
+    /// Name of the referenced key or unique constraint
+    KeyRef(String),
+}
+
+/// Complex type, which is implemented by hand instead of being generated
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ExternalClass {
+    pub name: String,
+    pub qualified_name: String,
+    pub delphi_type: String,
+    pub unit: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize)]
 pub struct Variable {
     pub name: String,
     pub data_type: DataType,
@@ -92,23 +227,86 @@ pub struct Variable {
     pub source: XMLSource,
     pub default_value: Option<String>,
     pub is_const: bool,
+    /// Bounds of lists, whose occurrence is restricted beyond optional or unbounded
+    pub occurrence: Option<Occurrence>,
+    /// Documentation of the element, extended by the generator, e.g. with the occurrence
     pub documentations: Vec<String>,
+    /// The schema documents the element or attribute with xs:documentation
+    pub is_documented: bool,
+    /// The object is freed by its class. Not owned objects, e.g. shared lookups,
+    /// are referenced only and freed by the caller.
+    pub is_owned: bool,
+    /// Namespace of attributes with qualified form, which are read and written namespace-aware
+    pub namespace: Option<AttributeNamespace>,
+}
+
+/// Number of times a repeated element may occur, `max` is `None` for unbounded elements.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize)]
+pub struct Occurrence {
+    pub min: usize,
+    pub max: Option<usize>,
+}
+
+/// Metadata of the schema, which is generated as constants on request
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct SchemaMetadata {
+    /// xs:appinfo of the types, which have any
+    pub app_infos: Vec<AppInfo>,
+    pub notations: Vec<Notation>,
+}
+
+/// Content of the xs:appinfo elements of a type
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct AppInfo {
+    /// Name of the type, which owns the xs:appinfo
+    pub name: String,
+    pub qualified_name: String,
+    /// Content of all xs:appinfo elements of the type, separated by a line break
+    pub content: String,
 }
 
-#[derive(Clone, Debug)]
+/// xs:notation
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Notation {
+    pub name: String,
+    pub public: Option<String>,
+    pub system: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize)]
 pub struct UnionType {
     pub name: String,
     pub qualified_name: String,
     pub variants: Vec<UnionVariant>,
     pub documentations: Vec<String>,
+    /// The schema documents the type with xs:documentation
+    pub is_documented: bool,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct UnionVariant {
     pub name: String,
     pub data_type: DataType,
 }
 
+impl UnionType {
+    /// Returns true if a variant is a reference type. Such a union can't be
+    /// represented by a variant record and is generated as class hierarchy instead.
+    pub fn is_class_hierarchy(&self) -> bool {
+        self.variants
+            .iter()
+            .any(|v| Self::is_class_variant(&v.data_type))
+    }
+
+    /// Returns true if a variant of the data type is a reference type
+    pub(crate) fn is_class_variant(data_type: &DataType) -> bool {
+        matches!(
+            data_type,
+            DataType::Custom(_) | DataType::List(_) | DataType::InlineList(_)
+        )
+    }
+}
+
 impl Dependable<String> for ClassType {
     fn key(&self) -> &String {
         &self.name
@@ -117,7 +315,7 @@ impl Dependable<String> for ClassType {
     fn key_and_deps(&self) -> (&String, Option<Vec<String>>) {
         (
             &self.name,
-            self.super_type.as_ref().cloned().map(|(n, _)| vec![n]),
+            self.super_type.as_ref().map(|(n, _)| vec![n.clone()]),
         )
     }
 }
@@ -127,11 +325,14 @@ impl Dependable<String> for TypeAlias {
         &self.name
     }
 
+    /// Depends on the type it is an alias for or a list of, e.g. another alias or a union type
     fn key_and_deps(&self) -> (&String, Option<Vec<String>>) {
-        match &self.for_type {
-            DataType::Custom(name) => (&self.name, Some(vec![name.clone()])),
-            _ => (&self.name, None),
-        }
+        (
+            &self.name,
+            self.for_type
+                .referenced_type_name()
+                .map(|name| vec![name.clone()]),
+        )
     }
 }
 
@@ -146,11 +347,7 @@ impl Dependable<String> for UnionType {
             Some(
                 self.variants
                     .iter()
-                    .map(|v| match &v.data_type {
-                        DataType::Union(n) => n.clone(),
-                        _ => String::new(),
-                    })
-                    .filter(|d| !d.is_empty())
+                    .filter_map(|v| v.data_type.referenced_type_name().cloned())
                     .collect::<Vec<String>>(),
             ),
         )
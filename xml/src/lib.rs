@@ -1,52 +1,145 @@
 #![allow(clippy::too_many_lines)]
 
-use std::{fs::File, io::BufWriter, path::PathBuf, time::Instant};
+use std::{
+    error::Error,
+    fmt::Display,
+    fs::File,
+    io::BufWriter,
+    path::{Path, PathBuf},
+};
 
+#[cfg(test)]
+mod fixtures;
+pub mod generation_metadata;
 pub mod generator;
 mod parser;
-mod type_registry;
+mod schema_cache;
+pub mod sections;
+pub mod type_registry;
 
 use generator::{
-    code_generator_trait::{CodeGenOptions, CodeGenerator},
-    delphi::code_generator::DelphiCodeGenerator,
+    code_generator_trait::{AnonymousTypeNaming, CodeGenError, CodeGenOptions, CodeGenerator},
+    delphi::code_generator::{build_include_file, include_file_name, DelphiCodeGenerator},
+    documentation_coverage::CoverageFormat,
     internal_representation::InternalRepresentation,
 };
 use parser::{types::ParsedData, xml::XmlParser};
 use type_registry::TypeRegistry;
 
-pub fn generate_xml(source: &[PathBuf], output_path: &PathBuf, options: CodeGenOptions) {
-    let overall_instant = Instant::now();
+pub use parser::types::{ParserError, ParserLimits};
+pub use schema_cache::{enable_schema_cache, schema_cache_stats, SchemaCacheStats};
+
+/// Errors, which abort the generation of a unit from xml schemas
+#[derive(Debug)]
+pub enum GenerationError {
+    /// The output or graph file could not be written
+    Io(String),
+    /// The schema files could not be parsed
+    Parser(ParserError),
+    /// The unit could not be generated from the parsed schema
+    CodeGen(CodeGenError),
+}
 
-    let output_file = match File::create(output_path) {
-        Ok(f) => f,
-        Err(e) => {
-            eprintln!("Could not create output file due to following error: \"{e:?}\"");
-            return;
+impl Display for GenerationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(message) => write!(f, "{message}"),
+            Self::Parser(e) => write!(f, "Failed to parse schema: {e}"),
+            Self::CodeGen(e) => write!(f, "Failed to generate code: {e:?}"),
         }
-    };
+    }
+}
 
-    let mut parser = XmlParser::default();
-    let mut type_registry = TypeRegistry::new();
+impl Error for GenerationError {}
 
-    let data: ParsedData = if source.len() == 1 {
-        match parser.parse_file(source.first().unwrap(), &mut type_registry) {
-            Ok(n) => n,
-            Err(error) => {
-                eprintln!("An error occured: {error}");
-                return;
-            }
-        }
-    } else {
-        match parser.parse_files(source, &mut type_registry) {
-            Ok(n) => n,
-            Err(error) => {
-                eprintln!("An error occured: {error}");
-                return;
-            }
-        }
-    };
+/// Generates a unit from the xml schemas and returns the path of the written unit.
+pub fn generate_xml(
+    source: &[PathBuf],
+    output_path: &PathBuf,
+    options: CodeGenOptions,
+    graph_path: &Option<PathBuf>,
+    name_map_path: &Option<PathBuf>,
+    manifest_path: &Option<PathBuf>,
+) -> Result<Vec<PathBuf>, GenerationError> {
+    generate_xml_with_registry(
+        source,
+        output_path,
+        options,
+        TypeRegistry::new(),
+        graph_path,
+        name_map_path,
+        manifest_path,
+    )
+}
+
+/// Generates a unit from the xml schemas like `generate_xml`, the schemas are parsed into
+/// `type_registry`, e.g. to reference hand-written types registered by
+/// `TypeRegistry::register_external_type` instead of generating them.
+pub fn generate_xml_with_registry(
+    source: &[PathBuf],
+    output_path: &PathBuf,
+    options: CodeGenOptions,
+    mut type_registry: TypeRegistry,
+    graph_path: &Option<PathBuf>,
+    name_map_path: &Option<PathBuf>,
+    manifest_path: &Option<PathBuf>,
+) -> Result<Vec<PathBuf>, GenerationError> {
+    let output_file = File::create(output_path).map_err(|e| {
+        GenerationError::Io(format!(
+            "Could not create output file due to following error: \"{e:?}\""
+        ))
+    })?;
+
+    let (data, internal_representation) =
+        build_representation(source, &options, &mut type_registry)?;
 
-    let internal_representation = InternalRepresentation::build(&data, &type_registry);
+    if let Some(graph_path) = graph_path {
+        let graph = internal_representation.to_dot(&options.output.unit_name);
+
+        std::fs::write(graph_path, graph).map_err(|e| {
+            GenerationError::Io(format!(
+                "Could not write dependency graph due to following error: \"{e:?}\""
+            ))
+        })?;
+    }
+
+    if let Some(name_map_path) = name_map_path {
+        let name_map = internal_representation.to_name_map(&options.naming.type_prefix);
+
+        std::fs::write(name_map_path, name_map).map_err(|e| {
+            GenerationError::Io(format!(
+                "Could not write name map due to following error: \"{e:?}\""
+            ))
+        })?;
+    }
+
+    if let Some(manifest_path) = manifest_path {
+        let manifest = internal_representation.to_manifest(&options.naming.type_prefix);
+
+        std::fs::write(manifest_path, manifest).map_err(|e| {
+            GenerationError::Io(format!(
+                "Could not write manifest due to following error: \"{e:?}\""
+            ))
+        })?;
+    }
+
+    let mut written_paths = vec![output_path.clone()];
+
+    // The include file holds manual code, so an existing one is never overwritten
+    if options.output.generate_include_file {
+        let include_path = output_path.with_file_name(include_file_name(&options));
+
+        if !include_path.exists() {
+            let content = build_include_file(&internal_representation, &options);
+
+            std::fs::write(&include_path, content).map_err(|e| {
+                GenerationError::Io(format!(
+                    "Could not write include file due to following error: \"{e:?}\""
+                ))
+            })?;
+            written_paths.push(include_path);
+        }
+    }
 
     let buffer = BufWriter::new(Box::new(output_file));
     let mut generator = DelphiCodeGenerator::new(
@@ -56,15 +149,180 @@ pub fn generate_xml(source: &[PathBuf], output_path: &PathBuf, options: CodeGenO
         data.documentations,
     );
 
-    match generator.generate() {
-        Ok(()) => {
-            println!(
-                "Completed successfully within {}ms",
-                overall_instant.elapsed().as_millis(),
-            );
-        }
-        Err(e) => {
-            eprintln!("Failed to write output to file due to following error: \"{e:?}\"");
-        }
+    generator.generate().map_err(GenerationError::CodeGen)?;
+
+    Ok(written_paths)
+}
+
+/// Describes the types generated for the xml schemas as JSON manifest without writing
+/// the unit, e.g. to validate the schemas and options.
+pub fn xml_manifest(
+    source: &[PathBuf],
+    options: &CodeGenOptions,
+    mut type_registry: TypeRegistry,
+) -> Result<String, GenerationError> {
+    let (_, internal_representation) = build_representation(source, options, &mut type_registry)?;
+
+    Ok(internal_representation.to_manifest(&options.naming.type_prefix))
+}
+
+/// Reports the types and fields of the xml schemas, which aren't documented by
+/// xs:documentation, in `format` without writing the unit.
+pub fn xml_documentation_coverage(
+    source: &[PathBuf],
+    options: &CodeGenOptions,
+    mut type_registry: TypeRegistry,
+    format: CoverageFormat,
+) -> Result<String, GenerationError> {
+    let (_, internal_representation) = build_representation(source, options, &mut type_registry)?;
+
+    Ok(internal_representation.to_documentation_coverage(&options.naming.type_prefix, format))
+}
+
+/// Parses the schemas and builds the types, which are generated with `options`
+fn build_representation(
+    source: &[PathBuf],
+    options: &CodeGenOptions,
+    type_registry: &mut TypeRegistry,
+) -> Result<(ParsedData, InternalRepresentation), GenerationError> {
+    let mut parser = XmlParser {
+        documentation_language: options.output.documentation_language.clone(),
+        limits: options.parser_limits,
+        ..Default::default()
+    };
+
+    let low_memory = options
+        .max_memory
+        .is_some_and(|max_memory| estimated_memory(source) > max_memory);
+
+    // The schema cache keeps a copy of the parsed schemas
+    let data = match low_memory {
+        true => parser
+            .parse_files(source, type_registry)
+            .map_err(GenerationError::Parser)?,
+        false => parse_schemas(source, &mut parser, type_registry)?,
+    };
+
+    for name in type_registry.unreferenced_anonymous_types(&data.nodes) {
+        eprintln!(
+            "Warning: anonymous type \"{name}\" is not referenced by its element or attribute"
+        );
     }
+
+    type_registry.name_anonymous_types(
+        options.naming.anonymous_type_naming,
+        options.naming.max_type_name_length,
+    );
+    let rename_map = options
+        .naming
+        .rename_map
+        .as_ref()
+        .map(|m| m.resolve(type_registry))
+        .transpose()
+        .map_err(GenerationError::CodeGen)?;
+    if let Some(rename_map) = &rename_map {
+        type_registry.rename_types(rename_map);
+    }
+    if let Some(transliteration) = &options.naming.transliteration {
+        type_registry.transliterate_type_names(transliteration);
+    }
+
+    let mut internal_representation = match low_memory {
+        true => InternalRepresentation::build_low_memory(&data, type_registry),
+        false => InternalRepresentation::build(&data, type_registry),
+    };
+    internal_representation
+        .prune(&options.roots)
+        .map_err(GenerationError::CodeGen)?;
+    if let Some(transliteration) = &options.naming.transliteration {
+        internal_representation.transliterate_identifiers(transliteration);
+    }
+    if let Some(rename_map) = &rename_map {
+        internal_representation
+            .rename_members(rename_map)
+            .map_err(GenerationError::CodeGen)?;
+    }
+    internal_representation
+        .disown_fields(&options.output.unowned_fields)
+        .map_err(GenerationError::CodeGen)?;
+    internal_representation.run_passes(&options.passes, &options.custom_passes);
+
+    Ok((data, internal_representation))
+}
+
+/// Replaces the built-in templates `models.pas` and `macros.pas` for all following generations
+/// of the process. A template, which is not given, keeps its built-in version.
+/// An empty list restores the built-in templates.
+///
+/// The templates are compiled once and shared by all generations instead of being
+/// compiled again for every unit.
+pub fn override_templates(templates: &[(&str, &str)]) -> Result<(), GenerationError> {
+    generator::delphi::code_generator::override_templates(templates)
+        .map_err(GenerationError::CodeGen)
+}
+
+/// Writes an example document for the root element `root` of the xml schemas.
+pub fn generate_sample(
+    source: &[PathBuf],
+    root: &str,
+    output_path: &PathBuf,
+) -> Result<(), GenerationError> {
+    let mut parser = XmlParser::default();
+    let mut type_registry = TypeRegistry::new();
+
+    let data = parse_schemas(source, &mut parser, &mut type_registry)?;
+
+    type_registry.name_anonymous_types(AnonymousTypeNaming::default(), None);
+
+    let sample = InternalRepresentation::build(&data, &type_registry)
+        .to_sample(root)
+        .map_err(GenerationError::CodeGen)?;
+
+    std::fs::write(output_path, sample).map_err(|e| {
+        GenerationError::Io(format!(
+            "Could not write sample document due to following error: \"{e:?}\""
+        ))
+    })
+}
+
+/// Writes the xml schema `source` with all included and imported schemas as a single schema,
+/// whose group references are replaced by the content of the groups.
+pub fn flatten_xml_schema(source: &Path, output_path: &Path) -> Result<(), GenerationError> {
+    let schema = parser::flatten::flatten_schema(source).map_err(GenerationError::Parser)?;
+
+    std::fs::write(output_path, schema).map_err(|e| {
+        GenerationError::Io(format!(
+            "Could not write flattened schema due to following error: \"{e:?}\""
+        ))
+    })
+}
+
+/// Rough estimate of the memory needed per byte of the schema files to parse them and
+/// build their internal representation
+const MEMORY_PER_SCHEMA_BYTE: u64 = 100;
+
+fn estimated_memory(source: &[PathBuf]) -> u64 {
+    source
+        .iter()
+        .filter_map(|p| std::fs::metadata(p).ok())
+        .map(|m| m.len())
+        .sum::<u64>()
+        .saturating_mul(MEMORY_PER_SCHEMA_BYTE)
+}
+
+fn parse_schemas(
+    source: &[PathBuf],
+    parser: &mut XmlParser,
+    type_registry: &mut TypeRegistry,
+) -> Result<ParsedData, GenerationError> {
+    let documentation_language = parser.documentation_language.clone();
+
+    schema_cache::parse_cached(source, &documentation_language, type_registry, |registry| {
+        if source.len() == 1 {
+            parser.parse_file(source.first().unwrap(), registry)
+        } else {
+            parser.parse_files(source, registry)
+        }
+    })
+    .map_err(GenerationError::Parser)
 }
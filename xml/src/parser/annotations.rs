@@ -1,26 +1,98 @@
-use std::{borrow::Cow, fs::File, io::BufReader};
+use std::borrow::Cow;
 
-use quick_xml::{events::Event, Reader};
+use quick_xml::{
+    events::{BytesStart, Event},
+    Writer,
+};
 
-use super::types::ParserError;
+use super::{
+    helper::XmlParserHelper,
+    types::{GenerationOverrides, NodeType, ParserError, SchemaReader},
+};
 
 /// Parser for xs:annotation elements
 pub struct AnnotationsParser;
 
+/// Content of an xs:annotation element
+#[derive(Debug, Default)]
+pub struct Annotation {
+    pub documentations: Vec<String>,
+    /// Whether an xs:documentation with text is present in any language. The documentations
+    /// also hold the text of xs:appinfo, so they don't tell it.
+    pub has_documentation: bool,
+    pub overrides: GenerationOverrides,
+    /// Content of the xs:appinfo elements as written in the schema
+    pub appinfo: Vec<String>,
+}
+
 impl AnnotationsParser {
     /// Parses the content of an xs:annotation element
     ///
-    /// Has support for xs:appinfo and xs:documentation elements
-    pub fn parse(reader: &mut Reader<BufReader<File>>) -> Result<Vec<String>, ParserError> {
-        let mut values = Vec::new();
+    /// Has support for xs:appinfo and xs:documentation elements.
+    /// Of the xs:documentation elements with a `xml:lang` attribute only those in `language` are kept,
+    /// or those in the first language of the annotation, when there are none in `language`.
+    pub fn parse(
+        reader: &mut SchemaReader,
+        language: Option<&str>,
+    ) -> Result<Vec<String>, ParserError> {
+        Self::parse_with_overrides(reader, language).map(|(values, _)| values)
+    }
+
+    /// Parses the content of an xs:annotation element including the
+    /// generation overrides of an `override` element inside of xs:appinfo
+    pub fn parse_with_overrides(
+        reader: &mut SchemaReader,
+        language: Option<&str>,
+    ) -> Result<(Vec<String>, GenerationOverrides), ParserError> {
+        Self::parse_annotation(reader, language).map(|a| (a.documentations, a.overrides))
+    }
+
+    /// Parses the content of an xs:annotation element including the generation overrides
+    /// and the content of the xs:appinfo elements
+    pub fn parse_annotation(
+        reader: &mut SchemaReader,
+        language: Option<&str>,
+    ) -> Result<Annotation, ParserError> {
+        let mut values = Vec::<(Option<String>, String)>::new();
+        let mut current_language = None;
+        let mut overrides = GenerationOverrides::default();
+        let mut appinfo = Vec::new();
+        let mut appinfo_writer = Writer::new(Vec::new());
         let mut buf = Vec::new();
         let mut current_value = String::new();
         let mut should_read_text = false;
+        let mut is_in_appinfo = false;
+        let mut has_documentation = false;
 
         loop {
-            match reader.read_event_into(&mut buf) {
+            let event = reader.read_event_into(&mut buf);
+
+            if is_in_appinfo {
+                match &event {
+                    Ok(Event::End(e)) if e.name().as_ref() == b"xs:appinfo" => (),
+                    Ok(e) => appinfo_writer
+                        .write_event(e.borrow())
+                        .map_err(|_| ParserError::UnexpectedError)?,
+                    Err(_) => (),
+                }
+            }
+
+            match event {
+                Ok(Event::Start(s)) | Ok(Event::Empty(s))
+                    if is_in_appinfo && s.local_name().as_ref() == b"override" =>
+                {
+                    overrides = Self::parse_overrides(&s)?;
+                }
                 Ok(Event::Start(s)) => match s.name().as_ref() {
-                    b"xs:appinfo" | b"xs:documentation" => should_read_text = true,
+                    b"xs:appinfo" => {
+                        should_read_text = true;
+                        is_in_appinfo = true;
+                    }
+                    b"xs:documentation" => {
+                        should_read_text = true;
+                        current_language =
+                            XmlParserHelper::get_attribute_value(&s, "xml:lang").ok();
+                    }
                     _ => (),
                 },
                 Ok(Event::Text(t)) if should_read_text => {
@@ -37,10 +109,24 @@ impl AnnotationsParser {
                 }
                 Ok(Event::End(e)) => match e.name().as_ref() {
                     b"xs:appinfo" | b"xs:documentation" => {
+                        if is_in_appinfo {
+                            let content = std::mem::take(appinfo_writer.get_mut());
+                            let content = String::from_utf8(content)
+                                .map_err(|_| ParserError::UnexpectedError)?;
+
+                            if !content.trim().is_empty() {
+                                appinfo.push(dedent(&content));
+                            }
+                        }
+
+                        has_documentation |= !is_in_appinfo && !current_value.trim().is_empty();
                         should_read_text = false;
+                        is_in_appinfo = false;
 
-                        if !current_value.is_empty() {
-                            values.push(current_value);
+                        if current_value.trim().is_empty() {
+                            current_value.clear();
+                        } else {
+                            values.push((current_language.take(), current_value));
                             current_value = String::new();
                         }
                     }
@@ -57,6 +143,180 @@ impl AnnotationsParser {
             buf.clear();
         }
 
-        Ok(values)
+        Ok(Annotation {
+            documentations: Self::select_language(values, language),
+            has_documentation,
+            overrides,
+            appinfo,
+        })
+    }
+
+    /// Keeps the values without language and those in `language`,
+    /// falling back to the first language of the values.
+    fn select_language(
+        values: Vec<(Option<String>, String)>,
+        language: Option<&str>,
+    ) -> Vec<String> {
+        let selected = language
+            .and_then(|language| {
+                values
+                    .iter()
+                    .filter_map(|(l, _)| l.as_deref())
+                    .find(|l| l.eq_ignore_ascii_case(language))
+            })
+            .or_else(|| values.iter().find_map(|(l, _)| l.as_deref()))
+            .map(str::to_owned);
+
+        values
+            .into_iter()
+            .filter(|(l, _)| l.is_none() || l.as_deref() == selected.as_deref())
+            .map(|(_, value)| value)
+            .collect()
+    }
+
+    fn parse_overrides(node: &BytesStart) -> Result<GenerationOverrides, ParserError> {
+        let optional_value = |name: &str| match XmlParserHelper::get_attribute_value(node, name) {
+            Ok(v) => Ok(Some(v)),
+            Err(ParserError::MissingAttribute(_)) => Ok(None),
+            Err(e) => Err(e),
+        };
+
+        let data_type = match optional_value("type")? {
+            Some(t) => match XmlParserHelper::base_type_str_to_node_type(&t) {
+                Some(NodeType::Standard(b)) => Some(b),
+                _ => {
+                    return Err(ParserError::MalformedAttribute(
+                        String::from("type"),
+                        Some(format!("\"{t}\" is not a built-in type")),
+                    ))
+                }
+            },
+            None => None,
+        };
+
+        Ok(GenerationOverrides {
+            rename: optional_value("rename")?,
+            skip: optional_value("skip")?.is_some_and(|v| v == "true"),
+            optional: optional_value("optional")?.is_some_and(|v| v == "true"),
+            data_type,
+            owned: optional_value("owned")?.map(|v| v == "true"),
+            flags: optional_value("flags")?.is_some_and(|v| v == "true"),
+        })
+    }
+}
+
+/// Removes the leading and trailing empty lines and the indentation common to all lines
+fn dedent(content: &str) -> String {
+    let lines = content
+        .lines()
+        .skip_while(|l| l.trim().is_empty())
+        .collect::<Vec<_>>();
+    let end = lines
+        .iter()
+        .rposition(|l| !l.trim().is_empty())
+        .map_or(0, |i| i + 1);
+    let lines = &lines[..end];
+
+    let indentation = lines
+        .iter()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| l.len() - l.trim_start().len())
+        .min()
+        .unwrap_or_default();
+
+    lines
+        .iter()
+        .map(|l| l.get(indentation..).unwrap_or_default().trim_end())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use pretty_assertions::assert_eq;
+    use quick_xml::Reader;
+
+    use super::*;
+    use crate::parser::types::NodeBaseType;
+
+    #[test]
+    fn reads_overrides_from_appinfo() {
+        let xml = r#"<xs:annotation>
+            <xs:appinfo>
+                <genphi:override xmlns:genphi="urn:genphi" rename="Id" type="xs:int" owned="false"/>
+            </xs:appinfo>
+            <xs:documentation>Identifier</xs:documentation>
+        </xs:annotation>"#;
+        let mut reader = Reader::from_reader(Cursor::new(xml.as_bytes().to_vec()));
+
+        let (values, overrides) =
+            AnnotationsParser::parse_with_overrides(&mut reader, None).unwrap();
+
+        assert_eq!(values, vec![String::from("Identifier")]);
+        assert_eq!(overrides.rename.as_deref(), Some("Id"));
+        assert!(matches!(overrides.data_type, Some(NodeBaseType::Integer)));
+        assert_eq!(overrides.owned, Some(false));
+        assert!(!overrides.skip && !overrides.optional);
+    }
+
+    #[test]
+    fn keeps_the_content_of_appinfo() {
+        let xml = r#"<xs:annotation>
+            <xs:appinfo>
+                <route path="/orders" method="POST"/>
+                <owner>sales &amp; support</owner>
+            </xs:appinfo>
+            <xs:appinfo>   </xs:appinfo>
+            <xs:documentation>Order</xs:documentation>
+        </xs:annotation>"#;
+        let mut reader = Reader::from_reader(Cursor::new(xml.as_bytes().to_vec()));
+
+        let annotation = AnnotationsParser::parse_annotation(&mut reader, None).unwrap();
+
+        assert_eq!(
+            annotation.appinfo,
+            vec![String::from(
+                "<route path=\"/orders\" method=\"POST\"/>\n<owner>sales &amp; support</owner>"
+            )]
+        );
+        assert_eq!(annotation.documentations.last().unwrap(), "Order");
+    }
+
+    #[test]
+    fn tells_whether_documentation_is_present() {
+        let parse = |xml: &str| {
+            let mut reader = Reader::from_reader(Cursor::new(xml.as_bytes().to_vec()));
+            AnnotationsParser::parse_annotation(&mut reader, Some("en")).unwrap()
+        };
+
+        let appinfo_only = parse(
+            "<xs:annotation><xs:appinfo>Internal</xs:appinfo><xs:documentation> </xs:documentation></xs:annotation>",
+        );
+        let other_language = parse(
+            r#"<xs:annotation><xs:documentation xml:lang="de">Bestellung</xs:documentation></xs:annotation>"#,
+        );
+
+        assert_eq!(appinfo_only.documentations, vec!["Internal"]);
+        assert!(!appinfo_only.has_documentation);
+        assert!(other_language.has_documentation);
+    }
+
+    #[test]
+    fn keeps_documentation_of_the_selected_language() {
+        let xml = r#"<xs:annotation>
+            <xs:documentation xml:lang="de">Bestellung</xs:documentation>
+            <xs:documentation xml:lang="en">Order</xs:documentation>
+            <xs:documentation>Since 2.0</xs:documentation>
+        </xs:annotation>"#;
+        let parse = |language| {
+            let mut reader = Reader::from_reader(Cursor::new(xml.as_bytes().to_vec()));
+            AnnotationsParser::parse(&mut reader, language).unwrap()
+        };
+
+        assert_eq!(parse(Some("EN")), vec!["Order", "Since 2.0"]);
+        assert_eq!(parse(Some("fr")), vec!["Bestellung", "Since 2.0"]);
+        assert_eq!(parse(None), vec!["Bestellung", "Since 2.0"]);
     }
 }
@@ -0,0 +1,58 @@
+use quick_xml::events::BytesStart;
+
+use super::{
+    helper::XmlParserHelper,
+    types::{ParserError, SchemaReader},
+};
+
+/// Parser for the XSD 1.1 constructs xs:assert, xs:assertion and xs:alternative
+pub struct AssertionParser;
+
+impl AssertionParser {
+    /// Returns the test-attribute of a xs:assert or xs:assertion and skips its annotations.
+    /// `is_empty` tells whether the element was read as empty element.
+    pub fn parse(
+        reader: &mut SchemaReader,
+        start: &BytesStart,
+        is_empty: bool,
+    ) -> Result<String, ParserError> {
+        let test = XmlParserHelper::get_attribute_value(start, "test")?;
+
+        if !is_empty {
+            Self::skip(reader, start)?;
+        }
+
+        Ok(test)
+    }
+
+    /// Skips a xs:alternative of the element `element`. The conditional type assignment is not
+    /// supported, the element always has its declared type.
+    pub fn skip_alternative(
+        reader: &mut SchemaReader,
+        start: &BytesStart,
+        is_empty: bool,
+        element: &str,
+    ) -> Result<(), ParserError> {
+        match XmlParserHelper::get_attribute_value(start, "test") {
+            Ok(test) => eprintln!(
+                "Warning: conditional type assignment \"{test}\" of element \"{element}\" is ignored, the declared type is used"
+            ),
+            Err(_) => eprintln!(
+                "Warning: default type alternative of element \"{element}\" is ignored, the declared type is used"
+            ),
+        }
+
+        match is_empty {
+            true => Ok(()),
+            false => Self::skip(reader, start),
+        }
+    }
+
+    /// Skips the content, as nested types or annotations don't belong to the enclosing element
+    fn skip(reader: &mut SchemaReader, start: &BytesStart) -> Result<(), ParserError> {
+        reader
+            .read_to_end_into(start.name(), &mut Vec::new())
+            .map(|_| ())
+            .map_err(|_| ParserError::UnexpectedError)
+    }
+}
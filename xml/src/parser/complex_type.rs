@@ -1,18 +1,19 @@
-use std::{fs::File, io::BufReader};
-
 use crate::parser::node::NodeParser;
-use quick_xml::{events::Event, Reader};
+use quick_xml::events::{BytesStart, Event};
 
 use crate::type_registry::TypeRegistry;
 
 use super::{
     annotations::AnnotationsParser,
+    assertion::AssertionParser,
     custom_attribute::CustomAttributeParser,
     helper::XmlParserHelper,
+    identity_constraint::IdentityConstraintParser,
     simple_type::SimpleTypeParser,
     types::{
-        BaseAttributes, ComplexType, CustomTypeDefinition, Node, NodeType, OrderIndicator,
-        ParserError, SingleNode,
+        BaseAttributes, ComplexType, CustomTypeDefinition, GenerationOverrides, Node, NodeType,
+        OrderIndicator, ParserError, ProcessContents, SchemaReader, SingleNode, Wildcard,
+        WildcardNamespaces,
     },
     xml::XmlParser,
 };
@@ -31,7 +32,7 @@ impl ComplexTypeParser {
     /// * `name` - Name of the complex type
     /// * `qualified_parent` - Qualified name of the parent type. Important for nested types
     pub fn parse(
-        reader: &mut Reader<BufReader<File>>,
+        reader: &mut SchemaReader,
         registry: &mut TypeRegistry,
         xml_parser: &XmlParser,
         name: String,
@@ -44,8 +45,14 @@ impl ComplexTypeParser {
         let mut extends_existing_type = false;
         let mut base_type = None::<String>;
         let mut annotations = Vec::new();
+        let mut is_documented = false;
+        let mut appinfo = Vec::new();
+        let mut overrides = GenerationOverrides::default();
         let mut current_element = None::<(String, BaseAttributes)>;
-        let mut order = OrderIndicator::Sequence;
+        let mut is_element_documented = false;
+        let mut order = OrderIndicator::Sequence(BaseAttributes::default());
+        let mut wildcard = None::<Wildcard>;
+        let mut assertions = Vec::new();
 
         let qualified_name = qualified_parent.map_or_else(
             || xml_parser.as_qualified_name(name.as_str()),
@@ -74,7 +81,10 @@ impl ComplexTypeParser {
                                     let base_attributes = XmlParserHelper::get_base_attributes(&s)?;
                                     order = OrderIndicator::Choice(base_attributes);
                                 }
-                                b"xs:sequence" => order = OrderIndicator::Sequence,
+                                b"xs:sequence" => {
+                                    let base_attributes = XmlParserHelper::get_base_attributes(&s)?;
+                                    order = OrderIndicator::Sequence(base_attributes);
+                                }
                                 _ => (),
                             }
                         }
@@ -95,6 +105,7 @@ impl ComplexTypeParser {
 
                                 let node = NodeParser::parse_element_with_type_node(
                                     reader,
+                                    xml_parser,
                                     node_type,
                                     name,
                                     base_attributes,
@@ -104,6 +115,7 @@ impl ComplexTypeParser {
                             }
                             Err(ParserError::MissingAttribute(_)) => {
                                 current_element = Some((name, base_attributes));
+                                is_element_documented = false;
                             }
                             Err(e) => return Err(e),
                         };
@@ -137,7 +149,7 @@ impl ComplexTypeParser {
                                 reader,
                                 registry,
                                 xml_parser,
-                                name.clone(),
+                                registry.anonymous_type_name(&qualified_name, name),
                                 Some(qualified_name.clone()),
                             )?;
 
@@ -145,12 +157,13 @@ impl ComplexTypeParser {
                             let c_type = CustomTypeDefinition::Complex(c_type);
                             registry.register_type(c_type);
 
-                            let node = SingleNode::new(
+                            let mut node = SingleNode::new(
                                 node_type,
                                 name.clone(),
                                 (*base_attributes).clone(),
                                 None,
                             );
+                            node.is_documented = is_element_documented;
                             children.push(Node::Single(node));
                         } else {
                             let name = XmlParserHelper::get_attribute_value(&s, "name")
@@ -175,19 +188,20 @@ impl ComplexTypeParser {
                                 reader,
                                 registry,
                                 xml_parser,
-                                name.clone(),
+                                registry.anonymous_type_name(&qualified_name, name),
                                 Some(qualified_name.clone()),
                             )?;
 
                             let node_type = NodeType::Custom(s_type.qualified_name.clone());
                             registry.register_type(s_type.into());
 
-                            let node = SingleNode::new(
+                            let mut node = SingleNode::new(
                                 node_type,
                                 name.clone(),
                                 (*base_attributes).clone(),
                                 None,
                             );
+                            node.is_documented = is_element_documented;
                             children.push(Node::Single(node));
                         } else {
                             let name = XmlParserHelper::get_attribute_value(&s, "name")
@@ -206,8 +220,22 @@ impl ComplexTypeParser {
                         }
                     }
                     b"xs:annotation" if current_element.is_none() => {
-                        let mut values = AnnotationsParser::parse(reader)?;
-                        annotations.append(&mut values);
+                        let mut annotation = AnnotationsParser::parse_annotation(
+                            reader,
+                            xml_parser.documentation_language.as_deref(),
+                        )?;
+                        annotations.append(&mut annotation.documentations);
+                        is_documented |= annotation.has_documentation;
+                        appinfo.append(&mut annotation.appinfo);
+                        overrides = annotation.overrides;
+                    }
+                    // Only the presence of the documentation of an element with an anonymous type is kept
+                    b"xs:annotation" => {
+                        let annotation = AnnotationsParser::parse_annotation(
+                            reader,
+                            xml_parser.documentation_language.as_deref(),
+                        )?;
+                        is_element_documented |= annotation.has_documentation;
                     }
                     b"xs:attribute" => {
                         let attr = CustomAttributeParser::parse(
@@ -221,6 +249,30 @@ impl ComplexTypeParser {
 
                         custom_attributes.push(attr);
                     }
+                    b"xs:any" => {
+                        wildcard = wildcard.or(Some(Self::parse_wildcard(&s, xml_parser)?));
+
+                        // Annotations of the wildcard don't belong to the complex type
+                        reader
+                            .read_to_end_into(s.name(), &mut Vec::new())
+                            .map_err(|_| ParserError::UnexpectedError)?;
+                    }
+                    b"xs:assert" => assertions.push(AssertionParser::parse(reader, &s, false)?),
+                    b"xs:alternative" => {
+                        let element = current_element.as_ref().map_or("", |(name, _)| name);
+                        AssertionParser::skip_alternative(reader, &s, false, element)?;
+                    }
+                    name if IdentityConstraintParser::is_identity_constraint(name) => {
+                        let constraint = IdentityConstraintParser::parse(reader, &s)?;
+
+                        if let Some((element, _)) = &current_element {
+                            IdentityConstraintParser::add_to_element(
+                                &mut children,
+                                element,
+                                constraint,
+                            );
+                        }
+                    }
                     _ => (),
                 },
                 Ok(Event::Empty(e)) => match e.name().as_ref() {
@@ -253,6 +305,14 @@ impl ComplexTypeParser {
 
                         custom_attributes.push(attr);
                     }
+                    b"xs:any" => {
+                        wildcard = wildcard.or(Some(Self::parse_wildcard(&e, xml_parser)?));
+                    }
+                    b"xs:assert" => assertions.push(AssertionParser::parse(reader, &e, true)?),
+                    b"xs:alternative" => {
+                        let element = current_element.as_ref().map_or("", |(name, _)| name);
+                        AssertionParser::skip_alternative(reader, &e, true, element)?;
+                    }
                     _ => (),
                 },
                 Ok(Event::End(e)) => match e.name().as_ref() {
@@ -273,10 +333,55 @@ impl ComplexTypeParser {
             name,
             qualified_name,
             base_type,
+            is_abstract: false,
             children,
             custom_attributes,
             order,
             documentations: annotations,
+            is_documented,
+            overrides,
+            appinfo,
+            wildcard,
+            assertions,
+        })
+    }
+
+    /// Parses the namespace- and processContents-attributes of a xs:any element
+    fn parse_wildcard(node: &BytesStart, xml_parser: &XmlParser) -> Result<Wildcard, ParserError> {
+        let target_namespace = xml_parser.current_namespace.clone().unwrap_or_default();
+        let namespace = XmlParserHelper::get_attribute_value(node, "namespace")
+            .unwrap_or_else(|_| String::from("##any"));
+
+        let namespaces = match namespace.trim() {
+            "##any" => WildcardNamespaces::Any,
+            "##other" => WildcardNamespaces::Other(target_namespace),
+            list => WildcardNamespaces::List(
+                list.split_whitespace()
+                    .map(|n| match n {
+                        "##targetNamespace" => target_namespace.clone(),
+                        "##local" => String::new(),
+                        _ => n.to_owned(),
+                    })
+                    .collect(),
+            ),
+        };
+
+        let process_contents =
+            match XmlParserHelper::get_attribute_value(node, "processContents").as_deref() {
+                Ok("strict") | Err(_) => ProcessContents::Strict,
+                Ok("lax") => ProcessContents::Lax,
+                Ok("skip") => ProcessContents::Skip,
+                Ok(value) => {
+                    return Err(ParserError::MalformedAttribute(
+                        String::from("processContents"),
+                        Some(format!("\"{value}\" is not one of strict, lax or skip")),
+                    ))
+                }
+            };
+
+        Ok(Wildcard {
+            namespaces,
+            process_contents,
         })
     }
 }
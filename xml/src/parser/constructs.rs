@@ -0,0 +1,92 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use super::{
+    flatten::{load, SchemaElement, SchemaFile, XSD_NAMESPACE},
+    types::ParserError,
+};
+
+/// Elements of the XSD namespace, which are read by the parser. Other elements, e.g.
+/// `xs:anyAttribute` or facets like `xs:maxLength`, are skipped together with their content.
+const SUPPORTED_CONSTRUCTS: [&str; 33] = [
+    "all",
+    "alternative",
+    "annotation",
+    "any",
+    "appinfo",
+    "assert",
+    "assertion",
+    "attribute",
+    "choice",
+    "complexContent",
+    "complexType",
+    "documentation",
+    "element",
+    "enumeration",
+    "extension",
+    "field",
+    "fractionDigits",
+    "key",
+    "keyref",
+    "list",
+    "notation",
+    "override",
+    "pattern",
+    "redefine",
+    "restriction",
+    "schema",
+    "selector",
+    "sequence",
+    "simpleContent",
+    "simpleType",
+    "totalDigits",
+    "union",
+    "unique",
+];
+
+/// Whether the parser reads the element of the XSD namespace with the local name `name`
+pub(crate) fn is_supported_construct(name: &str) -> bool {
+    SUPPORTED_CONSTRUCTS.contains(&name)
+}
+
+/// Number of the elements of the XSD namespace by their local name in the schemas and all
+/// schemas they include or import, e.g. `complexType` or `any`.
+///
+/// Each file is counted once, the content of `xs:appinfo` belongs to other applications
+/// and is not counted.
+pub(crate) fn count_constructs(source: &[PathBuf]) -> Result<BTreeMap<String, usize>, ParserError> {
+    let mut files = Vec::new();
+
+    for path in source {
+        load(path, &mut files)?;
+    }
+
+    let mut constructs = BTreeMap::new();
+
+    for file in &files {
+        count_element(file, &file.root, &mut constructs);
+    }
+
+    Ok(constructs)
+}
+
+fn count_element(
+    file: &SchemaFile,
+    element: &SchemaElement,
+    constructs: &mut BTreeMap<String, usize>,
+) {
+    if file.namespace_of(&element.name).map(String::as_str) != Some(XSD_NAMESPACE) {
+        return;
+    }
+
+    *constructs
+        .entry(element.local_name().to_owned())
+        .or_default() += 1;
+
+    if element.local_name() == "appinfo" {
+        return;
+    }
+
+    for child in element.elements() {
+        count_element(file, child, constructs);
+    }
+}
@@ -1,9 +1,4 @@
-use std::{fs::File, io::BufReader};
-
-use quick_xml::{
-    events::{BytesStart, Event},
-    Reader,
-};
+use quick_xml::events::{BytesStart, Event};
 
 use crate::type_registry::TypeRegistry;
 
@@ -11,7 +6,7 @@ use super::{
     annotations::AnnotationsParser,
     helper::XmlParserHelper,
     simple_type::SimpleTypeParser,
-    types::{CustomAttribute, NodeType, ParserError},
+    types::{CustomAttribute, GenerationOverrides, NodeType, ParserError, SchemaReader},
     xml::XmlParser,
 };
 
@@ -19,7 +14,7 @@ pub struct CustomAttributeParser;
 
 impl CustomAttributeParser {
     pub fn parse(
-        reader: &mut Reader<BufReader<File>>,
+        reader: &mut SchemaReader,
         registry: &mut TypeRegistry,
         xml_parser: &XmlParser,
         qualified_parent: Option<String>,
@@ -27,6 +22,8 @@ impl CustomAttributeParser {
         has_content: bool,
     ) -> Result<CustomAttribute, ParserError> {
         let mut documentations = Vec::new();
+        let mut is_documented = false;
+        let mut overrides = GenerationOverrides::default();
 
         let name = XmlParserHelper::get_attribute_value(start, "name")?;
 
@@ -53,6 +50,12 @@ impl CustomAttributeParser {
             Err(e) => return Err(e),
         };
 
+        let form = match XmlParserHelper::get_attribute_value(start, "form") {
+            Ok(v) => Some(v),
+            Err(ParserError::MissingAttribute(_)) => None,
+            Err(e) => return Err(e),
+        };
+
         let mut node_type = None::<NodeType>;
 
         if has_content {
@@ -62,21 +65,29 @@ impl CustomAttributeParser {
                 match reader.read_event_into(&mut buf) {
                     Ok(Event::Start(e)) => match e.name().as_ref() {
                         b"xs:annotation" => {
-                            let mut values = AnnotationsParser::parse(reader)?;
-                            documentations.append(&mut values);
+                            let mut annotation = AnnotationsParser::parse_annotation(
+                                reader,
+                                xml_parser.documentation_language.as_deref(),
+                            )?;
+                            documentations.append(&mut annotation.documentations);
+                            is_documented |= annotation.has_documentation;
+                            overrides = annotation.overrides;
                         }
                         b"xs:simpleType" => {
+                            let type_name = qualified_parent.as_ref().map_or_else(
+                                || name.clone(),
+                                |p| registry.anonymous_type_name(p, &name),
+                            );
                             let s_type = SimpleTypeParser::parse(
                                 reader,
                                 registry,
                                 xml_parser,
-                                name.clone(),
+                                type_name,
                                 qualified_parent.clone(),
                             )?;
 
+                            node_type = Some(NodeType::Custom(s_type.qualified_name.clone()));
                             registry.register_type(s_type.into());
-
-                            node_type = Some(NodeType::Custom(qualified_name.clone()));
                         }
                         _ => (),
                     },
@@ -105,10 +116,13 @@ impl CustomAttributeParser {
             name,
             qualified_name,
             documentations,
+            is_documented,
+            overrides,
             base_type: node_type,
             default_value,
             fixed_value,
             required,
+            namespace: xml_parser.attribute_namespace(form.as_deref()),
         })
     }
 }
@@ -0,0 +1,86 @@
+use encoding_rs::{Encoding, UTF_8};
+use quick_xml::{events::Event, Reader};
+
+use super::types::ParserError;
+
+/// Converts the content of a schema file to UTF-8.
+///
+/// The encoding is detected from the byte order mark. Without byte order mark the
+/// `encoding` of the XML declaration is used and UTF-8 is assumed, if neither is present.
+///
+/// Returns the converted content and the name of the detected encoding.
+pub fn decode_schema(bytes: &[u8]) -> Result<(String, &'static str), ParserError> {
+    let (encoding, content) = match Encoding::for_bom(bytes) {
+        Some((encoding, bom_length)) => (encoding, &bytes[bom_length..]),
+        None => (declared_encoding(bytes)?.unwrap_or(UTF_8), bytes),
+    };
+
+    let decoded = encoding
+        .decode_without_bom_handling_and_without_replacement(content)
+        .ok_or(ParserError::MalformedEncoding(encoding.name()))?;
+
+    Ok((decoded.into_owned(), encoding.name()))
+}
+
+/// Reads the `encoding` of the XML declaration, if the file starts with one.
+fn declared_encoding(bytes: &[u8]) -> Result<Option<&'static Encoding>, ParserError> {
+    let mut reader = Reader::from_reader(bytes);
+    let mut buf = Vec::new();
+
+    let Ok(Event::Decl(declaration)) = reader.read_event_into(&mut buf) else {
+        return Ok(None);
+    };
+
+    let label = match declaration.encoding() {
+        Some(Ok(label)) => label,
+        _ => return Ok(None),
+    };
+
+    Encoding::for_label(&label)
+        .map(Some)
+        .ok_or_else(|| ParserError::UnsupportedEncoding(String::from_utf8_lossy(&label).into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn decodes_encoding_of_xml_declaration() {
+        let bytes = b"<?xml version=\"1.0\" encoding=\"ISO-8859-1\"?><a>Gr\xF6\xDFe</a>";
+
+        let (content, encoding) = decode_schema(bytes).unwrap();
+
+        assert_eq!(encoding, "windows-1252");
+        assert!(content.ends_with("<a>Gr\u{f6}\u{df}e</a>"));
+    }
+
+    #[test]
+    fn byte_order_mark_takes_precedence() {
+        let bytes = [
+            b"\xFF\xFE".as_slice(),
+            &"<a>\u{f6}</a>"
+                .encode_utf16()
+                .flat_map(u16::to_le_bytes)
+                .collect::<Vec<u8>>(),
+        ]
+        .concat();
+
+        assert_eq!(
+            decode_schema(&bytes).unwrap(),
+            (String::from("<a>\u{f6}</a>"), "UTF-16LE")
+        );
+    }
+
+    #[test]
+    fn reports_content_not_matching_the_encoding() {
+        let bytes = b"<?xml version=\"1.0\" encoding=\"UTF-8\"?><a>\xF6</a>";
+
+        assert!(matches!(
+            decode_schema(bytes),
+            Err(ParserError::MalformedEncoding("UTF-8"))
+        ));
+    }
+}
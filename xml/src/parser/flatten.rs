@@ -0,0 +1,728 @@
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    path::{Path, PathBuf},
+};
+
+use quick_xml::{
+    events::{BytesCData, BytesDecl, BytesEnd, BytesStart, BytesText, Event},
+    Reader, Writer,
+};
+
+use super::{encoding::decode_schema, types::ParserError};
+
+pub(super) const XSD_NAMESPACE: &str = "http://www.w3.org/2001/XMLSchema";
+
+/// Attributes of schema elements, whose values are qualified names
+const QNAME_ATTRIBUTES: [&str; 6] = [
+    "type",
+    "base",
+    "ref",
+    "itemType",
+    "refer",
+    "substitutionGroup",
+];
+
+/// Merges the schema `source` with all schemas it includes or imports into a single,
+/// self-contained schema and returns its content.
+///
+/// The components of included and imported schemas are inserted in place of the
+/// `xs:include` or `xs:import`. Components of imported namespaces are moved into the target
+/// namespace of `source`, as a single schema has only one target namespace. References to
+/// named `xs:group` and `xs:attributeGroup` definitions are replaced by their content and the
+/// definitions are removed. `xs:redefine` and `xs:override` are kept unchanged.
+pub(crate) fn flatten_schema(source: &Path) -> Result<String, ParserError> {
+    let mut files = Vec::new();
+    load(source, &mut files)?;
+
+    let inlined_namespaces = files
+        .iter()
+        .map(|f| f.target_namespace.clone().unwrap_or_default())
+        .collect::<HashSet<_>>();
+
+    let main = &files[0];
+    let mut root = main.root.clone();
+    let mut prefixes = Prefixes {
+        xsd: main
+            .prefix_of(XSD_NAMESPACE)
+            .unwrap_or_else(|| String::from("xs")),
+        target: match &main.target_namespace {
+            Some(namespace) => main
+                .prefix_of(namespace)
+                .unwrap_or_else(|| String::from("tns")),
+            None => String::new(),
+        },
+        inlined_namespaces,
+        declarations: main.namespaces.clone(),
+    };
+
+    if let Some(namespace) = &main.target_namespace {
+        prefixes.declare(&prefixes.target.clone(), namespace);
+    }
+
+    let moved_namespaces = files
+        .iter()
+        .filter_map(|f| f.target_namespace.as_ref())
+        .filter(|n| Some(*n) != main.target_namespace.as_ref())
+        .collect::<BTreeSet<_>>();
+
+    for namespace in moved_namespaces {
+        eprintln!(
+            "Warning: components of namespace \"{namespace}\" are moved into the target namespace"
+        );
+    }
+
+    let mut emitted = HashSet::from([main.path.clone()]);
+    root.children = inline_files(0, &files, &mut prefixes, &mut emitted);
+    root.name = prefixes.qualify(&prefixes.xsd, "schema");
+
+    for (prefix, namespace) in &prefixes.declarations {
+        let name = match prefix.is_empty() {
+            true => String::from("xmlns"),
+            false => format!("xmlns:{prefix}"),
+        };
+
+        if root.attribute(&name).is_none() {
+            root.attributes.push((name, namespace.clone()));
+        }
+    }
+
+    inline_groups(&mut root, &prefixes.xsd)?;
+
+    write(&root)
+}
+
+/// Schema file with the namespaces declared by its root element
+pub(super) struct SchemaFile {
+    path: PathBuf,
+    pub(super) root: SchemaElement,
+    /// Namespaces by their prefix, the default namespace has an empty prefix
+    namespaces: HashMap<String, String>,
+    target_namespace: Option<String>,
+}
+
+impl SchemaFile {
+    fn prefix_of(&self, namespace: &str) -> Option<String> {
+        let mut prefixes = self
+            .namespaces
+            .iter()
+            .filter(|(_, n)| *n == namespace)
+            .map(|(p, _)| p.clone())
+            .collect::<Vec<_>>();
+        prefixes.sort();
+
+        prefixes.into_iter().next()
+    }
+
+    /// Namespace of a qualified name, unprefixed names are in the default namespace
+    pub(super) fn namespace_of(&self, qualified_name: &str) -> Option<&String> {
+        let prefix = qualified_name
+            .split_once(':')
+            .map_or("", |(prefix, _)| prefix);
+
+        self.namespaces.get(prefix)
+    }
+
+    /// Paths of the schemas included or imported by the file
+    fn referenced_paths(&self) -> Vec<PathBuf> {
+        self.root
+            .elements()
+            .filter(|e| matches!(e.local_name(), "include" | "import"))
+            .filter_map(|e| e.attribute("schemaLocation"))
+            .map(|location| self.resolve(location))
+            .collect()
+    }
+
+    fn resolve(&self, location: &str) -> PathBuf {
+        let path = self.path.parent().unwrap_or(Path::new("")).join(location);
+
+        std::fs::canonicalize(&path).unwrap_or(path)
+    }
+}
+
+/// Prefixes used by the flattened schema
+struct Prefixes {
+    xsd: String,
+    /// Prefix of the target namespace, empty if the schema has no target namespace
+    target: String,
+    /// Target namespaces of all schema files, no namespace is represented by an empty string
+    inlined_namespaces: HashSet<String>,
+    /// Namespaces declared by the root element of the flattened schema
+    declarations: HashMap<String, String>,
+}
+
+impl Prefixes {
+    fn qualify(&self, prefix: &str, local_name: &str) -> String {
+        match prefix.is_empty() {
+            true => local_name.to_owned(),
+            false => format!("{prefix}:{local_name}"),
+        }
+    }
+
+    /// Declares the namespace, returns a new prefix if `prefix` is used by another namespace
+    fn declare(&mut self, prefix: &str, namespace: &str) -> String {
+        let mut candidate = prefix.to_owned();
+        let mut suffix = 1;
+
+        while let Some(declared) = self.declarations.get(&candidate) {
+            if declared == namespace {
+                return candidate;
+            }
+
+            candidate = format!("{prefix}{suffix}");
+            suffix += 1;
+        }
+
+        self.declarations
+            .insert(candidate.clone(), namespace.to_owned());
+
+        candidate
+    }
+
+    /// Qualified name of the flattened schema for a qualified name of `file`
+    fn rewrite_qualified_name(&mut self, file: &SchemaFile, qualified_name: &str) -> String {
+        let (prefix, local_name) = qualified_name
+            .split_once(':')
+            .unwrap_or(("", qualified_name));
+
+        // The prefix `xml` is bound without declaration
+        if prefix == "xml" {
+            return qualified_name.to_owned();
+        }
+
+        let namespace = file.namespace_of(qualified_name).cloned();
+
+        match namespace {
+            Some(n) if n == XSD_NAMESPACE => self.qualify(&self.xsd, local_name),
+            Some(n) if self.inlined_namespaces.contains(&n) => {
+                self.qualify(&self.target, local_name)
+            }
+            None if self.inlined_namespaces.contains("") => self.qualify(&self.target, local_name),
+            Some(n) => {
+                let prefix = self.declare(prefix, &n);
+
+                self.qualify(&prefix, local_name)
+            }
+            None => qualified_name.to_owned(),
+        }
+    }
+
+    /// Moves the schema element into the namespaces of the flattened schema
+    fn rewrite_element(&mut self, file: &SchemaFile, element: &mut SchemaElement) {
+        if file.namespace_of(&element.name).map(String::as_str) != Some(XSD_NAMESPACE) {
+            return;
+        }
+
+        element.name = self.qualify(&self.xsd, element.local_name());
+
+        for (name, value) in element.attributes.iter_mut() {
+            if QNAME_ATTRIBUTES.contains(&name.as_str()) {
+                *value = self.rewrite_qualified_name(file, value);
+            } else if name == "memberTypes" {
+                *value = value
+                    .split_whitespace()
+                    .map(|v| self.rewrite_qualified_name(file, v))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+            }
+        }
+
+        // The content of xs:appinfo belongs to other applications
+        if element.local_name() == "appinfo" {
+            return;
+        }
+
+        for child in element.children.iter_mut() {
+            if let SchemaNode::Element(child) = child {
+                self.rewrite_element(file, child);
+            }
+        }
+    }
+}
+
+/// Loads the schema file and the files it references, each file is loaded once
+pub(super) fn load(path: &Path, files: &mut Vec<SchemaFile>) -> Result<(), ParserError> {
+    let path = std::fs::canonicalize(path).map_err(|_| ParserError::UnableToReadFile)?;
+
+    if files.iter().any(|f| f.path == path) {
+        return Ok(());
+    }
+
+    let root = read(&path)?;
+    let namespaces = root
+        .attributes
+        .iter()
+        .filter_map(|(name, value)| match name.as_str() {
+            "xmlns" => Some((String::new(), value.clone())),
+            _ => name
+                .strip_prefix("xmlns:")
+                .map(|prefix| (prefix.to_owned(), value.clone())),
+        })
+        .collect();
+    let target_namespace = root.attribute("targetNamespace").map(str::to_owned);
+
+    let file = SchemaFile {
+        path,
+        root,
+        namespaces,
+        target_namespace,
+    };
+    let referenced_paths = file.referenced_paths();
+    files.push(file);
+
+    for path in referenced_paths {
+        load(&path, files)?;
+    }
+
+    Ok(())
+}
+
+/// Top level nodes of the file, with the components of referenced files in place of their
+/// `xs:include` and `xs:import`
+fn inline_files(
+    index: usize,
+    files: &[SchemaFile],
+    prefixes: &mut Prefixes,
+    emitted: &mut HashSet<PathBuf>,
+) -> Vec<SchemaNode> {
+    let file = &files[index];
+    let mut nodes = Vec::new();
+
+    for node in &file.root.children {
+        let SchemaNode::Element(element) = node else {
+            nodes.push(node.clone());
+            continue;
+        };
+
+        let is_xsd = file.namespace_of(&element.name).map(String::as_str) == Some(XSD_NAMESPACE);
+
+        match (
+            is_xsd,
+            element.local_name(),
+            element.attribute("schemaLocation"),
+        ) {
+            (true, "include" | "import", Some(location)) => {
+                let path = file.resolve(location);
+
+                if emitted.insert(path.clone()) {
+                    if let Some(index) = files.iter().position(|f| f.path == path) {
+                        nodes.append(&mut inline_files(index, files, prefixes, emitted));
+                    }
+                }
+            }
+            (true, "import", None)
+                if element
+                    .attribute("namespace")
+                    .is_some_and(|n| prefixes.inlined_namespaces.contains(n)) => {}
+            (true, "redefine" | "override", _) => {
+                eprintln!(
+                    "Warning: xs:{} of \"{}\" is kept, as redefinitions are not flattened",
+                    element.local_name(),
+                    element.attribute("schemaLocation").unwrap_or_default()
+                );
+
+                let mut element = element.clone();
+                prefixes.rewrite_element(file, &mut element);
+                nodes.push(SchemaNode::Element(element));
+            }
+            _ => {
+                let mut element = element.clone();
+                prefixes.rewrite_element(file, &mut element);
+                nodes.push(SchemaNode::Element(element));
+            }
+        }
+    }
+
+    nodes
+}
+
+/// Replaces the references to named groups and attribute groups by their content
+fn inline_groups(root: &mut SchemaElement, xsd_prefix: &str) -> Result<(), ParserError> {
+    let is_definition = |node: &SchemaNode, local_name: &str| match node {
+        SchemaNode::Element(e) => {
+            e.is_xsd(xsd_prefix) && e.local_name() == local_name && e.attribute("name").is_some()
+        }
+        _ => false,
+    };
+
+    let mut definitions = Definitions::default();
+
+    for node in &root.children {
+        let SchemaNode::Element(element) = node else {
+            continue;
+        };
+        let name = element.attribute("name").unwrap_or_default().to_owned();
+
+        if is_definition(node, "group") {
+            definitions.groups.insert(name, element.clone());
+        } else if is_definition(node, "attributeGroup") {
+            definitions.attribute_groups.insert(name, element.clone());
+        }
+    }
+
+    root.children
+        .retain(|n| !is_definition(n, "group") && !is_definition(n, "attributeGroup"));
+
+    definitions.inline(root, xsd_prefix, &mut Vec::new())
+}
+
+#[derive(Default)]
+struct Definitions {
+    groups: HashMap<String, SchemaElement>,
+    attribute_groups: HashMap<String, SchemaElement>,
+}
+
+impl Definitions {
+    fn inline(
+        &self,
+        element: &mut SchemaElement,
+        xsd_prefix: &str,
+        stack: &mut Vec<String>,
+    ) -> Result<(), ParserError> {
+        // Groups inside of redefinitions reference the redefined groups
+        if matches!(element.local_name(), "appinfo" | "redefine" | "override") {
+            return Ok(());
+        }
+
+        let mut children = Vec::with_capacity(element.children.len());
+
+        for node in std::mem::take(&mut element.children) {
+            let SchemaNode::Element(mut child) = node else {
+                children.push(node);
+                continue;
+            };
+
+            let reference = child
+                .attribute("ref")
+                .filter(|_| child.is_xsd(xsd_prefix))
+                .map(|r| r.rsplit(':').next().unwrap_or(r).to_owned());
+
+            match (child.local_name(), reference) {
+                ("group", Some(name)) => {
+                    let mut compositor = self.definition(&self.groups, &name, stack)?;
+                    let Some(mut compositor) =
+                        compositor.children.drain(..).find_map(|n| match n {
+                            SchemaNode::Element(e) if e.local_name() != "annotation" => Some(e),
+                            _ => None,
+                        })
+                    else {
+                        continue;
+                    };
+
+                    for occurs in ["minOccurs", "maxOccurs"] {
+                        if let Some(value) = child.attribute(occurs) {
+                            compositor.set_attribute(occurs, value);
+                        }
+                    }
+
+                    stack.push(name);
+                    self.inline(&mut compositor, xsd_prefix, stack)?;
+                    stack.pop();
+
+                    children.push(SchemaNode::Element(compositor));
+                }
+                ("attributeGroup", Some(name)) => {
+                    let mut group = self.definition(&self.attribute_groups, &name, stack)?;
+
+                    stack.push(name);
+                    self.inline(&mut group, xsd_prefix, stack)?;
+                    stack.pop();
+
+                    children.extend(group.children.into_iter().filter(
+                        |n| !matches!(n, SchemaNode::Element(e) if e.local_name() == "annotation"),
+                    ));
+                }
+                _ => {
+                    self.inline(&mut child, xsd_prefix, stack)?;
+                    children.push(SchemaNode::Element(child));
+                }
+            }
+        }
+
+        element.children = children;
+
+        Ok(())
+    }
+
+    fn definition(
+        &self,
+        definitions: &HashMap<String, SchemaElement>,
+        name: &String,
+        stack: &[String],
+    ) -> Result<SchemaElement, ParserError> {
+        if stack.contains(name) {
+            return Err(ParserError::MalformedAttribute(
+                String::from("ref"),
+                Some(format!("Group \"{name}\" references itself")),
+            ));
+        }
+
+        definitions.get(name).cloned().ok_or_else(|| {
+            ParserError::MalformedAttribute(
+                String::from("ref"),
+                Some(format!("Group \"{name}\" is not defined")),
+            )
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+enum SchemaNode {
+    Element(SchemaElement),
+    /// Escaped text
+    Text(String),
+    CData(String),
+    Comment(String),
+}
+
+#[derive(Clone, Debug)]
+pub(super) struct SchemaElement {
+    pub(super) name: String,
+    /// Unescaped values of the attributes
+    attributes: Vec<(String, String)>,
+    children: Vec<SchemaNode>,
+}
+
+impl SchemaElement {
+    fn from_start(start: &BytesStart) -> Result<Self, ParserError> {
+        let attributes = start
+            .attributes()
+            .map(|a| {
+                let a = a.map_err(|e| ParserError::MalformedAttribute(e.to_string(), None))?;
+                let name = String::from_utf8_lossy(a.key.as_ref()).into_owned();
+                let value = a.unescape_value().map_err(|e| {
+                    ParserError::MalformedAttribute(name.clone(), Some(e.to_string()))
+                })?;
+
+                Ok((name, value.into_owned()))
+            })
+            .collect::<Result<Vec<_>, ParserError>>()?;
+
+        Ok(Self {
+            name: String::from_utf8_lossy(start.name().as_ref()).into_owned(),
+            attributes,
+            children: Vec::new(),
+        })
+    }
+
+    pub(super) fn local_name(&self) -> &str {
+        self.name
+            .split_once(':')
+            .map_or(self.name.as_str(), |(_, local_name)| local_name)
+    }
+
+    fn is_xsd(&self, xsd_prefix: &str) -> bool {
+        match self.name.split_once(':') {
+            Some((prefix, _)) => prefix == xsd_prefix,
+            None => xsd_prefix.is_empty(),
+        }
+    }
+
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    fn set_attribute(&mut self, name: &str, value: &str) {
+        match self.attributes.iter_mut().find(|(n, _)| n == name) {
+            Some((_, v)) => *v = value.to_owned(),
+            None => self.attributes.push((name.to_owned(), value.to_owned())),
+        }
+    }
+
+    pub(super) fn elements(&self) -> impl Iterator<Item = &SchemaElement> {
+        self.children.iter().filter_map(|n| match n {
+            SchemaNode::Element(e) => Some(e),
+            _ => None,
+        })
+    }
+}
+
+/// Reads the root element of the schema file
+fn read(path: &Path) -> Result<SchemaElement, ParserError> {
+    let in_file = |encoding, error| {
+        ParserError::InFile(path.display().to_string(), encoding, Box::new(error))
+    };
+
+    let bytes = std::fs::read(path).map_err(|_| ParserError::UnableToReadFile)?;
+    let (content, encoding) = decode_schema(&bytes).map_err(|e| in_file("unknown", e))?;
+
+    let mut reader = Reader::from_str(&content);
+    reader.config_mut().trim_text(true);
+
+    let mut stack = Vec::<SchemaElement>::new();
+    let mut root = None;
+
+    loop {
+        let node = match reader.read_event() {
+            Ok(Event::Start(start)) => {
+                stack.push(SchemaElement::from_start(&start).map_err(|e| in_file(encoding, e))?);
+                continue;
+            }
+            Ok(Event::Empty(start)) => SchemaNode::Element(
+                SchemaElement::from_start(&start).map_err(|e| in_file(encoding, e))?,
+            ),
+            Ok(Event::End(_)) => match stack.pop() {
+                Some(element) => SchemaNode::Element(element),
+                None => return Err(in_file(encoding, ParserError::UnexpectedError)),
+            },
+            Ok(Event::Text(text)) => SchemaNode::Text(String::from_utf8_lossy(&text).into_owned()),
+            Ok(Event::CData(data)) => {
+                SchemaNode::CData(String::from_utf8_lossy(&data).into_owned())
+            }
+            Ok(Event::Comment(comment)) => {
+                SchemaNode::Comment(String::from_utf8_lossy(&comment).into_owned())
+            }
+            Ok(Event::Eof) => break,
+            Ok(_) => continue,
+            Err(_) => return Err(in_file(encoding, ParserError::UnexpectedError)),
+        };
+
+        match (stack.last_mut(), node) {
+            (Some(parent), node) => parent.children.push(node),
+            (None, SchemaNode::Element(element)) => root = Some(element),
+            (None, _) => (),
+        }
+    }
+
+    match (root, stack.is_empty()) {
+        (Some(root), true) => Ok(root),
+        _ => Err(in_file(encoding, ParserError::UnexpectedEndOfFile)),
+    }
+}
+
+fn write(root: &SchemaElement) -> Result<String, ParserError> {
+    let mut writer = Writer::new_with_indent(Vec::new(), b' ', 2);
+
+    writer
+        .write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))
+        .map_err(|_| ParserError::UnexpectedError)?;
+    write_element(&mut writer, root).map_err(|_| ParserError::UnexpectedError)?;
+
+    let mut schema = writer.into_inner();
+    schema.push(b'\n');
+
+    String::from_utf8(schema).map_err(|_| ParserError::UnexpectedError)
+}
+
+fn write_element(writer: &mut Writer<Vec<u8>>, element: &SchemaElement) -> quick_xml::Result<()> {
+    let mut start = BytesStart::new(element.name.as_str());
+
+    for (name, value) in &element.attributes {
+        start.push_attribute((name.as_str(), value.as_str()));
+    }
+
+    if element.children.is_empty() {
+        return writer.write_event(Event::Empty(start));
+    }
+
+    writer.write_event(Event::Start(start))?;
+
+    for node in &element.children {
+        match node {
+            SchemaNode::Element(child) => write_element(writer, child)?,
+            SchemaNode::Text(text) => {
+                writer.write_event(Event::Text(BytesText::from_escaped(text.as_str())))?
+            }
+            SchemaNode::CData(data) => writer.write_event(Event::CData(BytesCData::new(data)))?,
+            SchemaNode::Comment(comment) => {
+                writer.write_event(Event::Comment(BytesText::from_escaped(comment.as_str())))?
+            }
+        }
+    }
+
+    writer.write_event(Event::End(BytesEnd::new(element.name.as_str())))
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn included_schemas_and_groups_are_inlined() {
+        let dir = std::env::temp_dir().join(format!("genphi-flatten-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("common")).unwrap();
+
+        std::fs::write(
+            dir.join("main.xsd"),
+            indoc! {r#"
+                <xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" xmlns:o="urn:orders" xmlns:c="urn:common" targetNamespace="urn:orders">
+                  <xs:include schemaLocation="common/types.xsd"/>
+                  <xs:import namespace="urn:common" schemaLocation="common/audit.xsd"/>
+                  <xs:group name="Lines">
+                    <xs:sequence>
+                      <xs:element name="line" type="xs:string"/>
+                    </xs:sequence>
+                  </xs:group>
+                  <xs:complexType name="Order">
+                    <xs:sequence>
+                      <xs:element name="id" type="o:Id"/>
+                      <xs:group ref="o:Lines" maxOccurs="unbounded"/>
+                    </xs:sequence>
+                    <xs:attributeGroup ref="c:Audit"/>
+                  </xs:complexType>
+                </xs:schema>
+            "#},
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("common/types.xsd"),
+            indoc! {r#"
+                <xsd:schema xmlns:xsd="http://www.w3.org/2001/XMLSchema" targetNamespace="urn:orders">
+                  <xsd:simpleType name="Id">
+                    <xsd:restriction base="xsd:string"/>
+                  </xsd:simpleType>
+                </xsd:schema>
+            "#},
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("common/audit.xsd"),
+            indoc! {r#"
+                <xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" xmlns="urn:common" targetNamespace="urn:common">
+                  <xs:include schemaLocation="types.xsd"/>
+                  <xs:attributeGroup name="Audit">
+                    <xs:attribute name="created" type="xs:dateTime"/>
+                    <xs:attribute name="by" type="User"/>
+                  </xs:attributeGroup>
+                  <xs:simpleType name="User">
+                    <xs:restriction base="xs:string"/>
+                  </xs:simpleType>
+                </xs:schema>
+            "#},
+        )
+        .unwrap();
+
+        let schema = flatten_schema(&dir.join("main.xsd"));
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            schema.unwrap(),
+            indoc! {r#"
+                <?xml version="1.0" encoding="UTF-8"?>
+                <xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" xmlns:o="urn:orders" xmlns:c="urn:common" targetNamespace="urn:orders">
+                  <xs:simpleType name="Id">
+                    <xs:restriction base="xs:string"/>
+                  </xs:simpleType>
+                  <xs:simpleType name="User">
+                    <xs:restriction base="xs:string"/>
+                  </xs:simpleType>
+                  <xs:complexType name="Order">
+                    <xs:sequence>
+                      <xs:element name="id" type="o:Id"/>
+                      <xs:sequence maxOccurs="unbounded">
+                        <xs:element name="line" type="xs:string"/>
+                      </xs:sequence>
+                    </xs:sequence>
+                    <xs:attribute name="created" type="xs:dateTime"/>
+                    <xs:attribute name="by" type="o:User"/>
+                  </xs:complexType>
+                </xs:schema>
+            "#}
+        );
+    }
+}
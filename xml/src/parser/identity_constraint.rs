@@ -0,0 +1,81 @@
+use quick_xml::events::{BytesStart, Event};
+
+use super::{
+    helper::XmlParserHelper,
+    types::{IdentityConstraint, IdentityConstraintKind, Node, ParserError, SchemaReader},
+};
+
+/// Parser for the identity constraints xs:key, xs:keyref and xs:unique
+pub struct IdentityConstraintParser;
+
+impl IdentityConstraintParser {
+    /// Returns true for the names of xs:key, xs:keyref and xs:unique
+    pub fn is_identity_constraint(name: &[u8]) -> bool {
+        matches!(name, b"xs:key" | b"xs:keyref" | b"xs:unique")
+    }
+
+    /// Parses the identity constraint started by `start` including its xs:selector and xs:field
+    pub fn parse(
+        reader: &mut SchemaReader,
+        start: &BytesStart,
+    ) -> Result<IdentityConstraint, ParserError> {
+        let name = XmlParserHelper::get_attribute_value(start, "name")?;
+        let kind = match start.name().as_ref() {
+            b"xs:key" => IdentityConstraintKind::Key,
+            b"xs:unique" => IdentityConstraintKind::Unique,
+            _ => {
+                let refer = XmlParserHelper::get_attribute_value(start, "refer")?;
+
+                // Constraints are referenced by their name, the namespace is not needed
+                IdentityConstraintKind::KeyRef(
+                    refer.rsplit(':').next().unwrap_or_default().to_owned(),
+                )
+            }
+        };
+
+        let mut selector = None;
+        let mut fields = Vec::new();
+        let mut buf = Vec::new();
+
+        loop {
+            match reader.read_event_into(&mut buf) {
+                Ok(Event::Start(e) | Event::Empty(e)) => match e.name().as_ref() {
+                    b"xs:selector" => {
+                        selector = Some(XmlParserHelper::get_attribute_value(&e, "xpath")?);
+                    }
+                    b"xs:field" => fields.push(XmlParserHelper::get_attribute_value(&e, "xpath")?),
+                    _ => (),
+                },
+                Ok(Event::End(e)) if e.name() == start.name() => break,
+                Ok(Event::Eof) => return Err(ParserError::UnexpectedEndOfFile),
+                Err(_) => return Err(ParserError::UnexpectedError),
+                _ => (),
+            }
+
+            // if we don't keep a borrow elsewhere, we can clear the buffer to keep memory usage low
+            buf.clear();
+        }
+
+        let Some(selector) = selector else {
+            return Err(ParserError::MissingAttribute(String::from("xpath")));
+        };
+
+        Ok(IdentityConstraint {
+            name,
+            kind,
+            selector,
+            fields,
+        })
+    }
+
+    /// Adds the constraint to the element `name`, whose node was the last one pushed to `nodes`.
+    /// Identity constraints follow the type of an element, so its node already exists.
+    pub fn add_to_element(nodes: &mut [Node], name: &str, constraint: IdentityConstraint) {
+        if let Some(Node::Single(node)) = nodes.last_mut().filter(|n| match n {
+            Node::Single(node) => node.name == name,
+            Node::Group(_) => false,
+        }) {
+            node.identity_constraints.push(constraint);
+        }
+    }
+}
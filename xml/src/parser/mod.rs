@@ -1,7 +1,12 @@
 mod annotations;
+mod assertion;
 mod complex_type;
+pub(crate) mod constructs;
 mod custom_attribute;
+mod encoding;
+pub mod flatten;
 mod helper;
+mod identity_constraint;
 mod node;
 mod simple_type;
 pub mod types;
@@ -1,10 +1,4 @@
-use std::fs::File;
-use std::io::BufReader;
-
-use quick_xml::{
-    events::{BytesStart, Event},
-    Reader,
-};
+use quick_xml::events::{BytesStart, Event};
 
 use crate::{
     parser::{helper::XmlParserHelper, types::OrderIndicator},
@@ -13,10 +7,13 @@ use crate::{
 
 use super::{
     annotations::AnnotationsParser,
+    assertion::AssertionParser,
     complex_type::ComplexTypeParser,
+    identity_constraint::IdentityConstraintParser,
     simple_type::SimpleTypeParser,
     types::{
-        BaseAttributes, CustomTypeDefinition, Node, NodeGroup, NodeType, ParserError, SingleNode,
+        BaseAttributes, CustomTypeDefinition, GenerationOverrides, Node, NodeGroup, NodeType,
+        ParserError, SchemaReader, SingleNode,
     },
     xml::XmlParser,
 };
@@ -25,19 +22,39 @@ pub struct NodeParser;
 
 impl NodeParser {
     pub fn parse_element_with_type_node(
-        reader: &mut Reader<BufReader<File>>,
+        reader: &mut SchemaReader,
+        xml_parser: &XmlParser,
         node_type: NodeType,
         name: String,
         base_attributes: BaseAttributes,
     ) -> Result<Node, ParserError> {
         let mut buf = Vec::new();
         let mut annotations = Vec::new();
+        let mut is_documented = false;
+        let mut overrides = GenerationOverrides::default();
+        let mut identity_constraints = Vec::new();
 
         loop {
             match reader.read_event_into(&mut buf) {
                 Ok(Event::Start(s)) if s.name().as_ref() == b"xs:annotation" => {
-                    let mut values = AnnotationsParser::parse(reader)?;
-                    annotations.append(&mut values);
+                    let mut annotation = AnnotationsParser::parse_annotation(
+                        reader,
+                        xml_parser.documentation_language.as_deref(),
+                    )?;
+                    annotations.append(&mut annotation.documentations);
+                    is_documented |= annotation.has_documentation;
+                    overrides = annotation.overrides;
+                }
+                Ok(Event::Start(s))
+                    if IdentityConstraintParser::is_identity_constraint(s.name().as_ref()) =>
+                {
+                    identity_constraints.push(IdentityConstraintParser::parse(reader, &s)?);
+                }
+                Ok(Event::Start(s)) if s.name().as_ref() == b"xs:alternative" => {
+                    AssertionParser::skip_alternative(reader, &s, false, &name)?;
+                }
+                Ok(Event::Empty(e)) if e.name().as_ref() == b"xs:alternative" => {
+                    AssertionParser::skip_alternative(reader, &e, true, &name)?;
                 }
                 Ok(Event::End(e)) if e.name().as_ref() == b"xs:element" => break,
                 Ok(Event::Eof) => return Err(ParserError::UnexpectedEndOfFile),
@@ -49,16 +66,16 @@ impl NodeParser {
             buf.clear();
         }
 
-        Ok(Node::Single(SingleNode::new(
-            node_type,
-            name,
-            base_attributes,
-            Some(annotations),
-        )))
+        let mut node = SingleNode::new(node_type, name, base_attributes, Some(annotations));
+        node.is_documented = is_documented;
+        node.overrides = overrides;
+        node.identity_constraints = identity_constraints;
+
+        Ok(Node::Single(node))
     }
 
     pub fn parse_node_group(
-        reader: &mut Reader<BufReader<File>>,
+        reader: &mut SchemaReader,
         registry: &mut TypeRegistry,
         xml_parser: &XmlParser,
         start: &BytesStart,
@@ -66,6 +83,7 @@ impl NodeParser {
     ) -> Result<NodeGroup, ParserError> {
         let mut children: Vec<Node> = Vec::new();
         let mut current_element = None::<(String, BaseAttributes)>;
+        let mut is_element_documented = false;
         let mut buf = Vec::new();
 
         let order = match start.name().as_ref() {
@@ -74,7 +92,10 @@ impl NodeParser {
                 let base_attributes = XmlParserHelper::get_base_attributes(start)?;
                 OrderIndicator::Choice(base_attributes)
             }
-            b"xs:sequence" => OrderIndicator::Sequence,
+            b"xs:sequence" => {
+                let base_attributes = XmlParserHelper::get_base_attributes(start)?;
+                OrderIndicator::Sequence(base_attributes)
+            }
             _ => {
                 return Err(ParserError::UnexpectedStartOfNode(
                     std::str::from_utf8(start.name().0)
@@ -103,6 +124,7 @@ impl NodeParser {
 
                                 let node = NodeParser::parse_element_with_type_node(
                                     reader,
+                                    xml_parser,
                                     node_type,
                                     name,
                                     base_attributes,
@@ -112,6 +134,7 @@ impl NodeParser {
                             }
                             Err(ParserError::MissingAttribute(_)) => {
                                 current_element = Some((name, base_attributes));
+                                is_element_documented = false;
                             }
                             Err(e) => return Err(e),
                         };
@@ -122,7 +145,7 @@ impl NodeParser {
                                 reader,
                                 registry,
                                 xml_parser,
-                                name.clone(),
+                                registry.anonymous_type_name(&qualified_name, name),
                                 Some(qualified_name.clone()),
                             )?;
 
@@ -130,20 +153,25 @@ impl NodeParser {
                             let c_type = CustomTypeDefinition::Complex(c_type);
                             registry.register_type(c_type);
 
-                            let node = SingleNode::new(
+                            let mut node = SingleNode::new(
                                 node_type,
                                 name.clone(),
                                 (*base_attributes).clone(),
                                 None,
                             );
+                            node.is_documented = is_element_documented;
                             children.push(Node::Single(node));
                         } else {
                             let name = XmlParserHelper::get_attribute_value(&s, "name")
                                 .ok()
                                 .unwrap_or_else(|| registry.generate_type_name());
 
-                            let c_type =
+                            let is_abstract = XmlParserHelper::get_attribute_value(&s, "abstract")
+                                .is_ok_and(|v| v == "true");
+
+                            let mut c_type =
                                 ComplexTypeParser::parse(reader, registry, xml_parser, name, None)?;
+                            c_type.is_abstract = is_abstract;
 
                             let c_type = CustomTypeDefinition::Complex(c_type);
 
@@ -156,19 +184,20 @@ impl NodeParser {
                                 reader,
                                 registry,
                                 xml_parser,
-                                name.clone(),
+                                registry.anonymous_type_name(&qualified_name, name),
                                 Some(qualified_name.clone()),
                             )?;
 
                             let node_type = NodeType::Custom(s_type.qualified_name.clone());
                             registry.register_type(s_type.into());
 
-                            let node = SingleNode::new(
+                            let mut node = SingleNode::new(
                                 node_type,
                                 name.clone(),
                                 (*base_attributes).clone(),
                                 None,
                             );
+                            node.is_documented = is_element_documented;
                             children.push(Node::Single(node));
                         } else {
                             let name = XmlParserHelper::get_attribute_value(&s, "name")
@@ -181,6 +210,29 @@ impl NodeParser {
                             registry.register_type(s_type.into());
                         }
                     }
+                    // Only the presence of the documentation of an element with an anonymous type is kept
+                    b"xs:annotation" if current_element.is_some() => {
+                        let annotation = AnnotationsParser::parse_annotation(
+                            reader,
+                            xml_parser.documentation_language.as_deref(),
+                        )?;
+                        is_element_documented |= annotation.has_documentation;
+                    }
+                    b"xs:alternative" => {
+                        let element = current_element.as_ref().map_or("", |(name, _)| name);
+                        AssertionParser::skip_alternative(reader, &s, false, element)?;
+                    }
+                    name if IdentityConstraintParser::is_identity_constraint(name) => {
+                        let constraint = IdentityConstraintParser::parse(reader, &s)?;
+
+                        if let Some((element, _)) = &current_element {
+                            IdentityConstraintParser::add_to_element(
+                                &mut children,
+                                element,
+                                constraint,
+                            );
+                        }
+                    }
                     _ => (),
                 },
                 Ok(Event::Empty(e)) if e.name().as_ref() == b"xs:element" => {
@@ -200,6 +252,10 @@ impl NodeParser {
 
                     children.push(Node::Single(node));
                 }
+                Ok(Event::Empty(e)) if e.name().as_ref() == b"xs:alternative" => {
+                    let element = current_element.as_ref().map_or("", |(name, _)| name);
+                    AssertionParser::skip_alternative(reader, &e, true, element)?;
+                }
                 Ok(Event::End(e)) if e.name() == start.name() => break,
                 Ok(Event::Eof) => return Err(ParserError::UnexpectedEndOfFile),
                 Err(_) => return Err(ParserError::UnexpectedError),
@@ -1,33 +1,57 @@
-use std::{error::Error, fmt::Display};
+use std::{error::Error, fmt::Display, io::Cursor};
+
+use quick_xml::Reader;
+use serde::{Deserialize, Serialize};
+
+/// Reader over the content of a schema file, which was converted to UTF-8.
+pub type SchemaReader = Reader<Cursor<Vec<u8>>>;
 
 /// xsd value for unbounded occurance is represented as -1
 pub const UNBOUNDED_OCCURANCE: i64 = -1;
 /// xsd default occurance is 1
 pub const DEFAULT_OCCURANCE: i64 = 1;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ParsedData {
     pub nodes: Vec<Node>,
     pub documentations: Vec<String>,
+    pub notations: Vec<Notation>,
+}
+
+/// xs:notation
+#[derive(Debug, Clone)]
+pub struct Notation {
+    /// name-attribute
+    pub name: String,
+    /// public-attribute, e.g. a MIME type
+    pub public: Option<String>,
+    /// system-attribute, e.g. the URI of an application
+    pub system: Option<String>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Node {
     Single(SingleNode),
     Group(NodeGroup),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct SingleNode {
     pub node_type: NodeType,
     pub name: String,
     pub base_attributes: BaseAttributes,
     /// Documentation extracted from xs:annotation
     pub documentations: Option<Vec<String>>,
+    /// The xs:annotation of the element has an xs:documentation
+    pub is_documented: bool,
+    /// Generation overrides extracted from xs:appinfo
+    pub overrides: GenerationOverrides,
+    /// xs:key, xs:keyref and xs:unique of the element
+    pub identity_constraints: Vec<IdentityConstraint>,
 }
 
 impl SingleNode {
-    pub const fn new(
+    pub fn new(
         node_type: NodeType,
         name: String,
         base_attributes: BaseAttributes,
@@ -38,11 +62,34 @@ impl SingleNode {
             name,
             base_attributes,
             documentations,
+            is_documented: false,
+            overrides: GenerationOverrides::default(),
+            identity_constraints: Vec::new(),
         }
     }
 }
 
-#[derive(Debug)]
+/// Overrides for the generation of a type or field.
+///
+/// Read from an `override` element inside of xs:appinfo, e.g.
+/// `<genphi:override xmlns:genphi="urn:genphi" rename="Id" optional="true"/>`
+#[derive(Debug, Clone, Default)]
+pub struct GenerationOverrides {
+    /// rename-attribute, name used in the generated code
+    pub rename: Option<String>,
+    /// skip-attribute, excludes the type or field from generation
+    pub skip: bool,
+    /// optional-attribute, generates the field as optional regardless of the schema
+    pub optional: bool,
+    /// type-attribute, built-in type used instead of the type from the schema
+    pub data_type: Option<NodeBaseType>,
+    /// owned-attribute, whether the generated class frees the value of the field
+    pub owned: Option<bool>,
+    /// flags-attribute, generates a set type for lists of the enumeration
+    pub flags: bool,
+}
+
+#[derive(Debug, Clone)]
 pub struct NodeGroup {
     pub nodes: Vec<Node>,
     pub order: OrderIndicator,
@@ -93,23 +140,32 @@ pub enum NodeBaseType {
     Uri,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct BaseAttributes {
     pub min_occurs: Option<i64>,
     pub max_occurs: Option<i64>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum CustomTypeDefinition {
     Simple(SimpleType),
     Complex(ComplexType),
 }
 
 impl CustomTypeDefinition {
+    /// Returns the name used in the generated code, which respects a rename override.
     pub fn get_name(&self) -> String {
         match self {
             Self::Simple(t) => t.name.clone(),
-            Self::Complex(t) => t.name.clone(),
+            Self::Complex(t) => t.get_name(),
+        }
+    }
+
+    /// Whether the type is excluded from generation by a skip override.
+    pub const fn is_skipped(&self) -> bool {
+        match self {
+            Self::Simple(_) => false,
+            Self::Complex(t) => t.overrides.skip,
         }
     }
 
@@ -121,6 +177,16 @@ impl CustomTypeDefinition {
     }
 }
 
+impl ComplexType {
+    /// Returns the name used in the generated code, which respects a rename override.
+    pub fn get_name(&self) -> String {
+        self.overrides
+            .rename
+            .clone()
+            .unwrap_or_else(|| self.name.clone())
+    }
+}
+
 impl From<SimpleType> for CustomTypeDefinition {
     fn from(value: SimpleType) -> Self {
         Self::Simple(value)
@@ -138,7 +204,7 @@ impl From<ComplexType> for CustomTypeDefinition {
 pub enum UnionVariant {
     Standard(NodeBaseType),
     Named(String),
-    Simple(SimpleType),
+    Simple(Box<SimpleType>),
 }
 
 /// xs:simpleType
@@ -151,6 +217,10 @@ pub struct SimpleType {
 
     /// Documentation extracted from xs:annotation
     pub documentations: Vec<String>,
+    /// The xs:annotation has an xs:documentation
+    pub is_documented: bool,
+    /// Content of the xs:appinfo elements of xs:annotation
+    pub appinfo: Vec<String>,
 
     pub base_type: Option<NodeType>,
     /// possible values for an enumeration
@@ -159,8 +229,14 @@ pub struct SimpleType {
     pub list_type: Option<NodeType>,
     /// type of items in a list
     pub pattern: Option<String>,
+    /// xs:totalDigits, maximum number of digits of a decimal
+    pub total_digits: Option<usize>,
+    /// xs:fractionDigits, maximum number of digits in the fractional part of a decimal
+    pub fraction_digits: Option<usize>,
     /// variants of union type
     pub variants: Option<Vec<UnionVariant>>,
+    /// flags-attribute of the override element, the values of the enumeration can be combined
+    pub is_flags: bool,
 }
 
 /// xs:enumeration
@@ -170,10 +246,12 @@ pub struct EnumerationVariant {
     pub name: String,
     /// Documentation
     pub documentations: Vec<String>,
+    /// The xs:annotation of the variant has an xs:documentation
+    pub is_documented: bool,
 }
 
 /// xs:complexType
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ComplexType {
     /// name-attribute
     pub name: String,
@@ -182,26 +260,72 @@ pub struct ComplexType {
 
     /// Documentation extracted from xs:annotation
     pub documentations: Vec<String>,
+    /// The xs:annotation has an xs:documentation
+    pub is_documented: bool,
+    /// Generation overrides extracted from xs:appinfo
+    pub overrides: GenerationOverrides,
+    /// Content of the xs:appinfo elements of xs:annotation
+    pub appinfo: Vec<String>,
 
     /// qualified name of another complex type
     pub base_type: Option<String>,
+    /// abstract-attribute, the type is only used through derived types
+    pub is_abstract: bool,
     /// elements of the complex type
     pub children: Vec<Node>,
     /// custom attributes of the complex type
     pub custom_attributes: Vec<CustomAttribute>,
     /// order of elements
     pub order: OrderIndicator,
+    /// xs:any of the compositor of the complex type
+    pub wildcard: Option<Wildcard>,
+    /// test-attributes of the XSD 1.1 xs:assert elements
+    pub assertions: Vec<String>,
 }
 
-#[derive(Debug)]
+/// xs:any, which allows elements not declared by the complex type
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize)]
+pub struct Wildcard {
+    /// namespace-attribute
+    pub namespaces: WildcardNamespaces,
+    /// processContents-attribute
+    pub process_contents: ProcessContents,
+}
+
+/// Namespaces of the elements allowed by xs:any.
+/// `##targetNamespace` is replaced by the target namespace, `##local` and no target namespace
+/// by an empty string.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize)]
+pub enum WildcardNamespaces {
+    /// `##any`
+    Any,
+    /// `##other`, any namespace except the target namespace and no namespace
+    Other(String),
+    /// List of namespaces
+    List(Vec<String>),
+}
+
+/// How the content of the elements allowed by xs:any is validated
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize)]
+pub enum ProcessContents {
+    /// The elements must be declared by the schemas
+    #[default]
+    Strict,
+    /// The elements are validated, if they are declared by the schemas
+    Lax,
+    /// The elements are not validated
+    Skip,
+}
+
+#[derive(Debug, Clone)]
 pub enum OrderIndicator {
     All,
     Choice(BaseAttributes),
-    Sequence,
+    Sequence(BaseAttributes),
 }
 
 /// xs:attribute
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct CustomAttribute {
     /// name-attribute
     pub name: String,
@@ -210,6 +334,10 @@ pub struct CustomAttribute {
 
     /// Documentation extracted from xs:annotation
     pub documentations: Vec<String>,
+    /// The xs:annotation has an xs:documentation
+    pub is_documented: bool,
+    /// Generation overrides extracted from xs:appinfo
+    pub overrides: GenerationOverrides,
 
     pub base_type: NodeType,
 
@@ -221,6 +349,39 @@ pub struct CustomAttribute {
 
     /// use-attribute (required or optional)
     pub required: bool,
+
+    /// Namespace of attributes with qualified form, unqualified attributes have none
+    pub namespace: Option<AttributeNamespace>,
+}
+
+/// Namespace of an attribute with qualified form, which is written with the prefix
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize)]
+pub struct AttributeNamespace {
+    pub prefix: String,
+    pub uri: String,
+}
+
+/// xs:key, xs:keyref or xs:unique of an element
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdentityConstraint {
+    /// name-attribute
+    pub name: String,
+    pub kind: IdentityConstraintKind,
+    /// xpath-attribute of xs:selector, relative to the element
+    pub selector: String,
+    /// xpath-attributes of the xs:field elements, relative to the selected nodes
+    pub fields: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IdentityConstraintKind {
+    /// xs:key, the fields are required and their values are unique
+    Key,
+    /// xs:unique, the values of the fields are unique, if all fields are present
+    Unique,
+    /// xs:keyref, the values of the fields are values of the key or unique constraint
+    /// named by the refer-attribute
+    KeyRef(String),
 }
 
 #[derive(Debug, Clone)]
@@ -231,9 +392,20 @@ pub enum ParserError {
     MissingOrNotSupportedBaseType(String),
     MissingAttribute(String),
     UnableToReadFile,
+    UnsupportedEncoding(String),
+    /// File content is not valid for the detected encoding
+    MalformedEncoding(&'static str),
+    /// Error, which occured while parsing the given file with the detected encoding
+    InFile(String, &'static str, Box<ParserError>),
     UnexpectedEndOfFile,
     UnexpectedError,
     UnexpectedStartOfNode(String),
+    /// The schema file is larger than the limit in bytes
+    FileTooLarge(u64, u64),
+    /// The xml elements of the schema are nested deeper than the limit
+    NestingTooDeep(usize),
+    /// The schemas define more types than the limit
+    TooManyTypes(usize),
 }
 
 impl Display for ParserError {
@@ -254,11 +426,54 @@ impl Display for ParserError {
             }
             Self::MissingAttribute(name) => write!(f, "Missing Attribute \"{name}\""),
             Self::UnableToReadFile => write!(f, "Failed to read input file"),
+            Self::UnsupportedEncoding(label) => write!(f, "Encoding \"{label}\" is not supported"),
+            Self::MalformedEncoding(_) => {
+                write!(f, "File content does not match the detected encoding")
+            }
+            Self::InFile(path, encoding, error) => {
+                write!(f, "{error} (File: \"{path}\", Encoding: {encoding})")
+            }
             Self::UnexpectedEndOfFile => write!(f, "File ended to early"),
             Self::UnexpectedError => write!(f, "An unexpected error occured"),
             Self::UnexpectedStartOfNode(name) => write!(f, "Unexpected start of \"{name}\""),
+            Self::FileTooLarge(size, limit) => write!(
+                f,
+                "File has {size} bytes, which exceeds the limit of {limit} bytes"
+            ),
+            Self::NestingTooDeep(limit) => {
+                write!(
+                    f,
+                    "Elements are nested deeper than the limit of {limit} levels"
+                )
+            }
+            Self::TooManyTypes(limit) => {
+                write!(f, "Schemas define more than the limit of {limit} types")
+            }
         }
     }
 }
 
 impl Error for ParserError {}
+
+/// Limits, which abort the parsing of pathological schemas with an error instead of
+/// overflowing the stack or running for minutes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ParserLimits {
+    /// Maximum nesting depth of the xml elements of a schema file
+    pub max_depth: usize,
+    /// Maximum number of types defined by all schemas
+    pub max_types: usize,
+    /// Maximum size of a schema file in bytes
+    pub max_file_size: u64,
+}
+
+impl Default for ParserLimits {
+    fn default() -> Self {
+        Self {
+            max_depth: 512,
+            max_types: 100_000,
+            max_file_size: 256 * 1024 * 1024,
+        }
+    }
+}
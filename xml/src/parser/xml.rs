@@ -1,24 +1,28 @@
-use std::{borrow::Cow, collections::HashMap, fs::File, io::BufReader, path::Path};
+use std::{borrow::Cow, collections::HashMap, io::Cursor, path::Path};
 
 use quick_xml::{events::BytesStart, events::Event, Reader};
 
 use super::{
     annotations::AnnotationsParser,
+    assertion::AssertionParser,
     complex_type::ComplexTypeParser,
+    encoding::decode_schema,
     helper::XmlParserHelper,
+    identity_constraint::IdentityConstraintParser,
     node::NodeParser,
     simple_type::SimpleTypeParser,
     types::{
-        BaseAttributes, CustomTypeDefinition, Node, NodeType, ParsedData, ParserError, SingleNode,
+        AttributeNamespace, BaseAttributes, CustomTypeDefinition, Node, NodeType, Notation,
+        ParsedData, ParserError, ParserLimits, SchemaReader, SingleNode,
     },
 };
-use crate::type_registry::TypeRegistry;
+use crate::type_registry::{Redefinition, TypeRegistry};
 
 /// A parser for XML files.
 ///
 /// # Examples
 ///
-/// ```rust
+/// ```ignore
 /// use std::path::PathBuf;
 ///
 /// use xsd_parser::{parser::XmlParser, type_registry::TypeRegistry};
@@ -37,6 +41,11 @@ use crate::type_registry::TypeRegistry;
 pub struct XmlParser {
     pub current_namespace: Option<String>,
     pub namespace_aliases: HashMap<String, String>,
+    /// attributeFormDefault-attribute of the current schema is `qualified`
+    pub qualified_attributes: bool,
+    /// Language of the documentations, which are kept when an annotation has several languages
+    pub documentation_language: Option<String>,
+    pub limits: ParserLimits,
 }
 
 impl XmlParser {
@@ -53,7 +62,7 @@ impl XmlParser {
     ///
     /// # Examples
     ///
-    /// ```rust
+    /// ```ignore
     /// use std::path::PathBuf;
     ///
     /// use xsd_parser::{parser::XmlParser, type_registry::TypeRegistry};
@@ -73,11 +82,16 @@ impl XmlParser {
         path: P,
         registry: &mut TypeRegistry,
     ) -> Result<ParsedData, ParserError> {
-        let Ok(mut reader) = Reader::from_file(path) else {
-            return Err(ParserError::UnableToReadFile);
-        };
+        let (mut reader, encoding) = self.read_schema(&path)?;
+
+        let data = self
+            .parse_nodes(&mut reader, registry)
+            .map_err(|e| Self::in_file(&path, encoding, e))?;
+        self.check_type_count(registry)?;
+
+        registry.apply_redefinitions();
 
-        self.parse_nodes(&mut reader, registry)
+        Ok(data)
     }
 
     /// Parses multiple XML files.
@@ -93,7 +107,7 @@ impl XmlParser {
     ///
     /// # Examples
     ///
-    /// ```rust
+    /// ```ignore
     /// use std::path::PathBuf;
     ///
     /// use xsd_parser::{parser::XmlParser, type_registry::TypeRegistry};
@@ -116,36 +130,122 @@ impl XmlParser {
     ) -> Result<ParsedData, ParserError> {
         let mut nodes = Vec::new();
         let mut documentations = Vec::new();
+        let mut notations = Vec::new();
 
         for path in paths {
-            let Ok(mut reader) = Reader::from_file(path) else {
-                return Err(ParserError::UnableToReadFile);
-            };
+            let (mut reader, encoding) = self.read_schema(path)?;
 
             self.current_namespace = None;
             self.namespace_aliases.clear();
+            self.qualified_attributes = false;
 
-            let file_nodes = self.parse_nodes(&mut reader, registry)?;
+            let file_nodes = self
+                .parse_nodes(&mut reader, registry)
+                .map_err(|e| Self::in_file(path, encoding, e))?;
+            self.check_type_count(registry)?;
             nodes.extend(file_nodes.nodes);
             documentations.extend(file_nodes.documentations);
+            notations.extend(file_nodes.notations);
         }
 
+        // Redefined types may be part of any of the files
+        registry.apply_redefinitions();
+
         Ok(ParsedData {
             nodes,
             documentations,
+            notations,
         })
     }
 
+    /// Reads the schema file and converts its content to UTF-8.
+    ///
+    /// Returns the reader for the converted content and the name of the detected encoding.
+    /// Files exceeding the size or nesting limits are rejected before they are parsed.
+    fn read_schema<P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> Result<(SchemaReader, &'static str), ParserError> {
+        let Ok(metadata) = std::fs::metadata(&path) else {
+            return Err(ParserError::UnableToReadFile);
+        };
+
+        if metadata.len() > self.limits.max_file_size {
+            return Err(Self::in_file(
+                &path,
+                "unknown",
+                ParserError::FileTooLarge(metadata.len(), self.limits.max_file_size),
+            ));
+        }
+
+        let Ok(bytes) = std::fs::read(&path) else {
+            return Err(ParserError::UnableToReadFile);
+        };
+
+        let (content, encoding) = decode_schema(&bytes).map_err(|e| match e {
+            ParserError::MalformedEncoding(encoding) => Self::in_file(&path, encoding, e),
+            _ => Self::in_file(&path, "unknown", e),
+        })?;
+
+        Self::check_depth(&content, self.limits.max_depth)
+            .map_err(|e| Self::in_file(&path, encoding, e))?;
+
+        Ok((
+            Reader::from_reader(Cursor::new(content.into_bytes())),
+            encoding,
+        ))
+    }
+
+    /// Checks the nesting depth of the elements without recursion, as the parsers of nested
+    /// types recurse for every level and would overflow the stack.
+    /// Malformed content is left to the parsers, which report it in detail.
+    fn check_depth(content: &str, max_depth: usize) -> Result<(), ParserError> {
+        let mut reader = Reader::from_str(content);
+        let mut depth = 0usize;
+
+        loop {
+            match reader.read_event() {
+                Ok(Event::Start(_)) => {
+                    depth += 1;
+
+                    if depth > max_depth {
+                        return Err(ParserError::NestingTooDeep(max_depth));
+                    }
+                }
+                Ok(Event::End(_)) => depth = depth.saturating_sub(1),
+                Ok(Event::Eof) | Err(_) => return Ok(()),
+                _ => (),
+            }
+        }
+    }
+
+    fn check_type_count(&self, registry: &TypeRegistry) -> Result<(), ParserError> {
+        match registry.types.len() > self.limits.max_types {
+            true => Err(ParserError::TooManyTypes(self.limits.max_types)),
+            false => Ok(()),
+        }
+    }
+
+    fn in_file<P: AsRef<Path>>(path: P, encoding: &'static str, error: ParserError) -> ParserError {
+        ParserError::InFile(
+            path.as_ref().display().to_string(),
+            encoding,
+            Box::new(error),
+        )
+    }
+
     fn parse_nodes(
         &mut self,
-        reader: &mut Reader<BufReader<File>>,
+        reader: &mut SchemaReader,
         registry: &mut TypeRegistry,
     ) -> Result<ParsedData, ParserError> {
         let mut nodes = Vec::new();
         let mut documentations = Vec::new();
+        let mut notations = Vec::new();
         let mut buf = Vec::new();
 
         let mut current_element = None::<(String, BaseAttributes)>;
+        let mut current_redefinition = None::<Redefinition>;
 
         loop {
             match reader.read_event_into(&mut buf) {
@@ -154,9 +254,14 @@ impl XmlParser {
                         b"xs:schema" => {
                             self.current_namespace =
                                 XmlParserHelper::get_attribute_value(&s, "targetNamespace").ok();
+                            self.qualified_attributes =
+                                XmlParserHelper::get_attribute_value(&s, "attributeFormDefault")
+                                    .is_ok_and(|f| f == "qualified");
 
                             self.extract_schema_namespace_aliases(&s)?;
                         }
+                        b"xs:redefine" => current_redefinition = Some(Redefinition::Redefine),
+                        b"xs:override" => current_redefinition = Some(Redefinition::Override),
                         b"xs:element" => {
                             let name = XmlParserHelper::get_attribute_value(&s, "name")?;
                             let base_attributes = XmlParserHelper::get_base_attributes(&s)?;
@@ -173,6 +278,7 @@ impl XmlParser {
 
                                     let node = NodeParser::parse_element_with_type_node(
                                         reader,
+                                        self,
                                         node_type,
                                         name,
                                         base_attributes,
@@ -212,12 +318,20 @@ impl XmlParser {
                                     .ok()
                                     .unwrap_or_else(|| registry.generate_type_name());
 
-                                let c_type =
+                                let is_abstract =
+                                    XmlParserHelper::get_attribute_value(&s, "abstract")
+                                        .is_ok_and(|v| v == "true");
+
+                                let mut c_type =
                                     ComplexTypeParser::parse(reader, registry, self, name, None)?;
+                                c_type.is_abstract = is_abstract;
 
                                 let c_type = CustomTypeDefinition::Complex(c_type);
 
-                                registry.register_type(c_type);
+                                match current_redefinition {
+                                    Some(r) => registry.register_redefinition(r, c_type),
+                                    None => registry.register_type(c_type),
+                                }
                             }
                         }
                         b"xs:simpleType" => {
@@ -248,37 +362,71 @@ impl XmlParser {
                                 let s_type =
                                     SimpleTypeParser::parse(reader, registry, self, name, None)?;
 
-                                registry.register_type(s_type.into());
+                                match current_redefinition {
+                                    Some(r) => registry.register_redefinition(r, s_type.into()),
+                                    None => registry.register_type(s_type.into()),
+                                }
                             }
                         }
                         b"xs:annotation" => {
-                            let mut values = AnnotationsParser::parse(reader)?;
+                            let mut values = AnnotationsParser::parse(
+                                reader,
+                                self.documentation_language.as_deref(),
+                            )?;
                             documentations.append(&mut values);
                         }
+                        b"xs:notation" => {
+                            notations.push(Self::parse_notation(&s)?);
+                            // The annotation of the notation isn't part of the documentation of the schema
+                            reader
+                                .read_to_end_into(s.name(), &mut Vec::new())
+                                .map_err(|_| ParserError::UnexpectedError)?;
+                        }
+                        b"xs:alternative" => {
+                            let element = current_element.as_ref().map_or("", |(name, _)| name);
+                            AssertionParser::skip_alternative(reader, &s, false, element)?;
+                        }
+                        name if IdentityConstraintParser::is_identity_constraint(name) => {
+                            let constraint = IdentityConstraintParser::parse(reader, &s)?;
+
+                            if let Some((element, _)) = &current_element {
+                                IdentityConstraintParser::add_to_element(
+                                    &mut nodes, element, constraint,
+                                );
+                            }
+                        }
                         _ => (),
                     }
                     //
                 }
-                Ok(Event::End(e)) => {
-                    if e.name().as_ref() == b"xs:element" {
-                        current_element = None;
-                    }
+                Ok(Event::End(e)) if e.name().as_ref() == b"xs:element" => {
+                    current_element = None;
                 }
-                Ok(Event::Empty(e)) => {
-                    if e.name().as_ref() == b"xs:element" {
-                        let name = XmlParserHelper::get_attribute_value(&e, "name")?;
-                        let b_type = XmlParserHelper::get_attribute_value(&e, "type")?;
-                        let b_type = self.resolve_namespace(b_type)?;
-                        let Some(node_type) =
-                            XmlParserHelper::base_type_str_to_node_type(b_type.as_str())
-                        else {
-                            return Err(ParserError::MissingOrNotSupportedBaseType(b_type));
-                        };
-
-                        let base_attributes = XmlParserHelper::get_base_attributes(&e)?;
-                        let node = SingleNode::new(node_type, name, base_attributes, None);
-                        nodes.push(Node::Single(node));
-                    }
+                Ok(Event::End(e))
+                    if matches!(e.name().as_ref(), b"xs:redefine" | b"xs:override") =>
+                {
+                    current_redefinition = None;
+                }
+                Ok(Event::Empty(e)) if e.name().as_ref() == b"xs:alternative" => {
+                    let element = current_element.as_ref().map_or("", |(name, _)| name);
+                    AssertionParser::skip_alternative(reader, &e, true, element)?;
+                }
+                Ok(Event::Empty(e)) if e.name().as_ref() == b"xs:notation" => {
+                    notations.push(Self::parse_notation(&e)?);
+                }
+                Ok(Event::Empty(e)) if e.name().as_ref() == b"xs:element" => {
+                    let name = XmlParserHelper::get_attribute_value(&e, "name")?;
+                    let b_type = XmlParserHelper::get_attribute_value(&e, "type")?;
+                    let b_type = self.resolve_namespace(b_type)?;
+                    let Some(node_type) =
+                        XmlParserHelper::base_type_str_to_node_type(b_type.as_str())
+                    else {
+                        return Err(ParserError::MissingOrNotSupportedBaseType(b_type));
+                    };
+
+                    let base_attributes = XmlParserHelper::get_base_attributes(&e)?;
+                    let node = SingleNode::new(node_type, name, base_attributes, None);
+                    nodes.push(Node::Single(node));
                 }
                 // Err(e) => panic!("Error at position {}: {:?}", reader.buffer_position(), e),
                 Err(_) => return Err(ParserError::UnexpectedError),
@@ -294,6 +442,15 @@ impl XmlParser {
         Ok(ParsedData {
             nodes,
             documentations,
+            notations,
+        })
+    }
+
+    fn parse_notation(node: &BytesStart) -> Result<Notation, ParserError> {
+        Ok(Notation {
+            name: XmlParserHelper::get_attribute_value(node, "name")?,
+            public: XmlParserHelper::get_attribute_value(node, "public").ok(),
+            system: XmlParserHelper::get_attribute_value(node, "system").ok(),
         })
     }
 
@@ -302,6 +459,27 @@ impl XmlParser {
         self.namespace_aliases.get(alias)
     }
 
+    /// Namespace of the attributes with qualified form in the current schema, prefixed by the
+    /// first alias of the target namespace or `tns`, if it has none, e.g. as default namespace.
+    ///
+    /// # Arguments
+    ///
+    /// * `form` - The form-attribute of the attribute, if any.
+    pub fn attribute_namespace(&self, form: Option<&str>) -> Option<AttributeNamespace> {
+        let is_qualified = form.map_or(self.qualified_attributes, |f| f == "qualified");
+        let uri = self.current_namespace.clone().filter(|_| is_qualified)?;
+
+        let prefix = self
+            .namespace_aliases
+            .iter()
+            .filter(|(_, namespace)| **namespace == uri)
+            .map(|(alias, _)| alias)
+            .min()
+            .map_or_else(|| String::from("tns"), Clone::clone);
+
+        Some(AttributeNamespace { prefix, uri })
+    }
+
     /// Creates a qualified name from a name and the current namespace.
     ///
     /// # Arguments
@@ -406,3 +584,236 @@ impl XmlParser {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::types::{ProcessContents, Wildcard, WildcardNamespaces};
+
+    const COMPOSITORS: [&str; 3] = ["sequence", "choice", "all"];
+
+    fn parse(content: &str) -> (ParsedData, TypeRegistry) {
+        let schema = format!(
+            r#"<?xml version="1.0"?><xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema">{content}</xs:schema>"#
+        );
+        let mut reader = Reader::from_reader(Cursor::new(schema.into_bytes()));
+        let mut registry = TypeRegistry::new();
+
+        let data = XmlParser::default()
+            .parse_nodes(&mut reader, &mut registry)
+            .unwrap();
+
+        (data, registry)
+    }
+
+    /// Element `name` with an anonymous type, which nests the elements of the following
+    /// compositors. Every level has a sibling element and an attribute with anonymous types.
+    /// With `wrap` the nested element is wrapped into a second compositor of the same kind.
+    fn nested_element(name: &str, compositors: &[&str], wrap: bool) -> String {
+        let Some((compositor, rest)) = compositors.split_first() else {
+            return format!(
+                r#"<xs:element name="{name}"><xs:complexType><xs:sequence>
+                     <xs:element name="value" type="xs:string"/>
+                   </xs:sequence></xs:complexType></xs:element>"#
+            );
+        };
+
+        let nested = nested_element(&format!("{name}x"), rest, wrap);
+        let nested = match *compositor {
+            "all" => nested,
+            _ if wrap => format!("<xs:{compositor}>{nested}</xs:{compositor}>"),
+            _ => nested,
+        };
+
+        format!(
+            r#"<xs:element name="{name}"><xs:complexType><xs:{compositor}>{nested}
+                <xs:element name="{name}s"><xs:complexType><xs:sequence>
+                  <xs:element name="leaf" type="xs:string"/>
+                </xs:sequence></xs:complexType></xs:element>
+              </xs:{compositor}>
+              <xs:attribute name="{name}a"><xs:simpleType><xs:restriction base="xs:string">
+                <xs:enumeration value="a"/>
+              </xs:restriction></xs:simpleType></xs:attribute>
+            </xs:complexType></xs:element>"#
+        )
+    }
+
+    #[test]
+    fn nested_anonymous_types_are_referenced_by_their_elements() {
+        for depth in 1..=3 {
+            let mut combinations = vec![vec![]];
+            for _ in 0..depth {
+                combinations = combinations
+                    .into_iter()
+                    .flat_map(|c: Vec<&str>| {
+                        COMPOSITORS.map(|compositor| [c.as_slice(), &[compositor]].concat())
+                    })
+                    .collect();
+            }
+
+            for compositors in &combinations {
+                for wrap in [false, true] {
+                    let element = nested_element("n", compositors, wrap);
+                    let (data, registry) = parse(&format!(
+                        r#"<xs:complexType name="Root"><xs:sequence>{element}</xs:sequence></xs:complexType>
+                           <xs:element name="root" type="Root"/>"#
+                    ));
+
+                    // Per level the element type, its sibling and its attribute type
+                    let expected = 1 + 3 * depth + 1;
+                    assert_eq!(
+                        registry.types.len(),
+                        expected,
+                        "{compositors:?}, wrap: {wrap}"
+                    );
+                    assert!(
+                        registry
+                            .unreferenced_anonymous_types(&data.nodes)
+                            .is_empty(),
+                        "{compositors:?}, wrap: {wrap}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn anonymous_types_of_the_same_name_are_kept_apart() {
+        let (data, registry) = parse(
+            r#"<xs:element name="root"><xs:complexType>
+                 <xs:choice>
+                   <xs:sequence>
+                     <xs:element name="item"><xs:complexType><xs:sequence>
+                       <xs:element name="a" type="xs:string"/>
+                     </xs:sequence></xs:complexType></xs:element>
+                   </xs:sequence>
+                   <xs:sequence>
+                     <xs:element name="item"><xs:complexType><xs:sequence>
+                       <xs:element name="b" type="xs:int"/>
+                     </xs:sequence></xs:complexType></xs:element>
+                   </xs:sequence>
+                 </xs:choice>
+                 <xs:attribute name="item"><xs:simpleType><xs:restriction base="xs:string">
+                   <xs:maxLength value="10"/>
+                 </xs:restriction></xs:simpleType></xs:attribute>
+               </xs:complexType></xs:element>"#,
+        );
+
+        let mut names = registry
+            .types
+            .keys()
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        names.sort_unstable();
+
+        assert_eq!(names, ["root", "root.item", "root.item2", "root.item3"]);
+        assert!(registry
+            .unreferenced_anonymous_types(&data.nodes)
+            .is_empty());
+    }
+
+    #[test]
+    fn wildcards_keep_their_namespaces_and_process_contents() {
+        let wildcard = |content: &str| {
+            let (_, registry) = parse(&format!(
+                r#"<xs:complexType name="Root"><xs:sequence>
+                     <xs:element name="id" type="xs:string"/>
+                     {content}
+                   </xs:sequence></xs:complexType>"#
+            ));
+
+            match registry.types.get("Root") {
+                Some(CustomTypeDefinition::Complex(ct)) => ct.wildcard.clone(),
+                _ => None,
+            }
+        };
+
+        assert_eq!(wildcard(""), None);
+        assert_eq!(
+            wildcard(r#"<xs:any/>"#),
+            Some(Wildcard {
+                namespaces: WildcardNamespaces::Any,
+                process_contents: ProcessContents::Strict,
+            })
+        );
+        assert_eq!(
+            wildcard(r###"<xs:any namespace="##other" processContents="lax"/>"###),
+            Some(Wildcard {
+                namespaces: WildcardNamespaces::Other(String::new()),
+                process_contents: ProcessContents::Lax,
+            })
+        );
+        assert_eq!(
+            wildcard(r###"<xs:any namespace="urn:a ##local" processContents="skip"></xs:any>"###),
+            Some(Wildcard {
+                namespaces: WildcardNamespaces::List(vec![String::from("urn:a"), String::new()]),
+                process_contents: ProcessContents::Skip,
+            })
+        );
+    }
+
+    #[test]
+    fn qualified_attributes_are_in_the_target_namespace() {
+        let namespaces = |schema_attributes: &str| {
+            let schema = format!(
+                r#"<?xml version="1.0"?>
+                   <xs:schema xmlns:xs="http://www.w3.org/2001/XMLSchema" targetNamespace="urn:orders" {schema_attributes}>
+                     <xs:complexType name="Order">
+                       <xs:attribute name="id" type="xs:string"/>
+                       <xs:attribute name="note" type="xs:string" form="qualified"/>
+                       <xs:attribute name="code" type="xs:string" form="unqualified"/>
+                     </xs:complexType>
+                   </xs:schema>"#
+            );
+            let mut reader = Reader::from_reader(Cursor::new(schema.into_bytes()));
+            let mut registry = TypeRegistry::new();
+
+            XmlParser::default()
+                .parse_nodes(&mut reader, &mut registry)
+                .unwrap();
+
+            match registry.types.get("urn:orders/Order") {
+                Some(CustomTypeDefinition::Complex(ct)) => ct
+                    .custom_attributes
+                    .iter()
+                    .map(|a| {
+                        a.namespace
+                            .as_ref()
+                            .map(|n| format!("{}={}", n.prefix, n.uri))
+                    })
+                    .collect::<Vec<_>>(),
+                _ => vec![],
+            }
+        };
+
+        let qualified = Some(String::from("o=urn:orders"));
+
+        assert_eq!(
+            namespaces(r#"xmlns:o="urn:orders""#),
+            [None, qualified.clone(), None]
+        );
+        assert_eq!(
+            namespaces(r#"xmlns:o="urn:orders" attributeFormDefault="qualified""#),
+            [qualified.clone(), qualified, None]
+        );
+        assert_eq!(
+            namespaces(r#"xmlns="urn:orders" attributeFormDefault="qualified""#),
+            [
+                Some(String::from("tns=urn:orders")),
+                Some(String::from("tns=urn:orders")),
+                None
+            ]
+        );
+    }
+
+    #[test]
+    fn nesting_depth_is_limited() {
+        let schema = nested_element("root", &["sequence", "choice", "sequence"], false);
+
+        assert!(XmlParser::check_depth(&schema, 12).is_ok());
+        assert!(matches!(
+            XmlParser::check_depth(&schema, 11),
+            Err(ParserError::NestingTooDeep(11))
+        ));
+    }
+}
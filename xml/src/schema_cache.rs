@@ -0,0 +1,165 @@
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{Mutex, MutexGuard, PoisonError},
+    time::SystemTime,
+};
+
+use crate::{parser::types::ParsedData, type_registry::TypeRegistry};
+
+/// Schemas parsed by earlier generations of this process, once enabled by `enable_schema_cache`
+static SCHEMA_CACHE: Mutex<Option<SchemaCache>> = Mutex::new(None);
+
+/// Number of cached schemas and how often they were reused
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SchemaCacheStats {
+    /// Cached sets of schema files
+    pub entries: usize,
+    /// Parses, which reused cached schemas
+    pub hits: u64,
+    /// Parses of schemas, which were not cached yet or have been modified since
+    pub misses: u64,
+}
+
+/// Keeps parsed schemas in memory for the rest of the process, e.g. for a server generating
+/// the same schemas repeatedly. Schemas are parsed again, once one of their files is modified.
+pub fn enable_schema_cache() {
+    lock().get_or_insert_with(SchemaCache::default);
+}
+
+/// Statistics of the schema cache, which are zero unless the cache is enabled
+pub fn schema_cache_stats() -> SchemaCacheStats {
+    lock().as_ref().map(SchemaCache::stats).unwrap_or_default()
+}
+
+/// Parses the schemas by `parse` or takes them from the cache, if it is enabled
+pub(crate) fn parse_cached<E>(
+    source: &[PathBuf],
+    documentation_language: &Option<String>,
+    type_registry: &mut TypeRegistry,
+    parse: impl FnOnce(&mut TypeRegistry) -> Result<ParsedData, E>,
+) -> Result<ParsedData, E> {
+    match lock().as_mut() {
+        Some(cache) => cache.parse(source, documentation_language, type_registry, parse),
+        None => parse(type_registry),
+    }
+}
+
+fn lock() -> MutexGuard<'static, Option<SchemaCache>> {
+    SCHEMA_CACHE.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Parsed schemas by their files and the language of the kept documentations
+#[derive(Default)]
+struct SchemaCache {
+    entries: HashMap<(Vec<PathBuf>, Option<String>), CachedSchemas>,
+    hits: u64,
+    misses: u64,
+}
+
+struct CachedSchemas {
+    /// Modification times of the files, when they were parsed
+    modified: Vec<SystemTime>,
+    data: ParsedData,
+    registry: TypeRegistry,
+}
+
+impl SchemaCache {
+    fn parse<E>(
+        &mut self,
+        source: &[PathBuf],
+        documentation_language: &Option<String>,
+        type_registry: &mut TypeRegistry,
+        parse: impl FnOnce(&mut TypeRegistry) -> Result<ParsedData, E>,
+    ) -> Result<ParsedData, E> {
+        // Files without a modification time are not cached, as changes can't be detected
+        let Some(modified) = source
+            .iter()
+            .map(|p| std::fs::metadata(p).and_then(|m| m.modified()).ok())
+            .collect::<Option<Vec<_>>>()
+        else {
+            return parse(type_registry);
+        };
+
+        let key = (source.to_vec(), documentation_language.clone());
+
+        if let Some(cached) = self.entries.get(&key).filter(|c| c.modified == modified) {
+            self.hits += 1;
+            type_registry.adopt_parsed_types(&cached.registry);
+
+            return Ok(cached.data.clone());
+        }
+
+        self.misses += 1;
+
+        let data = parse(type_registry)?;
+
+        self.entries.insert(
+            key,
+            CachedSchemas {
+                modified,
+                data: data.clone(),
+                registry: type_registry.clone(),
+            },
+        );
+
+        Ok(data)
+    }
+
+    fn stats(&self) -> SchemaCacheStats {
+        SchemaCacheStats {
+            entries: self.entries.len(),
+            hits: self.hits,
+            misses: self.misses,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs::File, time::Duration};
+
+    use super::*;
+
+    #[test]
+    fn modified_schemas_are_parsed_again() {
+        let path = std::env::temp_dir().join(format!("genphi-cache-{}.xsd", std::process::id()));
+        let file = File::create(&path).unwrap();
+        let source = vec![path.clone()];
+
+        let mut cache = SchemaCache::default();
+        let mut parses = 0;
+        let mut parse = |source: &[PathBuf], cache: &mut SchemaCache| {
+            cache
+                .parse(source, &None, &mut TypeRegistry::new(), |_| {
+                    parses += 1;
+
+                    Ok::<_, ()>(ParsedData {
+                        nodes: Vec::new(),
+                        documentations: Vec::new(),
+                        notations: Vec::new(),
+                    })
+                })
+                .unwrap();
+        };
+
+        parse(&source, &mut cache);
+        parse(&source, &mut cache);
+
+        file.set_modified(SystemTime::now() + Duration::from_secs(60))
+            .unwrap();
+        parse(&source, &mut cache);
+
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(parses, 2);
+        assert_eq!(
+            cache.stats(),
+            SchemaCacheStats {
+                entries: 1,
+                hits: 1,
+                misses: 2,
+            }
+        );
+    }
+}
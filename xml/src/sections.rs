@@ -0,0 +1,339 @@
+use std::ops::Range;
+
+/// Part of a generated unit, which can be replaced by the same part of another generation
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Section {
+    /// Every `{$REGION 'Name'}` up to its `{$ENDREGION}`, e.g. `Enumerations`
+    Region(String),
+    /// Declaration and implementation of a class or record, e.g. `TFoo`
+    Class(String),
+}
+
+impl std::fmt::Display for Section {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Region(name) => write!(f, "Region '{name}'"),
+            Self::Class(name) => write!(f, "Class {name}"),
+        }
+    }
+}
+
+/// Replaces the sections of the `existing` unit by the sections of the `generated` unit and
+/// keeps the rest of the existing unit byte-identical, e.g. manual changes or the header.
+///
+/// Regions occurring multiple times, e.g. in the interface and the implementation, are replaced
+/// in the order they occur. Sections inside of another replaced section are replaced with it.
+/// Fails, if a section is missing or occurs a different number of times in one of the units.
+pub fn update_sections(
+    existing: &str,
+    generated: &str,
+    sections: &[Section],
+) -> Result<String, String> {
+    let mut replacements = vec![];
+
+    for section in sections {
+        let existing_spans = section_spans(existing, section);
+        let generated_spans = section_spans(generated, section);
+
+        if existing_spans.is_empty() {
+            return Err(format!("{section} was not found in the unit"));
+        }
+
+        if existing_spans.len() != generated_spans.len() {
+            return Err(format!(
+                "{section} occurs {} times in the unit, but {} times in the generated unit",
+                existing_spans.len(),
+                generated_spans.len()
+            ));
+        }
+
+        replacements.extend(
+            existing_spans
+                .into_iter()
+                .zip(generated_spans.into_iter().map(|s| &generated[s])),
+        );
+    }
+
+    replacements.sort_by_key(|(span, _)| (span.start, usize::MAX - span.end));
+
+    let mut content = String::with_capacity(generated.len());
+    let mut position = 0;
+
+    for (span, replacement) in replacements {
+        // Contained in a replaced section
+        if span.start < position {
+            continue;
+        }
+
+        content.push_str(&existing[position..span.start]);
+        content.push_str(replacement);
+        position = span.end;
+    }
+
+    content.push_str(&existing[position..]);
+
+    Ok(content)
+}
+
+/// Lines of the content with their byte range, including the line break
+fn lines(content: &str) -> Vec<(Range<usize>, &str)> {
+    let mut position = 0;
+
+    content
+        .split_inclusive('\n')
+        .map(|line| {
+            let range = position..position + line.len();
+            position = range.end;
+
+            (range, line.trim_end())
+        })
+        .collect()
+}
+
+fn section_spans(content: &str, section: &Section) -> Vec<Range<usize>> {
+    match section {
+        Section::Region(name) => region_spans(content, name),
+        Section::Class(name) => class_spans(content, name),
+    }
+}
+
+/// Spans of the regions named `name` from the line of `{$REGION}` up to the line of the
+/// matching `{$ENDREGION}`
+fn region_spans(content: &str, name: &str) -> Vec<Range<usize>> {
+    let mut open_regions = vec![];
+    let mut spans = vec![];
+
+    for (range, line) in lines(content) {
+        let line = line.trim_start();
+
+        if let Some(region) = line.strip_prefix("{$REGION") {
+            // Some regions lack the closing quote, e.g. `{$REGION 'Declarations}`
+            let region = region.trim_end_matches('}').trim().trim_matches('\'');
+            open_regions.push((region == name, range.start));
+        } else if line.starts_with("{$ENDREGION") {
+            if let Some((true, start)) = open_regions.pop() {
+                spans.push(start..range.end);
+            }
+        }
+    }
+
+    spans.sort_by_key(|s| s.start);
+    spans
+}
+
+/// Spans of the declaration of the class or record `name` with its preceding comments and of
+/// its implementation, which starts with the comment `{ TName }`
+fn class_spans(content: &str, name: &str) -> Vec<Range<usize>> {
+    let lines = lines(content);
+    let mut spans = vec![];
+    let mut index = 0;
+
+    while index < lines.len() {
+        let (range, line) = &lines[index];
+        let indentation = &line[..line.len() - line.trim_start().len()];
+        let declaration = line.trim_start().strip_prefix(name).and_then(|d| {
+            d.trim_start()
+                .strip_prefix('=')
+                .map(|d| d.trim_start().to_ascii_lowercase())
+        });
+
+        match declaration {
+            // Forward declarations are kept
+            Some(d) if (d.starts_with("class") || d.starts_with("record")) && !d.ends_with(';') => {
+                let comments = lines[..index]
+                    .iter()
+                    .rev()
+                    .take_while(|(_, l)| l.trim_start().starts_with("//"))
+                    .count();
+                let end = lines[index..]
+                    .iter()
+                    .position(|(_, l)| *l == format!("{indentation}end;"))
+                    .map_or(lines.len() - 1, |p| index + p);
+
+                spans.push(lines[index - comments].0.start..lines[end].0.end);
+                index = end + 1;
+            }
+            _ if *line == format!("{{ {name} }}") => {
+                let end = lines[index + 1..]
+                    .iter()
+                    .position(|(_, l)| {
+                        (l.starts_with("{ ") && l.ends_with(" }"))
+                            || l.starts_with("{$REGION")
+                            || l.starts_with("{$ENDREGION")
+                            || *l == "end."
+                    })
+                    .map_or(lines.len(), |p| index + 1 + p);
+
+                spans.push(range.start..lines[end - 1].0.end);
+                index = end;
+            }
+            _ => index += 1,
+        }
+    }
+
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    const EXISTING: &str = "// Generated on 01.01.2024
+unit uOrders;
+
+interface
+
+type
+  {$REGION 'Enumerations'}
+  TColor = (Red, Green);
+  {$ENDREGION}
+
+  {$REGION 'Declarations}
+  TOrder = class;
+
+  // XML Qualified Name: Order
+  TOrder = class(TObject)
+  public
+    Id: String;
+  end;
+
+  TItem = class(TObject)
+  public
+    Name: String;
+  end;
+  {$ENDREGION}
+
+implementation
+
+{$REGION 'Declarations}
+{ TOrder }
+constructor TOrder.Create;
+begin
+  // Manual change
+end;
+
+{ TItem }
+constructor TItem.Create;
+begin
+end;
+{$ENDREGION}
+
+end.
+";
+
+    const GENERATED: &str = "// Generated on 02.02.2024
+unit uOrders;
+
+interface
+
+type
+  {$REGION 'Enumerations'}
+  TColor = (Red, Green, Blue);
+  {$ENDREGION}
+
+  {$REGION 'Declarations}
+  TOrder = class;
+
+  // XML Qualified Name: Order
+  TOrder = class(TObject)
+  public
+    Id: String;
+    Total: Double;
+  end;
+
+  TItem = class(TObject)
+  public
+    Name: String;
+    Count: Integer;
+  end;
+  {$ENDREGION}
+
+implementation
+
+{$REGION 'Declarations}
+{ TOrder }
+constructor TOrder.Create;
+begin
+  Total := 0;
+end;
+
+{ TItem }
+constructor TItem.Create;
+begin
+  Count := 0;
+end;
+{$ENDREGION}
+
+end.
+";
+
+    #[test]
+    fn only_the_sections_are_replaced() {
+        let updated = update_sections(
+            EXISTING,
+            GENERATED,
+            &[
+                Section::Region(String::from("Enumerations")),
+                Section::Class(String::from("TItem")),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(
+            updated,
+            EXISTING
+                .replace("(Red, Green)", "(Red, Green, Blue)")
+                .replace(
+                    "    Name: String;\n",
+                    "    Name: String;\n    Count: Integer;\n"
+                )
+                .replace(
+                    "TItem.Create;\nbegin\n",
+                    "TItem.Create;\nbegin\n  Count := 0;\n"
+                )
+        );
+    }
+
+    #[test]
+    fn regions_of_interface_and_implementation_are_replaced() {
+        let updated = update_sections(
+            EXISTING,
+            GENERATED,
+            &[
+                Section::Class(String::from("TOrder")),
+                Section::Region(String::from("Declarations")),
+            ],
+        )
+        .unwrap();
+
+        let start = |unit: &str| unit.find("  {$REGION 'Declarations}").unwrap();
+        assert_eq!(
+            updated,
+            EXISTING[..start(EXISTING)].to_owned() + &GENERATED[start(GENERATED)..]
+        );
+    }
+
+    #[test]
+    fn missing_sections_are_rejected() {
+        assert_eq!(
+            update_sections(
+                EXISTING,
+                GENERATED,
+                &[Section::Class(String::from("TCustomer"))]
+            ),
+            Err(String::from("Class TCustomer was not found in the unit"))
+        );
+        assert_eq!(
+            update_sections(
+                EXISTING,
+                &GENERATED.replace("{ TItem }\n", ""),
+                &[Section::Class(String::from("TItem"))]
+            ),
+            Err(String::from(
+                "Class TItem occurs 2 times in the unit, but 1 times in the generated unit"
+            ))
+        );
+    }
+}
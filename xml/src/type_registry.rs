@@ -1,13 +1,41 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use crate::parser::types::CustomTypeDefinition;
+use crate::{
+    generator::{code_generator_trait::AnonymousTypeNaming, delphi::helper::Helper},
+    parser::types::{ComplexType, CustomTypeDefinition, Node, NodeType, SimpleType, UnionVariant},
+};
+
+/// Hand-written Delphi type, which is referenced instead of generating a schema type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExternalType {
+    /// Name of the Delphi type, e.g. `TAddress`
+    pub delphi_type: String,
+    /// Unit declaring the Delphi type, which is added to the uses clause
+    pub unit: String,
+}
+
+/// Prefix of the names generated for anonymous types without an element
+const GENERATED_TYPE_NAME_PREFIX: &str = "__Custom_Type_";
+
+/// How a type of another schema is changed by xs:redefine or xs:override
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Redefinition {
+    /// xs:redefine, the new definition restricts or extends the original one
+    Redefine,
+    /// xs:override, the new definition replaces the original one
+    Override,
+}
 
 /// Stores all types that have been parsed
 ///
 /// This is used to resolve types that are referenced by other types
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct TypeRegistry {
     pub types: HashMap<String, CustomTypeDefinition>,
+    /// Redefined types, which are merged into `types` once all schemas are parsed
+    redefinitions: Vec<(Redefinition, CustomTypeDefinition)>,
+    /// Types implemented by hand, by the name or qualified name of the schema type
+    external_types: HashMap<String, ExternalType>,
     gen_type_count: i64,
 }
 
@@ -15,6 +43,8 @@ impl TypeRegistry {
     pub fn new() -> Self {
         Self {
             types: HashMap::new(),
+            redefinitions: Vec::new(),
+            external_types: HashMap::new(),
             gen_type_count: 0,
         }
     }
@@ -26,14 +56,370 @@ impl TypeRegistry {
         self.types.entry(name).or_insert(custom_type);
     }
 
+    /// Registers a hand-written Delphi type for the complex type `name`.
+    ///
+    /// The complex type is not generated, its usages reference `delphi_type` of `unit` instead.
+    /// `name` is either the name of the type in the schema or its qualified name. The Delphi
+    /// type has to provide the same members as a generated class, e.g. `FromXml` and `AppendToXml`.
+    /// Types can be registered before or after the schemas are parsed.
+    pub fn register_external_type(&mut self, name: &str, delphi_type: &str, unit: &str) {
+        self.external_types.insert(
+            name.to_owned(),
+            ExternalType {
+                delphi_type: delphi_type.to_owned(),
+                unit: unit.to_owned(),
+            },
+        );
+    }
+
+    /// Takes over the types parsed into `other`, e.g. by an earlier parse of the same schemas.
+    /// Hand-written types registered in this registry are kept.
+    pub(crate) fn adopt_parsed_types(&mut self, other: &TypeRegistry) {
+        self.types = other.types.clone();
+        self.redefinitions = other.redefinitions.clone();
+        self.gen_type_count = other.gen_type_count;
+    }
+
+    /// Hand-written Delphi type registered for the complex type
+    pub fn external_type(&self, complex_type: &ComplexType) -> Option<&ExternalType> {
+        self.external_types
+            .get(&complex_type.qualified_name)
+            .or_else(|| self.external_types.get(&complex_type.name))
+    }
+
+    /// Registers a type defined inside of xs:redefine or xs:override.
+    ///
+    /// The original type may be parsed later, so the redefinition is only
+    /// applied by `apply_redefinitions`.
+    pub fn register_redefinition(
+        &mut self,
+        redefinition: Redefinition,
+        custom_type: CustomTypeDefinition,
+    ) {
+        self.redefinitions.push((redefinition, custom_type));
+    }
+
+    /// Merges all registered redefinitions into the types of the registry.
+    pub fn apply_redefinitions(&mut self) {
+        for (redefinition, custom_type) in std::mem::take(&mut self.redefinitions) {
+            let name = custom_type.get_qualified_name();
+            let original = self.types.remove(&name);
+
+            let custom_type = match (redefinition, original, custom_type) {
+                (
+                    Redefinition::Redefine,
+                    Some(CustomTypeDefinition::Simple(original)),
+                    CustomTypeDefinition::Simple(redefined),
+                ) => CustomTypeDefinition::Simple(Self::redefine_simple_type(original, redefined)),
+                (
+                    Redefinition::Redefine,
+                    Some(CustomTypeDefinition::Complex(original)),
+                    CustomTypeDefinition::Complex(redefined),
+                ) => {
+                    CustomTypeDefinition::Complex(Self::redefine_complex_type(original, redefined))
+                }
+                (_, _, custom_type) => custom_type,
+            };
+
+            self.types.insert(name, custom_type);
+        }
+    }
+
+    /// A simple type can only be redefined by a restriction of itself, so the
+    /// facets of the redefinition replace the facets of the original type.
+    fn redefine_simple_type(original: SimpleType, redefined: SimpleType) -> SimpleType {
+        let restricts_original = matches!(
+            &redefined.base_type,
+            Some(NodeType::Custom(base)) if *base == redefined.qualified_name
+        );
+
+        if !restricts_original {
+            return redefined;
+        }
+
+        SimpleType {
+            documentations: if redefined.documentations.is_empty() {
+                original.documentations
+            } else {
+                redefined.documentations
+            },
+            is_documented: redefined.is_documented || original.is_documented,
+            appinfo: if redefined.appinfo.is_empty() {
+                original.appinfo
+            } else {
+                redefined.appinfo
+            },
+            enumeration: redefined.enumeration.or(original.enumeration),
+            pattern: redefined.pattern.or(original.pattern),
+            total_digits: redefined.total_digits.or(original.total_digits),
+            fraction_digits: redefined.fraction_digits.or(original.fraction_digits),
+            is_flags: redefined.is_flags || original.is_flags,
+            ..original
+        }
+    }
+
+    /// A complex type, which extends itself, keeps the content of the original
+    /// type and appends the new elements and attributes. Otherwise it restricts
+    /// the original type and its content replaces the original content.
+    fn redefine_complex_type(mut original: ComplexType, redefined: ComplexType) -> ComplexType {
+        if redefined.base_type.as_ref() != Some(&redefined.qualified_name) {
+            return redefined;
+        }
+
+        original.children.extend(redefined.children);
+        original
+            .custom_attributes
+            .extend(redefined.custom_attributes);
+        original.wildcard = original.wildcard.or(redefined.wildcard);
+        original.assertions.extend(redefined.assertions);
+
+        if !redefined.documentations.is_empty() {
+            original.documentations = redefined.documentations;
+        }
+
+        original.is_documented |= redefined.is_documented;
+
+        if !redefined.appinfo.is_empty() {
+            original.appinfo = redefined.appinfo;
+        }
+
+        original
+    }
+
+    /// Returns the name for an anonymous type declared by the element or attribute `name`
+    /// inside of the type `parent`.
+    ///
+    /// Elements of the same name in different branches of a choice, or an element and an
+    /// attribute of the same name, declare different types. A name already taken inside of
+    /// `parent` gets a numeric suffix, otherwise the later type would be dropped and its
+    /// element would reference the type of the first one. The types of a parent, which is
+    /// already registered, e.g. by another schema including the same file, keep their name.
+    pub fn anonymous_type_name(&self, parent: &str, name: &str) -> String {
+        if self.types.contains_key(parent) {
+            return name.to_owned();
+        }
+
+        let mut unique_name = name.to_owned();
+        let mut counter = 2;
+        while self.types.contains_key(&format!("{parent}.{unique_name}")) {
+            unique_name = format!("{name}{counter}");
+            counter += 1;
+        }
+
+        unique_name
+    }
+
+    /// Returns the qualified names of the anonymous types, which are not referenced by an
+    /// element, attribute or other type, sorted by name.
+    ///
+    /// Every anonymous type is declared for the element or attribute, which uses it,
+    /// so an unreferenced one points to a declaration, which was not parsed correctly.
+    /// Has to be called before the anonymous types are named.
+    pub fn unreferenced_anonymous_types(&self, nodes: &[Node]) -> Vec<String> {
+        let mut referenced = HashSet::new();
+        Self::collect_node_references(nodes, &mut referenced);
+
+        for custom_type in self.types.values() {
+            match custom_type {
+                CustomTypeDefinition::Simple(t) => {
+                    Self::collect_simple_references(t, &mut referenced)
+                }
+                CustomTypeDefinition::Complex(t) => {
+                    if let Some(base_type) = &t.base_type {
+                        referenced.insert(base_type.as_str());
+                    }
+
+                    Self::collect_node_references(&t.children, &mut referenced);
+
+                    for attribute in &t.custom_attributes {
+                        if let NodeType::Custom(name) = &attribute.base_type {
+                            referenced.insert(name);
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut unreferenced = self
+            .types
+            .iter()
+            .filter(|(key, custom_type)| {
+                let is_anonymous = custom_type
+                    .get_name()
+                    .starts_with(GENERATED_TYPE_NAME_PREFIX)
+                    || self.parent_of(custom_type).is_some();
+
+                is_anonymous && !referenced.contains(key.as_str())
+            })
+            .map(|(key, _)| key.clone())
+            .collect::<Vec<_>>();
+        unreferenced.sort();
+
+        unreferenced
+    }
+
+    fn collect_node_references<'a>(nodes: &'a [Node], referenced: &mut HashSet<&'a str>) {
+        for node in nodes {
+            match node {
+                Node::Single(n) => {
+                    if let NodeType::Custom(name) = &n.node_type {
+                        referenced.insert(name);
+                    }
+                }
+                Node::Group(g) => Self::collect_node_references(&g.nodes, referenced),
+            }
+        }
+    }
+
+    fn collect_simple_references<'a>(
+        simple_type: &'a SimpleType,
+        referenced: &mut HashSet<&'a str>,
+    ) {
+        for node_type in [&simple_type.base_type, &simple_type.list_type]
+            .into_iter()
+            .flatten()
+        {
+            if let NodeType::Custom(name) = node_type {
+                referenced.insert(name);
+            }
+        }
+
+        for variant in simple_type.variants.iter().flatten() {
+            match variant {
+                UnionVariant::Named(name) => {
+                    referenced.insert(name);
+                }
+                UnionVariant::Simple(t) => {
+                    referenced.insert(&t.qualified_name);
+                    Self::collect_simple_references(t, referenced);
+                }
+                UnionVariant::Standard(_) => (),
+            }
+        }
+    }
+
     /// Generates a unique type name for an anonymous type
     pub fn generate_type_name(&mut self) -> String {
-        let name = format!("__Custom_Type_{}__", self.gen_type_count);
+        let name = format!("{GENERATED_TYPE_NAME_PREFIX}{}__", self.gen_type_count);
 
         self.gen_type_count += 1;
 
         name
     }
+
+    /// Names the anonymous types, which are declared inside of an element of another type.
+    ///
+    /// Their names are derived from the element, with `AnonymousTypeNaming::Path` the name
+    /// of the parent type is prepended. Names longer than `max_length` are cut and get a hash
+    /// of the schema path appended. Names, which are already taken, get a numeric suffix.
+    /// The types are named in the order of their schema paths, so the names are stable.
+    pub fn name_anonymous_types(&mut self, naming: AnonymousTypeNaming, max_length: Option<usize>) {
+        let mut nested = self
+            .types
+            .iter()
+            .filter_map(|(key, custom_type)| {
+                self.parent_of(custom_type)
+                    .map(|parent| (key.clone(), parent.to_owned()))
+            })
+            .collect::<Vec<_>>();
+        // A parent path is a prefix of the paths of its children, so parents are named first.
+        nested.sort();
+
+        let nested_keys = nested.iter().map(|(k, _)| k).collect::<HashSet<_>>();
+        let mut used_names = self
+            .types
+            .iter()
+            .filter(|(key, _)| !nested_keys.contains(key))
+            .map(|(_, custom_type)| custom_type.get_name().to_lowercase())
+            .collect::<HashSet<_>>();
+
+        let mut names = HashMap::<String, String>::new();
+
+        for (key, parent) in &nested {
+            let Some(custom_type) = self.types.get(key) else {
+                continue;
+            };
+
+            let mut name = match naming {
+                AnonymousTypeNaming::Element => custom_type.get_name(),
+                AnonymousTypeNaming::Path => {
+                    let parent_name = names.get(parent).cloned().unwrap_or_else(|| {
+                        self.types
+                            .get(parent)
+                            .map(CustomTypeDefinition::get_name)
+                            .unwrap_or_default()
+                    });
+
+                    format!(
+                        "{parent_name}{}",
+                        Helper::first_char_uppercase(&custom_type.get_name())
+                    )
+                }
+            };
+
+            if let Some(max_length) = max_length {
+                name = Self::shorten_name(&name, key, max_length);
+            }
+
+            let mut unique_name = name.clone();
+            let mut counter = 2;
+            while !used_names.insert(unique_name.to_lowercase()) {
+                unique_name = format!("{name}{counter}");
+                counter += 1;
+            }
+
+            names.insert(key.clone(), unique_name);
+        }
+
+        for (key, name) in names {
+            match self.types.get_mut(&key) {
+                Some(CustomTypeDefinition::Simple(t)) => t.name = name,
+                Some(CustomTypeDefinition::Complex(t)) => t.name = name,
+                None => (),
+            }
+        }
+    }
+
+    /// Returns the qualified name of the type, in which an anonymous type is declared.
+    /// Types renamed by an override and types without an element keep their name.
+    fn parent_of<'a>(&self, custom_type: &'a CustomTypeDefinition) -> Option<&'a str> {
+        let (name, qualified_name) = match custom_type {
+            CustomTypeDefinition::Simple(t) => (&t.name, &t.qualified_name),
+            CustomTypeDefinition::Complex(t) if t.overrides.rename.is_none() => {
+                (&t.name, &t.qualified_name)
+            }
+            CustomTypeDefinition::Complex(_) => return None,
+        };
+
+        if name.starts_with(GENERATED_TYPE_NAME_PREFIX) {
+            return None;
+        }
+
+        qualified_name
+            .strip_suffix(name.as_str())
+            .and_then(|p| p.strip_suffix('.'))
+            .filter(|p| self.types.contains_key(*p))
+    }
+
+    /// Cuts the name to `max_length` characters, the last 9 of them are replaced by
+    /// `_` and a hash of the qualified name.
+    fn shorten_name(name: &str, qualified_name: &str, max_length: usize) -> String {
+        if name.chars().count() <= max_length {
+            return name.to_owned();
+        }
+
+        // FNV-1a, which unlike the hasher of std is stable across releases
+        let hash = qualified_name.bytes().fold(0x811c_9dc5_u32, |hash, b| {
+            (hash ^ u32::from(b)).wrapping_mul(0x0100_0193)
+        });
+
+        let kept = name
+            .chars()
+            .take(max_length.saturating_sub(9))
+            .collect::<String>();
+
+        format!("{kept}_{hash:08x}")
+    }
 }
 
 impl Default for TypeRegistry {
@@ -41,3 +427,178 @@ impl Default for TypeRegistry {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::types::{
+        BaseAttributes, EnumerationVariant, GenerationOverrides, Node, NodeBaseType,
+        OrderIndicator, SingleNode,
+    };
+
+    use super::*;
+
+    fn simple_type(base_type: NodeType, variants: &[&str]) -> SimpleType {
+        SimpleType {
+            name: String::from("Code"),
+            qualified_name: String::from("Code"),
+            documentations: vec![],
+            is_documented: false,
+            appinfo: vec![],
+            base_type: Some(base_type),
+            enumeration: Some(
+                variants
+                    .iter()
+                    .map(|v| EnumerationVariant {
+                        name: (*v).to_owned(),
+                        documentations: vec![],
+                        is_documented: false,
+                    })
+                    .collect(),
+            ),
+            list_type: None,
+            pattern: None,
+            total_digits: None,
+            fraction_digits: None,
+            variants: None,
+            is_flags: false,
+        }
+    }
+
+    fn complex_type(base_type: Option<&str>, element: &str) -> ComplexType {
+        ComplexType {
+            name: String::from("Person"),
+            qualified_name: String::from("Person"),
+            documentations: vec![],
+            is_documented: false,
+            overrides: GenerationOverrides::default(),
+            appinfo: vec![],
+            base_type: base_type.map(str::to_owned),
+            is_abstract: false,
+            children: vec![Node::Single(SingleNode::new(
+                NodeType::Standard(NodeBaseType::String),
+                element.to_owned(),
+                BaseAttributes::default(),
+                None,
+            ))],
+            custom_attributes: vec![],
+            order: OrderIndicator::Sequence(BaseAttributes::default()),
+            wildcard: None,
+            assertions: vec![],
+        }
+    }
+
+    fn child_names(registry: &TypeRegistry) -> Vec<String> {
+        match registry.types.get("Person") {
+            Some(CustomTypeDefinition::Complex(c)) => c
+                .children
+                .iter()
+                .filter_map(|n| match n {
+                    Node::Single(s) => Some(s.name.clone()),
+                    Node::Group(_) => None,
+                })
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    #[test]
+    fn redefine_restricts_and_extends_the_original_types() {
+        let mut registry = TypeRegistry::new();
+
+        registry.register_redefinition(
+            Redefinition::Redefine,
+            simple_type(NodeType::Custom(String::from("Code")), &["a"]).into(),
+        );
+        registry.register_redefinition(
+            Redefinition::Redefine,
+            complex_type(Some("Person"), "age").into(),
+        );
+        registry.register_type(
+            simple_type(NodeType::Standard(NodeBaseType::String), &["a", "b"]).into(),
+        );
+        registry.register_type(complex_type(None, "name").into());
+
+        registry.apply_redefinitions();
+
+        let Some(CustomTypeDefinition::Simple(code)) = registry.types.get("Code") else {
+            panic!("Code should be a simple type");
+        };
+        assert!(matches!(
+            code.base_type,
+            Some(NodeType::Standard(NodeBaseType::String))
+        ));
+        assert_eq!(code.enumeration.as_ref().map(Vec::len), Some(1));
+        assert_eq!(child_names(&registry), vec!["name", "age"]);
+    }
+
+    #[test]
+    fn override_replaces_the_original_type() {
+        let mut registry = TypeRegistry::new();
+
+        registry.register_type(complex_type(None, "name").into());
+        registry.register_redefinition(Redefinition::Override, complex_type(None, "city").into());
+
+        registry.apply_redefinitions();
+
+        assert_eq!(child_names(&registry), vec!["city"]);
+    }
+
+    fn anonymous_types_registry() -> TypeRegistry {
+        let mut registry = TypeRegistry::new();
+
+        for qualified_name in [
+            "Person",
+            "Company",
+            "Person.address",
+            "Person.address.geo",
+            "Company.address",
+        ] {
+            let mut c_type = complex_type(None, "name");
+            c_type.name = qualified_name.rsplit('.').next().unwrap().to_owned();
+            c_type.qualified_name = qualified_name.to_owned();
+
+            registry.register_type(c_type.into());
+        }
+
+        registry
+    }
+
+    fn type_name(registry: &TypeRegistry, qualified_name: &str) -> String {
+        registry.types[qualified_name].get_name()
+    }
+
+    #[test]
+    fn anonymous_types_with_the_same_element_name_are_deduplicated() {
+        let mut registry = anonymous_types_registry();
+
+        registry.name_anonymous_types(AnonymousTypeNaming::Element, None);
+
+        assert_eq!(type_name(&registry, "Company.address"), "address");
+        assert_eq!(type_name(&registry, "Person.address"), "address2");
+        assert_eq!(type_name(&registry, "Person.address.geo"), "geo");
+        assert_eq!(type_name(&registry, "Person"), "Person");
+    }
+
+    #[test]
+    fn anonymous_types_are_named_after_their_path() {
+        let mut registry = anonymous_types_registry();
+
+        registry.name_anonymous_types(AnonymousTypeNaming::Path, Some(16));
+
+        assert_eq!(type_name(&registry, "Company.address"), "CompanyAddress");
+        assert_eq!(type_name(&registry, "Person.address"), "PersonAddress");
+        assert_eq!(
+            type_name(&registry, "Person.address.geo"),
+            "PersonAddressGeo"
+        );
+
+        let mut registry = anonymous_types_registry();
+
+        registry.name_anonymous_types(AnonymousTypeNaming::Path, Some(14));
+
+        assert_eq!(type_name(&registry, "Company.address"), "CompanyAddress");
+        let shortened = type_name(&registry, "Person.address.geo");
+        assert_eq!(shortened.len(), 14);
+        assert!(shortened.starts_with("Perso_"));
+    }
+}